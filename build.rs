@@ -0,0 +1,142 @@
+// build.rs - 内置启动快照
+//
+// 默认 Context 从内置快照启动（扩展 JS 和 polyfill 已执行过的 V8 堆），快照通过 include_bytes! 编译进扩展，
+// 运行时不再构建、也不需要 leak 任何数据。
+//
+// 快照中的 op 引用必须与同一份源码编译出的 op 完全一致，而构建脚本无法链接本 crate 的 op 实现，
+// 所以快照分两步生成（发布的 wheel 由 .github/workflows/build-wheels.yml 按这两步构建）：
+//   1. 正常构建并安装扩展，运行 `python scripts/build_builtin_snapshot.py`，
+//      在 snapshot/ 目录中写入 builtin-<源码指纹>.bin 和 builtin-logging-<源码指纹>.bin
+//   2. 重新构建：这里按源码指纹找到对应的文件，复制到 OUT_DIR 后由 snapshot.rs 嵌入
+//
+// 源码指纹覆盖 src/ 下的全部文件和 Cargo.toml（不包括不受版本控制的 Cargo.lock，两步构建在同一份
+// checkout 中进行），源码改动后旧的快照文件自动失效。找不到对应文件时嵌入空数据，Context 回退到
+// 直接执行 polyfill（如从 sdist 构建）；设置 NEVER_JSCORE_REQUIRE_SNAPSHOT=1 时改为构建失败，
+// CI 的第二步用它确认快照确实被嵌入。快照目录可以用 NEVER_JSCORE_SNAPSHOT_DIR 覆盖。
+//
+// version_info() 报告的 deno_core 版本取自 Cargo.lock 中实际解析到的版本（找不到时使用 Cargo.toml 中的版本要求），
+// 以 NEVER_JSCORE_DENO_CORE_VERSION 传给 version.rs。
+//
+// compile(preset=[...]) 使用的上游库（src/dddd_js/vendor/，由 scripts/vendor_presets.py 下载）同样复制到
+// OUT_DIR 后由 presets.rs 嵌入；文件缺失时嵌入空数据，使用该预设时抛出 RuntimeError 而不是构建失败。
+
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+
+/// (快照文件名前缀, OUT_DIR 中嵌入的文件名)
+const SNAPSHOTS: [(&str, &str); 2] = [
+    ("builtin", "builtin_snapshot.bin"),
+    ("builtin-logging", "builtin_snapshot_logging.bin"),
+];
+
+/// presets.rs 嵌入的上游库（相对于 src/dddd_js/vendor/ 和 OUT_DIR/vendor/）
+const VENDORED: [&str; 2] = ["crypto-js/crypto-js.js", "jsencrypt/jsencrypt.js"];
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let snapshot_dir = env::var_os("NEVER_JSCORE_SNAPSHOT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manifest_dir.join("snapshot"));
+
+    let required = env::var_os("NEVER_JSCORE_REQUIRE_SNAPSHOT").is_some_and(|v| v != "0");
+
+    let fingerprint = source_fingerprint(&manifest_dir);
+    println!("cargo:rustc-env=NEVER_JSCORE_SOURCE_HASH={}", fingerprint);
+
+    let (deno_core, lockfile) = deno_core_version(&manifest_dir);
+    println!("cargo:rustc-env=NEVER_JSCORE_DENO_CORE_VERSION={}", deno_core);
+    if let Some(lockfile) = lockfile {
+        println!("cargo:rerun-if-changed={}", lockfile.display());
+    }
+
+    for (prefix, embedded) in SNAPSHOTS {
+        let source = snapshot_dir.join(format!("{}-{}.bin", prefix, fingerprint));
+        let data = fs::read(&source).unwrap_or_default();
+        if data.is_empty() && required {
+            panic!("no builtin snapshot at {} (NEVER_JSCORE_REQUIRE_SNAPSHOT is set)", source.display());
+        }
+        fs::write(out_dir.join(embedded), data).unwrap();
+    }
+
+    for file in VENDORED {
+        let target = out_dir.join("vendor").join(file);
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        let data = fs::read(manifest_dir.join("src/dddd_js/vendor").join(file)).unwrap_or_default();
+        fs::write(target, data).unwrap();
+    }
+
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed={}", snapshot_dir.display());
+    println!("cargo:rerun-if-env-changed=NEVER_JSCORE_SNAPSHOT_DIR");
+    println!("cargo:rerun-if-env-changed=NEVER_JSCORE_REQUIRE_SNAPSHOT");
+}
+
+/// 解析到的 deno_core 版本和读取的 Cargo.lock
+///
+/// Cargo.lock 在工作区根目录，本 crate 作为工作区成员构建时位于上级目录。
+fn deno_core_version(manifest_dir: &Path) -> (String, Option<PathBuf>) {
+    for dir in manifest_dir.ancestors() {
+        let lockfile = dir.join("Cargo.lock");
+        let Ok(content) = fs::read_to_string(&lockfile) else {
+            continue;
+        };
+        if let Some(version) = locked_version(&content, "deno_core") {
+            return (version, Some(lockfile));
+        }
+    }
+    let manifest = fs::read_to_string(manifest_dir.join("Cargo.toml")).unwrap_or_default();
+    let required = manifest
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("deno_core = \""))
+        .and_then(|rest| rest.split('"').next())
+        .map(|version| version.trim_start_matches(['=', '^', '~']).to_string());
+    (required.unwrap_or_else(|| "unknown".to_string()), None)
+}
+
+/// Cargo.lock 中名为 `name` 的包的版本
+fn locked_version(lockfile: &str, name: &str) -> Option<String> {
+    let name_line = format!("name = \"{}\"", name);
+    let mut lines = lockfile.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if line == name_line {
+            let version = lines.next()?.strip_prefix("version = \"")?;
+            return Some(version.trim_end_matches('"').to_string());
+        }
+    }
+    None
+}
+
+/// src/ 下全部文件（按路径排序）和 Cargo.toml 的内容指纹
+fn source_fingerprint(manifest_dir: &Path) -> String {
+    let mut files = Vec::new();
+    collect_files(&manifest_dir.join("src"), &mut files);
+    files.sort();
+    files.push(manifest_dir.join("Cargo.toml"));
+
+    let mut hasher = DefaultHasher::new();
+    for path in files {
+        let relative = path.strip_prefix(manifest_dir).unwrap_or(&path);
+        hasher.write(relative.to_string_lossy().replace('\\', "/").as_bytes());
+        hasher.write(&fs::read(&path).unwrap_or_default());
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
@@ -5,7 +5,83 @@ High-performance Python JavaScript execution engine based on Deno Core (V8)
 with full Promise/async support.
 """
 
-from .never_jscore import Context
+from .never_jscore import (
+    AbortToken,
+    CompileTask,
+    Context,
+    ContextPool,
+    ContextPoisoned,
+    ContextRegistry,
+    Handle,
+    JsUndefined,
+    MapStream,
+    PausedFrame,
+    PoolFull,
+    PreparedCall,
+    QuotaExceeded,
+    Realm,
+    SchemaMismatch,
+    ShadowRealm,
+    SharedBuffer,
+    SnapshotPool,
+    ThreadedContext,
+    UNDEFINED,
+    acall,
+    aeval,
+    build_snapshot,
+    clear_eval_cache,
+    compile_project,
+    compile_url,
+    configure_eval,
+    eval,
+    get_eval_context,
+    init,
+    register_converter,
+    set_defaults,
+    set_execution_hooks,
+    set_v8_flags,
+    shutdown,
+    transfer,
+    version_info,
+)
 
 __version__ = "2.4.4"
-__all__ = ["Context"]
+__all__ = [
+    "AbortToken",
+    "CompileTask",
+    "Context",
+    "ContextPool",
+    "ContextPoisoned",
+    "ContextRegistry",
+    "Handle",
+    "JsUndefined",
+    "MapStream",
+    "PausedFrame",
+    "PoolFull",
+    "PreparedCall",
+    "QuotaExceeded",
+    "Realm",
+    "SchemaMismatch",
+    "ShadowRealm",
+    "SharedBuffer",
+    "SnapshotPool",
+    "ThreadedContext",
+    "UNDEFINED",
+    "acall",
+    "aeval",
+    "build_snapshot",
+    "clear_eval_cache",
+    "compile_project",
+    "compile_url",
+    "configure_eval",
+    "eval",
+    "get_eval_context",
+    "init",
+    "register_converter",
+    "set_defaults",
+    "set_execution_hooks",
+    "set_v8_flags",
+    "shutdown",
+    "transfer",
+    "version_info",
+]
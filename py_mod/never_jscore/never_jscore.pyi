@@ -5,7 +5,34 @@ never_jscore - 基于 Deno Core 的 JavaScript 运行时
 py_mini_racer 风格的实例化 API。
 """
 
-from typing import Any, List, Union, Optional
+import os
+from typing import Any, AsyncIterator, Awaitable, Callable, Dict, Iterable, Iterator, List, Tuple, Union, Optional
+
+class QuotaExceeded(Exception):
+    """Context 的资源配额（quotas）已耗尽，或单次结果超出 max_result_bytes"""
+    ...
+
+class ContextPoisoned(RuntimeError):
+    """Context 的 isolate 遇到致命错误（超出 max_heap_mb、执行中的 panic）后不再可用（auto_recreate=False）"""
+    ...
+
+class PoolFull(Exception):
+    """ContextPool 的任务队列已满（max_queue），且 on_full="raise" 或是 asyncio 调用"""
+    ...
+
+class SchemaMismatch(ValueError):
+    """call() 的结果不符合 expect_schema"""
+
+    mismatches: List[str]
+    """不符合的位置，如 "$.data.token: expected string, got number"（最多 20 条）"""
+
+class JsUndefined:
+    """JavaScript 的 undefined（undefined_as="sentinel" 时的转换结果），唯一的实例是 UNDEFINED，布尔值为 False"""
+
+    def __bool__(self) -> bool: ...
+
+UNDEFINED: JsUndefined
+"""undefined_as="sentinel" 时结果中的 undefined（用 `is` 比较，与 null 转换的 None 区分）"""
 
 class Context:
     """
@@ -50,7 +77,35 @@ class Context:
         >>> # 只有 ECMAScript 标准 API
     """
 
-    def __init__(self, enable_extensions: bool = True, enable_logging: bool = False, random_seed: Optional[int] = None) -> None:
+    def __init__(
+        self,
+        enable_extensions: bool = True,
+        enable_logging: bool = False,
+        random_seed: Optional[int] = None,
+        snapshot: Optional[bytes] = None,
+        code_cache_dir: Optional[Union[str, os.PathLike]] = None,
+        initial_heap_mb: Optional[int] = None,
+        max_heap_mb: Optional[int] = None,
+        jitless: bool = False,
+        permissions: Union[Dict[str, Union[bool, str, List[str]]], str, None] = None,
+        allow_dynamic_code: bool = True,
+        timeout_ms: Optional[int] = None,
+        cpu_limit_ms: Optional[int] = None,
+        stack_size_kb: Optional[int] = None,
+        audit_ops: bool = False,
+        fs_roots: Union[str, List[str], Dict[str, str], None] = None,
+        env: Optional[Dict[str, str]] = None,
+        no_ops: bool = False,
+        quotas: Optional[Dict[str, int]] = None,
+        history: Optional[int] = None,
+        shared_memory: bool = False,
+        shared_buffers: Optional[Dict[str, "SharedBuffer"]] = None,
+        weak_refs: bool = True,
+        auto_recreate: bool = False,
+        max_handles: Optional[int] = None,
+        yield_interval_ms: Optional[int] = None,
+        undefined_as: Optional[str] = None,
+    ) -> None:
         """
         创建一个新的 JavaScript 执行上下文
 
@@ -66,6 +121,84 @@ class Context:
                         - int: 使用固定种子（确定性）
                           所有随机数 API（Math.random、crypto.getRandomValues 等）
                           将基于此种子生成，方便调试和算法对比
+            snapshot: 启动快照（可选），由 build_snapshot() 生成
+                     - 提供时直接从快照恢复 V8 堆，快照中定义的函数/变量立即可用
+                     - enable_extensions 必须与构建快照时一致，否则抛出 ValueError
+                     - 日志开关以构建快照时的 enable_logging 为准
+            code_cache_dir: V8 代码缓存目录（可选）
+                           - 设置后 compile()/compile_file() 会缓存编译后的字节码
+                           - 以代码内容的 SHA-256 为 key，进程重启后直接复用
+            initial_heap_mb: 初始堆大小（MB，可选）
+            max_heap_mb: 最大堆大小（MB，可选）
+                        - 每个 Context 独立设置，可以混用小型沙箱 Context 和大型工作 Context
+                        - 超出时终止当前执行并抛出 "Heap limit exceeded" 异常，而不是让进程 OOM 崩溃
+                        - 超出限制后 Context 的状态可能不完整，建议重新创建
+            jitless: 是否要求以无 JIT 模式运行，默认 False
+                    - True: V8 只用解释器执行（不生成机器码），适合执行不可信代码，性能明显下降
+                    - 这是进程级设置：必须在第一个 Context 之前开启（或使用 init(jitless=True)），
+                      否则抛出 RuntimeError
+            permissions: 网络 / 文件 / 环境变量权限（可选）
+                        - 默认 None 拒绝网络和文件访问，宿主环境变量不可见（process.env 为空，env= 注入的变量仍然可见）
+                        - "all": 不做任何限制（以前的默认行为）；其他字符串抛出 ValueError，非字典 / 字符串抛出 TypeError
+                        - 传入字典后未列出的类别全部拒绝，被拒绝的操作在 JS 中抛出
+                          name 为 "PermissionDenied" 的错误
+                        - 重定向的每一跳都重新检查 net 权限，重定向到未授权的主机时抛出 PermissionDenied
+                        - 键：net（主机名，可带端口）、read / write（目录或文件）、env（变量名）
+                        - 值：True 全部允许，False / None 拒绝，字符串或字符串列表为白名单
+            allow_dynamic_code: 是否允许 eval / new Function 从字符串生成代码，默认 True
+                        - False: eval()、new Function() 抛出 EvalError，注入的代码无法在运行时再生成新代码
+                        - evaluate()/call() 仍然可用（代码由 Rust 侧作为脚本编译执行），
+                          但其中的 var 和函数声明会进入全局作用域
+                        - require() 加载 JS 文件依赖 eval，此模式下不可用
+            timeout_ms: 每次调用的墙钟超时（毫秒，可选）
+                        - 包括等待定时器 / Promise 的时间，超出时抛出 "Execution timed out" 异常
+            cpu_limit_ms: 每次调用的 CPU 时间预算（毫秒，可选，目前仅支持 Linux）
+                        - 只统计实际消耗的 CPU 时间，死循环在宽松的 timeout_ms 下也会被及时终止
+                        - 超出时抛出 "CPU time limit exceeded" 异常，Context 仍然可用
+            stack_size_kb: V8 栈大小上限（KB，可选），默认约 1 MB
+                        - 递归超过上限时抛出可以捕获的 RangeError，而不是让进程崩溃
+                        - 进程级设置：必须在第一个 Context 之前设置（或使用 init(stack_size_kb=...)），否则抛出 RuntimeError
+                        - 超过当前线程的栈大小时抛出 RuntimeError
+            audit_ops: 是否记录每次 op 调用（默认 False），通过 get_audit_log() 获取
+                        - 用于调试扩展，或检查不可信脚本尝试了哪些操作
+            fs_roots: Deno 风格文件 API 可访问的目录（默认 None，不提供该 API）
+                        - 目录或目录列表（只读），或 {目录: "r" | "rw"} 字典；目录不存在时抛出 ValueError
+                        - 设置后 JS 中可以使用 Deno.readTextFile / readFile / readDir / stat（及 Sync 版本），
+                          "rw" 目录还可以使用 Deno.writeTextFile / writeFile
+                        - 访问其他路径抛出 PermissionDenied；不受 permissions 影响，需要 enable_extensions=True
+            env: 注入的环境变量 {名称: 值}（默认 None 不注入，宿主环境变量只在 permissions 授予 env 时可见）
+                        - 设置后 process.env 和 Deno.env.get() / has() / toObject() 只能看到这些变量，
+                          宿主环境变量不会泄露给脚本（load_wasm 的 inherit_env 同样使用注入的变量）
+                        - 仍然受 permissions 的 env 权限限制；需要 enable_extensions=True
+            no_ops: 纯计算模式（默认 False）
+                        - True: 不加载任何扩展（忽略 enable_extensions），console 不输出，
+                          脚本无法访问 V8 之外的任何资源，适合执行完全不可信的代码片段
+            quotas: Context 生命周期内累计的资源配额（默认 None，不限制）
+                        - max_executions: 最多执行次数；max_cpu_ms: 累计 CPU 时间（仅 Linux）；
+                          max_console_bytes: 累计 console 输出字节数；max_result_bytes: 单次结果（JSON）最大字节数
+                        - 前三项耗尽后拒绝之后的所有调用；超出时抛出 QuotaExceeded，使用情况通过 get_quota_usage() 获取
+            history: 记录最近多少次执行（默认 None，不记录）
+                        - 超出容量时丢弃最早的记录，通过 history() 获取，用于出错后回溯最近执行了什么
+            shared_memory: 像跨源隔离的页面一样报告 crossOriginIsolated = true（默认 False）
+                        - SharedArrayBuffer / Atomics 始终可用，但 emscripten 的 pthread 构建等脚本会先检查 crossOriginIsolated
+            shared_buffers: {全局变量名: SharedBuffer}（默认 None），在用户代码执行之前定义为 SharedArrayBuffer
+                        - Python 和共享同一个 SharedBuffer 的其他 Context 读写同一块内存
+            weak_refs: 是否提供 WeakRef / FinalizationRegistry（默认 True）
+                        - 为 False 时从全局对象中删除，依赖它们的代码会走不使用弱引用的回退路径
+            auto_recreate: 遇到致命错误后自动重建 isolate（默认 False）
+                        - 超出 max_heap_mb、执行中的 panic 之后 isolate 不再可靠：默认标记为 poisoned，之后的执行抛出 ContextPoisoned
+                        - 为 True 时在下一次调用之前按 recycle_after 的方式重建（初始化脚本和 register() 的函数原样重建）
+            max_handles: Realm / ShadowRealm / prepare() 函数 / call() 缓存函数的总数上限（默认 None 不限制）
+                        - 达到上限时先清空 call() 的函数缓存，仍然超出时 create_realm() / create_shadow_realm() / prepare()
+                          抛出 QuotaExceeded；通过 handles() 列出、Handle.release() 释放
+            yield_interval_ms: 同步执行期间处理 Python 信号的间隔（毫秒，默认 None，至少为 1）
+                        - Ctrl-C（KeyboardInterrupt）或信号处理函数抛出的异常会终止死循环并原样抛出，Context 仍然可用
+                        - 信号只在主线程上处理
+            undefined_as: 结果中 undefined 的转换策略（默认 None，沿用 JSON.stringify 的语义：
+                        顶层为 None，对象中省略该键，数组中为 None），对顶层结果、对象的值和数组元素（包括空位）一致地生效
+                        - "none": 一律转换为 None（对象保留该键）
+                        - "sentinel": 一律转换为 never_jscore.UNDEFINED，与 null 区分
+                        - "omit": 对象省略该键，数组删除该元素；顶层结果为 None
 
         Example:
             >>> # 使用固定随机数种子
@@ -76,18 +209,59 @@ class Context:
             >>> # 另一个相同种子的上下文将产生相同的随机数序列
             >>> ctx2 = Context(random_seed=12345)
             >>> r3 = ctx2.evaluate("Math.random()")  # r3 == r1
+            >>>
+            >>> # 限制堆大小（沙箱）
+            >>> sandbox = Context(max_heap_mb=64)
+            >>>
+            >>> # 执行不可信代码（禁用 JIT）
+            >>> untrusted = Context(jitless=True, max_heap_mb=64)
+            >>>
+            >>> # 只允许访问指定主机和目录，禁止读取环境变量
+            >>> sandboxed = Context(permissions={"net": ["api.example.com"], "read": ["./data"], "env": False})
+            >>>
+            >>> # 禁止 eval / new Function
+            >>> static = Context(allow_dynamic_code=False)
+            >>>
+            >>> # 死循环 100ms CPU 时间后终止，等待请求最多 30 秒
+            >>> limited = Context(timeout_ms=30000, cpu_limit_ms=100)
         """
         ...
 
-    def compile(self, code: str) -> None:
+    def compile(
+        self,
+        code: str,
+        preset: Optional[List[str]] = None,
+        recycle_after: Optional[Dict[str, int]] = None,
+        result_filter: Optional[str] = None,
+        tag: Optional[str] = None,
+    ) -> None:
         """
         编译 JavaScript 代码并加入全局作用域
 
         Args:
             code: JavaScript 代码字符串
+            preset: 在 code 之前加载的内置库（可选），如 ["crypto-js", "jsencrypt"]
+                   - "crypto-js": 全局变量 CryptoJS（原样内置的 crypto-js 4.2.0）
+                   - "jsencrypt": 全局变量 JSEncrypt（原样内置的 jsencrypt 3.3.2）
+            recycle_after: 自动回收阈值（可选），如 {"calls": 10000, "heap_mb": 300}。
+                   达到任一阈值时，call() / evaluate() / eval() / render_call() / repl_eval() 返回前按原顺序重建 isolate：
+                   构造参数、已执行的全局脚本和 register() 的函数保留，evaluate() / call() 中的副作用丢弃；
+                   调用统计、配额用量、执行历史和回调转移到新的 isolate。
+                   - calls: 当前 isolate 执行 JS 的次数
+                   - heap_mb: 调用结束后 V8 已用堆大小（MB）
+                   重建失败时继续使用当前 isolate，错误通过 sys.unraisablehook 报告。
+                   不能与 load_wasm()、ES 模块、share_buffer()、import_state()、
+                   create_realm() / create_shadow_realm() 同时使用
+            result_filter: 结果过滤函数的名称（可选），如 "__sanitize"。之后 evaluate() / call() / eval(return_value=True) /
+                   render_call() / repl_eval() 的结果先交给该函数，序列化它的返回值（异步模式下等待返回的 Promise）。
+                   名称在 code 执行之后求值，每次调用重新求值；传入空字符串清除。
+                   提前返回（__neverjscore_return__）的值不经过过滤
+            tag: 脚本标签（可选），如 "libA"。本次执行的脚本（包括 preset）记录为该标签，之后可以用 unload("libA") 卸载；
+                   同一标签可以多次使用
 
         Raises:
             Exception: 当代码编译失败时
+            ValueError: recycle_after 无效，或 Context 包含无法重建的状态；result_filter 不是函数
 
         Example:
             >>> ctx = Context()
@@ -97,6 +271,221 @@ class Context:
             ... ''')
             >>> ctx.call("add", [1, 2])
             3
+            >>> ctx.compile("function sign(s) { return CryptoJS.MD5(s).toString(); }", preset=["crypto-js"])
+            >>> # 长期运行的服务：每 10000 次调用或堆超过 300 MB 时重建 isolate
+            >>> ctx.compile(open("sign.js").read(), recycle_after={"calls": 10000, "heap_mb": 300})
+            >>> # 所有结果先经过 __sanitize
+            >>> ctx.compile("function __sanitize(v) { return v instanceof Uint8Array ? v.length : v; }", result_filter="__sanitize")
+            >>> # 带标签加载，之后可以用 unload() 换成另一个版本
+            >>> ctx.compile(open("lib_v1.js").read(), tag="lib")
+        """
+        ...
+
+    def unload(self, tag: str) -> None:
+        """
+        卸载 compile(code, tag=...) 加载的脚本
+
+        用相同的构造参数重建 isolate，按原顺序重新执行其余的全局脚本、注册 register() 的函数（与 recycle_after 的重建相同），
+        带该标签的脚本不再执行。evaluate() / call() 中的副作用随旧的 isolate 丢弃；
+        调用统计、配额用量、执行历史和回调保留。之后可以用同一标签加载新版本。
+
+        Args:
+            tag: compile() 传入的标签
+
+        Raises:
+            ValueError: 没有带该标签的脚本，或 Context 无法重建（load_wasm()、ES 模块、share_buffer()、import_state()、Realm 等）
+            Exception: 重新执行其余脚本失败（Context 保持不变）
+
+        Example:
+            >>> ctx.compile(open("crypto_v1.js").read(), tag="crypto")
+            >>> ctx.compile("function sign(s) { return Crypto.hash(s); }")
+            >>> ctx.unload("crypto")
+            >>> ctx.compile(open("crypto_v2.js").read(), tag="crypto")
+        """
+        ...
+
+    def compile_file(
+        self,
+        path: Union[str, os.PathLike],
+        encoding: Optional[str] = None,
+        mode: Optional[str] = None,
+        watch: bool = False,
+    ) -> None:
+        """
+        从文件编译 JavaScript 代码并加入全局作用域
+
+        效果与 compile(open(path).read()) 相同，但错误堆栈中会显示文件路径。
+        创建 Context 时设置了 code_cache_dir 的话，会使用 V8 代码缓存。
+        开头的 BOM 会被去掉，shebang（#!/usr/bin/env node）会被忽略。
+
+        Args:
+            path: JavaScript 文件路径
+            encoding: 文件编码（默认 UTF-8），如 "gbk"，名称与 open(encoding=...) 相同
+            mode: 执行方式，默认按扩展名选择（.mjs 为 "module"，其余为 "script"）
+                  - "script": 全局脚本，与 compile() 相同
+                  - "module": ES 模块，具名导出定义为全局变量（不能 import 其他文件，可以使用顶层 await）
+                  - "typescript": 不支持（.ts 文件同样），需要先转换为 JavaScript
+            watch: 监视文件（默认 False）。文件修改后，下一次 call() / evaluate() / eval() 之前自动重新编译；
+                   重新编译失败时该次调用抛出错误。顶层 let / const / class 重新执行会报重复声明
+
+        Raises:
+            ValueError: 无法按指定编码解码、未知的 mode 或 TypeScript 文件
+            Exception: 文件读取失败或代码编译失败时
+
+        Example:
+            >>> ctx = Context(code_cache_dir=".jscache")
+            >>> ctx.compile_file("webpack_bundle.js")  # 第二次运行时直接使用缓存
+            >>> ctx.call("sign", ["data"])
+            >>> ctx.compile_file("legacy.js", encoding="gbk")
+            >>> ctx.compile_file("sign.js", watch=True)  # 保存 sign.js 后下一次调用自动使用新代码
+        """
+        ...
+
+    def compile_project(
+        self,
+        dir: Union[str, os.PathLike],
+        entry: Optional[Union[str, os.PathLike]] = None,
+        encoding: Optional[str] = None,
+    ) -> List[str]:
+        """
+        按依赖顺序加载目录中的脚本
+
+        目录中有 never_jscore.json（{"files": ["env.js", "main.js"]}）时按其中的顺序加载；
+        否则扫描 .js / .mjs / .cjs 文件（跳过 node_modules 和隐藏目录），
+        根据 require('./x')、import ... from './x' 和注释指令 // @requires ./x.js 先加载被依赖的文件。
+        每个文件按 compile_file() 的方式执行。
+
+        Args:
+            dir: 项目目录
+            entry: 入口文件（相对 dir 或绝对路径）。指定时只加载入口及其依赖，默认加载全部文件
+            encoding: 文件编码（默认 UTF-8），同 compile_file()
+
+        Returns:
+            按加载顺序排列的文件路径
+
+        Raises:
+            ValueError: 循环依赖、清单格式错误或 @requires 指向不存在的文件
+            Exception: 文件读取失败或代码执行失败时（错误信息包含文件路径）
+
+        Example:
+            >>> ctx = Context(timeout_ms=5000)
+            >>> ctx.compile_project("sign", entry="main.js")
+            ['/.../sign/env.js', '/.../sign/lib/crypto.js', '/.../sign/main.js']
+            >>> ctx.call("sign", ["data"])
+        """
+        ...
+
+    def compile_url(
+        self,
+        url: str,
+        sha256: Optional[str] = None,
+        cache_dir: Optional[Union[str, os.PathLike]] = None,
+        proxy: Optional[str] = None,
+        timeout_ms: int = 30000,
+        encoding: Optional[str] = None,
+        mode: Optional[str] = None,
+    ) -> str:
+        """
+        下载远程脚本，校验 SHA-256 后编译
+
+        下载遵循 HTTP_PROXY / HTTPS_PROXY / NO_PROXY 环境变量（proxy 参数优先）。
+        传入 sha256 时优先使用磁盘缓存（同样重新校验）；不传时每次都下载、不缓存。
+        脚本按 compile_file() 的方式执行（.mjs 为 ES 模块），错误堆栈中的文件名为 URL。
+
+        Args:
+            url: 脚本的 http(s) URL
+            sha256: 期望的 SHA-256（十六进制），默认 None 不校验
+            cache_dir: 缓存目录，默认 $XDG_CACHE_HOME/never_jscore/scripts（~/.cache/never_jscore/scripts）
+            proxy: 代理地址，默认使用环境变量
+            timeout_ms: 下载超时（毫秒，默认 30000）
+            encoding / mode: 同 compile_file()，mode 默认按 URL 路径的扩展名选择
+
+        Returns:
+            脚本的 SHA-256（十六进制）
+
+        Raises:
+            ValueError: sha256 格式错误、哈希不一致（不缓存也不执行）或 URL 无效
+            Exception: 下载失败或脚本执行失败
+
+        Example:
+            >>> digest = ctx.compile_url("https://www.example.com/static/sign.js")
+            >>> ctx.compile_url("https://www.example.com/static/sign.js", sha256=digest)
+        """
+        ...
+
+    def compile_background(self, code: str) -> "CompileTask":
+        """
+        在后台线程编译 JavaScript 代码
+
+        适合几 MB 的大型 bundle：解析和编译在后台线程的独立 isolate 中进行，
+        调用方可以同时做其他工作，之后调用 wait() 执行脚本（效果与 compile() 相同）。
+        设置了 code_cache_dir 时会复用 / 写入磁盘缓存。
+
+        Args:
+            code: JavaScript 代码字符串
+
+        Returns:
+            CompileTask，提供 done() 和 wait()
+
+        Example:
+            >>> task = ctx.compile_background(open("bundle.js").read())
+            >>> load_config()  # 与编译并行
+            >>> task.wait()
+            >>> ctx.call("sign", ["data"])
+        """
+        ...
+
+    def create_realm(self) -> "Realm":
+        """
+        在此 Context 的 isolate 中创建轻量级 Realm
+
+        Realm 拥有独立的全局作用域和内置对象，但与 Context 共享 isolate（堆和线程），
+        创建 / 销毁只需几十微秒，适合按请求隔离。
+        Realm 中只有 ECMAScript 内置对象，没有扩展 API 和事件循环。
+
+        Returns:
+            Realm，提供 compile() / eval() / evaluate() / call() / close()
+
+        Example:
+            >>> with ctx.create_realm() as realm:
+            ...     realm.compile("var user = 'alice';")
+            ...     realm.evaluate("user")
+            'alice'
+        """
+        ...
+
+    def create_shadow_realm(self) -> "ShadowRealm":
+        """
+        在此 Context 中创建 ShadowRealm（等同于 JS 中的 new ShadowRealm()）
+
+        ShadowRealm 拥有独立的全局作用域和内置对象，边界两侧只能传递原始值和函数。
+        JS 代码也可以直接使用 ShadowRealm。
+
+        Returns:
+            ShadowRealm，提供 evaluate() / close()
+
+        Example:
+            >>> with ctx.create_shadow_realm() as realm:
+            ...     realm.evaluate("Array.prototype.map = null; 1")
+            ...     realm.evaluate("typeof Array.prototype.map")
+            'object'
+            >>> ctx.evaluate("typeof Array.prototype.map")
+            'function'
+        """
+        ...
+
+    def handles(self) -> List["Handle"]:
+        """
+        列出此 Context 持有的长期 V8 对象（按创建顺序）
+
+        包括 Realm、ShadowRealm（close() 或销毁后不再列出）、prepare() 定义的函数（同一模板一个，
+        PreparedCall 销毁后仍然保留）和 call() 缓存的目标函数。Context(max_handles=N) 限制它们的总数。
+
+        Example:
+            >>> ctx.call("sign", [1])
+            >>> [(h.kind, h.name) for h in ctx.handles()]
+            [('call_target', 'sign')]
+            >>> ctx.handles()[0].release()
         """
         ...
 
@@ -104,7 +493,9 @@ class Context:
         self,
         code: str,
         return_value: bool = False,
-        auto_await: Optional[bool] = None
+        auto_await: Optional[bool] = None,
+        capture_output: bool = False,
+        event_loop: Optional[Dict[str, Any]] = None,
     ) -> Any:
         """
         执行代码并将其加入全局作用域
@@ -113,9 +504,14 @@ class Context:
             code: JavaScript 代码字符串
             return_value: 是否返回最后一个表达式的值（默认 False）
             auto_await: 是否自动等待 Promise（默认 True）
+            capture_output: 是否收集本次调用的 console 输出（默认 False）。
+                            收集的输出不再写到 stdout / stderr，调用失败时照常输出
+            event_loop: 本次调用的事件循环选项（默认 None），同 evaluate()
 
         Returns:
-            如果 return_value=True，返回最后表达式的值；否则返回 None
+            如果 return_value=True，返回最后表达式的值；否则返回 None。
+            capture_output=True 时返回 (结果, 输出列表)，
+            每条输出为 {"stream": "stdout" | "stderr", "message": str}
 
         Raises:
             Exception: 当代码执行失败时
@@ -126,19 +522,47 @@ class Context:
             >>> result = ctx.eval("x * 2", return_value=True)
             >>> print(result)
             20
+            >>> ctx.eval("console.log('x =', x); x", return_value=True, capture_output=True)
+            (10, [{'stream': 'stdout', 'message': 'x = 10'}])
         """
         ...
 
-    def evaluate(self, code: str, auto_await: Optional[bool] = None) -> Any:
+    def evaluate(
+        self,
+        code: str,
+        auto_await: Optional[bool] = None,
+        return_timings: bool = False,
+        binary: bool = False,
+        stream_to: Optional[Any] = None,
+        event_loop: Optional[Dict[str, Any]] = None,
+        result_format: Optional[str] = None,
+    ) -> Any:
         """
         执行代码并返回结果（不影响全局作用域）
 
         Args:
             code: JavaScript 代码字符串
             auto_await: 是否自动等待 Promise（默认 True）
+            return_timings: 是否同时返回本次调用的耗时分解（默认 False）
+            binary: 把结果中的字符串作为二进制字符串（每个字符一个字节）返回为 bytes（默认 False），
+                    用于 latin1 密文、String.fromCharCode 的结果等；字符大于 0xFF 时抛出 ValueError
+            stream_to: 有 write(str) 方法的文件对象（默认 None）。结果的 JSON 文本约每 1 MB 写入一次，
+                       不在内存中保留完整结果，适合几十 MB 的返回值；不能与 binary=True 同时使用
+            event_loop: 本次调用的事件循环选项（默认 None），字典，键为：
+                - max_iterations: 事件循环最多被 poll 的次数（没有任务到期的唤醒同样计数）。达到上限时结果已就绪则正常返回
+                  （剩余的定时器保留到之后的调用），否则抛出异常
+                - pump_message_loop: 是否处理 V8 平台任务（WebAssembly 后台编译、Atomics.waitAsync 等），默认 True
+                - wait_for_inspector: 有调试器会话时等待其断开，默认 False
+            result_format: 结果的格式（默认 "python"）：
+                - "python": 在 Rust 中解析 JSON 并转换为 Python 对象
+                - "json_str": 不解析，直接返回 JSON 字符串（结果原样转发给其他服务时省去解析和重新序列化）
+                - "orjson": 交给 orjson.loads 解析（需要安装 orjson，缺少时抛出 ImportError；Temporal 值保持为字典）
+                后两者不能与 binary / stream_to 同时使用
 
         Returns:
-            表达式的值，自动转换为 Python 对象
+            表达式的值，自动转换为 Python 对象；
+            stream_to 不为 None 时返回写入的 UTF-8 字节数；
+            return_timings=True 时返回 (值, 耗时字典)，耗时字典的键见 get_stats()
 
         Raises:
             Exception: 当代码执行失败时
@@ -149,6 +573,17 @@ class Context:
             >>> print(result)
             6
 
+            >>> ctx.evaluate("String.fromCharCode(0xde, 0xad, 0xbe, 0xef)", binary=True)
+            b'\\xde\\xad\\xbe\\xef'
+
+            >>> # 大结果直接写入文件
+            >>> with open("items.json", "w", encoding="utf-8") as f:
+            ...     ctx.evaluate("extractAll()", stream_to=f)
+
+            >>> # setInterval 永不停止：结果就绪后最多再推进 100 轮事件循环
+            >>> ctx.evaluate("new Promise(r => { setInterval(() => {}, 0); r(1); })", event_loop={"max_iterations": 100})
+            1
+
             >>> # Promise（自动等待）
             >>> result = ctx.evaluate("Promise.resolve(42)")
             >>> print(result)
@@ -156,25 +591,76 @@ class Context:
         """
         ...
 
+    def repl_eval(self, line: str, auto_await: Optional[bool] = None) -> Any:
+        """
+        像浏览器控制台一样执行一行输入并返回结果
+
+        与 evaluate() 不同，输入作为全局脚本执行：
+        - 返回最后一条语句的完成值（`let x = 1; x + 1` 返回 2）
+        - let/const/class 声明保留到后续的输入中
+        - 结果赋给全局变量 `_`（用户自己定义了 `_` 之后不再覆盖）
+
+        与控制台相同，重复声明同名的 let/const 会抛出 SyntaxError。
+
+        Args:
+            line: JavaScript 代码（可以包含多条语句）
+            auto_await: 是否自动等待 Promise（默认 True），`_` 为 Promise 的结果
+
+        Returns:
+            完成值，自动转换为 Python 对象
+
+        Example:
+            >>> ctx.repl_eval("let a = 20")
+            >>> ctx.repl_eval("const b = 22")
+            >>> ctx.repl_eval("a + b")
+            42
+            >>> ctx.repl_eval("_ * 2")
+            84
+        """
+        ...
+
     def call(
         self,
         name: str,
         args: List[Any] = [],
-        auto_await: Optional[bool] = None
+        auto_await: Optional[bool] = None,
+        return_timings: bool = False,
+        binary: bool = False,
+        stream_to: Optional[Any] = None,
+        event_loop: Optional[Dict[str, Any]] = None,
+        trace_id: Optional[str] = None,
+        result_format: Optional[str] = None,
+        expect_schema: Optional[Dict[str, Any]] = None,
     ) -> Any:
         """
         调用 JavaScript 函数（支持 Promise）
 
+        name 是全局函数或属性路径（如 "utils.sign"）时，解析出的函数缓存在 Context 中，
+        之后的调用不再拼接代码重新解析；执行全局脚本（compile() / eval() 等）后重新解析。
+
         Args:
             name: 函数名称
             args: 参数列表
             auto_await: 是否自动等待 Promise（默认 True）
+            return_timings: 是否同时返回本次调用的耗时分解（默认 False）
+            binary: 把结果中的字符串作为二进制字符串返回为 bytes（默认 False），同 evaluate()
+            stream_to: 把结果的 JSON 文本分块写入文件对象，返回写入的字节数（默认 None），同 evaluate()
+            event_loop: 本次调用的事件循环选项（默认 None），同 evaluate()
+            trace_id: 请求的追踪 ID（默认 None）。调用期间 JS 中的 globalThis.__trace_id 为该 ID；
+                      console 输出加上 [trace_id=...] 前缀；
+                      执行钩子的字典带有 trace_id；调用失败时异常的 trace_id 属性为该 ID
+            result_format: 结果的格式："python"（默认）/ "json_str" / "orjson"，同 evaluate()
+            expect_schema: 结果的规则（默认 None），JSON Schema 的常用子集：type / enum / const / properties / required /
+                      additionalProperties / items / minItems / maxItems / minLength / maxLength / pattern / minimum / maximum。
+                      在 JS 侧序列化之前（经过 result_filter 之后）检查
 
         Returns:
-            函数返回值，自动转换为 Python 对象
+            函数返回值，自动转换为 Python 对象；
+            return_timings=True 时返回 (结果, 耗时字典)，耗时字典的键见 get_stats()
 
         Raises:
             Exception: 当函数调用失败时
+            SchemaMismatch: 结果不符合 expect_schema，mismatches 属性列出不符合的位置
 
         Example:
             >>> ctx = Context()
@@ -185,155 +671,645 @@ class Context:
         """
         ...
 
-    def gc(self) -> None:
+    def set_breakpoint(
+        self,
+        file: str,
+        line: int,
+        column: Optional[int] = None,
+        condition: Optional[str] = None
+    ) -> str:
         """
-        请求 V8 垃圾回收
+        按脚本名设置断点，在 debug_call() 中执行到该行时暂停
 
-        注意：这只是向 V8 发送 GC 请求，V8 会根据自己的策略决定是否执行。
-        在大多数情况下，V8 的自动 GC 已经足够好，无需手动调用。
+        脚本名以 file 结尾即匹配：compile() 的脚本名为 "<exec>"，compile_file() 为文件路径，
+        也可以在代码末尾用 //# sourceURL=name.js 指定。断点可以在脚本编译之前设置。
+
+        Args:
+            file: 脚本名或其结尾部分
+            line: 行号（从 1 开始）
+            column: 列号（可选，从 1 开始）
+            condition: 条件表达式（可选），求值为真时才暂停
+
+        Returns:
+            断点 ID，用于 remove_breakpoint() 和 PausedFrame.hit_breakpoints
         """
         ...
 
-    def get_stats(self) -> dict[str, int]:
+    def remove_breakpoint(self, breakpoint_id: str) -> None:
+        """删除 set_breakpoint() 设置的断点"""
+        ...
+
+    def debug_call(
+        self,
+        name: str,
+        args: List[Any],
+        on_pause: Callable[["PausedFrame"], Optional[str]],
+        pause_on_start: bool = False,
+        auto_await: Optional[bool] = None
+    ) -> Any:
         """
-        获取执行统计信息
+        在调试模式下调用 JavaScript 函数
+
+        执行遇到 debugger 语句或断点时暂停并调用 on_pause(frame)。
+        on_pause 返回 None / "continue" 继续执行，"step_over" / "step_into" / "step_out" 单步执行；
+        抛出的异常会终止执行并由 debug_call() 原样抛出。
+        on_pause 中不能调用此 Context 的其他方法，求值使用 frame.evaluate()。
+        暂停的时间计入 timeout_ms / cpu_limit_ms。
+
+        Args:
+            name: 函数名称
+            args: 参数列表
+            on_pause: 暂停时调用的函数，参数为 PausedFrame
+            pause_on_start: 是否在进入函数时暂停（frame.reason 为 "start"）
+            auto_await: 是否自动等待 Promise（默认 True）
 
         Returns:
-            包含各类操作计数的字典:
-            - evaluate_count: evaluate() 调用次数
-            - compile_count: compile() 调用次数
-            - call_count: call() 调用次数
+            函数返回值
 
         Example:
-            >>> ctx = Context()
-            >>> ctx.evaluate("1 + 1")
-            >>> ctx.compile("function test() {}")
-            >>> ctx.call("test", [])
-            >>> stats = ctx.get_stats()
-            >>> print(stats)
-            {'evaluate_count': 1, 'compile_count': 1, 'call_count': 1}
+            >>> ctx.compile("function add(a, b) { const sum = a + b; debugger; return sum; }")
+            >>> ctx.debug_call("add", [1, 2], lambda frame: print(frame.locals()))
+            {'a': 1, 'b': 2, 'sum': 3}
+            3
         """
         ...
 
-    def reset_stats(self) -> None:
+    def render_call(self, template: str, **values: Any) -> Any:
         """
-        重置统计信息
-        """
-        ...
+        用命名占位符拼接调用代码并求值（自动等待 Promise）
 
-    def get_heap_statistics(self) -> dict[str, int]:
-        """
-        获取 V8 堆内存统计信息
+        每个值按 call() 的参数规则（包括 register_converter() 注册的转换器）序列化为 JSON 字面量后替换 {名称}，
+        代替 f-string 拼接：参数中的引号、换行不会破坏代码，也无法注入代码。
+        {{名称}} 输出字面的 {名称}（对象简写、解构），其他花括号原样保留。
+
+        Args:
+            template: 带 {名称} 占位符的 JavaScript 表达式
+            **values: 占位符的值
 
         Returns:
-            包含 V8 堆内存统计的字典:
-            - total_heap_size: V8 堆总大小（字节），包括未使用空间
-            - used_heap_size: 已使用的堆内存（字节）
-            - heap_size_limit: V8 堆大小上限（字节）
-            - total_physical_size: 实际物理内存占用（字节）
-            - malloced_memory: 通过 malloc 分配的内存（字节）
-            - external_memory: 外部对象占用的内存（字节）
-            - number_of_native_contexts: 原生上下文数量
+            表达式的值，自动转换为 Python 对象
+
+        Raises:
+            KeyError: 模板中的占位符没有对应的值
+            TypeError: 传入了模板中没有的值
 
         Example:
-            >>> ctx = Context()
-            >>> ctx.evaluate("const arr = []; for(let i=0; i<10000; i++) arr.push({id:i})")
-            >>> heap = ctx.get_heap_statistics()
-            >>> print(f"Total: {heap['total_heap_size'] / 1024 / 1024:.2f} MB")
-            >>> print(f"Used: {heap['used_heap_size'] / 1024 / 1024:.2f} MB")
-            >>> print(f"Usage: {heap['used_heap_size'] / heap['total_heap_size'] * 100:.1f}%")
+            >>> ctx.compile("function sign(ts, payload) { return ts + ':' + payload.q; }")
+            >>> ctx.render_call("sign({ts}, {payload})", ts=1700000000, payload={"q": "it's \"quoted\""})
+            '1700000000:it\'s "quoted"'
         """
         ...
 
-    def take_heap_snapshot(self, file_path: str) -> None:
+    def prepare(self, template: str) -> "PreparedCall":
         """
-        导出 V8 堆快照到文件（Chrome DevTools 格式）
+        预编译带 ? 占位符的调用表达式，返回可重复调用的 PreparedCall
 
-        导出的 .heapsnapshot 文件可以在 Chrome DevTools 中分析:
-        1. 打开 Chrome -> F12 -> Memory 标签
-        2. 点击 "Load" 按钮加载快照文件
-        3. 查看对象分配、内存泄漏、循环引用等
+        表达式只编译一次，stmt(*args) 按 call() 的参数规则（包括 register_converter() 注册的转换器）
+        转换参数后直接传给编译好的函数：不重新解析代码，参数也不会拼进代码（无法注入代码）。
+        占位符是表达式位置上的 ?（开头，或括号、逗号、运算符、await / typeof 等之后）；
+        `?.`、`??`、三元运算符和字符串中的 ? 不是占位符。自动等待 Promise。
+
+        同一模板重复 prepare() 得到同一个函数；预编译的函数在 pickle / recycle_after 重建后仍然可用。
 
         Args:
-            file_path: 快照文件保存路径（通常以 .heapsnapshot 结尾）
+            template: 带 ? 占位符的 JavaScript 表达式
+
+        Returns:
+            PreparedCall，以位置参数调用：stmt(arg1, arg2, ...)
 
         Raises:
-            Exception: 文件创建失败或写入失败时
+            Exception: 模板不是有效的表达式（SyntaxError）
 
         Example:
-            >>> ctx = Context()
-            >>> # 执行一些代码
-            >>> ctx.evaluate("globalThis.data = []; for(let i=0; i<5000; i++) data.push({id:i})")
-            >>> # 导出堆快照
-            >>> ctx.take_heap_snapshot("heap_snapshot.heapsnapshot")
-            >>> # 在 Chrome DevTools 中分析内存使用
+            >>> ctx.compile("function hash(s, opts) { return opts ? s.length + opts.salt : s.length; }")
+            >>> stmt = ctx.prepare("hash(?)")
+            >>> [stmt(item) for item in ["a", "bb"]]
+            [1, 2]
+            >>> ctx.prepare("hash(?, { salt: ? })")("abc", "!")
+            '3!'
+        """
+        ...
 
-            >>> # 内存泄漏检测示例
-            >>> ctx.take_heap_snapshot("before.heapsnapshot")
-            >>> ctx.evaluate("globalThis.leaked = []; for(let i=0; i<10000; i++) leaked.push({data: new Array(100).fill(i)})")
-            >>> ctx.take_heap_snapshot("after.heapsnapshot")
-            >>> # 在 Chrome DevTools 中对比两个快照找出泄漏对象
+    def gc(self) -> None:
+        """
+        请求 V8 垃圾回收
+
+        注意：这只是向 V8 发送 GC 请求，V8 会根据自己的策略决定是否执行。
+        在大多数情况下，V8 的自动 GC 已经足够好，无需手动调用。
         """
         ...
 
-    def get_hook_data(self) -> Optional[str]:
+    def freeze_intrinsics(self) -> None:
         """
-        获取 Hook 拦截的数据
+        冻结内置对象（Object、Array、Function 等的原型和构造函数）
 
-        当 JavaScript 调用 __saveAndTerminate__() 或 $terminate() 时，
-        数据会保存到全局存储中。使用此方法可以在 JS 被终止后获取保存的数据。
+        在加载完初始化代码（JS 库）之后调用，之后执行的代码无法再修改内置原型，
+        防止一次调用留下的原型污染影响其他调用。冻结不可撤销，重复调用是安全的。
 
-        ⚠️ 重要特性：
-        - Hook 数据会一直保留，直到被手动清空或被新的 $terminate() 覆盖
-        - 可以多次调用 get_hook_data() 读取同一个数据
-        - 如果需要执行新的 hook，建议先调用 clear_hook_data() 清空旧数据
+        冻结后仍然可以在实例上覆盖 toString / constructor / name 等属性；
+        Error 构造函数本身不冻结（可以设置 Error.stackTraceLimit），它们的原型仍然冻结。
+
+        Example:
+            >>> ctx.compile(open("sign.js").read())
+            >>> ctx.freeze_intrinsics()
+            >>> ctx.evaluate("Array.prototype.map = () => 'hacked'")  # 静默失败（严格模式下抛出 TypeError）
+            >>> ctx.evaluate("[1, 2].map(x => x * 2)")
+            [2, 4]
+        """
+        ...
+
+    def missing_globals(self, code: str) -> List[str]:
+        """
+        列出代码用到但当前 Context 中不存在的全局变量
+
+        试运行代码：缺失的名称被记录下来并以吸收任何操作的桩对象代替，脚本继续执行以发现后续缺失的名称
+        （typeof X 探测的名称同样列出）。代码在函数作用域中执行，声明的变量和对新名称的赋值不会留在全局作用域；
+        调用已有函数的副作用是真实的。定时器等异步回调中用到的名称不会列出。
+
+        脚本因 `X is not defined` 失败时，错误信息末尾同样附加 `hint: missing_global="X" (...)` 提示。
+
+        Args:
+            code: JavaScript 代码
 
         Returns:
-            Optional[str]: 如果有保存的数据则返回 JSON 字符串，否则返回 None
+            缺失的全局变量名列表（按首次访问的顺序）
 
         Example:
-            >>> import never_jscore
-            >>> import json
-            >>>
-            >>> ctx = never_jscore.Context()
-            >>>
-            >>> # 第一次拦截
-            >>> try:
-            ...     ctx.evaluate('$terminate({ n: 1 });')
-            ... except:
-            ...     pass
-            >>> data1 = ctx.get_hook_data()  # {"n": 1}
-            >>>
-            >>> # 第二次拦截 - 自动清空了第一次的数据
-            >>> try:
-            ...     ctx.evaluate('$terminate({ n: 2 });')
-            ... except:
-            ...     pass
-            >>> data2 = ctx.get_hook_data()  # {"n": 2} ✅ 正确
-            >>>
-            >>> # 如果需要保留第一次的数据，必须在第二次执行前保存：
-            >>> # saved = ctx.get_hook_data()  # 在下一次 evaluate() 前保存
+            >>> ctx = never_jscore.Context(enable_extensions=False)
+            >>> ctx.missing_globals("var ua = navigator.userAgent; var t = btoa(require('x').key);")
+            ['navigator', 'btoa', 'require']
         """
         ...
 
-    def clear_hook_data(self) -> None:
+    def export_state(self, keys: List[str]) -> bytes:
         """
-        清空保存的 Hook 数据
+        把选定的全局变量序列化为 bytes（结构化克隆），之后用 import_state() 恢复
 
-        建议在执行新的 hook 之前调用此方法，以确保不会读到旧的 hook 数据。
+        只需要计算一次的状态（设备注册信息、协商的密钥等）导出后写入文件或数据库，
+        进程重启后在新的 Context 中恢复。支持对象、数组、Map / Set / Date / RegExp / 类型化数组和循环引用；
+        同时导出的变量之间共享的对象恢复后仍然是同一个对象。
 
-        Example:
-            >>> ctx = never_jscore.Context()
-            >>>
-            >>> # 第一次 hook
+        函数、Symbol 无法克隆（抛出 DataCloneError，指出是哪个全局变量）；类实例恢复为普通对象。
+        数据格式取决于 V8 版本：新版本能读取旧版本导出的数据，反之不行。
+
+        Args:
+            keys: 全局变量名列表（不存在的变量抛出 ReferenceError）
+
+        Returns:
+            状态数据
+
+        Example:
+            >>> ctx.evaluate("globalThis.device = register()")
+            >>> open("device.bin", "wb").write(ctx.export_state(keys=["device"]))
+            >>> # 进程重启后
+            >>> ctx = never_jscore.Context()
+            >>> ctx.import_state(open("device.bin", "rb").read())
+            ['device']
+        """
+        ...
+
+    def import_state(self, state: bytes) -> List[str]:
+        """
+        把 export_state() 导出的数据恢复为全局变量（覆盖同名变量）
+
+        导入状态后 Context 不能再 pickle / deepcopy。
+
+        Args:
+            state: export_state() 返回的 bytes
+
+        Returns:
+            恢复的全局变量名列表
+
+        Raises:
+            ValueError: 数据不是 export_state() 导出的
+        """
+        ...
+
+    def snapshot_globals(self) -> Dict[str, str]:
+        """
+        记录全局对象每个属性的指纹，之后用 diff_globals() 比较
+
+        指纹覆盖变量的内容：对象的属性（包括嵌套对象）、Map / Set 的条目、类型化数组的字节、函数的源码，
+        对象内部只修改了一个深层属性也能发现。访问器属性只记录 getter / setter，不调用它们；
+        闭包中的变量和全局 let/const/class 声明（不是全局对象的属性）不在快照中。
+
+        Returns:
+            {全局变量名: 指纹}，指纹形如 "object:1a2b3c4d"
+
+        Example:
+            >>> before = ctx.snapshot_globals()
+            >>> ctx.compile(open("sdk.js").read())
+            >>> ctx.diff_globals(before)
+            {'added': ['_sdk', 'sign'], 'changed': ['navigator'], 'removed': []}
+        """
+        ...
+
+    def diff_globals(self, prev: Dict[str, str]) -> Dict[str, List[str]]:
+        """
+        与 snapshot_globals() 的快照比较，返回新增、变化和删除的全局变量
+
+        用来确认初始化脚本实际创建了哪些全局状态，以及调用之间泄漏了什么状态
+        （缓存、计数器、被修改的环境对象等）。
+
+        Args:
+            prev: snapshot_globals() 返回的快照
+
+        Returns:
+            {"added": [...], "changed": [...], "removed": [...]}（名称按字母顺序）
+
+        Example:
+            >>> before = ctx.snapshot_globals()
+            >>> ctx.call("sign", ["payload"])
+            >>> leaked = ctx.diff_globals(before)
+            >>> assert not leaked["added"] and not leaked["changed"], leaked
+        """
+        ...
+
+    def warmup(
+        self,
+        calls: Iterable[Tuple[str, Union[List[Any], Any]]],
+        rounds: int = 1,
+        auto_await: Optional[bool] = None,
+    ) -> None:
+        """
+        预先执行有代表性的调用，让 V8 在接收真实流量之前完成函数的编译和内联缓存
+
+        新建的 Context 前几次调用明显更慢（函数在第一次调用时才编译，分层编译也要执行几次之后才生效），
+        服务启动后先用样例参数预热可以降低最初一批请求的延迟。结果被丢弃。
+        预热调用和普通调用一样会修改全局状态、计入配额，样例参数应当没有副作用。
+
+        Args:
+            calls: (函数名, 参数) 的列表，参数与 call() 的规则相同
+            rounds: 每个调用执行的次数（默认 1）；更多的轮数让 V8 有机会优化热点函数
+            auto_await: 是否自动等待 Promise（默认 True）
+
+        Raises:
+            ValueError: rounds 为 0
+            TypeError: calls 中的项不是 (name, args)
+            Exception: 某个预热调用失败（包含函数名）
+
+        Example:
+            >>> ctx.compile(open("sdk.js").read())
+            >>> ctx.warmup([("sign", ["sample"]), ("encrypt", [{"id": 1}])], rounds=20)
+        """
+        ...
+
+    @property
+    def poisoned(self) -> Optional[str]:
+        """
+        isolate 遇到致命错误后不再可用的原因（超出 max_heap_mb、执行中的 panic），正常时为 None
+
+        poisoned 之后的执行抛出 ContextPoisoned；auto_recreate=True 时下一次调用之前重建，重建后恢复为 None。
+        """
+        ...
+
+    def collect_garbage(self, full: bool = True) -> None:
+        """
+        强制垃圾回收（用于依赖 FinalizationRegistry 的代码和内存泄漏测试）
+
+        与 gc() 只是向 V8 发送请求不同，返回时 GC 一定已经完成：
+        失去引用的对象已被回收，WeakRef.deref() 返回 undefined，
+        FinalizationRegistry 的清理回调已经执行。
+
+        Args:
+            full: 为 True（默认）时反复执行完整 GC 直到没有更多可回收的对象；
+                  为 False 时只执行一次完整 GC（更快）
+
+        Example:
+            >>> ctx.eval("globalThis.freed = []; new FinalizationRegistry(v => freed.push(v)).register({}, 'obj')")
+            >>> ctx.collect_garbage()
+            >>> ctx.evaluate("freed")
+            ['obj']
+        """
+        ...
+
+    def notify_low_memory(self) -> None:
+        """
+        通知 V8 系统内存不足
+
+        与 gc() 不同，V8 会立即执行完整的垃圾回收并尽可能归还内存（耗时较长），
+        适合在收到系统内存告警或大批量任务结束后调用。
+        """
+        ...
+
+    def idle(self, idle_ms: int = 50) -> None:
+        """
+        告诉 V8 当前处于空闲期，可以利用这段时间回收内存
+
+        适合在两批请求之间调用：V8 会收缩堆并完成待处理的 GC 工作，
+        最多占用 idle_ms 毫秒（提前完成时立即返回）。
+
+        Args:
+            idle_ms: 可用的空闲时间（毫秒，默认 50）
+
+        Example:
+            >>> for batch in batches:
+            ...     results = [ctx.call("sign", [item]) for item in batch]
+            ...     ctx.idle(20)  # 批次之间收缩堆
+        """
+        ...
+
+    def run_microtasks(self) -> None:
+        """
+        执行一次微任务检查点
+
+        运行当前排队的 Promise 回调和 queueMicrotask()（以及它们新排入的微任务），不运行定时器和 I/O 回调。
+        用于 auto_await=False 时一步一步地观察 Promise 的执行顺序。
+        """
+        ...
+
+    def drain_tasks(self, max_ms: Optional[int] = None) -> bool:
+        """
+        运行事件循环，直到没有待处理的任务或经过 max_ms 毫秒
+
+        依次运行到期的定时器、完成的异步操作和微任务；定时器回调中未捕获的错误作为异常抛出。
+
+        Args:
+            max_ms: 最多运行的时间（毫秒），默认 None 一直运行；0 只处理已经就绪的任务
+
+        Returns:
+            True 表示已经没有待处理的任务，False 表示仍有任务（如 setInterval）
+
+        Example:
+            >>> ctx.eval("var tick = 0; setInterval(() => tick++, 10);", auto_await=False)
+            >>> ctx.drain_tasks(max_ms=55)
+            False
+        """
+        ...
+
+    def start_recording(self) -> None:
+        """
+        开始记录脚本消耗的不确定输入
+
+        记录 Math.random()、Date.now() / new Date()、performance.now()、crypto.getRandomValues() /
+        randomUUID()、fetch() 的响应和 register() 注册的 Python 函数的返回值。
+        包装在第一次调用时安装，之前保存的引用不经过记录；重建 isolate 后记录停止。
+
+        Example:
+            >>> ctx.start_recording()
+            >>> token = ctx.call("sign", [data])
+            >>> trace = ctx.stop_recording()
+        """
+        ...
+
+    def stop_recording(self) -> List[List[Any]]:
+        """
+        停止记录，返回 trace
+
+        Returns:
+            [kind, value] 的列表（kind 为 "random" / "now" / "perf" / "bytes" / "uuid" / "fetch" / "call"），
+            可以用 json 保存
+        """
+        ...
+
+    def start_replay(self, trace: List[List[Any]]) -> None:
+        """
+        按 trace 重放不确定输入
+
+        之后脚本消耗的随机数、时间、fetch() 响应和 Python 函数的结果都取自 trace（不发起请求，不调用 Python 函数）。
+        脚本请求的输入与 trace 不一致时在 JS 中抛出 Error（replay diverged）。
+
+        Args:
+            trace: stop_recording() 返回的列表
+
+        Example:
+            >>> ctx.start_replay(json.load(open("bad_token.json")))
+            >>> ctx.call("sign", [data])  # 与记录时相同的 token
+            >>> ctx.stop_replay()
+            0
+        """
+        ...
+
+    def stop_replay(self) -> int:
+        """
+        停止重放
+
+        Returns:
+            trace 中未使用的项数（0 表示脚本消耗了全部记录的输入）
+        """
+        ...
+
+    def get_stats(self, timings: bool = False) -> Any:
+        """
+        获取执行统计信息
+
+        Args:
+            timings: 是否返回耗时分解（默认 False）
+
+        Returns:
+            默认返回 (exec_count,) 执行次数；timings=True 时返回字典：
+            - exec_count: 执行次数
+            - total: 累计耗时，last: 最近一次调用的耗时，均为字典（单位毫秒）：
+              wrap_ms（准备求值）、compile_ms（编译脚本）、execute_ms（同步执行）、
+              event_loop_ms（等待 Promise / 定时器）、total_ms
+
+            evaluate()/call() 的代码在包装函数内通过 eval 编译执行，编译时间计入 execute_ms；
+            compile_ms 来自 compile()/compile_file()/eval() 执行的全局脚本。
+
+        Example:
+            >>> ctx = Context()
+            >>> ctx.compile("function test() { return 1; }")
+            >>> ctx.call("test", [])
+            >>> ctx.get_stats()
+            (2,)
+            >>> stats = ctx.get_stats(timings=True)
+            >>> stats["total"]["compile_ms"], stats["last"]["execute_ms"]
+        """
+        ...
+
+    def get_audit_log(self) -> Dict[str, Any]:
+        """
+        获取最近一次调用的 op 审计日志（需要 audit_ops=True）
+
+        每次 evaluate()/call()/compile() 开始时清空，只包含该次调用中 JS 通过扩展 API 调用的 op。
+
+        Returns:
+            字典：
+            - entries: 记录列表，每项为 {"op", "args", "start_ms", "duration_ms", "status", "worker"}
+              - args: 参数字符串列表（超过 200 个字符的参数被截断）
+              - start_ms: 开始时间（Unix 时间戳，毫秒）
+              - status: "ok" / "error" / "denied"（被 permissions 拒绝）/ "pending"（异步 op 尚未完成）
+              - worker: new Worker() 中的调用为 Worker 的名称，Context 自身的调用为 None
+            - dropped: 超过 10000 条上限未记录的调用次数
+
+        Raises:
+            RuntimeError: 未开启 audit_ops
+
+        Example:
+            >>> ctx = Context(audit_ops=True)
+            >>> ctx.evaluate("md5('x')")
+            >>> [e["op"] for e in ctx.get_audit_log()["entries"]]
+            ['op_md5']
+        """
+        ...
+
+    def get_quota_usage(self) -> Dict[str, Any]:
+        """
+        获取资源配额使用情况（需要设置 quotas）
+
+        Returns:
+            字典：
+            - executions: 已执行次数
+            - cpu_ms: 累计 CPU 时间（毫秒）
+            - console_bytes: 累计 console 输出字节数
+            - exhausted: 配额耗尽原因，未耗尽时为 None
+            - limits: 配额设置（max_executions / max_cpu_ms / max_result_bytes / max_console_bytes）
+
+        Raises:
+            RuntimeError: 未设置 quotas
+
+        Example:
+            >>> ctx = Context(quotas={"max_executions": 2})
+            >>> ctx.evaluate("1"); ctx.evaluate("2")
+            >>> ctx.evaluate("3")  # QuotaExceeded: Execution quota exhausted (max_executions=2)
+            >>> ctx.get_quota_usage()["exhausted"]
+            'Execution quota exhausted (max_executions=2)'
+        """
+        ...
+
+    def history(self) -> List[Dict[str, Any]]:
+        """
+        获取最近的执行记录（需要设置 history）
+
+        Returns:
+            列表（最早的在前），每条为字典：
+            - kind: "evaluate"（evaluate / call / eval(return_value=True)）或 "script"（compile / compile_file / eval）
+            - filename: 脚本名称（compile_file 为文件路径，求值为 "<eval>"）
+            - code: 代码（超过 500 个字符时截断），code_length: 代码字节数
+            - start_ms: 开始时间（Unix 时间戳，毫秒），duration_ms: 耗时
+            - success: 是否成功，error: 失败时的错误信息
+
+        Raises:
+            RuntimeError: 未设置 history
+
+        Example:
+            >>> ctx = Context(history=200)
+            >>> ctx.compile("function sign(x) { return x.length; }")
+            >>> ctx.call("sign", ["abc"])
+            >>> [(e["kind"], e["code"]) for e in ctx.history()][-1]
+            ('evaluate', 'sign("abc")')
+        """
+        ...
+
+    def reset_stats(self) -> None:
+        """
+        重置统计信息（执行次数和累计耗时）
+        """
+        ...
+
+    def get_heap_statistics(self) -> dict[str, int]:
+        """
+        获取 V8 堆内存统计信息
+
+        Returns:
+            包含 V8 堆内存统计的字典:
+            - total_heap_size: V8 堆总大小（字节），包括未使用空间
+            - used_heap_size: 已使用的堆内存（字节）
+            - heap_size_limit: V8 堆大小上限（字节）
+            - total_physical_size: 实际物理内存占用（字节）
+            - malloced_memory: 通过 malloc 分配的内存（字节）
+            - external_memory: 外部对象占用的内存（字节）
+            - number_of_native_contexts: 原生上下文数量
+
+        Example:
+            >>> ctx = Context()
+            >>> ctx.evaluate("const arr = []; for(let i=0; i<10000; i++) arr.push({id:i})")
+            >>> heap = ctx.get_heap_statistics()
+            >>> print(f"Total: {heap['total_heap_size'] / 1024 / 1024:.2f} MB")
+            >>> print(f"Used: {heap['used_heap_size'] / 1024 / 1024:.2f} MB")
+            >>> print(f"Usage: {heap['used_heap_size'] / heap['total_heap_size'] * 100:.1f}%")
+        """
+        ...
+
+    def take_heap_snapshot(self, file_path: str) -> None:
+        """
+        导出 V8 堆快照到文件（Chrome DevTools 格式）
+
+        导出的 .heapsnapshot 文件可以在 Chrome DevTools 中分析:
+        1. 打开 Chrome -> F12 -> Memory 标签
+        2. 点击 "Load" 按钮加载快照文件
+        3. 查看对象分配、内存泄漏、循环引用等
+
+        Args:
+            file_path: 快照文件保存路径（通常以 .heapsnapshot 结尾）
+
+        Raises:
+            Exception: 文件创建失败或写入失败时
+
+        Example:
+            >>> ctx = Context()
+            >>> # 执行一些代码
+            >>> ctx.evaluate("globalThis.data = []; for(let i=0; i<5000; i++) data.push({id:i})")
+            >>> # 导出堆快照
+            >>> ctx.take_heap_snapshot("heap_snapshot.heapsnapshot")
+            >>> # 在 Chrome DevTools 中分析内存使用
+
+            >>> # 内存泄漏检测示例
+            >>> ctx.take_heap_snapshot("before.heapsnapshot")
+            >>> ctx.evaluate("globalThis.leaked = []; for(let i=0; i<10000; i++) leaked.push({data: new Array(100).fill(i)})")
+            >>> ctx.take_heap_snapshot("after.heapsnapshot")
+            >>> # 在 Chrome DevTools 中对比两个快照找出泄漏对象
+        """
+        ...
+
+    def get_hook_data(self) -> Optional[str]:
+        """
+        获取 Hook 拦截的数据
+
+        当 JavaScript 调用 __saveAndTerminate__() 或 $terminate() 时，
+        数据会保存到全局存储中。使用此方法可以在 JS 被终止后获取保存的数据。
+
+        ⚠️ 重要特性：
+        - Hook 数据会一直保留，直到被手动清空或被新的 $terminate() 覆盖
+        - 可以多次调用 get_hook_data() 读取同一个数据
+        - 如果需要执行新的 hook，建议先调用 clear_hook_data() 清空旧数据
+
+        Returns:
+            Optional[str]: 如果有保存的数据则返回 JSON 字符串，否则返回 None
+
+        Example:
+            >>> import never_jscore
+            >>> import json
+            >>>
+            >>> ctx = never_jscore.Context()
+            >>>
+            >>> # 第一次拦截
             >>> try:
             ...     ctx.evaluate('$terminate({ n: 1 });')
             ... except:
             ...     pass
             >>> data1 = ctx.get_hook_data()  # {"n": 1}
             >>>
-            >>> # 清空旧数据，准备下一次 hook
-            >>> ctx.clear_hook_data()
+            >>> # 第二次拦截 - 自动清空了第一次的数据
+            >>> try:
+            ...     ctx.evaluate('$terminate({ n: 2 });')
+            ... except:
+            ...     pass
+            >>> data2 = ctx.get_hook_data()  # {"n": 2} ✅ 正确
+            >>>
+            >>> # 如果需要保留第一次的数据，必须在第二次执行前保存：
+            >>> # saved = ctx.get_hook_data()  # 在下一次 evaluate() 前保存
+        """
+        ...
+
+    def clear_hook_data(self) -> None:
+        """
+        清空保存的 Hook 数据
+
+        建议在执行新的 hook 之前调用此方法，以确保不会读到旧的 hook 数据。
+
+        Example:
+            >>> ctx = never_jscore.Context()
+            >>>
+            >>> # 第一次 hook
+            >>> try:
+            ...     ctx.evaluate('$terminate({ n: 1 });')
+            ... except:
+            ...     pass
+            >>> data1 = ctx.get_hook_data()  # {"n": 1}
+            >>>
+            >>> # 清空旧数据，准备下一次 hook
+            >>> ctx.clear_hook_data()
             >>>
             >>> # 第二次 hook
             >>> try:
@@ -344,6 +1320,1735 @@ class Context:
         """
         ...
 
+    def register(self, name: str, func: Callable[..., Any]) -> None:
+        """
+        把 Python 函数注册为 JS 全局函数
+
+        JS 调用该函数时，参数转换为 Python 对象作为位置参数传入，返回值转换回 JS 值；
+        Python 抛出的异常在 JS 中抛出为 Error。同名时替换之前注册的函数。
+        回调中不能再调用同一个 Context 的 evaluate() / call() 等方法（抛出 RuntimeError）。
+
+        Args:
+            name: JS 全局函数名
+            func: Python 可调用对象
+
+        Raises:
+            TypeError: func 不可调用
+
+        Example:
+            >>> def on_sign(data):
+            ...     print("sign called from", ctx.capture_stack()[0]["function"])
+            ...     return data.upper()
+            >>> ctx.register("pySign", on_sign)
+            >>> ctx.evaluate("function _0x3f2a(x) { return pySign(x); } _0x3f2a('abc')")
+            sign called from _0x3f2a
+            'ABC'
+        """
+        ...
+
+    def capture_stack(self) -> List[Dict[str, Any]]:
+        """
+        获取调用当前 Python 回调的 JS 调用栈
+
+        只能在 register() 注册的函数被 JS 调用期间使用。
+
+        Returns:
+            栈帧列表（栈顶在前），每项为
+            {"function", "script", "line", "column", "is_eval", "is_constructor"}；
+            匿名函数的 function 为 "<anonymous>"
+
+        Raises:
+            RuntimeError: 不在 JS 调用的 Python 回调中
+        """
+        ...
+
+    def load_wasm(
+        self,
+        name: str,
+        wasm_bytes: bytes,
+        imports: Optional[Dict[str, Dict[str, Union[Callable[..., Any], int, float, str]]]] = None,
+        wasi: Union[bool, Dict[str, Any], None] = None,
+    ) -> Dict[str, str]:
+        """
+        加载 WebAssembly 模块，实例的 exports 定义为 JS 全局变量 name
+
+        JS 中通过 name.fn(...) 调用，Python 中通过 ctx.call("name.fn", [...]) 调用。
+        同名时替换之前的模块。jitless 模式或 allow_dynamic_code=False 时 WebAssembly 不可用。
+
+        Args:
+            name: JS 全局变量名
+            wasm_bytes: .wasm 文件内容
+            imports: 导入对象 {模块名: {字段名: 值}}，值可以是 Python 可调用对象（与 register() 相同）、
+                     数字（global 导入）或在全局作用域求值的 JS 表达式字符串（如 "console.log"）
+            wasi: 为 wasi-sdk 编译的模块提供最小 WASI 宿主，True 或
+                  {"args": [...], "env": {...}, "inherit_env": False, "stdin": bytes | str}。
+                  stdout / stderr 按行输出到 console，不能访问文件系统；
+                  inherit_env=True 时传入宿主环境变量（受 env 权限限制）。
+                  命令模块的 _start() 返回退出码，reactor 模块的 _initialize() 在加载时调用
+
+        Returns:
+            导出项 {名称: 类型}，类型为 "function" / "memory" / "table" / "global" / "tag"
+
+        Raises:
+            Exception: 编译或实例化失败（CompileError / LinkError 等）
+            TypeError: imports / wasi 的值类型不支持
+            ValueError: wasi 中有未知的选项
+
+        Example:
+            >>> with open("sign.wasm", "rb") as f:
+            ...     ctx.load_wasm("signer", f.read(), imports={"env": {"log": print}})
+            {'sign': 'function', 'memory': 'memory'}
+            >>> ctx.call("signer.sign", [123])
+        """
+        ...
+
+    def wasm_memory(self, expr: str) -> memoryview:
+        """
+        零拷贝访问 WASM 线性内存
+
+        返回直接指向 V8 内存的可写 memoryview（格式 "B"），Python 读写 WASM 代码使用的缓冲区时不经过 JS 序列化。
+
+        生命周期：
+        - 视图存在期间内存不会被释放，始终可以安全访问
+        - 长度是调用时的内存大小；memory.grow() 之后旧视图可能不再反映当前内存，需要重新调用
+        - Context 销毁时视图被 release()，之后访问抛出 ValueError；
+          仍被切片、np.frombuffer() 等引用的内存保留到进程退出
+
+        Args:
+            expr: 求值为 WebAssembly.Memory / ArrayBuffer / SharedArrayBuffer 的 JS 表达式，如 "calc.memory"
+
+        Returns:
+            可写的 memoryview
+
+        Raises:
+            Exception: 表达式出错或结果不是上述类型
+
+        Example:
+            >>> mem = ctx.wasm_memory("calc.memory")
+            >>> mem[0:5] = b"hello"
+            >>> ctx.call("calc.process", [0, 5])
+            >>> bytes(mem[1024:1056])
+        """
+        ...
+
+    def share_buffer(self, name: str, buffer: "SharedBuffer") -> None:
+        """
+        把 SharedBuffer 定义为全局 SharedArrayBuffer
+
+        JS 与 Python（buffer.view()）以及共享同一个 SharedBuffer 的其他 Context 读写同一块内存。
+        创建时就需要的话使用 Context(shared_buffers={name: buffer})；ContextPool 只支持构造参数。
+        定义过共享内存的 Context 不能 pickle。
+
+        Args:
+            name: 全局变量名
+            buffer: SharedBuffer
+
+        Example:
+            >>> buf = SharedBuffer(16)
+            >>> ctx.share_buffer("shared", buf)
+            >>> ctx.evaluate("Atomics.store(new Int32Array(shared), 0, 42)")
+            >>> int.from_bytes(buf.view()[0:4], "little")
+            42
+        """
+        ...
+
+    def on_slow_script(self, threshold_ms: int, callback: Optional[Callable[[Dict[str, Any]], Any]]) -> None:
+        """
+        设置慢脚本回调：一次调用超过 threshold_ms（墙钟时间）时调用 callback，不终止执行
+
+        回调参数为字典：
+        - elapsed_ms: 已执行时间，threshold_ms: 阈值
+        - kind / filename / code: 正在执行的代码（与 history() 格式相同）
+        - stack: 超过阈值时的 JS 调用栈（与 capture_stack() 格式相同）；
+          JS 在等待定时器 / Promise 时无法获取，调用结束时报告且为 None
+
+        阈值小于 timeout_ms 时回调先于超时终止运行。回调在执行线程上运行，JS 处于暂停状态，
+        回调中不能使用同一个 Context 执行代码。callback=None 清除回调。
+
+        Raises:
+            ValueError: threshold_ms 为 0
+            TypeError: callback 不可调用
+
+        Example:
+            >>> ctx = Context(timeout_ms=5000)
+            >>> ctx.on_slow_script(500, lambda info: print(info["elapsed_ms"], info["stack"][0]["function"]))
+        """
+        ...
+
+    def on_gc(self, callback: Optional[Callable[[Dict[str, Any]], Any]]) -> None:
+        """
+        设置 GC 事件回调：每次 V8 垃圾回收产生一个事件
+
+        事件字典：
+        - type: "scavenge"（新生代）/ "mark_sweep_compact"（完整 GC）/ "minor_mark_sweep" /
+          "incremental_marking" / "process_weak_callbacks"
+        - forced: 是否为强制 GC（如 notify_low_memory()）
+        - duration_ms: GC 耗时
+        - heap_before / heap_after / freed: GC 前后的堆使用量及回收的字节数
+
+        GC 发生在 JS 执行过程中，事件在调用结束后（以及 gc() / notify_low_memory() / idle() 之后）
+        按发生顺序交给回调，回调中可以正常使用该 Context。callback=None 清除回调。
+
+        Raises:
+            TypeError: callback 不可调用
+
+        Example:
+            >>> events = []
+            >>> ctx.on_gc(events.append)
+            >>> ctx.notify_low_memory()
+            >>> events[-1]["type"], events[-1]["forced"]
+            ('mark_sweep_compact', True)
+        """
+        ...
+
+    def on_import_meta(self, callback: Optional[Callable[[Dict[str, str]], Optional[Dict[str, Any]]]]) -> None:
+        """
+        设置 ES 模块的 import.meta 字段
+
+        每次加载模块（compile_file(mode="module")）之前调用 callback({"url": ..., "path": ...})，
+        返回的 dict 合并到该模块的 import.meta 中（可以覆盖 url），返回 None 时保持默认值。
+        字段在模块的其他代码之前设置，值必须可以 JSON 序列化；回调抛出异常时模块加载失败。callback=None 清除回调。
+
+        Raises:
+            TypeError: callback 不可调用
+
+        Example:
+            >>> ctx.on_import_meta(lambda info: {"url": "https://www.example.com/app.mjs", "env": {"MODE": "prod"}})
+            >>> ctx.compile_file("app.mjs")
+        """
+        ...
+
+    def __reduce__(self) -> Any:
+        """
+        pickle / copy.deepcopy 支持
+
+        V8 不能序列化正在运行的 isolate：pickle 时在后台线程按原顺序重放 compile() / compile_file() /
+        eval() 等执行过的全局脚本，生成启动快照，与脚本和构造参数一起保存。
+        恢复时从快照启动（never_jscore 版本或 V8 参数不一致时重新执行脚本）。
+
+        不保存的状态：evaluate() / call() 中的副作用、失败的脚本、回调（on_gc 等）、配额计数和执行历史。
+
+        Raises:
+            TypeError: Context 包含无法重放的状态（register() 注册的函数、load_wasm()、ES 模块、
+                       permissions / fs_roots、自定义快照、import_state()）
+
+        Example:
+            >>> ctx = never_jscore.Context()
+            >>> ctx.compile(open("sign.js").read())
+            >>> data = pickle.dumps(ctx)     # 保存到磁盘或发送给 multiprocessing 的工作进程
+            >>> ctx2 = pickle.loads(data)    # 从快照启动，sign.js 已经加载
+            >>> ctx3 = copy.deepcopy(ctx)
+        """
+        ...
+
+
+class CompileTask:
+    """
+    后台编译任务，由 Context.compile_background() 返回
+
+    只能在创建它的 Context 所在线程上使用。
+    """
+
+    def done(self) -> bool:
+        """后台编译是否已完成（完成后 wait() 只需执行脚本）"""
+        ...
+
+    def wait(self) -> None:
+        """
+        等待编译完成并在 Context 中执行脚本
+
+        可以多次调用，脚本只执行一次；编译或执行失败时每次都抛出相同的异常。
+
+        Raises:
+            Exception: 语法错误或执行错误
+        """
+        ...
+
+
+class PausedFrame:
+    """
+    debug_call() 暂停时传给 on_pause 的调用栈，只在回调期间有效
+
+    行号和列号从 1 开始。
+    """
+
+    @property
+    def reason(self) -> str:
+        """暂停原因："debugger"、"breakpoint"、"step"、"start"（pause_on_start）"""
+        ...
+
+    @property
+    def url(self) -> str:
+        """暂停位置所在脚本的名称"""
+        ...
+
+    @property
+    def line(self) -> int:
+        """暂停位置的行号"""
+        ...
+
+    @property
+    def column(self) -> int:
+        """暂停位置的列号"""
+        ...
+
+    @property
+    def function(self) -> str:
+        """暂停位置所在的函数名（顶层代码为空字符串）"""
+        ...
+
+    @property
+    def call_stack(self) -> List[Dict[str, Any]]:
+        """调用栈，从暂停位置开始，每项为 {"function", "url", "line", "column"}"""
+        ...
+
+    @property
+    def hit_breakpoints(self) -> List[str]:
+        """命中的断点 ID"""
+        ...
+
+    def evaluate(self, expression: str, frame: int = 0) -> Any:
+        """
+        在调用帧上求值表达式，可以访问该帧的局部变量和闭包变量
+
+        Args:
+            expression: JavaScript 表达式
+            frame: 调用帧序号，0 为暂停位置所在的函数
+
+        Returns:
+            表达式的值（无法按值传递的对象返回描述字符串）
+        """
+        ...
+
+    def scopes(self, frame: int = 0) -> List[Dict[str, Any]]:
+        """调用帧的作用域链（不含全局作用域），从内到外每项为 {"type", "name", "variables"}"""
+        ...
+
+    def locals(self, frame: int = 0) -> Dict[str, Any]:
+        """调用帧中可见的变量（内层变量遮蔽外层，不含全局变量）"""
+        ...
+
+
+class PreparedCall:
+    """
+    预编译的调用语句，由 Context.prepare() 返回
+
+    只能在创建它的 Context 所在线程上使用；持有对 Context 的引用。
+    """
+
+    template: str
+    """prepare() 传入的模板"""
+
+    param_count: int
+    """占位符（参数）个数"""
+
+    def __call__(self, *args: Any) -> Any:
+        """
+        以位置参数执行预编译的表达式，返回结果（自动等待 Promise）
+
+        Raises:
+            TypeError: 参数个数与占位符个数不一致
+            Exception: 函数已经通过 Context.handles() 释放（重新 prepare() 同一模板即可）
+        """
+        ...
+
+
+class MapStream(Iterator[Any], AsyncIterator[Any]):
+    """
+    流式的批量调用结果，由 ContextPool.imap() 返回
+
+    可以用 for 或 async for 迭代（只能迭代一次）：ordered=True 时产出每一项的结果，
+    ordered=False 时按完成顺序产出 (索引, 结果)。async for 的每一项在辅助线程中等待，不阻塞事件循环。
+    """
+
+    def __iter__(self) -> "MapStream": ...
+    def __next__(self) -> Any: ...
+    def __aiter__(self) -> "MapStream": ...
+    def __anext__(self) -> Awaitable[Any]: ...
+
+
+class SharedBuffer:
+    """
+    Python 与 JavaScript 共享的内存
+
+    内存不属于任何 isolate：通过 Context(shared_buffers=...) / share_buffer() 定义为全局 SharedArrayBuffer，
+    多个 Context（包括 ContextPool 的每个工作线程）和 Python（view()）读写的是同一块内存，不经过 JSON。
+    读写之间没有同步：JS 之间用 Atomics，与 Python 之间需要调用方自行协调。
+
+    Example:
+        >>> buf = SharedBuffer(1024)
+        >>> pool = ContextPool(init_code, shared_buffers={"shared": buf})
+        >>> buf.view()[0:4] = b"\x01\x02\x03\x04"
+        >>> pool.evaluate("new Uint8Array(shared)[3]")
+        4
+    """
+
+    def __init__(self, size: int) -> None:
+        """
+        分配 size 字节的共享内存（初始为 0）
+
+        Raises:
+            ValueError: size 为 0
+        """
+        ...
+
+    @property
+    def size(self) -> int:
+        """字节数"""
+        ...
+
+    def view(self) -> memoryview:
+        """返回直接读写共享内存的可写 memoryview（格式 "B"），SharedBuffer 被回收时失效"""
+        ...
+
+    def __len__(self) -> int: ...
+
+
+class Realm:
+    """
+    与其他 Realm 共享同一个 isolate 的轻量级执行环境，由 Context.create_realm() 创建
+
+    每个 Realm 有独立的全局作用域和内置对象，创建 / 销毁比 Context 快几个数量级。
+
+    注意：
+    - 只有 ECMAScript 内置对象，没有 btoa / crypto / setTimeout 等扩展 API
+    - 没有事件循环，只支持立即完成（仅依赖微任务）的 Promise
+    - 只能在创建它的线程上使用，执行期间会占用所属 Context 的 isolate
+    """
+
+    closed: bool
+    """Realm 是否已关闭"""
+
+    def compile(self, code: str) -> None:
+        """执行代码并将其加入 Realm 的全局作用域"""
+        ...
+
+    def eval(
+        self,
+        code: str,
+        return_value: bool = False,
+        auto_await: Optional[bool] = None
+    ) -> Any:
+        """执行代码并将其加入全局作用域（与 Context.eval() 一致）"""
+        ...
+
+    def evaluate(self, code: str, auto_await: Optional[bool] = None) -> Any:
+        """执行代码并返回结果（不影响全局作用域）"""
+        ...
+
+    def call(self, name: str, args: Union[List[Any], Any], auto_await: Optional[bool] = None) -> Any:
+        """调用 Realm 中的 JavaScript 函数"""
+        ...
+
+    def close(self) -> None:
+        """
+        销毁 Realm，释放其全局对象（重复调用是安全的）
+
+        之后的调用会抛出异常。V8 会在下一次 GC 时回收 Realm 占用的内存。
+        """
+        ...
+
+    def __enter__(self) -> "Realm": ...
+    def __exit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool: ...
+
+
+class ContextRegistry:
+    """
+    按键（租户 / 目标站点）延迟创建并缓存 Context（或 Realm），按 LRU / 空闲时间释放
+
+    第一次使用某个键时创建实例并执行它的初始化代码，之后复用；与 Context 一样只能在创建它的线程上使用。
+
+    Example:
+        >>> registry = never_jscore.ContextRegistry(max_size=32, ttl=600)
+        >>> registry.define("site_a", js_a, max_heap_mb=64, timeout_ms=500)
+        >>> registry.define("site_b", js_b, realm=True)
+        >>> registry.call("site_a", "sign", ["data"])
+    """
+
+    def __init__(
+        self,
+        max_size: Optional[int] = None,
+        ttl: Optional[float] = None,
+        loader: Optional[Callable[[str], Union[str, Tuple[str, Dict[str, Any]]]]] = None,
+    ) -> None:
+        """
+        Args:
+            max_size: 缓存的实例数上限，超出时释放最久未使用的，默认 None 不限制
+            ttl: 空闲时间（秒），超过后在下一次访问注册表时释放，默认 None 不过期
+            loader: 未 define() 的键第一次使用时调用 loader(key)，返回初始化代码
+                或 (代码, 参数 dict)；默认 None（未定义的键抛出 KeyError）
+        """
+        ...
+
+    def define(self, key: str, code: str, *, preset: Optional[List[str]] = None, realm: bool = False, **options: Any) -> None:
+        """
+        登记键的初始化代码和构造参数（实例在第一次使用时创建）
+
+        重新定义已缓存的键会释放旧实例。
+
+        Args:
+            key: 键
+            code: 初始化代码（与 compile() 相同）
+            preset: 与 compile(preset=...) 相同
+            realm: 为 True 时在共享的宿主 Context 中创建 Realm，不能与其他参数同时使用
+            **options: Context 的构造参数（max_heap_mb、timeout_ms、quotas 等）
+        """
+        ...
+
+    def get(self, key: str) -> Union[Context, Realm]:
+        """获取键对应的实例，第一次使用（或已被释放）时创建并执行初始化代码"""
+        ...
+
+    def call(self, key: str, name: str, args: Union[List[Any], Any], **kwargs: Any) -> Any:
+        """调用键对应实例中的 JavaScript 函数（等同于 get(key).call(name, args, **kwargs)）"""
+        ...
+
+    def evict(self, key: str) -> bool:
+        """释放键对应的实例（定义保留），返回实例是否存在"""
+        ...
+
+    def clear(self) -> None:
+        """释放所有实例（定义保留）"""
+        ...
+
+    def keys(self) -> List[str]:
+        """当前缓存了实例的键（最近使用的在前）"""
+        ...
+
+    def stats(self) -> Dict[str, int]:
+        """统计信息：cached（缓存的实例数）、defined（定义的键数）、evictions（已释放的实例数）"""
+        ...
+
+    def __len__(self) -> int: ...
+    def __contains__(self, key: str) -> bool: ...
+
+
+class ShadowRealm:
+    """
+    Context 中的一个 ShadowRealm，由 Context.create_shadow_realm() 创建
+
+    等同于 JS 中的 new ShadowRealm()：独立的全局作用域和内置对象，
+    只有 ECMAScript 内置对象，没有扩展 API 和事件循环。
+
+    注意：
+    - 边界两侧只能传递原始值和函数，evaluate() 返回对象时抛出 TypeError
+    - 只能在创建它的线程上使用
+    """
+
+    closed: bool
+    """ShadowRealm 是否已关闭"""
+
+    def evaluate(self, code: str) -> Any:
+        """
+        在 ShadowRealm 中执行代码并返回结果（ShadowRealm.prototype.evaluate）
+
+        代码作为全局脚本执行，var / function 声明保留在 ShadowRealm 中。
+        函数返回其字符串形式，对象需要在 ShadowRealm 内 JSON.stringify。
+        """
+        ...
+
+    def close(self) -> None:
+        """释放 ShadowRealm（重复调用是安全的），之后的调用会抛出异常"""
+        ...
+
+    def __enter__(self) -> "ShadowRealm": ...
+    def __exit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool: ...
+
+
+class AbortToken:
+    """
+    异步调用的中止令牌（abort=...）
+
+    传给 ThreadedContext / ContextPool 的 *_async() 或 aeval() / acall()；abort() 后使用此令牌的调用
+    全部终止（排队中的不再执行），以 asyncio.CancelledError 结束，Context 可以继续使用。
+    不传令牌时，等待的任务被取消（task.cancel()、asyncio.wait_for 超时）同样会终止 JS。
+
+    Example:
+        >>> token = never_jscore.AbortToken()
+        >>> task = asyncio.ensure_future(ctx.call_async("crack", [data], abort=token))
+        >>> token.abort()
+    """
+
+    def __init__(self) -> None: ...
+
+    def abort(self) -> None:
+        """中止使用此令牌的所有调用（可以在任意线程调用，重复调用无效果）"""
+        ...
+
+    @property
+    def aborted(self) -> bool:
+        """是否已中止"""
+        ...
+
+
+class Handle:
+    """
+    Context 持有的一个长期 V8 对象，由 Context.handles() 返回
+
+    release() 释放对象：realm / shadow_realm 与 close() 相同；prepared 删除 prepare() 定义的函数，
+    同一模板的所有 PreparedCall 不能再调用；call_target 清除 call() 缓存的函数，下一次调用重新解析。
+    """
+
+    @property
+    def id(self) -> int:
+        """句柄编号（进程内唯一）"""
+        ...
+
+    @property
+    def kind(self) -> str:
+        """对象类型：realm / shadow_realm / prepared / call_target"""
+        ...
+
+    @property
+    def name(self) -> str:
+        """realm / shadow_realm 为 "<realm>" / "<shadow_realm>"，prepared 为模板，call_target 为函数名"""
+        ...
+
+    @property
+    def released(self) -> bool:
+        """是否已经释放（包括 close() 和对象销毁）"""
+        ...
+
+    def release(self) -> None:
+        """
+        释放对象（重复调用是安全的）
+
+        Raises:
+            RuntimeError: Realm / ShadowRealm 正在执行（在它调用的 Python 函数中释放）
+        """
+        ...
+
+
+class ContextPool:
+    """
+    多线程 Context 池
+
+    创建 size 个工作线程，每个线程持有一个独立的 Context 并执行 init_code。
+    池对象可以在任意 Python 线程之间共享（不受 Context unsendable 的限制），
+    调用时释放 GIL 并分派给空闲的工作线程，适合 Flask/FastAPI 等多线程服务。
+
+    ⚠️ 每个工作线程的 Context 相互独立：全局变量的修改只对执行该任务的线程可见，
+    适合无状态的函数调用（签名、加密等）。
+
+    Example:
+        >>> pool = ContextPool(js_code, size=4)
+        >>>
+        >>> @app.route("/sign")
+        ... def sign():
+        ...     return pool.call("sign", [request.args["data"]])
+    """
+
+    def __init__(
+        self,
+        init_code: Optional[str] = None,
+        size: int = 4,
+        enable_extensions: bool = True,
+        enable_logging: bool = False,
+        random_seed: Optional[int] = None,
+        snapshot: Optional[bytes] = None,
+        code_cache_dir: Optional[Union[str, os.PathLike]] = None,
+        initial_heap_mb: Optional[int] = None,
+        max_heap_mb: Optional[int] = None,
+        shared_memory: bool = False,
+        shared_buffers: Optional[Dict[str, "SharedBuffer"]] = None,
+        auto_recreate: bool = False,
+        max_queue: Optional[int] = None,
+        on_full: str = "block",
+        undefined_as: Optional[str] = None,
+        permissions: Union[Dict[str, Union[bool, str, List[str]]], str, None] = None,
+    ) -> None:
+        """
+        创建 Context 池
+
+        排队的任务按优先级（priority，高的先执行）调度，同一优先级内各租户（tenant）的任务轮流执行。
+
+        Args:
+            init_code: 每个 Context 创建后执行的初始化代码（通常是要加载的 JS 库）
+            size: 工作线程（isolate）数量，默认 4
+            enable_extensions / enable_logging / random_seed / snapshot / code_cache_dir / initial_heap_mb / max_heap_mb:
+                与 Context 构造函数含义相同，应用于池中每个 Context
+            shared_memory / shared_buffers: 与 Context 构造函数含义相同；
+                shared_buffers 中的 SharedBuffer 在每个工作线程中都是同一块内存，在 init_code 之前定义
+            auto_recreate: 工作线程的 Context 遇到致命错误（超出 max_heap_mb 等）后，在当前任务结束后自动重建，
+                默认 False（之后分配到该工作线程的任务抛出异常）
+            max_queue: 排队（尚未开始执行）的任务数上限，默认 None 不限制
+            on_full: 队列满时的处理方式，"block"（默认，等待空位）或 "raise"（抛出 PoolFull）；
+                call_async() / evaluate_async() 不能阻塞事件循环，队列满时总是抛出 PoolFull
+            undefined_as: 结果中 undefined 的转换策略（"none" / "sentinel" / "omit"），与 Context 构造函数含义相同
+            permissions: 网络 / 文件 / 环境变量权限（字典或 "all"），与 Context 构造函数含义相同，默认拒绝网络和文件读写
+
+        Raises:
+            ValueError: size 或 max_queue 为 0，on_full、undefined_as 或 permissions 无效，堆大小参数不合法，或快照与 enable_extensions 不一致
+            Exception: 初始化代码执行失败
+        """
+        ...
+
+    @property
+    def size(self) -> int:
+        """工作线程数量"""
+        ...
+
+    @property
+    def queued(self) -> int:
+        """排队中（尚未开始执行）的任务数"""
+        ...
+
+    def call(
+        self,
+        name: str,
+        args: Union[List[Any], Any],
+        auto_await: Optional[bool] = None,
+        result_format: Optional[str] = None,
+        priority: int = 0,
+        tenant: Optional[str] = None,
+    ) -> Any:
+        """
+        调用 JavaScript 函数（由空闲的工作线程执行）
+
+        Args:
+            name: 函数名称
+            args: 参数列表
+            auto_await: 是否自动等待 Promise（默认 True）
+            result_format: 结果的格式："python"（默认）/ "json_str" / "orjson"，参见 Context.evaluate()
+            priority: 优先级（默认 0），排队时优先级高的任务先执行
+            tenant: 租户（默认 None），同一优先级内各租户的任务轮流执行
+
+        Returns:
+            函数返回值，自动转换为 Python 对象
+
+        Raises:
+            PoolFull: 队列已满且 on_full="raise"
+        """
+        ...
+
+    def evaluate(
+        self,
+        code: str,
+        auto_await: Optional[bool] = None,
+        result_format: Optional[str] = None,
+        priority: int = 0,
+        tenant: Optional[str] = None,
+    ) -> Any:
+        """
+        在某个工作线程中求值（不影响全局作用域）
+
+        Args:
+            code: JavaScript 代码
+            auto_await: 是否自动等待 Promise（默认 True）
+            result_format: 结果的格式，同 call()
+            priority / tenant: 调度的优先级和租户，同 call()
+
+        Returns:
+            表达式的值
+        """
+        ...
+
+    def map(
+        self,
+        name: str,
+        items: Iterable[Any],
+        workers: Optional[int] = None,
+        auto_await: Optional[bool] = None,
+        result_format: Optional[str] = None,
+        priority: int = 0,
+        tenant: Optional[str] = None,
+    ) -> List[Any]:
+        """
+        并行批量调用：对 items 中的每一项调用一次 name 函数
+
+        任务分散到多个工作线程并行执行（释放 GIL），结果按 items 的顺序返回。
+
+        Args:
+            name: 函数名称
+            items: 参数的可迭代对象；每一项与 call() 的 args 规则相同（list 展开为多个参数）
+            workers: 最多同时使用的工作线程数（默认使用整个池）
+            auto_await: 是否自动等待 Promise（默认 True）
+            result_format: 每一项结果的格式，同 call()
+            priority / tenant: 每一项的优先级和租户，同 call()
+
+        Returns:
+            每一项的返回值
+
+        Raises:
+            Exception: 任意一项调用失败时抛出（包含失败项的索引）
+            PoolFull: 队列已满且 on_full="raise"（等待已提交的项完成后抛出）
+
+        Example:
+            >>> pool = ContextPool(js_code, size=8)
+            >>> tokens = pool.map("sign", [[uid, ts] for uid in user_ids])
+        """
+        ...
+
+    def imap(
+        self,
+        name: str,
+        items: Iterable[Any],
+        workers: Optional[int] = None,
+        ordered: bool = True,
+        auto_await: Optional[bool] = None,
+        result_format: Optional[str] = None,
+        priority: int = 0,
+        tenant: Optional[str] = None,
+    ) -> "MapStream":
+        """
+        map() 的流式版本：返回 MapStream，每一项完成后就可以取回结果
+
+        可以用 for 或 async for 迭代；Python 处理已完成的结果时，工作线程继续执行后面的项，
+        同时最多 2 × workers 个项在执行、排队或等待取走。
+
+        Args:
+            name / items / workers / auto_await / result_format / priority / tenant: 同 map()
+            ordered: True（默认）按 items 的顺序产出结果；False 按完成顺序产出 (索引, 结果)
+
+        Returns:
+            MapStream
+
+        Raises:
+            Exception: 迭代到失败的项时抛出（包含索引），之后迭代结束
+            PoolFull: 队列已满且 on_full="raise"（产出已提交的项之后抛出）
+
+        Example:
+            >>> for token in pool.imap("sign", [[uid, ts] for uid in user_ids]):
+            ...     db.insert(token)
+            >>> async for index, token in pool.imap("sign", items, ordered=False):
+            ...     await queue.put((index, token))
+        """
+        ...
+
+    def call_async(
+        self,
+        name: str,
+        args: Union[List[Any], Any],
+        auto_await: Optional[bool] = None,
+        priority: int = 0,
+        tenant: Optional[str] = None,
+        abort: Optional[AbortToken] = None,
+    ) -> Awaitable[Any]:
+        """
+        call() 的 asyncio 版本
+
+        立即返回 asyncio.Future，由空闲的工作线程执行，不阻塞事件循环。
+        必须在运行中的事件循环内调用。priority / tenant 同 call()；队列已满时不等待，直接抛出 PoolFull。
+        等待的任务被取消或 abort 令牌被中止时终止工作线程上的 JS（排队中的任务不再执行），抛出 asyncio.CancelledError。
+
+        Example:
+            >>> @app.get("/sign")
+            ... async def sign(data: str):
+            ...     return await pool.call_async("sign", [data])
+        """
+        ...
+
+    def evaluate_async(
+        self,
+        code: str,
+        auto_await: Optional[bool] = None,
+        priority: int = 0,
+        tenant: Optional[str] = None,
+        abort: Optional[AbortToken] = None,
+    ) -> Awaitable[Any]:
+        """evaluate() 的 asyncio 版本（priority / tenant / abort 同 call_async()）"""
+        ...
+
+    def warmup(
+        self,
+        calls: Iterable[Tuple[str, Union[List[Any], Any]]],
+        rounds: int = 1,
+        auto_await: Optional[bool] = None,
+    ) -> None:
+        """
+        在每个工作线程上预先执行有代表性的调用（参数与 Context.warmup() 相同）
+
+        预热任务排在所有排队的任务之前，不受 max_queue 限制；每个工作线程各执行一次，
+        正在执行任务的工作线程完成当前任务后才开始预热，warmup() 等待所有工作线程预热完成后返回。
+
+        Example:
+            >>> pool = ContextPool(js_code, size=8)
+            >>> pool.warmup([("sign", ["sample"])], rounds=20)
+        """
+        ...
+
+    def close(self) -> None:
+        """
+        关闭池：等待正在执行的任务完成后销毁所有工作线程
+
+        关闭后再调用 call()/evaluate() 会抛出异常。重复调用是安全的。
+        """
+        ...
+
+    def __enter__(self) -> "ContextPool": ...
+    def __exit__(self, exc_type: Any, exc_value: Any, traceback: Any) -> bool: ...
+
+
+class SnapshotPool:
+    """
+    从预热快照创建全新 Context 的工厂
+
+    创建时执行一次 init_code 并生成启动快照；之后每次 checkout() 得到一个
+    独立的全新 Context，其中已经加载好 init_code，不会读到其他请求留下的任何状态。
+    SnapshotPool 对象可以在线程之间共享，取出的 Context 只能在取出它的线程使用。
+
+    Example:
+        >>> pool = SnapshotPool(open("sign.js").read())
+        >>> pool.call("sign", ["data"])        # 每次调用一个全新的 Context
+        >>> with pool.checkout() as ctx:       # 需要多次调用时手动取出
+        ...     ctx.call("init", [config])
+        ...     ctx.call("sign", ["data"])
+    """
+
+    snapshot_size: int
+    """快照大小（字节）"""
+
+    checkouts: int
+    """累计取出的 Context 数量（包括 call() / evaluate() 内部创建的）"""
+
+    def __init__(
+        self,
+        init_code: str,
+        enable_extensions: bool = True,
+        enable_logging: bool = False,
+        random_seed: Optional[int] = None,
+        code_cache_dir: Optional[str] = None,
+        initial_heap_mb: Optional[int] = None,
+        max_heap_mb: Optional[int] = None,
+        freeze_intrinsics: bool = False,
+        permissions: Union[Dict[str, Union[bool, str, List[str]]], str, None] = None,
+    ) -> None:
+        """
+        执行初始化代码并生成快照
+
+        Args:
+            init_code: 初始化代码（通常是要加载的 JS 库），执行后的堆会被做成快照
+            freeze_intrinsics: 是否在初始化代码执行后冻结内置对象（见 Context.freeze_intrinsics()），
+                冻结状态保存在快照中，每个取出的 Context 都无法修改内置原型
+            enable_extensions / enable_logging / random_seed / code_cache_dir / initial_heap_mb / max_heap_mb / permissions:
+                与 Context 构造函数含义相同，应用于每个取出的 Context
+
+        Raises:
+            ValueError: permissions 无效
+            Exception: 初始化代码执行失败
+        """
+        ...
+
+    def checkout(self) -> Context:
+        """取出一个从快照启动的全新 Context（无需归还）"""
+        ...
+
+    def call(self, name: str, args: Union[List[Any], Any], auto_await: Optional[bool] = None) -> Any:
+        """在全新的 Context 中调用函数，调用后销毁 Context"""
+        ...
+
+    def evaluate(self, code: str, auto_await: Optional[bool] = None) -> Any:
+        """在全新的 Context 中求值，求值后销毁 Context"""
+        ...
+
+
+class ThreadedContext:
+    """
+    线程安全的 JavaScript 执行上下文
+
+    API 与 Context 相同，但可以在多个 Python 线程之间传递和共享，
+    不会出现 "unsendable" 错误。内部由一个专用线程持有 V8 isolate，
+    所有调用按顺序串行执行，等待期间释放 GIL。
+
+    Example:
+        >>> ctx = ThreadedContext()
+        >>> ctx.compile("function add(a, b) { return a + b; }")
+        >>>
+        >>> # 任意线程都可以使用同一个 ctx
+        >>> with ThreadPoolExecutor(4) as executor:
+        ...     results = list(executor.map(lambda i: ctx.call("add", [i, 1]), range(100)))
+    """
+
+    def __init__(
+        self,
+        enable_extensions: bool = True,
+        enable_logging: bool = False,
+        random_seed: Optional[int] = None,
+        snapshot: Optional[bytes] = None,
+        code_cache_dir: Optional[Union[str, os.PathLike]] = None,
+        initial_heap_mb: Optional[int] = None,
+        max_heap_mb: Optional[int] = None,
+        permissions: Union[Dict[str, Union[bool, str, List[str]]], str, None] = None,
+        allow_dynamic_code: bool = True,
+        timeout_ms: Optional[int] = None,
+        cpu_limit_ms: Optional[int] = None,
+        stack_size_kb: Optional[int] = None,
+        audit_ops: bool = False,
+        fs_roots: Union[str, List[str], Dict[str, str], None] = None,
+        env: Optional[Dict[str, str]] = None,
+        no_ops: bool = False,
+        quotas: Optional[Dict[str, int]] = None,
+        history: Optional[int] = None,
+        shared_memory: bool = False,
+        shared_buffers: Optional[Dict[str, "SharedBuffer"]] = None,
+        weak_refs: bool = True,
+        auto_recreate: bool = False,
+        undefined_as: Optional[str] = None,
+    ) -> None:
+        """参数与 Context 构造函数相同（auto_recreate 在遇到致命错误的任务结束后重建）"""
+        ...
+
+    def compile(
+        self,
+        code: str,
+        preset: Optional[List[str]] = None,
+        recycle_after: Optional[Dict[str, int]] = None,
+        result_filter: Optional[str] = None,
+    ) -> None:
+        """编译 JavaScript 代码并加入全局作用域（preset / recycle_after / result_filter 与 Context.compile() 相同）"""
+        ...
+
+    def compile_file(
+        self,
+        path: Union[str, os.PathLike],
+        encoding: Optional[str] = None,
+        mode: Optional[str] = None,
+        watch: bool = False,
+    ) -> None:
+        """从文件编译 JavaScript 代码并加入全局作用域（encoding / mode / watch 与 Context.compile_file() 相同）"""
+        ...
+
+    def eval(
+        self,
+        code: str,
+        return_value: bool = False,
+        auto_await: Optional[bool] = None,
+        capture_output: bool = False,
+        event_loop: Optional[Dict[str, Any]] = None,
+    ) -> Any:
+        """执行代码并将其加入全局作用域；capture_output=True 时返回 (结果, console 输出)，参见 Context.eval()"""
+        ...
+
+    def evaluate(
+        self,
+        code: str,
+        auto_await: Optional[bool] = None,
+        binary: bool = False,
+        stream_to: Optional[Any] = None,
+        event_loop: Optional[Dict[str, Any]] = None,
+        result_format: Optional[str] = None,
+    ) -> Any:
+        """执行代码并返回结果（不影响全局作用域）；binary=True 时字符串返回为 bytes，stream_to / event_loop / result_format 参见 Context.evaluate()"""
+        ...
+
+    def repl_eval(self, line: str, auto_await: Optional[bool] = None) -> Any:
+        """像浏览器控制台一样执行一行输入，参见 Context.repl_eval()"""
+        ...
+
+    def call(
+        self,
+        name: str,
+        args: Union[List[Any], Any],
+        auto_await: Optional[bool] = None,
+        binary: bool = False,
+        stream_to: Optional[Any] = None,
+        event_loop: Optional[Dict[str, Any]] = None,
+        trace_id: Optional[str] = None,
+        result_format: Optional[str] = None,
+    ) -> Any:
+        """调用 JavaScript 函数；binary=True 时字符串返回为 bytes，stream_to / event_loop / result_format 参见 Context.evaluate()，trace_id 参见 Context.call()"""
+        ...
+
+    def render_call(self, template: str, **values: Any) -> Any:
+        """用命名占位符拼接调用代码并求值，参见 Context.render_call()"""
+        ...
+
+    def eval_async(
+        self,
+        code: str,
+        return_value: bool = False,
+        auto_await: Optional[bool] = None,
+        abort: Optional[AbortToken] = None,
+    ) -> Awaitable[Any]:
+        """
+        eval() 的 asyncio 版本
+
+        立即返回 asyncio.Future，JS 在专用线程上执行，不阻塞事件循环。
+        必须在运行中的事件循环内调用。
+
+        Example:
+            >>> result = await ctx.eval_async("1 + 2", return_value=True)
+        """
+        ...
+
+    def evaluate_async(
+        self, code: str, auto_await: Optional[bool] = None, abort: Optional[AbortToken] = None
+    ) -> Awaitable[Any]:
+        """evaluate() 的 asyncio 版本"""
+        ...
+
+    def call_async(
+        self,
+        name: str,
+        args: Union[List[Any], Any],
+        auto_await: Optional[bool] = None,
+        abort: Optional[AbortToken] = None,
+    ) -> Awaitable[Any]:
+        """
+        call() 的 asyncio 版本
+
+        等待的任务被取消（或传入的 AbortToken 被中止）时终止正在执行的 JS，
+        以 asyncio.CancelledError 结束，ThreadedContext 可以继续使用。
+
+        Example:
+            >>> async def handler(data):
+            ...     return await asyncio.wait_for(ctx.call_async("sign", [data]), timeout=1.0)
+        """
+        ...
+
+    def start_event_loop(self) -> None:
+        """
+        启动后台事件循环
+
+        默认情况下定时器和 Promise 只在调用期间推进。启动后台事件循环后：
+        - 专用线程在两次调用之间持续推进事件循环，setInterval 心跳等会按时触发
+        - call()/evaluate() 在结果就绪时立即返回，不再等待所有定时器完成
+
+        定时器回调写入全局变量的结果可以在之后用 evaluate() 读取。
+
+        Example:
+            >>> ctx = ThreadedContext()
+            >>> ctx.start_event_loop()
+            >>> ctx.eval("var beats = 0; setInterval(() => beats++, 100);")
+            >>> time.sleep(1)
+            >>> ctx.evaluate("beats")  # ~10
+            >>> ctx.stop_event_loop()
+        """
+        ...
+
+    def stop_event_loop(self) -> None:
+        """停止后台事件循环（未完成的定时器会在下一次调用期间继续推进）"""
+        ...
+
+    @property
+    def event_loop_running(self) -> bool:
+        """后台事件循环是否正在运行"""
+        ...
+
+    def take_event_loop_errors(self) -> List[str]:
+        """取出后台事件循环中发生的错误（取出后清空）"""
+        ...
+
+    def gc(self) -> None:
+        """请求垃圾回收"""
+        ...
+
+    def freeze_intrinsics(self) -> None:
+        """冻结内置对象（与 Context.freeze_intrinsics() 相同）"""
+        ...
+
+    def missing_globals(self, code: str) -> List[str]:
+        """列出代码用到但当前 Context 中不存在的全局变量（与 Context.missing_globals() 相同）"""
+        ...
+
+    def export_state(self, keys: List[str]) -> bytes:
+        """把选定的全局变量序列化为 bytes（与 Context.export_state() 相同）"""
+        ...
+
+    def import_state(self, state: bytes) -> List[str]:
+        """把 export_state() 导出的数据恢复为全局变量（与 Context.import_state() 相同）"""
+        ...
+
+    def snapshot_globals(self) -> Dict[str, str]:
+        """记录全局对象每个属性的指纹（与 Context.snapshot_globals() 相同）"""
+        ...
+
+    def diff_globals(self, prev: Dict[str, str]) -> Dict[str, List[str]]:
+        """与 snapshot_globals() 的快照比较（与 Context.diff_globals() 相同）"""
+        ...
+
+    def warmup(
+        self,
+        calls: Iterable[Tuple[str, Union[List[Any], Any]]],
+        rounds: int = 1,
+        auto_await: Optional[bool] = None,
+    ) -> None:
+        """预先执行有代表性的调用（与 Context.warmup() 相同）"""
+        ...
+
+    @property
+    def poisoned(self) -> Optional[str]:
+        """isolate 遇到致命错误后不再可用的原因（与 Context.poisoned 相同）"""
+        ...
+
+    def collect_garbage(self, full: bool = True) -> None:
+        """强制垃圾回收并执行 FinalizationRegistry 清理回调（与 Context.collect_garbage() 相同）"""
+        ...
+
+    def notify_low_memory(self) -> None:
+        """通知 V8 系统内存不足（与 Context.notify_low_memory() 相同）"""
+        ...
+
+    def idle(self, idle_ms: int = 50) -> None:
+        """告诉 V8 当前处于空闲期（与 Context.idle() 相同）"""
+        ...
+
+    def get_stats(self, timings: bool = False) -> Any:
+        """获取执行统计信息（参数和返回值与 Context.get_stats() 相同）"""
+        ...
+
+    def get_audit_log(self) -> Dict[str, Any]:
+        """获取最近一次调用的 op 审计日志（返回值与 Context.get_audit_log() 相同）"""
+        ...
+
+    def get_quota_usage(self) -> Dict[str, Any]:
+        """获取资源配额使用情况（返回值与 Context.get_quota_usage() 相同）"""
+        ...
+
+    def history(self) -> List[Dict[str, Any]]:
+        """获取最近的执行记录（返回值与 Context.history() 相同）"""
+        ...
+
+    def reset_stats(self) -> None:
+        """重置统计信息"""
+        ...
+
+    def get_heap_statistics(self) -> dict[str, int]:
+        """获取 V8 堆内存统计信息"""
+        ...
+
+    def take_heap_snapshot(self, file_path: str) -> None:
+        """导出 V8 堆快照（Chrome DevTools 格式）"""
+        ...
+
+    def get_hook_data(self) -> Optional[str]:
+        """获取 $terminate() 保存的 Hook 数据"""
+        ...
+
+    def clear_hook_data(self) -> None:
+        """清空保存的 Hook 数据"""
+        ...
+
+    def register(self, name: str, func: Callable[..., Any]) -> None:
+        """
+        把 Python 函数注册为 JS 全局函数（与 Context.register() 相同，函数在专用线程上被调用）
+
+        回调中再调用同一个 ThreadedContext 会抛出 RuntimeError。
+        """
+        ...
+
+    def capture_stack(self) -> List[Dict[str, Any]]:
+        """获取调用当前 Python 回调的 JS 调用栈（与 Context.capture_stack() 相同）"""
+        ...
+
+    def load_wasm(
+        self,
+        name: str,
+        wasm_bytes: bytes,
+        imports: Optional[Dict[str, Dict[str, Union[Callable[..., Any], int, float, str]]]] = None,
+        wasi: Union[bool, Dict[str, Any], None] = None,
+    ) -> Dict[str, str]:
+        """加载 WebAssembly 模块（参数和返回值与 Context.load_wasm() 相同）"""
+        ...
+
+    def wasm_memory(self, expr: str) -> memoryview:
+        """零拷贝访问 WASM 线性内存（与 Context.wasm_memory() 相同，close() 时视图失效；不与 JS 执行同步）"""
+        ...
+
+    def share_buffer(self, name: str, buffer: "SharedBuffer") -> None:
+        """把 SharedBuffer 定义为全局 SharedArrayBuffer（与 Context.share_buffer() 相同）"""
+        ...
+
+    def on_slow_script(self, threshold_ms: int, callback: Optional[Callable[[Dict[str, Any]], Any]]) -> None:
+        """设置慢脚本回调（参数与 Context.on_slow_script() 相同，回调在工作线程上运行）"""
+        ...
+
+    def on_gc(self, callback: Optional[Callable[[Dict[str, Any]], Any]]) -> None:
+        """设置 GC 事件回调（参数与 Context.on_gc() 相同，回调在工作线程上运行）"""
+        ...
+
+    def close(self) -> None:
+        """
+        关闭上下文：等待正在执行的任务完成后销毁专用线程
+
+        关闭后再调用任何方法都会抛出异常。重复调用是安全的。
+        """
+        ...
+
+    def __enter__(self) -> "ThreadedContext": ...
+    def __exit__(self, exc_type: Any, exc_value: Any, traceback: Any) -> bool: ...
+
+
+def eval(code: str, auto_await: Optional[bool] = None, isolated: bool = False) -> Any:
+    """
+    在当前线程的隐式 Context 中求值
+
+    不需要先创建 Context，同一线程的多次调用共享同一个 Context（全局变量会保留）。
+    不能接受调用之间互相影响时使用 isolated=True。
+
+    Args:
+        code: JavaScript 代码
+        auto_await: 是否自动等待 Promise（默认 True）
+        isolated: 是否在全新的临时 Context 中执行（默认 False）。
+                  临时 Context 从快照启动（包含 configure_eval 的 init_code），执行完即销毁
+
+    Returns:
+        执行结果，自动转换为 Python 对象
+
+    Example:
+        >>> import never_jscore
+        >>> never_jscore.eval("1 + 2")
+        3
+        >>> never_jscore.eval("btoa('hello')")
+        'aGVsbG8='
+    """
+    ...
+
+
+def aeval(code: str, auto_await: Optional[bool] = None, abort: Optional[AbortToken] = None) -> Awaitable[Any]:
+    """
+    eval() 的 asyncio 版本
+
+    不需要先创建 Context：代码在进程内共享的后台线程中执行，等待期间不阻塞事件循环。
+    后台 Context 同样使用 configure_eval() 的配置，但与 eval() 的 Context 不是同一个，
+    全局变量不互通；多次 aeval() / acall() 之间共享同一个 Context，按提交顺序执行。
+
+    Args:
+        code: JavaScript 代码
+        auto_await: 是否自动等待 Promise（默认 True）
+        abort: AbortToken，中止时终止执行（等待的任务被取消时同样终止）
+
+    Returns:
+        asyncio.Future，结果自动转换为 Python 对象
+
+    Example:
+        >>> async def main():
+        ...     await never_jscore.aeval("function sign(x) { return btoa(x); }")
+        ...     return await never_jscore.acall("sign", ["data"])
+        >>> asyncio.run(main())
+        'ZGF0YQ=='
+    """
+    ...
+
+
+def acall(
+    name: str, args: List[Any], auto_await: Optional[bool] = None, abort: Optional[AbortToken] = None
+) -> Awaitable[Any]:
+    """
+    在 aeval() 使用的后台 Context 中调用函数（asyncio 版本）
+
+    Args:
+        name: 函数名（可以是任意可调用表达式，如 obj.method）
+        args: 参数列表
+        auto_await: 是否自动等待 Promise（默认 True）
+        abort: AbortToken，同 aeval()
+
+    Returns:
+        asyncio.Future，结果自动转换为 Python 对象
+    """
+    ...
+
+
+def clear_eval_cache() -> None:
+    """
+    丢弃隐式 Context，清除之前 eval() / aeval() 留下的全局状态
+
+    当前线程的 Context 和 aeval() 的后台 Context 立即释放（等待已提交的 aeval() 完成）；
+    其他线程的 Context 在下一次使用时重新创建。
+    """
+    ...
+
+
+def configure_eval(
+    preset: Optional[str] = None,
+    init_code: Optional[str] = None,
+    user_agent: Optional[str] = None,
+    url: Optional[str] = None,
+) -> None:
+    """
+    配置隐式 Context
+
+    修改后所有线程的隐式 Context（包括 aeval() 的后台 Context）在下一次使用时按新配置重新创建。
+    未传入的参数保持不变。
+
+    Args:
+        preset: 预设环境
+                - "default": 启用全部扩展（与 Context() 一致）
+                - "browser": 与 default 相同，并按 user_agent / url 设置 navigator / location / document
+                - "pure": 纯净 V8 环境（与 Context(enable_extensions=False) 一致）
+        init_code: 创建 Context 后执行的初始化代码（如要加载的 JS 库），传入空字符串清除
+        user_agent: browser 预设的 navigator.userAgent，传入空字符串清除
+        url: browser 预设的页面地址（location 和 document.URL / domain），传入空字符串清除
+
+    Raises:
+        ValueError: 未知的预设名称，或在 browser 以外的预设下设置 user_agent / url
+
+    Example:
+        >>> never_jscore.configure_eval(init_code="function sign(x) { return md5(x); }")
+        >>> never_jscore.eval("sign('data')")
+        >>> never_jscore.configure_eval(preset="browser", user_agent="Mozilla/5.0 ...", url="https://example.com/login")
+        >>> never_jscore.eval("location.hostname")
+        'example.com'
+    """
+    ...
+
+
+def get_eval_context() -> Context:
+    """
+    获取当前线程的隐式 Context
+
+    返回的是 eval() 使用的同一个 Context，可以用来检查状态、调用函数、查看堆统计等。
+    不存在时会创建。
+
+    Example:
+        >>> never_jscore.eval("globalThis.counter = 1")
+        >>> ctx = never_jscore.get_eval_context()
+        >>> ctx.evaluate("counter")
+        1
+    """
+    ...
+
+
+def init(
+    tokio_threads: Optional[int] = None,
+    current_thread: Optional[bool] = None,
+    single_threaded_platform: Optional[bool] = None,
+    jitless: Optional[bool] = None,
+    stack_size_kb: Optional[int] = None,
+    wasm_features: Optional[Dict[str, bool]] = None,
+    v8_flags: Optional[List[str]] = None,
+    icu: Optional[bool] = None,
+    snapshot: Optional[Union[bool, bytes]] = None,
+) -> None:
+    """
+    全局初始化配置（必须在第一次创建 Context 之前调用）
+
+    用于 uWSGI、celery、容器等对线程数敏感的环境，控制 never_jscore 和 V8 创建的后台线程。
+    不调用时使用默认配置。
+
+    Args:
+        tokio_threads: 每个线程的 tokio runtime 的 max_blocking_threads（默认 1）
+                       - 执行 JS 的每个线程各有一个单线程（current-thread）runtime，不创建工作线程；
+                         这里限制的是其中阻塞任务（文件读写等 spawn_blocking）线程池的大小
+        current_thread: 已弃用，传入时发出 DeprecationWarning
+                        - runtime 始终是每个线程独立的单线程 runtime（JsRuntime 不是 Send），False 抛出 ValueError
+        single_threaded_platform: 是否使用单线程 V8 平台（默认 False）
+                                  - True: 不创建 V8 后台编译 / GC 线程（适合线程配额严格的容器），
+                                    GC 和优化编译在执行 JS 的线程上同步完成
+        jitless: 是否禁用 JIT（默认 False）
+                 - True: 只用解释器执行 JS（不生成机器码），适合执行不可信代码，性能明显下降
+                 - jitless 模式下 WebAssembly 通常不可用
+        stack_size_kb: V8 栈大小上限（KB，默认约 1 MB）
+                 - 递归超过上限时抛出可以捕获的 RangeError
+                 - 不能超过执行 JS 的线程的栈大小；ThreadedContext / ContextPool 的工作线程会自动分配足够的栈
+        wasm_features: WASM 特性开关，如 {"exceptions": True, "stringref": True}（默认使用 V8 默认值）
+                 - simd / relaxed_simd / threads / memory64 / multi_memory / gc 在当前 V8 中始终启用，只接受 True
+                 - exceptions（异常处理，默认启用）、branch_hinting 可以关闭
+                 - type_reflection / stringref / fp16 / memory_control 为实验特性，默认关闭
+        v8_flags: V8 命令行参数列表，与 set_v8_flags() 相同，如 ["--max-old-space-size=512"]
+        icu: 是否提供 Intl（默认 True）
+                 - False: 每个 Context / Realm 删除全局 Intl（ICU 数据编译在扩展中，
+                   Date / String 的 toLocale* 方法仍然可用）
+        snapshot: 未传入 snapshot 的 Context 的启动快照（默认 True）
+                 - True: 启用扩展时使用编译时嵌入的内置快照（polyfill 已执行过的堆）；
+                   没有嵌入快照或修改了 V8 参数（jitless、v8_flags 等）时直接执行 polyfill
+                 - False: 不使用快照，每个 Context 首次执行时重新执行 polyfill
+                 - bytes: build_snapshot() 生成的快照，用于 enable_extensions 与快照一致的 Context
+                   （build_snapshot() 本身会初始化 V8，快照需要事先构建并保存到文件）
+
+    Raises:
+        ValueError: tokio_threads 或 stack_size_kb 为 0，current_thread 为 False，wasm_features 中有未知 / 无法关闭的特性，
+                    snapshot 不是有效的快照，或 v8_flags 中有 V8 无法识别的参数（其余配置仍然生效）
+        TypeError: snapshot 不是 bool / bytes
+        RuntimeError: 已经创建过 Context
+
+    Example:
+        >>> import never_jscore
+        >>> never_jscore.init(tokio_threads=1)
+        >>> ctx = never_jscore.Context()
+    """
+    ...
+
+
+def set_v8_flags(flags: List[str]) -> None:
+    """
+    设置 V8 命令行参数（必须在第一次创建 Context 之前调用）
+
+    参数立即传给 V8，多次调用会累加。用于调整堆大小、关闭 JIT 等。
+
+    Args:
+        flags: V8 参数列表，如 ["--max-old-space-size=512", "--jitless"]
+
+    Raises:
+        ValueError: 包含 V8 无法识别的参数（其余可识别的参数仍然生效）
+        RuntimeError: 已经创建过 Context
+
+    Example:
+        >>> import never_jscore
+        >>> never_jscore.set_v8_flags(["--max-old-space-size=512", "--jitless"])
+        >>> ctx = never_jscore.Context()
+    """
+    ...
+
+
+def shutdown() -> None:
+    """
+    按顺序关闭 never_jscore 的后台线程并释放 V8（进程退出前调用，可选）
+
+    插件宿主、测试框架等在退出时检查残留线程的环境中使用。必须在所有 Context 销毁之后调用：
+    关闭当前线程的 tokio runtime、
+    停止看门狗线程，最后释放 V8 和 V8 平台（后台编译 / GC 线程）。
+
+    释放后的 V8 不能重新初始化：之后创建 Context 抛出 RuntimeError。重复调用不做任何事。
+    其他线程的单线程 tokio runtime 随线程退出销毁；ThreadedContext / ContextPool 的工作线程在 close() 时退出。
+
+    Raises:
+        RuntimeError: 还有存活的 Context（包括 ThreadedContext / ContextPool 的工作线程、
+                      模块级 eval() 的隐式 Context，先 close() / clear_eval_cache() / del）
+
+    Example:
+        >>> ctx = never_jscore.Context()
+        >>> ...
+        >>> del ctx
+        >>> never_jscore.shutdown()
+    """
+    ...
+
+
+def compile_project(
+    dir: Union[str, os.PathLike],
+    entry: Optional[Union[str, os.PathLike]] = None,
+    encoding: Optional[str] = None,
+) -> Context:
+    """
+    按依赖顺序加载目录中的脚本，返回新的 Context
+
+    等价于 ctx = Context(); ctx.compile_project(dir, entry)；需要自定义 Context 参数时
+    先创建 Context 再调用 Context.compile_project()。
+
+    Args:
+        dir: 项目目录
+        entry: 入口文件（相对 dir 或绝对路径）。指定时只加载入口及其依赖，默认加载全部文件
+        encoding: 文件编码（默认 UTF-8），同 compile_file()
+
+    Returns:
+        加载好项目的 Context
+
+    Raises:
+        ValueError: 循环依赖、清单格式错误或 @requires 指向不存在的文件
+
+    Example:
+        >>> ctx = never_jscore.compile_project("sign", entry="main.js")
+        >>> ctx.call("sign", ["data"])
+    """
+    ...
+
+
+def compile_url(
+    url: str,
+    sha256: Optional[str] = None,
+    cache_dir: Optional[Union[str, os.PathLike]] = None,
+    proxy: Optional[str] = None,
+    timeout_ms: int = 30000,
+    encoding: Optional[str] = None,
+    mode: Optional[str] = None,
+) -> Context:
+    """
+    下载远程脚本，校验 SHA-256 后在新的 Context 中编译
+
+    等价于 ctx = Context(); ctx.compile_url(url, ...)；需要自定义 Context 参数时
+    先创建 Context 再调用 Context.compile_url()。参数与 Context.compile_url() 相同。
+
+    Example:
+        >>> ctx = never_jscore.compile_url("https://www.example.com/static/sign.js", sha256="9f86d0...")
+        >>> ctx.call("sign", ["data"])
+    """
+    ...
+
+
+def register_converter(py_type: type, to_js: Optional[Callable[[Any], Any]]) -> None:
+    """
+    注册 Python -> JavaScript 的类型转换器，对所有 Context 生效
+
+    传给 JS 的值（call() / acall() 的参数、register() 注册的 Python 函数的返回值等）
+    是 py_type 的实例（包括子类）时，先调用 to_js(value)，再按内置规则转换它的返回值，
+    返回值中嵌套的对象同样会应用转换器。转换器在内置类型之前检查，
+    同一个值匹配多个转换器时后注册的优先。
+
+    JS -> Python 方向仍然是 JSON 语义（Temporal 值除外，转换为 datetime 等）：需要自定义时在 JS 中定义 toJSON()。
+
+    Args:
+        py_type: Python 类型
+        to_js: 转换函数，返回可以转换为 JS 的值；传入 None 取消该类型的转换器
+
+    Raises:
+        TypeError: to_js 不可调用
+
+    Example:
+        >>> import datetime, decimal
+        >>> never_jscore.register_converter(datetime.datetime, lambda d: d.isoformat())
+        >>> never_jscore.register_converter(decimal.Decimal, str)
+        >>> ctx.call("f", [datetime.datetime(2024, 1, 1), decimal.Decimal("9.90")])
+    """
+    ...
+
+
+def set_defaults(
+    timeout_ms: Optional[int] = None,
+    cpu_limit_ms: Optional[int] = None,
+    max_heap_mb: Optional[int] = None,
+    max_result_bytes: Optional[int] = None,
+    timeout: Optional[float] = None,
+    max_result_mb: Optional[int] = None,
+) -> None:
+    """
+    设置所有新建 Context 的默认限制
+
+    应用于 Context / ThreadedContext / ContextPool / SnapshotPool 以及 eval() / aeval() 的隐式 Context。
+    每次调用替换全部默认值，不带参数调用清除所有默认值。创建 Context 时显式传入的参数优先；
+    已创建的 Context 不受影响，隐式 Context 在下一次使用时按新的默认值重新创建。
+
+    Args:
+        timeout_ms: 每次调用的墙钟超时（毫秒），同 Context(timeout_ms=...)
+        cpu_limit_ms: 每次调用的 CPU 时间预算（毫秒），同 Context(cpu_limit_ms=...)
+        max_heap_mb: 最大堆大小（MB），同 Context(max_heap_mb=...)
+        max_result_bytes: 单次结果的最大字节数，同 Context(quotas={'max_result_bytes': ...})，
+                          与 quotas 的其他项合并
+        timeout: 同 timeout_ms，单位为秒（可以是小数），不能与 timeout_ms 同时传入
+        max_result_mb: 同 max_result_bytes，单位为 MB，不能与 max_result_bytes 同时传入
+
+    Raises:
+        ValueError: 参数为 0 / 非正数，同时传入同一限制的两种单位，或当前平台不支持 cpu_limit_ms
+
+    Example:
+        >>> never_jscore.set_defaults(timeout=10, max_heap_mb=256, max_result_mb=64)
+        >>> ctx = never_jscore.Context()                # 继承上面的限制
+        >>> ctx = never_jscore.Context(timeout_ms=60_000)  # 显式参数优先
+    """
+    ...
+
+
+def version_info() -> Dict[str, Any]:
+    """
+    返回版本与构建信息
+
+    报告 bug 时附上它；也可以在运行时判断功能是否可用。features 反映当前进程的配置
+    （init() 的 icu / snapshot / jitless 等）。
+
+    Returns:
+        dict: 包含以下字段
+            - version: never_jscore 版本
+            - deno_core: deno_core 版本
+            - v8: V8 版本
+            - source_hash: 源码指纹，内置快照文件按它命名（见 scripts/build_builtin_snapshot.py）
+            - platform: 编译目标，如 "linux-x86_64"
+            - initialized: V8 是否已经初始化（之后不能再调用 init()）
+            - features: 功能是否可用（bool）：icu、snapshot、fetch、wasm、wasi、jitless、temporal
+
+    Example:
+        >>> info = never_jscore.version_info()
+        >>> info["version"], info["v8"]
+        ('2.4.4', '14.2.231.17')
+        >>> info["features"]["wasm"]
+        True
+    """
+    ...
+
+
+def transfer(
+    src: Union[Context, ThreadedContext],
+    name: str,
+    dst: Union[Context, ThreadedContext],
+    dst_name: Optional[str] = None,
+) -> None:
+    """
+    把 src 的全局变量 name 以结构化克隆复制到 dst
+
+    不经过 Python / JSON，Map、Set、Date、类型化数组、循环引用等原样保留；
+    SharedArrayBuffer 和 WebAssembly.Module 在两个 Context 之间共享而不是复制。
+    接收值之后 dst 不能再 pickle / deepcopy。
+
+    Args:
+        src: 源 Context 或 ThreadedContext
+        name: src 中的全局变量名（全局 let/const 声明不可见）
+        dst: 目标 Context 或 ThreadedContext（可以与 src 相同）
+        dst_name: dst 中的全局变量名，默认与 name 相同（覆盖同名变量）
+
+    Raises:
+        TypeError: src / dst 不是 Context 或 ThreadedContext
+        Exception: 变量不存在（ReferenceError）或无法克隆（DataCloneError）
+
+    Example:
+        >>> parser = never_jscore.Context()
+        >>> signer = never_jscore.Context()
+        >>> parser.eval("globalThis.parsed = new Map([['body', new Uint8Array([1, 2, 3])]])")
+        >>> never_jscore.transfer(parser, "parsed", signer)
+        >>> signer.evaluate("parsed.get('body') instanceof Uint8Array")
+        True
+    """
+    ...
+
+
+def set_execution_hooks(
+    on_execute_start: Optional[Callable[[Dict[str, Any]], Any]] = None,
+    on_execute_end: Optional[Callable[[Dict[str, Any]], Any]] = None,
+) -> None:
+    """
+    设置全局执行钩子
+
+    钩子在每次执行 JS 前后调用（所有 Context 类型、Realm 和模块级 eval()，包括 asyncio 版本），
+    适合接入 APM / 自定义监控。传入 None 清除对应的钩子。
+
+    钩子的参数为字典：
+    - kind: "evaluate"（evaluate / call / eval(return_value=True)）或 "script"（compile / compile_file / eval）
+    - filename: 脚本名称（compile_file 为文件路径，求值为 "<eval>"）
+    - code_hash: 代码的 SHA-256（十六进制）
+    - code_length: 代码字节数
+    - trace_id: call(trace_id=...) 的追踪 ID（没有时为 None）
+    - on_execute_end 额外包含 duration_ms、success、error（失败时的错误信息，成功为 None）
+
+    钩子在执行 JS 的线程上调用；钩子抛出的异常不影响执行，通过 sys.unraisablehook 报告；
+    钩子内部再执行 JS 不会再次触发钩子。
+
+    Args:
+        on_execute_start: 执行前调用
+        on_execute_end: 执行后调用
+
+    Raises:
+        TypeError: 钩子不可调用
+
+    Example:
+        >>> def on_end(info):
+        ...     print(info["kind"], info["filename"], info["duration_ms"], info["success"])
+        >>> never_jscore.set_execution_hooks(on_execute_end=on_end)
+        >>> never_jscore.Context().evaluate("1 + 1")
+        evaluate <eval> 0.12 True
+    """
+    ...
+
+
+def build_snapshot(
+    code: str,
+    enable_extensions: bool = True,
+    enable_logging: bool = False,
+    preset: Optional[List[str]] = None,
+) -> bytes:
+    """
+    构建 V8 启动快照
+
+    执行 polyfill 和给定的初始化代码，将得到的 V8 堆序列化为 bytes。
+    之后用 Context(snapshot=data) 从该堆启动，重复加载大型 JS 库时
+    启动时间从几十毫秒降低到接近零。
+
+    Args:
+        code: 初始化代码（通常是要预加载的 JS 库）
+        enable_extensions: 是否启用扩展，必须与加载快照的 Context 一致（默认 True）
+        enable_logging: polyfill 的日志开关（默认 False）
+        preset: 在 code 之前加载的内置库（可选），与 Context.compile(preset=...) 相同
+
+    Returns:
+        快照数据。可以保存到文件，在其他进程中复用（要求相同版本的 never_jscore）
+
+    Raises:
+        Exception: 初始化代码执行失败时
+
+    Example:
+        >>> snapshot = build_snapshot(open("crypto-lib.js").read())
+        >>> ctx = Context(snapshot=snapshot)
+        >>> ctx.call("encrypt", ["data"])
+    """
+    ...
+
 
 # 类型别名
 JSValue = Union[None, bool, int, float, str, List[Any], dict[str, Any]]
@@ -354,5 +3059,27 @@ __version__: str = "2.4.2"
 
 __all__ = [
     "Context",
+    "CompileTask",
+    "ContextPool",
+    "Realm",
+    "ShadowRealm",
+    "SharedBuffer",
+    "SnapshotPool",
+    "ThreadedContext",
+    "AbortToken",
     "JSValue",
+    "acall",
+    "aeval",
+    "build_snapshot",
+    "clear_eval_cache",
+    "compile_project",
+    "compile_url",
+    "configure_eval",
+    "eval",
+    "get_eval_context",
+    "init",
+    "register_converter",
+    "set_defaults",
+    "set_v8_flags",
+    "version_info",
 ]
@@ -0,0 +1,34 @@
+"""
+生成内置启动快照（build.rs 在下一次构建时嵌入）
+
+    maturin develop --release
+    python scripts/build_builtin_snapshot.py
+    maturin develop --release   # 或 maturin build --release
+
+快照文件按当前扩展的源码指纹命名，写入 snapshot/（或 NEVER_JSCORE_SNAPSHOT_DIR）。
+第二次构建必须使用同一份源码，否则指纹不一致，build.rs 不会嵌入旧的快照。
+"""
+
+import os
+import sys
+
+import never_jscore
+
+ROOT = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+
+
+def main():
+    out_dir = os.environ.get("NEVER_JSCORE_SNAPSHOT_DIR", os.path.join(ROOT, "snapshot"))
+    os.makedirs(out_dir, exist_ok=True)
+    source_hash = never_jscore.version_info()["source_hash"]
+
+    for prefix, enable_logging in (("builtin", False), ("builtin-logging", True)):
+        data = never_jscore.build_snapshot("", enable_logging=enable_logging)
+        path = os.path.join(out_dir, f"{prefix}-{source_hash}.bin")
+        with open(path, "wb") as f:
+            f.write(data)
+        print(f"{path}: {len(data)} bytes")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
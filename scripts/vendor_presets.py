@@ -0,0 +1,78 @@
+"""
+下载 compile(preset=[...]) 使用的上游 JS 库，原样写入 src/dddd_js/vendor/（presets.rs 通过 include_str! 嵌入）
+
+    python scripts/vendor_presets.py
+
+版本固定在 PACKAGES 中。从 npm registry 下载该版本的发布包，按 registry 记录的 dist.integrity（SHA-512）校验后，
+把库文件和 LICENSE 原样写入 vendor/<包名>/，并在 vendor/VERSIONS 中记录版本、来源和文件的 SHA-256。
+升级时修改 PACKAGES 中的版本，重新运行后提交 vendor/ 的改动（VERSIONS 的 diff 显示文件是否变化）。
+"""
+
+import base64
+import hashlib
+import io
+import json
+import os
+import sys
+import tarfile
+import urllib.request
+
+ROOT = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+VENDOR_DIR = os.path.join(ROOT, "src", "dddd_js", "vendor")
+REGISTRY = "https://registry.npmjs.org"
+
+# (包名, 版本, {发布包中的路径: vendor/<包名>/ 中的文件名})
+PACKAGES = [
+    ("crypto-js", "4.2.0", {
+        "package/crypto-js.js": "crypto-js.js",
+        "package/LICENSE": "LICENSE",
+    }),
+    ("jsencrypt", "3.3.2", {
+        "package/bin/jsencrypt.js": "jsencrypt.js",
+        "package/LICENSE.txt": "LICENSE.txt",
+    }),
+]
+
+
+def fetch(url):
+    with urllib.request.urlopen(url, timeout=60) as response:
+        return response.read()
+
+
+def verify_integrity(data, integrity):
+    algorithm, _, expected = integrity.partition("-")
+    if algorithm != "sha512":
+        raise ValueError(f"unsupported integrity algorithm {algorithm!r}")
+    actual = base64.b64encode(hashlib.sha512(data).digest()).decode()
+    if actual != expected:
+        raise ValueError(f"integrity mismatch: expected {expected}, got {actual}")
+
+
+def vendor(name, version, files):
+    meta = json.loads(fetch(f"{REGISTRY}/{name}/{version}"))
+    tarball = fetch(meta["dist"]["tarball"])
+    verify_integrity(tarball, meta["dist"]["integrity"])
+
+    out_dir = os.path.join(VENDOR_DIR, name)
+    os.makedirs(out_dir, exist_ok=True)
+    records = []
+    with tarfile.open(fileobj=io.BytesIO(tarball), mode="r:gz") as archive:
+        for member, target in files.items():
+            data = archive.extractfile(member).read()
+            with open(os.path.join(out_dir, target), "wb") as f:
+                f.write(data)
+            records.append(f"{name}/{target}  sha256:{hashlib.sha256(data).hexdigest()}")
+            print(f"{name}@{version}: {member} -> vendor/{name}/{target} ({len(data)} bytes)")
+    return [f"{name} {version} {meta['dist']['tarball']}"] + records
+
+
+def main():
+    lines = []
+    for name, version, files in PACKAGES:
+        lines += vendor(name, version, files)
+    with open(os.path.join(VENDOR_DIR, "VERSIONS"), "w", newline="\n") as f:
+        f.write("\n".join(lines) + "\n")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,139 @@
+// abort.rs - AbortToken：取消 asyncio 调用时终止正在执行的 JS
+//
+// 异步 API（ThreadedContext / ContextPool 的 *_async()、never_jscore.aeval() / acall()）把任务交给工作线程，
+// 等待的 asyncio 任务被取消（task.cancel()、asyncio.wait_for 超时）后，工作线程上的 JS 原本会继续运行，
+// 占住 isolate 直到自然结束。现在每个异步调用都带有一个中止信号：
+// - 等待的 Future 被取消时自动中止；也可以传入 abort=AbortToken()，由 token.abort() 主动中止
+//   （同一个 token 可以传给多个调用，一次中止全部）
+// - 还在排队的任务不再执行；正在执行的 JS 通过 terminate_execution() 终止
+//   （与 timeout_ms 相同的路径，Context 恢复后可以继续使用），等待定时器 / Promise 的事件循环立即停止等待
+// - 被中止的调用以 asyncio.CancelledError 结束
+
+use deno_core::v8;
+use pyo3::exceptions::asyncio::CancelledError;
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+use crate::watchdog::{ExceededSlot, LimitExceeded};
+
+/// 正在执行的调用：中止时终止它的 isolate
+struct Target {
+    handle: v8::IsolateHandle,
+    exceeded: ExceededSlot,
+}
+
+impl Target {
+    fn terminate(&self) {
+        let mut exceeded = self.exceeded.lock().unwrap();
+        if exceeded.is_none() {
+            *exceeded = Some(LimitExceeded::Aborted.into());
+        }
+        self.handle.terminate_execution();
+    }
+}
+
+/// 中止信号（可以跨线程共享）
+#[derive(Default)]
+pub struct AbortSignal {
+    aborted: AtomicBool,
+    notify: Notify,
+    targets: Mutex<Vec<(u64, Target)>>,
+    next_id: AtomicU64,
+}
+
+impl AbortSignal {
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+
+    /// 中止：终止所有正在执行的调用，唤醒等待中的事件循环
+    pub fn abort(&self) {
+        if self.aborted.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        for (_, target) in self.targets.lock().unwrap().iter() {
+            target.terminate();
+        }
+        self.notify.notify_waiters();
+    }
+
+    /// 开始一次执行（已中止时立即终止），返回 detach() 使用的 ID
+    pub fn attach(&self, handle: v8::IsolateHandle, exceeded: ExceededSlot) -> u64 {
+        let target = Target { handle, exceeded };
+        let mut targets = self.targets.lock().unwrap();
+        if self.is_aborted() {
+            target.terminate();
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        targets.push((id, target));
+        id
+    }
+
+    /// 执行结束
+    pub fn detach(&self, id: u64) {
+        self.targets.lock().unwrap().retain(|(target_id, _)| *target_id != id);
+    }
+
+    /// 等待中止
+    pub async fn aborted(&self) {
+        let notified = self.notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        if self.is_aborted() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+/// 被中止的异步调用抛出的异常
+pub fn cancelled_error() -> PyErr {
+    CancelledError::new_err("JavaScript execution was aborted")
+}
+
+/// 异步调用的中止令牌
+///
+/// Example:
+///     ```python
+///     token = never_jscore.AbortToken()
+///     task = asyncio.ensure_future(ctx.call_async("crack", [data], abort=token))
+///     ...
+///     token.abort()          # 终止 JS，await task 抛出 asyncio.CancelledError
+///     ```
+#[pyclass(module = "never_jscore", frozen)]
+pub struct AbortToken {
+    signal: Arc<AbortSignal>,
+}
+
+impl AbortToken {
+    pub(crate) fn signal(&self) -> Arc<AbortSignal> {
+        self.signal.clone()
+    }
+}
+
+#[pymethods]
+impl AbortToken {
+    #[new]
+    fn py_new() -> Self {
+        AbortToken {
+            signal: Arc::new(AbortSignal::default()),
+        }
+    }
+
+    /// 中止使用此令牌的所有调用（可以在任意线程调用，重复调用无效果）
+    fn abort(&self) {
+        self.signal.abort();
+    }
+
+    /// 是否已中止
+    #[getter]
+    fn aborted(&self) -> bool {
+        self.signal.is_aborted()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("AbortToken(aborted={})", if self.signal.is_aborted() { "True" } else { "False" })
+    }
+}
@@ -0,0 +1,109 @@
+// aio.rs - asyncio 集成
+//
+// 把任务提交到持有 Context 的工作线程后立即返回 asyncio.Future，
+// 工作线程执行完成后通过 loop.call_soon_threadsafe() 把结果交回事件循环。
+// 等待期间不占用 GIL，也不阻塞 Python 的事件循环。
+//
+// 每个任务带有中止信号（见 abort.rs）：Future 被取消时中止，工作线程上的 JS 随之终止。
+
+use pyo3::prelude::*;
+use pyo3::types::PyCFunction;
+use std::sync::Arc;
+
+use crate::abort::{AbortSignal, AbortToken};
+use crate::context::Context;
+use crate::convert::json_str_to_python;
+use crate::pool::TaskSink;
+
+/// 异步任务的结果：Some(JSON 字符串) 或 None（对应 Python 的 None）
+pub(crate) type AsyncResult = PyResult<Option<String>>;
+
+/// 在当前 asyncio 事件循环上创建 Future，并把任务提交到工作线程
+///
+/// 必须在事件循环运行时调用（即在协程中），否则抛出 RuntimeError。
+/// abort 为调用方传入的 AbortToken；Future 被取消时同样中止任务，被中止的任务以 CancelledError 结束。
+pub(crate) fn submit<'py, F>(
+    py: Python<'py>,
+    sender: &impl TaskSink,
+    abort: Option<&AbortToken>,
+    f: F,
+) -> PyResult<Bound<'py, PyAny>>
+where
+    F: FnOnce(&Context) -> AsyncResult + Send + 'static,
+{
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let future = event_loop.call_method0("create_future")?;
+
+    let signal = abort.map_or_else(|| Arc::new(AbortSignal::default()), AbortToken::signal);
+    let on_done = {
+        let signal = signal.clone();
+        PyCFunction::new_closure(py, None, None, move |args, _kwargs| -> PyResult<()> {
+            if args.get_item(0)?.call_method0("cancelled")?.is_truthy()? {
+                signal.abort();
+            }
+            Ok(())
+        })?
+    };
+    future.call_method1("add_done_callback", (on_done,))?;
+
+    let loop_handle = event_loop.unbind();
+    let future_handle = future.clone().unbind();
+
+    sender
+        .submit(Box::new(move |ctx: &Context| {
+            // 还在排队时被中止：不再执行
+            let result = if signal.is_aborted() {
+                Err(crate::abort::cancelled_error())
+            } else {
+                ctx.with_abort(&signal, || f(ctx))
+            };
+            let result = match result {
+                Err(_) if signal.is_aborted() => Err(crate::abort::cancelled_error()),
+                result => result,
+            };
+            Python::attach(|py| {
+                if let Err(e) = deliver(py, loop_handle.bind(py), future_handle.bind(py), result) {
+                    // 事件循环已关闭时无法投递结果，只能打印出来
+                    e.print(py);
+                }
+            });
+        }))
+        .map_err(|e| e.into_py("Context is closed"))?;
+
+    Ok(future)
+}
+
+/// 在工作线程中把结果投递给事件循环
+fn deliver(py: Python<'_>, event_loop: &Bound<'_, PyAny>, future: &Bound<'_, PyAny>, result: AsyncResult) -> PyResult<()> {
+    let outcome = result.and_then(|json| match json {
+        Some(json) => json_str_to_python(py, &json).map(Bound::unbind),
+        None => Ok(py.None()),
+    });
+    settle(py, event_loop, future, outcome)
+}
+
+/// 在其他线程中以结果或异常完成事件循环上的 Future（通过 call_soon_threadsafe）
+pub(crate) fn settle(py: Python<'_>, event_loop: &Bound<'_, PyAny>, future: &Bound<'_, PyAny>, outcome: PyResult<Py<PyAny>>) -> PyResult<()> {
+    let (outcome, is_error) = match outcome {
+        Ok(value) => (value, false),
+        Err(e) => (e.into_value(py).into_any(), true),
+    };
+
+    let setter = PyCFunction::new_closure(py, None, None, move |args, _kwargs| -> PyResult<()> {
+        let future = args.get_item(0)?;
+        let value = args.get_item(1)?;
+        // Future 可能已被取消（如 asyncio.wait_for 超时）
+        if future.call_method0("done")?.is_truthy()? {
+            return Ok(());
+        }
+        if is_error {
+            future.call_method1("set_exception", (value,))?;
+        } else {
+            future.call_method1("set_result", (value,))?;
+        }
+        Ok(())
+    })?;
+
+    event_loop.call_method1("call_soon_threadsafe", (setter, future, outcome))?;
+    Ok(())
+}
@@ -0,0 +1,157 @@
+// audit.rs - op 调用审计日志（Context(audit_ops=True)）
+//
+// 开启后 polyfill 通过代理调用 ops，每次调用记录 op 名称、截断后的参数、
+// 开始时间、耗时和结果（ok / error / denied）。日志按调用保存：
+// 每次 evaluate()/call()/compile() 开始时清空，之后通过 get_audit_log() 取出，
+// 用于调试扩展或检查不可信脚本尝试了哪些操作。
+//
+// 异步 op（定时器、fetch 等）在 Promise 完成时记录耗时；调用结束时仍未完成的记为 pending。
+// new Worker() 中的 op 调用在父 Context 收到 Worker 的事件时并入当前调用的日志（见 worker_ops.rs）。
+
+use deno_core::{extension, op2, OpState};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 每次调用最多保存的记录数，超出的只计数
+const MAX_ENTRIES: usize = 10_000;
+
+/// 一次 op 调用
+#[derive(Clone, Debug)]
+pub struct AuditEntry {
+    pub op: String,
+    /// 参数（已在 JS 侧转换为字符串并截断）
+    pub args: Vec<String>,
+    /// 开始时间（Unix 时间戳，毫秒）
+    pub start_ms: f64,
+    started: Instant,
+    pub duration: Option<Duration>,
+    /// "ok" / "error" / "denied"，尚未完成时为 None
+    pub status: Option<String>,
+    /// 调用所在的 Worker 名称（Context 自身的调用为 None）
+    pub worker: Option<String>,
+}
+
+/// Context 的审计日志（与 OpState 共享）
+#[derive(Default)]
+pub struct AuditLog {
+    entries: RefCell<Vec<AuditEntry>>,
+    dropped: Cell<usize>,
+    /// Worker 中超出上限未保存的记录数
+    worker_dropped: Cell<usize>,
+    /// 当前第一条记录的 ID，清空时增加，使上一次调用中未完成的 op 不会写到新记录上
+    first_id: Cell<u32>,
+}
+
+impl AuditLog {
+    /// 开始新的调用：清空之前的记录
+    pub fn clear(&self) {
+        let mut entries = self.entries.borrow_mut();
+        let consumed = entries.len() + self.dropped.get();
+        self.first_id.set(self.first_id.get().wrapping_add(consumed as u32));
+        entries.clear();
+        self.dropped.set(0);
+        self.worker_dropped.set(0);
+    }
+
+    fn begin(&self, op: String, args: Vec<String>) -> u32 {
+        let mut entries = self.entries.borrow_mut();
+        let id = self.first_id.get().wrapping_add((entries.len() + self.dropped.get()) as u32);
+        if entries.len() >= MAX_ENTRIES {
+            self.dropped.set(self.dropped.get() + 1);
+            return id;
+        }
+        let start_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or_default();
+        entries.push(AuditEntry {
+            op,
+            args,
+            start_ms,
+            started: Instant::now(),
+            duration: None,
+            status: None,
+            worker: None,
+        });
+        id
+    }
+
+    /// 并入 Worker 的记录（追加在末尾，不影响进行中的调用的记录 ID）
+    pub fn append(&self, worker_entries: Vec<AuditEntry>, dropped: usize) {
+        let mut entries = self.entries.borrow_mut();
+        let room = MAX_ENTRIES.saturating_sub(entries.len());
+        let skipped = worker_entries.len().saturating_sub(room);
+        entries.extend(worker_entries.into_iter().take(room));
+        self.worker_dropped.set(self.worker_dropped.get() + dropped + skipped);
+    }
+
+    fn end(&self, id: u32, status: String) {
+        let index = id.wrapping_sub(self.first_id.get()) as usize;
+        if let Some(entry) = self.entries.borrow_mut().get_mut(index) {
+            if entry.status.is_none() {
+                entry.duration = Some(entry.started.elapsed());
+                entry.status = Some(status);
+            }
+        }
+    }
+
+    /// 当前记录（以及超出上限未保存的记录数）
+    pub fn snapshot(&self) -> (Vec<AuditEntry>, usize) {
+        (self.entries.borrow().clone(), self.dropped.get() + self.worker_dropped.get())
+    }
+}
+
+/// 转换为 get_audit_log() 的返回值
+pub fn to_python<'py>(py: Python<'py>, entries: &[AuditEntry], dropped: usize) -> PyResult<Bound<'py, PyDict>> {
+    let list = PyList::empty(py);
+    for entry in entries {
+        let item = PyDict::new(py);
+        item.set_item("op", &entry.op)?;
+        item.set_item("args", &entry.args)?;
+        item.set_item("start_ms", entry.start_ms)?;
+        item.set_item("duration_ms", entry.duration.map(|d| d.as_secs_f64() * 1000.0))?;
+        item.set_item("status", entry.status.as_deref().unwrap_or("pending"))?;
+        item.set_item("worker", entry.worker.as_deref())?;
+        list.append(item)?;
+    }
+    let result = PyDict::new(py);
+    result.set_item("entries", list)?;
+    result.set_item("dropped", dropped)?;
+    Ok(result)
+}
+
+#[op2(fast)]
+/// 是否开启了审计（polyfill 据此决定是否通过代理调用 ops）
+pub fn op_audit_enabled(state: &mut OpState) -> bool {
+    state.has::<Rc<AuditLog>>()
+}
+
+#[op2(fast)]
+/// 记录 op 调用开始，`args` 为截断后参数的 JSON 数组，返回记录 ID
+pub fn op_audit_begin(state: &mut OpState, #[string] op: String, #[string] args: String) -> u32 {
+    let Some(log) = state.try_borrow::<Rc<AuditLog>>() else {
+        return 0;
+    };
+    let args = serde_json::from_str(&args).unwrap_or_else(|_| vec![args]);
+    log.begin(op, args)
+}
+
+#[op2(fast)]
+/// 记录 op 调用结束
+pub fn op_audit_end(state: &mut OpState, id: u32, #[string] status: String) {
+    if let Some(log) = state.try_borrow::<Rc<AuditLog>>() {
+        log.end(id, status);
+    }
+}
+
+extension!(
+    audit_ops,
+    ops = [
+        op_audit_enabled,
+        op_audit_begin,
+        op_audit_end,
+    ],
+);
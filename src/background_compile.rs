@@ -0,0 +1,123 @@
+// background_compile.rs - 后台编译大型 bundle
+//
+// V8 的流式编译接口没有暴露给 Rust，这里借助代码缓存实现同样的效果：
+// 后台线程用独立的 isolate 解析并编译代码、生成代码缓存，
+// 主线程在 wait() 时消费缓存执行脚本，只剩下反序列化和执行的开销。
+// 编译期间 Python 线程可以继续做其他工作。
+
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crate::context::{Context, format_error};
+use crate::snapshot::OwnedBlob;
+
+/// 后台编译的结果：代码缓存或格式化后的错误
+type CompileResult = Result<Vec<u8>, String>;
+
+/// 启动后台编译线程
+///
+/// 后台 isolate 与 Context 使用相同的启动快照和扩展，保证生成的代码缓存能被 Context 接受。
+pub(crate) fn spawn(
+    code: Arc<str>,
+    code_cache_dir: Option<PathBuf>,
+    snapshot: Option<&'static [u8]>,
+    snapshot_owner: Option<Arc<OwnedBlob>>,
+    enable_extensions: bool,
+) -> PyResult<JoinHandle<CompileResult>> {
+    std::thread::Builder::new()
+        .stack_size(crate::runtime::worker_stack_size())
+        .name("never_jscore-compile".to_string())
+        .spawn(move || {
+            // 编译用的 isolate 从 Context 的快照启动，Context 先销毁时快照要保留到编译结束
+            let _owner = snapshot_owner;
+            crate::code_cache::precompile("<exec>", &code, code_cache_dir.as_deref(), snapshot, enable_extensions)
+                .map_err(format_error)
+        })
+        .map_err(|e| PyException::new_err(format!("Failed to spawn compile thread: {}", e)))
+}
+
+/// 后台编译任务
+///
+/// 由 `Context.compile_background()` 返回。编译在后台线程进行，
+/// 调用 `wait()` 时在 Context 中执行脚本（与 `compile()` 效果相同）。
+///
+/// Example:
+///     ```python
+///     task = ctx.compile_background(open("bundle.js").read())
+///     prepare_other_things()   # 与编译并行
+///     task.wait()              # 执行脚本，之后可以调用 bundle 中的函数
+///     ctx.call("sign", ["data"])
+///     ```
+#[pyclass(unsendable)]
+pub struct CompileTask {
+    context: Py<Context>,
+    code: Arc<str>,
+    handle: RefCell<Option<JoinHandle<CompileResult>>>,
+    outcome: RefCell<Option<Result<(), String>>>,
+}
+
+impl CompileTask {
+    pub(crate) fn new(context: Py<Context>, code: Arc<str>, handle: JoinHandle<CompileResult>) -> Self {
+        CompileTask {
+            context,
+            code,
+            handle: RefCell::new(Some(handle)),
+            outcome: RefCell::new(None),
+        }
+    }
+}
+
+#[pymethods]
+impl CompileTask {
+    /// 后台编译是否已完成（完成后 wait() 只需执行脚本）
+    fn done(&self) -> bool {
+        match self.handle.borrow().as_ref() {
+            Some(handle) => handle.is_finished(),
+            None => true,
+        }
+    }
+
+    /// 等待编译完成并在 Context 中执行脚本
+    ///
+    /// 可以多次调用，脚本只执行一次；编译或执行失败时每次都抛出相同的异常。
+    ///
+    /// Raises:
+    ///     Exception: 语法错误或执行错误
+    fn wait(&self, py: Python<'_>) -> PyResult<()> {
+        if self.outcome.borrow().is_none() {
+            let outcome = self.run(py);
+            *self.outcome.borrow_mut() = Some(outcome);
+        }
+
+        match self.outcome.borrow().as_ref() {
+            Some(Err(e)) => Err(PyException::new_err(format!("Compile error: {}", e))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl CompileTask {
+    fn run(&self, py: Python<'_>) -> Result<(), String> {
+        let handle = self
+            .handle
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| "Compile task already consumed".to_string())?;
+
+        let cache = py
+            .detach(|| handle.join())
+            .map_err(|_| "Compile thread panicked".to_string())??;
+
+        let context = self.context.borrow(py);
+        context.check_fork().map_err(|e| e.to_string())?;
+
+        let code = self.code.to_string();
+        context
+            .without_gil(py, |ctx| ctx.exec_precompiled_script("<exec>", code, &cache))
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,222 @@
+// call_cache.rs - call() 目标函数的缓存
+//
+// call(name, args) 把 `name(参数 JSON)` 拼接成代码交给求值包装函数 eval，每次调用都要重新解析整段代码，
+// 参数较大（几 MB 的 JSON）时解析时间超过函数本身。name 是全局函数或属性路径（sign、utils.crypto.sign）时，
+// 第一次调用解析出函数缓存为 v8::Global<Function>，之后直接用 JSON.parse 的参数调用，不需要修改调用代码：
+// - 每次调用沿属性路径重新读取（几次属性访问），函数被替换（`sign = function () {...}` 式的惰性初始化）时更新缓存
+// - `a.b.fn` 的 this 为 a.b，与拼接的代码一致；执行钩子、执行历史中仍然是拼接的代码
+// - 执行全局脚本（compile() / eval() / repl_eval() 等）后清空：脚本可能声明同名的 let/const/class，
+//   它们不是全局对象的属性，且会遮蔽同名属性，这类名称不使用缓存
+// - name 是其他表达式、路径中途不是对象、或结果不是函数时仍然拼接代码执行，错误信息不变
+// - 缓存的函数计入 Context(max_handles=N) 的句柄数，通过 handles() 列出、逐个释放（见 handles.rs）
+
+use anyhow::{anyhow, Result};
+use deno_core::{v8, JsRuntime};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::code_cache::exception_to_error;
+
+/// 不能作为属性路径第一段的关键字（拼接的代码中它们不是全局变量）
+const KEYWORDS: &[&str] = &[
+    "this", "new", "typeof", "void", "delete", "await", "yield", "super", "import", "class", "function", "null", "true",
+    "false",
+];
+
+/// 缓存的调用目标
+enum Target {
+    /// 属性路径（第一段是全局对象的属性）和上次解析出的函数
+    Resolved {
+        /// 句柄编号（handles()），函数被替换时不变
+        id: u64,
+        path: Rc<[v8::Global<v8::String>]>,
+        function: v8::Global<v8::Function>,
+    },
+    /// 不是属性路径，或第一段被同名的 let/const/class 遮蔽：拼接代码执行
+    Uncached,
+}
+
+/// call() 目标函数的缓存（每个 Context 一个）
+#[derive(Default)]
+pub struct CallTargets {
+    targets: RefCell<HashMap<String, Target>>,
+    /// 即将执行的 call() 的函数名，由求值包装函数取出
+    pending: RefCell<Option<String>>,
+}
+
+impl CallTargets {
+    /// 标记下一次求值是 call(name, ...)
+    pub fn expect(&self, name: &str) {
+        *self.pending.borrow_mut() = Some(name.to_string());
+    }
+
+    /// 取出 expect() 标记的函数名（执行没有到达包装函数时调用方用它清除标记）
+    pub fn take_pending(&self) -> Option<String> {
+        self.pending.borrow_mut().take()
+    }
+
+    /// 清空缓存（执行全局脚本后，以及 isolate 销毁之前）
+    pub fn clear(&self) {
+        self.targets.borrow_mut().clear();
+    }
+
+    /// 缓存的函数：(句柄编号, 函数名)，按编号排序
+    pub fn resolved(&self) -> Vec<(u64, String)> {
+        let mut resolved: Vec<_> = self
+            .targets
+            .borrow()
+            .iter()
+            .filter_map(|(name, target)| match target {
+                Target::Resolved { id, .. } => Some((*id, name.clone())),
+                Target::Uncached => None,
+            })
+            .collect();
+        resolved.sort_unstable();
+        resolved
+    }
+
+    /// 编号为 id 的函数是否仍在缓存中
+    pub fn contains(&self, id: u64) -> bool {
+        self.targets.borrow().values().any(|target| matches!(target, Target::Resolved { id: i, .. } if *i == id))
+    }
+
+    /// 从缓存中删除编号为 id 的函数，不存在时返回 false
+    pub fn remove(&self, id: u64) -> bool {
+        let mut targets = self.targets.borrow_mut();
+        let before = targets.len();
+        targets.retain(|_, target| !matches!(target, Target::Resolved { id: i, .. } if *i == id));
+        targets.len() != before
+    }
+
+    /// 用缓存的函数执行 code（format_call(name, args) 拼接的代码），返回函数的返回值
+    ///
+    /// 无法使用缓存时返回 None，由调用方拼接代码执行。cache_new 为 false 时（达到 max_handles）
+    /// 只使用已经缓存的函数，不解析新的名称。
+    pub fn call(
+        &self,
+        runtime: &mut JsRuntime,
+        name: &str,
+        code: &str,
+        cache_new: bool,
+    ) -> Result<Option<v8::Global<v8::Value>>> {
+        let Some(args) = code
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'))
+        else {
+            return Ok(None);
+        };
+        let cached = match self.targets.borrow().get(name) {
+            Some(Target::Uncached) => return Ok(None),
+            Some(Target::Resolved { id, path, function }) => Some((*id, path.clone(), function.clone())),
+            None if !cache_new => return Ok(None),
+            None => None,
+        };
+
+        deno_core::scope!(scope, runtime);
+        v8::tc_scope!(let tc_scope, scope);
+
+        let (id, path, cached_function) = match cached {
+            Some((id, path, function)) => (id, path, Some(function)),
+            None => {
+                let Some(path) = parse_path(tc_scope, name) else {
+                    self.targets.borrow_mut().insert(name.to_string(), Target::Uncached);
+                    return Ok(None);
+                };
+                match shadowed(tc_scope, name, &path[0]) {
+                    // 第一段还不是全局对象的属性（未定义或 let/const 声明），下次调用再解析
+                    None => return Ok(None),
+                    Some(true) => {
+                        self.targets.borrow_mut().insert(name.to_string(), Target::Uncached);
+                        return Ok(None);
+                    }
+                    Some(false) => (crate::handles::next_id(), path, None),
+                }
+            }
+        };
+
+        let Some((receiver, function)) = resolve(tc_scope, &path).ok_or_else(|| exception_to_error(tc_scope))? else {
+            return Ok(None);
+        };
+        let unchanged = cached_function.is_some_and(|cached| v8::Local::new(tc_scope, cached) == function);
+        if !unchanged {
+            let function = v8::Global::new(tc_scope, function);
+            self.targets.borrow_mut().insert(name.to_string(), Target::Resolved { id, path, function });
+        }
+
+        let source = crate::source_string::new_source(tc_scope, format!("[{}]", args))
+            .ok_or_else(|| anyhow!("Arguments are too large"))?;
+        let args = v8::json::parse(tc_scope, source)
+            .and_then(|args| args.try_cast::<v8::Array>().ok())
+            .ok_or_else(|| exception_to_error(tc_scope))?;
+        let args = (0..args.length())
+            .map(|i| args.get_index(tc_scope, i))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| exception_to_error(tc_scope))?;
+
+        let value = function
+            .call(tc_scope, receiver, &args)
+            .ok_or_else(|| exception_to_error(tc_scope))?;
+        Ok(Some(v8::Global::new(tc_scope, value)))
+    }
+}
+
+/// 把 name 解析为属性路径 `a.b.c`，每段是 ASCII 标识符；其他表达式返回 None
+fn parse_path(scope: &mut v8::PinScope<'_, '_>, name: &str) -> Option<Rc<[v8::Global<v8::String>]>> {
+    let is_identifier = |segment: &str| {
+        segment.bytes().next().is_some_and(|first| first.is_ascii_alphabetic() || first == b'_' || first == b'$')
+            && segment.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'$')
+    };
+    let segments: Vec<&str> = name.split('.').collect();
+    if !segments.iter().all(|segment| is_identifier(segment)) || KEYWORDS.contains(&segments[0]) {
+        return None;
+    }
+    segments
+        .into_iter()
+        .map(|segment| v8::String::new(scope, segment).map(|key| v8::Global::new(scope, key)))
+        .collect()
+}
+
+/// 第一段是否被同名的全局 let/const/class 遮蔽（按名称求值的结果与全局对象的属性不同）
+///
+/// 不是全局对象的属性时返回 None。
+fn shadowed(scope: &mut v8::PinScope<'_, '_>, name: &str, first: &v8::Global<v8::String>) -> Option<bool> {
+    let global = scope.get_current_context().global(scope);
+    let key = v8::Local::new(scope, first);
+    if !global.has(scope, key.into())? {
+        return None;
+    }
+    let property = global.get(scope, key.into())?;
+    let first = name.split('.').next().unwrap_or(name);
+    let source = v8::String::new(scope, first)?;
+    let value = v8::Script::compile(scope, source, None).and_then(|script| script.run(scope))?;
+    Some(!value.strict_equals(property))
+}
+
+/// 沿属性路径读取，返回 (this, 函数)；路径中途不是对象或结果不是函数时返回 Some(None)
+///
+/// 读取属性抛出异常时返回 None（异常留在 TryCatch 中）。
+fn resolve<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    path: &[v8::Global<v8::String>],
+) -> Option<Option<(v8::Local<'s, v8::Value>, v8::Local<'s, v8::Function>)>> {
+    let global = scope.get_current_context().global(scope);
+    let mut receiver: v8::Local<v8::Value> = v8::undefined(scope).into();
+    let mut value: v8::Local<v8::Value> = global.into();
+    for (i, key) in path.iter().enumerate() {
+        let Ok(object) = value.try_cast::<v8::Object>() else {
+            return Some(None);
+        };
+        let key = v8::Local::new(scope, key);
+        if i == 0 && !object.has(scope, key.into())? {
+            return Some(None);
+        }
+        // 只有一段时与拼接的 `sign(...)` 一样，this 为 undefined
+        if i > 0 {
+            receiver = value;
+        }
+        value = object.get(scope, key.into())?;
+    }
+    Some(value.try_cast::<v8::Function>().ok().map(|function| (receiver, function)))
+}
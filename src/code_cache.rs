@@ -0,0 +1,198 @@
+// code_cache.rs - V8 code cache for compile() / compile_file()
+//
+// 以源代码的 SHA-256 作为 key，把 V8 编译得到的字节码缓存到磁盘。
+// 进程重启后再次编译同一份大型 bundle 时直接消费缓存，跳过解析和编译。
+//
+// 代码缓存与 isolate 无关，compile_background() 也借助它把编译工作挪到后台线程：
+// 后台线程用独立的 isolate 编译并生成缓存，主线程只需消费缓存。
+
+use anyhow::{Result, anyhow};
+use deno_core::{JsRuntime, RuntimeOptions, error::JsError, v8};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::context::create_extensions;
+use crate::storage::ResultStorage;
+use crate::timings::Timings;
+
+/// 缓存文件扩展名
+const CACHE_EXTENSION: &str = "v8cache";
+
+/// 计算代码对应的缓存文件路径
+///
+/// 文件名为源代码的 SHA-256；V8 版本或编译参数不同导致缓存失效时，
+/// V8 会拒绝该缓存，此时重新生成并覆盖。
+pub fn cache_path(cache_dir: &Path, code: &str) -> PathBuf {
+    let hash = hex::encode(Sha256::digest(code.as_bytes()));
+    cache_dir.join(format!("{}.{}", hash, CACHE_EXTENSION))
+}
+
+/// 读取缓存（不存在或读取失败时返回 None）
+fn read_cache(path: &Path) -> Option<Vec<u8>> {
+    fs::read(path).ok().filter(|data| !data.is_empty())
+}
+
+/// 写入缓存
+///
+/// 先写临时文件再 rename，多个进程同时编译同一份代码时不会读到半个缓存文件。
+/// 写入失败不影响执行结果，直接忽略。
+fn write_cache(path: &Path, data: &[u8]) {
+    if let Some(dir) = path.parent() {
+        if fs::create_dir_all(dir).is_err() {
+            return;
+        }
+    }
+
+    let tmp_path = path.with_extension(format!("{}.{}.tmp", CACHE_EXTENSION, std::process::id()));
+    if fs::write(&tmp_path, data).is_ok() && fs::rename(&tmp_path, path).is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+}
+
+/// 使用代码缓存执行脚本（全局作用域）
+///
+/// 行为与 `JsRuntime::execute_script` 一致，区别在于：
+/// - 缓存命中时使用 `ConsumeCodeCache` 编译，跳过解析
+/// - 未命中或缓存被 V8 拒绝时，正常编译并在执行前生成新缓存
+pub fn execute_script(runtime: &mut JsRuntime, name: &str, code: String, cache_dir: &Path, timings: &mut Timings) -> Result<()> {
+    let path = cache_path(cache_dir, &code);
+    let cached = read_cache(&path);
+
+    if let Some(data) = run_script(runtime, name, code, cached.as_deref(), true, timings)? {
+        write_cache(&path, &data);
+    }
+    Ok(())
+}
+
+/// 使用给定的代码缓存执行脚本（全局作用域）
+///
+/// 缓存缺失或被 V8 拒绝时正常编译；`produce_cache` 为 true 时返回新生成的缓存。
+/// 编译和执行的耗时分别累加到 `timings`。
+pub fn run_script(
+    runtime: &mut JsRuntime,
+    name: &str,
+    code: String,
+    cached: Option<&[u8]>,
+    produce_cache: bool,
+    timings: &mut Timings,
+) -> Result<Option<Vec<u8>>> {
+    let compile_start = Instant::now();
+    deno_core::scope!(scope, runtime);
+
+    let name = v8::String::new(scope, name).ok_or_else(|| anyhow!("Failed to create script name"))?;
+    let source = crate::source_string::new_source(scope, code).ok_or_else(|| anyhow!("Script source is too large"))?;
+    let origin = v8::ScriptOrigin::new(scope, name.into(), 0, 0, false, 0, None, false, false, false, None);
+
+    v8::tc_scope!(let tc_scope, scope);
+
+    let (script, needs_cache) = match cached {
+        Some(data) => {
+            let mut source = v8::script_compiler::Source::new_with_cached_data(
+                source,
+                Some(&origin),
+                v8::CachedData::new(data),
+            );
+            let script = v8::script_compiler::compile(
+                tc_scope,
+                &mut source,
+                v8::script_compiler::CompileOptions::ConsumeCodeCache,
+                v8::script_compiler::NoCacheReason::NoReason,
+            );
+            let rejected = source.get_cached_data().is_none_or(|data| data.rejected());
+            (script, rejected)
+        }
+        None => (v8::Script::compile(tc_scope, source, Some(&origin)), true),
+    };
+
+    let script = match script {
+        Some(script) => script,
+        None => {
+            timings.compile += compile_start.elapsed();
+            return Err(exception_to_error(tc_scope));
+        }
+    };
+
+    let new_cache = if needs_cache && produce_cache {
+        script.get_unbound_script(tc_scope).create_code_cache().map(|data| data.to_vec())
+    } else {
+        None
+    };
+    timings.compile += compile_start.elapsed();
+
+    let execute_start = Instant::now();
+    let result = script.run(tc_scope);
+    timings.execute += execute_start.elapsed();
+
+    match result {
+        Some(_) => Ok(new_cache),
+        None => Err(exception_to_error(tc_scope)),
+    }
+}
+
+/// 在独立的 isolate 中编译代码并生成代码缓存（在后台线程中调用）
+///
+/// 使用 EagerCompile 编译所有函数，主线程消费缓存后不再需要惰性编译。
+/// 设置了 cache_dir 时优先复用磁盘缓存，并把新生成的缓存写回磁盘。
+/// `snapshot` / `enable_extensions` 与消费缓存的 Context 一致（V8 会校验堆的只读部分）。
+pub fn precompile(
+    name: &str,
+    code: &str,
+    cache_dir: Option<&Path>,
+    snapshot: Option<&'static [u8]>,
+    enable_extensions: bool,
+) -> Result<Vec<u8>> {
+    let path = cache_dir.map(|dir| cache_path(dir, code));
+    if let Some(data) = path.as_deref().and_then(read_cache) {
+        return Ok(data);
+    }
+
+    let _isolate = crate::runtime::IsolateGuard::acquire()?;
+    let mut runtime = JsRuntime::new(RuntimeOptions {
+        extensions: create_extensions(Rc::new(ResultStorage::new()), enable_extensions),
+        startup_snapshot: snapshot,
+        skip_op_registration: snapshot.is_some(),
+        ..Default::default()
+    });
+    let data = {
+        deno_core::scope!(scope, &mut runtime);
+
+        let name = v8::String::new(scope, name).ok_or_else(|| anyhow!("Failed to create script name"))?;
+        let source = v8::String::new(scope, code).ok_or_else(|| anyhow!("Script source is too large"))?;
+        let origin = v8::ScriptOrigin::new(scope, name.into(), 0, 0, false, 0, None, false, false, false, None);
+
+        v8::tc_scope!(let tc_scope, scope);
+
+        let mut source = v8::script_compiler::Source::new(source, Some(&origin));
+        let script = match v8::script_compiler::compile_unbound_script(
+            tc_scope,
+            &mut source,
+            v8::script_compiler::CompileOptions::EagerCompile,
+            v8::script_compiler::NoCacheReason::NoReason,
+        ) {
+            Some(script) => script,
+            None => return Err(exception_to_error(tc_scope)),
+        };
+
+        script
+            .create_code_cache()
+            .map(|data| data.to_vec())
+            .ok_or_else(|| anyhow!("Failed to create code cache"))?
+    };
+
+    if let Some(path) = &path {
+        write_cache(path, &data);
+    }
+    Ok(data)
+}
+
+/// 将 TryCatch 中捕获的异常转换为 JsError（由 format_error 统一格式化）
+pub(crate) fn exception_to_error(tc_scope: &mut v8::PinnedRef<v8::TryCatch<v8::HandleScope>>) -> anyhow::Error {
+    match tc_scope.exception() {
+        Some(exception) => (*JsError::from_v8_exception(tc_scope, exception)).into(),
+        // 没有异常对象说明执行被 terminate_execution 中断
+        None => anyhow!("execution terminated"),
+    }
+}
@@ -0,0 +1,94 @@
+// console_capture.rs - eval(capture_output=True)：收集单次调用的 console 输出
+//
+// 所有 console 方法最终都经过 op_print（quota.rs 中的 op_print_quota），
+// 捕获期间输出写入 Context 的缓冲区而不是 stdout / stderr，调用结束后作为列表返回：
+//   [{"stream": "stdout", "message": "..."}, {"stream": "stderr", "message": "..."}]
+// console.log / info / debug 等为 stdout，console.warn / error 为 stderr。
+//
+// 调用失败时捕获的输出照常写到 stdout / stderr，不会丢失。
+// register() 的 Python 函数中不能再次调用同一个 Context（抛出 RuntimeError），捕获不会嵌套。
+// call(trace_id=...) 期间写到 stdout / stderr 的输出加上 [trace_id=...] 前缀（见 trace.rs）。
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::cell::RefCell;
+use std::io::Write;
+
+/// 一条 console 输出
+pub struct ConsoleEntry {
+    pub is_err: bool,
+    pub message: String,
+}
+
+impl ConsoleEntry {
+    pub fn to_py_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("stream", if self.is_err { "stderr" } else { "stdout" })?;
+        dict.set_item("message", &self.message)?;
+        Ok(dict)
+    }
+}
+
+/// Context 的 console 输出缓冲区（放在 OpState 中，与 op_print_quota 共享）
+#[derive(Default)]
+pub struct ConsoleCapture {
+    buffer: RefCell<Option<Vec<ConsoleEntry>>>,
+    /// 当前 call(trace_id=...) 的追踪 ID
+    trace_id: RefCell<Option<String>>,
+}
+
+impl ConsoleCapture {
+    /// 开始捕获，返回外层调用的缓冲区（finish 时恢复）
+    pub fn start(&self) -> Option<Vec<ConsoleEntry>> {
+        self.buffer.replace(Some(Vec::new()))
+    }
+
+    /// 结束捕获，返回本次调用的输出
+    pub fn finish(&self, outer: Option<Vec<ConsoleEntry>>) -> Vec<ConsoleEntry> {
+        self.buffer.replace(outer).unwrap_or_default()
+    }
+
+    /// 正在捕获时记录输出并返回 true（op_print 不再写 stdout / stderr）
+    pub fn record(&self, msg: &str, is_err: bool) -> bool {
+        match self.buffer.borrow_mut().as_mut() {
+            Some(entries) => {
+                let message = msg.strip_suffix('\n').unwrap_or(msg).to_string();
+                entries.push(ConsoleEntry { is_err, message });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 设置当前的追踪 ID，返回之前的 ID
+    pub fn set_trace_id(&self, trace_id: Option<String>) -> Option<String> {
+        self.trace_id.replace(trace_id)
+    }
+
+    /// 有追踪 ID 时返回加上 [trace_id=...] 前缀的输出
+    pub fn traced(&self, msg: &str) -> Option<String> {
+        self.trace_id.borrow().as_deref().map(|trace_id| format!("[trace_id={}] {}", trace_id, msg))
+    }
+}
+
+/// 把捕获的输出写到 stdout / stderr（调用失败时使用）
+pub fn replay(entries: &[ConsoleEntry]) {
+    for entry in entries {
+        let line = format!("{}\n", entry.message);
+        let _ = if entry.is_err {
+            std::io::stderr().write_all(line.as_bytes())
+        } else {
+            std::io::stdout().write_all(line.as_bytes())
+        };
+    }
+    let _ = std::io::stdout().flush();
+}
+
+/// capture_output=True 时把结果和输出组成 (result, output)
+pub fn with_output<'py>(py: Python<'py>, result: Bound<'py, PyAny>, output: Option<Vec<ConsoleEntry>>) -> PyResult<Bound<'py, PyAny>> {
+    let Some(output) = output else {
+        return Ok(result);
+    };
+    let entries = output.iter().map(|entry| entry.to_py_dict(py)).collect::<PyResult<Vec<_>>>()?;
+    Ok((result, entries).into_pyobject(py)?.into_any())
+}
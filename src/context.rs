@@ -1,17 +1,61 @@
 use anyhow::{Result, anyhow};
-use deno_core::{JsRuntime, RuntimeOptions, error::JsError};
-use pyo3::exceptions::PyException;
+use deno_core::futures::FutureExt;
+use deno_core::{Extension, JsRuntime, RuntimeOptions, error::JsError, v8};
+use pyo3::exceptions::{PyException, PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::IntoPyObjectExt;
+use pyo3::marker::Ungil;
+use pyo3::types::{PyBytes, PyDict, PyList, PyMemoryView, PyType};
 use serde_json::Value as JsonValue;
-use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::cell::{Cell, RefCell};
+use std::mem::ManuallyDrop;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use rand::SeedableRng;
 
-use crate::convert::{json_to_python, python_to_json};
+use crate::background_compile::CompileTask;
+use crate::realm::Realm;
+use crate::shadow_realm::ShadowRealm;
+use crate::convert::{call_args_to_json, json_str_to_python, ResultFormat};
 use crate::ops;
-use crate::runtime::run_with_tokio;
+use crate::runtime::{run_with_tokio, StartupSnapshot};
+use crate::source_file::SourceMode;
+use crate::console_capture::{ConsoleCapture, ConsoleEntry};
 use crate::storage::ResultStorage;
+use crate::snapshot::OwnedBlob;
+use crate::code_cache::exception_to_error;
+use crate::harden::FREEZE_INTRINSICS;
+use crate::permissions::Permissions;
+use crate::timings::Timings;
+use crate::watchdog::{Exceeded, ExceededSlot, LimitExceeded, Limits, WatchGuard};
+use crate::audit::{AuditEntry, AuditLog};
+use crate::sandbox_fs::FsRoots;
+use crate::fs_ops::InjectedEnv;
+use crate::quota::{Quota, QuotaLimits, QuotaUsage};
+use crate::history::{History, HistoryEntry};
+use crate::slow_script::SlowScript;
+use crate::yield_points::YieldPoints;
+use crate::undefined_policy::UndefinedAs;
+use crate::gc_events::GcObserver;
+use crate::wasm::{Import as WasmImport, WasiConfig};
+use crate::wasm_memory::{MemoryViews, PinnedStore};
+use crate::shared_buffer::SharedBuffer;
+use crate::pickle::{RebuildStep, ReplayLog};
+use crate::file_watch::{FileStamp, FileWatcher};
+use crate::worker_ops::{WorkerHost, WorkerPort};
+use crate::result_stream::{stream_target, ResultStream};
+use crate::event_loop::EventLoopOptions;
+use crate::recycle::RecyclePolicy;
+use crate::state::Mode;
+use crate::timer_real_ops::SleepCancel;
+use crate::prepared::PreparedCall;
+use crate::call_cache::CallTargets;
+use crate::handles::{Handle, HandleTable, Release};
+use crate::abort::AbortSignal;
 
 // ============================================
 // 权限容器 - Web扩展需要
@@ -28,6 +72,260 @@ struct PermissionsContainer;
 //     }
 // }
 
+/// Context 构造选项
+///
+/// 集中保存构造 Context 所需的全部参数，Python 构造函数、快照加载等入口共用。
+#[derive(Clone)]
+pub struct ContextOptions {
+    /// 是否启用扩展（crypto, encoding 等）
+    pub enable_extensions: bool,
+    /// 是否启用操作日志输出
+    pub enable_logging: bool,
+    /// 随机数种子（可选）
+    pub random_seed: Option<u32>,
+    /// 启动快照（由 build_snapshot() 生成，已去掉文件头）
+    pub snapshot: Option<&'static [u8]>,
+    /// V8 代码缓存目录（可选），compile()/compile_file() 使用
+    pub code_cache_dir: Option<PathBuf>,
+    /// 初始堆大小（MB，可选）
+    pub initial_heap_mb: Option<usize>,
+    /// 最大堆大小（MB，可选），超出时终止执行而不是让进程 OOM 崩溃
+    pub max_heap_mb: Option<usize>,
+    /// 网络 / 文件 / 环境变量权限（None 表示不限制）
+    pub permissions: Option<Arc<Permissions>>,
+    /// 是否允许 eval / new Function 从字符串生成代码
+    pub allow_dynamic_code: bool,
+    /// 每次调用的墙钟超时（包括等待定时器 / Promise 的时间）
+    pub timeout_ms: Option<u64>,
+    /// 每次调用的 CPU 时间预算（只统计执行线程实际消耗的 CPU 时间）
+    pub cpu_limit_ms: Option<u64>,
+    /// 同步执行期间处理 Python 信号的间隔（Ctrl-C 可以中断死循环，见 yield_points.rs）
+    pub yield_interval_ms: Option<u64>,
+    /// 是否记录每次 op 调用（get_audit_log()）
+    pub audit_ops: bool,
+    /// Deno 风格文件 API 可访问的目录（None 表示不提供该 API）
+    pub fs_roots: Option<Arc<FsRoots>>,
+    /// 注入的环境变量，代替宿主环境变量（None 表示读取宿主环境）
+    pub env: Option<Arc<InjectedEnv>>,
+    /// 纯计算模式：不加载任何扩展，console 也不输出（同时要求 enable_extensions 为 false）
+    pub no_ops: bool,
+    /// Context 生命周期内累计的资源配额（None 表示不限制）
+    pub quotas: Option<QuotaLimits>,
+    /// 执行历史保存的最近执行次数（None 表示不记录）
+    pub history: Option<usize>,
+    /// 像跨源隔离页面一样报告 crossOriginIsolated = true（使用 SharedArrayBuffer 的脚本会检查它）
+    pub shared_memory: bool,
+    /// 定义为全局 SharedArrayBuffer 的共享内存（名称, 内存）
+    pub shared_buffers: Vec<(String, PinnedStore)>,
+    /// 是否提供 WeakRef / FinalizationRegistry（为 false 时从全局对象中删除）
+    pub weak_refs: bool,
+    /// 遇到致命错误（超出 max_heap_mb、panic）后自动重建 isolate，而不是保持 poisoned（poison.rs）
+    pub auto_recreate: bool,
+    /// Realm / ShadowRealm / prepare() 函数 / call() 缓存函数的总数上限（None 表示不限制，见 handles.rs）
+    pub max_handles: Option<usize>,
+    /// 结果中 undefined 的转换策略（undefined_policy.rs）
+    pub undefined_as: UndefinedAs,
+    /// 作为 Worker 运行时与父 Context 通信的通道（worker_ops.rs，由 new Worker() 设置）
+    pub(crate) worker: Option<Arc<WorkerPort>>,
+}
+
+impl Default for ContextOptions {
+    fn default() -> Self {
+        Self {
+            enable_extensions: true,
+            enable_logging: false,
+            random_seed: None,
+            snapshot: None,
+            code_cache_dir: None,
+            initial_heap_mb: None,
+            max_heap_mb: None,
+            permissions: None,
+            allow_dynamic_code: true,
+            timeout_ms: None,
+            cpu_limit_ms: None,
+            yield_interval_ms: None,
+            audit_ops: false,
+            fs_roots: None,
+            env: None,
+            no_ops: false,
+            quotas: None,
+            history: None,
+            shared_memory: false,
+            shared_buffers: Vec::new(),
+            weak_refs: true,
+            auto_recreate: false,
+            max_handles: None,
+            undefined_as: UndefinedAs::Json,
+            worker: None,
+        }
+    }
+}
+
+impl ContextOptions {
+    /// 设置堆大小限制（校验参数）
+    pub(crate) fn with_heap_limits(mut self, initial_heap_mb: Option<usize>, max_heap_mb: Option<usize>) -> PyResult<Self> {
+        if initial_heap_mb == Some(0) || max_heap_mb == Some(0) {
+            return Err(PyValueError::new_err("Heap size must be at least 1 MB"));
+        }
+        if let (Some(initial), Some(max)) = (initial_heap_mb, max_heap_mb) {
+            if initial > max {
+                return Err(PyValueError::new_err(format!(
+                    "initial_heap_mb ({}) must not exceed max_heap_mb ({})",
+                    initial, max
+                )));
+            }
+        }
+        self.initial_heap_mb = initial_heap_mb;
+        self.max_heap_mb = max_heap_mb;
+        Ok(self)
+    }
+
+    /// 设置权限（Python 字典或 "all"，None 使用默认权限，见 permissions.rs）
+    pub(crate) fn with_permissions(mut self, permissions: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        self.permissions = permissions.map(Permissions::from_arg).transpose()?.map(Arc::new);
+        Ok(self)
+    }
+
+    /// 设置 Deno 风格文件 API 可访问的目录（校验目录存在）
+    pub(crate) fn with_fs_roots(mut self, fs_roots: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let Some(fs_roots) = fs_roots else {
+            return Ok(self);
+        };
+        if !self.enable_extensions {
+            return Err(PyValueError::new_err("fs_roots requires enable_extensions=True"));
+        }
+        self.fs_roots = Some(Arc::new(FsRoots::from_py(fs_roots)?));
+        Ok(self)
+    }
+
+    /// 设置注入的环境变量（{名称: 值}，值必须是字符串）
+    pub(crate) fn with_env(mut self, env: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let Some(env) = env else {
+            return Ok(self);
+        };
+        if !self.enable_extensions {
+            return Err(PyValueError::new_err("env requires enable_extensions=True"));
+        }
+        let vars = env
+            .iter()
+            .map(|(name, value)| {
+                Ok((
+                    name.extract().map_err(|_| PyTypeError::new_err("env names must be strings"))?,
+                    value.extract().map_err(|_| PyTypeError::new_err("env values must be strings"))?,
+                ))
+            })
+            .collect::<PyResult<Vec<(String, String)>>>()?;
+        self.env = Some(Arc::new(InjectedEnv(vars)));
+        Ok(self)
+    }
+
+    /// 设置执行时间限制（校验参数）
+    pub(crate) fn with_time_limits(mut self, timeout_ms: Option<u64>, cpu_limit_ms: Option<u64>) -> PyResult<Self> {
+        if timeout_ms == Some(0) || cpu_limit_ms == Some(0) {
+            return Err(PyValueError::new_err("timeout_ms and cpu_limit_ms must be at least 1"));
+        }
+        if cpu_limit_ms.is_some() && !crate::watchdog::cpu_limit_supported() {
+            return Err(PyValueError::new_err("cpu_limit_ms is not supported on this platform"));
+        }
+        self.timeout_ms = timeout_ms;
+        self.cpu_limit_ms = cpu_limit_ms;
+        Ok(self)
+    }
+
+    /// 设置处理 Python 信号的间隔（校验参数）
+    pub(crate) fn with_yield_interval(mut self, yield_interval_ms: Option<u64>) -> PyResult<Self> {
+        if yield_interval_ms == Some(0) {
+            return Err(PyValueError::new_err("yield_interval_ms must be at least 1"));
+        }
+        self.yield_interval_ms = yield_interval_ms;
+        Ok(self)
+    }
+
+    /// 设置结果中 undefined 的转换策略（校验参数）
+    pub(crate) fn with_undefined_as(mut self, undefined_as: Option<&str>) -> PyResult<Self> {
+        self.undefined_as = UndefinedAs::from_py(undefined_as)?;
+        Ok(self)
+    }
+
+    /// 设置资源配额（Python 字典，None 表示不限制）
+    pub(crate) fn with_quotas(mut self, quotas: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let quotas = quotas.map(QuotaLimits::from_py).transpose()?;
+        if quotas.is_some_and(|q| q.max_cpu_ms.is_some()) && !crate::watchdog::cpu_limit_supported() {
+            return Err(PyValueError::new_err("quotas['max_cpu_ms'] is not supported on this platform"));
+        }
+        self.quotas = quotas;
+        Ok(self)
+    }
+
+    /// 设置执行历史容量（校验参数）
+    pub(crate) fn with_history(mut self, history: Option<usize>) -> PyResult<Self> {
+        if history == Some(0) {
+            return Err(PyValueError::new_err("history must be at least 1"));
+        }
+        self.history = history;
+        Ok(self)
+    }
+
+    /// 设置共享内存（shared_buffers 为 {名称: SharedBuffer}）
+    pub(crate) fn with_shared_memory(mut self, shared_memory: bool, shared_buffers: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        self.shared_memory = shared_memory;
+        self.shared_buffers = crate::shared_buffer::from_py(shared_buffers)?;
+        Ok(self)
+    }
+
+    /// 执行时间限制
+    fn limits(&self) -> Limits {
+        Limits {
+            timeout: self.timeout_ms.map(Duration::from_millis),
+            cpu_limit: self.cpu_limit_ms.map(Duration::from_millis),
+            cpu_quota: None,
+            slow: None,
+            yield_interval: self.yield_interval_ms.map(Duration::from_millis),
+        }
+    }
+
+    /// 根据堆大小限制生成 V8 CreateParams
+    fn create_params(&self) -> Option<v8::CreateParams> {
+        if self.initial_heap_mb.is_none() && self.max_heap_mb.is_none() {
+            return None;
+        }
+        let initial = self.initial_heap_mb.unwrap_or(0) * MB;
+        // 只设置初始大小时，最大值不小于初始值
+        let max = self.max_heap_mb.map(|mb| mb * MB).unwrap_or_else(|| initial.max(DEFAULT_MAX_HEAP_MB * MB));
+        Some(v8::CreateParams::default().heap_limits(initial, max))
+    }
+}
+
+const MB: usize = 1024 * 1024;
+
+/// 只设置 initial_heap_mb 时使用的最大堆大小
+const DEFAULT_MAX_HEAP_MB: usize = 4096;
+
+/// 接近堆上限时临时放宽的最小空间（终止执行后异常展开需要的内存）
+const HEAP_LIMIT_HEADROOM: usize = 16 * MB;
+
+/// 注册接近堆上限的回调：终止执行，并只放宽到足够异常展开的上限（当前上限的 1/4，至少 16 MB）
+///
+/// raised 记录第一次放宽之前的上限，执行结束后由 Context::restore_heap_limit() 恢复。
+fn install_heap_limit_callback(
+    runtime: &mut JsRuntime,
+    handle: &v8::IsolateHandle,
+    reached: &Rc<Cell<bool>>,
+    raised: &Rc<Cell<Option<usize>>>,
+) {
+    let handle = handle.clone();
+    let reached = reached.clone();
+    let raised = raised.clone();
+    runtime.add_near_heap_limit_callback(move |current_limit, _initial_limit| {
+        reached.set(true);
+        handle.terminate_execution();
+        if raised.get().is_none() {
+            raised.set(Some(current_limit));
+        }
+        current_limit + (current_limit / 4).max(HEAP_LIMIT_HEADROOM)
+    });
+}
+
 /// JavaScript 执行上下文
 ///
 /// 每个 Context 包含一个独立的 V8 isolate 和 JavaScript 运行时环境。
@@ -39,20 +337,698 @@ struct PermissionsContainer;
 /// ctx.eval("function add(a, b) { return a + b; }")
 /// result = ctx.call("add", [1, 2])
 /// ```
-#[pyclass(unsendable)]
+#[pyclass(unsendable, module = "never_jscore")]  // module: pickle 按 never_jscore.Context 查找类
 pub struct Context {
-    runtime: RefCell<JsRuntime>,
+    runtime: ManuallyDrop<RefCell<JsRuntime>>,  // Dropped manually (leaked when inherited across fork)
+    _isolate: crate::runtime::IsolateGuard,     // Counted by shutdown(); released after the runtime
+    snapshot_owner: Option<Arc<OwnedBlob>>,  // Freeable startup snapshot the isolate was created from; released after the runtime
     result_storage: Rc<ResultStorage>,
     exec_count: RefCell<usize>,
     extensions_loaded: bool,
     logging_enabled: bool,
     polyfill_loaded: RefCell<bool>,  // Track if polyfill has been loaded
     random_seed: Option<u32>,  // Store seed for deferred initialization
+    snapshot: Option<&'static [u8]>,  // Startup snapshot: polyfill and user code already live in it
+    code_cache_dir: Option<PathBuf>,  // Persistent V8 code cache for compile()/compile_file()
+    eval_wrappers: RefCell<Option<EvalWrappers>>,  // Precompiled eval wrappers, compiled on first use
+    call_targets: CallTargets,  // call(): resolved functions by name, cleared when a global script runs
+    handle_table: HandleTable,  // handles(): Realms, ShadowRealms and prepare() functions, capped by max_handles
+    background_event_loop: RefCell<bool>,  // Timers keep running between calls (ThreadedContext only)
+    event_loop_errors: RefCell<Vec<String>>,  // Errors raised while pumping the event loop in the background
+    event_loop: Cell<EventLoopOptions>,  // Event loop options of the call in progress (event_loop={...})
+    fork_generation: usize,  // Fork generation at creation, see fork.rs
+    max_heap_mb: Option<usize>,
+    heap_limit_reached: Rc<Cell<bool>>,  // Set by the near-heap-limit callback before terminating execution
+    heap_limit_raised: Rc<Cell<Option<usize>>>,  // Heap limit before the callback raised it, restored after the execution
+    timings: Cell<Timings>,  // Stage durations of the call in progress
+    last_timings: Cell<Timings>,
+    total_timings: Cell<Timings>,
+    allow_dynamic_code: bool,  // False: eval / new Function are disabled (evaluate() runs code as a script)
+    no_ops: bool,  // Pure-compute mode: no extension ops, console is V8's silent built-in
+    isolate_handle: v8::IsolateHandle,
+    limits: Limits,  // timeout_ms / cpu_limit_ms, enforced by the watchdog thread
+    limit_exceeded: ExceededSlot,  // Set by the watchdog before terminating execution
+    audit_log: Option<Rc<AuditLog>>,  // Op invocations of the current call (audit_ops=True), shared with OpState
+    quota: Option<Rc<Quota>>,  // Lifetime resource quotas (quotas={...}), shared with OpState for console output
+    console_capture: Rc<ConsoleCapture>,  // Console output of the current eval(capture_output=True), shared with OpState
+    sleeps: Rc<SleepCancel>,  // Pending op_sleep() calls, interrupted when execution is terminated; shared with OpState
+    history: Option<History>,  // Ring buffer of the last N executions (history=N)
+    slow_script: Rc<SlowScript>,  // on_slow_script() callback, shared with the isolate slot for the watchdog interrupt
+    yield_points: Rc<YieldPoints>,  // Exception raised by a Python signal handler at a yield point (yield_interval_ms)
+    gc_observer: Box<GcObserver>,  // on_gc() callback; boxed because its address is the GC callbacks' data pointer
+    wasm_views: MemoryViews,  // memoryviews handed out by wasm_memory(); pins their backing stores
+    replay: RefCell<ReplayLog>,  // Global scripts executed so far, replayed into a snapshot by pickle / deepcopy
+    watched: RefCell<FileWatcher>,  // compile_file(watch=True): recompiled before the next call when modified
+    shared_memory: bool,  // crossOriginIsolated = true (shared_memory=True)
+    shared_buffers: Vec<(String, PinnedStore)>,  // SharedBuffers defined as globals before user code runs
+    weak_refs: bool,  // False: WeakRef / FinalizationRegistry are removed from the global object
+    undefined_as: UndefinedAs,  // undefined_as=...: how undefined in results is converted, applied by the result serializer
+    worker: bool,  // running inside a Worker thread: install the worker global scope
+    recycle: Cell<Option<RecyclePolicy>>,  // compile(recycle_after={...}): rebuild the isolate when a threshold is reached
+    recycle_calls: Cell<u64>,  // JS executions in the current isolate, checked against recycle_after["calls"]
+    result_filter: RefCell<Option<String>>,  // compile(result_filter=...): JS function applied to every result before serialization
+    import_meta: RefCell<Option<Py<PyAny>>>,  // on_import_meta(callback): import.meta fields of each loaded ES module
+    trace_id: RefCell<Option<String>>,  // call(trace_id=...): trace ID of the call in progress
+    abort_signal: RefCell<Option<Arc<AbortSignal>>>,  // Abort signal of the asyncio call in progress, wakes the event loop wait
+    auto_recreate: bool,  // auto_recreate=True: rebuild the isolate after a fatal error instead of staying poisoned
+    poisoned: RefCell<Option<String>>,  // Reason of the fatal error that made the isolate unusable (poison.rs)
+    panicked: Cell<bool>,  // A panic unwound through the isolate: it may still be entered, so it is leaked on drop
+}
+
+/// 一次正在进行的执行（执行钩子和执行历史）
+pub(crate) struct Tracking {
+    hooks: Option<crate::exec_hooks::Execution>,
+    history: Option<crate::history::Pending>,
+}
+
+/// 预编译的求值包装函数
+///
+/// 包装函数只编译一次，之后每次求值把代码作为字符串参数传入，
+/// 不再需要为每次调用拼接、转义和重新解析整段包装脚本。
+struct EvalWrappers {
+    sync: v8::Global<v8::Function>,
+    async_: v8::Global<v8::Function>,
+    /// repl_eval() 的结果赋值函数（把完成值赋给全局 _）
+    repl: v8::Global<v8::Function>,
 }
 
 // JavaScript polyfill 代码
 const JS_POLYFILL: &str = include_str!("dddd_js/js_polyfill.js");
 
+// 结果序列化：JSON.stringify，无法序列化时转换为字符串
+//
+// Temporal 对象的 toJSON 只返回 ISO 字符串，序列化对象结果期间把它们的 toJSON 临时换成
+// 带类型标记（TEMPORAL_TAG）的对象，convert.rs 据此转换为 datetime / date / time / timedelta。
+// 原始值结果不需要替换，不增加开销。PlainYearMonth / PlainMonthDay 没有对应的 Python 类型，保持 ISO 字符串。
+//
+// 返回的函数带有 stream(value, emit)：与 JSON.stringify 输出相同的分块序列化（evaluate(stream_to=...)），
+// 每凑满 CHUNK 个字符调用一次 emit，不生成完整的字符串（参见 result_stream.rs）。
+//
+// 脚本是一个工厂函数，由 Rust 传入 Context 的 undefined_as（见 undefined_policy.rs，默认为 undefined）：
+// 设置时序列化带上替换 undefined 的 replacer，toJson.undefined 是顶层结果为 undefined 时存储的 JSON。
+const RESULT_TO_JSON: &str = r#"
+(function(undefinedAs) {
+    const TAG = '__never_jscore_temporal__';
+    const T = typeof Temporal === 'object' && Temporal !== null ? Temporal : null;
+    const fields = v => ({
+        year: v.year, month: v.month, day: v.day,
+        hour: v.hour, minute: v.minute, second: v.second,
+        microsecond: v.millisecond * 1000 + v.microsecond,
+    });
+    const encoders = T ? [
+        ['Instant', T.Instant, v => ({ epochNanoseconds: String(v.epochNanoseconds) })],
+        ['ZonedDateTime', T.ZonedDateTime, v => ({
+            ...fields(v.withCalendar('iso8601')), timeZone: v.timeZoneId, offsetNanoseconds: v.offsetNanoseconds,
+        })],
+        ['PlainDateTime', T.PlainDateTime, v => fields(v.withCalendar('iso8601'))],
+        ['PlainDate', T.PlainDate, v => {
+            const d = v.withCalendar('iso8601');
+            return { year: d.year, month: d.month, day: d.day };
+        }],
+        ['PlainTime', T.PlainTime, v => ({
+            hour: v.hour, minute: v.minute, second: v.second, microsecond: v.millisecond * 1000 + v.microsecond,
+        })],
+        ['Duration', T.Duration, v => ({
+            iso: v.toString(), years: v.years, months: v.months, weeks: v.weeks, days: v.days,
+            hours: v.hours, minutes: v.minutes, seconds: v.seconds,
+            milliseconds: v.milliseconds, microseconds: v.microseconds,
+        })],
+    ] : [];
+    const patched = encoders
+        .filter(([, type]) => typeof type === 'function')
+        .map(([name, type, encode]) => [type.prototype, function() { return { [TAG]: name, ...encode(this) }; }]);
+
+    const withTemporal = (value, serialize) => {
+        const swap = patched.length > 0 && value !== null && typeof value === 'object';
+        const saved = swap ? patched.map(([proto, toJSON]) => { const old = proto.toJSON; proto.toJSON = toJSON; return old; }) : null;
+        try {
+            return serialize(value);
+        } finally {
+            if (saved) patched.forEach(([proto], i) => { proto.toJSON = saved[i]; });
+        }
+    };
+
+    const CHUNK = 1 << 20;
+    const skipped = v => v === undefined || typeof v === 'function' || typeof v === 'symbol';
+
+    // undefined 的替代值（undefined 表示保持 JSON.stringify 的语义）；omit 时从数组中删除
+    const UNDEFINED = { __never_jscore_undefined__: true };
+    const omit = undefinedAs === 'omit';
+    const missing = undefinedAs === 'none' ? null : undefinedAs === 'sentinel' ? UNDEFINED : undefined;
+    const replacer = missing !== undefined ? (key, v) => v === undefined ? missing : v
+        : omit ? (key, v) => Array.isArray(v) ? v.filter(item => item !== undefined) : v
+        : undefined;
+    const replace = v => v === undefined && missing !== undefined ? missing : v;
+
+    function stream(value, emit) {
+        const parts = [];
+        const stack = [];
+        let size = 0;
+        const push = s => {
+            parts.push(s);
+            size += s.length;
+            if (size >= CHUNK) flush();
+        };
+        const flush = () => {
+            if (parts.length === 0) return;
+            emit(parts.join(''));
+            parts.length = 0;
+            size = 0;
+        };
+        const prepare = (key, v) => {
+            if (v !== null && (typeof v === 'object' || typeof v === 'bigint') && typeof v.toJSON === 'function') {
+                v = v.toJSON(key);
+            }
+            if (v instanceof Number) return Number(v);
+            if (v instanceof String) return String(v);
+            if (v instanceof Boolean) return v.valueOf();
+            return v;
+        };
+        // 长字符串分段转义，不在代理对中间切开
+        const writeString = s => {
+            if (s.length <= CHUNK) return push(JSON.stringify(s));
+            push('"');
+            for (let i = 0; i < s.length;) {
+                let end = Math.min(i + CHUNK, s.length);
+                const last = s.charCodeAt(end - 1);
+                if (end < s.length && last >= 0xD800 && last <= 0xDBFF) end--;
+                push(JSON.stringify(s.slice(i, end)).slice(1, -1));
+                i = end;
+            }
+            push('"');
+        };
+        const write = v => {
+            switch (typeof v) {
+                case 'string': return writeString(v);
+                case 'number': return push(Number.isFinite(v) ? String(v) : 'null');
+                case 'boolean': return push(v ? 'true' : 'false');
+                case 'bigint': throw new TypeError('Do not know how to serialize a BigInt');
+            }
+            if (v === null) return push('null');
+            if (stack.includes(v)) throw new TypeError('Converting circular structure to JSON');
+            stack.push(v);
+            if (Array.isArray(v)) {
+                push('[');
+                for (let i = 0, n = 0; i < v.length; i++) {
+                    if (omit && v[i] === undefined) continue;
+                    if (n++ > 0) push(',');
+                    const item = replace(prepare(String(i), v[i]));
+                    skipped(item) ? push('null') : write(item);
+                }
+                push(']');
+            } else {
+                push('{');
+                let first = true;
+                for (const key of Object.keys(v)) {
+                    const item = replace(prepare(key, v[key]));
+                    if (skipped(item)) continue;
+                    push(first ? '' : ',');
+                    first = false;
+                    writeString(key);
+                    push(':');
+                    write(item);
+                }
+                push('}');
+            }
+            stack.pop();
+        };
+
+        withTemporal(value, v => {
+            const item = replace(prepare('', v));
+            skipped(item) ? push('null') : write(item);
+        });
+        flush();
+    }
+
+    const toJson = function(value) {
+        try {
+            return withTemporal(value, v => JSON.stringify(v, replacer));
+        } catch (e) {
+            return JSON.stringify(String(value));
+        }
+    };
+    toJson.stream = stream;
+    toJson.undefined = JSON.stringify(replace(undefined)) ?? 'null';
+    return toJson;
+})
+"#;
+
+// 同步求值包装：直接 eval 代码并存储结果
+//
+// 在函数内使用直接 eval，与原先的 IIFE 包装语义一致（let/const/var 不泄漏到全局）。
+// 执行 eval 的函数在脚本顶层定义，作用域链上只有全局作用域：
+// 用户代码看不到包装函数的 ops / toJson / checkSchema / callId，也不会被它们遮蔽同名的全局变量。
+// callId 由 ResultStorage 分配，结果按 ID 存储。
+// isValue 为 true 时 code 是已经求出的值（禁止动态代码时由 Rust 侧执行脚本得到）
+// __stream 为 true 时结果分块经 op_store_result_chunk 写出（stream_to=...），最后存储空字符串表示完成
+// __filter 是 compile(result_filter=...) 指定的函数（未设置时为 undefined），序列化之前作用于结果
+// __schema 是 call(expect_schema=...) 的规则（JSON 文本，未设置时为 undefined），过滤之后检查结果（见 schema.rs）
+//
+// 脚本返回工厂函数，由 Rust 传入 Deno.core.ops、RESULT_TO_JSON 和 schema::CHECK_SCHEMA 生成包装函数：
+// ops 只存在于闭包中，用户代码无法调用 op_store_result 篡改结果。
+const EVAL_WRAPPER_SYNC: &str = r#"
+(function(evaluate) {
+    return function(ops, toJson, checkSchema) {
+        return function(code, callId, isValue, __stream, __filter, __schema) {
+            let __result = isValue ? code : evaluate(code);
+            if (__filter) __result = __filter(__result);
+            if (__schema !== undefined) {
+                const mismatches = checkSchema(__result, __schema);
+                if (mismatches.length) {
+                    ops.op_store_schema_mismatch(callId, JSON.stringify(mismatches));
+                    return;
+                }
+            }
+            if (__result === undefined) {
+                ops.op_store_result(callId, toJson.undefined);
+                return;
+            }
+            if (__stream) {
+                toJson.stream(__result, chunk => ops.op_store_result_chunk(callId, chunk));
+                ops.op_store_result(callId, "");
+                return;
+            }
+            ops.op_store_result(callId, toJson(__result));
+        };
+    };
+})(function() { return eval(arguments[0]); })
+"#;
+
+// 异步求值包装：等待 Promise 后存储结果
+//
+// 包装函数本身不返回 Promise，rejection 作为未处理的 Promise 错误由 event loop 报告
+const EVAL_WRAPPER_ASYNC: &str = r#"
+(function(evaluate) {
+    return function(ops, toJson, checkSchema) {
+        return function(code, callId, isValue, __stream, __filter, __schema) {
+            (async function() {
+                let __result = await Promise.resolve(isValue ? code : evaluate(code));
+                if (__filter) __result = await __filter(__result);
+                if (__schema !== undefined) {
+                    const mismatches = checkSchema(__result, __schema);
+                    if (mismatches.length) {
+                        ops.op_store_schema_mismatch(callId, JSON.stringify(mismatches));
+                        return;
+                    }
+                }
+
+                if (__result === undefined) {
+                    ops.op_store_result(callId, toJson.undefined);
+                    return;
+                }
+
+                if (__stream) {
+                    toJson.stream(__result, chunk => ops.op_store_result_chunk(callId, chunk));
+                    ops.op_store_result(callId, "");
+                    return;
+                }
+
+                ops.op_store_result(callId, toJson(__result));
+            })();
+        };
+    };
+})(function() { return eval(arguments[0]); })
+"#;
+
+// repl_eval() 的结果赋值：把完成值（auto_await 时为 Promise 的结果）赋给全局 _
+//
+// 与 Node REPL 一样，用户自己定义了 _（或加载了占用 _ 的库）之后不再覆盖。
+// 返回的值交给求值包装函数存储；异步模式下返回的 Promise 由包装函数等待。
+const REPL_ASSIGN: &str = r#"
+(function(ops) {
+    let assigned;
+    function assign(value) {
+        if (!('_' in globalThis) || globalThis._ === assigned) {
+            globalThis._ = value;
+            assigned = value;
+        }
+        return value;
+    }
+    return function(value, awaitValue) {
+        if (awaitValue && value !== null && (typeof value === 'object' || typeof value === 'function')
+            && typeof value.then === 'function') {
+            return Promise.resolve(value).then(assign);
+        }
+        return assign(value);
+    };
+})
+"#;
+
+// shared_memory=True：像跨源隔离（COOP / COEP）的页面一样报告 crossOriginIsolated，
+// emscripten 的 pthread 构建等脚本检查它之后才使用 SharedArrayBuffer / Atomics
+const SHARED_MEMORY: &str = r#"
+Object.defineProperty(globalThis, 'crossOriginIsolated', {
+    value: true, writable: false, enumerable: true, configurable: true,
+});
+"#;
+
+// weak_refs=False：删除 WeakRef / FinalizationRegistry，库会走不使用弱引用的回退路径
+const HIDE_WEAK_REFS: &str = "delete globalThis.WeakRef; delete globalThis.FinalizationRegistry;";
+
+// 作为 Worker 运行时把全局作用域换成 Worker 的全局作用域（polyfill 中登记，见 worker_ops.rs）
+const WORKER_SCOPE: &str = r#"
+globalThis.__never_jscore_worker_scope__();
+"#;
+
+// 隐藏全局 Deno 和 deno_core 的 __bootstrap（其中也有 Deno.core）
+//
+// 每个 Context 在用户代码执行之前运行一次：快照中保留了 Deno，加载后才能隐藏。
+// polyfill 登记了自己的隐藏函数（保留 fs_roots 的文件 API），纯净环境直接定义为 undefined。
+const HIDE_DENO: &str = r#"
+(function() {
+    const hide = globalThis.__never_jscore_hide_deno__;
+    delete globalThis.__never_jscore_hide_deno__;
+    delete globalThis.__never_jscore_worker_scope__;
+    delete globalThis.__bootstrap;
+    if (typeof hide === 'function') {
+        hide();
+        return;
+    }
+    delete globalThis.Deno;
+    Object.defineProperty(globalThis, 'Deno', {
+        value: undefined,
+        writable: false,
+        enumerable: false,
+        configurable: false
+    });
+})();
+"#;
+
+// no_ops=True：把 deno_core 的 console（通过 op_print 写 stdout）换回 V8 内置的 console，
+// 没有连接 inspector 时它的方法什么也不做
+const SILENCE_CONSOLE: &str = r#"
+globalThis.console = Deno.core.console;
+"#;
+
+/// 保存 Deno.core.ops 的私有属性名（v8::Private，JS 代码无法读取）
+const CORE_OPS_KEY: &str = "never_jscore.core_ops";
+
+/// 把模块的具名导出定义为全局变量
+fn export_module_globals(runtime: &mut JsRuntime, id: deno_core::ModuleId) -> Result<()> {
+    let namespace = runtime
+        .get_module_namespace(id)
+        .map_err(|e| anyhow!("{}", format_error(e.into())))?;
+    deno_core::scope!(scope, runtime);
+    let namespace = v8::Local::new(scope, namespace);
+    let global = scope.get_current_context().global(scope);
+
+    let names = namespace
+        .get_own_property_names(scope, Default::default())
+        .ok_or_else(|| anyhow!("Failed to read module exports"))?;
+    for i in 0..names.length() {
+        let Some(key) = names.get_index(scope, i) else { continue };
+        if key.to_rust_string_lossy(scope) == "default" {
+            continue;
+        }
+        if let Some(value) = namespace.get(scope, key) {
+            global.set(scope, key, value);
+        }
+    }
+    Ok(())
+}
+
+/// 构造函数调用代码 `name(arg1, arg2, ...)`
+///
+/// 参数以 JSON 字面量形式拼接，name 可以是任意可调用表达式（如 `obj.method`）。
+pub(crate) fn format_call(name: &str, args: &[JsonValue]) -> String {
+    let args_str = args
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{}({})", name, args_str)
+}
+
+/// Context 的执行统计，见 Context.get_stats()
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ContextStats {
+    pub exec_count: usize,
+    pub total: Timings,
+    pub last: Timings,
+}
+
+/// 把执行统计转换为 get_stats() 的返回值
+pub(crate) fn stats_to_python<'py>(py: Python<'py>, stats: &ContextStats, timings: bool) -> PyResult<Bound<'py, PyAny>> {
+    if !timings {
+        return Ok((stats.exec_count,).into_pyobject(py)?.into_any());
+    }
+    let dict = PyDict::new(py);
+    dict.set_item("exec_count", stats.exec_count)?;
+    dict.set_item("total", stats.total.to_py_dict(py)?)?;
+    dict.set_item("last", stats.last.to_py_dict(py)?)?;
+    Ok(dict.into_any())
+}
+
+/// 绑定在当前线程 isolate 上的状态（Context 本身、重建的 Context、V8 句柄）
+///
+/// 只有这些类型可以通过 [`IsolateLocal`] 带过 `py.detach`：detach 的闭包在当前线程上同步执行，
+/// 它们不会被移动到其他线程。Python 对象和其他值不实现此 trait，仍然受 Ungil 约束。
+///
+/// # Safety
+///
+/// 实现的类型不能包含 Python 对象（`Bound` / `Python` 等需要 GIL 的值）。
+pub(crate) unsafe trait IsolateBound {}
+
+unsafe impl IsolateBound for &Context {}
+unsafe impl IsolateBound for Option<Context> {}
+unsafe impl IsolateBound for Result<Context> {}
+unsafe impl<T> IsolateBound for &v8::Global<T> {}
+
+/// 嵌套调用同一个 Context 时抛出的错误
+fn nested_call_error() -> PyErr {
+    PyRuntimeError::new_err(
+        "Context is already executing JavaScript: it cannot be called again from a register() callback",
+    )
+}
+
+/// [`Context::without_gil`] 遇到嵌套调用时代替闭包返回的值
+pub(crate) trait NestedCall {
+    fn nested_call() -> Self;
+}
+
+impl<T> NestedCall for Result<T> {
+    fn nested_call() -> Self {
+        Err(anyhow::Error::from(nested_call_error()))
+    }
+}
+
+impl NestedCall for Option<Context> {
+    fn nested_call() -> Self {
+        None
+    }
+}
+
+impl<T: IsolateBound + NestedCall> NestedCall for IsolateLocal<T> {
+    fn nested_call() -> Self {
+        IsolateLocal::new(T::nested_call())
+    }
+}
+
+/// 在释放 GIL 期间传递 [`IsolateBound`] 的值
+pub(crate) struct IsolateLocal<T: IsolateBound>(T);
+
+unsafe impl<T: IsolateBound> Send for IsolateLocal<T> {}
+
+impl<T: IsolateBound> IsolateLocal<T> {
+    pub(crate) fn new(value: T) -> Self {
+        IsolateLocal(value)
+    }
+
+    /// 通过方法取出内部值，确保闭包捕获整个包装而不是其中的字段
+    pub(crate) fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// 把代码包在块语句中作为脚本执行，返回完成值（禁止动态代码时代替包装函数中的 eval）
+///
+/// 块语句让 let/const/class 声明留在块作用域内，与 eval 包装一样不泄漏到全局；
+/// 但 var 和函数声明会成为全局变量。
+/// repl 为 true 时（repl_eval()）不加块语句，let/const/class 声明保留到后续的输入中。
+fn evaluate_as_script(runtime: &mut JsRuntime, code: &str, repl: bool) -> Result<v8::Global<v8::Value>> {
+    deno_core::scope!(scope, runtime);
+    // "{" 与代码放在同一行，错误行号保持不变
+    let (source, name) = if repl {
+        (v8::String::new(scope, code), "<repl>")
+    } else {
+        (v8::String::new(scope, &format!("{{{}\n}}", code)), "<eval>")
+    };
+    let source = source.ok_or_else(|| anyhow!("Code is too large"))?;
+    let name = v8::String::new(scope, name).ok_or_else(|| anyhow!("Failed to create script name"))?;
+    let origin = v8::ScriptOrigin::new(scope, name.into(), 0, 0, false, 0, None, false, false, false, None);
+
+    v8::tc_scope!(let tc_scope, scope);
+    let script = v8::Script::compile(tc_scope, source, Some(&origin)).ok_or_else(|| exception_to_error(tc_scope))?;
+    let value = script.run(tc_scope).ok_or_else(|| exception_to_error(tc_scope))?;
+    Ok(v8::Global::new(tc_scope, value))
+}
+
+/// 求值 compile(result_filter=...) 指定的名称，返回过滤函数（交给求值包装函数）
+fn resolve_result_filter(runtime: &mut JsRuntime, name: &str) -> Result<v8::Global<v8::Value>> {
+    deno_core::scope!(scope, runtime);
+    let source = v8::String::new(scope, name).ok_or_else(|| anyhow!("Failed to create string"))?;
+    v8::tc_scope!(let tc_scope, scope);
+    let value = v8::Script::compile(tc_scope, source, None)
+        .and_then(|script| script.run(tc_scope))
+        .ok_or_else(|| exception_to_error(tc_scope))?;
+    if !value.is_function() {
+        return Err(anyhow!("result_filter '{}' is not a function", name));
+    }
+    Ok(v8::Global::new(tc_scope, value))
+}
+
+/// 把共享内存定义为全局 SharedArrayBuffer（Context(shared_buffers=...) / share_buffer()）
+fn define_shared_buffer(runtime: &mut JsRuntime, name: &str, store: &PinnedStore) -> Result<()> {
+    deno_core::scope!(scope, runtime);
+    let key = v8::String::new(scope, name).ok_or_else(|| anyhow!("Failed to create string"))?;
+    let buffer = store.to_shared_array_buffer(scope);
+    let global = scope.get_current_context().global(scope);
+    global
+        .set(scope, key.into(), buffer.into())
+        .filter(|ok| *ok)
+        .ok_or_else(|| anyhow!("Failed to define shared buffer '{}'", name))?;
+    Ok(())
+}
+
+/// 调用 REPL_ASSIGN 生成的函数，把完成值赋给全局 _，返回交给求值包装函数的值
+fn assign_repl_result(
+    runtime: &mut JsRuntime,
+    assign: &v8::Global<v8::Function>,
+    value: v8::Global<v8::Value>,
+    auto_await: bool,
+) -> Result<v8::Global<v8::Value>> {
+    deno_core::scope!(scope, runtime);
+    let assign = v8::Local::new(scope, assign);
+    let value = v8::Local::new(scope, value);
+    let await_value = v8::Boolean::new(scope, auto_await);
+    let undefined = v8::undefined(scope);
+
+    v8::tc_scope!(let tc_scope, scope);
+    let result = assign
+        .call(tc_scope, undefined.into(), &[value, await_value.into()])
+        .ok_or_else(|| exception_to_error(tc_scope))?;
+    Ok(v8::Global::new(tc_scope, result))
+}
+
+/// 编译包装脚本，传入 Deno.core.ops 和结果序列化函数生成包装函数并取出函数句柄
+fn compile_wrapper(
+    runtime: &mut JsRuntime,
+    name: &'static str,
+    source: &'static str,
+    to_json: &v8::Global<v8::Value>,
+    check_schema: &v8::Global<v8::Value>,
+) -> Result<v8::Global<v8::Function>> {
+    let value = runtime
+        .execute_script(name, source)
+        .map_err(|e| anyhow!("Failed to compile eval wrapper: {}", format_error(e.into())))?;
+
+    deno_core::scope!(scope, runtime);
+    let factory = v8::Local::<v8::Function>::try_from(v8::Local::new(scope, value))
+        .map_err(|_| anyhow!("Eval wrapper is not a function"))?;
+    let global = scope.get_current_context().global(scope);
+    let key = core_ops_key(scope);
+    let ops = global
+        .get_private(scope, key)
+        .filter(|ops| ops.is_object())
+        .ok_or_else(|| anyhow!("Deno.core.ops was not captured before user code ran"))?;
+    let to_json = v8::Local::new(scope, to_json);
+    let check_schema = v8::Local::new(scope, check_schema);
+    let undefined = v8::undefined(scope);
+    let function = factory
+        .call(scope, undefined.into(), &[ops, to_json, check_schema])
+        .and_then(|f| v8::Local::<v8::Function>::try_from(f).ok())
+        .ok_or_else(|| anyhow!("Eval wrapper factory did not return a function"))?;
+    Ok(v8::Global::new(scope, function))
+}
+
+pub(crate) fn core_ops_key<'s>(scope: &v8::PinScope<'s, '_>) -> v8::Local<'s, v8::Private> {
+    let name = v8::String::new(scope, CORE_OPS_KEY).expect("private key name");
+    v8::Private::for_api(scope, Some(name))
+}
+
+/// 把 Deno.core.ops 保存到全局对象的私有属性中（已保存时什么也不做）
+///
+/// 必须在用户代码执行之前、全局 Deno 被隐藏之前调用；
+/// 之后只有 Rust 侧能取出 ops 交给求值包装函数。
+pub(crate) fn capture_core_ops(runtime: &mut JsRuntime) -> Result<()> {
+    deno_core::scope!(scope, runtime);
+    let global = scope.get_current_context().global(scope);
+    let key = core_ops_key(scope);
+    if global.has_private(scope, key) == Some(true) {
+        return Ok(());
+    }
+
+    let mut value: v8::Local<v8::Value> = global.into();
+    for name in ["Deno", "core", "ops"] {
+        let object = v8::Local::<v8::Object>::try_from(value)
+            .map_err(|_| anyhow!("Deno.core.ops is not available"))?;
+        let name = v8::String::new(scope, name).ok_or_else(|| anyhow!("Failed to create property name"))?;
+        value = object
+            .get(scope, name.into())
+            .ok_or_else(|| anyhow!("Deno.core.ops is not available"))?;
+    }
+    if !value.is_object() {
+        return Err(anyhow!("Deno.core.ops is not available"));
+    }
+    global.set_private(scope, key, value);
+    Ok(())
+}
+
+/// 构建 Context 使用的扩展列表
+///
+/// 快照的构建与加载必须注册完全相同的 ops（顺序也一致），
+/// 因此 Context 和 build_snapshot() 都从这里获取扩展列表。
+pub(crate) fn create_extensions(storage: Rc<ResultStorage>, enable_extensions: bool) -> Vec<Extension> {
+    let mut extensions = vec![
+        // Custom ops for result storage
+        ops::pyexecjs_ext::init(storage),
+        // console 输出配额（替换 op_print 的实现）
+        crate::quota::quota_ops::init(),
+    ];
+
+    // 根据参数决定是否加载扩展
+    if enable_extensions {
+        extensions.push(crate::random_ops::random_ops::init());  // Random seed control (always loaded with extensions)
+        extensions.push(crate::crypto_ops::crypto_ops::init());
+        extensions.push(crate::encoding_ops::encoding_ops::init());
+        // Real async timers (using channel + thread to avoid Tokio reactor issues)
+        extensions.push(crate::timer_real_ops::timer_real_ops::init());
+        extensions.push(crate::worker_ops::worker_ops::init());
+        extensions.push(crate::fs_ops::fs_ops::init());
+        extensions.push(crate::sandbox_fs::sandbox_fs_ops::init());
+        extensions.push(crate::fetch_ops::fetch_ops::init());
+        extensions.push(crate::performance_ops::performance_ops::init());
+
+        // 新增: 浏览器环境 API
+        extensions.push(crate::ops::web_storage::web_storage_ops::init());
+        extensions.push(crate::ops::browser_env::browser_env_ops::init());
+        extensions.push(crate::audit::audit_ops::init());
+    }
+
+    extensions
+}
+
+/// 在 runtime 中执行日志开关设置和 polyfill
+///
+/// polyfill 在加载时读取 __NEVER_JSCORE_LOGGING__，所以日志开关必须先设置。
+pub(crate) fn load_polyfill(runtime: &mut JsRuntime, enable_logging: bool) -> Result<()> {
+    let logging_flag = if enable_logging { "true" } else { "false" };
+    let logging_setup = format!("globalThis.__NEVER_JSCORE_LOGGING__ = {};", logging_flag);
+
+    let _log_result = runtime
+        .execute_script("<logging_setup>", logging_setup)
+        .map_err(|e| anyhow!("Failed to setup logging: {:?}", e))?;
+
+    // 全局 Deno 之后会被隐藏，先保存 ops 供求值包装函数使用
+    capture_core_ops(runtime)?;
+
+    let _result = runtime
+        .execute_script("<polyfill>", JS_POLYFILL.to_string())
+        .map_err(|e| anyhow!("Failed to load polyfill: {:?}", e))?;
+
+    Ok(())
+}
+
 /// 格式化 JavaScript 错误为人类可读的字符串
 ///
 /// 将 deno_core 的 JsError 转换为清晰的错误消息，包含：
@@ -136,13 +1112,19 @@ fn format_js_error(error: &JsError) -> String {
         output.push('\n');
     }
 
+    // 4. X is not defined：缺失的全局变量和解决办法
+    if let Some(hint) = crate::missing_globals::hint(error.name.as_deref(), error.message.as_deref()) {
+        output.push_str(&hint);
+        output.push('\n');
+    }
+
     output
 }
 
 /// 从 anyhow::Error 中提取并格式化 JsError
 ///
 /// 尝试从错误链中找到 JsError 并格式化，如果找不到则返回原始错误消息
-fn format_error(error: anyhow::Error) -> String {
+pub(crate) fn format_error(error: anyhow::Error) -> String {
     // 尝试 downcast 到 JsError
     match error.downcast::<JsError>() {
         Ok(js_error) => format_js_error(&js_error),
@@ -184,45 +1166,106 @@ impl Context {
     /// 创建新的 Context
     ///
     /// # Arguments
-    /// * `enable_extensions` - 是否启用扩展（crypto, encoding 等）
-    /// * `enable_logging` - 是否启用操作日志输出
-    /// * `random_seed` - 随机数种子（可选）。如果提供，所有随机数 API 将使用固定种子
-    pub fn new(enable_extensions: bool, enable_logging: bool, random_seed: Option<u32>) -> PyResult<Self> {
-        let storage = Rc::new(ResultStorage::new());
+    /// * `options` - 构造选项，见 [`ContextOptions`]
+    ///   - `enable_extensions` - 是否启用扩展（crypto, encoding 等）
+    ///   - `enable_logging` - 是否启用操作日志输出
+    ///   - `random_seed` - 随机数种子（可选）。如果提供，所有随机数 API 将使用固定种子
+    ///   - `snapshot` - 启动快照（可选）。提供时直接从快照恢复堆，跳过 polyfill 和初始化代码
+    ///   - `code_cache_dir` - V8 代码缓存目录（可选）
+    ///
+    /// 未设置的 timeout_ms / cpu_limit_ms / max_heap_mb / max_result_bytes 使用 set_defaults() 的默认值。
+    ///
+    /// 未提供 `snapshot` 时按 `init(snapshot=...)` 选择：默认启用扩展时使用编译时嵌入的内置快照
+    /// （见 [`crate::snapshot::builtin_snapshot`]），也可以关闭或换成 build_snapshot() 生成的快照。
+    pub fn new(mut options: ContextOptions) -> PyResult<Self> {
+        crate::runtime::check_thread_stack().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        crate::defaults::apply(&mut options);
+        let mut replay = ReplayLog::new(&options);
+
+        if options.snapshot.is_none() {
+            match crate::runtime::runtime_config().snapshot {
+                StartupSnapshot::Builtin if options.enable_extensions => {
+                    options.snapshot = crate::snapshot::builtin_snapshot(options.enable_logging);
+                }
+                StartupSnapshot::Custom { enable_extensions, enable_logging, blob }
+                    if enable_extensions == options.enable_extensions =>
+                {
+                    // polyfill 的日志开关已固化在快照中
+                    options.enable_logging = enable_logging;
+                    options.snapshot = Some(blob);
+                    replay.block("it was started from the init(snapshot=...) snapshot");
+                }
+                _ => {}
+            }
+        }
 
-        let mut extensions = vec![
-            // Custom ops for result storage
-            ops::pyexecjs_ext::init(storage.clone()),
-        ];
+        let storage = Rc::new(ResultStorage::new());
 
-        // 根据参数决定是否加载扩展
-        if enable_extensions {
-            extensions.push(crate::random_ops::random_ops::init());  // Random seed control (always loaded with extensions)
-            extensions.push(crate::crypto_ops::crypto_ops::init());
-            extensions.push(crate::encoding_ops::encoding_ops::init());
-            // Real async timers (using channel + thread to avoid Tokio reactor issues)
-            extensions.push(crate::timer_real_ops::timer_real_ops::init());
-            extensions.push(crate::worker_ops::worker_ops::init());
-            extensions.push(crate::fs_ops::fs_ops::init());
-            extensions.push(crate::fetch_ops::fetch_ops::init());
-            extensions.push(crate::performance_ops::performance_ops::init());
+        let extensions = create_extensions(storage.clone(), options.enable_extensions);
 
-            // 新增: 浏览器环境 API
-            extensions.push(crate::ops::web_storage::web_storage_ops::init());
-            extensions.push(crate::ops::browser_env::browser_env_ops::init());
-        }
+        let isolate = crate::runtime::IsolateGuard::acquire().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
 
+        // 从快照启动时 ops 已经存在于快照堆中（并且 Deno 全局已被 polyfill 隐藏），
+        // 跳过重新注册，扩展列表与构建快照时一致即可
         let mut runtime = JsRuntime::new(RuntimeOptions {
             extensions,
+            startup_snapshot: options.snapshot,
+            skip_op_registration: options.snapshot.is_some(),
+            create_params: options.create_params(),
+            // postMessage 在 isolate 之间共享 SharedArrayBuffer / WebAssembly.Module
+            shared_array_buffer_store: Some(crate::worker_ops::shared_array_buffer_store()),
+            compiled_wasm_module_store: Some(crate::worker_ops::compiled_wasm_module_store()),
             ..Default::default()
         });
 
         // 获取 IsolateHandle 并存储到 OpState，用于 op_terminate_execution
         let isolate_handle = runtime.v8_isolate().thread_safe_handle();
+
+        // 接近堆上限时终止执行并临时放宽上限，让异常能够正常展开，
+        // 避免 V8 直接 OOM 中止整个进程；执行结束后由 restore_heap_limit() 恢复原来的上限
+        let heap_limit_reached = Rc::new(Cell::new(false));
+        let heap_limit_raised = Rc::new(Cell::new(None));
+        if options.max_heap_mb.is_some() {
+            install_heap_limit_callback(&mut runtime, &isolate_handle, &heap_limit_reached, &heap_limit_raised);
+        }
+        crate::regexp_guard::install(&mut runtime);
+        crate::shadow_realm::install(&mut runtime, options.allow_dynamic_code);
+        let slow_script = SlowScript::install(&mut runtime);
+        let yield_points = YieldPoints::install(&mut runtime);
+        // Worker 的配额与创建它的 Context 累计同一份用量
+        let quota = match &options.worker {
+            Some(port) => port.quota(&options),
+            None => options.quotas.map(Quota::new),
+        }
+        .map(Rc::new);
         {
             let op_state = runtime.op_state();
             let mut op_state_mut = op_state.borrow_mut();
-            op_state_mut.put(isolate_handle);
+            op_state_mut.put(isolate_handle.clone());
+            let permissions = options.permissions.clone();
+            op_state_mut.put(permissions.unwrap_or_else(|| Arc::new(Permissions::restricted(options.env.is_some()))));
+            if let Some(fs_roots) = &options.fs_roots {
+                op_state_mut.put(fs_roots.clone());
+            }
+            if let Some(env) = &options.env {
+                op_state_mut.put(env.clone());
+            }
+            // new Worker() 以相同的选项创建 Context
+            op_state_mut.put(WorkerHost::new(&options, quota.as_deref()));
+            if let Some(port) = &options.worker {
+                op_state_mut.put(port.clone());
+            }
+        }
+        let audit_log = options.audit_ops.then(|| Rc::new(AuditLog::default()));
+        if let Some(log) = &audit_log {
+            runtime.op_state().borrow_mut().put(log.clone());
+        }
+        let console_capture = Rc::new(ConsoleCapture::default());
+        runtime.op_state().borrow_mut().put(console_capture.clone());
+        let sleeps = Rc::new(SleepCancel::default());
+        runtime.op_state().borrow_mut().put(sleeps.clone());
+        if let Some(quota) = &quota {
+            runtime.op_state().borrow_mut().put(quota.clone());
         }
 
         // DON'T access OpState or Isolate during construction
@@ -230,23 +1273,105 @@ impl Context {
 
         // DON'T load polyfill here - defer to first execution to avoid isolate conflicts
 
+        let limits = options.limits();
         Ok(Context {
-            runtime: RefCell::new(runtime),
+            runtime: ManuallyDrop::new(RefCell::new(runtime)),
+            _isolate: isolate,
             result_storage: storage,
             exec_count: RefCell::new(0),
-            extensions_loaded: enable_extensions,
-            logging_enabled: enable_logging,
+            extensions_loaded: options.enable_extensions,
+            logging_enabled: options.enable_logging,
             polyfill_loaded: RefCell::new(false),
-            random_seed,
+            random_seed: options.random_seed,
+            snapshot: options.snapshot,
+            code_cache_dir: options.code_cache_dir,
+            eval_wrappers: RefCell::new(None),
+            call_targets: CallTargets::default(),
+            handle_table: HandleTable::new(options.max_handles),
+            background_event_loop: RefCell::new(false),
+            event_loop_errors: RefCell::new(Vec::new()),
+            event_loop: Cell::new(EventLoopOptions::default()),
+            fork_generation: crate::fork::generation(),
+            max_heap_mb: options.max_heap_mb,
+            heap_limit_reached,
+            heap_limit_raised,
+            timings: Cell::new(Timings::default()),
+            last_timings: Cell::new(Timings::default()),
+            total_timings: Cell::new(Timings::default()),
+            allow_dynamic_code: options.allow_dynamic_code,
+            no_ops: options.no_ops,
+            isolate_handle,
+            limits,
+            limit_exceeded: ExceededSlot::default(),
+            audit_log,
+            quota,
+            console_capture,
+            sleeps,
+            history: options.history.map(History::new),
+            slow_script,
+            yield_points,
+            gc_observer: Box::default(),
+            wasm_views: MemoryViews::default(),
+            replay: RefCell::new(replay),
+            watched: RefCell::default(),
+            shared_memory: options.shared_memory,
+            shared_buffers: options.shared_buffers,
+            weak_refs: options.weak_refs,
+            undefined_as: options.undefined_as,
+            worker: options.worker.is_some(),
+            recycle: Cell::new(None),
+            recycle_calls: Cell::new(0),
+            result_filter: RefCell::new(None),
+            import_meta: RefCell::new(None),
+            trace_id: RefCell::new(None),
+            abort_signal: RefCell::new(None),
+            auto_recreate: options.auto_recreate,
+            poisoned: RefCell::new(None),
+            panicked: Cell::new(false),
+            snapshot_owner: None,
         })
     }
 
-    /// Load polyfill on first execution
-    fn ensure_polyfill_loaded(&self) -> Result<()> {
-        if !self.extensions_loaded {
-            return Ok(());
+    /// 根据 Python 构造参数生成 ContextOptions
+    ///
+    /// 校验快照与 enable_extensions 是否一致。Context、ContextPool 等入口共用。
+    pub(crate) fn build_options(
+        enable_extensions: bool,
+        enable_logging: bool,
+        random_seed: Option<u32>,
+        snapshot: Option<&[u8]>,
+        code_cache_dir: Option<PathBuf>,
+    ) -> PyResult<ContextOptions> {
+        let mut options = ContextOptions {
+            enable_extensions,
+            enable_logging,
+            random_seed,
+            snapshot: None,
+            code_cache_dir,
+            ..Default::default()
+        };
+
+        if let Some(data) = snapshot {
+            let info = crate::snapshot::snapshot_from_py(data)?;
+            if info.enable_extensions != enable_extensions {
+                return Err(PyValueError::new_err(format!(
+                    "Snapshot was built with enable_extensions={}, but Context was created with enable_extensions={}",
+                    if info.enable_extensions { "True" } else { "False" },
+                    if enable_extensions { "True" } else { "False" },
+                )));
+            }
+            // polyfill 的日志开关已固化在快照中
+            options.enable_logging = info.enable_logging;
+            options.snapshot = Some(info.blob);
         }
 
+        Ok(options)
+    }
+
+    /// Load polyfill on first execution
+    ///
+    /// 纯净环境不加载 polyfill，但同样保存 ops 并隐藏全局 Deno。
+    fn ensure_polyfill_loaded(&self) -> Result<()> {
         if *self.polyfill_loaded.borrow() {
             return Ok(());
         }
@@ -267,17 +1392,49 @@ impl Context {
             }
         }
 
-        // Set logging flag
-        let logging_flag = if self.logging_enabled { "true" } else { "false" };
-        let logging_setup = format!("globalThis.__NEVER_JSCORE_LOGGING__ = {};", logging_flag);
+        // 从快照启动时 polyfill 已在快照中执行过
+        if self.extensions_loaded && self.snapshot.is_none() {
+            load_polyfill(&mut runtime, self.logging_enabled)?;
+        }
+        if self.no_ops {
+            runtime
+                .execute_script("<silence_console>", SILENCE_CONSOLE)
+                .map_err(|e| anyhow!("Failed to replace console: {}", format_error(e.into())))?;
+        }
+        capture_core_ops(&mut runtime)?;
+        if self.worker {
+            runtime
+                .execute_script("<worker_scope>", WORKER_SCOPE)
+                .map_err(|e| anyhow!("Failed to set up worker scope: {}", format_error(e.into())))?;
+        }
+        runtime
+            .execute_script("<hide_deno>", HIDE_DENO)
+            .map_err(|e| anyhow!("Failed to hide Deno: {}", format_error(e.into())))?;
+        if !crate::runtime::runtime_config().icu {
+            runtime
+                .execute_script("<hide_intl>", crate::runtime::HIDE_INTL)
+                .map_err(|e| anyhow!("Failed to hide Intl: {}", format_error(e.into())))?;
+        }
 
-        let _log_result = runtime
-            .execute_script("<logging_setup>", logging_setup)
-            .map_err(|e| anyhow!("Failed to setup logging: {:?}", e))?;
+        if self.shared_memory {
+            runtime
+                .execute_script("<shared_memory>", SHARED_MEMORY)
+                .map_err(|e| anyhow!("Failed to enable shared memory: {}", format_error(e.into())))?;
+        }
+        for (name, store) in &self.shared_buffers {
+            define_shared_buffer(&mut runtime, name, store)?;
+        }
+        if !self.weak_refs {
+            runtime
+                .execute_script("<hide_weak_refs>", HIDE_WEAK_REFS)
+                .map_err(|e| anyhow!("Failed to hide WeakRef: {}", format_error(e.into())))?;
+        }
 
-        let _result = runtime
-            .execute_script("<polyfill>", JS_POLYFILL.to_string())
-            .map_err(|e| anyhow!("Failed to load polyfill: {:?}", e))?;
+        // 禁止 eval / new Function 等从字符串生成代码（在 polyfill 加载之后设置）
+        if !self.allow_dynamic_code {
+            deno_core::scope!(scope, &mut runtime);
+            scope.get_current_context().set_allow_generation_from_strings(false);
+        }
 
         *self.polyfill_loaded.borrow_mut() = true;
 
@@ -332,536 +1489,3019 @@ impl Context {
         }
     }
 
-    /// 执行脚本，将代码加入全局作用域（不返回值）
+    /// 检查 Context 是否是 fork 前从父进程继承的
+    pub(crate) fn check_fork(&self) -> PyResult<()> {
+        crate::fork::check(self.fork_generation, "Context")
+    }
+
+    /// 检查 Context 当前没有在执行 JavaScript（register() 的 Python 函数中不能再次调用同一个 Context）
+    pub(crate) fn check_idle(&self) -> PyResult<()> {
+        match self.runtime.try_borrow_mut() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(nested_call_error()),
+        }
+    }
+
+    /// 句柄表（Realm / ShadowRealm 创建时登记）
+    pub(crate) fn handle_table(&self) -> &HandleTable {
+        &self.handle_table
+    }
+
+    /// call() 缓存的目标函数：(句柄编号, 函数名)
     ///
-    /// 这个方法会直接执行代码并将定义的函数/变量加入全局作用域
-    fn exec_script(&self, code: &str) -> Result<()> {
-        // Ensure polyfill is loaded before first execution
-        self.ensure_polyfill_loaded()?;
+    /// prepare() 的函数经过同一个缓存调用，它们属于 prepared 句柄（释放时一起清除），不单独列出和计数。
+    fn cached_call_targets(&self) -> Vec<(u64, String)> {
+        let mut targets = self.call_targets.resolved();
+        targets.retain(|(_, name)| !name.starts_with(crate::prepared::HOLDER));
+        targets
+    }
 
-        // CRITICAL: Re-enter isolate to ensure it's current
-        // This fixes the multi-Context issue where creating ctx2 breaks ctx1
-        self.enter_isolate();
+    /// 是否还能缓存新的 call() 目标函数（没有达到 max_handles）
+    fn handle_room(&self) -> bool {
+        self.handle_table
+            .max()
+            .is_none_or(|max| self.handle_table.prune() + self.cached_call_targets().len() < max)
+    }
 
-        let mut runtime = self.runtime.borrow_mut();
+    /// 为新的 Realm / ShadowRealm / prepare() 函数预留句柄
+    ///
+    /// 达到 max_handles 时先清空 call() 的函数缓存，仍然没有空位时返回 QuotaError。
+    pub(crate) fn reserve_handle(&self) -> Result<()> {
+        let Some(max) = self.handle_table.max() else {
+            return Ok(());
+        };
+        let live = self.handle_table.prune();
+        if live + self.cached_call_targets().len() >= max {
+            self.call_targets.clear();
+        }
+        if live >= max {
+            return Err(crate::quota::quota_error(format!(
+                "Handle limit exceeded (max_handles={}), release handles from handles() first",
+                max
+            )));
+        }
+        Ok(())
+    }
 
-        // execute_script returns a v8::Global<v8::Value>
-        // We let it drop immediately
-        let _result = runtime
-            .execute_script("<exec>", code.to_string())
-            .map_err(|e| anyhow!("{}", format_error(e.into())))?;
-        // v8::Global drops here
+    /// 句柄是否仍然存在（handle.released）
+    pub(crate) fn handle_is_live(&self, id: u64) -> bool {
+        self.handle_table.is_live(id).unwrap_or_else(|| self.call_targets.contains(id))
+    }
 
-        // 简化的定时器处理：只运行 event loop 来处理微任务
-        // 定时器通过 queueMicrotask 自动调度，依赖真实时间
-        drop(runtime);
+    /// 释放句柄（handle.release()），已经释放时什么也不做
+    pub(crate) fn release_handle(slf: &Bound<'_, Self>, py: Python<'_>, id: u64) -> PyResult<()> {
+        let this = slf.borrow();
+        this.check_fork()?;
+        match this.handle_table.release(id) {
+            None => {
+                this.call_targets.remove(id);
+                Ok(())
+            }
+            Some(Release::Done) => Ok(()),
+            Some(Release::Busy) => Err(PyRuntimeError::new_err("Release error: the realm is executing")),
+            // 作为全局脚本删除，pickle / recycle_after 重放时同样删除
+            Some(Release::Prepared(path)) => this
+                .without_gil(py, |ctx| ctx.exec_named_script("<release>", format!("delete {};", path), false))
+                .map_err(|e| crate::quota::py_error("Release error", e)),
+        }
+    }
 
-        // 使用 Tokio 运行 event loop (处理 queueMicrotask 队列)
-        run_with_tokio(async {
-            let mut rt = self.runtime.borrow_mut();
+    /// 持有创建 isolate 时使用的可释放快照（options.snapshot 为 owner.blob()），isolate 销毁之后才释放
+    pub(crate) fn keep_snapshot_alive(&mut self, owner: Arc<OwnedBlob>) {
+        self.snapshot_owner = Some(owner);
+    }
 
-            // 运行 event loop 直到微任务队列为空
-            rt.run_event_loop(Default::default()).await.ok();
-        });
+    /// $terminate() 保存的 Hook 数据（ThreadedContext.get_hook_data() 使用）
+    pub(crate) fn saved_hook_data(&self) -> Option<String> {
+        self.result_storage.hook_data()
+    }
 
-        // Exit isolate after operations complete
-        self.exit_isolate();
+    /// 清空保存的 Hook 数据（ThreadedContext.clear_hook_data() 使用）
+    pub(crate) fn clear_saved_hook_data(&self) {
+        self.result_storage.clear_hook_data();
+    }
 
-        // 更新执行计数
-        let mut count = self.exec_count.borrow_mut();
-        *count += 1;
+    /// 标记为 poisoned（之后的执行抛出 ContextPoisoned），返回本次调用的错误信息
+    fn poison(&self, reason: String) -> String {
+        let message = crate::poison::fatal_message(&reason, self.can_recreate());
+        self.poisoned.borrow_mut().get_or_insert(reason);
+        message
+    }
 
-        // 每 100 次执行后提示 GC
-        if *count % 100 == 0 {
-            std::hint::black_box(());
-        }
+    /// 致命错误的原因（没有 poisoned 时为 None）
+    pub(crate) fn poison_reason(&self) -> Option<String> {
+        self.poisoned.borrow().clone()
+    }
 
-        Ok(())
+    /// poisoned 后能否自动重建（auto_recreate=True 且没有无法重放的状态）
+    fn can_recreate(&self) -> bool {
+        self.auto_recreate && self.replay.borrow().rebuild_blocker().is_none()
     }
 
-    /// 执行 JavaScript 代码并返回结果
-    ///
-    /// 根据 auto_await 参数决定是否自动等待 Promise。
-    /// 注意：这个方法用于求值，代码在IIFE中执行，不会影响全局作用域
+    /// 执行可能遇到致命错误的操作：已经 poisoned 时拒绝执行，panic 时捕获并标记为 poisoned
+    fn guard_fatal<R>(&self, f: impl FnOnce() -> Result<R>) -> Result<R> {
+        if let Some(reason) = self.poisoned.borrow().as_deref() {
+            return Err(crate::poison::poisoned_error(reason));
+        }
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+        self.restore_heap_limit();
+        match result {
+            Ok(result) => result,
+            Err(payload) => {
+                self.panicked.set(true);
+                let reason = format!("panic during execution: {}", crate::poison::panic_message(payload.as_ref()));
+                Err(anyhow!("{}", self.poison(reason)))
+            }
+        }
+    }
+
+    /// 恢复接近堆上限时放宽的上限（重新注册回调，之后的执行同样受 max_heap_mb 限制）
     ///
-    /// Early Return 机制：
-    /// - 当 JS 调用 __neverjscore_return__(value) 时，会抛出 EarlyReturnError
-    /// - 该错误会携带返回值并中断 JS 执行
-    /// - Rust 侧通过 downcast 检测并提取返回值
-    fn execute_js(&self, code: &str, auto_await: bool) -> Result<String> {
-        // Ensure polyfill is loaded before first execution
-        self.ensure_polyfill_loaded()?;
+    /// runtime 仍被借用（嵌套执行）时保留到外层执行结束。
+    fn restore_heap_limit(&self) {
+        let Some(original) = self.heap_limit_raised.get() else {
+            return;
+        };
+        let Ok(mut runtime) = self.runtime.try_borrow_mut() else {
+            return;
+        };
+        self.heap_limit_raised.set(None);
+        runtime.remove_near_heap_limit_callback(original);
+        install_heap_limit_callback(&mut runtime, &self.isolate_handle, &self.heap_limit_reached, &self.heap_limit_raised);
+    }
 
-        // CRITICAL: Re-enter isolate
-        self.enter_isolate();
+    /// 执行是否因超出 max_heap_mb / timeout_ms / cpu_limit_ms / quotas 被终止（或在让出点上被 Python 信号终止）
+    fn limit_reached(&self) -> bool {
+        self.heap_limit_reached.get()
+            || self.limit_exceeded.lock().unwrap().is_some()
+            || self.quota.as_ref().is_some_and(|quota| quota.terminated())
+            || self.yield_points.pending()
+    }
 
-        self.result_storage.clear();
+    /// 执行因超出限制被终止时，返回对应的错误（并清除标志）
+    ///
+    /// 执行被终止的路径都会经过这里，同时中断未完成的 op_sleep()。
+    /// 调用方负责先调用 cancel_terminate_execution() 恢复 isolate。
+    fn take_limit_error(&self) -> Option<anyhow::Error> {
+        self.sleeps.interrupt();
+        if self.heap_limit_reached.replace(false) {
+            self.limit_exceeded.lock().unwrap().take();
+            let reason = format!("Heap limit exceeded (max_heap_mb={})", self.max_heap_mb.unwrap_or_default());
+            return Some(anyhow!("{}", self.poison(reason)));
+        }
+        if let Some(e) = self.quota.as_ref().and_then(|quota| quota.take_terminated()) {
+            return Some(e);
+        }
+        if let Some(e) = self.yield_points.take() {
+            // 原样抛出信号处理函数的异常（KeyboardInterrupt 等），见 quota::py_error
+            self.limit_exceeded.lock().unwrap().take();
+            return Some(anyhow::Error::new(e));
+        }
+        self.limit_exceeded.lock().unwrap().take().map(Exceeded::to_error)
+    }
 
-        if auto_await {
-            // 异步模式：自动等待 Promise
-            let result = run_with_tokio(async {
-                let mut runtime = self.runtime.borrow_mut();
-
-                // 序列化代码
-                let code_json = serde_json::to_string(code)
-                    .map_err(|e| anyhow!("Failed to serialize code: {}", e))?;
-
-                // 简化的包装：只需要 async 函数和结果存储
-                let wrapped_code = format!(
-                    r#"
-                    (async function() {{
-                        const code = {};
-                        const __result = await Promise.resolve(eval(code));
-
-                        if (__result === undefined) {{
-                            __getDeno().core.ops.op_store_result("null");
-                            return null;
-                        }}
-
-                        try {{
-                            const json = JSON.stringify(__result);
-                            __getDeno().core.ops.op_store_result(json);
-                            return __result;
-                        }} catch(e) {{
-                            const str = JSON.stringify(String(__result));
-                            __getDeno().core.ops.op_store_result(str);
-                            return __result;
-                        }}
-                    }})()
-                    "#,
-                    code_json
-                );
+    /// 开始监视一次调用的执行时间（未设置 timeout_ms / cpu_limit_ms / max_cpu_ms 时为 None）
+    fn watch(&self) -> Option<WatchGuard> {
+        let limits = Limits {
+            cpu_quota: self.quota.as_ref().and_then(|quota| quota.remaining_cpu()),
+            slow: self.slow_script.threshold(),
+            ..self.limits
+        };
+        self.slow_script.begin();
+        crate::watchdog::watch(&self.isolate_handle, limits, &self.limit_exceeded)
+    }
 
-                // 执行脚本
-                let execute_result = runtime.execute_script("<eval_async>", wrapped_code);
+    /// 在配额内执行一次调用：配额已耗尽时拒绝，结束后累计消耗的 CPU 时间
+    pub(crate) fn with_quota<R>(&self, f: impl FnOnce() -> Result<R>) -> Result<R> {
+        let Some(quota) = &self.quota else {
+            return f();
+        };
+        quota.begin()?;
+        let cpu_start = crate::watchdog::thread_cpu_time();
+        let result = f();
+        self.charge_quota_cpu(cpu_start);
+        result
+    }
 
-                // 检查是否是 EarlyReturnError
-                match execute_result {
-                    Err(e) => {
-                        // 检查是否是早期返回
-                        if self.result_storage.is_early_return() {
-                            // 提前返回：直接返回存储的值
-                            let result = self.result_storage.take()
-                                .ok_or_else(|| anyhow!("Early return but no result stored"))?;
-                            let mut count = self.exec_count.borrow_mut();
-                            *count += 1;
-                            return Ok(result);
-                        }
+    /// 把从 cpu_start 开始消耗的线程 CPU 时间计入 max_cpu_ms 配额
+    fn charge_quota_cpu(&self, cpu_start: Option<Duration>) {
+        if let (Some(quota), Some(start), Some(end)) = (&self.quota, cpu_start, crate::watchdog::thread_cpu_time()) {
+            quota.charge_cpu(end.saturating_sub(start));
+        }
+    }
 
-                        // ⚠️ 检查是否是 terminate_execution 错误
-                        let error_msg = format!("{}", format_error(e.into()));
-                        if error_msg.contains("execution terminated") {
-                            // 恢复 isolate 状态，允许后续执行
-                            runtime.v8_isolate().cancel_terminate_execution();
-                        }
+    /// 检查结果大小是否超出 max_result_bytes
+    pub(crate) fn check_result_quota(&self, result: &str) -> Result<()> {
+        match &self.quota {
+            Some(quota) => quota.check_result(result.len()),
+            None => Ok(()),
+        }
+    }
 
-                        // 其他错误 - 格式化后返回
-                        return Err(anyhow!("{}", error_msg));
-                    }
-                    Ok(result_handle) => {
-                        // 正常执行，leak handle
-                        std::mem::forget(result_handle);
-                    }
-                }
+    /// 配额使用情况（未设置 quotas 时返回错误）
+    pub(crate) fn quota_usage(&self) -> PyResult<QuotaUsage> {
+        self.quota
+            .as_ref()
+            .map(|quota| quota.usage())
+            .ok_or_else(|| PyRuntimeError::new_err("Quotas are not configured; create the Context with quotas={...}"))
+    }
 
-                // 运行 event loop 等待 Promise 完成
-                let event_loop_result = runtime
-                    .run_event_loop(Default::default())
-                    .await;
+    /// 调用结束后处理监视结果
+    ///
+    /// 看门狗可能恰好在调用完成时触发，此时 isolate 仍处于终止状态，需要恢复，
+    /// 否则下一次调用会被错误地终止。
+    fn finish_watch<R>(&self, guard: Option<WatchGuard>, result: Result<R>) -> Result<R> {
+        self.slow_script.finish();
+        if guard.is_none() {
+            return result;
+        }
+        drop(guard);
+        match self.limit_exceeded.lock().unwrap().take() {
+            Some(exceeded) => {
+                self.isolate_handle.cancel_terminate_execution();
+                result.map_err(|_| exceeded.to_error())
+            }
+            None => result,
+        }
+    }
 
-                // 检查 event loop 是否遇到 EarlyReturnError
-                if let Err(e) = event_loop_result {
-                    // 检查是否是早期返回
-                    if self.result_storage.is_early_return() {
-                        // Event loop 中的提前返回
-                        let result = self.result_storage.take()
-                            .ok_or_else(|| anyhow!("Early return but no result stored"))?;
-                        let mut count = self.exec_count.borrow_mut();
-                        *count += 1;
-                        return Ok(result);
-                    }
+    /// 在中止信号下执行一次异步调用（见 abort.rs）
+    ///
+    /// 中止时终止正在执行的 JS，等待中的事件循环立即返回，错误为 "Execution aborted"。
+    pub(crate) fn with_abort<R>(&self, signal: &Arc<AbortSignal>, f: impl FnOnce() -> R) -> R {
+        let id = signal.attach(self.isolate_handle.clone(), self.limit_exceeded.clone());
+        let previous = self.abort_signal.replace(Some(signal.clone()));
+        let result = f();
+        self.abort_signal.replace(previous);
+        signal.detach(id);
+
+        // 中止恰好发生在执行结束之后：清除终止状态，否则下一次调用会被错误地终止
+        let mut exceeded = self.limit_exceeded.lock().unwrap();
+        if exceeded.is_some_and(|exceeded| matches!(exceeded.limit, LimitExceeded::Aborted)) {
+            *exceeded = None;
+            self.isolate_handle.cancel_terminate_execution();
+        }
+        result
+    }
 
-                    // ⚠️ 检查是否是 terminate_execution 错误
-                    let error_msg = format!("{}", format_error(e.into()));
-                    if error_msg.contains("execution terminated") {
-                        // 恢复 isolate 状态，允许后续执行
-                        runtime.v8_isolate().cancel_terminate_execution();
-                    }
+    /// 执行统计（可以在工作线程中获取后传回 Python 线程）
+    pub(crate) fn stats(&self) -> ContextStats {
+        ContextStats {
+            exec_count: *self.exec_count.borrow(),
+            total: self.total_timings.get(),
+            last: self.last_timings.get(),
+        }
+    }
 
-                    // 其他错误 - 格式化后返回
-                    return Err(anyhow!("{}", error_msg));
-                }
+    /// return_timings=True 时把结果和本次调用的耗时打包为元组
+    fn with_timings<'py>(&self, py: Python<'py>, result: Bound<'py, PyAny>, return_timings: bool) -> PyResult<Bound<'py, PyAny>> {
+        if !return_timings {
+            return Ok(result);
+        }
+        let timings = self.last_timings().to_py_dict(py)?;
+        Ok((result, timings).into_pyobject(py)?.into_any())
+    }
 
-                // 检查是否设置了 early return 标志（即使 event loop 正常完成）
-                // 这处理了 eval() 内部调用 __neverjscore_return__ 的情况
-                if self.result_storage.is_early_return() {
-                    let result = self.result_storage.take()
-                        .ok_or_else(|| anyhow!("Early return but no result stored"))?;
-                    let mut count = self.exec_count.borrow_mut();
-                    *count += 1;
-                    return Ok(result);
+    /// 执行 f，enabled 为 true 时收集期间的 console 输出（eval(capture_output=True)）
+    ///
+    /// 执行失败时把收集到的输出照常写到 stdout / stderr。
+    pub(crate) fn capture_console<T>(&self, enabled: bool, f: impl FnOnce() -> Result<T>) -> Result<(T, Option<Vec<ConsoleEntry>>)> {
+        if !enabled {
+            return f().map(|value| (value, None));
+        }
+        let outer = self.console_capture.start();
+        let result = f();
+        let output = self.console_capture.finish(outer);
+        match result {
+            Ok(value) => Ok((value, Some(output))),
+            Err(e) => {
+                crate::console_capture::replay(&output);
+                Err(e)
+            }
+        }
+    }
+
+    /// 把耗时记录到当前调用
+    fn record_timing(&self, f: impl FnOnce(&mut Timings)) {
+        let mut timings = self.timings.get();
+        f(&mut timings);
+        self.timings.set(timings);
+    }
+
+    /// 当前调用结束：保存为最近一次调用的耗时并累加到总耗时
+    fn finish_timings(&self) {
+        let timings = self.timings.get();
+        self.last_timings.set(timings);
+        let mut total = self.total_timings.get();
+        total.add(&timings);
+        self.total_timings.set(total);
+    }
+
+    /// 最近一次调用的耗时
+    pub(crate) fn last_timings(&self) -> Timings {
+        self.last_timings.get()
+    }
+
+    /// 开始新的调用时清空审计日志
+    fn clear_audit_log(&self) {
+        if let Some(log) = &self.audit_log {
+            log.clear();
+        }
+    }
+
+    /// 最近一次调用的 op 审计记录（未开启 audit_ops 时返回错误）
+    pub(crate) fn audit_log(&self) -> PyResult<(Vec<AuditEntry>, usize)> {
+        self.audit_log
+            .as_ref()
+            .map(|log| log.snapshot())
+            .ok_or_else(|| PyRuntimeError::new_err("Op auditing is disabled; create the Context with audit_ops=True"))
+    }
+
+    /// 使用此 Context 的调试会话（第一次使用时创建，保存在 isolate 的 slot 中，随 isolate 一起销毁）
+    fn with_debugger<R>(&self, f: impl FnOnce(&crate::debugger::Debugger) -> Result<R>) -> Result<R> {
+        self.enter_isolate();
+        let result = (|| {
+            let mut runtime = self.runtime.borrow_mut();
+            let debugger = match runtime.v8_isolate().get_slot::<Rc<crate::debugger::Debugger>>() {
+                Some(debugger) => debugger.clone(),
+                None => {
+                    let debugger = Rc::new(crate::debugger::Debugger::new(&mut runtime)?);
+                    runtime.v8_isolate().set_slot(debugger.clone());
+                    debugger
                 }
+            };
+            deno_core::scope!(_scope, &mut *runtime);
+            f(&debugger)
+        })();
+        self.exit_isolate();
+        result
+    }
 
-                // 正常完成：从 result_storage 获取结果
-                let result = self
-                    .result_storage
-                    .take()
-                    .ok_or_else(|| anyhow!("No result stored after event loop"))?;
+    /// 释放 GIL 执行 V8 工作
+    ///
+    /// 长时间运行的脚本不再阻塞其他 Python 线程。
+    /// 参数转换必须在调用前完成，结果转换在调用后完成（闭包内不能访问 Python 对象）。
+    /// 只有 Context 本身不受 Ungil 约束，闭包捕获的其他 isolate 状态用 [`IsolateLocal`] 包装。
+    ///
+    /// register() 的 Python 函数中再次调用同一个 Context 时外层调用仍在执行，不运行 f，返回 RuntimeError。
+    pub(crate) fn without_gil<R: Ungil + NestedCall>(&self, py: Python<'_>, f: impl FnOnce(&Self) -> R + Ungil) -> R {
+        if self.check_idle().is_err() {
+            return R::nested_call();
+        }
+        let this = IsolateLocal::new(self);
+        py.detach(move || f(this.into_inner()))
+    }
 
-                let mut count = self.exec_count.borrow_mut();
-                *count += 1;
+    /// 在此 Context 的 isolate 中执行 V8 操作（Realm 使用）
+    ///
+    /// 负责进入 / 退出 isolate；执行被终止时恢复 isolate，超出堆上限时返回内存错误。
+    pub(crate) fn with_runtime<R>(&self, f: impl FnOnce(&mut JsRuntime) -> Result<R>) -> Result<R> {
+        self.guard_fatal(|| {
+            self.enter_isolate();
+            let mut runtime = self.runtime.borrow_mut();
 
-                Ok(result)
-            });
+            let guard = self.watch();
+            let result = f(&mut runtime);
+            let result = match result {
+                Err(e) if runtime.v8_isolate().is_execution_terminating() || self.limit_reached() => {
+                    runtime.v8_isolate().cancel_terminate_execution();
+                    Err(self.take_limit_error().unwrap_or(e))
+                }
+                result => result,
+            };
 
-            // Exit isolate after async operations complete
+            drop(runtime);
             self.exit_isolate();
+            let result = self.finish_watch(guard, result);
+            self.gc_observer.deliver();
             result
-        } else {
-            // 同步模式：不等待 Promise
-            let mut runtime = self.runtime.borrow_mut();
+        })
+    }
 
-            let code_json = serde_json::to_string(code)
-                .map_err(|e| anyhow!("Failed to serialize code: {}", e))?;
-
-            let wrapped_code = format!(
-                r#"
-                (function() {{
-                    const code = {};
-                    const __result = eval(code);
-                    if (__result === undefined) {{
-                        __getDeno().core.ops.op_store_result("null");
-                        return null;
-                    }}
-                    try {{
-                        const json = JSON.stringify(__result);
-                        __getDeno().core.ops.op_store_result(json);
-                        return __result;
-                    }} catch(e) {{
-                        const str = JSON.stringify(String(__result));
-                        __getDeno().core.ops.op_store_result(str);
-                        return __result;
-                    }}
-                }})()
-                "#,
-                code_json
-            );
-
-            let execute_result = runtime.execute_script("<eval_sync>", wrapped_code);
+    /// 执行脚本，将代码加入全局作用域（不返回值）
+    ///
+    /// 这个方法会直接执行代码并将定义的函数/变量加入全局作用域
+    pub(crate) fn exec_script(&self, code: String) -> Result<()> {
+        self.reload_watched()?;
+        self.exec_named_script("<exec>", code, false)
+    }
 
-            // 检查是否是 EarlyReturnError
-            match execute_result {
-                Err(e) => {
-                    // 检查是否是早期返回
-                    if self.result_storage.is_early_return() {
-                        // 提前返回
-                        let result = self.result_storage.take()
-                            .ok_or_else(|| anyhow!("Early return but no result stored"))?;
-                        let mut count = self.exec_count.borrow_mut();
-                        *count += 1;
-                        return Ok(result);
-                    }
+    /// 执行源文件（compile_file() 使用）
+    pub(crate) fn exec_source_file(&self, path: &Path, code: String, mode: SourceMode) -> Result<()> {
+        self.exec_source(&path.to_string_lossy(), code, mode)
+    }
 
-                    // ⚠️ 检查是否是 terminate_execution 错误
-                    let error_msg = format!("{}", format_error(e.into()));
-                    if error_msg.contains("execution terminated") {
-                        // 恢复 isolate 状态，允许后续执行
-                        runtime.v8_isolate().cancel_terminate_execution();
-                    }
+    /// 按执行方式执行具名源码（文件路径或 compile_url() 的 URL）
+    pub(crate) fn exec_source(&self, name: &str, code: String, mode: SourceMode) -> Result<()> {
+        match mode {
+            SourceMode::Module => self.exec_module(name, code),
+            _ => self.exec_named_script(name, code, true),
+        }
+    }
 
-                    return Err(anyhow!("{}", error_msg));
-                }
-                Ok(result_handle) => {
-                    std::mem::forget(result_handle);
-                }
-            }
+    /// 监视 compile_file(watch=True) 编译的文件，`stamp` 为读取文件之前的修改时间
+    pub(crate) fn watch_file(&self, path: PathBuf, encoding: Option<String>, mode: Option<String>, stamp: Option<FileStamp>) {
+        self.watched.borrow_mut().watch(path, encoding, mode, stamp);
+    }
 
-            // 从 storage 获取结果
-            let result = self
-                .result_storage
-                .take()
-                .ok_or_else(|| anyhow!("No result stored"))?;
+    /// 停止监视文件
+    pub(crate) fn unwatch_file(&self, path: &Path) {
+        self.watched.borrow_mut().unwatch(path);
+    }
 
-            let mut count = self.exec_count.borrow_mut();
-            *count += 1;
+    /// 重新编译自上次调用以来修改过的被监视文件
+    fn reload_watched(&self) -> Result<()> {
+        if self.watched.borrow().is_empty() {
+            return Ok(());
+        }
+        let changed = self.watched.borrow_mut().changed();
+        for file in changed {
+            let (code, mode) = Python::attach(|py| {
+                crate::source_file::load(py, &file.path, file.encoding.as_deref(), file.mode.as_deref())
+            })
+            .map_err(|e| anyhow!("Failed to reload {}: {}", file.path.display(), e))?;
+            self.exec_source_file(&file.path, code, mode)
+                .map_err(|e| anyhow!("Failed to reload {}: {}", file.path.display(), e))?;
+        }
+        Ok(())
+    }
 
-            // Exit isolate after sync operations complete
-            self.exit_isolate();
+    /// 执行具名脚本
+    ///
+    /// `name` 会出现在错误堆栈中；`use_code_cache` 为 true 且设置了 code_cache_dir 时
+    /// 通过 V8 代码缓存编译（compile()/compile_file() 使用）
+    pub(crate) fn exec_named_script(&self, name: &str, code: String, use_code_cache: bool) -> Result<()> {
+        let cache_dir = self.code_cache_dir.as_deref().filter(|_| use_code_cache);
+        let replay_code = self.replay_copy(&code);
+
+        let execution = self.begin_execution("script", name, &code);
+        let result = self.exec_script_with(|runtime, timings| match cache_dir {
+            Some(cache_dir) => crate::code_cache::execute_script(runtime, name, code, cache_dir, timings)
+                .map_err(|e| anyhow!("{}", format_error(e))),
+            // 分别编译和执行，以便记录两部分耗时
+            None => crate::code_cache::run_script(runtime, name, code, None, false, timings)
+                .map(drop)
+                .map_err(|e| anyhow!("{}", format_error(e))),
+        });
+        self.record_script(name, replay_code, self.end_execution(execution, result))
+    }
 
-            Ok(result)
+    /// 执行前复制一份要记录到重放日志的代码（不再记录或超过记录上限时为 None）
+    fn replay_copy(&self, code: &str) -> Option<String> {
+        let unbounded = self.recycle.get().is_some();
+        self.replay.borrow_mut().reserve(code.len(), unbounded).then(|| code.to_string())
+    }
+
+    /// 脚本执行成功后记录到重放日志（pickle / deepcopy 使用）
+    fn record_script(&self, name: &str, code: Option<String>, result: Result<()>) -> Result<()> {
+        if let (Ok(()), Some(code)) = (&result, code) {
+            self.replay.borrow_mut().record(name, code);
         }
+        result
     }
 
+    /// 标记为无法重建 isolate（recycle_after 不可用）；已设置 recycle_after 时拒绝执行该操作
+    pub(crate) fn block_rebuild(&self, reason: &'static str) -> Result<()> {
+        if self.recycle.get().is_some() {
+            return Err(anyhow!("{}", crate::recycle::blocked_message(reason)));
+        }
+        self.replay.borrow_mut().block_rebuild(reason);
+        Ok(())
+    }
 
-    /// 请求垃圾回收
-    fn request_gc(&self) -> Result<()> {
-        self.enter_isolate();
-        let mut runtime = self.runtime.borrow_mut();
-        let _ =
-            runtime.execute_script("<gc_hint>", "if (typeof gc === 'function') { gc(); } null;");
-        drop(runtime);
-        self.exit_isolate();
+    /// 标记为无法重放（pickle 和 recycle_after 都不可用）
+    pub(crate) fn block_replay(&self, reason: &'static str) -> Result<()> {
+        self.block_rebuild(reason)?;
+        self.replay.borrow_mut().block(reason);
         Ok(())
     }
 
-    /// 获取 V8 堆内存统计信息
+    /// 设置 recycle_after 阈值（从现在开始计数）
+    pub(crate) fn set_recycle_policy(&self, policy: RecyclePolicy) -> Result<()> {
+        if let Some(reason) = self.replay.borrow().rebuild_blocker() {
+            return Err(anyhow!("{}", crate::recycle::blocked_message(reason)));
+        }
+        self.recycle.set(Some(policy));
+        self.recycle_calls.set(0);
+        Ok(())
+    }
+
+    /// 设置结果过滤函数（compile(result_filter=...)），name 为空字符串时清除
     ///
-    /// 返回当前 JavaScript 运行时的内存使用情况，包括总堆大小、已用大小等详细指标
-    fn get_heap_stats(&self) -> Result<std::collections::HashMap<String, usize>> {
+    /// 设置时检查 name 能求值为函数（compile() 的代码已经执行）。
+    pub(crate) fn set_result_filter(&self, name: String) -> Result<()> {
+        if name.is_empty() {
+            self.result_filter.borrow_mut().take();
+            return Ok(());
+        }
         self.enter_isolate();
-        let mut runtime = self.runtime.borrow_mut();
+        let resolved = resolve_result_filter(&mut self.runtime.borrow_mut(), &name).map(drop);
+        self.exit_isolate();
+        resolved?;
+        *self.result_filter.borrow_mut() = Some(name);
+        Ok(())
+    }
 
-        // 直接访问 V8 isolate 并获取堆统计信息
-        let isolate = runtime.v8_isolate();
-        let heap_stats = isolate.get_heap_statistics();
+    /// 是否达到 recycle_after 阈值，或 poisoned 后需要自动重建（auto_recreate=True）
+    pub(crate) fn recycle_due(&self) -> bool {
+        self.recreate_due()
+            || self
+                .recycle
+                .get()
+                .is_some_and(|policy| policy.reached(self.recycle_calls.get(), || self.used_heap_bytes()))
+    }
 
-        let mut stats = std::collections::HashMap::new();
-        stats.insert("total_heap_size".to_string(), heap_stats.total_heap_size());
-        stats.insert("total_heap_size_executable".to_string(), heap_stats.total_heap_size_executable());
-        stats.insert("total_physical_size".to_string(), heap_stats.total_physical_size());
-        stats.insert("total_available_size".to_string(), heap_stats.total_available_size());
-        stats.insert("used_heap_size".to_string(), heap_stats.used_heap_size());
-        stats.insert("heap_size_limit".to_string(), heap_stats.heap_size_limit());
-        stats.insert("malloced_memory".to_string(), heap_stats.malloced_memory());
-        stats.insert("external_memory".to_string(), heap_stats.external_memory());
-        stats.insert("peak_malloced_memory".to_string(), heap_stats.peak_malloced_memory());
-        stats.insert("number_of_native_contexts".to_string(), heap_stats.number_of_native_contexts());
-        stats.insert("number_of_detached_contexts".to_string(), heap_stats.number_of_detached_contexts());
+    /// poisoned 且可以自动重建
+    fn recreate_due(&self) -> bool {
+        self.poisoned.borrow().is_some() && self.can_recreate()
+    }
 
-        drop(runtime);
-        self.exit_isolate();
+    /// 达到 recycle_after 阈值（或 poisoned 且 auto_recreate=True）时返回重建的 Context
+    ///
+    /// 重建失败时通过 sys.unraisablehook 报告并返回 None，继续使用当前 isolate，下一次调用结束时重试。
+    pub(crate) fn recycled(&self) -> Option<Context> {
+        if !self.recycle_due() {
+            return None;
+        }
+        match self.rebuild(None) {
+            Ok(fresh) => Some(fresh),
+            Err(e) => {
+                Python::attach(|py| crate::quota::py_error("Recycle error", e).write_unraisable(py, None));
+                None
+            }
+        }
+    }
 
-        Ok(stats)
+    /// 用相同的构造参数创建新的 Context，按原顺序执行初始化脚本（跳过带 skip_tag 标签的脚本）、注册 Python 函数
+    fn rebuild(&self, skip_tag: Option<&str>) -> Result<Context> {
+        let (options, steps) = Python::attach(|py| self.replay.borrow().rebuild_steps(py, skip_tag));
+        let fresh = Context::new(options).map_err(|e| anyhow!("{}", e))?;
+        for step in steps {
+            match step {
+                RebuildStep::Script(name, code) => fresh.exec_named_script(&name, code, true)?,
+                RebuildStep::Function(name, func) => fresh.register_py_function(&name, func)?,
+            }
+        }
+        Ok(fresh)
     }
-}
 
-impl Drop for Context {
-    fn drop(&mut self) {
-        // V8 runtime 会在 RefCell 销毁时自动清理
-        // 注意：不要在这里调用 gc()，因为 Drop 可能在不同线程上被调用
-        // 如果需要手动 GC，请在业务代码中显式调用 ctx.gc() 或使用 with 语句
+    /// 去掉带 tag 标签的脚本后重建的 Context（unload() 使用），成功时从重放记录中删除这些脚本
+    fn unloaded(&self, tag: &str) -> Result<Context> {
+        let fresh = self.rebuild(Some(tag))?;
+        self.replay.borrow_mut().remove_tag(tag);
+        Ok(fresh)
     }
-}
 
-// ============================================
-// Python Methods
-// ============================================
+    /// 换成重建的 Context，转移调用统计、配额用量、执行历史、回调和脚本标签
+    pub(crate) fn replace_with(&mut self, fresh: Context) {
+        let mut old = std::mem::replace(self, fresh);
+        // 重建时重放的脚本与原记录相同，但只有原记录带有 compile(tag=...) 的标签
+        self.replay.swap(&old.replay);
+        *self.exec_count.borrow_mut() = *old.exec_count.borrow();
+        self.total_timings.set(old.total_timings.get());
+        self.last_timings.set(old.last_timings.get());
+        if let (Some(quota), Some(old_quota)) = (&self.quota, &old.quota) {
+            quota.carry_over(old_quota);
+        }
+        self.history = old.history.take();
+        if let Some((threshold, callback)) = old.slow_script.take() {
+            self.set_slow_script(threshold, Some(callback));
+        }
+        old.gc_observer.deliver();
+        if let Some(callback) = old.gc_observer.callback() {
+            self.set_gc_callback(Some(callback));
+        }
+        *self.background_event_loop.borrow_mut() = *old.background_event_loop.borrow();
+        self.watched.swap(&old.watched);
+        self.recycle.set(old.recycle.get());
+        self.recycle_calls.set(0);
+        self.result_filter.swap(&old.result_filter);
+        self.import_meta.swap(&old.import_meta);
+        self.handle_table.carry_over(&old.handle_table);
+    }
 
-#[pymethods]
-impl Context {
-    /// Python构造函数
-    ///
-    /// 创建一个新的JavaScript执行上下文
-    ///
-    /// Args:
-    ///     enable_extensions: 是否启用扩展（crypto, encoding 等），默认 True
-    ///                       - True: 启用所有扩展，自动注入 btoa/atob/md5/sha256 等函数
-    ///                       - False: 纯净 V8 环境，只包含 ECMAScript 标准 API
-    ///     enable_logging: 是否启用操作日志输出，默认 False
-    ///                     - True: 输出所有扩展操作的日志（用于调试）
-    ///                     - False: 不输出日志（推荐生产环境）
-    ///     random_seed: 随机数种子（可选），用于确定性随机数生成
-    ///                  - None: 使用系统随机数（非确定性）
-    ///                  - int: 使用固定种子（确定性）
-    ///                    所有随机数 API（Math.random、crypto.getRandomValues 等）
-    ///                    将基于此种子生成，方便调试和算法对比
+    /// Python 调用结束后检查 recycle_after 阈值，达到时换成重建的 Context
     ///
-    /// Example:
-    ///     ```python
-    ///     import never_jscore
+    /// register() 的 Python 函数中再次调用（抛出 RuntimeError）时外层调用仍在使用 Context，留到外层调用结束时检查。
+    fn recycle_if_due(slf: &Bound<'_, Self>) {
+        if !slf.try_borrow_mut().is_ok_and(|this| this.recycle_due()) {
+            return;
+        }
+        let fresh = {
+            let this = slf.borrow();
+            this.without_gil(slf.py(), |ctx| IsolateLocal::new(ctx.recycled())).into_inner()
+        };
+        if let Some(fresh) = fresh {
+            slf.borrow_mut().replace_with(fresh);
+        }
+    }
+
+    /// 执行 Python 调用，结束后检查 recycle_after 阈值
     ///
-    ///     # 创建带扩展的上下文（默认）
+    /// compile() 等不检查阈值的方法遇到致命错误后，在调用之前重建。
+    fn recycling<R>(slf: &Bound<'_, Self>, f: impl FnOnce(&Self) -> PyResult<R>) -> PyResult<R> {
+        if slf.borrow().recreate_due() {
+            Self::recycle_if_due(slf);
+        }
+        let result = f(&slf.borrow());
+        Self::recycle_if_due(slf);
+        result
+    }
+
+    /// 调用 prepare() 定义的函数（PreparedCall 使用），path 为函数的属性路径
+    pub(crate) fn call_prepared<'py>(
+        slf: &Bound<'py, Self>,
+        py: Python<'py>,
+        path: &str,
+        args: Vec<JsonValue>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Self::recycling(slf, |this| {
+            this.check_fork()?;
+            let result_json = this
+                .without_gil(py, |ctx| ctx.execute_call(path, &args, true))
+                .map_err(|e| crate::quota::py_error("Call error", e))?;
+
+            json_str_to_python(py, &result_json)
+        })
+    }
+
+    /// 作为 ES 模块执行（compile_file(mode="module") 使用）
+    ///
+    /// 模块的具名导出定义为全局变量，之后可以用 call() / evaluate() 访问（default 导出除外）。
+    /// 没有模块加载器：模块不能 import 其他文件，但可以使用顶层 await。
+    pub(crate) fn exec_module(&self, name: &str, code: String) -> Result<()> {
+        let specifier = crate::source_file::module_specifier(name)?;
+        self.block_replay("it has executed an ES module")?;
+        let callback = Python::attach(|py| self.import_meta.borrow().as_ref().map(|callback| callback.clone_ref(py)));
+        let code = match callback {
+            Some(callback) => crate::import_meta::apply(&callback, specifier.as_str(), name, code)?,
+            None => code,
+        };
+
+        let execution = self.begin_execution("script", name, &code);
+        let result = self.exec_script_with(|runtime, timings| {
+            let start = Instant::now();
+            let evaluated = run_with_tokio(async {
+                let id = runtime.load_side_es_module_from_code(&specifier, code).await?;
+                let evaluation = runtime.mod_evaluate(id);
+                runtime.with_event_loop_future(Box::pin(evaluation), Default::default()).await?;
+                Ok::<_, deno_core::error::CoreError>(id)
+            });
+            timings.execute += start.elapsed();
+            let id = evaluated.map_err(|e| anyhow!("{}", format_error(e.into())))?;
+            export_module_globals(runtime, id)
+        });
+        self.end_execution(execution, result)
+    }
+
+    /// 使用后台线程生成的代码缓存执行脚本（compile_background() 使用）
+    pub(crate) fn exec_precompiled_script(&self, name: &str, code: String, cache: &[u8]) -> Result<()> {
+        let replay_code = self.replay_copy(&code);
+        let execution = self.begin_execution("script", name, &code);
+        let result = self.exec_script_with(|runtime, timings| {
+            crate::code_cache::run_script(runtime, name, code, Some(cache), false, timings)
+                .map(drop)
+                .map_err(|e| anyhow!("{}", format_error(e)))
+        });
+        self.record_script(name, replay_code, self.end_execution(execution, result))
+    }
+
+    /// 开始一次执行：触发执行钩子（exec_hooks.rs）并开始记录执行历史（history=N）
+    pub(crate) fn begin_execution(&self, kind: &'static str, filename: &str, code: &str) -> Tracking {
+        self.recycle_calls.set(self.recycle_calls.get() + 1);
+        self.slow_script.set_current(kind, filename, code);
+        Tracking {
+            hooks: crate::exec_hooks::start(kind, filename, code, self.trace_id.borrow().as_deref()),
+            history: self.history.as_ref().map(|history| history.begin(kind, filename, code)),
+        }
+    }
+
+    /// 执行结束：触发 on_execute_end 并保存执行历史
+    pub(crate) fn end_execution<R>(&self, tracking: Tracking, result: Result<R>) -> Result<R> {
+        if let Some(execution) = tracking.hooks {
+            execution.finish(&result);
+        }
+        if let (Some(history), Some(pending)) = (&self.history, tracking.history) {
+            history.finish(pending, &result);
+        }
+        self.gc_observer.deliver();
+        result
+    }
+
+    /// 设置 GC 事件回调（callback 为 None 时清除）
+    pub(crate) fn set_gc_callback(&self, callback: Option<Py<PyAny>>) {
+        let mut runtime = self.runtime.borrow_mut();
+        self.gc_observer.set(runtime.v8_isolate(), callback);
+    }
+
+    /// 设置慢脚本回调（callback 为 None 时清除）
+    pub(crate) fn set_slow_script(&self, threshold: Duration, callback: Option<Py<PyAny>>) {
+        self.slow_script.set(threshold, callback);
+    }
+
+    /// 执行历史（未开启 history 时返回错误）
+    pub(crate) fn history_entries(&self) -> PyResult<Vec<HistoryEntry>> {
+        self.history
+            .as_ref()
+            .map(History::snapshot)
+            .ok_or_else(|| PyRuntimeError::new_err("Execution history is disabled; create the Context with history=N"))
+    }
+
+    /// 执行脚本的公共流程：加载 polyfill、进入 isolate、执行、运行 event loop、更新计数
+    ///
+    /// `run` 负责编译和执行，并把两部分耗时记录到传入的 Timings。
+    fn exec_script_with(&self, run: impl FnOnce(&mut JsRuntime, &mut Timings) -> Result<()>) -> Result<()> {
+        self.guard_fatal(|| {
+            self.with_quota(|| {
+                self.timings.set(Timings::default());
+                self.clear_audit_log();
+                let guard = self.watch();
+                let deadline = guard.as_ref().and_then(WatchGuard::deadline);
+                let result = self.exec_script_inner(run, deadline);
+                let result = self.finish_watch(guard, result);
+                self.finish_timings();
+                result
+            })
+        })
+    }
+
+    fn exec_script_inner(
+        &self,
+        run: impl FnOnce(&mut JsRuntime, &mut Timings) -> Result<()>,
+        deadline: Option<Instant>,
+    ) -> Result<()> {
+        // Ensure polyfill is loaded before first execution
+        self.ensure_polyfill_loaded()?;
+
+        // CRITICAL: Re-enter isolate to ensure it's current
+        // This fixes the multi-Context issue where creating ctx2 breaks ctx1
+        self.enter_isolate();
+
+        let mut runtime = self.runtime.borrow_mut();
+
+        // 全局脚本可能声明遮蔽全局属性的 let/const，call() 重新解析目标函数
+        self.call_targets.clear();
+
+        let mut timings = Timings::default();
+        let result = run(&mut runtime, &mut timings);
+        self.record_timing(|t| t.add(&timings));
+
+        if let Err(e) = result {
+            if self.limit_reached() {
+                runtime.v8_isolate().cancel_terminate_execution();
+                return Err(self.take_limit_error().unwrap_or(e));
+            }
+            return Err(e);
+        }
+
+        // 简化的定时器处理：只运行 event loop 来处理微任务
+        // 定时器通过 queueMicrotask 自动调度，依赖真实时间
+        drop(runtime);
+
+        // 使用 Tokio 运行 event loop (处理 queueMicrotask 队列)
+        // 后台事件循环模式下由工作线程在空闲时推进，这里不等待（setInterval 永远不会完成）
+        if !*self.background_event_loop.borrow() {
+            let start = Instant::now();
+            run_with_tokio(async {
+                // 运行 event loop 直到微任务队列为空（超过 timeout_ms 时停止等待）
+                let event_loop = crate::event_loop::run(&self.runtime, self.event_loop.get(), || true, false);
+                self.run_until_deadline(event_loop, deadline).await;
+            });
+            self.record_timing(|t| t.event_loop += start.elapsed());
+        }
+
+        if self.limit_reached() {
+            self.runtime.borrow_mut().v8_isolate().cancel_terminate_execution();
+            if let Some(e) = self.take_limit_error() {
+                return Err(e);
+            }
+        }
+
+        // Exit isolate after operations complete
+        self.exit_isolate();
+
+        // 更新执行计数
+        let mut count = self.exec_count.borrow_mut();
+        *count += 1;
+
+        // 每 100 次执行后提示 GC
+        if *count % 100 == 0 {
+            std::hint::black_box(());
+        }
+
+        Ok(())
+    }
+
+    /// 调用预编译的求值包装函数
+    ///
+    /// 首次调用时编译包装函数并缓存在 Context 中。
+    /// 结果通过 op_store_result 按 call_id 写入 result_storage；异步模式下需要随后运行 event loop。
+    ///
+    /// repl 为 true 时代码总是作为全局脚本执行（repl_eval()），完成值赋给全局 _ 后交给包装函数。
+    fn call_eval_wrapper(&self, runtime: &mut JsRuntime, code: String, auto_await: bool, repl: bool, call_id: u32) -> Result<()> {
+        let wrap_start = Instant::now();
+        let mut wrappers = self.eval_wrappers.borrow_mut();
+        if wrappers.is_none() {
+            let undefined_as = self.undefined_as.name().map_or("undefined".to_string(), |name| format!("'{}'", name));
+            let to_json = runtime
+                .execute_script("<result_to_json>", format!("{}({})", RESULT_TO_JSON.trim(), undefined_as))
+                .map_err(|e| anyhow!("Failed to compile result serializer: {}", format_error(e.into())))?;
+            let check_schema = runtime
+                .execute_script("<check_schema>", crate::schema::check_schema_source())
+                .map_err(|e| anyhow!("Failed to compile schema checker: {}", format_error(e.into())))?;
+            *wrappers = Some(EvalWrappers {
+                sync: compile_wrapper(runtime, "<eval_sync>", EVAL_WRAPPER_SYNC, &to_json, &check_schema)?,
+                async_: compile_wrapper(runtime, "<eval_async>", EVAL_WRAPPER_ASYNC, &to_json, &check_schema)?,
+                repl: compile_wrapper(runtime, "<repl_assign>", REPL_ASSIGN, &to_json, &check_schema)?,
+            });
+        }
+        let wrappers = wrappers.as_ref().expect("eval wrappers compiled above");
+        let wrapper = if auto_await { &wrappers.async_ } else { &wrappers.sync };
+
+        // 每次调用按名称重新求值过滤函数，代码中替换了它时使用新的函数
+        let filter = self.result_filter.borrow().clone();
+        let filter = filter.map(|name| resolve_result_filter(runtime, &name)).transpose()?;
+
+        // call() 的目标函数可以解析时直接调用缓存的函数，把返回值交给包装函数（见 call_cache.rs）
+        let called = match self.call_targets.take_pending() {
+            Some(name) if !repl => {
+                let execute_start = Instant::now();
+                let value = self.call_targets.call(runtime, &name, &code, self.handle_room());
+                self.record_timing(|t| t.execute += execute_start.elapsed());
+                value?
+            }
+            _ => None,
+        };
+
+        // 禁止动态代码时包装函数不能 eval，先在 Rust 侧把代码作为脚本执行，再把值交给包装函数
+        // repl_eval() 需要全局脚本的完成值语义和保留的 let/const，同样走这条路径
+        let precomputed = if called.is_some() {
+            called
+        } else if self.allow_dynamic_code && !repl {
+            None
+        } else {
+            let execute_start = Instant::now();
+            let value = evaluate_as_script(runtime, &code, repl)
+                .and_then(|value| if repl { assign_repl_result(runtime, &wrappers.repl, value, auto_await) } else { Ok(value) });
+            self.record_timing(|t| t.execute += execute_start.elapsed());
+            Some(value?)
+        };
+
+        let stream = self.result_storage.stream(call_id).is_some();
+        let schema = self.result_storage.schema(call_id);
+        let (code_arg, call_id_arg, is_value_arg, stream_arg, filter_arg, schema_arg) = {
+            deno_core::scope!(scope, runtime);
+            let code_arg = match &precomputed {
+                Some(value) => value.clone(),
+                None => {
+                    let code = crate::source_string::new_source(scope, code).ok_or_else(|| anyhow!("Code is too large"))?;
+                    v8::Global::new(scope, v8::Local::<v8::Value>::from(code))
+                }
+            };
+            let call_id = v8::Integer::new_from_unsigned(scope, call_id);
+            let is_value = v8::Boolean::new(scope, precomputed.is_some());
+            let stream = v8::Boolean::new(scope, stream);
+            let filter = filter.unwrap_or_else(|| v8::Global::new(scope, v8::Local::<v8::Value>::from(v8::undefined(scope))));
+            let schema: v8::Local<v8::Value> = match schema.as_deref().and_then(|schema| v8::String::new(scope, schema)) {
+                Some(schema) => schema.into(),
+                None => v8::undefined(scope).into(),
+            };
+            (
+                code_arg,
+                v8::Global::new(scope, v8::Local::<v8::Value>::from(call_id)),
+                v8::Global::new(scope, v8::Local::<v8::Value>::from(is_value)),
+                v8::Global::new(scope, v8::Local::<v8::Value>::from(stream)),
+                filter,
+                v8::Global::new(scope, schema),
+            )
+        };
+
+        self.record_timing(|t| t.wrap += wrap_start.elapsed());
+
+        // 包装函数不返回 Promise，调用结果立即可用
+        let execute_start = Instant::now();
+        let result = runtime
+            .call_with_args(wrapper, &[code_arg, call_id_arg, is_value_arg, stream_arg, filter_arg, schema_arg])
+            .now_or_never();
+        self.record_timing(|t| t.execute += execute_start.elapsed());
+
+        match result {
+            Some(Err(e)) => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// 执行 JavaScript 代码并返回结果
+    ///
+    /// 根据 auto_await 参数决定是否自动等待 Promise。
+    /// 注意：这个方法用于求值，代码在IIFE中执行，不会影响全局作用域
+    ///
+    /// Early Return 机制：
+    /// - 当 JS 调用 __neverjscore_return__(value) 时，会抛出 EarlyReturnError
+    /// - 该错误会携带返回值并中断 JS 执行
+    /// - Rust 侧通过 downcast 检测并提取返回值
+    pub(crate) fn execute_js(&self, code: String, auto_await: bool) -> Result<String> {
+        self.evaluate_code(code, auto_await, false)
+    }
+
+    /// 调用函数 name(args...)（call()），目标函数可以解析时使用缓存的函数，见 call_cache.rs
+    pub(crate) fn execute_call(&self, name: &str, args: &[JsonValue], auto_await: bool) -> Result<String> {
+        self.call_targets.expect(name);
+        let result = self.execute_js(format_call(name, args), auto_await);
+        self.call_targets.take_pending();
+        result
+    }
+
+    /// 以 expect_schema 检查 f 中第一次求值的结果（call(expect_schema=...)），schema 为规则的 JSON 文本
+    pub(crate) fn with_schema<T>(&self, schema: Option<&str>, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        if schema.is_none() {
+            return f();
+        }
+        self.result_storage.set_pending_schema(schema.map(Rc::from));
+        let result = f();
+        self.result_storage.set_pending_schema(None);
+        result
+    }
+
+    /// execute_js 的分块输出版本（evaluate(stream_to=...) / call(stream_to=...)）
+    ///
+    /// 结果 JSON 分块写入 stream，返回写入的字节数。没有经过分块的结果（undefined、提前返回的值）整体写入。
+    pub(crate) fn execute_js_streaming(&self, code: String, auto_await: bool, stream: ResultStream) -> Result<usize> {
+        self.streaming(stream, || self.execute_js(code, auto_await))
+    }
+
+    /// execute_call 的分块输出版本（call(stream_to=...)）
+    pub(crate) fn execute_call_streaming(&self, name: &str, args: &[JsonValue], auto_await: bool, stream: ResultStream) -> Result<usize> {
+        self.streaming(stream, || self.execute_call(name, args, auto_await))
+    }
+
+    fn streaming(&self, stream: ResultStream, execute: impl FnOnce() -> Result<String>) -> Result<usize> {
+        let stream = Rc::new(stream);
+        self.result_storage.set_pending_stream(Some(stream.clone()));
+        let result = execute();
+        self.result_storage.set_pending_stream(None);
+        if let Some(e) = stream.take_error() {
+            return Err(e);
+        }
+        let rest = result?;
+        if !rest.is_empty() {
+            stream.write(&rest, self.quota.as_deref())?;
+        }
+        Ok(stream.written())
+    }
+
+    /// 以 trace_id 执行 f（call(trace_id=...)），结束后恢复外层调用的 ID；trace_id 为 None 时沿用外层调用的 ID
+    pub(crate) fn with_trace_id<T>(&self, trace_id: Option<String>, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        if trace_id.is_none() {
+            return f();
+        }
+        self.ensure_polyfill_loaded()?;
+        let outer = self.set_trace_id(trace_id)?;
+        let result = f();
+        let restored = self.set_trace_id(outer);
+        let result = result?;
+        restored?;
+        Ok(result)
+    }
+
+    /// 设置当前的追踪 ID（JS 全局变量和 console 输出），返回之前的 ID
+    fn set_trace_id(&self, trace_id: Option<String>) -> Result<Option<String>> {
+        self.enter_isolate();
+        let defined = crate::trace::set_global(&mut self.runtime.borrow_mut(), trace_id.as_deref());
+        self.exit_isolate();
+        defined?;
+        self.console_capture.set_trace_id(trace_id.clone());
+        Ok(self.trace_id.replace(trace_id))
+    }
+
+    /// 使用 options 执行 f（eval() / evaluate() / call() 的 event_loop={...}），结束后恢复外层调用的选项
+    pub(crate) fn with_event_loop<T>(&self, options: EventLoopOptions, f: impl FnOnce() -> T) -> T {
+        let outer = self.event_loop.replace(options);
+        let result = f();
+        self.event_loop.set(outer);
+        result
+    }
+
+    /// 像控制台一样执行一行输入（repl_eval()）
+    ///
+    /// 代码作为全局脚本执行：返回完成值，let/const/class 声明在后续输入中可见，
+    /// 结果赋给全局 _。成功执行的输入会被记录，pickle 时按顺序重放。
+    pub(crate) fn execute_repl(&self, code: String, auto_await: bool) -> Result<String> {
+        let recorded = self.replay_copy(&code);
+        self.call_targets.clear();
+        let result = self.evaluate_code(code, auto_await, true)?;
+        self.record_script("<repl>", recorded, Ok(()))?;
+        Ok(result)
+    }
+
+    /// execute_js / execute_repl 的实现
+    fn evaluate_code(&self, code: String, auto_await: bool, repl: bool) -> Result<String> {
+        self.reload_watched()?;
+        let execution = self.begin_execution("evaluate", if repl { "<repl>" } else { "<eval>" }, &code);
+        let result = self.guard_fatal(|| {
+            self.with_quota(|| {
+                // 每次求值使用独立的调用 ID，结束后丢弃迟到的结果
+                let call_id = self.result_storage.begin_call();
+                self.timings.set(Timings::default());
+                self.clear_audit_log();
+                let guard = self.watch();
+                let deadline = guard.as_ref().and_then(WatchGuard::deadline);
+                let result = self.execute_js_call(code, auto_await, repl, call_id, deadline);
+                let result = self.finish_watch(guard, result);
+                self.finish_timings();
+                let mismatch = self.result_storage.take_mismatch(call_id);
+                self.result_storage.end_call(call_id);
+                let result = result?;
+                if let Some(mismatch) = mismatch {
+                    return Err(crate::schema::mismatch_error(&mismatch));
+                }
+                self.check_result_quota(&result)?;
+                Ok(result)
+            })
+        });
+        self.end_execution(execution, result)
+    }
+
+    /// execute_js 的实现，结果从 result_storage 中按 call_id 取出
+    fn execute_js_call(&self, code: String, auto_await: bool, repl: bool, call_id: u32, deadline: Option<Instant>) -> Result<String> {
+        // Ensure polyfill is loaded before first execution
+        self.ensure_polyfill_loaded()?;
+
+        // CRITICAL: Re-enter isolate
+        self.enter_isolate();
+
+        if auto_await {
+            // 异步模式：自动等待 Promise
+            let result = run_with_tokio(async {
+                // 调用预编译的包装函数（runtime 只在调用期间借用，运行 event loop 时不持有借用）
+                let execute_result = self.call_eval_wrapper(&mut self.runtime.borrow_mut(), code, true, repl, call_id);
+
+                // 检查是否是 EarlyReturnError
+                if let Err(e) = execute_result {
+                    // 检查是否是早期返回
+                    if self.result_storage.is_early_return() {
+                        // 提前返回：直接返回存储的值
+                        let result = self.result_storage.take(call_id)
+                            .ok_or_else(|| anyhow!("Early return but no result stored"))?;
+                        let mut count = self.exec_count.borrow_mut();
+                        *count += 1;
+                        return Ok(result);
+                    }
+
+                    // ⚠️ 检查是否是 terminate_execution 错误
+                    let error_msg = format_error(e);
+                    if error_msg.contains("execution terminated") {
+                        // 恢复 isolate 状态，允许后续执行
+                        self.runtime.borrow_mut().v8_isolate().cancel_terminate_execution();
+                        if let Some(e) = self.take_limit_error() {
+                            return Err(e);
+                        }
+                    }
+
+                    // 其他错误 - 格式化后返回
+                    return Err(anyhow!("{}", error_msg));
+                }
+
+                // 运行 event loop 等待 Promise 完成
+                let event_loop_start = Instant::now();
+                // 后台事件循环模式：结果就绪即返回，剩余的定时器交给后台继续运行
+                let background = *self.background_event_loop.borrow();
+                let event_loop = crate::event_loop::run(
+                    &self.runtime,
+                    self.event_loop.get(),
+                    || self.result_storage.has_value(call_id) || self.result_storage.is_early_return(),
+                    background,
+                );
+                // 超过 timeout_ms 时停止等待，按执行被终止处理
+                let mut event_loop_result = Err(anyhow!("execution terminated"));
+                self.run_until_deadline(
+                    async { event_loop_result = event_loop.await },
+                    deadline,
+                )
+                .await;
+                self.record_timing(|t| t.event_loop += event_loop_start.elapsed());
+
+                // 检查 event loop 是否遇到 EarlyReturnError
+                if let Err(e) = event_loop_result {
+                    // 检查是否是早期返回
+                    if self.result_storage.is_early_return() {
+                        // Event loop 中的提前返回
+                        let result = self.result_storage.take(call_id)
+                            .ok_or_else(|| anyhow!("Early return but no result stored"))?;
+                        let mut count = self.exec_count.borrow_mut();
+                        *count += 1;
+                        return Ok(result);
+                    }
+
+                    // ⚠️ 检查是否是 terminate_execution 错误
+                    let error_msg = format_error(e);
+                    if error_msg.contains("execution terminated") {
+                        // 恢复 isolate 状态，允许后续执行
+                        self.runtime.borrow_mut().v8_isolate().cancel_terminate_execution();
+                        if let Some(e) = self.take_limit_error() {
+                            return Err(e);
+                        }
+                    }
+
+                    // 其他错误 - 格式化后返回
+                    return Err(anyhow!("{}", error_msg));
+                }
+
+                // 检查是否设置了 early return 标志（即使 event loop 正常完成）
+                // 这处理了 eval() 内部调用 __neverjscore_return__ 的情况
+                if self.result_storage.is_early_return() {
+                    let result = self.result_storage.take(call_id)
+                        .ok_or_else(|| anyhow!("Early return but no result stored"))?;
+                    let mut count = self.exec_count.borrow_mut();
+                    *count += 1;
+                    return Ok(result);
+                }
+
+                // 正常完成：从 result_storage 获取结果
+                let result = self
+                    .result_storage
+                    .take(call_id)
+                    .ok_or_else(|| anyhow!("No result stored after event loop"))?;
+
+                let mut count = self.exec_count.borrow_mut();
+                *count += 1;
+
+                Ok(result)
+            });
+
+            // Exit isolate after async operations complete
+            self.exit_isolate();
+            result
+        } else {
+            // 同步模式：不等待 Promise
+            let mut runtime = self.runtime.borrow_mut();
+
+            let execute_result = self.call_eval_wrapper(&mut runtime, code, false, repl, call_id);
+
+            // 检查是否是 EarlyReturnError
+            if let Err(e) = execute_result {
+                // 检查是否是早期返回
+                if self.result_storage.is_early_return() {
+                    // 提前返回
+                    let result = self.result_storage.take(call_id)
+                        .ok_or_else(|| anyhow!("Early return but no result stored"))?;
+                    let mut count = self.exec_count.borrow_mut();
+                    *count += 1;
+                    return Ok(result);
+                }
+
+                // ⚠️ 检查是否是 terminate_execution 错误
+                let error_msg = format_error(e);
+                if error_msg.contains("execution terminated") {
+                    // 恢复 isolate 状态，允许后续执行
+                    runtime.v8_isolate().cancel_terminate_execution();
+                    if let Some(e) = self.take_limit_error() {
+                        return Err(e);
+                    }
+                }
+
+                return Err(anyhow!("{}", error_msg));
+            }
+
+            // 从 storage 获取结果
+            let result = self
+                .result_storage
+                .take(call_id)
+                .ok_or_else(|| anyhow!("No result stored"))?;
+
+            let mut count = self.exec_count.borrow_mut();
+            *count += 1;
+
+            // Exit isolate after sync operations complete
+            self.exit_isolate();
+
+            Ok(result)
+        }
+    }
+
+
+    /// 运行 future 直到完成或到达墙钟截止时间，超时返回 false 并记录超时原因
+    ///
+    /// 异步调用被中止时同样返回 false（原因已由 AbortSignal 记录）。
+    async fn run_until_deadline(&self, future: impl Future<Output = impl Sized>, deadline: Option<Instant>) -> bool {
+        let abort = self.abort_signal.borrow().clone();
+        let future = async {
+            match &abort {
+                Some(signal) => tokio::select! {
+                    _ = future => true,
+                    _ = signal.aborted() => false,
+                },
+                None => {
+                    future.await;
+                    true
+                }
+            }
+        };
+        let Some(deadline) = deadline else {
+            return future.await;
+        };
+        if let Ok(completed) = tokio::time::timeout_at(deadline.into(), future).await {
+            return completed;
+        }
+        let mut exceeded = self.limit_exceeded.lock().unwrap();
+        if exceeded.is_none() {
+            *exceeded = self.limits.timeout.map(|timeout| LimitExceeded::Timeout(timeout).into());
+        }
+        false
+    }
+
+    /// 开启/关闭后台事件循环模式
+    ///
+    /// 开启后 execute_js 在结果就绪时立即返回，不再等待所有定时器完成；
+    /// 由持有 Context 的工作线程在空闲时调用 pump_event_loop() 推进剩余的定时器和 Promise。
+    pub(crate) fn set_background_event_loop(&self, enabled: bool) {
+        *self.background_event_loop.borrow_mut() = enabled;
+    }
+
+    /// 是否处于后台事件循环模式
+    pub(crate) fn background_event_loop(&self) -> bool {
+        *self.background_event_loop.borrow()
+    }
+
+    /// 推进事件循环，最多运行 max_duration
+    ///
+    /// 定时器回调中未捕获的错误会被记录下来，通过 take_event_loop_errors() 获取。
+    pub(crate) fn pump_event_loop(&self, max_duration: Duration) {
+        if self.ensure_polyfill_loaded().is_err() {
+            return;
+        }
+
+        self.enter_isolate();
+
+        // 定时器回调同样受 cpu_limit_ms 限制，消耗的 CPU 时间计入 max_cpu_ms 配额
+        let guard = self.watch();
+        let cpu_start = crate::watchdog::thread_cpu_time();
+        // 每次 poll 单独借用 runtime，等待期间不持有 RefCell 借用
+        let result = run_with_tokio(async {
+            let drain = std::future::poll_fn(|cx| self.runtime.borrow_mut().poll_event_loop(cx, Default::default()));
+            tokio::time::timeout(max_duration, drain).await
+        });
+        self.charge_quota_cpu(cpu_start);
+        let result = match result {
+            Ok(Err(e)) if self.limit_reached() => {
+                self.isolate_handle.cancel_terminate_execution();
+                let e = self.take_limit_error().unwrap_or_else(|| e.into());
+                self.finish_watch(guard, Err(e))
+            }
+            Ok(Err(e)) => self.finish_watch(guard, Err(e.into())),
+            _ => self.finish_watch(guard, Ok(())),
+        };
+
+        self.restore_heap_limit();
+
+        // 超时说明还有未完成的定时器，属于正常情况
+        if let Err(e) = result {
+            self.event_loop_errors.borrow_mut().push(format_error(e));
+        }
+
+        self.exit_isolate();
+        self.gc_observer.deliver();
+    }
+
+    /// 在 isolate 中单步推进事件循环（run_microtasks() / drain_tasks()）
+    ///
+    /// 与一次执行相同：受 timeout_ms / cpu_limit_ms 和配额限制，致命错误使 Context 进入 poisoned 状态。
+    fn step_event_loop<R>(&self, step: impl FnOnce(&mut JsRuntime, Option<Instant>) -> Result<R>) -> Result<R> {
+        self.guard_fatal(|| {
+            self.with_quota(|| {
+                self.ensure_polyfill_loaded()?;
+                self.enter_isolate();
+                let guard = self.watch();
+                let deadline = guard.as_ref().and_then(WatchGuard::deadline);
+                let result = step(&mut self.runtime.borrow_mut(), deadline);
+                let result = if self.limit_reached() {
+                    self.runtime.borrow_mut().v8_isolate().cancel_terminate_execution();
+                    self.take_limit_error().map_or(result, Err)
+                } else {
+                    result
+                };
+                let result = self.finish_watch(guard, result);
+                self.exit_isolate();
+                self.gc_observer.deliver();
+                result
+            })
+        })
+    }
+
+    /// 执行一次微任务检查点：运行已排队的 Promise 回调和 queueMicrotask()，不运行定时器
+    pub(crate) fn microtask_checkpoint(&self) -> Result<()> {
+        self.step_event_loop(|runtime, _| {
+            runtime.v8_isolate().perform_microtask_checkpoint();
+            Ok(())
+        })
+    }
+
+    /// 运行事件循环（到期的定时器、完成的异步 op 和微任务），直到没有待处理的任务或经过 max_duration
+    ///
+    /// 返回是否已经没有待处理的任务；定时器回调中未捕获的错误作为错误返回。
+    pub(crate) fn drain_event_loop(&self, max_duration: Option<Duration>) -> Result<bool> {
+        self.step_event_loop(|runtime, deadline| {
+            run_with_tokio(async {
+                let drain = runtime.run_event_loop(Default::default());
+                let mut result = Err(anyhow!("execution terminated"));
+                let drained = async {
+                    result = match max_duration {
+                        Some(max_duration) => match tokio::time::timeout(max_duration, drain).await {
+                            Ok(drained) => drained.map(|()| true).map_err(Into::into),
+                            Err(_) => Ok(false),
+                        },
+                        None => drain.await.map(|()| true).map_err(Into::into),
+                    };
+                };
+                self.run_until_deadline(drained, deadline).await;
+                result.map_err(|e| anyhow!("{}", format_error(e)))
+            })
+        })
+    }
+
+    /// 运行事件循环直到没有待处理的任务（Worker 线程收到消息时使用）
+    pub(crate) fn run_event_loop(&self) -> Result<()> {
+        self.exec_script_with(|_, _| Ok(()))
+    }
+
+    /// 取出后台事件循环中记录的错误
+    pub(crate) fn take_event_loop_errors(&self) -> Vec<String> {
+        std::mem::take(&mut *self.event_loop_errors.borrow_mut())
+    }
+
+    /// 请求垃圾回收
+    pub(crate) fn request_gc(&self) -> Result<()> {
+        self.enter_isolate();
+        let mut runtime = self.runtime.borrow_mut();
+        let _ =
+            runtime.execute_script("<gc_hint>", "if (typeof gc === 'function') { gc(); } null;");
+        drop(runtime);
+        self.exit_isolate();
+        self.gc_observer.deliver();
+        Ok(())
+    }
+
+    /// 试运行代码，返回其中用到但全局对象上不存在的名称（missing_globals()）
+    pub(crate) fn find_missing_globals(&self, code: &str) -> Result<Vec<String>> {
+        self.ensure_polyfill_loaded()?;
+        let script = crate::missing_globals::probe_script(code);
+        let json = self.with_runtime(|runtime| {
+            let value = runtime
+                .execute_script("<missing_globals>", script)
+                .map_err(|e| anyhow!("{}", format_error(e.into())))?;
+            deno_core::scope!(scope, runtime);
+            let value = v8::Local::new(scope, value);
+            Ok(value.to_rust_string_lossy(scope))
+        })?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// 以结构化克隆序列化全局变量 keys（export_state() 使用）
+    pub(crate) fn export_globals(&self, keys: &[String]) -> Result<Vec<u8>> {
+        self.ensure_polyfill_loaded()?;
+        self.with_runtime(|runtime| crate::state::export(runtime, keys, Mode::Storage))
+    }
+
+    /// 计算全局对象每个属性的指纹（snapshot_globals() / diff_globals() 使用）
+    pub(crate) fn snapshot_global_fingerprints(&self) -> Result<BTreeMap<String, String>> {
+        self.ensure_polyfill_loaded()?;
+        self.with_runtime(crate::globals_diff::snapshot)
+    }
+
+    /// 把 export_state() 的数据恢复为全局变量，返回恢复的名称
+    pub(crate) fn import_globals(&self, payload: Vec<u8>) -> Result<Vec<String>> {
+        self.block_replay("it has imported state with import_state()")?;
+        self.ensure_polyfill_loaded()?;
+        self.with_runtime(|runtime| crate::state::import(runtime, payload, Mode::Storage, None))
+    }
+
+    /// 序列化全局变量 name，用于在进程内传给另一个 Context（transfer()）
+    pub(crate) fn send_global(&self, name: &str) -> Result<Vec<u8>> {
+        self.ensure_polyfill_loaded()?;
+        self.with_runtime(|runtime| crate::state::export(runtime, &[name.to_string()], Mode::Transfer))
+    }
+
+    /// 把 send_global() 的数据定义为全局变量 name（transfer()）
+    pub(crate) fn receive_global(&self, payload: Vec<u8>, name: &str) -> Result<()> {
+        self.block_replay("it has received a value with transfer()")?;
+        self.ensure_polyfill_loaded()?;
+        let payload = crate::state::payload(&payload).map_err(|e| anyhow!("{}", e))?.to_vec();
+        self.with_runtime(|runtime| crate::state::import(runtime, payload, Mode::Transfer, Some(name)).map(drop))
+    }
+
+    /// 把 Python 函数注册为 JS 全局函数
+    pub(crate) fn register_py_function(&self, name: &str, func: Py<PyAny>) -> Result<()> {
+        let recorded = Python::attach(|py| func.clone_ref(py));
+        self.with_runtime(|runtime| crate::py_functions::register(runtime, name, func))?;
+        self.replay.borrow_mut().record_function(name, recorded);
+        Ok(())
+    }
+
+    /// 加载 WebAssembly 模块，返回导出项 [(名称, 类型)]
+    pub(crate) fn load_wasm_module(
+        &self,
+        name: &str,
+        bytes: Vec<u8>,
+        imports: Vec<WasmImport>,
+        wasi: Option<WasiConfig>,
+    ) -> Result<Vec<(String, String)>> {
+        self.block_replay("it has loaded WebAssembly modules")?;
+        self.ensure_polyfill_loaded()?;
+        self.with_runtime(|runtime| {
+            crate::wasm::load(runtime, name, bytes, imports, wasi).map_err(|e| anyhow!("{}", crate::wasm::feature_hint(format_error(e))))
+        })
+    }
+
+    /// 把共享内存定义为全局 SharedArrayBuffer（share_buffer()）
+    pub(crate) fn share_buffer_store(&self, name: &str, store: PinnedStore) -> Result<()> {
+        self.ensure_polyfill_loaded()?;
+        self.block_replay("it has shared buffers")?;
+        self.with_runtime(|runtime| define_shared_buffer(runtime, name, &store))
+    }
+
+    /// 取出 expr 指向的 WASM 内存的 backing store
+    pub(crate) fn wasm_memory_store(&self, expr: &str) -> Result<PinnedStore> {
+        self.ensure_polyfill_loaded()?;
+        self.with_runtime(|runtime| crate::wasm_memory::find_store(runtime, expr).map_err(|e| anyhow!("{}", format_error(e))))
+    }
+
+    /// 通知 V8 系统内存不足：立即执行完整 GC 并尽可能释放内存
+    pub(crate) fn low_memory_notification(&self) -> Result<()> {
+        self.with_runtime(|runtime| {
+            runtime.v8_isolate().low_memory_notification();
+            Ok(())
+        })
+    }
+
+    /// 强制垃圾回收，并执行因此触发的 FinalizationRegistry 清理回调（collect_garbage()）
+    ///
+    /// full=true 时反复执行完整 GC，直到没有更多可回收的对象（与 low_memory_notification 相同）；
+    /// 否则只执行一次完整 GC。之后处理平台投递到该 isolate 的清理任务并执行微任务，
+    /// 返回时失去引用的 WeakRef 已经 deref() 为 undefined，清理回调已经执行。
+    pub(crate) fn force_gc(&self, full: bool) -> Result<()> {
+        self.ensure_polyfill_loaded()?;
+        self.with_runtime(|runtime| {
+            let platform = v8::V8::get_current_platform();
+            let isolate = runtime.v8_isolate();
+
+            // 当前任务中 deref() 过的 WeakRef 目标会被保留到任务结束，GC 之前先释放
+            isolate.clear_kept_objects();
+            if full {
+                isolate.low_memory_notification();
+            } else {
+                isolate.memory_pressure_notification(v8::MemoryPressureLevel::Critical);
+                isolate.memory_pressure_notification(v8::MemoryPressureLevel::None);
+            }
+            // FinalizationRegistry 的清理回调作为平台任务投递
+            while v8::Platform::pump_message_loop(&platform, isolate, false) {}
+            isolate.perform_microtask_checkpoint();
+            Ok(())
+        })
+    }
+
+    /// 告诉 V8 接下来 idle_ms 毫秒空闲，可以用来做 GC 等后台工作
+    ///
+    /// 以中等内存压力通知 V8 收缩堆，然后在期限内处理平台投递到该 isolate 的任务
+    /// （GC 收尾、空闲任务等），结束时恢复正常的内存压力级别。
+    pub(crate) fn idle_notification(&self, idle_ms: u64) -> Result<()> {
+        self.with_runtime(|runtime| {
+            let deadline = Instant::now() + Duration::from_millis(idle_ms);
+            let platform = v8::V8::get_current_platform();
+            let isolate = runtime.v8_isolate();
+
+            isolate.memory_pressure_notification(v8::MemoryPressureLevel::Moderate);
+            while Instant::now() < deadline && v8::Platform::pump_message_loop(&platform, isolate, false) {}
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !remaining.is_zero() {
+                v8::Platform::run_idle_tasks(&platform, isolate, remaining.as_secs_f64());
+            }
+            isolate.memory_pressure_notification(v8::MemoryPressureLevel::None);
+            Ok(())
+        })
+    }
+
+    /// 开始记录或重放不确定输入（第一次调用时安装 JS 侧的包装）
+    pub(crate) fn start_tape(&self, mode: crate::replay::Mode, entries: Vec<(String, String)>) -> Result<()> {
+        self.ensure_polyfill_loaded()?;
+        self.with_runtime(|runtime| crate::replay::start(runtime, mode, entries))
+    }
+
+    /// 停止记录或重放，取出 trace
+    pub(crate) fn stop_tape(&self, mode: crate::replay::Mode) -> Result<crate::replay::Stopped> {
+        self.with_runtime(|runtime| crate::replay::stop(runtime, mode))
+    }
+
+    /// 获取 V8 堆内存统计信息
+    ///
+    /// 返回当前 JavaScript 运行时的内存使用情况，包括总堆大小、已用大小等详细指标
+    pub(crate) fn get_heap_stats(&self) -> Result<std::collections::HashMap<String, usize>> {
+        self.enter_isolate();
+        let mut runtime = self.runtime.borrow_mut();
+
+        // 直接访问 V8 isolate 并获取堆统计信息
+        let isolate = runtime.v8_isolate();
+        let heap_stats = isolate.get_heap_statistics();
+
+        let mut stats = std::collections::HashMap::new();
+        stats.insert("total_heap_size".to_string(), heap_stats.total_heap_size());
+        stats.insert("total_heap_size_executable".to_string(), heap_stats.total_heap_size_executable());
+        stats.insert("total_physical_size".to_string(), heap_stats.total_physical_size());
+        stats.insert("total_available_size".to_string(), heap_stats.total_available_size());
+        stats.insert("used_heap_size".to_string(), heap_stats.used_heap_size());
+        stats.insert("heap_size_limit".to_string(), heap_stats.heap_size_limit());
+        stats.insert("malloced_memory".to_string(), heap_stats.malloced_memory());
+        stats.insert("external_memory".to_string(), heap_stats.external_memory());
+        stats.insert("peak_malloced_memory".to_string(), heap_stats.peak_malloced_memory());
+        stats.insert("number_of_native_contexts".to_string(), heap_stats.number_of_native_contexts());
+        stats.insert("number_of_detached_contexts".to_string(), heap_stats.number_of_detached_contexts());
+
+        drop(runtime);
+        self.exit_isolate();
+
+        Ok(stats)
+    }
+
+    /// V8 已用堆大小（recycle_after["heap_mb"] 使用）
+    fn used_heap_bytes(&self) -> usize {
+        self.enter_isolate();
+        let used = self.runtime.borrow_mut().v8_isolate().get_heap_statistics().used_heap_size();
+        self.exit_isolate();
+        used
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        // fork 前创建的 Context：子进程中没有 V8 平台线程，销毁 isolate 可能死锁，直接泄漏；
+        // panic 后 isolate 可能仍处于进入状态，销毁时 V8 会中止进程，同样泄漏
+        if crate::fork::is_inherited(self.fork_generation) || self.panicked.get() {
+            std::mem::forget(self.eval_wrappers.borrow_mut().take());
+            std::mem::forget(std::mem::take(&mut self.call_targets));
+            std::mem::forget(self.snapshot_owner.take());
+            self.wasm_views.forget();
+            return;
+        }
+
+        // wasm_memory() 返回的视图在 Context 销毁时失效
+        Python::attach(|py| self.wasm_views.release_all(py));
+
+        // 包装函数和缓存的调用目标的 v8::Global 必须在 isolate 销毁之前释放
+        self.eval_wrappers.borrow_mut().take();
+        self.call_targets.clear();
+
+        // SAFETY: runtime 只在这里释放一次，之后不再访问
+        unsafe { ManuallyDrop::drop(&mut self.runtime) };
+
+        // V8 runtime 会在 RefCell 销毁时自动清理
+        // 注意：不要在这里调用 gc()，因为 Drop 可能在不同线程上被调用
+        // 如果需要手动 GC，请在业务代码中显式调用 ctx.gc() 或使用 with 语句
+    }
+}
+
+// ============================================
+// Python Methods
+// ============================================
+
+#[pymethods]
+impl Context {
+    /// Python构造函数
+    ///
+    /// 创建一个新的JavaScript执行上下文
+    ///
+    /// Args:
+    ///     enable_extensions: 是否启用扩展（crypto, encoding 等），默认 True
+    ///                       - True: 启用所有扩展，自动注入 btoa/atob/md5/sha256 等函数
+    ///                       - False: 纯净 V8 环境，只包含 ECMAScript 标准 API
+    ///     enable_logging: 是否启用操作日志输出，默认 False
+    ///                     - True: 输出所有扩展操作的日志（用于调试）
+    ///                     - False: 不输出日志（推荐生产环境）
+    ///     random_seed: 随机数种子（可选），用于确定性随机数生成
+    ///                  - None: 使用系统随机数（非确定性）
+    ///                  - int: 使用固定种子（确定性）
+    ///                    所有随机数 API（Math.random、crypto.getRandomValues 等）
+    ///                    将基于此种子生成，方便调试和算法对比
+    ///     snapshot: 启动快照（可选），由 never_jscore.build_snapshot() 生成
+    ///               - 提供时直接从快照恢复 V8 堆，快照中的函数/变量立即可用
+    ///               - enable_extensions 必须与构建快照时一致
+    ///     code_cache_dir: V8 代码缓存目录（可选）
+    ///                     - 设置后 compile()/compile_file() 会把编译后的字节码缓存到该目录
+    ///                     - 以代码内容的 SHA-256 为 key，进程重启后编译同一份代码直接复用缓存
+    ///     initial_heap_mb: 初始堆大小（MB，可选）
+    ///     max_heap_mb: 最大堆大小（MB，可选）
+    ///                  - 每个 Context 独立设置，可以混用小型沙箱 Context 和大型工作 Context
+    ///                  - 超出时终止当前执行并抛出 "Heap limit exceeded" 异常，而不是让进程 OOM 崩溃
+    ///     jitless: 是否要求以无 JIT 模式运行，默认 False
+    ///              - True: V8 只用解释器执行（不生成机器码），适合执行不可信代码
+    ///              - 这是进程级设置：必须在第一个 Context 之前开启（或使用 never_jscore.init(jitless=True)），
+    ///                否则抛出 RuntimeError
+    ///     permissions: 网络 / 文件 / 环境变量权限（可选），默认 None 拒绝网络和文件读写
+    ///                  - None: 拒绝网络和文件读写，宿主环境变量不可见（process.env 为空），env= 注入的变量可见
+    ///                  - 字典：未列出的类别全部拒绝，被拒绝的操作在 JS 中抛出 PermissionDenied 错误
+    ///                    - 键：net（主机名，可带端口）、read / write（目录或文件）、env（变量名）
+    ///                    - 值：True 全部允许，False 拒绝，字符串或列表为白名单
+    ///                  - "all": 不做任何限制（以前的默认行为）
+    ///                  - 网络请求的每一次重定向都重新检查 net 白名单
+    ///     allow_dynamic_code: 是否允许 eval / new Function 从字符串生成代码，默认 True
+    ///                  - False: 调用 eval()、new Function() 抛出 EvalError，
+    ///                    注入的代码无法在运行时再生成新代码
+    ///                  - evaluate()/call() 仍然可用（代码由 Rust 侧作为脚本编译执行），
+    ///                    但其中的 var 和函数声明会进入全局作用域
+    ///                  - require() 加载 JS 文件依赖 eval，此模式下不可用
+    ///     timeout_ms: 每次调用的墙钟超时（毫秒，可选）
+    ///                 - 从调用开始计算，包括等待定时器 / Promise 的时间
+    ///                 - 超出时终止执行并抛出 "Execution timed out" 异常，Context 仍然可用
+    ///     cpu_limit_ms: 每次调用的 CPU 时间预算（毫秒，可选，目前仅支持 Linux）
+    ///                 - 只统计执行线程实际消耗的 CPU 时间，等待 I/O 和定时器不计入
+    ///                 - 死循环在宽松的 timeout_ms 下也会被及时终止，抛出 "CPU time limit exceeded" 异常
+    ///     stack_size_kb: V8 栈大小上限（KB，可选），默认使用 V8 默认值（约 1 MB）
+    ///                 - 递归超过上限时抛出可以捕获的 RangeError，而不是让进程崩溃
+    ///                 - 这是进程级设置：必须在第一个 Context 之前设置（或使用 never_jscore.init(stack_size_kb=...)），
+    ///                   否则抛出 RuntimeError
+    ///                 - 不能超过当前线程的栈大小（超过时抛出 RuntimeError）；
+    ///                   ThreadedContext / ContextPool 的工作线程会自动分配足够的栈
+    ///     audit_ops: 是否记录每次 op 调用，默认 False
+    ///                 - 记录 op 名称、截断后的参数、开始时间、耗时和结果，通过 get_audit_log() 获取
+    ///                 - 用于调试扩展，或检查不可信脚本尝试了哪些操作（读文件、发请求等）
+    ///     fs_roots: Deno 风格文件 API 可访问的目录（可选），默认 None 不提供该 API
+    ///                 - 目录或目录列表（只读），或 {目录: "r" | "rw"} 字典
+    ///                 - 设置后 JS 中可以使用 Deno.readTextFile / readFile / readDir / stat，
+    ///                   可写目录还可以使用 Deno.writeTextFile / writeFile，访问其他路径抛出 PermissionDenied
+    ///                 - 不受 permissions 的 read / write 影响；需要 enable_extensions=True
+    ///     env: 注入的环境变量 {名称: 值}（可选），默认 None 读取宿主环境变量
+    ///                 - 设置后 process.env 和 Deno.env.get() / has() / toObject() 只能看到这些变量，
+    ///                   宿主环境变量不会泄露给脚本（load_wasm 的 inherit_env 同样使用注入的变量）
+    ///                 - 仍然受 permissions 的 env 权限限制；需要 enable_extensions=True
+    ///     no_ops: 纯计算模式，默认 False
+    ///                 - True: 不加载任何扩展（忽略 enable_extensions），只保留内部传递结果的 op（用户代码无法访问），
+    ///                   console 换成 V8 内置的空实现，脚本无法访问 V8 之外的任何资源
+    ///                 - 适合执行完全不可信的代码片段，建议配合 timeout_ms / max_heap_mb 使用
+    ///     quotas: Context 生命周期内累计的资源配额（可选），默认 None 不限制
+    ///                 - max_executions: 最多执行次数（evaluate / call / compile 等各计一次）
+    ///                 - max_cpu_ms: 累计 CPU 时间（毫秒，目前仅支持 Linux），超出时终止当前调用
+    ///                 - max_console_bytes: 累计 console 输出字节数，超出时丢弃输出并终止当前调用
+    ///                 - max_result_bytes: 单次调用结果（JSON）的最大字节数，超出时本次调用失败
+    ///                 - 前三项耗尽后 Context 拒绝之后的所有调用；超出配额时抛出 never_jscore.QuotaExceeded，
+    ///                   使用情况通过 get_quota_usage() 获取
+    ///     history: 记录最近多少次执行（可选），默认 None 不记录
+    ///                 - 每次 evaluate / call / eval / compile 等记录类型、脚本名称、代码片段、开始时间、耗时和结果
+    ///                 - 超出容量时丢弃最早的记录，通过 history() 获取，用于长时间运行的服务出错后回溯
+    ///     shared_memory: 像跨源隔离的页面一样报告 crossOriginIsolated = true（默认 False）。
+    ///                 SharedArrayBuffer / Atomics 始终可用，但 emscripten 的 pthread 构建等脚本会先检查 crossOriginIsolated
+    ///     shared_buffers: {全局变量名: SharedBuffer}（可选），在用户代码执行之前定义为 SharedArrayBuffer，
+    ///                 Python 和共享同一个 SharedBuffer 的其他 Context 读写同一块内存
+    ///     weak_refs: 是否提供 WeakRef / FinalizationRegistry，默认 True。
+    ///                 为 False 时从全局对象中删除，依赖它们的代码会走不使用弱引用的回退路径
+    ///     auto_recreate: 遇到致命错误后自动重建 isolate（默认 False）。
+    ///                 超出 max_heap_mb、执行中的 panic 之后 isolate 不再可靠：默认标记为 poisoned，
+    ///                 之后的执行抛出 never_jscore.ContextPoisoned；为 True 时在下一次调用之前按 recycle_after 的方式重建
+    ///     max_handles: Realm / ShadowRealm / prepare() 函数 / call() 缓存函数的总数上限（可选），默认 None 不限制。
+    ///                 达到上限时先清空 call() 的函数缓存，仍然超出时创建 Realm / ShadowRealm、prepare() 抛出
+    ///                 never_jscore.QuotaExceeded；通过 handles() 列出、handle.release() 释放
+    ///     yield_interval_ms: 同步执行期间处理 Python 信号的间隔（毫秒，可选，至少为 1）。
+    ///                 JS 执行期间每隔这么久在执行线程上运行一次 Python 的信号处理函数，
+    ///                 Ctrl-C（KeyboardInterrupt）或自定义信号处理函数抛出的异常会终止死循环并原样抛出，Context 仍然可用。
+    ///                 信号只在主线程上处理
+    ///     undefined_as: 结果中 undefined 的转换策略（默认 None，沿用 JSON.stringify 的语义：
+    ///                 顶层为 None，对象中省略该键，数组中为 None），对顶层结果、对象的值和数组元素（包括空位）一致地生效：
+    ///                 - "none": 一律转换为 None（对象保留该键）
+    ///                 - "sentinel": 一律转换为 never_jscore.UNDEFINED，与 null 区分
+    ///                 - "omit": 对象省略该键，数组删除该元素；顶层结果为 None
+    ///
+    /// Example:
+    ///     ```python
+    ///     import never_jscore
+    ///
+    ///     # 创建带扩展的上下文（默认）
+    ///     ctx = never_jscore.Context()
+    ///     result = ctx.evaluate("btoa('hello')")  # 可以直接使用 btoa
+    ///
+    ///     # 创建纯净 V8 环境
+    ///     ctx_pure = never_jscore.Context(enable_extensions=False)
+    ///     # 只有 ECMAScript 标准 API，无 btoa/atob 等
+    ///
+    ///     # 创建带日志的上下文（用于调试）
+    ///     ctx_debug = never_jscore.Context(enable_logging=True)
+    ///
+    ///     # 创建带固定随机数种子的上下文（用于调试和算法对比）
+    ///     ctx_seeded = never_jscore.Context(random_seed=12345)
+    ///     r1 = ctx_seeded.evaluate("Math.random()")  # 确定性随机数
+    ///     r2 = ctx_seeded.evaluate("Math.random()")  # 下一个确定性随机数
+    ///
+    ///     # 另一个相同种子的上下文将产生相同的随机数序列
+    ///     ctx_seeded2 = never_jscore.Context(random_seed=12345)
+    ///     r3 = ctx_seeded2.evaluate("Math.random()")  # r3 == r1
+    ///
+    ///     # 从快照启动（跳过重复加载大型 JS 库）
+    ///     snapshot = never_jscore.build_snapshot("function add(a, b) { return a + b; }")
+    ///     ctx_fast = never_jscore.Context(snapshot=snapshot)
+    ///     ctx_fast.call("add", [1, 2])  # 3
+    ///
+    ///     # 使用代码缓存（重复编译大型 bundle 时跳过解析）
+    ///     ctx_cached = never_jscore.Context(code_cache_dir=".jscache")
+    ///     ctx_cached.compile_file("bundle.js")
+    ///
+    ///     # 限制堆大小（沙箱）
+    ///     ctx_small = never_jscore.Context(max_heap_mb=64)
+    ///
+    ///     # 执行不可信代码（禁用 JIT）
+    ///     ctx_untrusted = never_jscore.Context(jitless=True, max_heap_mb=64)
+    ///
+    ///     # 只允许访问指定主机和目录
+    ///     ctx_sandboxed = never_jscore.Context(permissions={"net": ["api.example.com"], "read": ["./data"], "env": False})
+    ///
+    ///     # 禁止 eval / new Function
+    ///     ctx_static = never_jscore.Context(allow_dynamic_code=False)
+    ///
+    ///     # 允许等待较慢的请求，但死循环 100ms CPU 时间后终止
+    ///     ctx_limited = never_jscore.Context(timeout_ms=30000, cpu_limit_ms=100)
+    ///
+    ///     # 深度递归的混淆代码（较小的栈上限更早抛出 RangeError）
+    ///     ctx_deep = never_jscore.Context(stack_size_kb=4096)
+    ///
+    ///     # 记录脚本调用了哪些 op
+    ///     ctx_audit = never_jscore.Context(audit_ops=True)
+    ///
+    ///     # 只允许读取字典和 WASM 目录
+    ///     ctx_assets = never_jscore.Context(fs_roots=["./wordlists", "./wasm"])
+    ///
+    ///     # 脚本从 process.env 读取配置，不修改源码
+    ///     ctx_config = never_jscore.Context(env={"API_KEY": "secret", "NODE_ENV": "production"})
+    ///
+    ///     # 执行完全不可信的代码片段
+    ///     ctx_pure_compute = never_jscore.Context(no_ops=True, timeout_ms=1000, max_heap_mb=64)
+    ///
+    ///     # 多租户服务：每个租户的 Context 最多执行 1000 次、累计 10 秒 CPU 时间
+    ///     ctx_tenant = never_jscore.Context(quotas={"max_executions": 1000, "max_cpu_ms": 10000})
+    ///
+    ///     # 保留最近 200 次执行，出错后查看之前执行了什么
+    ///     ctx_service = never_jscore.Context(history=200)
+    ///
+    ///     # 超出堆上限后自动重建，服务继续运行
+    ///     ctx_resilient = never_jscore.Context(max_heap_mb=256, auto_recreate=True)
+    ///
+    ///     # 长期运行的服务：最多保留 1000 个 Realm / prepare() 函数等长期对象
+    ///     ctx_bounded = never_jscore.Context(max_handles=1000)
+    ///     ```
+    #[new]
+    #[pyo3(signature = (enable_extensions=true, enable_logging=false, random_seed=None, snapshot=None, code_cache_dir=None, initial_heap_mb=None, max_heap_mb=None, jitless=false, permissions=None, allow_dynamic_code=true, timeout_ms=None, cpu_limit_ms=None, stack_size_kb=None, audit_ops=false, fs_roots=None, env=None, no_ops=false, quotas=None, history=None, shared_memory=false, shared_buffers=None, weak_refs=true, auto_recreate=false, max_handles=None, yield_interval_ms=None, undefined_as=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        enable_extensions: bool,
+        enable_logging: bool,
+        random_seed: Option<u32>,
+        snapshot: Option<&[u8]>,
+        code_cache_dir: Option<PathBuf>,
+        initial_heap_mb: Option<usize>,
+        max_heap_mb: Option<usize>,
+        jitless: bool,
+        permissions: Option<&Bound<'_, PyAny>>,
+        allow_dynamic_code: bool,
+        timeout_ms: Option<u64>,
+        cpu_limit_ms: Option<u64>,
+        stack_size_kb: Option<usize>,
+        audit_ops: bool,
+        fs_roots: Option<&Bound<'_, PyAny>>,
+        env: Option<&Bound<'_, PyDict>>,
+        no_ops: bool,
+        quotas: Option<&Bound<'_, PyDict>>,
+        history: Option<usize>,
+        shared_memory: bool,
+        shared_buffers: Option<&Bound<'_, PyDict>>,
+        weak_refs: bool,
+        auto_recreate: bool,
+        max_handles: Option<usize>,
+        yield_interval_ms: Option<u64>,
+        undefined_as: Option<&str>,
+    ) -> PyResult<Self> {
+        if max_handles == Some(0) {
+            return Err(PyValueError::new_err("max_handles must be greater than 0"));
+        }
+        if jitless {
+            crate::runtime::require_jitless().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        }
+        crate::runtime::validate_stack_size(stack_size_kb)?;
+        if let Some(kb) = stack_size_kb {
+            crate::runtime::require_stack_size(kb).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        }
+        crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+        let options = Self::build_options(enable_extensions && !no_ops, enable_logging, random_seed, snapshot, code_cache_dir)?
+            .with_heap_limits(initial_heap_mb, max_heap_mb)?
+            .with_permissions(permissions)?
+            .with_fs_roots(fs_roots)?
+            .with_env(env)?
+            .with_time_limits(timeout_ms, cpu_limit_ms)?
+            .with_yield_interval(yield_interval_ms)?
+            .with_undefined_as(undefined_as)?
+            .with_quotas(quotas)?
+            .with_history(history)?
+            .with_shared_memory(shared_memory, shared_buffers)?;
+        Self::new(ContextOptions { allow_dynamic_code, audit_ops, no_ops, weak_refs, auto_recreate, max_handles, ..options })
+    }
+
+    /// 编译JavaScript代码（便捷方法）
+    ///
+    /// 这是一个便捷方法，等价于 eval(code)。
+    /// 执行代码并将函数/变量加入全局作用域。
+    /// 创建 Context 时设置了 code_cache_dir 的话，会使用 V8 代码缓存。
+    ///
+    /// Args:
+    ///     code: JavaScript 代码字符串
+    ///     preset: 在 code 之前加载的内置库（可选），如 ["crypto-js", "jsencrypt"]
+    ///             - "crypto-js": 全局变量 CryptoJS（原样内置的 crypto-js 4.2.0）
+    ///             - "jsencrypt": 全局变量 JSEncrypt（原样内置的 jsencrypt 3.3.2）
+    ///     recycle_after: 自动回收阈值（可选），如 {"calls": 10000, "heap_mb": 300}。
+    ///                    达到任一阈值时，call() / evaluate() / eval() / render_call() / repl_eval() 返回前
+    ///                    按原顺序重建 isolate：构造参数、已执行的全局脚本和 register() 的函数保留，
+    ///                    evaluate() / call() 中的副作用丢弃；调用统计、配额用量、执行历史和回调转移到新的 isolate。
+    ///                    - calls: 当前 isolate 执行 JS 的次数
+    ///                    - heap_mb: 调用结束后 V8 已用堆大小（MB）
+    ///                    重建失败时继续使用当前 isolate，错误通过 sys.unraisablehook 报告。
+    ///                    不能与 load_wasm()、ES 模块、share_buffer()、import_state()、create_realm() /
+    ///                    create_shadow_realm() 同时使用（ValueError）
+    ///     result_filter: 结果过滤函数的名称（可选），如 "__sanitize"。之后每次 evaluate() / call() / eval(return_value=True) /
+    ///                    render_call() / repl_eval() 的结果先交给该函数，序列化它的返回值（异步模式下等待返回的 Promise），
+    ///                    用于在沙箱内统一去掉 DOM 节点、截断大缓冲区等。名称在 code 执行之后求值，不是函数时抛出 ValueError；
+    ///                    每次调用重新求值，替换同名函数立即生效。传入空字符串清除。提前返回（__neverjscore_return__）的值不经过过滤
+    ///     tag: 脚本标签（可选），如 "libA"。本次执行的脚本（包括 preset）记录为该标签，之后可以用 unload("libA") 卸载；
+    ///          同一标签可以多次使用
+    ///
+    /// Returns:
+    ///     None
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context()
+    ///     ctx.compile('''
+    ///         function add(a, b) { return a + b; }
+    ///         function sub(a, b) { return a - b; }
+    ///     ''')
+    ///     result = ctx.call("add", [5, 3])
+    ///
+    ///     ctx.compile("function sign(s) { return CryptoJS.MD5(s).toString(); }", preset=["crypto-js"])
+    ///
+    ///     # 长期运行的服务：每 10000 次调用或堆超过 300 MB 时重建 isolate
+    ///     ctx.compile(open("sign.js").read(), recycle_after={"calls": 10000, "heap_mb": 300})
+    ///
+    ///     # 所有结果先经过 __sanitize
+    ///     ctx.compile("function __sanitize(v) { return v instanceof Uint8Array ? v.length : v; }", result_filter="__sanitize")
+    ///
+    ///     # 带标签加载，之后可以用 unload() 换成另一个版本
+    ///     ctx.compile(open("lib_v1.js").read(), tag="lib")
+    ///     ```
+    #[pyo3(signature = (code, preset=None, recycle_after=None, result_filter=None, tag=None))]
+    pub fn compile(
+        &self,
+        py: Python<'_>,
+        code: String,
+        preset: Option<Vec<String>>,
+        recycle_after: Option<&Bound<'_, PyDict>>,
+        result_filter: Option<String>,
+        tag: Option<String>,
+    ) -> PyResult<()> {
+        self.check_fork()?;
+        let presets = crate::presets::resolve(preset)?;
+        if let Some(recycle_after) = recycle_after {
+            self.set_recycle_policy(RecyclePolicy::from_py(recycle_after)?)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        }
+        let start = self.replay.borrow().script_count();
+        // 直接执行脚本，不经过 eval
+        let result = self.without_gil(py, |ctx| {
+            for (name, source) in presets {
+                ctx.exec_named_script(&name, source.to_string(), true)?;
+            }
+            ctx.exec_named_script("<exec>", code, true)
+        });
+        // 出错之前已经执行的 preset 同样属于这个标签
+        if let Some(tag) = &tag {
+            self.replay.borrow_mut().tag_since(start, tag);
+        }
+        result.map_err(|e| crate::quota::py_error("Compile error", e))?;
+        if let Some(name) = result_filter {
+            self.without_gil(py, |ctx| ctx.set_result_filter(name))
+                .map_err(|e| PyValueError::new_err(format_error(e)))?;
+        }
+        Ok(())
+    }
+
+    /// 卸载 compile(code, tag=...) 加载的脚本
+    ///
+    /// 用相同的构造参数重建 isolate，按原顺序重新执行其余的全局脚本、注册 register() 的函数（与 recycle_after 的重建相同），
+    /// 带该标签的脚本不再执行。evaluate() / call() 中的副作用随旧的 isolate 丢弃；
+    /// 调用统计、配额用量、执行历史和回调保留。之后可以用同一标签加载新版本。
+    ///
+    /// Args:
+    ///     tag: compile() 传入的标签
+    ///
+    /// Raises:
+    ///     ValueError: 没有带该标签的脚本，或 Context 无法重建（load_wasm()、ES 模块、share_buffer()、import_state()、Realm 等）
+    ///     Exception: 重新执行其余脚本失败（Context 保持不变）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.compile(open("crypto_v1.js").read(), tag="crypto")
+    ///     ctx.compile("function sign(s) { return Crypto.hash(s); }")
+    ///     ctx.unload("crypto")
+    ///     ctx.compile(open("crypto_v2.js").read(), tag="crypto")
+    ///     ```
+    #[pyo3(signature = (tag))]
+    pub fn unload(slf: &Bound<'_, Self>, py: Python<'_>, tag: &str) -> PyResult<()> {
+        let mut this = slf.try_borrow_mut()?;
+        this.check_fork()?;
+        {
+            let replay = this.replay.borrow();
+            if let Some(reason) = replay.rebuild_blocker() {
+                return Err(PyValueError::new_err(format!("cannot unload scripts: {}", reason)));
+            }
+            if !replay.has_tag(tag) {
+                return Err(PyValueError::new_err(format!("no scripts tagged '{}'", tag)));
+            }
+        }
+        let fresh = this
+            .without_gil(py, |ctx| IsolateLocal::new(ctx.unloaded(tag)))
+            .into_inner()
+            .map_err(|e| crate::quota::py_error("Unload error", e))?;
+        this.replace_with(fresh);
+        Ok(())
+    }
+
+    /// 在后台线程编译 JavaScript 代码
+    ///
+    /// 适合几 MB 的大型 bundle：解析和编译在后台线程的独立 isolate 中进行，
+    /// 调用方可以同时做其他工作，之后调用返回对象的 wait() 执行脚本（效果与 compile() 相同）。
+    /// 设置了 code_cache_dir 时会复用 / 写入磁盘缓存。
+    ///
+    /// Args:
+    ///     code: JavaScript 代码字符串
+    ///
+    /// Returns:
+    ///     CompileTask，提供 done() 和 wait()
+    ///
+    /// Example:
+    ///     ```python
+    ///     task = ctx.compile_background(bundle_code)
+    ///     load_config()  # 与编译并行
+    ///     task.wait()
+    ///     ctx.call("sign", ["data"])
+    ///     ```
+    #[pyo3(signature = (code))]
+    pub fn compile_background(slf: &Bound<'_, Self>, code: String) -> PyResult<CompileTask> {
+        let this = slf.borrow();
+        this.check_fork()?;
+
+        let code: Arc<str> = code.into();
+        let handle = crate::background_compile::spawn(
+            code.clone(),
+            this.code_cache_dir.clone(),
+            this.snapshot,
+            this.snapshot_owner.clone(),
+            this.extensions_loaded,
+        )?;
+        Ok(CompileTask::new(slf.clone().unbind(), code, handle))
+    }
+
+    /// 在此 Context 的 isolate 中创建轻量级 Realm
+    ///
+    /// Realm 拥有独立的全局作用域和内置对象，但与 Context 共享 isolate（堆和线程），
+    /// 创建 / 销毁只需几十微秒，适合按请求隔离。
+    /// Realm 中只有 ECMAScript 内置对象，没有扩展 API 和事件循环，详见 Realm 类型说明。
+    ///
+    /// Returns:
+    ///     Realm，提供 compile() / eval() / evaluate() / call() / close()
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context()
+    ///     with ctx.create_realm() as realm:
+    ///         realm.compile("var user = 'alice';")
+    ///         realm.evaluate("user")  # 'alice'
+    ///     ctx.evaluate("typeof user")  # 'undefined'
+    ///     ```
+    pub fn create_realm(slf: &Bound<'_, Self>) -> PyResult<Realm> {
+        slf.borrow()
+            .block_rebuild("it has created realms")
+            .map_err(|e| PyException::new_err(e.to_string()))?;
+        Realm::new(slf.py(), slf.clone().unbind())
+    }
+
+    /// 在此 Context 中创建 ShadowRealm（等同于 JS 中的 new ShadowRealm()）
+    ///
+    /// ShadowRealm 拥有独立的全局作用域和内置对象，边界两侧只能传递原始值和函数，
+    /// 详见 ShadowRealm 类型说明。JS 代码也可以直接使用 ShadowRealm。
+    ///
+    /// Returns:
+    ///     ShadowRealm，提供 evaluate() / close()
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context()
+    ///     with ctx.create_shadow_realm() as realm:
+    ///         realm.evaluate("Array.prototype.map = null; 1")
+    ///         realm.evaluate("typeof Array.prototype.map")  # 'object'
+    ///     ctx.evaluate("typeof Array.prototype.map")  # 'function'
+    ///     ```
+    pub fn create_shadow_realm(slf: &Bound<'_, Self>) -> PyResult<ShadowRealm> {
+        slf.borrow()
+            .block_rebuild("it has created realms")
+            .map_err(|e| PyException::new_err(e.to_string()))?;
+        ShadowRealm::new(slf.py(), slf.clone().unbind())
+    }
+
+    /// 列出此 Context 持有的长期 V8 对象
+    ///
+    /// 包括 Realm、ShadowRealm（close() 或销毁后不再列出）、prepare() 定义的函数（同一模板一个，
+    /// PreparedCall 销毁后仍然保留）和 call() 缓存的目标函数。长期运行的服务用它找出不断增长的对象，
+    /// 逐个 release()；Context(max_handles=N) 限制它们的总数。
+    ///
+    /// Returns:
+    ///     Handle 列表（按创建顺序），每个提供 id / kind / name / released 和 release()
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context(max_handles=100)
+    ///     ctx.compile("function sign(x) { return x; }")
+    ///     ctx.call("sign", [1])
+    ///     [(h.kind, h.name) for h in ctx.handles()]  # [('call_target', 'sign')]
+    ///
+    ///     for handle in ctx.handles():
+    ///         if handle.kind == "prepared":
+    ///             handle.release()
+    ///     ```
+    pub fn handles(slf: &Bound<'_, Self>) -> PyResult<Vec<Handle>> {
+        let this = slf.borrow();
+        this.check_fork()?;
+        let mut handles = this.handle_table.list();
+        handles.extend(this.cached_call_targets().into_iter().map(|(id, name)| (id, "call_target", name)));
+        handles.sort_unstable_by_key(|(id, _, _)| *id);
+        Ok(handles
+            .into_iter()
+            .map(|(id, kind, name)| Handle::new(slf.clone().unbind(), id, kind, name))
+            .collect())
+    }
+
+    /// 从文件编译JavaScript代码
+    ///
+    /// 读取文件内容并执行，效果与 compile() 相同。
+    /// 错误堆栈中会显示文件路径，方便定位大型 bundle 中的问题。
+    /// 开头的 BOM 会被去掉，shebang（#!/usr/bin/env node）会被忽略。
+    ///
+    /// Args:
+    ///     path: JavaScript 文件路径
+    ///     encoding: 文件编码（默认 UTF-8），如 "gbk"，名称与 open(encoding=...) 相同
+    ///     mode: 执行方式，默认按扩展名选择（.mjs 为 "module"，其余为 "script"）
+    ///           - "script": 全局脚本，与 compile() 相同
+    ///           - "module": ES 模块，具名导出定义为全局变量（不能 import 其他文件，可以使用顶层 await）
+    ///           - "typescript": 不支持（.ts 文件同样），需要先转换为 JavaScript
+    ///     watch: 监视文件（默认 False）。文件修改后，下一次 call() / evaluate() / eval() 之前自动重新编译，
+    ///            编辑目标脚本时不需要重启程序。重新编译失败时该次调用抛出错误
+    ///
+    /// Returns:
+    ///     None
+    ///
+    /// Raises:
+    ///     ValueError: 无法按指定编码解码、未知的 mode 或 TypeScript 文件
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context(code_cache_dir=".jscache")
+    ///     ctx.compile_file("webpack_bundle.js")  # 第二次运行时直接使用缓存
+    ///     result = ctx.call("sign", ["data"])
+    ///
+    ///     ctx.compile_file("legacy.js", encoding="gbk")
+    ///     ctx.compile_file("sign.mjs")  # export function sign() {...} -> ctx.call("sign", [...])
+    ///
+    ///     ctx.compile_file("sign.js", watch=True)
+    ///     ctx.call("sign", ["data"])  # 保存 sign.js 后自动使用新代码
+    ///     ```
+    #[pyo3(signature = (path, encoding=None, mode=None, watch=false))]
+    pub fn compile_file(
+        &self,
+        py: Python<'_>,
+        path: PathBuf,
+        encoding: Option<&str>,
+        mode: Option<&str>,
+        watch: bool,
+    ) -> PyResult<()> {
+        self.check_fork()?;
+        let stamp = crate::file_watch::stamp(&path);
+        let (code, source_mode) = crate::source_file::load(py, &path, encoding, mode)?;
+
+        self.without_gil(py, |ctx| ctx.exec_source_file(&path, code, source_mode))
+            .map_err(|e| crate::quota::py_error("Compile error", e))?;
+        if watch {
+            self.watch_file(path, encoding.map(str::to_string), mode.map(str::to_string), stamp);
+        } else {
+            self.unwatch_file(&path);
+        }
+        Ok(())
+    }
+
+    /// 下载远程脚本，校验 SHA-256 后编译
+    ///
+    /// 用于固定正在逆向的线上脚本的版本：下载（遵循 HTTP_PROXY / HTTPS_PROXY / NO_PROXY 环境变量）后
+    /// 与 sha256 比较，一致时缓存到磁盘并按 compile_file() 的方式执行（.mjs 为 ES 模块），错误堆栈中的文件名为 URL。
+    /// 传入 sha256 时优先使用缓存（同样重新校验），离线也能加载；不传时每次都下载、不缓存。
+    ///
+    /// Args:
+    ///     url: 脚本的 http(s) URL
+    ///     sha256: 期望的 SHA-256（十六进制），默认 None 不校验
+    ///     cache_dir: 缓存目录，默认 $XDG_CACHE_HOME/never_jscore/scripts（~/.cache/never_jscore/scripts）
+    ///     proxy: 代理地址（如 "http://127.0.0.1:8888"），默认使用环境变量
+    ///     timeout_ms: 下载超时（毫秒，默认 30000）
+    ///     encoding / mode: 同 compile_file()，mode 默认按 URL 路径的扩展名选择
+    ///
+    /// Returns:
+    ///     脚本的 SHA-256（十六进制），可以用来固定版本
+    ///
+    /// Raises:
+    ///     ValueError: sha256 格式错误、哈希不一致（不缓存也不执行）或 URL 无效
+    ///     Exception: 下载失败（网络错误或非 2xx 状态码）或脚本执行失败
+    ///
+    /// Example:
+    ///     ```python
+    ///     digest = ctx.compile_url("https://www.example.com/static/sign.js")  # 第一次：记下哈希值
+    ///     ctx.compile_url("https://www.example.com/static/sign.js", sha256=digest)  # 之后固定这个版本
+    ///     ```
+    #[pyo3(signature = (url, sha256=None, cache_dir=None, proxy=None, timeout_ms=30000, encoding=None, mode=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn compile_url(
+        &self,
+        py: Python<'_>,
+        url: &str,
+        sha256: Option<&str>,
+        cache_dir: Option<PathBuf>,
+        proxy: Option<String>,
+        timeout_ms: u64,
+        encoding: Option<&str>,
+        mode: Option<&str>,
+    ) -> PyResult<String> {
+        self.check_fork()?;
+        let options = crate::remote_script::RemoteOptions::new(sha256, cache_dir, proxy, timeout_ms)?;
+        let script = crate::remote_script::fetch(py, url, &options, encoding, mode)?;
+        let crate::remote_script::RemoteScript { code, mode, sha256 } = script;
+        self.without_gil(py, |ctx| ctx.exec_source(url, code, mode))
+            .map_err(|e| crate::quota::py_error("Compile error", e))?;
+        Ok(sha256)
+    }
+
+    /// 按依赖顺序加载目录中的脚本
+    ///
+    /// 目录中有 never_jscore.json（`{"files": ["env.js", "main.js"]}`）时按其中的顺序加载；
+    /// 否则扫描 .js / .mjs / .cjs 文件（跳过 node_modules 和隐藏目录），
+    /// 根据 `require('./x')`、`import ... from './x'` 和注释指令 `// @requires ./x.js` 先加载被依赖的文件。
+    /// 每个文件按 compile_file() 的方式执行。
+    ///
+    /// Args:
+    ///     dir: 项目目录
+    ///     entry: 入口文件（相对 dir 或绝对路径）。指定时只加载入口及其依赖，默认加载全部文件
+    ///     encoding: 文件编码（默认 UTF-8），同 compile_file()
+    ///
+    /// Returns:
+    ///     按加载顺序排列的文件路径
+    ///
+    /// Raises:
+    ///     ValueError: 循环依赖、清单格式错误或 @requires 指向不存在的文件
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context(timeout_ms=5000)
+    ///     ctx.compile_project("sign", entry="main.js")  # ['/.../sign/env.js', '/.../sign/lib/crypto.js', '/.../sign/main.js']
+    ///     ctx.call("sign", ["data"])
+    ///     ```
+    #[pyo3(signature = (dir, entry=None, encoding=None))]
+    pub fn compile_project(&self, py: Python<'_>, dir: PathBuf, entry: Option<PathBuf>, encoding: Option<&str>) -> PyResult<Vec<PathBuf>> {
+        self.check_fork()?;
+        let files = crate::project::plan(py, &dir, entry.as_deref(), encoding)?;
+        let mut loaded = Vec::with_capacity(files.len());
+        for file in files {
+            let crate::project::ProjectFile { path, code, mode } = file;
+            self.without_gil(py, |ctx| ctx.exec_source_file(&path, code, mode))
+                .map_err(|e| crate::quota::py_error(&format!("Compile error in {}", path.display()), e))?;
+            loaded.push(path);
+        }
+        Ok(loaded)
+    }
+
+    /// 调用 JavaScript 函数
+    ///
+    /// name 是全局函数或属性路径时，解析出的函数缓存在 Context 中，之后的调用不再拼接代码重新解析（见 call_cache.rs）
+    ///
+    /// Args:
+    ///     name: 函数名称
+    ///     args: 参数列表
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     return_timings: 是否同时返回本次调用的耗时分解（默认 False）
+    ///     binary: 把结果中的字符串作为二进制字符串（每个字符一个字节）返回为 bytes（默认 False），
+    ///             用于 latin1 密文、String.fromCharCode 的结果等，避免按 UTF-8 编码导致字节变化
+    ///     stream_to: 有 write(str) 方法的文件对象（默认 None）。结果的 JSON 文本分块写入其中，
+    ///                不在内存中保留完整结果，适合几十 MB 的返回值；此时返回写入的 UTF-8 字节数
+    ///     event_loop: 本次调用的事件循环选项（默认 None），字典，键为：
+    ///                 max_iterations（事件循环最多被 poll 的次数，包括没有任务到期的唤醒，超出时调用失败）、
+    ///                 pump_message_loop（是否处理 V8 平台任务，默认 True）、
+    ///                 wait_for_inspector（有调试器会话时等待其断开，默认 False）
+    ///     trace_id: 请求的追踪 ID（默认 None）。调用期间 JS 中的 globalThis.__trace_id 为该 ID，
+    ///               console 输出（[trace_id=...] 前缀）、执行钩子的字典和失败时异常的 trace_id 属性带上它，
+    ///               用于关联 JS 和 Python 的日志
+    ///     result_format: 结果的格式（默认 "python"）：
+    ///                    "python"（在 Rust 中解析 JSON 并转换为 Python 对象）、
+    ///                    "json_str"（不解析，直接返回 JSON 字符串，结果原样转发给其他服务时省去解析和重新序列化）、
+    ///                    "orjson"（交给 orjson.loads 解析，需要安装 orjson；Temporal 值保持为字典）。
+    ///                    后两者不能与 binary / stream_to 同时使用
+    ///     expect_schema: 结果的规则（默认 None），JSON Schema 的常用子集：type / enum / const / properties / required /
+    ///                    additionalProperties / items / minItems / maxItems / minLength / maxLength / pattern / minimum / maximum。
+    ///                    在 JS 侧序列化之前（经过 result_filter 之后）检查，不符合时抛出 SchemaMismatch
+    ///
+    /// Returns:
+    ///     函数返回值，自动转换为 Python 对象；
+    ///     return_timings=True 时返回 (结果, 耗时字典)，耗时字典的键见 get_stats()
+    ///
+    /// Raises:
+    ///     ValueError: binary=True 且字符串中有大于 0xFF 的字符，同时指定 binary 和 stream_to，
+    ///                 或 result_format 无效
+    ///     ImportError: result_format="orjson" 但没有安装 orjson
+    ///     SchemaMismatch: 结果不符合 expect_schema，mismatches 属性列出不符合的位置（如 "$.data.token: expected string, got number"）
+    #[pyo3(signature = (name, args, auto_await=None, return_timings=false, binary=false, stream_to=None, event_loop=None, trace_id=None, result_format=None, expect_schema=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn call<'py>(
+        slf: &Bound<'py, Self>,
+        py: Python<'py>,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        auto_await: Option<bool>,
+        return_timings: bool,
+        binary: bool,
+        stream_to: Option<&Bound<'_, PyAny>>,
+        event_loop: Option<&Bound<'_, PyDict>>,
+        trace_id: Option<String>,
+        result_format: Option<&str>,
+        expect_schema: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Self::recycling(slf, |this| {
+            this.check_fork()?;
+            let format = ResultFormat::from_py(result_format, binary, stream_to.is_some())?;
+            let args = call_args_to_json(args)?;
+            let event_loop = EventLoopOptions::from_py(event_loop)?;
+            let schema = expect_schema.map(crate::schema::from_py).transpose()?;
+            let tag = |e| crate::trace::tag_error(py, e, trace_id.as_deref());
+
+            if let Some(stream) = stream_target(stream_to, binary)? {
+                let written = this
+                    .without_gil(py, |ctx| {
+                        ctx.with_trace_id(trace_id.clone(), || {
+                            ctx.with_event_loop(event_loop, || {
+                                ctx.with_schema(schema.as_deref(), || {
+                                    ctx.execute_call_streaming(&name, &args, auto_await.unwrap_or(true), stream)
+                                })
+                            })
+                        })
+                    })
+                    .map_err(|e| tag(crate::result_stream::py_error("Call error", e)))?;
+                return this.with_timings(py, written.into_bound_py_any(py)?, return_timings);
+            }
+
+            let result_json = this
+                .without_gil(py, |ctx| {
+                    ctx.with_trace_id(trace_id.clone(), || {
+                        ctx.with_event_loop(event_loop, || {
+                            ctx.with_schema(schema.as_deref(), || ctx.execute_call(&name, &args, auto_await.unwrap_or(true)))
+                        })
+                    })
+                })
+                .map_err(|e| tag(crate::quota::py_error("Call error", e)))?;
+
+            this.with_timings(py, format.to_python(py, &result_json, binary)?, return_timings)
+        })
+    }
+
+    /// 用命名占位符拼接调用代码并求值
+    ///
+    /// 每个值按 call() 的参数规则（包括 register_converter() 注册的转换器）序列化为 JSON 字面量后替换 {名称}，
+    /// 代替 f-string 拼接，参数中的引号、换行不会破坏代码，也无法注入代码。
+    /// {{名称}} 输出字面的 {名称}，其他花括号原样保留。自动等待 Promise。
+    ///
+    /// Args:
+    ///     template: 带 {名称} 占位符的 JavaScript 表达式
+    ///     **values: 占位符的值
+    ///
+    /// Returns:
+    ///     表达式的值
+    ///
+    /// Raises:
+    ///     KeyError: 模板中的占位符没有对应的值
+    ///     TypeError: 传入了模板中没有的值
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.render_call("sign({ts}, {payload})", ts=1700000000, payload={"q": "it's \"quoted\""})
+    ///     # 执行 sign(1700000000, ({"q":"it's \"quoted\""}))
+    ///     ```
+    #[pyo3(signature = (template, **values))]
+    pub fn render_call<'py>(
+        slf: &Bound<'py, Self>,
+        py: Python<'py>,
+        template: &str,
+        values: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Self::recycling(slf, |this| {
+            this.check_fork()?;
+            let code = crate::template::render(template, values)?;
+            let result_json = this
+                .without_gil(py, |ctx| ctx.execute_js(code, true))
+                .map_err(|e| crate::quota::py_error("Call error", e))?;
+
+            json_str_to_python(py, &result_json)
+        })
+    }
+
+    /// 预编译带 ? 占位符的调用表达式，返回可重复调用的 PreparedCall
+    ///
+    /// 表达式只编译一次，stmt(*args) 按 call() 的参数规则（包括 register_converter() 注册的转换器）
+    /// 转换参数后直接传给编译好的函数：不重新解析代码，参数也不会拼进代码（无法注入代码）。
+    /// 占位符是表达式位置上的 ?；`?.`、`??`、三元运算符和字符串中的 ? 不是占位符。自动等待 Promise。
+    ///
+    /// Args:
+    ///     template: 带 ? 占位符的 JavaScript 表达式
+    ///
+    /// Returns:
+    ///     PreparedCall，以位置参数调用：stmt(arg1, arg2, ...)
+    ///
+    /// Raises:
+    ///     Exception: 模板不是有效的表达式（SyntaxError）
+    ///
+    /// Example:
+    ///     ```python
+    ///     stmt = ctx.prepare("hash(?)")
+    ///     for item in items:
+    ///         stmt(item)  # 等同于 render_call("hash({x})", x=item)，但只编译一次
+    ///     ```
+    pub fn prepare(slf: &Bound<'_, Self>, py: Python<'_>, template: String) -> PyResult<PreparedCall> {
+        let compiled = crate::prepared::compile(&template);
+        let released = Self::recycling(slf, |this| {
+            this.check_fork()?;
+            // 同一模板已经定义的函数不占用新的句柄
+            let script = compiled.script.clone();
+            this.without_gil(py, |ctx| {
+                if !ctx.handle_table.has_prepared(&compiled.path) {
+                    ctx.reserve_handle()?;
+                }
+                ctx.exec_named_script("<prepare>", script, false)
+            })
+            .map_err(|e| crate::quota::py_error("Prepare error", e))?;
+            Ok(this.handle_table.register_prepared(&template, &compiled.path))
+        })?;
+        Ok(PreparedCall::new(slf.clone().unbind(), template, &compiled, released))
+    }
+
+    /// 执行代码并将其加入全局作用域
+    ///
+    /// 这个方法会执行JavaScript代码，并将定义的函数/变量保留在全局作用域中。
+    /// 类似 py_mini_racer 的 eval() 方法。
+    ///
+    /// Args:
+    ///     code: JavaScript 代码
+    ///     return_value: 是否返回最后一个表达式的值（默认 False）
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     capture_output: 是否收集本次调用的 console 输出（默认 False）。收集的输出不再写到 stdout / stderr，
+    ///                     调用失败时照常输出
+    ///     event_loop: 本次调用的事件循环选项（默认 None），同 call()
+    ///
+    /// Returns:
+    ///     如果 return_value=True，返回最后一个表达式的值；否则返回 None。
+    ///     capture_output=True 时返回 (结果, 输出列表)，每条输出为 {"stream": "stdout" | "stderr", "message": str}
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = Context()
+    ///     ctx.eval("function add(a, b) { return a + b; }")
+    ///     result = ctx.call("add", [1, 2])  # 可以调用，因为add在全局作用域
+    ///
+    ///     result, output = ctx.eval("console.log('step', 1); add(1, 2)", return_value=True, capture_output=True)
+    ///     # 3, [{'stream': 'stdout', 'message': 'step 1'}]
+    ///     ```
+    #[pyo3(signature = (code, return_value=false, auto_await=None, capture_output=false, event_loop=None))]
+    pub fn eval<'py>(
+        slf: &Bound<'py, Self>,
+        py: Python<'py>,
+        code: String,
+        return_value: bool,
+        auto_await: Option<bool>,
+        capture_output: bool,
+        event_loop: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Self::recycling(slf, |this| {
+            this.check_fork()?;
+            let event_loop = EventLoopOptions::from_py(event_loop)?;
+            let (result_json, output) = this
+                .without_gil(py, |ctx| {
+                    ctx.capture_console(capture_output, || {
+                        ctx.with_event_loop(event_loop, || {
+                            if return_value {
+                                // 需要返回值：使用包装的execute_js
+                                ctx.execute_js(code, auto_await.unwrap_or(true)).map(Some)
+                            } else {
+                                // 不需要返回值：直接执行脚本，加入全局作用域
+                                ctx.exec_script(code).map(|_| None)
+                            }
+                        })
+                    })
+                })
+                .map_err(|e| crate::quota::py_error("Eval error", e))?;
+
+            let result = match result_json {
+                Some(json) => json_str_to_python(py, &json)?,
+                None => py.None().into_bound(py),
+            };
+            crate::console_capture::with_output(py, result, output)
+        })
+    }
+
+    /// 执行代码并返回结果（不影响全局作用域）
+    ///
+    /// 这个方法用于求值，代码在独立的作用域中执行，不会影响全局变量。
+    ///
+    /// Args:
+    ///     code: JavaScript 代码
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     return_timings: 是否同时返回本次调用的耗时分解（默认 False）
+    ///     binary: 把结果中的字符串作为二进制字符串返回为 bytes（默认 False），同 call()
+    ///     stream_to: 把结果的 JSON 文本分块写入文件对象，返回写入的字节数（默认 None），同 call()
+    ///     event_loop: 本次调用的事件循环选项（默认 None），同 call()
+    ///     result_format: 结果的格式："python"（默认）/ "json_str" / "orjson"，同 call()
+    ///
+    /// Returns:
+    ///     表达式的值；return_timings=True 时返回 (值, 耗时字典)
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.evaluate("String.fromCharCode(0xde, 0xad, 0xbe, 0xef)", binary=True)  # b'\xde\xad\xbe\xef'
+    ///
+    ///     with open("items.json", "w", encoding="utf-8") as f:
+    ///         ctx.evaluate("extractAll()", stream_to=f)  # 写入的字节数
+    ///
+    ///     # setInterval 永不停止：最多推进 100 轮事件循环
+    ///     ctx.evaluate("new Promise(r => { setInterval(() => {}, 0); r(1); })", event_loop={"max_iterations": 100})
+    ///
+    ///     ctx.evaluate("buildPayload()", result_format="json_str")  # '{"sign":"..."}'，不解析
+    ///     ```
+    #[pyo3(signature = (code, auto_await=None, return_timings=false, binary=false, stream_to=None, event_loop=None, result_format=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn evaluate<'py>(
+        slf: &Bound<'py, Self>,
+        py: Python<'py>,
+        code: String,
+        auto_await: Option<bool>,
+        return_timings: bool,
+        binary: bool,
+        stream_to: Option<&Bound<'_, PyAny>>,
+        event_loop: Option<&Bound<'_, PyDict>>,
+        result_format: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Self::recycling(slf, |this| {
+            this.check_fork()?;
+            let format = ResultFormat::from_py(result_format, binary, stream_to.is_some())?;
+            let event_loop = EventLoopOptions::from_py(event_loop)?;
+            if let Some(stream) = stream_target(stream_to, binary)? {
+                let written = this
+                    .without_gil(py, |ctx| {
+                        ctx.with_event_loop(event_loop, || ctx.execute_js_streaming(code, auto_await.unwrap_or(true), stream))
+                    })
+                    .map_err(|e| crate::result_stream::py_error("Evaluate error", e))?;
+                return this.with_timings(py, written.into_bound_py_any(py)?, return_timings);
+            }
+
+            let result_json = this
+                .without_gil(py, |ctx| ctx.with_event_loop(event_loop, || ctx.execute_js(code, auto_await.unwrap_or(true))))
+                .map_err(|e| crate::quota::py_error("Evaluate error", e))?;
+
+            this.with_timings(py, format.to_python(py, &result_json, binary)?, return_timings)
+        })
+    }
+
+    /// 像浏览器控制台一样执行一行输入并返回结果
+    ///
+    /// 与 evaluate() 不同，输入作为全局脚本执行：
+    /// - 返回最后一条语句的完成值（`let x = 1; x + 1` 返回 2，`if (...) { 1 } else { 2 }` 也有值）
+    /// - let/const/class 声明保留到后续的输入中
+    /// - 结果赋给全局变量 `_`（用户自己定义了 `_` 之后不再覆盖）
+    ///
+    /// 与控制台相同，重复声明同名的 let/const 会抛出 SyntaxError。
+    ///
+    /// Args:
+    ///     line: JavaScript 代码（可以包含多条语句）
+    ///     auto_await: 是否自动等待 Promise（默认 True），`_` 为 Promise 的结果
+    ///
+    /// Returns:
+    ///     完成值
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.repl_eval("let a = 20")
+    ///     ctx.repl_eval("const b = 22")
+    ///     ctx.repl_eval("a + b")   # 42
+    ///     ctx.repl_eval("_ * 2")   # 84
+    ///     ```
+    #[pyo3(signature = (line, auto_await=None))]
+    pub fn repl_eval<'py>(slf: &Bound<'py, Self>, py: Python<'py>, line: String, auto_await: Option<bool>) -> PyResult<Bound<'py, PyAny>> {
+        Self::recycling(slf, |this| {
+            this.check_fork()?;
+            let result_json = this
+                .without_gil(py, |ctx| ctx.execute_repl(line, auto_await.unwrap_or(true)))
+                .map_err(|e| crate::quota::py_error("Eval error", e))?;
+
+            json_str_to_python(py, &result_json)
+        })
+    }
+
+    /// 请求垃圾回收
+    ///
+    /// 注意：这只是向 V8 发送 GC 请求，V8 会根据自己的策略决定是否执行。
+    fn gc(&self) -> PyResult<()> {
+        self.check_fork()?;
+        self.check_idle()?;
+        self.request_gc()
+            .map_err(|e| PyException::new_err(format!("GC error: {}", e)))
+    }
+
+    /// 通知 V8 系统内存不足
+    ///
+    /// 与 gc() 不同，V8 会立即执行完整的垃圾回收并尽可能归还内存（耗时较长），
+    /// 适合在收到系统内存告警或大批量任务结束后调用。
+    fn notify_low_memory(&self) -> PyResult<()> {
+        self.check_fork()?;
+        self.low_memory_notification()
+            .map_err(|e| PyException::new_err(format!("GC error: {}", e)))
+    }
+
+    /// 强制垃圾回收（用于依赖 FinalizationRegistry 的代码和内存泄漏测试）
+    ///
+    /// 与 gc() 只是向 V8 发送请求不同，返回时 GC 一定已经完成：
+    /// 失去引用的对象已被回收，WeakRef.deref() 返回 undefined，
+    /// FinalizationRegistry 的清理回调已经执行。
+    ///
+    /// Args:
+    ///     full: 为 True（默认）时反复执行完整 GC 直到没有更多可回收的对象；
+    ///           为 False 时只执行一次完整 GC（更快，互相引用的多层弱引用可能需要多次调用）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.eval("globalThis.freed = []; new FinalizationRegistry(v => freed.push(v)).register({}, 'obj')")
+    ///     ctx.collect_garbage()
+    ///     ctx.evaluate("freed")  # ['obj']
+    ///     ```
+    #[pyo3(signature = (full=true))]
+    fn collect_garbage(&self, py: Python<'_>, full: bool) -> PyResult<()> {
+        self.check_fork()?;
+        self.without_gil(py, |ctx| ctx.force_gc(full))
+            .map_err(|e| PyException::new_err(format!("GC error: {}", e)))
+    }
+
+    /// 告诉 V8 当前处于空闲期，可以利用这段时间回收内存
+    ///
+    /// 适合在两批请求之间调用：V8 会收缩堆并完成待处理的 GC 工作，
+    /// 最多占用 idle_ms 毫秒（提前完成时立即返回）。
+    ///
+    /// Args:
+    ///     idle_ms: 可用的空闲时间（毫秒，默认 50）
+    ///
+    /// Example:
+    ///     ```python
+    ///     for batch in batches:
+    ///         results = [ctx.call("sign", [item]) for item in batch]
+    ///         ctx.idle(20)  # 批次之间收缩堆
+    ///     ```
+    #[pyo3(signature = (idle_ms=50))]
+    fn idle(&self, py: Python<'_>, idle_ms: u64) -> PyResult<()> {
+        self.check_fork()?;
+        self.without_gil(py, |ctx| ctx.idle_notification(idle_ms))
+            .map_err(|e| PyException::new_err(format!("Idle error: {}", e)))
+    }
+
+    /// 执行一次微任务检查点
+    ///
+    /// auto_await=False 时求值不推进事件循环，Promise 的 then 回调和 queueMicrotask() 留在微任务队列中。
+    /// run_microtasks() 运行当前排队的所有微任务（以及它们新排入的微任务），不运行定时器和 I/O 回调，
+    /// 配合 drain_tasks() 可以一步一步地观察 Promise 的执行顺序。
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.eval("globalThis.log = []; Promise.resolve().then(() => log.push('micro')); setTimeout(() => log.push('macro'), 0);",
+    ///              auto_await=False)
+    ///     ctx.evaluate("log", auto_await=False)  # []
+    ///     ctx.run_microtasks()
+    ///     ctx.evaluate("log", auto_await=False)  # ['micro']
+    ///     ctx.drain_tasks()
+    ///     ctx.evaluate("log", auto_await=False)  # ['micro', 'macro']
+    ///     ```
+    fn run_microtasks(&self, py: Python<'_>) -> PyResult<()> {
+        self.check_fork()?;
+        self.without_gil(py, |ctx| ctx.microtask_checkpoint())
+            .map_err(|e| crate::quota::py_error("Event loop error", e))
+    }
+
+    /// 运行事件循环，直到没有待处理的任务或经过 max_ms 毫秒
+    ///
+    /// 依次运行到期的定时器、完成的异步操作（fetch 等）和微任务。定时器回调中未捕获的错误作为异常抛出
+    /// （之后的任务保留，可以再次调用）。受 timeout_ms / cpu_limit_ms 限制。
+    ///
+    /// Args:
+    ///     max_ms: 最多运行的时间（毫秒），默认 None 一直运行到没有待处理的任务；
+    ///         0 只处理已经就绪的任务
+    ///
+    /// Returns:
+    ///     True 表示已经没有待处理的任务，False 表示经过 max_ms 后仍有任务（如 setInterval）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.eval("setInterval(() => tick++, 10); var tick = 0;", auto_await=False)
+    ///     ctx.drain_tasks(max_ms=55)   # False
+    ///     ctx.evaluate("tick", auto_await=False)  # 5
+    ///     ```
+    #[pyo3(signature = (max_ms=None))]
+    fn drain_tasks(&self, py: Python<'_>, max_ms: Option<u64>) -> PyResult<bool> {
+        self.check_fork()?;
+        self.without_gil(py, |ctx| ctx.drain_event_loop(max_ms.map(Duration::from_millis)))
+            .map_err(|e| crate::quota::py_error("Event loop error", e))
+    }
+
+    /// 开始记录脚本消耗的不确定输入
+    ///
+    /// 记录 Math.random()、Date.now() / new Date()、performance.now()、crypto.getRandomValues() /
+    /// randomUUID()、fetch() 的响应和 register() 注册的 Python 函数的返回值，
+    /// stop_recording() 返回按顺序排列的 trace。偶发的错误结果可以在另一个 Context 中用 start_replay(trace) 精确复现。
+    ///
+    /// 包装在第一次调用时安装：之前保存的引用（如 `const rnd = Math.random`）不经过记录。
+    /// recycle_after / unload() 重建 isolate 后记录停止。
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.start_recording()
+    ///     token = ctx.call("sign", [data])
+    ///     trace = ctx.stop_recording()
+    ///     json.dump(trace, open("bad_token.json", "w"))
+    ///     ```
+    fn start_recording(&self, py: Python<'_>) -> PyResult<()> {
+        self.check_fork()?;
+        self.without_gil(py, |ctx| ctx.start_tape(crate::replay::Mode::Record, Vec::new()))
+            .map_err(|e| PyRuntimeError::new_err(format!("Replay error: {}", e)))
+    }
+
+    /// 停止记录，返回 trace
+    ///
+    /// Returns:
+    ///     [kind, value] 的列表（kind 为 "random" / "now" / "perf" / "bytes" / "uuid" / "fetch" / "call"），
+    ///     可以用 json 保存
+    fn stop_recording<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        self.check_fork()?;
+        let stopped = self
+            .without_gil(py, |ctx| ctx.stop_tape(crate::replay::Mode::Record))
+            .map_err(|e| PyRuntimeError::new_err(format!("Replay error: {}", e)))?;
+        let trace = PyList::empty(py);
+        for (kind, value) in stopped.entries {
+            trace.append(PyList::new(py, [kind.into_bound_py_any(py)?, json_str_to_python(py, &value)?])?)?;
+        }
+        Ok(trace)
+    }
+
+    /// 按 trace 重放不确定输入
+    ///
+    /// 之后脚本消耗的随机数、时间、fetch() 响应和 Python 函数的结果都取自 trace（不发起请求，不调用 Python 函数），
+    /// 执行相同的调用得到与记录时相同的结果。脚本请求的输入与 trace 不一致（类型、函数名、URL 不同或 trace 已用完）时，
+    /// 在 JS 中抛出 Error（replay diverged）。
+    ///
+    /// Args:
+    ///     trace: stop_recording() 返回的列表
+    ///
+    /// Example:
+    ///     ```python
     ///     ctx = never_jscore.Context()
-    ///     result = ctx.evaluate("btoa('hello')")  # 可以直接使用 btoa
+    ///     ctx.compile(js_code)
+    ///     ctx.start_replay(json.load(open("bad_token.json")))
+    ///     ctx.call("sign", [data])  # 与记录时相同的 token
+    ///     ctx.stop_replay()
+    ///     ```
+    #[pyo3(signature = (trace))]
+    fn start_replay(&self, py: Python<'_>, trace: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.check_fork()?;
+        let mut entries = Vec::new();
+        for item in trace.try_iter()? {
+            let item = item?;
+            let kind: String = item.get_item(0)?.extract()?;
+            let value = crate::convert::python_to_json(&item.get_item(1)?)?;
+            entries.push((kind, value.to_string()));
+        }
+        self.without_gil(py, |ctx| ctx.start_tape(crate::replay::Mode::Replay, entries))
+            .map_err(|e| PyRuntimeError::new_err(format!("Replay error: {}", e)))
+    }
+
+    /// 停止重放
     ///
-    ///     # 创建纯净 V8 环境
-    ///     ctx_pure = never_jscore.Context(enable_extensions=False)
-    ///     # 只有 ECMAScript 标准 API，无 btoa/atob 等
+    /// Returns:
+    ///     trace 中未使用的项数（0 表示脚本消耗了全部记录的输入）
+    fn stop_replay(&self, py: Python<'_>) -> PyResult<usize> {
+        self.check_fork()?;
+        self.without_gil(py, |ctx| ctx.stop_tape(crate::replay::Mode::Replay))
+            .map(|stopped| stopped.remaining)
+            .map_err(|e| PyRuntimeError::new_err(format!("Replay error: {}", e)))
+    }
+
+    /// 列出代码用到但当前 Context 中不存在的全局变量
     ///
-    ///     # 创建带日志的上下文（用于调试）
-    ///     ctx_debug = never_jscore.Context(enable_logging=True)
+    /// 试运行代码：缺失的名称被记录下来并以吸收任何操作的桩对象代替，脚本继续执行以发现后续缺失的名称，
+    /// 一次得到脚本期望的全部环境（`typeof X` 探测的名称同样列出）。用来判断需要启用扩展、
+    /// 使用 browser 预设，还是需要自己定义哪些桩。
     ///
-    ///     # 创建带固定随机数种子的上下文（用于调试和算法对比）
-    ///     ctx_seeded = never_jscore.Context(random_seed=12345)
-    ///     r1 = ctx_seeded.evaluate("Math.random()")  # 确定性随机数
-    ///     r2 = ctx_seeded.evaluate("Math.random()")  # 下一个确定性随机数
+    /// 代码在函数作用域中执行，声明的变量和对新名称的赋值不会留在全局作用域中；
+    /// 但调用已有函数的副作用（修改已有对象、设置定时器等）是真实的，试运行不可信代码时使用单独的 Context。
+    /// 定时器等异步回调中用到的名称不会列出。
     ///
-    ///     # 另一个相同种子的上下文将产生相同的随机数序列
-    ///     ctx_seeded2 = never_jscore.Context(random_seed=12345)
-    ///     r3 = ctx_seeded2.evaluate("Math.random()")  # r3 == r1
+    /// Args:
+    ///     code: JavaScript 代码
+    ///
+    /// Returns:
+    ///     缺失的全局变量名列表（按首次访问的顺序）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context(enable_extensions=False)
+    ///     ctx.missing_globals("var ua = navigator.userAgent; var t = btoa(require('x').key);")
+    ///     # ['navigator', 'btoa', 'require']
+    ///     ```
+    fn missing_globals(&self, py: Python<'_>, code: String) -> PyResult<Vec<String>> {
+        self.check_fork()?;
+        self.without_gil(py, |ctx| ctx.find_missing_globals(&code))
+            .map_err(|e| PyException::new_err(format!("Missing globals error: {}", e)))
+    }
+
+    /// 把选定的全局变量序列化为 bytes（结构化克隆），之后用 import_state() 恢复
+    ///
+    /// 只需要计算一次的状态（设备注册信息、协商的密钥等）导出后写入文件或数据库，
+    /// 进程重启后在新的 Context 中恢复，不必重新计算。支持对象、数组、Map / Set / Date / RegExp /
+    /// 类型化数组和循环引用；同时导出的变量之间共享的对象恢复后仍然是同一个对象。
+    ///
+    /// 函数、Symbol 无法克隆（抛出 DataCloneError，指出是哪个全局变量）；
+    /// 类实例恢复为普通对象（原型不保留）。数据格式取决于 V8 版本：
+    /// 新版本能读取旧版本导出的数据，反之不行。
+    ///
+    /// Args:
+    ///     keys: 全局变量名列表（不存在的变量抛出 ReferenceError）
+    ///
+    /// Returns:
+    ///     状态数据（bytes）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.evaluate("globalThis.device = register()")
+    ///     open("device.bin", "wb").write(ctx.export_state(keys=["device"]))
+    ///
+    ///     # 进程重启后
+    ///     ctx = never_jscore.Context()
+    ///     ctx.import_state(open("device.bin", "rb").read())
+    ///     ctx.evaluate("device.id")
+    ///     ```
+    fn export_state<'py>(&self, py: Python<'py>, keys: Vec<String>) -> PyResult<Bound<'py, PyBytes>> {
+        self.check_fork()?;
+        let data = self
+            .without_gil(py, |ctx| ctx.export_globals(&keys))
+            .map_err(|e| PyException::new_err(format!("Export state error: {}", e)))?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// 把 export_state() 导出的数据恢复为全局变量（覆盖同名变量）
+    ///
+    /// 导入状态后 Context 不能再 pickle / deepcopy。
+    ///
+    /// Args:
+    ///     state: export_state() 返回的 bytes
+    ///
+    /// Returns:
+    ///     恢复的全局变量名列表
+    ///
+    /// Raises:
+    ///     ValueError: 数据不是 export_state() 导出的
+    fn import_state(&self, py: Python<'_>, state: &[u8]) -> PyResult<Vec<String>> {
+        self.check_fork()?;
+        let payload = crate::state::payload(state).map_err(PyValueError::new_err)?.to_vec();
+        self.without_gil(py, |ctx| ctx.import_globals(payload))
+            .map_err(|e| PyException::new_err(format!("Import state error: {}", e)))
+    }
+
+    /// 记录全局对象每个属性的指纹，之后用 diff_globals() 比较
+    ///
+    /// 指纹覆盖变量的内容：对象的属性（包括嵌套对象）、Map / Set 的条目、类型化数组的字节、函数的源码，
+    /// 对象内部只修改了一个深层属性也能发现。访问器属性只记录 getter / setter，不调用它们；
+    /// 闭包中的变量和全局 let/const/class 声明（不是全局对象的属性）不在快照中。
+    ///
+    /// Returns:
+    ///     {全局变量名: 指纹}，指纹形如 "object:1a2b3c4d"
+    ///
+    /// Example:
+    ///     ```python
+    ///     before = ctx.snapshot_globals()
+    ///     ctx.compile(open("sdk.js").read())
+    ///     ctx.diff_globals(before)
+    ///     # {'added': ['_sdk', 'sign'], 'changed': ['navigator'], 'removed': []}
     ///     ```
-    #[new]
-    #[pyo3(signature = (enable_extensions=true, enable_logging=false, random_seed=None))]
-    fn py_new(enable_extensions: bool, enable_logging: bool, random_seed: Option<u32>) -> PyResult<Self> {
-        crate::runtime::ensure_v8_initialized();
-        Self::new(enable_extensions, enable_logging, random_seed)
+    fn snapshot_globals(&self, py: Python<'_>) -> PyResult<BTreeMap<String, String>> {
+        self.check_fork()?;
+        self.without_gil(py, |ctx| ctx.snapshot_global_fingerprints())
+            .map_err(|e| PyException::new_err(format!("Snapshot globals error: {}", e)))
     }
 
-    /// 编译JavaScript代码（便捷方法）
+    /// 与 snapshot_globals() 的快照比较，返回新增、变化和删除的全局变量
     ///
-    /// 这是一个便捷方法，等价于 eval(code)。
-    /// 执行代码并将函数/变量加入全局作用域。
+    /// 用来确认初始化脚本实际创建了哪些全局状态，以及调用之间泄漏了什么状态
+    /// （缓存、计数器、被修改的环境对象等）。
     ///
     /// Args:
-    ///     code: JavaScript 代码字符串
+    ///     prev: snapshot_globals() 返回的快照
     ///
     /// Returns:
-    ///     None
+    ///     {"added": [...], "changed": [...], "removed": [...]}（名称按字母顺序）
     ///
     /// Example:
     ///     ```python
-    ///     ctx = never_jscore.Context()
-    ///     ctx.compile('''
-    ///         function add(a, b) { return a + b; }
-    ///         function sub(a, b) { return a - b; }
-    ///     ''')
-    ///     result = ctx.call("add", [5, 3])
+    ///     before = ctx.snapshot_globals()
+    ///     ctx.call("sign", ["payload"])
+    ///     leaked = ctx.diff_globals(before)
+    ///     assert not leaked["added"] and not leaked["changed"], leaked
     ///     ```
-    #[pyo3(signature = (code))]
-    pub fn compile(&self, code: String) -> PyResult<()> {
-        // 直接调用 exec_script，不经过 eval
-        self.exec_script(&code)
-            .map_err(|e| PyException::new_err(format!("Compile error: {}", e)))?;
-        Ok(())
+    fn diff_globals<'py>(&self, py: Python<'py>, prev: BTreeMap<String, String>) -> PyResult<Bound<'py, PyDict>> {
+        self.check_fork()?;
+        let current = self
+            .without_gil(py, |ctx| ctx.snapshot_global_fingerprints())
+            .map_err(|e| PyException::new_err(format!("Diff globals error: {}", e)))?;
+        crate::globals_diff::GlobalsDiff::between(&prev, &current).to_py_dict(py)
     }
 
-    /// 调用 JavaScript 函数
+    /// 预先执行有代表性的调用，让 V8 在接收真实流量之前完成函数的编译和内联缓存
+    ///
+    /// 新建的 Context 前几次调用明显更慢（函数在第一次调用时才编译，分层编译也要执行几次之后才生效），
+    /// 服务启动后先用样例参数预热可以降低最初一批请求的延迟。结果被丢弃。
+    /// 预热调用和普通调用一样会修改全局状态、计入配额，样例参数应当没有副作用。
     ///
     /// Args:
-    ///     name: 函数名称
-    ///     args: 参数列表
+    ///     calls: (函数名, 参数) 的列表，参数与 call() 的规则相同
+    ///     rounds: 每个调用执行的次数（默认 1）；更多的轮数让 V8 有机会优化热点函数
     ///     auto_await: 是否自动等待 Promise（默认 True）
     ///
-    /// Returns:
-    ///     函数返回值，自动转换为 Python 对象
-    #[pyo3(signature = (name, args, auto_await=None))]
-    pub fn call<'py>(
-        &self,
-        py: Python<'py>,
-        name: String,
-        args: &Bound<'_, PyAny>,
-        auto_await: Option<bool>,
-    ) -> PyResult<Bound<'py, PyAny>> {
-        let json_args = if args.is_instance_of::<PyList>() {
-            let list = args.downcast::<PyList>()?;
-            let mut vec_args = Vec::with_capacity(list.len());
-            for item in list.iter() {
-                vec_args.push(python_to_json(&item)?);
-            }
-            vec_args
-        } else {
-            vec![python_to_json(args)?]
-        };
-
-        let args_json: Vec<String> = json_args
-            .iter()
-            .map(|arg| serde_json::to_string(arg).unwrap())
-            .collect();
-        let args_str = args_json.join(", ");
-        let call_code = format!("{}({})", name, args_str);
-
-        let result_json = self
-            .execute_js(&call_code, auto_await.unwrap_or(true))
-            .map_err(|e| PyException::new_err(format!("Call error: {}", e)))?;
-
-        let result: JsonValue = serde_json::from_str(&result_json)
-            .map_err(|e| PyException::new_err(format!("JSON parse error: {}", e)))?;
+    /// Raises:
+    ///     ValueError: rounds 为 0
+    ///     TypeError: calls 中的项不是 (name, args)
+    ///     Exception: 某个预热调用失败（包含函数名）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context()
+    ///     ctx.compile(open("sdk.js").read())
+    ///     ctx.warmup([("sign", ["sample"]), ("encrypt", [{"id": 1}])], rounds=20)
+    ///     ```
+    #[pyo3(signature = (calls, rounds=1, auto_await=None))]
+    fn warmup(&self, py: Python<'_>, calls: &Bound<'_, PyAny>, rounds: usize, auto_await: Option<bool>) -> PyResult<()> {
+        self.check_fork()?;
+        let calls = crate::warmup::parse_calls(calls, rounds)?;
+        self.without_gil(py, |ctx| crate::warmup::run(ctx, &calls, rounds, auto_await.unwrap_or(true)))
+            .map_err(|e| crate::quota::py_error("Warmup error", e))
+    }
 
-        json_to_python(py, &result)
+    /// isolate 遇到致命错误后不再可用的原因（超出 max_heap_mb、执行中的 panic），正常时为 None
+    ///
+    /// poisoned 之后的执行抛出 ContextPoisoned；auto_recreate=True 时下一次调用之前重建，重建后恢复为 None。
+    #[getter]
+    fn poisoned(&self) -> Option<String> {
+        self.poison_reason()
     }
 
-    /// 执行代码并将其加入全局作用域
+    /// 冻结内置对象（Object、Array、Function 等的原型和构造函数）
     ///
-    /// 这个方法会执行JavaScript代码，并将定义的函数/变量保留在全局作用域中。
-    /// 类似 py_mini_racer 的 eval() 方法。
+    /// 在加载完初始化代码（JS 库）之后调用，之后执行的代码无法再修改内置原型，
+    /// 防止一次调用留下的原型污染影响其他调用。冻结不可撤销，重复调用是安全的。
+    ///
+    /// 冻结后仍然可以在实例上覆盖 toString / constructor / name 等属性；
+    /// Error 构造函数本身不冻结（可以设置 Error.stackTraceLimit），它们的原型仍然冻结。
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context()
+    ///     ctx.compile(open("sign.js").read())
+    ///     ctx.freeze_intrinsics()
+    ///
+    ///     ctx.evaluate("Array.prototype.map = () => 'hacked'")  # 静默失败（严格模式下抛出 TypeError）
+    ///     ctx.evaluate("[1, 2].map(x => x * 2)")                 # [2, 4]
+    ///     ```
+    fn freeze_intrinsics(&self, py: Python<'_>) -> PyResult<()> {
+        self.check_fork()?;
+        self.without_gil(py, |ctx| ctx.exec_named_script("<freeze_intrinsics>", FREEZE_INTRINSICS.to_string(), false))
+            .map_err(|e| PyException::new_err(format!("Freeze error: {}", e)))
+    }
+
+    /// 获取执行统计信息
     ///
     /// Args:
-    ///     code: JavaScript 代码
-    ///     return_value: 是否返回最后一个表达式的值（默认 False）
-    ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     timings: 是否返回耗时分解（默认 False）
     ///
     /// Returns:
-    ///     如果 return_value=True，返回最后一个表达式的值；否则返回 None
+    ///     默认返回 (exec_count,) 执行次数；
+    ///     timings=True 时返回字典：
+    ///     - exec_count: 执行次数
+    ///     - total: 累计耗时，last: 最近一次调用的耗时，均为字典（单位毫秒）：
+    ///       wrap_ms（准备求值）、compile_ms（编译脚本）、execute_ms（同步执行）、
+    ///       event_loop_ms（等待 Promise / 定时器）、total_ms
+    ///
+    ///     evaluate()/call() 的代码在包装函数内通过 eval 编译执行，编译时间计入 execute_ms；
+    ///     compile_ms 来自 compile()/compile_file()/eval() 执行的全局脚本。
     ///
     /// Example:
     ///     ```python
-    ///     ctx = Context()
-    ///     ctx.eval("function add(a, b) { return a + b; }")
-    ///     result = ctx.call("add", [1, 2])  # 可以调用，因为add在全局作用域
+    ///     ctx.compile(bundle)
+    ///     ctx.call("sign", ["data"])
+    ///     stats = ctx.get_stats(timings=True)
+    ///     print(stats["total"]["compile_ms"], stats["last"]["event_loop_ms"])
     ///     ```
-    #[pyo3(signature = (code, return_value=false, auto_await=None))]
-    pub fn eval<'py>(
-        &self,
-        py: Python<'py>,
-        code: String,
-        return_value: bool,
-        auto_await: Option<bool>,
-    ) -> PyResult<Bound<'py, PyAny>> {
-        if return_value {
-            // 需要返回值：使用包装的execute_js
-            let result_json = self
-                .execute_js(&code, auto_await.unwrap_or(true))
-                .map_err(|e| PyException::new_err(format!("Eval error: {}", e)))?;
-
-            let result: JsonValue = serde_json::from_str(&result_json)
-                .map_err(|e| PyException::new_err(format!("JSON parse error: {}", e)))?;
-
-            json_to_python(py, &result)
-        } else {
-            // 不需要返回值：直接执行脚本，加入全局作用域
-            self.exec_script(&code)
-                .map_err(|e| PyException::new_err(format!("Eval error: {}", e)))?;
-
-            Ok(py.None().into_bound(py))
-        }
+    #[pyo3(signature = (timings=false))]
+    pub(crate) fn get_stats<'py>(&self, py: Python<'py>, timings: bool) -> PyResult<Bound<'py, PyAny>> {
+        let stats = self.stats();
+        stats_to_python(py, &stats, timings)
     }
 
-    /// 执行代码并返回结果（不影响全局作用域）
-    ///
-    /// 这个方法用于求值，代码在独立的作用域中执行，不会影响全局变量。
+    /// 获取最近一次调用的 op 审计日志（需要 audit_ops=True）
     ///
-    /// Args:
-    ///     code: JavaScript 代码
-    ///     auto_await: 是否自动等待 Promise（默认 True）
+    /// 每次 evaluate()/call()/compile() 开始时清空，只包含该次调用中 JS 通过扩展 API 调用的 op。
     ///
     /// Returns:
-    ///     表达式的值
-    #[pyo3(signature = (code, auto_await=None))]
-    pub fn evaluate<'py>(
-        &self,
-        py: Python<'py>,
-        code: String,
-        auto_await: Option<bool>,
-    ) -> PyResult<Bound<'py, PyAny>> {
-        let result_json = self
-            .execute_js(&code, auto_await.unwrap_or(true))
-            .map_err(|e| PyException::new_err(format!("Evaluate error: {}", e)))?;
-
-        let result: JsonValue = serde_json::from_str(&result_json)
-            .map_err(|e| PyException::new_err(format!("JSON parse error: {}", e)))?;
-
-        json_to_python(py, &result)
+    ///     字典：
+    ///     - entries: 记录列表，每项为 {"op", "args", "start_ms", "duration_ms", "status", "worker"}
+    ///       - args: 参数字符串列表（超过 200 个字符的参数被截断）
+    ///       - start_ms: 开始时间（Unix 时间戳，毫秒）
+    ///       - status: "ok" / "error" / "denied"（被 permissions 拒绝）/ "pending"（异步 op 尚未完成，duration_ms 为 None）
+    ///       - worker: new Worker() 中的调用为 Worker 的名称，Context 自身的调用为 None
+    ///     - dropped: 超过 10000 条上限未记录的调用次数
+    ///
+    /// Raises:
+    ///     RuntimeError: 未开启 audit_ops
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context(audit_ops=True)
+    ///     ctx.evaluate("require('fs').readFileSync('/etc/hosts'); md5('x')")
+    ///     for entry in ctx.get_audit_log()["entries"]:
+    ///         print(entry["op"], entry["args"], entry["status"])
+    ///     # op_read_file_sync ['/etc/hosts'] ok
+    ///     # op_md5 ['x'] ok
+    ///     ```
+    fn get_audit_log<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let (entries, dropped) = self.audit_log()?;
+        crate::audit::to_python(py, &entries, dropped)
     }
 
-    /// 请求垃圾回收
+    /// 获取资源配额使用情况
     ///
-    /// 注意：这只是向 V8 发送 GC 请求，V8 会根据自己的策略决定是否执行。
-    fn gc(&self) -> PyResult<()> {
-        self.request_gc()
-            .map_err(|e| PyException::new_err(format!("GC error: {}", e)))
+    /// Returns:
+    ///     dict: {"executions": 已执行次数, "cpu_ms": 累计 CPU 时间, "console_bytes": 累计 console 输出字节数,
+    ///            "exhausted": 配额耗尽原因（未耗尽时为 None）, "limits": 配额设置}
+    ///
+    /// Raises:
+    ///     RuntimeError: 未设置 quotas
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context(quotas={"max_executions": 100})
+    ///     ctx.evaluate("1 + 1")
+    ///     ctx.get_quota_usage()["executions"]  # 1
+    ///     ```
+    fn get_quota_usage<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.quota_usage()?.to_python(py)
     }
 
-    /// 获取执行统计信息
+    /// 获取执行历史
     ///
     /// Returns:
-    ///     (exec_count,) 执行次数
-    fn get_stats(&self) -> PyResult<(usize,)> {
-        Ok((*self.exec_count.borrow(),))
+    ///     list: 最近的执行记录（最早的在前），每条为 {"kind": "evaluate" | "script", "filename": 脚本名称,
+    ///           "code": 代码（超过 500 个字符时截断）, "code_length": 代码字节数, "start_ms": 开始时间（Unix 毫秒）,
+    ///           "duration_ms": 耗时, "success": 是否成功, "error": 错误信息}
+    ///
+    /// Raises:
+    ///     RuntimeError: 未设置 history
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context(history=200)
+    ///     ctx.compile("function sign(x) { return x.split('').reverse().join(''); }")
+    ///     try:
+    ///         ctx.call("sign", [None])
+    ///     except Exception:
+    ///         for entry in ctx.history():
+    ///             print(entry["kind"], entry["code"][:40], entry["success"], entry["error"])
+    ///     ```
+    fn history<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        crate::history::to_python(py, &self.history_entries()?)
     }
 
-    /// 重置统计信息
-    fn reset_stats(&self) -> PyResult<()> {
+    /// 重置统计信息（执行次数和累计耗时）
+    pub(crate) fn reset_stats(&self) -> PyResult<()> {
         *self.exec_count.borrow_mut() = 0;
+        self.total_timings.set(Timings::default());
         Ok(())
     }
 
@@ -908,6 +4548,8 @@ impl Context {
     ///     print(f"内存增加: {increase / 1024 / 1024:.2f} MB")
     ///     ```
     fn get_heap_statistics(&self, py: Python) -> PyResult<Py<PyDict>> {
+        self.check_fork()?;
+        self.check_idle()?;
         let stats = self.get_heap_stats()
             .map_err(|e| PyException::new_err(format!("Failed to get heap statistics: {}", e)))?;
 
@@ -958,7 +4600,9 @@ impl Context {
     ///     - 可以对比两个快照找内存泄漏（before/after）
     ///     - 搜索已知字符串可以快速定位关键对象
     ///     - 查看对象的 Retainers 了解为什么对象没有被回收
-    fn take_heap_snapshot(&self, file_path: String) -> PyResult<()> {
+    pub(crate) fn take_heap_snapshot(&self, file_path: String) -> PyResult<()> {
+        self.check_fork()?;
+        self.check_idle()?;
         use std::io::Write;
 
         self.enter_isolate();
@@ -986,6 +4630,113 @@ impl Context {
         Ok(())
     }
 
+    /// 按脚本名设置断点，在 debug_call() 中执行到该行时暂停
+    ///
+    /// 脚本名以 file 结尾即匹配：compile() 的脚本名为 "<exec>"，compile_file() 为文件路径，
+    /// 也可以在代码末尾用 `//# sourceURL=name.js` 指定。断点可以在脚本编译之前设置。
+    ///
+    /// Args:
+    ///     file: 脚本名或其结尾部分（如 "sign.js"）
+    ///     line: 行号（从 1 开始）
+    ///     column: 列号（可选，从 1 开始）
+    ///     condition: 条件表达式（可选），在断点处求值为真时才暂停
+    ///
+    /// Returns:
+    ///     断点 ID，用于 remove_breakpoint() 和 PausedFrame.hit_breakpoints
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.compile_file("sign.js")
+    ///     bp = ctx.set_breakpoint("sign.js", 42, condition="i > 10")
+    ///     ctx.debug_call("sign", ["data"], on_pause=lambda frame: print(frame.locals()))
+    ///     ctx.remove_breakpoint(bp)
+    ///     ```
+    #[pyo3(signature = (file, line, column=None, condition=None))]
+    pub fn set_breakpoint(&self, file: &str, line: u32, column: Option<u32>, condition: Option<&str>) -> PyResult<String> {
+        self.check_fork()?;
+        self.check_idle()?;
+        if line == 0 || column == Some(0) {
+            return Err(PyValueError::new_err("line and column are 1-based"));
+        }
+        self.with_debugger(|debugger| debugger.set_breakpoint(file, line, column, condition))
+            .map_err(|e| PyRuntimeError::new_err(format!("Debugger error: {}", e)))
+    }
+
+    /// 删除 set_breakpoint() 设置的断点
+    #[pyo3(signature = (breakpoint_id))]
+    pub fn remove_breakpoint(&self, breakpoint_id: &str) -> PyResult<()> {
+        self.check_fork()?;
+        self.check_idle()?;
+        self.with_debugger(|debugger| debugger.remove_breakpoint(breakpoint_id))
+            .map_err(|e| PyRuntimeError::new_err(format!("Debugger error: {}", e)))
+    }
+
+    /// 在调试模式下调用 JavaScript 函数
+    ///
+    /// 与 call() 相同，但执行遇到 debugger 语句或 set_breakpoint() 设置的断点时暂停，
+    /// 调用 on_pause(frame)：frame 为 PausedFrame，可以读取调用栈、作用域变量，在调用帧上求值。
+    /// on_pause 的返回值决定如何继续：None / "continue" 继续执行，
+    /// "step_over" / "step_into" / "step_out" 单步执行后再次暂停。
+    /// on_pause 抛出的异常会终止执行并由 debug_call() 原样抛出。
+    ///
+    /// 注意：
+    /// - on_pause 中不能调用此 Context 的其他方法（执行仍在进行），需要求值时使用 frame.evaluate()
+    /// - 暂停的时间计入 timeout_ms / cpu_limit_ms
+    /// - call() / evaluate() 等普通调用不会在断点和 debugger 语句处暂停
+    ///
+    /// Args:
+    ///     name: 函数名称
+    ///     args: 参数列表
+    ///     on_pause: 暂停时调用的函数，参数为 PausedFrame
+    ///     pause_on_start: 是否在进入函数时暂停（frame.reason 为 "start"），默认 False
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///
+    /// Returns:
+    ///     函数返回值
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.compile("function add(a, b) { const sum = a + b; debugger; return sum; }")
+    ///
+    ///     def on_pause(frame):
+    ///         print(frame.line, frame.locals())  # 1 {'a': 1, 'b': 2, 'sum': 3}
+    ///         print(frame.evaluate("sum * 10"))  # 30
+    ///
+    ///     ctx.debug_call("add", [1, 2], on_pause)  # 3
+    ///     ```
+    #[pyo3(signature = (name, args, on_pause, pause_on_start=false, auto_await=None))]
+    pub fn debug_call<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        on_pause: &Bound<'_, PyAny>,
+        pause_on_start: bool,
+        auto_await: Option<bool>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.check_fork()?;
+        if !on_pause.is_callable() {
+            return Err(pyo3::exceptions::PyTypeError::new_err("on_pause must be callable"));
+        }
+        let call_code = format_call(&name, &call_args_to_json(args)?);
+
+        self.ensure_polyfill_loaded()
+            .and_then(|_| self.with_debugger(|debugger| debugger.begin(on_pause.clone().unbind(), pause_on_start.then_some(name.as_str()))))
+            .map_err(|e| crate::quota::py_error("Debug error", e))?;
+        let result = self.without_gil(py, |ctx| ctx.execute_js(call_code, auto_await.unwrap_or(true)));
+        let callback_error = self.with_debugger(|debugger| Ok(debugger.end())).ok().flatten();
+
+        if let Some(e) = callback_error {
+            // on_pause 出错时执行被终止，恢复 isolate
+            self.enter_isolate();
+            self.runtime.borrow_mut().v8_isolate().cancel_terminate_execution();
+            self.exit_isolate();
+            return Err(e);
+        }
+        let result_json = result.map_err(|e| crate::quota::py_error("Call error", e))?;
+        json_str_to_python(py, &result_json)
+    }
+
     /// 获取 Hook 拦截的数据
     ///
     /// 当 JavaScript 调用 __saveAndTerminate__() 或 $terminate() 时，
@@ -1032,7 +4783,7 @@ impl Context {
     ///         print(f"Intercepted Body: {data['body']}")
     ///     ```
     fn get_hook_data(&self) -> Option<String> {
-        crate::storage::get_hook_data()
+        self.result_storage.hook_data()
     }
 
     /// 清空保存的 Hook 数据
@@ -1056,7 +4807,294 @@ impl Context {
     ///     data = ctx.get_hook_data()
     ///     ```
     fn clear_hook_data(&self) {
-        crate::storage::clear_hook_data();
+        self.result_storage.clear_hook_data();
+    }
+
+    /// 把 Python 函数注册为 JS 全局函数
+    ///
+    /// JS 调用该函数时，参数转换为 Python 对象作为位置参数传入，返回值转换回 JS 值；
+    /// Python 抛出的异常在 JS 中抛出为 Error。可以用来把 JS 中的关键调用（加密、签名）
+    /// 转发到 Python 记录或替换。同名时替换之前注册的函数。
+    ///
+    /// 回调中不能再调用同一个 Context 的 evaluate() / call() 等方法（抛出 RuntimeError）。
+    ///
+    /// Args:
+    ///     name: JS 全局函数名
+    ///     func: Python 可调用对象
+    ///
+    /// Example:
+    ///     ```python
+    ///     def on_sign(data):
+    ///         print("sign called from", ctx.capture_stack()[0]["function"])
+    ///         return data.upper()
+    ///
+    ///     ctx.register("pySign", on_sign)
+    ///     ctx.evaluate("function _0x3f2a(x) { return pySign(x); } _0x3f2a('abc')")  # 'ABC'
+    ///     ```
+    #[pyo3(signature = (name, func))]
+    fn register(&self, name: String, func: Bound<'_, PyAny>) -> PyResult<()> {
+        self.check_fork()?;
+        if !func.is_callable() {
+            return Err(PyTypeError::new_err("func must be callable"));
+        }
+        self.register_py_function(&name, func.unbind())
+            .map_err(|e| PyException::new_err(format!("Register error: {}", e)))
+    }
+
+    /// 获取调用当前 Python 回调的 JS 调用栈
+    ///
+    /// 只能在 register() 注册的函数被 JS 调用期间使用，返回 JS 调用该函数时的调用栈，
+    /// 用于确定是哪个（混淆过的）函数触发了 Python 回调。
+    ///
+    /// Returns:
+    ///     栈帧列表（栈顶在前），每项为 {function, script, line, column, is_eval, is_constructor}
+    ///
+    /// Raises:
+    ///     RuntimeError: 不在 JS 调用的 Python 回调中
+    fn capture_stack<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        crate::py_functions::capture_stack(py)
+    }
+
+    /// 加载 WebAssembly 模块
+    ///
+    /// 从 Python 提供的字节编译并实例化模块，实例的 exports 定义为 JS 全局变量 `name`：
+    /// JS 中通过 `name.fn(...)` 调用，Python 中通过 `ctx.call("name.fn", [...])` 调用。
+    /// 同名时替换之前的模块。jitless 模式或 allow_dynamic_code=False 时 WebAssembly 不可用。
+    ///
+    /// Args:
+    ///     name: JS 全局变量名
+    ///     wasm_bytes: .wasm 文件内容
+    ///     imports: 导入对象（可选），{模块名: {字段名: 值}}，值可以是：
+    ///              - Python 可调用对象（与 register() 相同，参数和返回值经 JSON 转换）
+    ///              - 数字（用于 global 导入）
+    ///              - 字符串：在全局作用域求值的 JS 表达式，如 "console.log"
+    ///     wasi: 为 wasi-sdk 编译的模块提供最小 WASI 宿主（可选），True 或
+    ///           {"args": [...], "env": {...}, "inherit_env": False, "stdin": b""}：
+    ///           - 提供命令行参数、环境变量、时钟、随机数；stdout / stderr 按行输出到 console.log / console.error
+    ///           - 没有预打开目录，不能访问文件系统
+    ///           - inherit_env=True 时传入宿主环境变量，受 permissions 的 env 权限限制
+    ///           - 命令模块的 `_start()` 返回退出码；reactor 模块的 `_initialize()` 在加载时自动调用
+    ///
+    /// Returns:
+    ///     dict: 导出项 {名称: 类型}，类型为 "function" / "memory" / "table" / "global" / "tag"
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context()
+    ///     with open("sign.wasm", "rb") as f:
+    ///         exports = ctx.load_wasm("signer", f.read(), imports={"env": {"log": print}})
+    ///     ctx.call("signer.sign", [123])
+    ///
+    ///     with open("tool.wasm", "rb") as f:
+    ///         ctx.load_wasm("tool", f.read(), wasi={"args": ["tool", "--help"]})
+    ///     exit_code = ctx.call("tool._start", [])
+    ///     ```
+    #[pyo3(signature = (name, wasm_bytes, imports=None, wasi=None))]
+    fn load_wasm<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        wasm_bytes: &[u8],
+        imports: Option<&Bound<'_, PyDict>>,
+        wasi: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        self.check_fork()?;
+        let imports = crate::wasm::imports_from_py(imports)?;
+        let wasi = crate::wasm::wasi_from_py(wasi)?;
+        let bytes = wasm_bytes.to_vec();
+        let exports = self
+            .without_gil(py, |ctx| ctx.load_wasm_module(&name, bytes, imports, wasi))
+            .map_err(|e| PyException::new_err(format!("WASM error: {}", e)))?;
+        crate::wasm::exports_to_python(py, exports)
+    }
+
+    /// 零拷贝访问 WASM 线性内存
+    ///
+    /// 返回直接指向 V8 内存的可写 memoryview，Python 读写 WASM 代码使用的缓冲区时不经过 JS 序列化。
+    ///
+    /// 生命周期：
+    /// - 视图存在期间内存不会被释放，始终可以安全访问
+    /// - 长度是调用时的内存大小；memory.grow() 之后旧视图可能不再反映当前内存，需要重新调用
+    /// - Context 销毁时视图被 release()，之后访问抛出 ValueError
+    ///
+    /// Args:
+    ///     expr: 求值为 WebAssembly.Memory / ArrayBuffer / SharedArrayBuffer 的 JS 表达式，如 "calc.memory"
+    ///
+    /// Returns:
+    ///     可写的 memoryview（格式 "B"）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.load_wasm("calc", wasm_bytes)
+    ///     mem = ctx.wasm_memory("calc.memory")
+    ///     mem[0:5] = b"hello"                   # 写入输入
+    ///     ctx.call("calc.process", [0, 5])
+    ///     result = bytes(mem[1024:1056])        # 读取输出
+    ///     ```
+    fn wasm_memory<'py>(&self, py: Python<'py>, expr: &str) -> PyResult<Bound<'py, PyMemoryView>> {
+        self.check_fork()?;
+        let store = self
+            .without_gil(py, |ctx| ctx.wasm_memory_store(expr))
+            .map_err(|e| PyException::new_err(format!("WASM error: {}", e)))?;
+        self.wasm_views.view(py, store)
+    }
+
+    /// 把 SharedBuffer 定义为全局 SharedArrayBuffer
+    ///
+    /// JS 与 Python（buffer.view()）以及共享同一个 SharedBuffer 的其他 Context 读写同一块内存。
+    /// 创建时就需要的话使用 Context(shared_buffers={name: buffer})；ContextPool 只支持构造参数。
+    ///
+    /// Args:
+    ///     name: 全局变量名
+    ///     buffer: SharedBuffer
+    ///
+    /// Example:
+    ///     ```python
+    ///     buf = never_jscore.SharedBuffer(16)
+    ///     ctx.share_buffer("shared", buf)
+    ///     ctx.evaluate("Atomics.store(new Int32Array(shared), 0, 42)")
+    ///     int.from_bytes(buf.view()[0:4], "little")  # 42
+    ///     ```
+    fn share_buffer(&self, py: Python<'_>, name: &str, buffer: PyRef<'_, SharedBuffer>) -> PyResult<()> {
+        self.check_fork()?;
+        let store = buffer.store();
+        self.without_gil(py, |ctx| ctx.share_buffer_store(name, store))
+            .map_err(|e| PyException::new_err(format!("Shared buffer error: {}", e)))
+    }
+
+    /// 设置慢脚本回调
+    ///
+    /// 一次调用超过 threshold_ms（墙钟时间）时调用 callback，但不终止执行，
+    /// 服务可以记录慢调用而不让请求失败。阈值小于 timeout_ms 时回调先于超时终止运行。
+    ///
+    /// 回调在执行线程上运行，此时 JS 处于暂停状态，回调中不能使用同一个 Context 执行代码；
+    /// 回调抛出的异常不影响执行，只通过 sys.unraisablehook 报告。
+    ///
+    /// Args:
+    ///     threshold_ms: 报告阈值（毫秒）
+    ///     callback: 回调，参数为字典 {elapsed_ms, threshold_ms, kind, filename, code, stack}；None 清除回调
+    ///               - stack: 超过阈值时的 JS 调用栈（与 capture_stack() 格式相同）。
+    ///                 JS 在等待定时器 / Promise 时无法获取，调用结束时报告且 stack 为 None
+    ///
+    /// Example:
+    ///     ```python
+    ///     def on_slow(info):
+    ///         top = info["stack"][0]["function"] if info["stack"] else None
+    ///         logger.warning("slow JS call %.0fms in %s: %s", info["elapsed_ms"], top, info["code"][:80])
+    ///
+    ///     ctx = never_jscore.Context(timeout_ms=5000)
+    ///     ctx.on_slow_script(500, on_slow)
+    ///     ```
+    #[pyo3(signature = (threshold_ms, callback))]
+    fn on_slow_script(&self, threshold_ms: u64, callback: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+        self.check_fork()?;
+        let (threshold, callback) = crate::slow_script::validate(threshold_ms, callback)?;
+        self.set_slow_script(threshold, callback);
+        Ok(())
+    }
+
+    /// 设置 GC 事件回调
+    ///
+    /// 每次 V8 垃圾回收都会产生一个事件，用于观察负载下的 GC 行为（频率、停顿时间、回收量）。
+    /// GC 发生在 JS 执行过程中，不能在那时调用 Python：事件先记录下来，
+    /// 在调用结束后（以及 gc() / notify_low_memory() / idle() 之后）按发生顺序交给回调，
+    /// 回调中可以正常使用该 Context。回调抛出的异常只通过 sys.unraisablehook 报告。
+    ///
+    /// Args:
+    ///     callback: 回调，参数为字典：
+    ///               - type: "scavenge"（新生代）/ "mark_sweep_compact"（完整 GC）/ "minor_mark_sweep" /
+    ///                 "incremental_marking" / "process_weak_callbacks"
+    ///               - forced: 是否为强制 GC（如 notify_low_memory()）
+    ///               - duration_ms: GC 耗时
+    ///               - heap_before / heap_after / freed: GC 前后的堆使用量及回收的字节数
+    ///               None 清除回调
+    ///
+    /// Example:
+    ///     ```python
+    ///     def on_gc(event):
+    ///         metrics.timing("v8.gc." + event["type"], event["duration_ms"])
+    ///
+    ///     ctx = never_jscore.Context()
+    ///     ctx.on_gc(on_gc)
+    ///     ```
+    #[pyo3(signature = (callback))]
+    fn on_gc(&self, callback: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+        self.check_fork()?;
+        self.check_idle()?;
+        self.set_gc_callback(crate::gc_events::validate(callback)?);
+        Ok(())
+    }
+
+    /// 设置 ES 模块的 import.meta 字段
+    ///
+    /// 每次加载模块（compile_file(mode="module")）之前调用 callback，返回的 dict 合并到该模块的 import.meta 中，
+    /// 可以覆盖 url（默认为本地文件的 file:// 地址）或加入构建工具注入的字段（如 env），
+    /// 供初始化时根据 import.meta 选择分支的 bundle 使用。字段在模块的其他代码之前设置，值必须可以 JSON 序列化。
+    ///
+    /// Args:
+    ///     callback: 回调，参数为字典 {url, path}（url 为默认的 import.meta.url，path 为文件路径），
+    ///               返回 dict 或 None（保持默认值）；回调抛出异常时模块加载失败。None 清除回调
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.on_import_meta(lambda info: {
+    ///         "url": "https://www.example.com/static/" + os.path.basename(info["path"]),
+    ///         "env": {"MODE": "production"},
+    ///     })
+    ///     ctx.compile_file("bundle.mjs", mode="module")
+    ///     ```
+    #[pyo3(signature = (callback))]
+    fn on_import_meta(&self, callback: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+        self.check_fork()?;
+        *self.import_meta.borrow_mut() = crate::import_meta::validate(callback)?;
+        Ok(())
+    }
+
+    /// pickle / copy.deepcopy 支持
+    ///
+    /// V8 不能序列化正在运行的 isolate：pickle 时在后台线程按原顺序重放 compile() / compile_file() /
+    /// eval() 等执行过的全局脚本，生成启动快照，与脚本和构造参数一起保存。
+    /// 恢复时从快照启动（版本或 V8 参数不一致时重新执行脚本），得到与原 Context 全局状态相同的新 Context。
+    ///
+    /// 不保存的状态：evaluate() / call() 中的副作用、失败的脚本、回调（on_gc 等）、配额计数和执行历史。
+    ///
+    /// Raises:
+    ///     TypeError: Context 包含无法重放的状态（register() 注册的函数、load_wasm()、ES 模块、
+    ///                permissions / fs_roots、自定义快照、import_state()）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context()
+    ///     ctx.compile(open("sign.js").read())
+    ///     data = pickle.dumps(ctx)            # 保存到磁盘或发送给 multiprocessing 的工作进程
+    ///     ctx2 = pickle.loads(data)           # 从快照启动，sign.js 已经加载
+    ///     ctx3 = copy.deepcopy(ctx)
+    ///     ```
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (Bound<'py, PyDict>,))> {
+        let this = slf.borrow();
+        this.check_fork()?;
+        let state = this.replay.borrow().to_python(slf.py())?;
+        Ok((slf.get_type().getattr("_from_pickle")?, (state,)))
+    }
+
+    /// 从 __reduce__() 生成的状态恢复 Context（pickle 内部使用）
+    #[classmethod]
+    #[pyo3(name = "_from_pickle")]
+    fn from_pickle(_cls: &Bound<'_, PyType>, py: Python<'_>, state: &Bound<'_, PyDict>) -> PyResult<Self> {
+        crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        let restored = crate::pickle::restore(state)?;
+        let mut ctx = Self::new(restored.options.clone())?;
+        if let Some(owner) = restored.snapshot {
+            ctx.keep_snapshot_alive(owner);
+            *ctx.replay.borrow_mut() = crate::pickle::restored_log(&restored.options, restored.scripts);
+        } else {
+            for (name, code) in restored.scripts {
+                ctx.without_gil(py, |ctx| ctx.exec_named_script(&name, code, true))
+                    .map_err(|e| crate::quota::py_error("Unpickle error", e))?;
+            }
+        }
+        ctx.replay.borrow_mut().restore_tags(restored.tags);
+        Ok(ctx)
     }
 
     /// 上下文管理器支持：__enter__
@@ -1,8 +1,129 @@
 use pyo3::IntoPyObjectExt;
-use pyo3::exceptions::PyException;
+use pyo3::exceptions::{PyException, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyBytes, PyDict, PyList, PyModule, PyType};
 use serde_json::Value as JsonValue;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::undefined_policy::UNDEFINED_TAG;
+
+/// 模块级类型转换器（register_converter()）：Python 类型 -> 转换函数
+struct Converter {
+    py_type: Py<PyType>,
+    to_js: Py<PyAny>,
+}
+
+/// 已注册的转换器（后注册的在后面）；转换时取出快照，调用 Python 时不持有锁
+static CONVERTERS: Mutex<Option<Arc<Vec<Converter>>>> = Mutex::new(None);
+
+/// 是否注册了任意转换器（未注册时不需要加锁）
+static HAS_CONVERTERS: AtomicBool = AtomicBool::new(false);
+
+/// 转换器嵌套调用的层数上限（转换器的返回值、以及其中嵌套的对象再次匹配转换器时加一层）
+///
+/// 互相转换的转换器（A -> B -> A）或返回值中包含同类对象的转换器会无限递归，超过上限时抛出 TypeError。
+const MAX_CONVERTER_DEPTH: usize = 32;
+
+thread_local! {
+    /// 当前线程正在进行的转换器调用层数
+    static CONVERTER_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// 注册 Python -> JavaScript 的类型转换器，对所有 Context 生效
+///
+/// 传给 JS 的值（call() / acall() 的参数、register() 注册的 Python 函数的返回值等）
+/// 是 `py_type` 的实例（包括子类）时，先调用 `to_js(value)`，再按内置规则转换它的返回值，
+/// 返回值中嵌套的对象同样会应用转换器。转换器在内置类型之前检查，
+/// 同一个值匹配多个转换器时后注册的优先。转换器嵌套超过 32 层（例如互相转换的转换器）时抛出 TypeError。
+///
+/// JS -> Python 方向仍然是 JSON 语义（Temporal 值除外，见 json_to_python）：需要自定义时在 JS 中定义 `toJSON()`。
+///
+/// Args:
+///     py_type: Python 类型
+///     to_js: 转换函数，返回可以转换为 JS 的值（None / bool / int / float / str / list / dict
+///            或其他已注册转换器的类型）；传入 None 取消该类型的转换器
+///
+/// Raises:
+///     TypeError: to_js 不可调用（转换时：转换器返回同类型的值，或嵌套超过 32 层）
+///
+/// Example:
+///     ```python
+///     import datetime, decimal
+///
+///     never_jscore.register_converter(datetime.datetime, lambda d: d.isoformat())
+///     never_jscore.register_converter(decimal.Decimal, str)
+///
+///     ctx = never_jscore.Context()
+///     ctx.evaluate("function f(t, amount) { return [t, amount]; }")
+///     ctx.call("f", [datetime.datetime(2024, 1, 1), decimal.Decimal("9.90")])
+///     # ['2024-01-01T00:00:00', '9.90']
+///     ```
+#[pyfunction]
+#[pyo3(signature = (py_type, to_js))]
+pub fn register_converter(py_type: Bound<'_, PyType>, to_js: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+    if to_js.as_ref().is_some_and(|f| !f.is_callable()) {
+        return Err(PyTypeError::new_err("to_js must be callable or None"));
+    }
+    let mut converters = CONVERTERS.lock().unwrap();
+    let mut list: Vec<Converter> = converters
+        .as_deref()
+        .into_iter()
+        .flatten()
+        .filter(|c| !c.py_type.bind(py_type.py()).is(&py_type))
+        .map(|c| Converter {
+            py_type: c.py_type.clone_ref(py_type.py()),
+            to_js: c.to_js.clone_ref(py_type.py()),
+        })
+        .collect();
+    if let Some(to_js) = to_js {
+        list.push(Converter {
+            py_type: py_type.unbind(),
+            to_js: to_js.unbind(),
+        });
+    }
+    HAS_CONVERTERS.store(!list.is_empty(), Ordering::Release);
+    *converters = Some(Arc::new(list));
+    Ok(())
+}
+
+/// 按注册的转换器转换值，没有匹配的转换器时返回 None
+///
+/// 嵌套超过 MAX_CONVERTER_DEPTH 层时抛出 TypeError。
+fn apply_converter(obj: &Bound<'_, PyAny>) -> PyResult<Option<JsonValue>> {
+    if !HAS_CONVERTERS.load(Ordering::Acquire) {
+        return Ok(None);
+    }
+    let Some(converters) = CONVERTERS.lock().unwrap().clone() else {
+        return Ok(None);
+    };
+    let py = obj.py();
+    let Some(converter) = converters.iter().rev().find(|c| obj.is_instance(c.py_type.bind(py)).unwrap_or(false)) else {
+        return Ok(None);
+    };
+    let depth = CONVERTER_DEPTH.with(Cell::get);
+    if depth >= MAX_CONVERTER_DEPTH {
+        return Err(PyTypeError::new_err(format!(
+            "Converters for {} nested more than {} levels deep (do the converters form a cycle?)",
+            converter.py_type.bind(py).name()?,
+            MAX_CONVERTER_DEPTH
+        )));
+    }
+    CONVERTER_DEPTH.with(|d| d.set(depth + 1));
+    let result = (|| {
+        let converted = converter.to_js.call1(py, (obj,))?.into_bound(py);
+        if converted.is_instance(converter.py_type.bind(py))? {
+            return Err(PyTypeError::new_err(format!(
+                "Converter for {} returned an instance of the same type",
+                converter.py_type.bind(py).name()?
+            )));
+        }
+        python_to_json(&converted)
+    })();
+    CONVERTER_DEPTH.with(|d| d.set(depth));
+    result.map(Some)
+}
 
 /// Python 对象转换为 JSON 值
 ///
@@ -14,9 +135,12 @@ use serde_json::Value as JsonValue;
 /// - str -> string
 /// - list -> array
 /// - dict -> object
+/// - register_converter() 注册的类型 -> 转换函数的返回值
 #[inline]
 pub fn python_to_json(obj: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
-    if obj.is_none() {
+    if let Some(value) = apply_converter(obj)? {
+        Ok(value)
+    } else if obj.is_none() {
         Ok(JsonValue::Null)
     } else if let Ok(b) = obj.extract::<bool>() {
         Ok(JsonValue::Bool(b))
@@ -44,7 +168,122 @@ pub fn python_to_json(obj: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
         }
         Ok(JsonValue::Object(map))
     } else {
-        Err(PyException::new_err("Unsupported Python type"))
+        Err(PyException::new_err(format!(
+            "Unsupported Python type: {} (use never_jscore.register_converter() to convert it)",
+            obj.get_type().name()?
+        )))
+    }
+}
+
+/// 求值结果中 Temporal 值的类型标记（context.rs 的 RESULT_TO_JSON 写入）
+const TEMPORAL_TAG: &str = "__never_jscore_temporal__";
+
+fn temporal_value<'a>(obj: &'a serde_json::Map<String, JsonValue>, key: &str) -> PyResult<&'a JsonValue> {
+    obj.get(key)
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid Temporal value: missing field '{}'", key)))
+}
+
+fn temporal_int(obj: &serde_json::Map<String, JsonValue>, key: &str) -> PyResult<i64> {
+    temporal_value(obj, key)?
+        .as_i64()
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid Temporal value: field '{}' is not an integer", key)))
+}
+
+fn temporal_float(obj: &serde_json::Map<String, JsonValue>, key: &str) -> PyResult<f64> {
+    temporal_value(obj, key)?
+        .as_f64()
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid Temporal value: field '{}' is not a number", key)))
+}
+
+fn temporal_str<'a>(obj: &'a serde_json::Map<String, JsonValue>, key: &str) -> PyResult<&'a str> {
+    temporal_value(obj, key)?
+        .as_str()
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid Temporal value: field '{}' is not a string", key)))
+}
+
+/// ZonedDateTime 的时区：IANA 名称使用 zoneinfo.ZoneInfo，偏移量（"+08:00"）或 zoneinfo 不可用时使用固定偏移
+fn temporal_tzinfo<'py>(py: Python<'py>, datetime: &Bound<'py, PyModule>, zone: &str, offset_ns: i64) -> PyResult<Bound<'py, PyAny>> {
+    if !zone.starts_with(['+', '-']) {
+        if let Ok(tz) = py.import("zoneinfo").and_then(|m| m.getattr("ZoneInfo")?.call1((zone,))) {
+            return Ok(tz);
+        }
+    }
+    let offset = datetime.getattr("timedelta")?.call1((0, offset_ns / 1_000_000_000, (offset_ns % 1_000_000_000) / 1000))?;
+    datetime.getattr("timezone")?.call1((offset,))
+}
+
+/// Temporal 值转换为 Python 对象（纳秒精度截断为微秒）
+///
+/// - Instant -> datetime（UTC）
+/// - ZonedDateTime -> datetime（zoneinfo.ZoneInfo 时区，重复的本地时间通过 fold 区分）
+/// - PlainDateTime -> datetime（naive）
+/// - PlainDate -> date
+/// - PlainTime -> time
+/// - Duration -> timedelta（包含年 / 月时无法换算，返回 ISO 字符串）
+fn temporal_to_python<'py>(py: Python<'py>, kind: &str, obj: &serde_json::Map<String, JsonValue>) -> PyResult<Bound<'py, PyAny>> {
+    let datetime = py.import("datetime")?;
+    let date_time = |tzinfo: Option<Bound<'py, PyAny>>| -> PyResult<Bound<'py, PyAny>> {
+        let args = (
+            temporal_int(obj, "year")?,
+            temporal_int(obj, "month")?,
+            temporal_int(obj, "day")?,
+            temporal_int(obj, "hour")?,
+            temporal_int(obj, "minute")?,
+            temporal_int(obj, "second")?,
+            temporal_int(obj, "microsecond")?,
+            tzinfo,
+        );
+        datetime.getattr("datetime")?.call1(args)
+    };
+
+    match kind {
+        "Instant" => {
+            let nanos: i128 = temporal_str(obj, "epochNanoseconds")?
+                .parse()
+                .map_err(|_| PyValueError::new_err("Invalid Temporal.Instant"))?;
+            let seconds = i64::try_from(nanos.div_euclid(1_000_000_000)).map_err(|_| PyValueError::new_err("Temporal.Instant out of range"))?;
+            let micros = (nanos.rem_euclid(1_000_000_000) / 1000) as i64;
+            let utc = datetime.getattr("timezone")?.getattr("utc")?;
+            let epoch = datetime.getattr("datetime")?.call1((1970, 1, 1, 0, 0, 0, 0, utc))?;
+            let delta = datetime.getattr("timedelta")?.call1((0, seconds, micros))?;
+            epoch.add(delta)
+        }
+        "ZonedDateTime" => {
+            let offset_ns = temporal_int(obj, "offsetNanoseconds")?;
+            let tzinfo = temporal_tzinfo(py, &datetime, temporal_str(obj, "timeZone")?, offset_ns)?;
+            let value = date_time(Some(tzinfo))?;
+            // 夏令时结束时同一本地时间出现两次，偏移量与 fold=0 不一致时取第二次
+            let offset = value.call_method0("utcoffset")?.call_method0("total_seconds")?.extract::<f64>()?;
+            if (offset * 1e9) as i64 != offset_ns {
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("fold", 1)?;
+                return value.call_method("replace", (), Some(&kwargs));
+            }
+            Ok(value)
+        }
+        "PlainDateTime" => date_time(None),
+        "PlainDate" => datetime.getattr("date")?.call1((
+            temporal_int(obj, "year")?,
+            temporal_int(obj, "month")?,
+            temporal_int(obj, "day")?,
+        )),
+        "PlainTime" => datetime.getattr("time")?.call1((
+            temporal_int(obj, "hour")?,
+            temporal_int(obj, "minute")?,
+            temporal_int(obj, "second")?,
+            temporal_int(obj, "microsecond")?,
+        )),
+        "Duration" => {
+            if temporal_float(obj, "years")? != 0.0 || temporal_float(obj, "months")? != 0.0 {
+                return temporal_str(obj, "iso")?.into_bound_py_any(py);
+            }
+            let kwargs = PyDict::new(py);
+            for key in ["weeks", "days", "hours", "minutes", "seconds", "milliseconds", "microseconds"] {
+                kwargs.set_item(key, temporal_float(obj, key)?)?;
+            }
+            datetime.getattr("timedelta")?.call((), Some(&kwargs))
+        }
+        _ => Err(PyValueError::new_err(format!("Unsupported Temporal type: {}", kind))),
     }
 }
 
@@ -57,6 +296,8 @@ pub fn python_to_json(obj: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
 /// - string -> str
 /// - array -> list
 /// - object -> dict
+/// - Temporal 值（带 TEMPORAL_TAG 标记的对象）-> datetime / date / time / timedelta
+/// - undefined（undefined_as="sentinel" 时带 UNDEFINED_TAG 标记的对象）-> never_jscore.UNDEFINED
 #[inline]
 pub fn json_to_python<'py>(py: Python<'py>, value: &JsonValue) -> PyResult<Bound<'py, PyAny>> {
     match value {
@@ -80,6 +321,12 @@ pub fn json_to_python<'py>(py: Python<'py>, value: &JsonValue) -> PyResult<Bound
             Ok(list.into_any())
         }
         JsonValue::Object(obj) => {
+            if let Some(JsonValue::String(kind)) = obj.get(TEMPORAL_TAG) {
+                return temporal_to_python(py, kind, obj);
+            }
+            if obj.len() == 1 && obj.contains_key(UNDEFINED_TAG) {
+                return Ok(crate::undefined_policy::undefined(py)?.into_any());
+            }
             let dict = PyDict::new(py);
             for (k, v) in obj {
                 dict.set_item(k, json_to_python(py, v)?)?;
@@ -88,3 +335,139 @@ pub fn json_to_python<'py>(py: Python<'py>, value: &JsonValue) -> PyResult<Bound
         }
     }
 }
+
+/// 解析 JS 侧存储的 JSON 结果字符串并转换为 Python 对象
+pub fn json_str_to_python<'py>(py: Python<'py>, json: &str) -> PyResult<Bound<'py, PyAny>> {
+    let value: JsonValue = serde_json::from_str(json)
+        .map_err(|e| PyException::new_err(format!("JSON parse error: {}", e)))?;
+    json_to_python(py, &value)
+}
+
+/// 解析 JSON 结果；binary 为 true 时把其中的字符串作为二进制数据转换为 bytes（evaluate() / call() 的 binary=True）
+///
+/// JS 中的二进制字符串（latin1 密文、String.fromCharCode 的结果）每个字符是一个字节（0-255），
+/// 按 UTF-8 编码返回会把 0x80 以上的字节变成两个字节。这里按字符取字节值；
+/// 数组和对象中的字符串同样转换（对象的键保持 str），其他类型不变。
+pub fn result_json_to_python<'py>(py: Python<'py>, json: &str, binary: bool) -> PyResult<Bound<'py, PyAny>> {
+    if !binary {
+        return json_str_to_python(py, json);
+    }
+    let value: JsonValue = serde_json::from_str(json)
+        .map_err(|e| PyException::new_err(format!("JSON parse error: {}", e)))?;
+    binary_json_to_python(py, &value)
+}
+
+/// evaluate() / call() 的 result_format：JS 返回的 JSON 结果如何交给 Python
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResultFormat {
+    /// 在 Rust 中解析并转换为 Python 对象（默认，支持 binary=True 和 Temporal 值）
+    #[default]
+    Python,
+    /// 不解析，直接返回 JSON 字符串（结果原样转发给其他服务时）
+    JsonStr,
+    /// 交给 orjson.loads 解析（需要安装 orjson）
+    Orjson,
+}
+
+impl ResultFormat {
+    /// 解析 result_format 参数；binary=True 和 stream_to 只能与默认的 "python" 一起使用
+    pub fn from_py(value: Option<&str>, binary: bool, streaming: bool) -> PyResult<Self> {
+        let format = match value {
+            None | Some("python") => return Ok(ResultFormat::Python),
+            Some("json_str") => ResultFormat::JsonStr,
+            Some("orjson") => ResultFormat::Orjson,
+            Some(other) => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid result_format '{}': expected 'python', 'json_str' or 'orjson'",
+                    other
+                )))
+            }
+        };
+        if binary {
+            return Err(PyValueError::new_err(format!(
+                "binary=True cannot be combined with result_format='{}'",
+                format.name()
+            )));
+        }
+        if streaming {
+            return Err(PyValueError::new_err(format!(
+                "stream_to cannot be combined with result_format='{}'",
+                format.name()
+            )));
+        }
+        Ok(format)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ResultFormat::Python => "python",
+            ResultFormat::JsonStr => "json_str",
+            ResultFormat::Orjson => "orjson",
+        }
+    }
+
+    /// 按格式转换 JSON 结果
+    pub fn to_python<'py>(self, py: Python<'py>, json: &str, binary: bool) -> PyResult<Bound<'py, PyAny>> {
+        match self {
+            ResultFormat::Python => result_json_to_python(py, json, binary),
+            ResultFormat::JsonStr => json.into_bound_py_any(py),
+            ResultFormat::Orjson => {
+                let orjson = py.import("orjson").map_err(|e| {
+                    pyo3::exceptions::PyImportError::new_err(format!(
+                        "result_format='orjson' requires the orjson package (pip install orjson): {}",
+                        e
+                    ))
+                })?;
+                orjson.call_method1("loads", (PyBytes::new(py, json.as_bytes()),))
+            }
+        }
+    }
+}
+
+fn binary_json_to_python<'py>(py: Python<'py>, value: &JsonValue) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        JsonValue::String(s) => {
+            let mut bytes = Vec::with_capacity(s.len());
+            for (index, c) in s.chars().enumerate() {
+                let byte = u8::try_from(u32::from(c)).map_err(|_| {
+                    PyValueError::new_err(format!(
+                        "binary=True: character U+{:04X} at index {} is not a byte (0-255); \
+                         the result must be a binary string such as String.fromCharCode(...) output",
+                        u32::from(c),
+                        index
+                    ))
+                })?;
+                bytes.push(byte);
+            }
+            Ok(PyBytes::new(py, &bytes).into_any())
+        }
+        JsonValue::Array(arr) => {
+            let items = arr.iter().map(|item| binary_json_to_python(py, item)).collect::<PyResult<Vec<_>>>()?;
+            Ok(PyList::new(py, items)?.into_any())
+        }
+        JsonValue::Object(obj) if !obj.contains_key(TEMPORAL_TAG) && !obj.contains_key(UNDEFINED_TAG) => {
+            let dict = PyDict::new(py);
+            for (k, v) in obj {
+                dict.set_item(k, binary_json_to_python(py, v)?)?;
+            }
+            Ok(dict.into_any())
+        }
+        _ => json_to_python(py, value),
+    }
+}
+
+/// 将 call() 的参数转换为 JSON 参数列表
+///
+/// list 会展开为多个参数，其他值作为单个参数。
+pub fn call_args_to_json(args: &Bound<'_, PyAny>) -> PyResult<Vec<JsonValue>> {
+    if args.is_instance_of::<PyList>() {
+        let list = args.downcast::<PyList>()?;
+        let mut vec_args = Vec::with_capacity(list.len());
+        for item in list.iter() {
+            vec_args.push(python_to_json(&item)?);
+        }
+        Ok(vec_args)
+    } else {
+        Ok(vec![python_to_json(args)?])
+    }
+}
@@ -1,28 +1,122 @@
 // js_polyfill.js - JavaScript 封装层，提供 Web API 兼容的接口
 // 自动注入到 never_jscore 运行时环境
 
+// 整个 polyfill 在一个闭包中执行：内部的函数和变量（包括 Deno 引用）不会成为全局绑定，
+// 对外的 API 都显式挂到 globalThis 上
+(function() {
+
 // ============================================
 // Browser Environment Protection (最优先加载)
 // ============================================
 
-// 保存原始 Deno 对象供内部使用
+// 保存原始 Deno 对象供内部使用（只在闭包内可见，用户代码无法访问）
 const __internalDeno = (typeof Deno !== 'undefined' && Deno !== null && Deno !== undefined) ? globalThis.Deno : null;
 
 // 隐藏 Deno 特征
+// Context(fs_roots=...) 时全局 Deno 只提供限定目录的文件 API，Context(env=...) 时只提供 Deno.env
+// （见 __getSandboxDeno），否则为 undefined
+//
+// 快照中必须保留全局 Deno（deno_core 加载快照时要从 Deno.core 取回调），
+// 因此这里只登记隐藏函数，由 Context 在用户代码执行之前调用（见 context.rs 的 HIDE_DENO）
 if (__internalDeno) {
-    delete globalThis.Deno;
-    Object.defineProperty(globalThis, 'Deno', {
-        value: undefined,
+    Object.defineProperty(globalThis, '__never_jscore_hide_deno__', {
+        value: function() {
+            delete globalThis.Deno;
+            Object.defineProperty(globalThis, 'Deno', {
+                get() {
+                    const ops = __internalDeno.core.ops;
+                    return ops.op_sandbox_fs_enabled() || ops.op_env_injected() ? __getSandboxDeno() : undefined;
+                },
+                enumerable: false,
+                configurable: false
+            });
+        },
         writable: false,
         enumerable: false,
-        configurable: false
+        configurable: true
     });
 }
 
 // 安全访问内部 Deno 对象
-globalThis.__getDeno = function() {
+// Context(audit_ops=True) 时返回记录每次 op 调用的代理（见 audit.rs）
+function __getDeno() {
+    if (__internalDeno && __internalDeno.core.ops.op_audit_enabled()) {
+        return __getAuditedDeno();
+    }
     return __internalDeno;
-};
+}
+
+// ============================================
+// Op audit log (Context(audit_ops=True))
+// ============================================
+
+// 参数记录的最大长度
+const __AUDIT_ARG_LIMIT__ = 200;
+let __auditedDeno = null;
+
+function __auditArg(value) {
+    let text;
+    if (typeof value === 'string') {
+        text = value;
+    } else {
+        try {
+            text = JSON.stringify(value);
+        } catch (e) {
+            text = undefined;
+        }
+        if (text === undefined) {
+            text = String(value);
+        }
+    }
+    return text.length > __AUDIT_ARG_LIMIT__ ? text.slice(0, __AUDIT_ARG_LIMIT__) + '...' : text;
+}
+
+function __auditStatus(result) {
+    return typeof result === 'string' && result.startsWith('PermissionDenied: ') ? 'denied' : 'ok';
+}
+
+// 包装 Deno.core.ops：调用前后通知 Rust 记录，异步 op 在 Promise 完成时记录
+function __getAuditedDeno() {
+    if (__auditedDeno) {
+        return __auditedDeno;
+    }
+    const ops = __internalDeno.core.ops;
+    const wrapped = new Map();
+    const auditedOps = new Proxy(ops, {
+        get(target, name) {
+            const op = target[name];
+            if (typeof op !== 'function' || typeof name !== 'string') {
+                return op;
+            }
+            let fn = wrapped.get(name);
+            if (!fn) {
+                fn = function(...args) {
+                    const id = ops.op_audit_begin(name, JSON.stringify(args.map(__auditArg)));
+                    let result;
+                    try {
+                        result = op.apply(this, args);
+                    } catch (e) {
+                        ops.op_audit_end(id, 'error');
+                        throw e;
+                    }
+                    if (result !== null && typeof result === 'object' && typeof result.then === 'function') {
+                        result.then(
+                            value => ops.op_audit_end(id, __auditStatus(value)),
+                            () => ops.op_audit_end(id, 'error')
+                        );
+                    } else {
+                        ops.op_audit_end(id, __auditStatus(result));
+                    }
+                    return result;
+                };
+                wrapped.set(name, fn);
+            }
+            return fn;
+        }
+    });
+    __auditedDeno = { core: { ops: auditedOps }, build: __internalDeno.build };
+    return __auditedDeno;
+}
 
 // ============================================
 // Logging utility (必须在最前面定义)
@@ -40,6 +134,22 @@ function log(...args) {
 
 log('never-jscore polyfill loading...');
 
+// ============================================
+// Permissions (Context(permissions=...))
+// ============================================
+
+// 被权限拒绝的 op 返回 "PermissionDenied: ..."，转换为 PermissionDenied 错误
+const __PERMISSION_DENIED__ = 'PermissionDenied: ';
+
+function __checkPermission(result) {
+    if (typeof result === 'string' && result.startsWith(__PERMISSION_DENIED__)) {
+        const error = new Error(result.slice(__PERMISSION_DENIED__.length));
+        error.name = 'PermissionDenied';
+        throw error;
+    }
+    return result;
+}
+
 // ============================================
 // Random Number Generation (Support seeded RNG)
 // ============================================
@@ -67,24 +177,97 @@ globalThis.cryptoRandom = function() {
 // Base64 操作 (兼容 Web API)
 // ============================================
 
+function __invalidCharacterError(message) {
+    const error = new Error(message);
+    error.name = 'InvalidCharacterError';
+    return error;
+}
+
 /**
- * Base64 编码（兼容浏览器 btoa）
+ * Base64 编码（与浏览器 btoa 相同：按 Latin-1 编码，字符超出 U+00FF 时抛出 InvalidCharacterError）
  * @param {string} str - 要编码的字符串
  * @returns {string} Base64 编码结果
  */
 function btoa(str) {
-    return __getDeno().core.ops.op_base64_encode(String(str));
+    const encoded = __getDeno().core.ops.op_codec_btoa(String(str));
+    if (encoded === null) {
+        throw __invalidCharacterError("Failed to execute 'btoa': The string to be encoded contains characters outside of the Latin1 range.");
+    }
+    return encoded;
 }
 
 /**
- * Base64 解码（兼容浏览器 atob）
+ * Base64 解码（与浏览器 atob 相同：忽略空白、补齐可选，无效输入抛出 InvalidCharacterError）
  * @param {string} str - Base64 编码的字符串
- * @returns {string} 解码结果
+ * @returns {string} 解码结果（Latin-1 字符串，charCode 为字节值）
  */
 function atob(str) {
-    return __getDeno().core.ops.op_base64_decode(String(str));
+    const decoded = __getDeno().core.ops.op_codec_atob(String(str));
+    if (decoded === null) {
+        throw __invalidCharacterError("Failed to execute 'atob': The string to be decoded is not correctly encoded.");
+    }
+    return decoded;
 }
 
+/**
+ * 二进制编解码（Rust 实现，处理几 MB 的数据时比纯 JS 快得多）
+ *
+ *   __codecs.b64encode(data, { urlSafe = false, pad = !urlSafe })  字节 -> Base64 字符串
+ *   __codecs.b64decode(str)                                         Base64（标准 / URL-safe，补齐可选）-> Uint8Array
+ *   __codecs.hexEncode(data)                                        字节 -> 小写 hex
+ *   __codecs.hexDecode(str)                                         hex -> Uint8Array
+ *
+ * data 可以是 Uint8Array / 其他 TypedArray / DataView / ArrayBuffer / 字节数组，字符串按 UTF-8 编码。
+ */
+function __codecBytes(data) {
+    if (data instanceof Uint8Array) {
+        return data;
+    }
+    if (ArrayBuffer.isView(data)) {
+        return new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+    }
+    if (data instanceof ArrayBuffer || (typeof SharedArrayBuffer !== 'undefined' && data instanceof SharedArrayBuffer)) {
+        return new Uint8Array(data);
+    }
+    if (typeof data === 'string') {
+        return new TextEncoder().encode(data);
+    }
+    if (Array.isArray(data)) {
+        return Uint8Array.from(data);
+    }
+    throw new TypeError('__codecs: expected bytes (Uint8Array, ArrayBuffer, TypedArray, array) or string, got ' + typeof data);
+}
+
+function __codecDecode(op, str) {
+    try {
+        return op(String(str));
+    } catch (e) {
+        throw __invalidCharacterError(e.message);
+    }
+}
+
+Object.defineProperty(globalThis, '__codecs', {
+    value: Object.freeze({
+        b64encode(data, options = {}) {
+            const urlSafe = Boolean(options.urlSafe);
+            const pad = options.pad === undefined ? !urlSafe : Boolean(options.pad);
+            return __getDeno().core.ops.op_codec_b64_encode(__codecBytes(data), urlSafe, pad);
+        },
+        b64decode(str) {
+            return __codecDecode(__getDeno().core.ops.op_codec_b64_decode, str);
+        },
+        hexEncode(data) {
+            return __getDeno().core.ops.op_codec_hex_encode(__codecBytes(data));
+        },
+        hexDecode(str) {
+            return __codecDecode(__getDeno().core.ops.op_codec_hex_decode, str);
+        },
+    }),
+    writable: false,
+    enumerable: false,
+    configurable: true,
+});
+
 // ============================================
 // 哈希函数
 // ============================================
@@ -464,9 +647,9 @@ log('Early return API loaded: __neverjscore_return__, $return, $exit');
  * @param {any} data - 要保存的数据 (会被 JSON.stringify)
  */
 globalThis.__saveAndTerminate__ = function(data) {
-    if (typeof __getDeno !== 'undefined' && __getDeno().core && __getDeno().core.ops) {
+    if (__internalDeno && __getDeno().core && __getDeno().core.ops) {
         try {
-            // 1. 先保存数据到 Rust 全局存储
+            // 1. 先保存数据到当前 Context 的 Rust 侧存储
             const jsonData = JSON.stringify(data);
             __getDeno().core.ops.op_save_hook_data(jsonData);
 
@@ -627,67 +810,33 @@ if (typeof setTimeout === 'undefined') {
     log('Real async timers loaded: setTimeout, setInterval (Rust-backed)');
 }
 
-// ============================================
-// Worker API polyfill (fake single-threaded)
-// ============================================
-
 /**
- * Fake Worker - single-threaded, for API detection
+ * 单调时钟与非忙等的等待（Rust ops: op_now_ns / op_sleep）
+ *
+ * - __neverjscore_now_ns__() / $nowNs(): 单调时钟的纳秒数（BigInt），不受系统时间调整影响
+ * - __neverjscore_sleep__(ms) / $sleep(ms): 等待 ms 毫秒，返回 Promise<boolean>；
+ *   执行被终止（timeout_ms 等）时以 false 完成，循环中等待时应检查返回值
+ *
+ * @example
+ * const start = $nowNs();
+ * while (!ready()) {
+ *     if (!await $sleep(10)) break;
+ * }
+ * const elapsedMs = Number($nowNs() - start) / 1e6;
  */
-if (typeof Worker === 'undefined') {
-    class Worker {
-        constructor(scriptURL, options = {}) {
-            this._id = Math.floor(Math.random() * 1000000);
-            this._scriptURL = scriptURL;
-            this._terminated = false;
-            this.onmessage = null;
-            this.onerror = null;
-        }
-
-        postMessage(message) {
-            if (this._terminated) {
-                throw new Error('Worker has been terminated');
-            }
-
-            // Fake processing - immediately trigger onmessage if set
-            if (this.onmessage) {
-                setTimeout(() => {
-                    if (!this._terminated && this.onmessage) {
-                        const event = {
-                            type: 'message',
-                            data: message,
-                            origin: '',
-                        };
-                        this.onmessage(event);
-                    }
-                }, 0);
-            }
-        }
-
-        terminate() {
-            this._terminated = true;
-        }
+globalThis.__neverjscore_now_ns__ = function() {
+    return __getDeno().core.ops.op_now_ns();
+};
 
-        addEventListener(type, listener) {
-            if (type === 'message') {
-                this.onmessage = listener;
-            } else if (type === 'error') {
-                this.onerror = listener;
-            }
-        }
+globalThis.__neverjscore_sleep__ = function(ms = 0) {
+    return __getDeno().core.ops.op_sleep(Number(ms));
+};
 
-        removeEventListener(type, listener) {
-            if (type === 'message' && this.onmessage === listener) {
-                this.onmessage = null;
-            } else if (type === 'error' && this.onerror === listener) {
-                this.onerror = null;
-            }
-        }
-    }
+// 简短别名
+globalThis.$nowNs = globalThis.__neverjscore_now_ns__;
+globalThis.$sleep = globalThis.__neverjscore_sleep__;
 
-    globalThis.Worker = Worker;
-    log('Worker API loaded (single-threaded mock)');
-}
+log('Monotonic clock API loaded: __neverjscore_now_ns__, __neverjscore_sleep__, $nowNs, $sleep');
 
 // ============================================
 // Node.js Compatibility APIs
@@ -724,14 +873,26 @@ if (typeof setImmediate === 'undefined') {
 // ============================================
 
 if (typeof process === 'undefined' || !process.version) {
-    // 读取所有环境变量
-    let envVars = {};
-    try {
-        if (__internalDeno && __getDeno().core.ops.op_getenv_all) {
-            envVars = JSON.parse(__getDeno().core.ops.op_getenv_all());
+    // 环境变量在第一次访问 process.env 时读取（而不是加载 polyfill 时），
+    // 避免被固化进快照，并且受 Context 的 env 权限控制
+    let envVars = null;
+    function readEnv() {
+        if (envVars === null) {
+            envVars = {};
+            if (__internalDeno && __getDeno().core.ops.op_getenv_all) {
+                const result = __getDeno().core.ops.op_getenv_all();
+                try {
+                    envVars = JSON.parse(__checkPermission(result));
+                } catch (e) {
+                    if (e.name === 'PermissionDenied') {
+                        envVars = null;
+                        throw e;
+                    }
+                    log('Failed to read environment variables:', e);
+                }
+            }
         }
-    } catch (e) {
-        log('Failed to read environment variables:', e);
+        return envVars;
     }
 
     globalThis.process = {
@@ -748,7 +909,8 @@ if (typeof process === 'undefined' || !process.version) {
             ? __internalDeno.build.os
             : 'win32',
         arch: 'x64',
-        env: envVars,  // 使用真实的环境变量
+        get env() { return readEnv(); },  // 使用真实的环境变量
+        set env(value) { envVars = value; },
         argv: ['node', 'script.js'],
         execPath: '/usr/bin/node',
         execArgv: [],
@@ -895,18 +1057,11 @@ if (typeof Buffer === 'undefined') {
                 }
                 return bytes;
             } else if (encoding === 'hex') {
-                const bytes = [];
-                for (let i = 0; i < str.length; i += 2) {
-                    bytes.push(parseInt(str.substr(i, 2), 16));
-                }
-                return bytes;
-            } else if (encoding === 'base64') {
-                const decoded = atob(str);
-                const bytes = [];
-                for (let i = 0; i < decoded.length; i++) {
-                    bytes.push(decoded.charCodeAt(i));
-                }
-                return bytes;
+                // 与 Node.js 相同：解码到第一个无效字符为止
+                return __codecs.hexDecode(/^(?:[0-9a-fA-F]{2})*/.exec(str)[0]);
+            } else if (encoding === 'base64' || encoding === 'base64url') {
+                // 与 Node.js 相同：两种字母表都接受，忽略无效字符
+                return __codecs.b64decode(str.replace(/[^A-Za-z0-9+/\-_]/g, ''));
             } else if (encoding === 'latin1' || encoding === 'binary') {
                 const bytes = [];
                 for (let i = 0; i < str.length; i++) {
@@ -958,11 +1113,11 @@ if (typeof Buffer === 'undefined') {
                 }
                 return result;
             } else if (encoding === 'hex') {
-                return Array.from(slice)
-                    .map(b => b.toString(16).padStart(2, '0'))
-                    .join('');
+                return __codecs.hexEncode(slice);
             } else if (encoding === 'base64') {
-                return btoa(String.fromCharCode(...slice));
+                return __codecs.b64encode(slice);
+            } else if (encoding === 'base64url') {
+                return __codecs.b64encode(slice, { urlSafe: true });
             } else if (encoding === 'latin1' || encoding === 'binary') {
                 return String.fromCharCode(...slice);
             } else if (encoding === 'ascii') {
@@ -1214,6 +1369,15 @@ class Response {
             }
         }
 
+        // new Response(bytes) 构造的二进制 body
+        if (this._body instanceof ArrayBuffer) {
+            return Promise.resolve(this._body.slice(0));
+        }
+        if (ArrayBuffer.isView(this._body)) {
+            const view = this._body;
+            return Promise.resolve(view.buffer.slice(view.byteOffset, view.byteOffset + view.byteLength));
+        }
+
         // 否则将文本编码为 ArrayBuffer
         const encoder = new TextEncoder();
         return Promise.resolve(encoder.encode(this._body).buffer);
@@ -1328,6 +1492,7 @@ if (typeof fetch === 'undefined') {
 
         // 检查错误
         if (responseData.error) {
+            __checkPermission(responseData.error);
             throw new Error('Fetch failed: ' + responseData.error);
         }
 
@@ -1347,6 +1512,48 @@ if (typeof fetch === 'undefined') {
     globalThis.Headers = Headers;
 }
 
+// ============================================
+// WebAssembly streaming API
+// ============================================
+
+// V8 只在嵌入方注册了流式编译回调时才提供 compileStreaming / instantiateStreaming，
+// 这里基于 fetch 实现：等待 Response 读取完整 body 后交给 WebAssembly.compile / instantiate，
+// 浏览器脚本中的 WebAssembly.instantiateStreaming(fetch(url), imports) 可以直接运行。
+// 除 Response 外也接受 ArrayBuffer / TypedArray（及它们的 Promise）；不检查 Content-Type
+if (typeof WebAssembly !== 'undefined' && typeof WebAssembly.compileStreaming !== 'function') {
+    const wasmSourceBytes = async function(source, method) {
+        source = await source;
+        if (source instanceof ArrayBuffer || ArrayBuffer.isView(source)) {
+            return source;
+        }
+        if (source === null || typeof source !== 'object' || typeof source.arrayBuffer !== 'function') {
+            throw new TypeError(`WebAssembly.${method}(): Argument 0 must be provided and must be a Response`);
+        }
+        if (source.ok === false) {
+            throw new TypeError(`WebAssembly.${method}(): HTTP status code is not ok (${source.status})`);
+        }
+        return source.arrayBuffer();
+    };
+
+    Object.defineProperty(WebAssembly, 'compileStreaming', {
+        value: async function compileStreaming(source) {
+            return WebAssembly.compile(await wasmSourceBytes(source, 'compileStreaming'));
+        },
+        writable: true,
+        enumerable: false,
+        configurable: true
+    });
+
+    Object.defineProperty(WebAssembly, 'instantiateStreaming', {
+        value: async function instantiateStreaming(source, importObject) {
+            return WebAssembly.instantiate(await wasmSourceBytes(source, 'instantiateStreaming'), importObject);
+        },
+        writable: true,
+        enumerable: false,
+        configurable: true
+    });
+}
+
 // ============================================
 // File System API for require()
 // ============================================
@@ -1356,7 +1563,7 @@ if (typeof fetch === 'undefined') {
  */
 const fs = {
     readFileSync: function(path, encoding = 'utf8') {
-        const content = __getDeno().core.ops.op_read_file_sync(path);
+        const content = __checkPermission(__getDeno().core.ops.op_read_file_sync(path));
         if (content.startsWith('Error:')) {
             throw new Error(content);
         }
@@ -1364,7 +1571,7 @@ const fs = {
     },
 
     writeFileSync: function(path, content) {
-        const result = __getDeno().core.ops.op_write_file_sync(path, String(content));
+        const result = __checkPermission(__getDeno().core.ops.op_write_file_sync(path, String(content)));
         if (result !== 'OK') {
             throw new Error(result);
         }
@@ -1382,7 +1589,7 @@ const fs = {
     },
 
     readdirSync: function(path) {
-        const result = __getDeno().core.ops.op_readdir(path);
+        const result = __checkPermission(__getDeno().core.ops.op_readdir(path));
         if (result.startsWith('Error:')) {
             throw new Error(result);
         }
@@ -1390,6 +1597,121 @@ const fs = {
     }
 };
 
+// ============================================
+// Sandboxed file API (Context(fs_roots=...)) / Deno.env (Context(env=...))
+// ============================================
+
+// Deno 风格的文件 API，只能访问 fs_roots 列出的目录（见 sandbox_fs.rs）；
+// 以及 Deno.env，只读取 Context(env=...) 注入的变量（见 fs_ops.rs）
+let __sandboxDeno = null;
+
+function __sandboxResult(result) {
+    __checkPermission(result);
+    if (result.startsWith('Error:')) {
+        throw new Error(result);
+    }
+    return result;
+}
+
+function __sandboxPath(path) {
+    return path instanceof URL ? decodeURIComponent(path.pathname) : String(path);
+}
+
+// 二进制内容以 Base64 传给 ops（不经过 atob / btoa，它们按字符串处理且不适合大文件）
+const __BASE64_CHARS__ = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/';
+
+function __base64ToBytes(base64) {
+    const lookup = new Uint8Array(128);
+    for (let i = 0; i < __BASE64_CHARS__.length; i++) {
+        lookup[__BASE64_CHARS__.charCodeAt(i)] = i;
+    }
+    const padding = base64.endsWith('==') ? 2 : base64.endsWith('=') ? 1 : 0;
+    const bytes = new Uint8Array(base64.length / 4 * 3 - padding);
+    let j = 0;
+    for (let i = 0; i < base64.length; i += 4) {
+        const n = (lookup[base64.charCodeAt(i)] << 18) | (lookup[base64.charCodeAt(i + 1)] << 12) |
+            (lookup[base64.charCodeAt(i + 2)] << 6) | lookup[base64.charCodeAt(i + 3)];
+        bytes[j++] = n >> 16;
+        if (j < bytes.length) bytes[j++] = (n >> 8) & 0xFF;
+        if (j < bytes.length) bytes[j++] = n & 0xFF;
+    }
+    return bytes;
+}
+
+function __bytesToBase64(bytes) {
+    const parts = [];
+    for (let i = 0; i < bytes.length; i += 3) {
+        const n = (bytes[i] << 16) | ((bytes[i + 1] || 0) << 8) | (bytes[i + 2] || 0);
+        parts.push(
+            __BASE64_CHARS__[n >> 18] + __BASE64_CHARS__[(n >> 12) & 63] +
+            (i + 1 < bytes.length ? __BASE64_CHARS__[(n >> 6) & 63] : '=') +
+            (i + 2 < bytes.length ? __BASE64_CHARS__[n & 63] : '=')
+        );
+    }
+    return parts.join('');
+}
+
+function __sandboxFileInfo(info) {
+    return Object.assign(info, { mtime: info.mtime === null ? null : new Date(info.mtime) });
+}
+
+function __getSandboxDeno() {
+    if (__sandboxDeno) {
+        return __sandboxDeno;
+    }
+
+    const readTextFileSync = path => JSON.parse(__sandboxResult(__getDeno().core.ops.op_sandbox_read_text(__sandboxPath(path))));
+    const readFileSync = path => __base64ToBytes(__sandboxResult(__getDeno().core.ops.op_sandbox_read_bytes(__sandboxPath(path))));
+    const writeTextFileSync = (path, data) => {
+        __sandboxResult(__getDeno().core.ops.op_sandbox_write(__sandboxPath(path), String(data), false));
+    };
+    const writeFileSync = (path, data) => {
+        const base64 = __bytesToBase64(new Uint8Array(data.buffer, data.byteOffset, data.byteLength));
+        __sandboxResult(__getDeno().core.ops.op_sandbox_write(__sandboxPath(path), base64, true));
+    };
+    const statSync = path => __sandboxFileInfo(JSON.parse(__sandboxResult(__getDeno().core.ops.op_sandbox_stat(__sandboxPath(path)))));
+    const readDirSync = path => JSON.parse(__sandboxResult(__getDeno().core.ops.op_sandbox_read_dir(__sandboxPath(path))));
+
+    // Context(env=...) 注入的环境变量（只读），与 Deno 一样逐个变量检查 env 权限
+    const env = Object.freeze({
+        get(key) {
+            const value = JSON.parse(__checkPermission(__getDeno().core.ops.op_getenv_json(String(key))));
+            return value === null ? undefined : value;
+        },
+        has(key) {
+            return env.get(key) !== undefined;
+        },
+        toObject() {
+            return JSON.parse(__checkPermission(__getDeno().core.ops.op_getenv_all()));
+        },
+    });
+
+    // 异步版本与 Deno 的签名一致（内部同步执行）
+    const files = {
+        readTextFile: async path => readTextFileSync(path),
+        readTextFileSync,
+        readFile: async path => readFileSync(path),
+        readFileSync,
+        writeTextFile: async (path, data) => writeTextFileSync(path, data),
+        writeTextFileSync,
+        writeFile: async (path, data) => writeFileSync(path, data),
+        writeFileSync,
+        stat: async path => statSync(path),
+        statSync,
+        readDir: async function* (path) { yield* readDirSync(path); },
+        readDirSync,
+    };
+
+    // 只提供 Context 选项启用的部分
+    const ops = __getDeno().core.ops;
+    __sandboxDeno = Object.freeze(Object.assign(
+        {},
+        ops.op_sandbox_fs_enabled() ? files : {},
+        ops.op_env_injected() ? { env } : {}
+    ));
+    return __sandboxDeno;
+}
+
 /**
  * 简化的 path 模块（Node.js风格）
  */
@@ -1637,9 +1959,14 @@ function createRequire(baseDir) {
             currentDir = parentDir;
         }
 
-        // 添加 NODE_PATH 环境变量指定的路径
-        if (typeof process !== 'undefined' && process.env && process.env.NODE_PATH) {
-            const nodePath = process.env.NODE_PATH;
+        // 添加 NODE_PATH 环境变量指定的路径（没有 env 权限时忽略）
+        let nodePath = null;
+        try {
+            nodePath = typeof process !== 'undefined' && process.env ? process.env.NODE_PATH : null;
+        } catch (e) {
+            log('NODE_PATH is not readable:', e);
+        }
+        if (nodePath) {
             // 根据平台选择分隔符 (Windows: ';', Unix: ':')
             const delimiter = process.platform === 'win32' ? ';' : ':';
             const nodePathDirs = nodePath.split(delimiter)
@@ -2123,17 +2450,241 @@ if (typeof screen === 'undefined') {
     globalThis.screen = createConfigurableObject(screenData);
 }
 
-// Enhanced Console (if needed)
-if (typeof console.table === 'undefined') {
-    console.table = function(data) {
-        console.log(JSON.stringify(data, null, 2));
-    };
-}
-
 // Mark browser environment loaded
 globalThis.__NEVER_JSCORE_BROWSER_ENV_LOADED__ = true;
 
 
+// ============================================
+// Console: table / group / time / count / assert 等
+// ============================================
+// deno_core 的 console 只实现了 log / debug / warn / error（通过 op_print 输出到 stdout），
+// 其余方法来自 V8，只发给 inspector，调用后没有任何输出。
+// 这里补全 console 命名空间：输出格式与 Node.js 接近，全部经 deno_core 的 console.log 输出
+// （因此同样计入 quotas 的 max_console_bytes）。
+
+(function() {
+    const write = console.log;
+    const counts = new Map();
+    const timers = new Map();
+    let indent = '';
+
+    const now = () => (typeof performance !== 'undefined' ? performance.now() : Date.now());
+
+    // 把任意值转换为字符串（对象为 JSON，循环引用显示为 [Circular]）
+    function inspect(value, compact) {
+        switch (typeof value) {
+            case 'string':
+                return compact ? `'${value}'` : value;
+            case 'bigint':
+                return `${value}n`;
+            case 'symbol':
+                return value.toString();
+            case 'function':
+                return `[Function: ${value.name || '(anonymous)'}]`;
+            case 'object':
+                break;
+            default:
+                return String(value);
+        }
+        if (value === null) {
+            return 'null';
+        }
+        if (value instanceof Error) {
+            return value.stack || String(value);
+        }
+        if (ArrayBuffer.isView(value) && !(value instanceof DataView)) {
+            const items = Array.prototype.slice.call(value, 0, 10).join(', ');
+            return `${value.constructor.name}(${value.length}) [${items}${value.length > 10 ? ', ...' : ''}]`;
+        }
+        if (value instanceof Map || value instanceof Set) {
+            const items = Array.from(value, (item) => (value instanceof Map
+                ? `${inspect(item[0], true)} => ${inspect(item[1], true)}`
+                : inspect(item, true)));
+            return `${value.constructor.name}(${value.size}) { ${items.join(', ')} }`;
+        }
+        const seen = new WeakSet();
+        try {
+            const json = JSON.stringify(value, function(_key, item) {
+                if (typeof item === 'bigint') return `${item}n`;
+                if (typeof item === 'function') return `[Function: ${item.name || '(anonymous)'}]`;
+                if (typeof item === 'symbol') return String(item);
+                if (typeof item === 'object' && item !== null) {
+                    if (seen.has(item)) return '[Circular]';
+                    seen.add(item);
+                }
+                return item;
+            }, compact ? undefined : 2);
+            return json === undefined ? Object.prototype.toString.call(value) : json;
+        } catch (e) {
+            return Object.prototype.toString.call(value);
+        }
+    }
+
+    // 格式化参数，支持 %s %d %i %f %o %O %j %c %% 占位符
+    function format(args) {
+        let rest = args;
+        let head = '';
+        if (typeof args[0] === 'string' && args.length > 1) {
+            let index = 1;
+            head = args[0].replace(/%[sdifjoOc%]/g, (spec) => {
+                if (spec === '%%') return '%';
+                if (index >= args.length) return spec;
+                const arg = args[index++];
+                switch (spec) {
+                    case '%s': return typeof arg === 'string' ? arg : inspect(arg, true);
+                    case '%d':
+                    case '%i': return typeof arg === 'bigint' ? `${arg}n` : String(spec === '%i' ? parseInt(arg) : Number(arg));
+                    case '%f': return String(parseFloat(arg));
+                    case '%c': return '';
+                    default: return inspect(arg, spec !== '%O');
+                }
+            });
+            rest = args.slice(index);
+            if (rest.length === 0) return head;
+            head += ' ';
+        }
+        return head + rest.map((arg) => inspect(arg, false)).join(' ');
+    }
+
+    // 输出一行（group 中按层级缩进）
+    function emit(text) {
+        write(indent ? text.split('\n').map((line) => indent + line).join('\n') : text);
+    }
+
+    // 渲染 console.table 的表格
+    function renderTable(data, columns) {
+        let indexHeader = '(index)';
+        let rows;
+        if (data instanceof Map) {
+            indexHeader = '(iteration index)';
+            rows = Array.from(data, ([key, value], i) => [String(i), { Key: key, Values: value }]);
+        } else if (data instanceof Set) {
+            indexHeader = '(iteration index)';
+            rows = Array.from(data, (value, i) => [String(i), value]);
+        } else {
+            rows = Object.keys(data).map((key) => [key, data[key]]);
+        }
+
+        const keys = [];
+        let hasValues = false;
+        for (const [, row] of rows) {
+            if (row !== null && typeof row === 'object') {
+                for (const key of Object.keys(row)) {
+                    if (!keys.includes(key)) keys.push(key);
+                }
+            } else {
+                hasValues = true;
+            }
+        }
+        const shown = Array.isArray(columns) ? columns.map(String) : keys;
+        const header = [indexHeader, ...shown, ...(hasValues ? ['Values'] : [])];
+        const body = rows.map(([index, row]) => {
+            const isObject = row !== null && typeof row === 'object';
+            const cells = shown.map((key) => (isObject && key in row ? inspect(row[key], true) : ''));
+            if (hasValues) cells.push(isObject ? '' : inspect(row, true));
+            return [index, ...cells];
+        });
+
+        const widths = header.map((title, i) => Math.max(title.length, ...body.map((cells) => cells[i].length)) + 2);
+        const line = (left, middle, right) => left + widths.map((width) => '─'.repeat(width)).join(middle) + right;
+        const row = (cells) => '│' + cells.map((cell, i) => ` ${cell}`.padEnd(widths[i])).join('│') + '│';
+        return [
+            line('┌', '┬', '┐'),
+            row(header),
+            line('├', '┼', '┤'),
+            ...body.map(row),
+            line('└', '┴', '┘'),
+        ].join('\n');
+    }
+
+    for (const level of ['log', 'info', 'debug', 'warn', 'error', 'dirxml']) {
+        console[level] = function(...args) {
+            emit(format(args));
+        };
+    }
+
+    console.dir = function(value) {
+        emit(inspect(value, false));
+    };
+
+    console.trace = function(...args) {
+        const stack = (new Error().stack || '').split('\n').slice(2).join('\n');
+        emit(`Trace${args.length ? ': ' + format(args) : ''}\n${stack}`);
+    };
+
+    console.assert = function(condition, ...args) {
+        if (!condition) {
+            emit(args.length ? `Assertion failed: ${format(args)}` : 'Assertion failed');
+        }
+    };
+
+    console.count = function(label = 'default') {
+        label = String(label);
+        const count = (counts.get(label) || 0) + 1;
+        counts.set(label, count);
+        emit(`${label}: ${count}`);
+    };
+
+    console.countReset = function(label = 'default') {
+        label = String(label);
+        if (counts.has(label)) {
+            counts.set(label, 0);
+        } else {
+            emit(`Count for '${label}' does not exist`);
+        }
+    };
+
+    console.group = function(...args) {
+        if (args.length) emit(format(args));
+        indent += '  ';
+    };
+    console.groupCollapsed = console.group;
+    console.groupEnd = function() {
+        indent = indent.slice(0, -2);
+    };
+
+    const elapsed = (label) => `${label}: ${(now() - timers.get(label)).toFixed(3)}ms`;
+
+    console.time = function(label = 'default') {
+        label = String(label);
+        if (timers.has(label)) {
+            emit(`Timer '${label}' already exists`);
+            return;
+        }
+        timers.set(label, now());
+    };
+
+    console.timeLog = function(label = 'default', ...data) {
+        label = String(label);
+        if (!timers.has(label)) {
+            emit(`Timer '${label}' does not exist`);
+            return;
+        }
+        emit(data.length ? `${elapsed(label)} ${format(data)}` : elapsed(label));
+    };
+
+    console.timeEnd = function(label = 'default') {
+        label = String(label);
+        if (!timers.has(label)) {
+            emit(`Timer '${label}' does not exist`);
+            return;
+        }
+        emit(elapsed(label));
+        timers.delete(label);
+    };
+
+    console.table = function(data, columns) {
+        if (data === null || typeof data !== 'object') {
+            console.log(data);
+            return;
+        }
+        emit(renderTable(data, columns));
+    };
+
+    console.clear = function() {};
+})();
+
+
 // ============================================
 // Blob API
 // ============================================
@@ -2720,6 +3271,352 @@ if (typeof EventTarget === 'undefined') {
     globalThis.EventTarget = EventTarget;
 }
 
+// ============================================
+// Worker API（每个 Worker 是独立线程上的 Context，见 worker_ops.rs）
+// ============================================
+
+// Worker 发给父 Context 的事件类型（事件的第一个字节）
+const __WORKER_EVENT_MESSAGE__ = 0;
+const __WORKER_EVENT_ERROR__ = 1;
+const __WORKER_EVENT_IDLE__ = 2;
+const __WORKER_EVENT_CLOSED__ = 3;
+
+// 结构化克隆：SharedArrayBuffer / WebAssembly.Module 在 isolate 之间共享，其余复制
+// （transfer 列表中的 ArrayBuffer 同样按复制处理，不会被分离）
+function __serializeMessage(message) {
+    return __internalDeno.core.serialize(message, undefined, reason => {
+        const error = new Error(`Failed to execute 'postMessage': ${reason}`);
+        error.name = 'DataCloneError';
+        throw error;
+    });
+}
+
+function __messageEvent(data, target) {
+    const event = new Event('message');
+    event.data = data;
+    event.origin = '';
+    event.lastEventId = '';
+    event.source = null;
+    event.ports = [];
+    event.target = target;
+    event.currentTarget = target;
+    return event;
+}
+
+// 读取 Worker / importScripts 的脚本：blob: 和 data: URL、文件路径同步读取，http(s) 返回 Promise
+function __workerSource(url) {
+    if (url.startsWith('blob:')) {
+        const blob = URL._getObjectURL(url);
+        if (!blob) {
+            throw new Error(`Failed to load worker script: ${url} has been revoked or does not exist`);
+        }
+        const bytes = new Uint8Array(blob.size);
+        let offset = 0;
+        for (const part of blob._parts) {
+            bytes.set(part, offset);
+            offset += part.byteLength;
+        }
+        return new TextDecoder().decode(bytes);
+    }
+    if (url.startsWith('data:')) {
+        const comma = url.indexOf(',');
+        const meta = url.slice(5, comma);
+        const body = url.slice(comma + 1);
+        if (meta.endsWith(';base64')) {
+            const binary = atob(body);
+            return new TextDecoder().decode(Uint8Array.from(binary, c => c.charCodeAt(0)));
+        }
+        return decodeURIComponent(body);
+    }
+    if (url.startsWith('http://') || url.startsWith('https://')) {
+        return fetch(url).then(response => {
+            if (!response.ok) {
+                throw new Error(`Failed to load worker script: ${url} (HTTP ${response.status})`);
+            }
+            return response.text();
+        });
+    }
+    return fs.readFileSync(url.startsWith('file://') ? url.slice(7) : url);
+}
+
+if (typeof Worker === 'undefined') {
+    class Worker extends EventTarget {
+        constructor(scriptURL, options = {}) {
+            super();
+            if (arguments.length === 0) {
+                throw new TypeError("Failed to construct 'Worker': 1 argument required, but only 0 present.");
+            }
+            this.onmessage = null;
+            this.onmessageerror = null;
+            this.onerror = null;
+
+            this._url = String(scriptURL);
+            this._name = options && options.name !== undefined ? String(options.name) : '';
+            this._module = !!options && options.type === 'module';
+            this._id = 0;
+            this._queue = [];       // 脚本加载完成之前发出的消息
+            this._sent = 0;         // 已发出的消息数
+            this._handled = 0;      // Worker 已处理的消息数
+            this._started = false;  // Worker 脚本已执行完
+            this._closed = false;
+            this._pending = null;   // 等待下一个事件的 op Promise
+
+            const source = __workerSource(this._url);
+            if (typeof source === 'string') {
+                this._start(source);
+            } else {
+                source.then(code => this._start(code), error => this._fail(error));
+            }
+        }
+
+        postMessage(message, transfer) {
+            if (arguments.length === 0) {
+                throw new TypeError("Failed to execute 'postMessage' on 'Worker': 1 argument required, but only 0 present.");
+            }
+            const data = __serializeMessage(message);
+            if (this._closed) {
+                return;
+            }
+            if (!this._id) {
+                this._queue.push(data);
+                return;
+            }
+            this._post(data);
+        }
+
+        terminate() {
+            if (this._closed) {
+                return;
+            }
+            this._closed = true;
+            this._queue = [];
+            if (this._id) {
+                __getDeno().core.ops.op_worker_terminate(this._id);
+            }
+            if (this._pending) {
+                __internalDeno.core.unrefOpPromise(this._pending);
+            }
+        }
+
+        _start(code) {
+            if (this._closed) {
+                return;
+            }
+            this._id = __getDeno().core.ops.op_worker_create(code, this._url, this._name, this._module);
+            log(`Worker started: ${this._url} (id=${this._id})`);
+            this._pump();
+            for (const data of this._queue) {
+                this._post(data);
+            }
+            this._queue = [];
+        }
+
+        _fail(error) {
+            this._closed = true;
+            this._dispatch(Object.assign(new Event('error', { cancelable: true }), {
+                message: String(error && error.message || error),
+                filename: this._url, lineno: 0, colno: 0, error,
+            }));
+        }
+
+        _post(data) {
+            this._sent++;
+            __getDeno().core.ops.op_worker_post_message(this._id, data);
+            // Worker 有未处理的消息时父 Context 的事件循环等待它的回复
+            if (this._pending) {
+                __internalDeno.core.refOpPromise(this._pending);
+            }
+        }
+
+        // 等待 Worker 的下一个事件；Worker 空闲时不阻止事件循环结束
+        _pump() {
+            const pending = __getDeno().core.ops.op_worker_recv(this._id);
+            this._pending = pending;
+            if (this._started && this._handled >= this._sent) {
+                __internalDeno.core.unrefOpPromise(pending);
+            }
+            pending.then(event => {
+                this._pending = null;
+                if (this._handle(event)) {
+                    this._pump();
+                }
+            });
+        }
+
+        _handle(event) {
+            const payload = event.subarray(1);
+            switch (event[0]) {
+                case __WORKER_EVENT_MESSAGE__: {
+                    if (this._closed) {
+                        return false;
+                    }
+                    let data;
+                    try {
+                        data = __internalDeno.core.deserialize(payload);
+                    } catch (error) {
+                        this._dispatch(__messageEvent(null, this), 'messageerror');
+                        return true;
+                    }
+                    this._dispatch(__messageEvent(data, this));
+                    return true;
+                }
+                case __WORKER_EVENT_ERROR__: {
+                    if (this._closed) {
+                        return false;
+                    }
+                    const message = new TextDecoder().decode(payload);
+                    const event = Object.assign(new Event('error', { cancelable: true }), {
+                        message, filename: this._url, lineno: 0, colno: 0, error: null,
+                    });
+                    if (this._dispatch(event)) {
+                        console.error(`Uncaught (in worker "${this._name || this._url}") ${message}`);
+                    }
+                    return true;
+                }
+                case __WORKER_EVENT_IDLE__:
+                    this._started = true;
+                    this._handled = new DataView(payload.buffer, payload.byteOffset, 4).getUint32(0, true);
+                    return !this._closed;
+                default:
+                    this._closed = true;
+                    return false;
+            }
+        }
+
+        // 调用 on<type> 和 addEventListener 注册的监听器，返回是否未被 preventDefault()
+        _dispatch(event, type = event.type) {
+            if (type !== event.type) {
+                event.type = type;
+            }
+            const handler = this['on' + type];
+            if (typeof handler === 'function') {
+                event.target = this;
+                event.currentTarget = this;
+                try {
+                    handler.call(this, event);
+                } catch (err) {
+                    console.error('Error in event listener:', err);
+                }
+            }
+            return this.dispatchEvent(event);
+        }
+    }
+    globalThis.Worker = Worker;
+    log('Worker API loaded');
+}
+
+// Worker 线程中的全局作用域：self.postMessage / onmessage / close / importScripts
+// 只在作为 Worker 运行的 Context 中由 context.rs 调用一次（之后与 Deno 一起被删除）
+Object.defineProperty(globalThis, '__never_jscore_worker_scope__', {
+    value: function() {
+        const core = __internalDeno.core;
+        const ops = __getDeno().core.ops;
+        const listeners = new EventTarget();
+        let closing = false;
+
+        delete globalThis.window;
+        delete globalThis.document;
+
+        function WorkerGlobalScope() {
+            throw new TypeError('Illegal constructor');
+        }
+        function DedicatedWorkerGlobalScope() {
+            throw new TypeError('Illegal constructor');
+        }
+        Object.setPrototypeOf(WorkerGlobalScope.prototype, Object.getPrototypeOf(globalThis));
+        Object.setPrototypeOf(DedicatedWorkerGlobalScope.prototype, WorkerGlobalScope.prototype);
+        try {
+            Object.setPrototypeOf(globalThis, DedicatedWorkerGlobalScope.prototype);
+        } catch (e) {
+            // 全局对象的原型不可修改时只提供构造函数
+        }
+
+        const reportError = error => {
+            ops.op_worker_scope_error(String(error && error.stack || error));
+        };
+
+        const scope = {
+            WorkerGlobalScope,
+            DedicatedWorkerGlobalScope,
+            self: globalThis,
+            name: ops.op_worker_scope_name(),
+            onmessage: null,
+            onmessageerror: null,
+            onerror: null,
+            postMessage: function postMessage(message, transfer) {
+                if (arguments.length === 0) {
+                    throw new TypeError("Failed to execute 'postMessage' on 'DedicatedWorkerGlobalScope': 1 argument required, but only 0 present.");
+                }
+                ops.op_worker_scope_post(__serializeMessage(message));
+            },
+            close: function close() {
+                closing = true;
+                ops.op_worker_scope_close();
+            },
+            importScripts: function importScripts(...urls) {
+                for (const url of urls) {
+                    const source = __workerSource(String(url));
+                    if (typeof source !== 'string') {
+                        throw new Error(`importScripts() cannot load ${url} synchronously`);
+                    }
+                    (0, eval)(source);
+                }
+            },
+            addEventListener: function addEventListener(type, callback, options) {
+                listeners.addEventListener(type, callback, options);
+            },
+            removeEventListener: function removeEventListener(type, callback, options) {
+                listeners.removeEventListener(type, callback, options);
+            },
+        };
+        for (const [key, value] of Object.entries(scope)) {
+            Object.defineProperty(globalThis, key, { value, writable: true, enumerable: true, configurable: true });
+        }
+
+        const dispatch = event => {
+            const handler = globalThis['on' + event.type];
+            if (typeof handler === 'function') {
+                try {
+                    handler.call(globalThis, event);
+                } catch (error) {
+                    reportError(error);
+                }
+            }
+            const callbacks = listeners._listeners.get(event.type) || [];
+            for (const listener of [...callbacks]) {
+                try {
+                    listener.callback.call(globalThis, event);
+                } catch (error) {
+                    reportError(error);
+                }
+            }
+        };
+
+        // 接收父 Context 的消息；等待消息本身不阻止事件循环结束（Worker 线程在空闲时阻塞等待）
+        (async () => {
+            while (!closing) {
+                const pending = ops.op_worker_scope_recv();
+                core.unrefOpPromise(pending);
+                const data = await pending;
+                if (data.length === 0) {
+                    break;
+                }
+                let message;
+                try {
+                    message = core.deserialize(data);
+                } catch (error) {
+                    dispatch(Object.assign(__messageEvent(null, globalThis), { type: 'messageerror' }));
+                    continue;
+                }
+                dispatch(__messageEvent(message, globalThis));
+            }
+        })();
+    },
+    writable: false,
+    enumerable: false,
+    configurable: true
+});
+
 // ============================================
 // XMLHttpRequest API (基于 fetch 实现)
 // ============================================
@@ -3354,7 +4251,10 @@ log('Proxy logging system loaded');
 
     // 3. 保护 console 方法
     if (typeof console !== 'undefined') {
-        const consoleMethods = ['log', 'warn', 'error', 'info', 'debug', 'trace', 'dir', 'table'];
+        const consoleMethods = [
+            'log', 'warn', 'error', 'info', 'debug', 'trace', 'dir', 'dirxml', 'table', 'assert',
+            'count', 'countReset', 'group', 'groupCollapsed', 'groupEnd', 'time', 'timeLog', 'timeEnd', 'clear'
+        ];
         for (const method of consoleMethods) {
             if (typeof console[method] === 'function') {
                 makeNative(console[method], method);
@@ -3370,6 +4270,15 @@ log('Proxy logging system loaded');
         });
     }
 
+    // WebAssembly 流式 API（polyfill 实现）
+    if (typeof WebAssembly !== 'undefined') {
+        for (const method of ['compileStreaming', 'instantiateStreaming']) {
+            if (typeof WebAssembly[method] === 'function') {
+                makeNative(WebAssembly[method], method);
+            }
+        }
+    }
+
     // 4. 保护 navigator 方法
     if (typeof navigator !== 'undefined') {
         // 确保 navigator 不可配置
@@ -3522,3 +4431,4 @@ log('Proxy logging system loaded');
     }
 })();
 
+})();
@@ -0,0 +1,278 @@
+// wasi.js - 最小 WASI（wasi_snapshot_preview1）宿主，由 load_wasm(..., wasi=...) 按需求值（见 wasm.rs）
+//
+// 整个文件是一个函数表达式：(config, stdin) => host
+// - config: { args: [...], env: [[name, value], ...] }
+// - stdin: ArrayBuffer，fd 0 读取的内容
+// - host.imports: wasi_snapshot_preview1 导入模块
+// - host.start(instance): 绑定内存、初始化 reactor 模块，返回定义为全局变量的导出对象
+//
+// 只提供命令行参数、环境变量、时钟、随机数和标准输入输出：
+// fd 1 / 2 按行输出到 console.log / console.error，没有预打开目录（不能访问文件系统），
+// 其他函数返回 ENOSYS。不依赖 polyfill，enable_extensions=False 时同样可用。
+(function(config, stdin) {
+    'use strict';
+
+    const ERRNO_SUCCESS = 0;
+    const ERRNO_BADF = 8;
+    const ERRNO_INVAL = 28;
+    const ERRNO_NOSYS = 52;
+    const ERRNO_SPIPE = 70;
+
+    const FILETYPE_CHARACTER_DEVICE = 2;
+    const CLOCK_REALTIME = 0;
+
+    // proc_exit() 抛出，由 _start 包装函数转换为退出码
+    class WasiExit extends Error {
+        constructor(code) {
+            super(`WASI exit code ${code}`);
+            this.name = 'WasiExit';
+            this.code = code;
+        }
+    }
+
+    let memory = null;
+    const stdinBytes = new Uint8Array(stdin);
+    let stdinOffset = 0;
+    const pending = { 1: [], 2: [] };
+
+    function view() {
+        return new DataView(memory.buffer);
+    }
+
+    function bytes() {
+        return new Uint8Array(memory.buffer);
+    }
+
+    function encode(text) {
+        const binary = unescape(encodeURIComponent(text));
+        const result = new Uint8Array(binary.length);
+        for (let i = 0; i < binary.length; i++) {
+            result[i] = binary.charCodeAt(i);
+        }
+        return result;
+    }
+
+    function decode(data) {
+        let binary = '';
+        for (let i = 0; i < data.length; i++) {
+            binary += String.fromCharCode(data[i]);
+        }
+        try {
+            return decodeURIComponent(escape(binary));
+        } catch (e) {
+            return binary;  // 不是合法的 UTF-8
+        }
+    }
+
+    // 按行输出，未结束的行留到下次写入或 flush()
+    function emit(fd, data) {
+        const buffer = pending[fd];
+        for (const byte of data) {
+            if (byte === 10) {
+                output(fd, buffer.splice(0));
+            } else {
+                buffer.push(byte);
+            }
+        }
+    }
+
+    function output(fd, line) {
+        const text = decode(line);
+        if (fd === 2) {
+            console.error(text);
+        } else {
+            console.log(text);
+        }
+    }
+
+    function flush() {
+        for (const fd of [1, 2]) {
+            if (pending[fd].length > 0) {
+                output(fd, pending[fd].splice(0));
+            }
+        }
+    }
+
+    // 以 NUL 结尾的字符串列表（args / environ）
+    const argList = config.args.map(arg => encode(arg + '\0'));
+    const envList = config.env.map(([name, value]) => encode(name + '=' + value + '\0'));
+
+    function sizesGet(list, countPtr, sizePtr) {
+        const dv = view();
+        dv.setUint32(countPtr, list.length, true);
+        dv.setUint32(sizePtr, list.reduce((size, item) => size + item.length, 0), true);
+        return ERRNO_SUCCESS;
+    }
+
+    function listGet(list, ptrsPtr, bufPtr) {
+        const dv = view();
+        const mem = bytes();
+        for (let i = 0; i < list.length; i++) {
+            dv.setUint32(ptrsPtr + i * 4, bufPtr, true);
+            mem.set(list[i], bufPtr);
+            bufPtr += list[i].length;
+        }
+        return ERRNO_SUCCESS;
+    }
+
+    function now(clockId) {
+        if (clockId !== CLOCK_REALTIME && typeof performance !== 'undefined' && performance.now) {
+            return BigInt(Math.round(performance.now() * 1e6));
+        }
+        return BigInt(Date.now()) * 1000000n;
+    }
+
+    function isStdio(fd) {
+        return fd === 0 || fd === 1 || fd === 2;
+    }
+
+    const wasi = {
+        args_sizes_get: (countPtr, sizePtr) => sizesGet(argList, countPtr, sizePtr),
+        args_get: (argvPtr, bufPtr) => listGet(argList, argvPtr, bufPtr),
+        environ_sizes_get: (countPtr, sizePtr) => sizesGet(envList, countPtr, sizePtr),
+        environ_get: (environPtr, bufPtr) => listGet(envList, environPtr, bufPtr),
+
+        clock_res_get(clockId, resultPtr) {
+            view().setBigUint64(resultPtr, clockId === CLOCK_REALTIME ? 1000000n : 1000n, true);
+            return ERRNO_SUCCESS;
+        },
+
+        clock_time_get(clockId, precision, resultPtr) {
+            view().setBigUint64(resultPtr, now(clockId), true);
+            return ERRNO_SUCCESS;
+        },
+
+        random_get(bufPtr, length) {
+            const target = bytes().subarray(bufPtr, bufPtr + length);
+            if (typeof crypto !== 'undefined' && crypto.getRandomValues) {
+                // getRandomValues 每次最多 65536 字节
+                for (let offset = 0; offset < length; offset += 65536) {
+                    crypto.getRandomValues(target.subarray(offset, offset + 65536));
+                }
+            } else {
+                for (let i = 0; i < length; i++) {
+                    target[i] = Math.floor(Math.random() * 256);
+                }
+            }
+            return ERRNO_SUCCESS;
+        },
+
+        fd_write(fd, iovsPtr, iovsLen, nwrittenPtr) {
+            if (fd !== 1 && fd !== 2) {
+                return ERRNO_BADF;
+            }
+            const dv = view();
+            const mem = bytes();
+            let written = 0;
+            for (let i = 0; i < iovsLen; i++) {
+                const ptr = dv.getUint32(iovsPtr + i * 8, true);
+                const len = dv.getUint32(iovsPtr + i * 8 + 4, true);
+                emit(fd, mem.subarray(ptr, ptr + len));
+                written += len;
+            }
+            dv.setUint32(nwrittenPtr, written, true);
+            return ERRNO_SUCCESS;
+        },
+
+        fd_read(fd, iovsPtr, iovsLen, nreadPtr) {
+            if (fd !== 0) {
+                return ERRNO_BADF;
+            }
+            const dv = view();
+            const mem = bytes();
+            let read = 0;
+            for (let i = 0; i < iovsLen && stdinOffset < stdinBytes.length; i++) {
+                const ptr = dv.getUint32(iovsPtr + i * 8, true);
+                const len = dv.getUint32(iovsPtr + i * 8 + 4, true);
+                const chunk = stdinBytes.subarray(stdinOffset, stdinOffset + len);
+                mem.set(chunk, ptr);
+                stdinOffset += chunk.length;
+                read += chunk.length;
+            }
+            dv.setUint32(nreadPtr, read, true);
+            return ERRNO_SUCCESS;
+        },
+
+        fd_fdstat_get(fd, statPtr) {
+            if (!isStdio(fd)) {
+                return ERRNO_BADF;
+            }
+            const dv = view();
+            dv.setUint8(statPtr, FILETYPE_CHARACTER_DEVICE);
+            dv.setUint16(statPtr + 2, 0, true);
+            dv.setBigUint64(statPtr + 8, 0xFFFFFFFFFFFFFFFFn, true);
+            dv.setBigUint64(statPtr + 16, 0xFFFFFFFFFFFFFFFFn, true);
+            return ERRNO_SUCCESS;
+        },
+
+        fd_filestat_get(fd, statPtr) {
+            if (!isStdio(fd)) {
+                return ERRNO_BADF;
+            }
+            bytes().fill(0, statPtr, statPtr + 64);
+            view().setUint8(statPtr + 16, FILETYPE_CHARACTER_DEVICE);
+            return ERRNO_SUCCESS;
+        },
+
+        fd_fdstat_set_flags: fd => isStdio(fd) ? ERRNO_SUCCESS : ERRNO_BADF,
+        fd_close: fd => isStdio(fd) ? ERRNO_SUCCESS : ERRNO_BADF,
+        fd_seek: fd => isStdio(fd) ? ERRNO_SPIPE : ERRNO_BADF,
+        fd_tell: fd => isStdio(fd) ? ERRNO_SPIPE : ERRNO_BADF,
+        fd_sync: fd => isStdio(fd) ? ERRNO_INVAL : ERRNO_BADF,
+
+        // 没有预打开的目录：wasi-libc 据此认为文件系统不可用
+        fd_prestat_get: () => ERRNO_BADF,
+        fd_prestat_dir_name: () => ERRNO_BADF,
+
+        sched_yield: () => ERRNO_SUCCESS,
+
+        proc_exit(code) {
+            flush();
+            throw new WasiExit(code);
+        },
+    };
+
+    // 未实现的函数返回 ENOSYS，模块仍然可以链接
+    const imports = new Proxy(wasi, {
+        get(target, name) {
+            if (name in target) {
+                return target[name];
+            }
+            return () => ERRNO_NOSYS;
+        }
+    });
+
+    function start(instance) {
+        const exports = instance.exports;
+        if (!(exports.memory instanceof WebAssembly.Memory)) {
+            throw new TypeError('WASI modules must export their memory');
+        }
+        memory = exports.memory;
+
+        // reactor 模块：先运行初始化函数
+        if (typeof exports._initialize === 'function') {
+            exports._initialize();
+        }
+
+        // 命令模块：_start() 返回退出码（proc_exit 不作为异常抛出）
+        const result = Object.assign({}, exports);
+        if (typeof exports._start === 'function') {
+            result._start = function _start() {
+                try {
+                    exports._start();
+                    return 0;
+                } catch (e) {
+                    if (e instanceof WasiExit) {
+                        return e.code;
+                    }
+                    throw e;
+                } finally {
+                    flush();
+                }
+            };
+        }
+        return result;
+    }
+
+    return { imports: imports, start: start };
+})
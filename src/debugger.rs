@@ -0,0 +1,604 @@
+// debugger.rs - set_breakpoint() / debug_call()：不依赖 DevTools 的调试接口
+//
+// 在 Context 的 isolate 上创建进程内的 V8 inspector 会话，直接收发 Chrome DevTools Protocol（CDP）消息，
+// 不需要启动 WebSocket 服务或连接 Chrome：
+// - set_breakpoint(file, line)：按脚本名设置断点（Debugger.setBreakpointByUrl），file 匹配脚本名的结尾。
+//   compile() 的脚本名为 "<exec>"，compile_file() 为文件路径，也可以在代码中用 //# sourceURL=name 指定。
+//   断点可以在脚本编译之前设置，之后编译的同名脚本同样生效
+// - debug_call(name, args, on_pause)：调用函数，执行在 debugger 语句或断点处暂停时，在执行线程上调用
+//   on_pause(frame)。PausedFrame 读取调用栈和作用域变量、在暂停的调用帧上求值；
+//   on_pause 的返回值决定如何继续：None / "continue" 继续执行，"step_over" / "step_into" / "step_out" 单步执行
+//
+// 暂停只在 debug_call() 中生效：其余时间会话设置了 Debugger.setSkipAllPauses，
+// 普通的 call() / evaluate() 遇到断点和 debugger 语句不会停下。
+// inspector 在第一次使用时创建，之后一直附加在 isolate 上（V8 会因此关闭部分优化）。
+//
+// V8 在暂停时调用 V8InspectorClientImpl::run_message_loop_on_pause，此时 JS 调用栈仍在执行中：
+// 回调里同步地调用 Python，期间通过同一个会话派发 CDP 消息（响应由 Channel 同步写回），
+// 最后派发 Debugger.resume / stepOver 等消息后返回，V8 继续执行。
+
+use deno_core::{v8, JsRuntime};
+use pyo3::exceptions::{PyException, PyIndexError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::{json, Value as JsonValue};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use v8::inspector::{
+    Channel, ChannelImpl, StringBuffer, StringView, V8Inspector, V8InspectorClient,
+    V8InspectorClientImpl, V8InspectorClientTrustLevel, V8InspectorSession,
+};
+
+use crate::convert::json_to_python;
+
+/// inspector 的 context group（每个 Context 只有一个主 context）
+const CONTEXT_GROUP_ID: i32 = 1;
+
+/// 会话状态，由 Debugger、Channel、inspector 客户端和 PausedFrame 共享
+struct DebuggerState {
+    session: RefCell<Option<V8InspectorSession>>,
+    /// 按消息 ID 保存的响应（派发消息时同步写入）
+    responses: RefCell<HashMap<i32, JsonValue>>,
+    next_id: Cell<i32>,
+    /// 最近一次 Debugger.paused 通知的参数
+    paused: RefCell<Option<JsonValue>>,
+    /// scriptId -> 脚本名（Debugger.scriptParsed）
+    scripts: RefCell<HashMap<String, String>>,
+    /// debug_call() 期间的 on_pause 回调
+    handler: RefCell<Option<Py<PyAny>>>,
+    /// pause_on_start 使用的断点 ID
+    start_breakpoint: RefCell<Option<String>>,
+    /// 上一次暂停后是否选择了单步执行
+    stepping: Cell<bool>,
+    /// on_pause 抛出的异常（执行随之被终止）
+    error: RefCell<Option<PyErr>>,
+    isolate_handle: v8::IsolateHandle,
+    isolate_ptr: v8::UnsafeRawIsolatePtr,
+    context: v8::Global<v8::Context>,
+}
+
+impl DebuggerState {
+    /// 派发一条 CDP 命令并返回 result（错误响应转换为 Err）
+    fn send(&self, method: &str, params: JsonValue) -> Result<JsonValue, String> {
+        let id = self.next_id.get() + 1;
+        self.next_id.set(id);
+        let message = json!({ "id": id, "method": method, "params": params }).to_string();
+        {
+            let session = self.session.borrow();
+            let session = session.as_ref().ok_or("Debugger session is closed")?;
+            session.dispatch_protocol_message(StringView::from(message.as_bytes()));
+        }
+
+        let mut response = self
+            .responses
+            .borrow_mut()
+            .remove(&id)
+            .ok_or_else(|| format!("No response to {}", method))?;
+        if let Some(error) = response.get("error") {
+            let message = error.get("message").and_then(JsonValue::as_str).unwrap_or("unknown error");
+            return Err(format!("{} failed: {}", method, message));
+        }
+        Ok(response.get_mut("result").map(JsonValue::take).unwrap_or(JsonValue::Null))
+    }
+
+    fn on_message(&self, message: v8::UniquePtr<StringBuffer>) -> Option<JsonValue> {
+        let message = message.unwrap().string().to_string();
+        serde_json::from_str(&message).ok()
+    }
+
+    /// 执行暂停：调用 on_pause，按返回值恢复执行
+    fn on_pause(self: &Rc<Self>) {
+        let params = self.paused.borrow_mut().take().unwrap_or(JsonValue::Null);
+        let action = Python::attach(|py| -> PyResult<String> {
+            let handler = self.handler.borrow().as_ref().map(|handler| handler.clone_ref(py));
+            let Some(handler) = handler else {
+                return Ok("continue".to_string());
+            };
+            let reason = self.pause_reason(&params);
+            let frame = Py::new(py, PausedFrame {
+                state: self.clone(),
+                params,
+                reason,
+                valid: Cell::new(true),
+            })?;
+            let result = handler.call1(py, (frame.clone_ref(py),));
+            frame.borrow(py).valid.set(false);
+            let result = result?;
+            if result.is_none(py) {
+                return Ok("continue".to_string());
+            }
+            result.extract::<String>(py)
+        });
+
+        let method = action.and_then(|action| match action.as_str() {
+            "continue" => Ok("Debugger.resume"),
+            "step_over" => Ok("Debugger.stepOver"),
+            "step_into" => Ok("Debugger.stepInto"),
+            "step_out" => Ok("Debugger.stepOut"),
+            other => Err(PyValueError::new_err(format!(
+                "Invalid on_pause result '{}': expected None, 'continue', 'step_over', 'step_into' or 'step_out'",
+                other
+            ))),
+        });
+        let method = match method {
+            Ok(method) => method,
+            Err(e) => {
+                // 回调出错：终止执行，debug_call() 原样抛出该异常
+                *self.error.borrow_mut() = Some(e);
+                self.isolate_handle.terminate_execution();
+                "Debugger.resume"
+            }
+        };
+        self.stepping.set(method != "Debugger.resume");
+        let _ = self.send(method, json!({}));
+    }
+
+    /// 暂停原因："start" / "breakpoint" / "step" / "debugger"，其他情况沿用 CDP 的 reason
+    fn pause_reason(&self, params: &JsonValue) -> String {
+        let hit: Vec<&str> = params
+            .get("hitBreakpoints")
+            .and_then(JsonValue::as_array)
+            .map(|ids| ids.iter().filter_map(JsonValue::as_str).collect())
+            .unwrap_or_default();
+        let reason = params.get("reason").and_then(JsonValue::as_str).unwrap_or("other");
+        if let Some(start) = self.start_breakpoint.borrow().as_deref() {
+            if hit.contains(&start) {
+                return "start".to_string();
+            }
+        }
+        if !hit.is_empty() {
+            "breakpoint".to_string()
+        } else if reason != "other" {
+            reason.to_string()
+        } else if self.stepping.get() {
+            "step".to_string()
+        } else {
+            "debugger".to_string()
+        }
+    }
+}
+
+/// CDP 响应和通知的接收端
+struct SessionChannel(Weak<DebuggerState>);
+
+impl ChannelImpl for SessionChannel {
+    fn send_response(&self, call_id: i32, message: v8::UniquePtr<StringBuffer>) {
+        let Some(state) = self.0.upgrade() else { return };
+        if let Some(response) = state.on_message(message) {
+            state.responses.borrow_mut().insert(call_id, response);
+        }
+    }
+
+    fn send_notification(&self, message: v8::UniquePtr<StringBuffer>) {
+        let Some(state) = self.0.upgrade() else { return };
+        let Some(mut notification) = state.on_message(message) else { return };
+        let params = notification.get_mut("params").map(JsonValue::take).unwrap_or(JsonValue::Null);
+        match notification.get("method").and_then(JsonValue::as_str) {
+            Some("Debugger.paused") => *state.paused.borrow_mut() = Some(params),
+            Some("Debugger.scriptParsed") => {
+                if let (Some(id), Some(url)) = (
+                    params.get("scriptId").and_then(JsonValue::as_str),
+                    params.get("url").and_then(JsonValue::as_str),
+                ) {
+                    state.scripts.borrow_mut().insert(id.to_string(), url.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn flush_protocol_notifications(&self) {}
+}
+
+/// inspector 客户端：暂停时调用 on_pause
+struct InspectorClient(Weak<DebuggerState>);
+
+impl V8InspectorClientImpl for InspectorClient {
+    fn run_message_loop_on_pause(&self, _context_group_id: i32) {
+        if let Some(state) = self.0.upgrade() {
+            state.on_pause();
+        }
+    }
+
+    fn ensure_default_context_in_group(&self, _context_group_id: i32) -> Option<v8::Local<'_, v8::Context>> {
+        let state = self.0.upgrade()?;
+        let mut isolate = unsafe { v8::Isolate::from_raw_isolate_ptr(state.isolate_ptr) };
+        let isolate = &mut isolate;
+        v8::callback_scope!(unsafe scope, isolate);
+        let local = v8::Local::new(scope, state.context.clone());
+        Some(unsafe { local.extend_lifetime_unchecked() })
+    }
+}
+
+/// Context 的调试会话
+pub struct Debugger {
+    state: Rc<DebuggerState>,
+    _inspector: V8Inspector,
+}
+
+impl Debugger {
+    /// 在 runtime 的主 context 上创建 inspector 和会话（调用方已进入 isolate）
+    pub fn new(runtime: &mut JsRuntime) -> anyhow::Result<Self> {
+        let isolate_handle = runtime.v8_isolate().thread_safe_handle();
+        let isolate_ptr = unsafe { runtime.v8_isolate().as_raw_isolate_ptr() };
+        let context = runtime.main_context();
+        let state = Rc::new(DebuggerState {
+            session: RefCell::new(None),
+            responses: RefCell::default(),
+            next_id: Cell::new(0),
+            paused: RefCell::new(None),
+            scripts: RefCell::default(),
+            handler: RefCell::new(None),
+            start_breakpoint: RefCell::new(None),
+            stepping: Cell::new(false),
+            error: RefCell::new(None),
+            isolate_handle,
+            isolate_ptr,
+            context,
+        });
+
+        deno_core::scope!(scope, runtime);
+        let client = V8InspectorClient::new(Box::new(InspectorClient(Rc::downgrade(&state))));
+        let inspector = V8Inspector::create(scope, client);
+        let context = scope.get_current_context();
+        inspector.context_created(
+            context,
+            CONTEXT_GROUP_ID,
+            StringView::from(&b"never_jscore"[..]),
+            StringView::from(&br#"{"isDefault": true}"#[..]),
+        );
+        let channel = Channel::new(Box::new(SessionChannel(Rc::downgrade(&state))));
+        let session = inspector.connect(
+            CONTEXT_GROUP_ID,
+            channel,
+            StringView::empty(),
+            V8InspectorClientTrustLevel::FullyTrusted,
+        );
+        *state.session.borrow_mut() = Some(session);
+
+        let debugger = Self { state, _inspector: inspector };
+        debugger.send("Debugger.enable", json!({}))?;
+        debugger.send("Debugger.setSkipAllPauses", json!({ "skip": true }))?;
+        Ok(debugger)
+    }
+
+    fn send(&self, method: &str, params: JsonValue) -> anyhow::Result<JsonValue> {
+        self.state.send(method, params).map_err(anyhow::Error::msg)
+    }
+
+    /// 按脚本名设置断点，返回断点 ID（line / column 从 1 开始，由调用方检查）
+    pub fn set_breakpoint(&self, file: &str, line: u32, column: Option<u32>, condition: Option<&str>) -> anyhow::Result<String> {
+        let mut params = json!({
+            "urlRegex": format!("(^|[\\\\/]){}$", escape_regex(file)),
+            "lineNumber": line - 1,
+        });
+        if let Some(column) = column {
+            params["columnNumber"] = json!(column - 1);
+        }
+        if let Some(condition) = condition {
+            params["condition"] = json!(condition);
+        }
+        let result = self.send("Debugger.setBreakpointByUrl", params)?;
+        result
+            .get("breakpointId")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Debugger.setBreakpointByUrl returned no breakpoint ID"))
+    }
+
+    pub fn remove_breakpoint(&self, id: &str) -> anyhow::Result<()> {
+        self.send("Debugger.removeBreakpoint", json!({ "breakpointId": id }))?;
+        Ok(())
+    }
+
+    /// debug_call() 开始：安装 on_pause，允许暂停；pause_on_start 时在 name 指向的函数入口设置断点
+    pub fn begin(&self, handler: Py<PyAny>, pause_on_start: Option<&str>) -> anyhow::Result<()> {
+        if let Some(name) = pause_on_start {
+            let result = self.send(
+                "Runtime.evaluate",
+                json!({ "expression": name, "silent": true }),
+            )?;
+            let function = result
+                .get("result")
+                .filter(|object| object.get("type").and_then(JsonValue::as_str) == Some("function"))
+                .and_then(|object| object.get("objectId"))
+                .ok_or_else(|| anyhow::anyhow!("{} is not a function", name))?;
+            let result = self.send("Debugger.setBreakpointOnFunctionCall", json!({ "objectId": function }))?;
+            *self.state.start_breakpoint.borrow_mut() =
+                result.get("breakpointId").and_then(JsonValue::as_str).map(str::to_string);
+            let _ = self.send("Runtime.releaseObject", json!({ "objectId": function }));
+        }
+        *self.state.handler.borrow_mut() = Some(handler);
+        self.state.stepping.set(false);
+        self.send("Debugger.setSkipAllPauses", json!({ "skip": false }))?;
+        Ok(())
+    }
+
+    /// debug_call() 结束：恢复跳过暂停，返回 on_pause 抛出的异常
+    pub fn end(&self) -> Option<PyErr> {
+        let _ = self.send("Debugger.setSkipAllPauses", json!({ "skip": true }));
+        if let Some(id) = self.state.start_breakpoint.borrow_mut().take() {
+            let _ = self.send("Debugger.removeBreakpoint", json!({ "breakpointId": id }));
+        }
+        self.state.handler.borrow_mut().take();
+        self.state.error.borrow_mut().take()
+    }
+}
+
+impl Drop for Debugger {
+    fn drop(&mut self) {
+        // 会话必须在 inspector 之前销毁
+        self.state.session.borrow_mut().take();
+    }
+}
+
+/// 转义正则表达式元字符
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}/".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 暂停时的调用栈（只在 on_pause 回调期间有效）
+///
+/// 行号和列号从 1 开始。
+///
+/// Example:
+///     ```python
+///     def on_pause(frame):
+///         print(frame.reason, frame.url, frame.line, frame.function)
+///         print(frame.locals())           # {'a': 1, 'b': 2}
+///         print(frame.evaluate("a + b"))  # 3
+///         return "continue"
+///
+///     ctx.compile("function add(a, b) { debugger; return a + b; }")
+///     ctx.debug_call("add", [1, 2], on_pause)
+///     ```
+#[pyclass(unsendable)]
+pub struct PausedFrame {
+    state: Rc<DebuggerState>,
+    /// Debugger.paused 通知的参数
+    params: JsonValue,
+    reason: String,
+    valid: Cell<bool>,
+}
+
+impl PausedFrame {
+    fn check_valid(&self) -> PyResult<()> {
+        if self.valid.get() {
+            Ok(())
+        } else {
+            Err(PyRuntimeError::new_err("PausedFrame is only valid inside the on_pause callback"))
+        }
+    }
+
+    fn call_frames(&self) -> &[JsonValue] {
+        self.params
+            .get("callFrames")
+            .and_then(JsonValue::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn call_frame(&self, index: usize) -> PyResult<&JsonValue> {
+        self.call_frames()
+            .get(index)
+            .ok_or_else(|| PyIndexError::new_err(format!("Call frame {} does not exist", index)))
+    }
+
+    fn send(&self, method: &str, params: JsonValue) -> PyResult<JsonValue> {
+        self.check_valid()?;
+        self.state.send(method, params).map_err(PyRuntimeError::new_err)
+    }
+
+    /// 调用帧的位置信息 {"function", "url", "line", "column"}
+    fn location(&self, frame: &JsonValue) -> JsonValue {
+        let location = &frame["location"];
+        let url = frame
+            .get("url")
+            .and_then(JsonValue::as_str)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .or_else(|| {
+                let script_id = location.get("scriptId").and_then(JsonValue::as_str)?;
+                self.state.scripts.borrow().get(script_id).cloned()
+            })
+            .unwrap_or_default();
+        json!({
+            "function": frame.get("functionName").and_then(JsonValue::as_str).unwrap_or_default(),
+            "url": url,
+            "line": location.get("lineNumber").and_then(JsonValue::as_u64).unwrap_or_default() + 1,
+            "column": location.get("columnNumber").and_then(JsonValue::as_u64).unwrap_or_default() + 1,
+        })
+    }
+
+    /// 把 CDP RemoteObject 转换为 Python 对象：能按值传递的对象转换为对应的值，否则为描述字符串
+    fn remote_to_python<'py>(&self, py: Python<'py>, object: &JsonValue) -> PyResult<Bound<'py, PyAny>> {
+        if let Some(value) = object.get("value") {
+            return json_to_python(py, value);
+        }
+        if let Some(object_id) = object.get("objectId") {
+            let result = self.send(
+                "Runtime.callFunctionOn",
+                json!({
+                    "functionDeclaration": "function() { return this; }",
+                    "objectId": object_id,
+                    "returnByValue": true,
+                    "silent": true,
+                }),
+            )?;
+            if result.get("exceptionDetails").is_none() {
+                if let Some(value) = result["result"].get("value") {
+                    return json_to_python(py, value);
+                }
+            }
+        }
+        match object.get("type").and_then(JsonValue::as_str) {
+            Some("undefined") | None => Ok(py.None().into_bound(py)),
+            _ => {
+                let description = object
+                    .get("unserializableValue")
+                    .or_else(|| object.get("description"))
+                    .and_then(JsonValue::as_str)
+                    .unwrap_or_default();
+                Ok(description.into_pyobject(py)?.into_any())
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl PausedFrame {
+    /// 暂停原因："debugger"（debugger 语句）、"breakpoint"、"step"、"start"（pause_on_start）
+    #[getter]
+    fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// 暂停位置所在脚本的名称
+    #[getter]
+    fn url(&self) -> PyResult<String> {
+        let location = self.location(self.call_frame(0)?);
+        Ok(location["url"].as_str().unwrap_or_default().to_string())
+    }
+
+    /// 暂停位置的行号（从 1 开始）
+    #[getter]
+    fn line(&self) -> PyResult<u64> {
+        Ok(self.location(self.call_frame(0)?)["line"].as_u64().unwrap_or_default())
+    }
+
+    /// 暂停位置的列号（从 1 开始）
+    #[getter]
+    fn column(&self) -> PyResult<u64> {
+        Ok(self.location(self.call_frame(0)?)["column"].as_u64().unwrap_or_default())
+    }
+
+    /// 暂停位置所在的函数名（顶层代码为空字符串）
+    #[getter]
+    fn function(&self) -> PyResult<String> {
+        let location = self.location(self.call_frame(0)?);
+        Ok(location["function"].as_str().unwrap_or_default().to_string())
+    }
+
+    /// 调用栈，从暂停位置开始，每项为 {"function", "url", "line", "column"}
+    #[getter]
+    fn call_stack<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let frames: Vec<JsonValue> = self.call_frames().iter().map(|frame| self.location(frame)).collect();
+        json_to_python(py, &JsonValue::Array(frames))
+    }
+
+    /// 命中的断点 ID（set_breakpoint() 的返回值）
+    #[getter]
+    fn hit_breakpoints(&self) -> Vec<String> {
+        let start = self.state.start_breakpoint.borrow();
+        self.params
+            .get("hitBreakpoints")
+            .and_then(JsonValue::as_array)
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(JsonValue::as_str)
+                    .filter(|id| Some(*id) != start.as_deref())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 在调用帧上求值表达式，可以访问该帧的局部变量和闭包变量
+    ///
+    /// Args:
+    ///     expression: JavaScript 表达式
+    ///     frame: 调用帧序号，0 为暂停位置所在的函数
+    ///
+    /// Returns:
+    ///     表达式的值（无法按值传递的对象返回描述字符串）
+    #[pyo3(signature = (expression, frame=0))]
+    fn evaluate<'py>(&self, py: Python<'py>, expression: &str, frame: usize) -> PyResult<Bound<'py, PyAny>> {
+        self.check_valid()?;
+        let call_frame_id = self.call_frame(frame)?["callFrameId"].clone();
+        let result = self.send(
+            "Debugger.evaluateOnCallFrame",
+            json!({ "callFrameId": call_frame_id, "expression": expression, "silent": true }),
+        )?;
+        if let Some(details) = result.get("exceptionDetails") {
+            let message = details
+                .get("exception")
+                .and_then(|exception| exception.get("description"))
+                .or_else(|| details.get("text"))
+                .and_then(JsonValue::as_str)
+                .unwrap_or("unknown error");
+            return Err(PyException::new_err(format!("Evaluate error: {}", message)));
+        }
+        self.remote_to_python(py, &result["result"])
+    }
+
+    /// 调用帧的作用域链（不含全局作用域）
+    ///
+    /// Returns:
+    ///     列表，从内到外每项为 {"type": "local" / "block" / "closure" / ..., "name": 函数名, "variables": {变量名: 值}}
+    #[pyo3(signature = (frame=0))]
+    fn scopes<'py>(&self, py: Python<'py>, frame: usize) -> PyResult<Bound<'py, PyList>> {
+        self.check_valid()?;
+        let scopes = PyList::empty(py);
+        let chain = self.call_frame(frame)?.get("scopeChain").and_then(JsonValue::as_array).cloned().unwrap_or_default();
+        for scope in chain {
+            let kind = scope.get("type").and_then(JsonValue::as_str).unwrap_or_default();
+            if kind == "global" {
+                continue;
+            }
+            let variables = PyDict::new(py);
+            if let Some(object_id) = scope["object"].get("objectId") {
+                let result = self.send("Runtime.getProperties", json!({ "objectId": object_id, "ownProperties": true }))?;
+                for property in result.get("result").and_then(JsonValue::as_array).into_iter().flatten() {
+                    let Some(name) = property.get("name").and_then(JsonValue::as_str) else { continue };
+                    let value = match property.get("value") {
+                        Some(value) => self.remote_to_python(py, value)?,
+                        None => py.None().into_bound(py),
+                    };
+                    variables.set_item(name, value)?;
+                }
+            }
+            let entry = PyDict::new(py);
+            entry.set_item("type", kind)?;
+            entry.set_item("name", scope.get("name").and_then(JsonValue::as_str).unwrap_or_default())?;
+            entry.set_item("variables", variables)?;
+            scopes.append(entry)?;
+        }
+        Ok(scopes)
+    }
+
+    /// 调用帧中可见的变量（作用域链合并，内层变量遮蔽外层，不含全局变量）
+    #[pyo3(signature = (frame=0))]
+    fn locals<'py>(&self, py: Python<'py>, frame: usize) -> PyResult<Bound<'py, PyDict>> {
+        let merged = PyDict::new(py);
+        for scope in self.scopes(py, frame)?.iter().collect::<Vec<_>>().into_iter().rev() {
+            let variables = scope.get_item("variables")?.cast_into::<PyDict>()?;
+            merged.update(variables.as_mapping())?;
+        }
+        Ok(merged)
+    }
+
+    fn __repr__(&self) -> String {
+        match self.call_frames().first() {
+            Some(frame) => {
+                let location = self.location(frame);
+                format!(
+                    "<PausedFrame {} at {}:{}:{} in {}>",
+                    self.reason,
+                    location["url"].as_str().unwrap_or_default(),
+                    location["line"],
+                    location["column"],
+                    location["function"].as_str().filter(|name| !name.is_empty()).unwrap_or("<anonymous>"),
+                )
+            }
+            None => format!("<PausedFrame {}>", self.reason),
+        }
+    }
+}
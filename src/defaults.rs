@@ -0,0 +1,126 @@
+// defaults.rs - 全局默认限制（set_defaults()）
+//
+// 默认值在 Context::new 中应用，覆盖所有新建的 Context：Context / ThreadedContext / ContextPool /
+// SnapshotPool 的工作 Context，以及模块级 eval() / aeval() 的隐式 Context。
+// 创建 Context 时显式传入的参数优先，默认值只填补未设置的项；已创建的 Context 不受影响。
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::sync::Mutex;
+
+use crate::context::ContextOptions;
+use crate::quota::QuotaLimits;
+
+/// 全局默认限制（None 表示不设置默认值）
+#[derive(Clone, Copy, Default)]
+struct Defaults {
+    timeout_ms: Option<u64>,
+    cpu_limit_ms: Option<u64>,
+    max_heap_mb: Option<usize>,
+    max_result_bytes: Option<usize>,
+}
+
+static DEFAULTS: Mutex<Defaults> = Mutex::new(Defaults {
+    timeout_ms: None,
+    cpu_limit_ms: None,
+    max_heap_mb: None,
+    max_result_bytes: None,
+});
+
+/// 用全局默认值填补 ContextOptions 中未设置的限制
+pub fn apply(options: &mut ContextOptions) {
+    let defaults = *DEFAULTS.lock().unwrap();
+    options.timeout_ms = options.timeout_ms.or(defaults.timeout_ms);
+    options.cpu_limit_ms = options.cpu_limit_ms.or(defaults.cpu_limit_ms);
+    if options.max_heap_mb.is_none() {
+        // 显式的 initial_heap_mb 大于默认上限时保留原行为，不让默认值导致创建失败
+        options.max_heap_mb = defaults
+            .max_heap_mb
+            .filter(|max| options.initial_heap_mb.is_none_or(|initial| initial <= *max));
+    }
+    if let Some(max_result_bytes) = defaults.max_result_bytes {
+        let quotas = options.quotas.get_or_insert_with(QuotaLimits::default);
+        quotas.max_result_bytes = quotas.max_result_bytes.or(Some(max_result_bytes));
+    }
+}
+
+/// 合并同一限制的两种单位（timeout / timeout_ms、max_result_mb / max_result_bytes），不能同时传入
+fn either<T>(name: &str, alias: &str, value: Option<T>, converted: Option<T>) -> PyResult<Option<T>> {
+    match (value, converted) {
+        (Some(_), Some(_)) => Err(PyValueError::new_err(format!("Pass either {} or {}, not both", name, alias))),
+        (value, converted) => Ok(value.or(converted)),
+    }
+}
+
+/// timeout（秒）转换为毫秒，不足 1 毫秒的部分向上取整
+fn timeout_to_ms(timeout: f64) -> PyResult<u64> {
+    if !timeout.is_finite() || timeout <= 0.0 {
+        return Err(PyValueError::new_err("timeout must be a positive number of seconds"));
+    }
+    Ok((timeout * 1000.0).ceil() as u64)
+}
+
+/// max_result_mb 转换为字节数
+fn mb_to_bytes(mb: usize) -> PyResult<usize> {
+    mb.checked_mul(1024 * 1024)
+        .ok_or_else(|| PyValueError::new_err("max_result_mb is too large"))
+}
+
+/// 设置所有新建 Context 的默认限制
+///
+/// 每次调用替换全部默认值：未传入的参数不再有默认值，不带参数调用清除所有默认值。
+/// 创建 Context 时显式传入的参数优先；已创建的 Context 不受影响，
+/// 隐式 Context（eval() / aeval()）在下一次使用时按新的默认值重新创建。
+///
+/// Args:
+///     timeout_ms: 每次调用的墙钟超时（毫秒），同 Context(timeout_ms=...)
+///     cpu_limit_ms: 每次调用的 CPU 时间预算（毫秒），同 Context(cpu_limit_ms=...)
+///     max_heap_mb: 最大堆大小（MB），同 Context(max_heap_mb=...)
+///     max_result_bytes: 单次结果的最大字节数，同 Context(quotas={'max_result_bytes': ...})，
+///                       与 quotas 的其他项合并
+///     timeout: 同 timeout_ms，单位为秒（可以是小数），不能与 timeout_ms 同时传入
+///     max_result_mb: 同 max_result_bytes，单位为 MB，不能与 max_result_bytes 同时传入
+///
+/// Raises:
+///     ValueError: 参数为 0 / 非正数，同时传入同一限制的两种单位，或当前平台不支持 cpu_limit_ms
+///
+/// Example:
+///     ```python
+///     never_jscore.set_defaults(timeout=10, max_heap_mb=256, max_result_mb=64)
+///
+///     ctx = never_jscore.Context()              # 继承上面的限制
+///     ctx = never_jscore.Context(timeout_ms=60_000)  # 显式参数优先
+///     never_jscore.eval("while (true) {}")    # 10 秒后抛出超时异常
+///     ```
+#[pyfunction]
+#[pyo3(signature = (timeout_ms=None, cpu_limit_ms=None, max_heap_mb=None, max_result_bytes=None, timeout=None, max_result_mb=None))]
+pub fn set_defaults(
+    timeout_ms: Option<u64>,
+    cpu_limit_ms: Option<u64>,
+    max_heap_mb: Option<usize>,
+    max_result_bytes: Option<usize>,
+    timeout: Option<f64>,
+    max_result_mb: Option<usize>,
+) -> PyResult<()> {
+    let timeout_ms = either("timeout_ms", "timeout", timeout_ms, timeout.map(timeout_to_ms).transpose()?)?;
+    let max_result_bytes = either(
+        "max_result_bytes",
+        "max_result_mb",
+        max_result_bytes,
+        max_result_mb.map(mb_to_bytes).transpose()?,
+    )?;
+    if [timeout_ms, cpu_limit_ms].contains(&Some(0)) || [max_heap_mb, max_result_bytes].contains(&Some(0)) {
+        return Err(PyValueError::new_err("Default limits must be at least 1"));
+    }
+    if cpu_limit_ms.is_some() && !crate::watchdog::cpu_limit_supported() {
+        return Err(PyValueError::new_err("cpu_limit_ms is not supported on this platform"));
+    }
+    *DEFAULTS.lock().unwrap() = Defaults {
+        timeout_ms,
+        cpu_limit_ms,
+        max_heap_mb,
+        max_result_bytes,
+    };
+    crate::eval_context::invalidate();
+    Ok(())
+}
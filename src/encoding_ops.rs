@@ -1,4 +1,8 @@
 // encoding_ops.rs - Encoding operations for JS reverse engineering
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::prelude::*;
 use deno_core::{extension, op2};
 
 // ============================================
@@ -64,6 +68,95 @@ pub fn op_decode_uri_component(#[string] input: String) -> String {
     }
 }
 
+// ============================================
+// Byte Codecs (__codecs, atob / btoa)
+// ============================================
+
+/// Decoder for the WHATWG forgiving-base64 algorithm (padding already stripped, trailing bits ignored)
+const FORGIVING: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::RequireNone)
+        .with_decode_allow_trailing_bits(true),
+);
+
+/// Forgiving base64 decode: ASCII whitespace ignored, padding optional;
+/// url_safe also accepts the '-' / '_' alphabet. None when the input is not valid base64.
+fn forgiving_base64_decode(input: &str, url_safe: bool) -> Option<Vec<u8>> {
+    let mut data: Vec<u8> = input
+        .bytes()
+        .filter(|b| !matches!(b, b' ' | b'\t' | b'\n' | b'\x0c' | b'\r'))
+        .map(|b| match b {
+            b'-' if url_safe => b'+',
+            b'_' if url_safe => b'/',
+            b => b,
+        })
+        .collect();
+    if data.len().is_multiple_of(4) {
+        for _ in 0..2 {
+            if data.last() == Some(&b'=') {
+                data.pop();
+            }
+        }
+    }
+    if data.len() % 4 == 1 {
+        return None;
+    }
+    FORGIVING.decode(&data).ok()
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+#[op2]
+#[string]
+/// Base64 encode bytes (url_safe: '-' / '_' alphabet; pad: '=' padding)
+pub fn op_codec_b64_encode(#[buffer] data: &[u8], url_safe: bool, pad: bool) -> String {
+    match (url_safe, pad) {
+        (false, true) => BASE64_STANDARD.encode(data),
+        (false, false) => BASE64_STANDARD_NO_PAD.encode(data),
+        (true, true) => BASE64_URL_SAFE.encode(data),
+        (true, false) => BASE64_URL_SAFE_NO_PAD.encode(data),
+    }
+}
+
+#[op2]
+#[buffer]
+/// Base64 decode to bytes (standard and URL-safe alphabet, padding optional)
+pub fn op_codec_b64_decode(#[string] input: &str) -> Result<Vec<u8>, std::io::Error> {
+    forgiving_base64_decode(input, true).ok_or_else(|| invalid_data("The string to be decoded is not correctly encoded."))
+}
+
+#[op2]
+#[string]
+/// Hex encode bytes (lowercase)
+pub fn op_codec_hex_encode(#[buffer] data: &[u8]) -> String {
+    hex::encode(data)
+}
+
+#[op2]
+#[buffer]
+/// Hex decode to bytes (either case)
+pub fn op_codec_hex_decode(#[string] input: &str) -> Result<Vec<u8>, std::io::Error> {
+    hex::decode(input).map_err(|e| invalid_data(&format!("Invalid hex string: {}", e)))
+}
+
+#[op2]
+#[string]
+/// btoa(): base64 of a Latin-1 string; None when a character is outside Latin-1
+pub fn op_codec_btoa(#[string] input: &str) -> Option<String> {
+    let bytes: Option<Vec<u8>> = input.chars().map(|c| u8::try_from(c).ok()).collect();
+    bytes.map(|bytes| BASE64_STANDARD.encode(bytes))
+}
+
+#[op2]
+#[string]
+/// atob(): forgiving base64 decode to a Latin-1 string; None when the input is not valid base64
+pub fn op_codec_atob(#[string] input: &str) -> Option<String> {
+    forgiving_base64_decode(input, false).map(|bytes| bytes.into_iter().map(char::from).collect())
+}
+
 // ============================================
 // Extension Definition
 // ============================================
@@ -76,5 +169,11 @@ extension!(
         op_encode_uri_component,
         op_encode_uri,
         op_decode_uri_component,
+        op_codec_b64_encode,
+        op_codec_b64_decode,
+        op_codec_hex_encode,
+        op_codec_hex_decode,
+        op_codec_btoa,
+        op_codec_atob,
     ],
 );
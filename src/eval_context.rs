@@ -0,0 +1,558 @@
+// eval_context.rs - 模块级 eval() 使用的隐式 Context
+//
+// never_jscore.eval(code) 不需要先创建 Context：每个线程懒加载一个共享的 Context（EVAL_CONTEXT），
+// 之后的调用复用它。Context 是 unsendable 的，所以按线程保存（thread_local）。
+//
+// 配置（预设、浏览器环境、初始化代码）是全局的，修改配置或 clear_eval_cache() 会递增版本号，
+// 各线程在下一次使用时发现版本号变化，丢弃旧 Context 并重新创建。
+//
+// eval(code, isolated=True) 每次使用一个全新的临时 Context，执行完即销毁。
+// 配置了 init_code 时，初始化后的堆会被做成启动快照（按配置版本缓存），
+// 临时 Context 直接从快照启动，不必每次重新执行初始化代码。
+//
+// aeval() / acall() 是 asyncio 版本：在事件循环线程上执行 JS 会阻塞事件循环，
+// 所以使用进程内共享的一个后台工作线程（ASYNC_EVAL_WORKER，与 ThreadedContext 相同的机制），
+// 它与各线程的 eval() Context 是不同的 Context，全局变量不互通。
+// init_code 作为工作线程的第一个任务执行，启动时不会阻塞事件循环。
+
+use once_cell::sync::Lazy;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::cell::RefCell;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::JoinHandle;
+
+use crate::abort::AbortToken;
+use crate::aio::AsyncResult;
+use crate::context::{Context, ContextOptions};
+use crate::convert::call_args_to_json;
+use crate::pool::{Task, spawn_worker};
+use crate::snapshot::OwnedBlob;
+
+/// 隐式 Context 的预设环境
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EvalPreset {
+    /// 启用全部扩展（与 Context() 默认一致）
+    Default,
+    /// 纯净 V8 环境（Context(enable_extensions=False)）
+    Pure,
+    /// 启用全部扩展，并按 user_agent / url 设置 navigator / location / document
+    Browser,
+}
+
+impl EvalPreset {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "default" => Ok(EvalPreset::Default),
+            "pure" => Ok(EvalPreset::Pure),
+            "browser" => Ok(EvalPreset::Browser),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown eval preset '{}', expected 'default', 'browser' or 'pure'",
+                name
+            ))),
+        }
+    }
+}
+
+/// 隐式 Context 的全局配置
+#[derive(Clone, Debug)]
+pub struct EvalConfig {
+    pub preset: EvalPreset,
+    /// 创建 Context 后执行的初始化代码
+    pub init_code: Option<String>,
+    /// browser 预设的 navigator.userAgent
+    pub user_agent: Option<String>,
+    /// browser 预设的页面地址（location / document.URL）
+    pub url: Option<String>,
+    /// 配置版本号，变化后各线程重新创建 Context
+    version: u64,
+}
+
+impl EvalConfig {
+    /// 新建 Context 后要执行的代码：browser 预设的环境设置 + init_code
+    fn setup_code(&self) -> Option<String> {
+        let browser = (self.preset == EvalPreset::Browser && (self.user_agent.is_some() || self.url.is_some()))
+            .then(|| format!("{}({}, {});", BROWSER_SETUP, json_or_null(&self.user_agent), json_or_null(&self.url)));
+        match (browser, &self.init_code) {
+            (Some(browser), Some(code)) => Some(format!("{}\n{}", browser, code)),
+            (browser, code) => browser.or_else(|| code.clone()),
+        }
+    }
+}
+
+fn json_or_null(value: &Option<String>) -> String {
+    value.as_ref().map_or_else(|| "null".to_string(), |v| serde_json::Value::from(v.as_str()).to_string())
+}
+
+// browser 预设：覆盖 polyfill 提供的 navigator / location / document 默认值
+const BROWSER_SETUP: &str = r#"
+(function(userAgent, url) {
+    function set(obj, key, value) {
+        Object.defineProperty(obj, key, { value: value, writable: true, enumerable: true, configurable: true });
+    }
+    if (userAgent !== null) {
+        set(navigator, 'userAgent', userAgent);
+        set(navigator, 'appVersion', userAgent.replace(/^Mozilla\//, ''));
+    }
+    if (url !== null) {
+        const parsed = new URL(url);
+        for (const key of ['href', 'protocol', 'host', 'hostname', 'port', 'pathname', 'search', 'hash', 'origin']) {
+            set(location, key, parsed[key]);
+        }
+        set(document, 'URL', parsed.href);
+        set(document, 'domain', parsed.hostname);
+    }
+})
+"#;
+
+static EVAL_CONFIG: Lazy<Mutex<EvalConfig>> = Lazy::new(|| {
+    Mutex::new(EvalConfig {
+        preset: EvalPreset::Default,
+        init_code: None,
+        user_agent: None,
+        url: None,
+        version: 0,
+    })
+});
+
+/// 隔离求值使用的启动快照：(配置版本号, 快照)
+///
+/// 每个配置只保留一份：配置变化后换成新的快照，旧快照在使用它的临时 Context 都销毁后释放。
+type VersionedSnapshot = Option<(u64, Arc<OwnedBlob>)>;
+
+static ISOLATED_SNAPSHOT: Lazy<Mutex<VersionedSnapshot>> = Lazy::new(|| Mutex::new(None));
+
+thread_local! {
+    /// 当前线程的隐式 Context 及其创建时的配置版本号
+    static EVAL_CONTEXT: RefCell<Option<(u64, Py<Context>)>> = const { RefCell::new(None) };
+}
+
+/// aeval() / acall() 使用的后台工作线程
+struct AsyncEvalWorker {
+    /// 创建时的配置版本号
+    version: u64,
+    /// 创建时的 fork 代数（fork 后的子进程中重新创建）
+    fork_generation: usize,
+    sender: Sender<Task>,
+    worker: JoinHandle<()>,
+    /// 配置中的初始化代码（包括 browser 预设的环境设置）
+    init_code: Option<String>,
+    /// 初始化代码的执行结果（在工作线程上第一次执行任务时设置）
+    init_result: Arc<OnceLock<Result<(), String>>>,
+}
+
+static ASYNC_EVAL_WORKER: Lazy<Mutex<Option<AsyncEvalWorker>>> = Lazy::new(|| Mutex::new(None));
+
+/// 根据配置创建新的隐式 Context
+fn create_eval_context(py: Python<'_>, config: &EvalConfig) -> PyResult<Py<Context>> {
+    crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+    let options = ContextOptions {
+        enable_extensions: config.preset != EvalPreset::Pure,
+        ..Default::default()
+    };
+    let context = Context::new(options)?;
+
+    if let Some(code) = config.setup_code() {
+        context
+            .exec_script(code)
+            .map_err(|e| PyRuntimeError::new_err(format!("Eval init code error: {}", e)))?;
+    }
+
+    Py::new(py, context)
+}
+
+/// 获取隔离求值使用的启动快照（包含 init_code 执行后的堆）
+///
+/// 没有 init_code（和 browser 预设的环境设置）时返回 None，临时 Context 使用默认的内置快照即可。
+/// 快照在锁外构建，构建期间其他线程的隔离求值不会被阻塞。
+fn isolated_snapshot(config: &EvalConfig) -> PyResult<Option<Arc<OwnedBlob>>> {
+    let Some(code) = config.setup_code() else {
+        return Ok(None);
+    };
+
+    if let Some((version, blob)) = ISOLATED_SNAPSHOT.lock().unwrap().as_ref() {
+        if *version == config.version {
+            return Ok(Some(blob.clone()));
+        }
+    }
+
+    let blob = crate::snapshot::create_snapshot_blob(&code, config.preset != EvalPreset::Pure, false)
+        .map_err(|e| PyRuntimeError::new_err(format!("Eval init code error: {}", e)))?;
+    let blob = OwnedBlob::new(blob.into_vec());
+
+    // 其他线程同时构建了同一个或更新的配置的快照时保留它，只替换旧配置的快照
+    let mut cached = ISOLATED_SNAPSHOT.lock().unwrap();
+    match cached.as_ref() {
+        Some((version, existing)) if *version >= config.version => {
+            if *version == config.version {
+                return Ok(Some(existing.clone()));
+            }
+        }
+        _ => *cached = Some((config.version, blob.clone())),
+    }
+    Ok(Some(blob))
+}
+
+/// 在全新的临时 Context 中求值，执行完即销毁
+fn eval_isolated<'py>(py: Python<'py>, code: String, auto_await: Option<bool>) -> PyResult<Bound<'py, PyAny>> {
+    let config = EVAL_CONFIG.lock().unwrap().clone();
+    crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+    let snapshot = isolated_snapshot(&config)?;
+    let options = ContextOptions {
+        enable_extensions: config.preset != EvalPreset::Pure,
+        snapshot: snapshot.as_ref().map(|blob| blob.blob()),
+        ..Default::default()
+    };
+    let mut context = Context::new(options)?;
+    if let Some(blob) = snapshot {
+        context.keep_snapshot_alive(blob);
+    }
+    let context = Bound::new(py, context)?;
+    Context::evaluate(&context, py, code, auto_await, false, false, None, None, None)
+}
+
+/// 获取当前线程的隐式 Context（不存在或配置已变化时重新创建）
+pub fn eval_context(py: Python<'_>) -> PyResult<Py<Context>> {
+    let config = EVAL_CONFIG.lock().unwrap().clone();
+
+    let cached = EVAL_CONTEXT.with(|cell| match cell.borrow().as_ref() {
+        Some((version, ctx)) if *version == config.version => Some(ctx.clone_ref(py)),
+        _ => None,
+    });
+    if let Some(ctx) = cached {
+        return Ok(ctx);
+    }
+
+    // 先丢弃旧 Context，避免同一线程上同时存在两个 isolate
+    let old = EVAL_CONTEXT.with(|cell| cell.borrow_mut().take());
+    drop(old);
+
+    let ctx = create_eval_context(py, &config)?;
+    EVAL_CONTEXT.with(|cell| *cell.borrow_mut() = Some((config.version, ctx.clone_ref(py))));
+    Ok(ctx)
+}
+
+/// 启动 aeval() / acall() 使用的后台工作线程
+fn spawn_async_worker(py: Python<'_>, config: &EvalConfig) -> PyResult<AsyncEvalWorker> {
+    crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+    let options = ContextOptions {
+        enable_extensions: config.preset != EvalPreset::Pure,
+        ..Default::default()
+    };
+    let (sender, receiver) = mpsc::channel::<Task>();
+    let worker = py
+        .detach(|| spawn_worker("never_jscore-aeval".to_string(), options, None, Arc::new(Mutex::new(receiver))))
+        .map_err(|e| PyRuntimeError::new_err(format!("Eval context error: {}", e)))?;
+
+    Ok(AsyncEvalWorker {
+        version: config.version,
+        fork_generation: crate::fork::generation(),
+        sender,
+        worker,
+        init_code: config.setup_code(),
+        init_result: Arc::new(OnceLock::new()),
+    })
+}
+
+/// 停止后台工作线程（等待已提交的任务完成）
+fn shutdown_async_worker(py: Python<'_>, worker: AsyncEvalWorker) {
+    drop(worker.sender);
+    if crate::fork::is_inherited(worker.fork_generation) {
+        // fork 后的子进程中工作线程并不存在，不能 join
+        std::mem::forget(worker.worker);
+        return;
+    }
+    // 工作线程投递 asyncio 结果需要 GIL
+    py.detach(|| {
+        let _ = worker.worker.join();
+    });
+}
+
+/// 工作线程是否需要重新创建（配置已变化，或是从父进程继承的）
+fn async_worker_stale(worker: &AsyncEvalWorker, config: &EvalConfig) -> bool {
+    worker.version != config.version || crate::fork::is_inherited(worker.fork_generation)
+}
+
+/// 提交任务所需的句柄：(发送端, 初始化代码, 初始化结果)
+type AsyncHandles = (Sender<Task>, Option<String>, Arc<OnceLock<Result<(), String>>>);
+
+fn async_handles(worker: &AsyncEvalWorker) -> AsyncHandles {
+    (worker.sender.clone(), worker.init_code.clone(), worker.init_result.clone())
+}
+
+/// 获取后台工作线程（不存在或已过期时重新创建）
+///
+/// 启动 / 停止工作线程时会释放 GIL，此时不能持有 ASYNC_EVAL_WORKER 的锁，
+/// 否则持有 GIL 等待锁的线程与等待 GIL 的工作线程会互相等待。
+fn async_worker(py: Python<'_>) -> PyResult<AsyncHandles> {
+    let config = EVAL_CONFIG.lock().unwrap().clone();
+
+    let old = {
+        let mut slot = ASYNC_EVAL_WORKER.lock().unwrap();
+        match slot.as_ref() {
+            Some(worker) if !async_worker_stale(worker, &config) => return Ok(async_handles(worker)),
+            Some(_) => slot.take(),
+            None => None,
+        }
+    };
+    if let Some(old) = old {
+        shutdown_async_worker(py, old);
+    }
+
+    let created = spawn_async_worker(py, &config)?;
+    let mut slot = ASYNC_EVAL_WORKER.lock().unwrap();
+    match slot.as_ref() {
+        // 其他线程已经创建了工作线程：使用它，丢弃刚创建的
+        Some(worker) if !async_worker_stale(worker, &config) => {
+            let handles = async_handles(worker);
+            drop(slot);
+            shutdown_async_worker(py, created);
+            Ok(handles)
+        }
+        _ => {
+            let handles = async_handles(&created);
+            let replaced = slot.replace(created);
+            drop(slot);
+            if let Some(replaced) = replaced {
+                shutdown_async_worker(py, replaced);
+            }
+            Ok(handles)
+        }
+    }
+}
+
+/// 在后台工作线程上执行任务（先执行初始化代码），返回 asyncio.Future
+fn submit_async<'py, F>(py: Python<'py>, abort: Option<&AbortToken>, f: F) -> PyResult<Bound<'py, PyAny>>
+where
+    F: FnOnce(&Context) -> AsyncResult + Send + 'static,
+{
+    let (sender, init_code, init_result) = async_worker(py)?;
+    crate::aio::submit(py, &sender, abort, move |ctx| {
+        let init = init_result.get_or_init(|| match init_code {
+            Some(code) => ctx.exec_script(code).map_err(|e| e.to_string()),
+            None => Ok(()),
+        });
+        if let Err(e) = init {
+            return Err(PyRuntimeError::new_err(format!("Eval init code error: {}", e)));
+        }
+        f(ctx)
+    })
+}
+
+/// 丢弃 aeval() / acall() 的后台工作线程（下一次使用时按当前配置重新创建）
+fn clear_async_worker(py: Python<'_>) {
+    let old = ASYNC_EVAL_WORKER.lock().unwrap().take();
+    if let Some(old) = old {
+        shutdown_async_worker(py, old);
+    }
+}
+
+/// 在当前线程的隐式 Context 中求值
+///
+/// 不需要先创建 Context，同一线程的多次调用共享同一个 Context（全局变量会保留）。
+/// 不能接受调用之间互相影响时使用 isolated=True。
+///
+/// Args:
+///     code: JavaScript 代码
+///     auto_await: 是否自动等待 Promise（默认 True）
+///     isolated: 是否在全新的临时 Context 中执行（默认 False）。
+///               临时 Context 从快照启动（包含 configure_eval 的 init_code），执行完即销毁，
+///               不会读到也不会留下任何全局状态
+///
+/// Returns:
+///     执行结果，自动转换为 Python 对象
+///
+/// Example:
+///     ```python
+///     import never_jscore
+///
+///     never_jscore.eval("1 + 2")           # 3
+///     never_jscore.eval("btoa('hello')")   # 'aGVsbG8='
+///
+///     never_jscore.eval("globalThis.x = 1", isolated=True)
+///     never_jscore.eval("typeof x", isolated=True)  # 'undefined'
+///     ```
+#[pyfunction]
+#[pyo3(signature = (code, auto_await=None, isolated=false))]
+pub fn eval<'py>(
+    py: Python<'py>,
+    code: String,
+    auto_await: Option<bool>,
+    isolated: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    if isolated {
+        return eval_isolated(py, code, auto_await);
+    }
+
+    let ctx = eval_context(py)?;
+    Context::evaluate(ctx.bind(py), py, code, auto_await, false, false, None, None, None)
+}
+
+/// eval() 的 asyncio 版本
+///
+/// 不需要先创建 Context：代码在进程内共享的后台线程中执行，等待期间不阻塞事件循环。
+/// 后台 Context 同样使用 configure_eval() 的配置，但与 eval() 的 Context 不是同一个，
+/// 全局变量不互通；多次 aeval() / acall() 之间共享同一个 Context，按提交顺序执行。
+///
+/// Args:
+///     code: JavaScript 代码
+///     auto_await: 是否自动等待 Promise（默认 True）
+///     abort: AbortToken，中止时终止执行（等待的任务被取消时同样终止）
+///
+/// Returns:
+///     asyncio.Future，结果自动转换为 Python 对象
+///
+/// Example:
+///     ```python
+///     import asyncio
+///     import never_jscore
+///
+///     async def main():
+///         await never_jscore.aeval("function sign(x) { return btoa(x); }")
+///         print(await never_jscore.acall("sign", ["data"]))  # 'ZGF0YQ=='
+///
+///     asyncio.run(main())
+///     ```
+#[pyfunction]
+#[pyo3(signature = (code, auto_await=None, abort=None))]
+pub fn aeval<'py>(
+    py: Python<'py>,
+    code: String,
+    auto_await: Option<bool>,
+    abort: Option<PyRef<'_, AbortToken>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let auto_await = auto_await.unwrap_or(true);
+    submit_async(py, abort.as_deref(), move |ctx| {
+        ctx.execute_js(code, auto_await)
+            .map(Some)
+            .map_err(|e| crate::quota::py_error("Evaluate error", e))
+    })
+}
+
+/// 在 aeval() 使用的后台 Context 中调用函数（asyncio 版本）
+///
+/// Args:
+///     name: 函数名（可以是任意可调用表达式，如 `obj.method`）
+///     args: 参数列表
+///     auto_await: 是否自动等待 Promise（默认 True）
+///     abort: AbortToken，同 aeval()
+///
+/// Returns:
+///     asyncio.Future，结果自动转换为 Python 对象
+#[pyfunction]
+#[pyo3(signature = (name, args, auto_await=None, abort=None))]
+pub fn acall<'py>(
+    py: Python<'py>,
+    name: String,
+    args: &Bound<'_, PyAny>,
+    auto_await: Option<bool>,
+    abort: Option<PyRef<'_, AbortToken>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let args = call_args_to_json(args)?;
+    let auto_await = auto_await.unwrap_or(true);
+    submit_async(py, abort.as_deref(), move |ctx| {
+        ctx.execute_call(&name, &args, auto_await)
+            .map(Some)
+            .map_err(|e| crate::quota::py_error("Call error", e))
+    })
+}
+
+/// 丢弃隐式 Context，清除之前 eval() / aeval() 留下的全局状态
+///
+/// 当前线程的 Context 和 aeval() 的后台 Context 立即释放（等待已提交的 aeval() 完成）；
+/// 其他线程的 Context 在下一次使用时重新创建。
+#[pyfunction]
+pub fn clear_eval_cache(py: Python<'_>) {
+    EVAL_CONFIG.lock().unwrap().version += 1;
+    let old = EVAL_CONTEXT.with(|cell| cell.borrow_mut().take());
+    drop(old);
+    clear_async_worker(py);
+}
+
+/// 让所有线程的隐式 Context 在下一次使用时重新创建（set_defaults() 修改默认限制后调用）
+pub(crate) fn invalidate() {
+    EVAL_CONFIG.lock().unwrap().version += 1;
+    let old = EVAL_CONTEXT.with(|cell| cell.borrow_mut().take());
+    drop(old);
+}
+
+/// 配置隐式 Context
+///
+/// 修改后所有线程的隐式 Context（包括 aeval() 的后台 Context）在下一次使用时按新配置重新创建。
+/// 未传入的参数保持不变。
+///
+/// Args:
+///     preset: 预设环境
+///             - "default": 启用全部扩展（与 Context() 一致）
+///             - "browser": 与 default 相同，并按 user_agent / url 设置 navigator / location / document
+///             - "pure": 纯净 V8 环境（与 Context(enable_extensions=False) 一致）
+///     init_code: 创建 Context 后执行的初始化代码（如要加载的 JS 库），传入空字符串清除
+///     user_agent: browser 预设的 navigator.userAgent，传入空字符串清除
+///     url: browser 预设的页面地址（location 和 document.URL / domain），传入空字符串清除
+///
+/// Raises:
+///     ValueError: 未知的预设名称，或在 browser 以外的预设下设置 user_agent / url
+///
+/// Example:
+///     ```python
+///     never_jscore.configure_eval(init_code="function sign(x) { return md5(x); }")
+///     never_jscore.eval("sign('data')")
+///
+///     never_jscore.configure_eval(preset="browser", user_agent="Mozilla/5.0 ...", url="https://example.com/login")
+///     never_jscore.eval("location.hostname")  # 'example.com'
+///     ```
+#[pyfunction]
+#[pyo3(signature = (preset=None, init_code=None, user_agent=None, url=None))]
+pub fn configure_eval(
+    preset: Option<&str>,
+    init_code: Option<String>,
+    user_agent: Option<String>,
+    url: Option<String>,
+) -> PyResult<()> {
+    let preset = preset.map(EvalPreset::parse).transpose()?;
+
+    {
+        let mut config = EVAL_CONFIG.lock().unwrap();
+        let browser = preset.unwrap_or(config.preset) == EvalPreset::Browser;
+        if !browser && (user_agent.as_deref().is_some_and(|ua| !ua.is_empty()) || url.as_deref().is_some_and(|url| !url.is_empty())) {
+            return Err(PyValueError::new_err("user_agent and url require preset='browser'"));
+        }
+        if let Some(preset) = preset {
+            config.preset = preset;
+        }
+        if let Some(code) = init_code {
+            config.init_code = Some(code).filter(|code| !code.is_empty());
+        }
+        if let Some(user_agent) = user_agent {
+            config.user_agent = Some(user_agent).filter(|ua| !ua.is_empty());
+        }
+        if let Some(url) = url {
+            config.url = Some(url).filter(|url| !url.is_empty());
+        }
+        config.version += 1;
+    }
+
+    let old = EVAL_CONTEXT.with(|cell| cell.borrow_mut().take());
+    drop(old);
+    Ok(())
+}
+
+/// 获取当前线程的隐式 Context
+///
+/// 返回的是 eval() 使用的同一个 Context，可以用来检查状态、调用函数、查看堆统计等。
+/// 不存在时会创建。
+///
+/// Example:
+///     ```python
+///     never_jscore.eval("globalThis.counter = 1")
+///     ctx = never_jscore.get_eval_context()
+///     ctx.evaluate("counter")  # 1
+///     ```
+#[pyfunction]
+pub fn get_eval_context(py: Python<'_>) -> PyResult<Py<Context>> {
+    eval_context(py)
+}
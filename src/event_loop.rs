@@ -0,0 +1,108 @@
+// event_loop.rs - eval() / evaluate() / call() 的 event_loop={...}：单次调用的事件循环控制
+//
+// 自动等待 Promise 时，调用会一直运行事件循环，直到没有待处理的定时器和 Promise。
+// 脚本注册了永不停止的 setInterval / 递归 setTimeout 时，调用只能靠 timeout_ms 结束。
+// event_loop 字典为单次调用调整事件循环：
+// - max_iterations: 事件循环最多被 poll 的次数。每次 poll 运行到期的定时器和微任务，
+//   但事件循环被唤醒时不一定有任务到期（未到期的定时器、ops、V8 平台任务都可能唤醒它），
+//   这些空转的 poll 同样计数，因此它限制的是 poll 次数而不是定时器回调的次数。达到上限时停止等待：
+//   结果已经就绪（或 eval() 不需要返回值）时正常返回，剩余的定时器保留到之后的调用；
+//   结果仍未就绪时调用失败
+// - pump_message_loop: 是否处理 V8 平台任务（WebAssembly 后台编译、Atomics.waitAsync、
+//   FinalizationRegistry 回调等），默认 True
+// - wait_for_inspector: 有调试器会话时等待其断开后才结束（deno_core 的 PollEventLoopOptions），默认 False；
+//   没有连接 inspector 时不起作用
+//
+// 选项只作用于本次调用，结束后恢复默认值。
+
+use anyhow::{anyhow, Result};
+use deno_core::{JsRuntime, PollEventLoopOptions};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::cell::RefCell;
+use std::future::poll_fn;
+use std::task::Poll;
+
+/// 单次调用的事件循环选项
+#[derive(Clone, Copy, Debug)]
+pub struct EventLoopOptions {
+    pub max_iterations: Option<u64>,
+    pub pump_message_loop: bool,
+    pub wait_for_inspector: bool,
+}
+
+impl Default for EventLoopOptions {
+    fn default() -> Self {
+        Self {
+            max_iterations: None,
+            pump_message_loop: true,
+            wait_for_inspector: false,
+        }
+    }
+}
+
+impl EventLoopOptions {
+    /// 从 Python 字典解析（未知的键报错）
+    pub fn from_py(dict: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut options = EventLoopOptions::default();
+        let Some(dict) = dict else {
+            return Ok(options);
+        };
+        for (key, value) in dict.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "max_iterations" => {
+                    let max: u64 = value
+                        .extract()
+                        .ok()
+                        .filter(|max| *max > 0)
+                        .ok_or_else(|| PyValueError::new_err("event_loop['max_iterations'] must be a positive integer"))?;
+                    options.max_iterations = Some(max);
+                }
+                "pump_message_loop" => options.pump_message_loop = value.is_truthy()?,
+                "wait_for_inspector" => options.wait_for_inspector = value.is_truthy()?,
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown event_loop option '{}', expected max_iterations / pump_message_loop / wait_for_inspector",
+                        key
+                    )))
+                }
+            }
+        }
+        Ok(options)
+    }
+
+    fn poll_options(&self) -> PollEventLoopOptions {
+        PollEventLoopOptions {
+            wait_for_inspector: self.wait_for_inspector,
+            pump_v8_message_loop: self.pump_message_loop,
+        }
+    }
+}
+
+/// 按选项运行事件循环，直到没有待处理的任务
+///
+/// 每次 poll 单独借用 runtime，等待期间不持有 RefCell 借用。
+/// ready() 表示本次调用的结果是否已就绪：background 为 true（后台事件循环模式）时结果就绪即返回；
+/// 达到 max_iterations（poll 次数，包括没有任务到期的 poll）时结果已就绪则正常返回，否则返回错误。
+pub async fn run(runtime: &RefCell<JsRuntime>, options: EventLoopOptions, ready: impl Fn() -> bool, background: bool) -> Result<()> {
+    let poll_options = options.poll_options();
+    let mut iterations = 0u64;
+    poll_fn(|cx| {
+        if let Some(max) = options.max_iterations.filter(|max| iterations >= *max) {
+            return Poll::Ready(if ready() {
+                Ok(())
+            } else {
+                Err(anyhow!("Event loop did not settle within max_iterations={}", max))
+            });
+        }
+        iterations += 1;
+        match runtime.borrow_mut().poll_event_loop(cx, poll_options) {
+            Poll::Ready(result) => Poll::Ready(result.map_err(anyhow::Error::from)),
+            Poll::Pending if background && ready() => Poll::Ready(Ok(())),
+            Poll::Pending => Poll::Pending,
+        }
+    })
+    .await
+}
@@ -0,0 +1,140 @@
+// exec_hooks.rs - 全局执行钩子（set_execution_hooks(on_execute_start=..., on_execute_end=...)）
+//
+// 钩子在每次执行 JS 前后调用（Context / ThreadedContext / ContextPool / SnapshotPool / Realm
+// 以及模块级 eval()，包括 asyncio 版本），参数为描述本次执行的字典：
+// - kind: "evaluate"（返回值的求值，包括 evaluate / call / eval(return_value=True)）
+//         或 "script"（compile / compile_file / eval 等执行脚本、不返回值）
+// - filename: 脚本名称（compile_file 为文件路径，求值为 "<eval>"）
+// - code_hash: 代码的 SHA-256（十六进制），code_length: 代码字节数
+// - trace_id: call(trace_id=...) 的追踪 ID（没有时为 None）
+// - on_execute_end 额外包含 duration_ms、success 和 error（失败时的错误信息）
+//
+// 钩子在执行 JS 的线程上调用（ThreadedContext / 池的工作线程会先获取 GIL），
+// 钩子抛出的异常不影响 JS 执行，只通过 sys.unraisablehook 报告。
+// 钩子内部再执行 JS 时不会再次触发钩子。
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use sha2::{Digest, Sha256};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+struct Hooks {
+    on_start: Option<Py<PyAny>>,
+    on_end: Option<Py<PyAny>>,
+}
+
+static HOOKS: Mutex<Hooks> = Mutex::new(Hooks {
+    on_start: None,
+    on_end: None,
+});
+
+/// 是否设置了任意钩子（未设置时不计算哈希、不获取 GIL）
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// 当前线程正在运行钩子
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 一次已触发 on_execute_start 的执行
+pub struct Execution {
+    kind: &'static str,
+    filename: String,
+    code_hash: String,
+    code_length: usize,
+    trace_id: Option<String>,
+    started: Instant,
+}
+
+/// 开始一次执行：调用 on_execute_start，未设置钩子时返回 None
+pub fn start(kind: &'static str, filename: &str, code: &str, trace_id: Option<&str>) -> Option<Execution> {
+    if !ENABLED.load(Ordering::Acquire) || IN_HOOK.with(Cell::get) {
+        return None;
+    }
+    let mut execution = Execution {
+        kind,
+        filename: filename.to_string(),
+        code_hash: hex::encode(Sha256::digest(code.as_bytes())),
+        code_length: code.len(),
+        trace_id: trace_id.map(str::to_string),
+        started: Instant::now(),
+    };
+    execution.fire(|hooks| hooks.on_start.as_ref(), None);
+    execution.started = Instant::now();
+    Some(execution)
+}
+
+impl Execution {
+    /// 执行结束：调用 on_execute_end
+    pub fn finish<R>(self, result: &anyhow::Result<R>) {
+        let error = result.as_ref().err().map(|e| e.to_string());
+        self.fire(|hooks| hooks.on_end.as_ref(), Some(error));
+    }
+
+    fn fire(&self, select: fn(&Hooks) -> Option<&Py<PyAny>>, end: Option<Option<String>>) {
+        Python::attach(|py| {
+            let Some(hook) = select(&HOOKS.lock().unwrap()).map(|hook| hook.clone_ref(py)) else {
+                return;
+            };
+            let result = self.to_python(py, end).and_then(|info| {
+                IN_HOOK.with(|flag| flag.set(true));
+                let result = hook.call1(py, (info,));
+                IN_HOOK.with(|flag| flag.set(false));
+                result
+            });
+            if let Err(e) = result {
+                e.write_unraisable(py, Some(hook.bind(py)));
+            }
+        });
+    }
+
+    fn to_python<'py>(&self, py: Python<'py>, end: Option<Option<String>>) -> PyResult<Bound<'py, PyDict>> {
+        let info = PyDict::new(py);
+        info.set_item("kind", self.kind)?;
+        info.set_item("filename", &self.filename)?;
+        info.set_item("code_hash", &self.code_hash)?;
+        info.set_item("code_length", self.code_length)?;
+        info.set_item("trace_id", &self.trace_id)?;
+        if let Some(error) = end {
+            info.set_item("duration_ms", self.started.elapsed().as_secs_f64() * 1000.0)?;
+            info.set_item("success", error.is_none())?;
+            info.set_item("error", error)?;
+        }
+        Ok(info)
+    }
+}
+
+/// 设置全局执行钩子
+///
+/// 钩子在每次执行 JS 前后调用，适合接入 APM / 自定义监控，不需要包装每个调用点。
+/// 传入 None 清除对应的钩子。
+///
+/// Args:
+///     on_execute_start: 执行前调用，参数为字典 {kind, filename, code_hash, code_length, trace_id}
+///     on_execute_end: 执行后调用，参数额外包含 duration_ms、success、error
+///
+/// Example:
+///     ```python
+///     def on_end(info):
+///         metrics.timing("js." + info["kind"], info["duration_ms"])
+///
+///     never_jscore.set_execution_hooks(on_execute_end=on_end)
+///     ```
+#[pyfunction]
+#[pyo3(signature = (on_execute_start=None, on_execute_end=None))]
+pub fn set_execution_hooks(on_execute_start: Option<Bound<'_, PyAny>>, on_execute_end: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+    for (name, hook) in [("on_execute_start", &on_execute_start), ("on_execute_end", &on_execute_end)] {
+        if hook.as_ref().is_some_and(|hook| !hook.is_callable()) {
+            return Err(PyTypeError::new_err(format!("{} must be callable or None", name)));
+        }
+    }
+    let mut hooks = HOOKS.lock().unwrap();
+    hooks.on_start = on_execute_start.map(Bound::unbind);
+    hooks.on_end = on_execute_end.map(Bound::unbind);
+    ENABLED.store(hooks.on_start.is_some() || hooks.on_end.is_some(), Ordering::Release);
+    Ok(())
+}
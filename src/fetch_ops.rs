@@ -1,10 +1,12 @@
 // fetch_ops.rs - HTTP Fetch operations for Node.js compatibility
-use deno_core::{extension, op2};
+use deno_core::{extension, op2, OpState};
 use reqwest::blocking::Client;
 use serde_json::{json, Value as JsonValue};
 use std::collections::HashMap;
 use std::time::Duration;
 
+use crate::permissions;
+
 // ============================================
 // HTTP Fetch Operations
 // ============================================
@@ -33,7 +35,18 @@ use std::time::Duration;
 ///   "body": "response body text",
 ///   "error": null
 /// }
-pub fn op_fetch(#[string] url: String, #[string] options_json: String) -> String {
+pub fn op_fetch(state: &mut OpState, #[string] url: String, #[string] options_json: String) -> String {
+    if let Err(denied) = permissions::check_net(state, &url) {
+        return json!({
+            "ok": false,
+            "status": 0,
+            "statusText": "",
+            "headers": {},
+            "body": "",
+            "error": denied
+        }).to_string();
+    }
+
     // 解析请求选项
     let options: JsonValue = match serde_json::from_str(&options_json) {
         Ok(opts) => opts,
@@ -55,9 +68,10 @@ pub fn op_fetch(#[string] url: String, #[string] options_json: String) -> String
     let body = options["body"].as_str().unwrap_or("");
     let timeout_ms = options["timeout"].as_u64().unwrap_or(30000);
 
-    // 创建 HTTP 客户端
+    // 创建 HTTP 客户端（每一次重定向都重新检查 net 权限）
     let client = match Client::builder()
         .timeout(Duration::from_millis(timeout_ms))
+        .redirect(permissions::redirect_policy(state))
         .build()
     {
         Ok(c) => c,
@@ -117,7 +131,7 @@ pub fn op_fetch(#[string] url: String, #[string] options_json: String) -> String
                 "statusText": "",
                 "headers": {},
                 "body": "",
-                "error": format!("Request failed: {}", e)
+                "error": permissions::redirect_denied(&e).unwrap_or_else(|| format!("Request failed: {}", e))
             }).to_string();
         }
     };
@@ -203,15 +217,22 @@ pub fn op_fetch(#[string] url: String, #[string] options_json: String) -> String
 ///
 /// # Returns
 /// 响应文本，失败时返回错误信息
-pub fn op_http_get(#[string] url: String) -> String {
-    match Client::new().get(&url).send() {
+pub fn op_http_get(state: &mut OpState, #[string] url: String) -> String {
+    if let Err(denied) = permissions::check_net(state, &url) {
+        return denied;
+    }
+    let client = match client(state) {
+        Ok(client) => client,
+        Err(e) => return e,
+    };
+    match client.get(&url).send() {
         Ok(response) => {
             match response.text() {
                 Ok(text) => text,
                 Err(e) => format!("Error: Failed to read response: {}", e),
             }
         },
-        Err(e) => format!("Error: HTTP request failed: {}", e),
+        Err(e) => request_error(e),
     }
 }
 
@@ -225,8 +246,14 @@ pub fn op_http_get(#[string] url: String) -> String {
 ///
 /// # Returns
 /// 响应文本，失败时返回错误信息
-pub fn op_http_post_json(#[string] url: String, #[string] json_body: String) -> String {
-    let client = Client::new();
+pub fn op_http_post_json(state: &mut OpState, #[string] url: String, #[string] json_body: String) -> String {
+    if let Err(denied) = permissions::check_net(state, &url) {
+        return denied;
+    }
+    let client = match client(state) {
+        Ok(client) => client,
+        Err(e) => return e,
+    };
 
     match client.post(&url)
         .header("Content-Type", "application/json")
@@ -239,10 +266,23 @@ pub fn op_http_post_json(#[string] url: String, #[string] json_body: String) ->
                 Err(e) => format!("Error: Failed to read response: {}", e),
             }
         },
-        Err(e) => format!("Error: HTTP request failed: {}", e),
+        Err(e) => request_error(e),
     }
 }
 
+/// op_http_get / op_http_post_json 使用的客户端（每一次重定向都重新检查 net 权限）
+fn client(state: &OpState) -> Result<Client, String> {
+    Client::builder()
+        .redirect(permissions::redirect_policy(state))
+        .build()
+        .map_err(|e| format!("Error: Failed to create HTTP client: {}", e))
+}
+
+/// 请求失败的错误信息：重定向被拒绝时为 "PermissionDenied: ..."
+fn request_error(e: reqwest::Error) -> String {
+    permissions::redirect_denied(&e).unwrap_or_else(|| format!("Error: HTTP request failed: {}", e))
+}
+
 // ============================================
 // Extension Definition
 // ============================================
@@ -0,0 +1,72 @@
+// file_watch.rs - compile_file(path, watch=True)：文件修改后在下一次调用前自动重新编译
+//
+// 每次 call() / evaluate() / eval() 之前比较被监视文件的修改时间和大小（一次 stat，开销可以忽略），
+// 有变化时按 compile_file 的参数（encoding / mode）重新读取并执行，再继续本次调用。
+// 重新编译失败时本次调用抛出该错误；文件再次修改之前不会重试。
+// 文件暂时不存在（编辑器保存时先删除再写入）时跳过，等它重新出现。
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// 文件的修改时间和大小
+#[derive(Clone, Copy, PartialEq)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+/// 读取文件的修改时间和大小（文件不存在时返回 None）
+pub fn stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(FileStamp {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+    })
+}
+
+/// 被监视的文件及其 compile_file 参数
+#[derive(Clone)]
+pub struct WatchedFile {
+    pub path: PathBuf,
+    pub encoding: Option<String>,
+    pub mode: Option<String>,
+    stamp: Option<FileStamp>,
+}
+
+/// Context 监视的文件
+#[derive(Default)]
+pub struct FileWatcher {
+    files: Vec<WatchedFile>,
+}
+
+impl FileWatcher {
+    /// 开始监视文件（同一路径替换之前的参数）
+    ///
+    /// `stamp` 应在读取文件之前获取，读取期间的修改会在下一次调用前被发现。
+    pub fn watch(&mut self, path: PathBuf, encoding: Option<String>, mode: Option<String>, stamp: Option<FileStamp>) {
+        self.files.retain(|file| file.path != path);
+        self.files.push(WatchedFile { path, encoding, mode, stamp });
+    }
+
+    /// 停止监视文件（compile_file(path) 不带 watch 时调用）
+    pub fn unwatch(&mut self, path: &Path) {
+        self.files.retain(|file| file.path != path);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// 取出自上次检查以来发生变化的文件，并更新记录的修改时间
+    pub fn changed(&mut self) -> Vec<WatchedFile> {
+        let mut changed = Vec::new();
+        for file in &mut self.files {
+            let current = stamp(&file.path);
+            if current.is_some() && current != file.stamp {
+                file.stamp = current;
+                changed.push(file.clone());
+            }
+        }
+        changed
+    }
+}
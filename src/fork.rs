@@ -0,0 +1,63 @@
+// fork.rs - os.fork() 检测
+//
+// fork() 只复制调用 fork 的线程。V8 平台的工作线程、ContextPool / ThreadedContext 的工作线程、
+// tokio 的后台线程在子进程中都不存在，继续使用从父进程继承的 isolate 会死锁或崩溃。
+//
+// 通过 pthread_atfork 在子进程中递增 fork 代数（generation）：
+// - 每个 Context / ContextPool / ThreadedContext 记录创建时的代数，代数不一致时抛出异常
+// - 子进程中当前线程的 tokio runtime 被丢弃（不析构），下次使用时重新创建
+// - V8 平台延迟到第一次创建 Context 时才初始化，父进程只 import 不创建 Context 时，
+//   子进程可以正常初始化自己的平台（gunicorn preload、multiprocessing fork 模式）
+//
+// 这里只检测并报错，不提供回退：父进程已经初始化过 V8 时，子进程无法重新初始化（V8 平台每个进程只能初始化一次），
+// 创建 Context 抛出异常，也不会自动改用新的工作进程。
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use std::sync::Once;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 当前进程经历的 fork 次数（每次在子进程中 +1）
+static FORK_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+static INSTALL_HANDLERS: Once = Once::new();
+
+/// 注册 fork 处理函数（幂等）
+pub fn install_handlers() {
+    INSTALL_HANDLERS.call_once(|| {
+        #[cfg(unix)]
+        unsafe {
+            libc::pthread_atfork(None, None, Some(after_fork_in_child));
+        }
+    });
+}
+
+/// 子进程中 fork() 返回前调用（此时子进程只有当前一个线程）
+#[cfg(unix)]
+extern "C" fn after_fork_in_child() {
+    FORK_GENERATION.fetch_add(1, Ordering::SeqCst);
+    crate::runtime::forget_tokio_runtime();
+}
+
+/// 当前 fork 代数
+pub fn generation() -> usize {
+    FORK_GENERATION.load(Ordering::SeqCst)
+}
+
+/// 对象是否是在 fork 之前创建、从父进程继承而来的
+pub fn is_inherited(created_generation: usize) -> bool {
+    created_generation != generation()
+}
+
+/// 检查对象是否可以在当前进程中使用
+///
+/// `kind` 为对象类型名，用于错误信息。
+pub fn check(created_generation: usize, kind: &str) -> PyResult<()> {
+    if is_inherited(created_generation) {
+        return Err(PyRuntimeError::new_err(format!(
+            "{} was created before os.fork() and cannot be used in the child process; create a new {} after fork",
+            kind, kind
+        )));
+    }
+    Ok(())
+}
@@ -1,7 +1,34 @@
 // fs_ops.rs - File System operations for require() implementation
-use deno_core::{extension, op2};
+use deno_core::{extension, op2, OpState};
+use deno_error::JsErrorBox;
 use std::fs;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::permissions;
+
+/// Context(env=...) 注入的环境变量
+///
+/// 设置后 process.env / Deno.env（以及 load_wasm 的 inherit_env）只能看到这些变量，不再读取宿主环境；
+/// 与宿主环境变量一样受 permissions 的 env 权限限制。
+pub struct InjectedEnv(pub Vec<(String, String)>);
+
+/// Context 可见的全部环境变量：注入的变量，没有注入时为宿主环境变量（不检查权限）
+pub fn env_vars(state: &OpState) -> Vec<(String, String)> {
+    match state.try_borrow::<Arc<InjectedEnv>>() {
+        Some(env) => env.0.clone(),
+        None => std::env::vars().collect(),
+    }
+}
+
+/// 读取一个环境变量（不检查权限）
+fn env_var(state: &OpState, key: &str) -> Option<String> {
+    match state.try_borrow::<Arc<InjectedEnv>>() {
+        Some(env) => env.0.iter().find(|(name, _)| name == key).map(|(_, value)| value.clone()),
+        None => std::env::var(key).ok(),
+    }
+}
 
 // ============================================
 // File System Operations
@@ -16,10 +43,19 @@ use std::path::{Path, PathBuf};
 ///
 /// # Returns
 /// 文件内容字符串，失败时返回错误信息
-pub fn op_read_file_sync(#[string] path: String) -> String {
-    match fs::read_to_string(&path) {
-        Ok(content) => content,
-        Err(e) => format!("Error: Failed to read file '{}': {}", path, e),
+pub fn op_read_file_sync(state: &mut OpState, #[string] path: String) -> String {
+    let target = match permissions::check_read(state, &path) {
+        Ok(target) => target,
+        Err(denied) => return denied,
+    };
+    let mut content = String::new();
+    let read = target.open("read", &path, false).and_then(|mut file| {
+        file.read_to_string(&mut content)
+            .map_err(|e| format!("Error: Failed to read file '{}': {}", path, e))
+    });
+    match read {
+        Ok(_) => content,
+        Err(e) => e,
     }
 }
 
@@ -33,10 +69,19 @@ pub fn op_read_file_sync(#[string] path: String) -> String {
 ///
 /// # Returns
 /// 成功返回 "OK"，失败返回错误信息
-pub fn op_write_file_sync(#[string] path: String, #[string] content: String) -> String {
-    match fs::write(&path, content) {
+pub fn op_write_file_sync(state: &mut OpState, #[string] path: String, #[string] content: String) -> String {
+    let target = match permissions::check_write(state, &path) {
+        Ok(target) => target,
+        Err(denied) => return denied,
+    };
+    let written = target.open("write", &path, true).and_then(|mut file| {
+        file.set_len(0)
+            .and_then(|_| file.write_all(content.as_bytes()))
+            .map_err(|e| format!("Error: Failed to write file '{}': {}", path, e))
+    });
+    match written {
         Ok(_) => "OK".to_string(),
-        Err(e) => format!("Error: Failed to write file '{}': {}", path, e),
+        Err(e) => e,
     }
 }
 
@@ -47,9 +92,10 @@ pub fn op_write_file_sync(#[string] path: String, #[string] content: String) ->
 /// * `path` - 文件路径
 ///
 /// # Returns
-/// true 如果文件存在，false 如果不存在
-pub fn op_file_exists(#[string] path: String) -> bool {
-    Path::new(&path).exists()
+/// true 如果文件存在，false 如果不存在（没有读取权限时也返回 false）
+pub fn op_file_exists(state: &mut OpState, #[string] path: String) -> bool {
+    permissions::check_read(state, &path)
+        .is_ok_and(|target| target.path().exists() && target.recheck("read", &path).is_ok())
 }
 
 #[op2(fast)]
@@ -60,9 +106,11 @@ pub fn op_file_exists(#[string] path: String) -> bool {
 ///
 /// # Returns
 /// true 如果是文件，false 如果不是或不存在
-pub fn op_is_file(#[string] path: String) -> bool {
-    match Path::new(&path).metadata() {
-        Ok(metadata) => metadata.is_file(),
+pub fn op_is_file(state: &mut OpState, #[string] path: String) -> bool {
+    match permissions::check_read(state, &path) {
+        Ok(target) => {
+            target.path().metadata().is_ok_and(|metadata| metadata.is_file()) && target.recheck("read", &path).is_ok()
+        }
         Err(_) => false,
     }
 }
@@ -75,9 +123,11 @@ pub fn op_is_file(#[string] path: String) -> bool {
 ///
 /// # Returns
 /// true 如果是目录，false 如果不是或不存在
-pub fn op_is_directory(#[string] path: String) -> bool {
-    match Path::new(&path).metadata() {
-        Ok(metadata) => metadata.is_dir(),
+pub fn op_is_directory(state: &mut OpState, #[string] path: String) -> bool {
+    match permissions::check_read(state, &path) {
+        Ok(target) => {
+            target.path().metadata().is_ok_and(|metadata| metadata.is_dir()) && target.recheck("read", &path).is_ok()
+        }
         Err(_) => false,
     }
 }
@@ -194,8 +244,16 @@ pub fn op_getcwd() -> String {
 ///
 /// # Returns
 /// JSON 数组字符串，包含目录中的所有条目名称
-pub fn op_readdir(#[string] path: String) -> String {
-    match fs::read_dir(&path) {
+pub fn op_readdir(state: &mut OpState, #[string] path: String) -> String {
+    let target = match permissions::check_read(state, &path) {
+        Ok(target) => target,
+        Err(denied) => return denied,
+    };
+    let entries = fs::read_dir(target.path());
+    if let Err(denied) = target.recheck("read", &path) {
+        return denied;
+    }
+    match entries {
         Ok(entries) => {
             let mut items = Vec::new();
             for entry in entries {
@@ -219,9 +277,31 @@ pub fn op_readdir(#[string] path: String) -> String {
 /// * `key` - 环境变量名
 ///
 /// # Returns
-/// 环境变量值，如果不存在返回空字符串
-pub fn op_getenv(#[string] key: String) -> String {
-    std::env::var(&key).unwrap_or_default()
+/// 环境变量值，如果不存在返回空字符串；没有权限时抛出 PermissionDenied 错误
+pub fn op_getenv(state: &mut OpState, #[string] key: String) -> Result<String, JsErrorBox> {
+    permissions::check_env(state, &key).map_err(|denied| {
+        JsErrorBox::new("PermissionDenied", denied.trim_start_matches(permissions::DENIED_PREFIX).to_string())
+    })?;
+    Ok(env_var(state, &key).unwrap_or_default())
+}
+
+#[op2]
+#[string]
+/// 读取环境变量，区分不存在和空字符串（Deno.env.get 使用）
+///
+/// # Returns
+/// 值的 JSON 字符串字面量，不存在时返回 null
+pub fn op_getenv_json(state: &mut OpState, #[string] key: String) -> String {
+    if let Err(denied) = permissions::check_env(state, &key) {
+        return denied;
+    }
+    serde_json::to_string(&env_var(state, &key)).unwrap_or_else(|_| "null".to_string())
+}
+
+#[op2(fast)]
+/// 是否设置了 Context(env=...)（polyfill 据此决定是否提供全局 Deno.env）
+pub fn op_env_injected(state: &mut OpState) -> bool {
+    state.has::<Arc<InjectedEnv>>()
 }
 
 #[op2]
@@ -229,17 +309,17 @@ pub fn op_getenv(#[string] key: String) -> String {
 /// 获取所有环境变量（JSON 格式）
 ///
 /// # Returns
-/// JSON 对象字符串，包含所有环境变量
-pub fn op_getenv_all() -> String {
-    let env_vars: Vec<String> = std::env::vars()
-        .map(|(k, v)| {
-            // 转义 JSON 特殊字符
-            let key = k.replace('\\', "\\\\").replace('"', "\\\"");
-            let value = v.replace('\\', "\\\\").replace('"', "\\\"");
-            format!("\"{}\":\"{}\"", key, value)
-        })
+/// JSON 对象字符串，包含所有环境变量（设置了 env 白名单时只包含列出的变量）
+pub fn op_getenv_all(state: &mut OpState) -> String {
+    if permissions::env_denied(state) {
+        return format!("{}access to environment variables is not allowed", permissions::DENIED_PREFIX);
+    }
+    let visible: serde_json::Map<String, serde_json::Value> = env_vars(state)
+        .into_iter()
+        .filter(|(k, _)| permissions::env_visible(state, k))
+        .map(|(k, v)| (k, serde_json::Value::String(v)))
         .collect();
-    format!("{{{}}}", env_vars.join(","))
+    serde_json::Value::Object(visible).to_string()
 }
 
 // ============================================
@@ -261,6 +341,8 @@ extension!(
         op_getcwd,
         op_readdir,
         op_getenv,
+        op_getenv_json,
         op_getenv_all,
+        op_env_injected,
     ],
 );
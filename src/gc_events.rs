@@ -0,0 +1,168 @@
+// gc_events.rs - GC 事件回调（ctx.on_gc(callback)）
+//
+// 设置回调后在 isolate 上注册 V8 的 GC prologue / epilogue 回调，记录每次 GC 的类型、耗时
+// 和前后的堆使用量。GC 发生在 JS 执行（分配内存）过程中，此时不能调用 Python，
+// 事件先保存在队列中，在调用结束后（以及 gc() / notify_low_memory() / idle() 之后）按顺序交给回调，
+// 回调中可以正常使用同一个 Context。
+
+use deno_core::v8;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::time::{Duration, Instant};
+
+/// 等待交给回调的最大事件数，超出时丢弃最早的事件
+const MAX_PENDING: usize = 10_000;
+
+/// 一次 GC
+struct GcEvent {
+    gc_type: &'static str,
+    forced: bool,
+    duration: Duration,
+    heap_before: usize,
+    heap_after: usize,
+}
+
+/// 正在进行的 GC（prologue 记录，epilogue 取出；GC 可能嵌套，例如增量标记期间的 Scavenge）
+struct Started {
+    at: Instant,
+    heap_before: usize,
+}
+
+/// Context 的 GC 事件回调（地址作为 GC 回调的参数，在 isolate 销毁前保持有效）
+#[derive(Default)]
+pub struct GcObserver {
+    callback: RefCell<Option<Py<PyAny>>>,
+    started: RefCell<Vec<Started>>,
+    pending: RefCell<VecDeque<GcEvent>>,
+}
+
+impl GcObserver {
+    /// 设置回调（None 时清除），在 isolate 上注册 / 移除 GC 回调
+    pub fn set(&self, isolate: &mut v8::Isolate, callback: Option<Py<PyAny>>) {
+        let data = self as *const Self as *mut c_void;
+        let was_set = self.callback.replace(callback).is_some();
+        let is_set = self.callback.borrow().is_some();
+        if is_set && !was_set {
+            isolate.add_gc_prologue_callback(gc_prologue, data, v8::GCType::kGCTypeAll);
+            isolate.add_gc_epilogue_callback(gc_epilogue, data, v8::GCType::kGCTypeAll);
+        } else if was_set && !is_set {
+            isolate.remove_gc_prologue_callback(gc_prologue, data);
+            isolate.remove_gc_epilogue_callback(gc_epilogue, data);
+            self.started.borrow_mut().clear();
+            self.pending.borrow_mut().clear();
+        }
+    }
+
+    /// 当前的回调（recycle_after 重建 isolate 时转移到新的 Context）
+    pub fn callback(&self) -> Option<Py<PyAny>> {
+        Python::attach(|py| self.callback.borrow().as_ref().map(|callback| callback.clone_ref(py)))
+    }
+
+    /// 把记录的事件交给回调（不在 isolate 中执行时调用）
+    pub fn deliver(&self) {
+        if self.pending.borrow().is_empty() {
+            return;
+        }
+        Python::attach(|py| {
+            let Some(callback) = self.callback.borrow().as_ref().map(|callback| callback.clone_ref(py)) else {
+                return;
+            };
+            // 回调中可能再次执行 JS 产生新的事件，逐个取出
+            loop {
+                let Some(event) = self.pending.borrow_mut().pop_front() else {
+                    break;
+                };
+                if let Err(e) = event.to_python(py).and_then(|info| callback.call1(py, (info,))) {
+                    e.write_unraisable(py, Some(callback.bind(py)));
+                }
+            }
+        });
+    }
+
+    fn begin(&self, heap_before: usize) {
+        self.started.borrow_mut().push(Started {
+            at: Instant::now(),
+            heap_before,
+        });
+    }
+
+    fn end(&self, gc_type: v8::GCType, flags: v8::GCCallbackFlags, heap_after: usize) {
+        let Some(started) = self.started.borrow_mut().pop() else {
+            return;
+        };
+        let mut pending = self.pending.borrow_mut();
+        if pending.len() >= MAX_PENDING {
+            pending.pop_front();
+        }
+        pending.push_back(GcEvent {
+            gc_type: type_name(gc_type),
+            forced: (flags & v8::GCCallbackFlags::kGCCallbackFlagForced).0 != 0,
+            duration: started.at.elapsed(),
+            heap_before: started.heap_before,
+            heap_after,
+        });
+    }
+}
+
+impl GcEvent {
+    fn to_python<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let info = PyDict::new(py);
+        info.set_item("type", self.gc_type)?;
+        info.set_item("forced", self.forced)?;
+        info.set_item("duration_ms", self.duration.as_secs_f64() * 1000.0)?;
+        info.set_item("heap_before", self.heap_before)?;
+        info.set_item("heap_after", self.heap_after)?;
+        info.set_item("freed", self.heap_before.saturating_sub(self.heap_after))?;
+        Ok(info)
+    }
+}
+
+/// 校验 on_gc() 的参数
+pub fn validate(callback: Option<Bound<'_, PyAny>>) -> PyResult<Option<Py<PyAny>>> {
+    if callback.as_ref().is_some_and(|callback| !callback.is_callable()) {
+        return Err(PyTypeError::new_err("callback must be callable or None"));
+    }
+    Ok(callback.map(Bound::unbind))
+}
+
+fn type_name(gc_type: v8::GCType) -> &'static str {
+    match gc_type {
+        v8::GCType::kGCTypeScavenge => "scavenge",
+        v8::GCType::kGCTypeMinorMarkSweep => "minor_mark_sweep",
+        v8::GCType::kGCTypeMarkSweepCompact => "mark_sweep_compact",
+        v8::GCType::kGCTypeIncrementalMarking => "incremental_marking",
+        v8::GCType::kGCTypeProcessWeakCallbacks => "process_weak_callbacks",
+        _ => "unknown",
+    }
+}
+
+/// 当前堆使用量
+fn used_heap_size(isolate: v8::UnsafeRawIsolatePtr) -> usize {
+    // SAFETY: V8 传入的是正在执行 GC 的 isolate，v8::Isolate 只是指针的包装，不负责销毁
+    let mut isolate = unsafe { v8::Isolate::from_raw_isolate_ptr(isolate) };
+    isolate.get_heap_statistics().used_heap_size()
+}
+
+unsafe extern "C" fn gc_prologue(
+    isolate: v8::UnsafeRawIsolatePtr,
+    _gc_type: v8::GCType,
+    _flags: v8::GCCallbackFlags,
+    data: *mut c_void,
+) {
+    let observer = unsafe { &*(data as *const GcObserver) };
+    observer.begin(used_heap_size(isolate));
+}
+
+unsafe extern "C" fn gc_epilogue(
+    isolate: v8::UnsafeRawIsolatePtr,
+    gc_type: v8::GCType,
+    flags: v8::GCCallbackFlags,
+    data: *mut c_void,
+) {
+    let observer = unsafe { &*(data as *const GcObserver) };
+    observer.end(gc_type, flags, used_heap_size(isolate));
+}
@@ -0,0 +1,158 @@
+// globals_diff.rs - snapshot_globals() / diff_globals()：比较两次调用之间全局变量的变化
+//
+// 初始化脚本实际创建了哪些全局状态、一次调用在全局作用域留下了什么，很难从代码中看出来。
+// snapshot_globals() 为全局对象的每个属性计算一个指纹（类型和内容的哈希），
+// diff_globals(prev) 重新计算并与之前的快照比较，返回新增 / 变化 / 删除的全局变量名：
+// - 指纹覆盖对象内容：属性（包括不可枚举的和 Symbol 键）、Map / Set 的条目、Date 的时间、
+//   类型化数组的字节、函数的源码；对象内部只修改了一个深层属性也算变化
+// - 访问器属性只记录 getter / setter 本身，不调用它们；比较深度和每个全局变量遍历的属性数有上限，
+//   超出部分不参与比较
+// - 闭包中的变量、全局 let/const/class 声明（它们不是全局对象的属性）不在快照中
+//
+// 快照是普通的 dict（名称 → 指纹），可以保存下来与其他 Context 的快照比较。
+
+use anyhow::{anyhow, Result};
+use deno_core::{v8, JsRuntime};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::BTreeMap;
+
+use crate::context::format_error;
+
+/// 对象内部的最大比较深度
+const MAX_DEPTH: usize = 8;
+
+/// 每个全局变量最多遍历的属性数
+const MAX_NODES: usize = 20_000;
+
+// 计算全局对象每个属性的指纹，返回 JSON 字符串 {名称: "类型:哈希"}
+const SNAPSHOT_FUNCTION: &str = r#"
+(function(maxDepth, maxNodes) {
+    const G = globalThis;
+    const { getOwnPropertyDescriptor, getOwnPropertyNames } = Object;
+    const ownKeys = Reflect.ownKeys;
+    const fnToString = Function.prototype.toString;
+    const isView = ArrayBuffer.isView;
+
+    const fingerprint = (root) => {
+        let hash = 0x811c9dc5 | 0;
+        let nodes = 0;
+        const seen = new Map();
+        const add = (text) => {
+            text = String(text);
+            for (let i = 0; i < text.length; i++) {
+                hash = Math.imul(hash ^ text.charCodeAt(i), 0x01000193);
+            }
+            hash = Math.imul(hash ^ 0xff, 0x01000193);
+        };
+        const walk = (value, depth) => {
+            const type = value === null ? 'null' : typeof value;
+            add(type);
+            if (type !== 'object' && type !== 'function') {
+                add(type === 'symbol' ? value.description : value);
+                return;
+            }
+            if (value === G) {
+                add('[global]');
+                return;
+            }
+            if (seen.has(value)) {
+                add('#' + seen.get(value));
+                return;
+            }
+            seen.set(value, seen.size);
+            if (type === 'function') {
+                try { add(fnToString.call(value)); } catch { add('[function]'); }
+            }
+            if (depth >= maxDepth) return;
+            if (isView(value)) {
+                const bytes = new Uint8Array(value.buffer, value.byteOffset, value.byteLength);
+                nodes += bytes.length >> 6;
+                add(bytes.join(','));
+            } else if (value instanceof Date) {
+                add(value.getTime());
+            } else if (value instanceof Map || value instanceof Set) {
+                for (const entry of value.entries()) {
+                    if (++nodes > maxNodes) return;
+                    walk(entry[0], depth + 1);
+                    walk(entry[1], depth + 1);
+                }
+            }
+            for (const key of ownKeys(value)) {
+                if (++nodes > maxNodes) return;
+                add(typeof key === 'symbol' ? '@' + key.description : key);
+                const descriptor = getOwnPropertyDescriptor(value, key);
+                if (!descriptor) continue;
+                if ('value' in descriptor) {
+                    walk(descriptor.value, depth + 1);
+                } else {
+                    add('accessor');
+                    walk(descriptor.get, maxDepth);
+                    walk(descriptor.set, maxDepth);
+                }
+            }
+        };
+        const type = root === null ? 'null' : typeof root;
+        try {
+            walk(root, 0);
+        } catch {
+            // Proxy 的陷阱等抛出异常：只比较已经遍历的部分
+            add('[error]');
+        }
+        return type + ':' + (hash >>> 0).toString(16).padStart(8, '0');
+    };
+
+    const snapshot = {};
+    for (const name of getOwnPropertyNames(G)) {
+        const descriptor = getOwnPropertyDescriptor(G, name);
+        if (!descriptor) continue;
+        snapshot[name] = 'value' in descriptor
+            ? fingerprint(descriptor.value)
+            : 'accessor:' + fingerprint([descriptor.get, descriptor.set]).split(':')[1];
+    }
+    return JSON.stringify(snapshot);
+})
+"#;
+
+/// diff_globals() 的结果（名称按字母顺序）
+#[derive(Debug, Default)]
+pub struct GlobalsDiff {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl GlobalsDiff {
+    /// 比较两个快照
+    pub fn between(prev: &BTreeMap<String, String>, current: &BTreeMap<String, String>) -> Self {
+        let mut diff = GlobalsDiff::default();
+        for (name, fingerprint) in current {
+            match prev.get(name) {
+                None => diff.added.push(name.clone()),
+                Some(previous) if previous != fingerprint => diff.changed.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = prev.keys().filter(|name| !current.contains_key(*name)).cloned().collect();
+        diff
+    }
+
+    pub fn to_py_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("added", &self.added)?;
+        dict.set_item("changed", &self.changed)?;
+        dict.set_item("removed", &self.removed)?;
+        Ok(dict)
+    }
+}
+
+/// 计算全局对象每个属性的指纹
+pub fn snapshot(runtime: &mut JsRuntime) -> Result<BTreeMap<String, String>> {
+    let code = format!("{}({}, {})", SNAPSHOT_FUNCTION.trim(), MAX_DEPTH, MAX_NODES);
+    let value = runtime
+        .execute_script("<snapshot_globals>", code)
+        .map_err(|e| anyhow!("{}", format_error(e.into())))?;
+    deno_core::scope!(scope, runtime);
+    let value = v8::Local::new(scope, value);
+    Ok(serde_json::from_str(&value.to_rust_string_lossy(scope))?)
+}
@@ -0,0 +1,263 @@
+// handles.rs - handles()：Context 持有的长期 V8 对象
+//
+// 下面这些对象在 Python 侧释放之前一直占用 isolate 中的句柄或堆内存，长期运行的服务中会不断增长：
+// - realm：create_realm() 的 V8 context 和求值包装函数（v8::Global）
+// - shadow_realm：create_shadow_realm() 的 ShadowRealm 实例（v8::Global）
+// - prepared：prepare() 定义在隐藏全局对象上的函数（同一模板共用一个），PreparedCall 对象销毁后仍然保留
+// - call_target：call() 缓存的目标函数（v8::Global，见 call_cache.rs）
+//
+// ctx.handles() 列出这些对象，handle.release() 逐个释放；Context(max_handles=N) 限制总数：
+// 达到上限时先清空 call() 的函数缓存（之后按需重新解析），仍然超出时 create_realm() / create_shadow_realm() /
+// prepare() 抛出 QuotaExceeded，call() 不再缓存新的函数（拼接代码执行，结果不变）。
+//
+// Realm / ShadowRealm 的句柄由 Python 对象持有，表中只保存弱引用：对象销毁或 close() 之后自动从表中消失。
+
+use pyo3::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::context::Context;
+
+/// 句柄编号（进程内唯一，call_cache.rs 同样使用）
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 分配新的句柄编号
+pub fn next_id() -> u64 {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Realm / ShadowRealm 保存句柄的槽
+pub trait HandleSlot {
+    /// 句柄是否仍然存在（没有 close()）
+    fn is_live(&self) -> bool;
+    /// 释放句柄；正在使用（执行中）时返回 false
+    fn release(&self) -> bool;
+}
+
+impl<T> HandleSlot for RefCell<Option<T>> {
+    fn is_live(&self) -> bool {
+        self.try_borrow().map_or(true, |slot| slot.is_some())
+    }
+
+    fn release(&self) -> bool {
+        match self.try_borrow_mut() {
+            Ok(mut slot) => {
+                slot.take();
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// 句柄指向的对象
+enum Resource {
+    /// Realm / ShadowRealm 的句柄槽
+    Slot(Weak<dyn HandleSlot>),
+    /// prepare() 的函数：隐藏全局对象上的属性路径，released 与 PreparedCall 共享
+    Prepared { path: String, released: Rc<Cell<bool>> },
+}
+
+struct Entry {
+    id: u64,
+    kind: &'static str,
+    name: String,
+    resource: Resource,
+}
+
+impl Entry {
+    fn is_live(&self) -> bool {
+        match &self.resource {
+            Resource::Slot(slot) => slot.upgrade().is_some_and(|slot| slot.is_live()),
+            Resource::Prepared { released, .. } => !released.get(),
+        }
+    }
+}
+
+/// 释放句柄需要的操作
+pub enum Release {
+    /// 已释放（或句柄已经不存在）
+    Done,
+    /// 正在执行中，不能释放
+    Busy,
+    /// 删除隐藏全局对象上的 prepare() 函数（由 Context 执行删除脚本）
+    Prepared(String),
+}
+
+/// Context 的句柄表（realm / shadow_realm / prepared；call_target 由 CallTargets 保存）
+pub struct HandleTable {
+    entries: RefCell<Vec<Entry>>,
+    max: Option<usize>,
+}
+
+impl HandleTable {
+    pub fn new(max: Option<usize>) -> Self {
+        HandleTable {
+            entries: RefCell::new(Vec::new()),
+            max,
+        }
+    }
+
+    /// Context(max_handles=...)
+    pub fn max(&self) -> Option<usize> {
+        self.max
+    }
+
+    /// 去掉已经关闭或销毁的 Realm / ShadowRealm 和已释放的 prepare() 函数，返回剩余个数
+    pub fn prune(&self) -> usize {
+        let mut entries = self.entries.borrow_mut();
+        entries.retain(Entry::is_live);
+        entries.len()
+    }
+
+    /// 登记 Realm / ShadowRealm 的句柄槽，返回句柄编号
+    pub fn register_slot<T: HandleSlot + 'static>(&self, kind: &'static str, name: &str, slot: &Rc<T>) -> u64 {
+        let slot: Rc<dyn HandleSlot> = slot.clone();
+        self.push(kind, name, Resource::Slot(Rc::downgrade(&slot)))
+    }
+
+    /// 登记 prepare() 的函数，返回与 PreparedCall 共享的释放标记
+    ///
+    /// 同一模板（同一路径）已经登记且没有释放时返回原来的标记，不占用新的句柄。
+    pub fn register_prepared(&self, template: &str, path: &str) -> Rc<Cell<bool>> {
+        let existing = self.entries.borrow().iter().find_map(|entry| match &entry.resource {
+            Resource::Prepared { path: p, released } if p == path && !released.get() => Some(released.clone()),
+            _ => None,
+        });
+        existing.unwrap_or_else(|| {
+            let released = Rc::new(Cell::new(false));
+            let resource = Resource::Prepared {
+                path: path.to_string(),
+                released: released.clone(),
+            };
+            self.push("prepared", template, resource);
+            released
+        })
+    }
+
+    /// 同一模板的 prepare() 函数是否已经登记（不需要新的句柄）
+    pub fn has_prepared(&self, path: &str) -> bool {
+        self.entries.borrow().iter().any(|entry| {
+            matches!(&entry.resource, Resource::Prepared { path: p, released } if p == path && !released.get())
+        })
+    }
+
+    fn push(&self, kind: &'static str, name: &str, resource: Resource) -> u64 {
+        let id = next_id();
+        self.entries.borrow_mut().push(Entry {
+            id,
+            kind,
+            name: name.to_string(),
+            resource,
+        });
+        id
+    }
+
+    /// 仍然存在的句柄：(编号, 类型, 名称)
+    pub fn list(&self) -> Vec<(u64, &'static str, String)> {
+        self.prune();
+        self.entries
+            .borrow()
+            .iter()
+            .map(|entry| (entry.id, entry.kind, entry.name.clone()))
+            .collect()
+    }
+
+    /// 句柄是否仍然存在（不在表中时返回 None，可能是 call_target）
+    pub fn is_live(&self, id: u64) -> Option<bool> {
+        self.entries.borrow().iter().find(|entry| entry.id == id).map(Entry::is_live)
+    }
+
+    /// 释放句柄（不在表中时返回 None，可能是 call_target）
+    pub fn release(&self, id: u64) -> Option<Release> {
+        let mut entries = self.entries.borrow_mut();
+        let index = entries.iter().position(|entry| entry.id == id)?;
+        let release = match &entries[index].resource {
+            Resource::Slot(slot) => match slot.upgrade() {
+                Some(slot) if !slot.release() => return Some(Release::Busy),
+                _ => Release::Done,
+            },
+            Resource::Prepared { released, .. } if released.get() => Release::Done,
+            Resource::Prepared { path, released } => {
+                released.set(true);
+                Release::Prepared(path.clone())
+            }
+        };
+        entries.remove(index);
+        Some(release)
+    }
+
+    /// 重建 isolate 后接管原来的句柄（重放的脚本重新定义了 prepare() 的函数；有 Realm 时不能重建）
+    pub fn carry_over(&self, from: &HandleTable) {
+        self.entries.swap(&from.entries);
+    }
+}
+
+/// Context 持有的一个长期 V8 对象
+///
+/// 由 `Context.handles()` 返回。`release()` 释放对象，之后：
+/// - realm / shadow_realm：与 close() 相同，之后的调用抛出异常
+/// - prepared：删除 prepare() 定义的函数，同一模板的所有 PreparedCall 抛出异常（重新 prepare() 即可）
+/// - call_target：清除 call() 缓存的函数，下一次调用重新解析
+///
+/// Example:
+///     ```python
+///     ctx = never_jscore.Context(max_handles=1000)
+///     stmt = ctx.prepare("hash(?)")
+///     for handle in ctx.handles():
+///         print(handle.id, handle.kind, handle.name)  # 1 prepared hash(?)
+///     ctx.handles()[0].release()
+///     ```
+#[pyclass(unsendable)]
+pub struct Handle {
+    context: Py<Context>,
+    id: u64,
+    kind: &'static str,
+    name: String,
+}
+
+impl Handle {
+    pub(crate) fn new(context: Py<Context>, id: u64, kind: &'static str, name: String) -> Self {
+        Handle { context, id, kind, name }
+    }
+}
+
+#[pymethods]
+impl Handle {
+    /// 句柄编号（进程内唯一）
+    #[getter]
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// "realm" / "shadow_realm" / "prepared" / "call_target"
+    #[getter]
+    fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    /// realm / shadow_realm 为 "<realm>" / "<shadow_realm>"，prepared 为模板，call_target 为函数名
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 是否已经释放（包括 close() 和对象销毁）
+    #[getter]
+    fn released(&self, py: Python<'_>) -> bool {
+        !self.context.borrow(py).handle_is_live(self.id)
+    }
+
+    /// 释放对象（重复调用是安全的）
+    ///
+    /// Raises:
+    ///     RuntimeError: Realm / ShadowRealm 正在执行（在它调用的 Python 函数中释放）
+    fn release(&self, py: Python<'_>) -> PyResult<()> {
+        Context::release_handle(self.context.bind(py), py, self.id)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Handle(id={}, kind={:?}, name={:?})", self.id, self.kind, self.name)
+    }
+}
@@ -0,0 +1,117 @@
+// harden.rs - 冻结内置对象（frozen intrinsics）
+//
+// 多次调用共享同一个 Context 时，一段代码修改 Array.prototype 等内置原型
+// （原型污染）会影响之后所有调用。加载完初始化代码后冻结内置对象，
+// 之后执行的代码就无法再修改它们。
+//
+// 冻结后给继承来的属性赋值会失败（所谓 override mistake，例如 `obj.toString = f`、
+// 子类构造函数中的 `this.name = ...`）。与 SES 的做法类似，常被覆盖的属性改为访问器：
+// 在实例上赋值时定义实例自己的属性，直接修改原型时抛出 TypeError。
+//
+// Error 构造函数本身不冻结（库代码常设置 Error.stackTraceLimit / prepareStackTrace），
+// 它们的原型仍然冻结。全局变量（如 globalThis.Array）不受影响，仍然可以重新赋值。
+
+/// 冻结内置原型和构造函数的脚本（重复执行是安全的）
+pub(crate) const FREEZE_INTRINSICS: &str = r#"
+(function() {
+    'use strict';
+    if (Object.isFrozen(Object.prototype)) {
+        return;
+    }
+
+    const { defineProperty, freeze, getOwnPropertyDescriptor, getOwnPropertyNames, getOwnPropertySymbols, getPrototypeOf } = Object;
+
+    const OVERRIDABLE = ['constructor', 'toString', 'toLocaleString', 'valueOf', 'toJSON', 'name', 'message'];
+
+    // 常被覆盖的数据属性改为访问器，避免冻结后无法在实例上覆盖
+    function enableOverride(proto) {
+        for (const key of OVERRIDABLE) {
+            const desc = getOwnPropertyDescriptor(proto, key);
+            if (!desc || !('value' in desc) || !desc.configurable) {
+                continue;
+            }
+            const value = desc.value;
+            defineProperty(proto, key, {
+                get() { return value; },
+                set(newValue) {
+                    if (this === proto) {
+                        throw new TypeError(`Cannot assign to read only property '${key}' of frozen intrinsic`);
+                    }
+                    defineProperty(this, key, { value: newValue, writable: true, enumerable: true, configurable: true });
+                },
+                enumerable: desc.enumerable,
+                configurable: false,
+            });
+        }
+    }
+
+    // 冻结对象本身以及作为属性值的函数（方法、getter / setter）
+    function harden(obj) {
+        if (obj === null || (typeof obj !== 'object' && typeof obj !== 'function') || Object.isFrozen(obj)) {
+            return;
+        }
+        freeze(obj);
+        for (const key of [...getOwnPropertyNames(obj), ...getOwnPropertySymbols(obj)]) {
+            const desc = getOwnPropertyDescriptor(obj, key);
+            for (const fn of [desc.value, desc.get, desc.set]) {
+                if (typeof fn === 'function' && fn !== obj && !('prototype' in fn && fn.prototype)) {
+                    freeze(fn);
+                }
+            }
+        }
+    }
+
+    function hardenPrototype(proto) {
+        if (proto) {
+            enableOverride(proto);
+            harden(proto);
+        }
+    }
+
+    const constructors = [
+        'Object', 'Function', 'Array', 'String', 'Number', 'Boolean', 'Symbol', 'BigInt', 'Date', 'RegExp',
+        'Promise', 'Map', 'Set', 'WeakMap', 'WeakSet', 'WeakRef', 'FinalizationRegistry', 'Iterator',
+        'ArrayBuffer', 'SharedArrayBuffer', 'DataView',
+        'Int8Array', 'Uint8Array', 'Uint8ClampedArray', 'Int16Array', 'Uint16Array',
+        'Int32Array', 'Uint32Array', 'Float32Array', 'Float64Array', 'BigInt64Array', 'BigUint64Array',
+    ];
+    const errors = [
+        'Error', 'EvalError', 'RangeError', 'ReferenceError', 'SyntaxError', 'TypeError', 'URIError', 'AggregateError',
+    ];
+    const namespaces = ['JSON', 'Math', 'Reflect', 'Atomics'];
+
+    // 没有全局名称的内置原型
+    const hidden = [
+        getPrototypeOf(function* () {}),                             // %GeneratorFunction.prototype%
+        getPrototypeOf(async function () {}),                        // %AsyncFunction.prototype%
+        getPrototypeOf(async function* () {}),                       // %AsyncGeneratorFunction.prototype%
+        getPrototypeOf(function* () {}).prototype,                   // %GeneratorPrototype%
+        getPrototypeOf(async function* () {}).prototype,             // %AsyncGeneratorPrototype%
+        getPrototypeOf(getPrototypeOf([][Symbol.iterator]())),       // %IteratorPrototype%
+        getPrototypeOf([][Symbol.iterator]()),                       // %ArrayIteratorPrototype%
+        getPrototypeOf(new Map()[Symbol.iterator]()),
+        getPrototypeOf(new Set()[Symbol.iterator]()),
+        getPrototypeOf(''[Symbol.iterator]()),
+        getPrototypeOf(/x/[Symbol.matchAll]('')),
+    ];
+
+    for (const ctor of [...constructors.map(name => globalThis[name]), getPrototypeOf(Int8Array) /* %TypedArray% */]) {
+        if (typeof ctor === 'function') {
+            hardenPrototype(ctor.prototype);
+            harden(ctor);
+        }
+    }
+    for (const name of errors) {
+        const ctor = globalThis[name];
+        if (typeof ctor === 'function') {
+            hardenPrototype(ctor.prototype);
+        }
+    }
+    for (const name of namespaces) {
+        harden(globalThis[name]);
+    }
+    for (const proto of hidden) {
+        hardenPrototype(proto);
+    }
+})();
+"#;
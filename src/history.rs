@@ -0,0 +1,115 @@
+// history.rs - 执行历史环形缓冲区（Context(history=N)）
+//
+// 开启后记录最近 N 次执行（evaluate / call / eval / compile / compile_file / Realm），
+// 每条记录包含执行类型、脚本名称、代码片段、开始时间、耗时和结果，超出 N 条时丢弃最早的记录。
+// 通过 ctx.history() 取出，用于长时间运行的服务出错后回溯最近执行了什么。
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 每条记录保存的代码最大字符数，超出部分截断
+const MAX_CODE_CHARS: usize = 500;
+
+/// 代码片段（超过 MAX_CODE_CHARS 时截断，slow_script.rs 同样使用）
+pub fn preview(code: &str) -> String {
+    let mut preview: String = code.chars().take(MAX_CODE_CHARS).collect();
+    if preview.len() < code.len() {
+        preview.push_str("...");
+    }
+    preview
+}
+
+/// 一次执行
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    /// "evaluate" / "script"，含义与执行钩子相同
+    pub kind: &'static str,
+    pub filename: String,
+    /// 代码（超过 MAX_CODE_CHARS 时截断）
+    pub code: String,
+    pub code_length: usize,
+    /// 开始时间（Unix 时间戳，毫秒）
+    pub start_ms: f64,
+    pub duration: Duration,
+    /// 失败时的错误信息
+    pub error: Option<String>,
+}
+
+/// 一次正在进行的执行
+pub struct Pending {
+    entry: HistoryEntry,
+    started: Instant,
+}
+
+/// Context 的执行历史
+pub struct History {
+    capacity: usize,
+    entries: RefCell<VecDeque<HistoryEntry>>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: RefCell::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// 开始记录一次执行
+    pub fn begin(&self, kind: &'static str, filename: &str, code: &str) -> Pending {
+        let start_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or_default();
+        Pending {
+            entry: HistoryEntry {
+                kind,
+                filename: filename.to_string(),
+                code: preview(code),
+                code_length: code.len(),
+                start_ms,
+                duration: Duration::ZERO,
+                error: None,
+            },
+            started: Instant::now(),
+        }
+    }
+
+    /// 执行结束：保存记录，超出容量时丢弃最早的记录
+    pub fn finish<R>(&self, pending: Pending, result: &anyhow::Result<R>) {
+        let Pending { mut entry, started } = pending;
+        entry.duration = started.elapsed();
+        entry.error = result.as_ref().err().map(|e| e.to_string());
+        let mut entries = self.entries.borrow_mut();
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// 当前记录（最早的在前）
+    pub fn snapshot(&self) -> Vec<HistoryEntry> {
+        self.entries.borrow().iter().cloned().collect()
+    }
+}
+
+/// 转换为 history() 的返回值
+pub fn to_python<'py>(py: Python<'py>, entries: &[HistoryEntry]) -> PyResult<Bound<'py, PyList>> {
+    let list = PyList::empty(py);
+    for entry in entries {
+        let item = PyDict::new(py);
+        item.set_item("kind", entry.kind)?;
+        item.set_item("filename", &entry.filename)?;
+        item.set_item("code", &entry.code)?;
+        item.set_item("code_length", entry.code_length)?;
+        item.set_item("start_ms", entry.start_ms)?;
+        item.set_item("duration_ms", entry.duration.as_secs_f64() * 1000.0)?;
+        item.set_item("success", entry.error.is_none())?;
+        item.set_item("error", &entry.error)?;
+        list.append(item)?;
+    }
+    Ok(list)
+}
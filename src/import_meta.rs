@@ -0,0 +1,52 @@
+// import_meta.rs - ctx.on_import_meta(callback)：由 Python 设置 ES 模块的 import.meta 字段
+//
+// 一些 bundle 在初始化时根据 import.meta.url（或构建工具注入的 import.meta.env 等）选择分支，
+// 从磁盘加载时 url 是本地的 file:// 地址，与浏览器中不同。设置回调后，每次加载模块
+// （compile_file(mode="module")）之前调用 callback({"url": ..., "path": ...})，
+// 返回的 dict 合并到该模块的 import.meta 中（可以覆盖 url），返回 None 时保持默认值。
+//
+// 字段在模块代码最前面（第一行，不增加行数）以 Object.assign(import.meta, {...}) 设置，
+// 先于模块的其他代码执行；值必须可以 JSON 序列化。
+
+use anyhow::{anyhow, Result};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::convert::python_to_json;
+
+/// 检查回调（None 清除）
+pub fn validate(callback: Option<Bound<'_, PyAny>>) -> PyResult<Option<Py<PyAny>>> {
+    if callback.as_ref().is_some_and(|callback| !callback.is_callable()) {
+        return Err(PyTypeError::new_err("callback must be callable or None"));
+    }
+    Ok(callback.map(Bound::unbind))
+}
+
+/// 调用回调，返回设置 import.meta 字段的代码（回调返回 None 或空 dict 时为 None）
+fn fields(py: Python<'_>, callback: &Py<PyAny>, url: &str, path: &str) -> PyResult<Option<String>> {
+    let info = PyDict::new(py);
+    info.set_item("url", url)?;
+    info.set_item("path", path)?;
+    let fields = callback.bind(py).call1((info,))?;
+    if fields.is_none() {
+        return Ok(None);
+    }
+    let fields = fields
+        .cast::<PyDict>()
+        .map_err(|_| PyTypeError::new_err(format!("must return a dict or None, got {}", fields.get_type())))?;
+    if fields.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(format!("Object.assign(import.meta, {});", python_to_json(fields.as_any())?)))
+}
+
+/// 在模块代码前加上设置 import.meta 字段的代码
+pub fn apply(callback: &Py<PyAny>, url: &str, path: &str, code: String) -> Result<String> {
+    let prelude = Python::attach(|py| fields(py, callback, url, path))
+        .map_err(|e| anyhow!("import.meta callback for {} failed: {}", path, e))?;
+    Ok(match prelude {
+        Some(prelude) => prelude + &code,
+        None => code,
+    })
+}
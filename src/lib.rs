@@ -14,21 +14,77 @@ mod fetch_ops;
 mod performance_ops;
 mod random_state;  // New: Seedable RNG state management
 mod random_ops;     // New: Random seed control operations
+mod snapshot;       // V8 startup snapshots
+mod code_cache;     // V8 code cache for compile()/compile_file()
+mod pool;           // ContextPool: worker threads each owning an isolate
+mod threaded;       // ThreadedContext: Send-able handle to a dedicated isolate thread
+mod aio;            // asyncio integration for ThreadedContext / ContextPool
+mod fork;           // os.fork() detection (pthread_atfork)
+mod background_compile;  // compile_background(): off-thread compilation via code cache
+mod eval_context;   // Thread-local implicit Context for module-level eval()
+mod realm;          // Realm: extra V8 contexts sharing a Context's isolate
+mod shadow_realm;   // ShadowRealm proposal: host hook + Python-side evaluate()
+mod snapshot_pool;  // SnapshotPool: fresh Contexts cloned from a warm snapshot
+mod timings;        // Per-stage execution timings (wrap / compile / execute / event loop)
+mod source_string;  // Zero-copy external V8 strings for large code inputs
+mod source_file;    // compile_file(): encoding, BOM / shebang handling, script vs module mode
+mod file_watch;     // compile_file(watch=True): recompile modified files before the next call
+mod project;        // compile_project(): load a directory of scripts in dependency order
+mod console_capture;  // eval(capture_output=True): per-call console output
+mod shared_buffer;  // SharedBuffer: memory shared between Python and Contexts as a SharedArrayBuffer
+mod permissions;    // Opt-in net / read / write / env capabilities per Context
+mod harden;         // freeze_intrinsics(): freeze built-in prototypes against pollution
+mod watchdog;       // timeout_ms / cpu_limit_ms: wall-clock and CPU-time execution limits
+mod audit;          // audit_ops: per-call log of op invocations
+mod sandbox_fs;     // fs_roots: Deno-style file API limited to allowlisted directories
+mod quota;          // quotas: per-Context lifetime limits (executions, CPU, result size, console output)
+mod regexp_guard;   // Interrupt-based termination that reports runaway regular expressions
+mod debugger;       // set_breakpoint()/debug_call(): in-process V8 inspector session
+mod exec_hooks;     // set_execution_hooks(): global callbacks around every JS execution
+mod py_functions;   // register(): Python callables exposed as JS globals, capture_stack()
+mod history;        // history=N: ring buffer of the last executions for post-mortem debugging
+mod slow_script;    // on_slow_script(): report calls exceeding a threshold without killing them
+mod gc_events;      // on_gc(): V8 GC events (type, duration, heap before/after) delivered to Python
+mod wasm;           // load_wasm(): instantiate WebAssembly modules from Python bytes
+mod wasm_memory;    // wasm_memory(): zero-copy memoryview over WASM linear memory
+mod version;        // version_info(): crate / deno_core / V8 versions and enabled features
+mod pickle;         // pickle / deepcopy of Context: replayed global scripts + startup snapshot
+mod missing_globals;  // `X is not defined` hints and missing_globals() dry runs
+mod presets;        // compile(preset=[...]): bundled crypto-js / jsencrypt compatible libraries
+mod defaults;       // set_defaults(): process-wide default limits for every new Context
+mod result_stream;  // evaluate(stream_to=...): chunked result output to a Python file object
+mod event_loop;     // event_loop={...}: per-call max_iterations / pump_message_loop / wait_for_inspector
+mod template;       // render_call(): call templates with JSON-serialized named placeholders
+mod state;          // export_state() / import_state(): structured-clone snapshots of selected globals
+mod recycle;        // compile(recycle_after={...}): rebuild the isolate when call / heap thresholds are reached
+mod call_cache;     // call(): cached v8::Global<Function> per function name instead of eval-ing the call code
+mod trace;          // call(trace_id=...): per-call trace IDs in JS, console output, hooks and errors
+mod globals_diff;   // snapshot_globals() / diff_globals(): fingerprints of global object properties between calls
+mod scheduler;      // ContextPool scheduling: per-call priority, bounded queue with backpressure, per-tenant fairness
+mod warmup;         // warmup(): pre-run representative calls so functions are compiled before traffic arrives
+mod poison;         // Fatal isolate errors: poison the Context (ContextPoisoned) or recreate it (auto_recreate=True)
+mod transfer;       // transfer(): copy a global between Contexts via V8 structured clone, no Python/JSON detour
+mod prepared;       // prepare("hash(?)"): compile a call expression once, call it with converted arguments
+mod map_stream;     // ContextPool.imap(): yield map() results as items complete (sync iterator / async iterator)
+mod schema;         // call(expect_schema={...}): check the result's shape in JS before transport (SchemaMismatch)
+mod replay;         // start_recording() / start_replay(): record and replay random values, time, fetch responses and Python callback results
+mod registry;       // ContextRegistry: lazily created, LRU / TTL cached Context (or Realm) per tenant key
+mod import_meta;    // on_import_meta(callback): host-defined import.meta fields for each loaded ES module
+mod remote_script;  // compile_url(url, sha256=...): download, verify, cache to disk and compile a pinned remote script
+mod abort;          // AbortToken: abort a running asyncio call (also when the awaiting task is cancelled)
+mod handles;        // handles(): list / release long-lived V8 objects (Realms, prepared functions, call cache), max_handles cap
+mod yield_points;   // yield_interval_ms: service Python signals from periodic V8 interrupts during long synchronous scripts
+mod undefined_policy;  // undefined_as="none"|"sentinel"|"omit": one conversion policy for undefined in results (never_jscore.UNDEFINED)
 
 use pyo3::prelude::*;
-use std::sync::Once;
 
+use background_compile::CompileTask;
 use context::Context;
-
-// V8 platform initialization - must happen exactly once
-static INIT: Once = Once::new();
-
-fn ensure_v8_initialized() {
-    INIT.call_once(|| {
-        // Initialize V8 platform (required before creating any isolates)
-        deno_core::JsRuntime::init_platform(None, false);
-    });
-}
+use pool::ContextPool;
+use realm::Realm;
+use shadow_realm::ShadowRealm;
+use snapshot_pool::SnapshotPool;
+use threaded::ThreadedContext;
 
 /// never_jscore Python 模块
 ///
@@ -55,13 +111,60 @@ fn ensure_v8_initialized() {
 ///     ctx2 = never_jscore.Context()
 ///     # ctx1 和 ctx2 完全隔离，互不影响
 ///     ```
-#[pymodule]
+///
+/// 自由线程（free-threaded，3.13t）解释器：
+/// - 模块声明为 `gil_used = false`，导入时不会重新启用 GIL；abi3 wheel 不能在自由线程解释器上加载，
+///   需要关闭默认的 `abi3` feature 单独构建（`maturin build --no-default-features -i python3.13t`）
+/// - Context 是 unsendable 的，只能在创建它的线程上使用，每个线程持有独立的 isolate
+/// - 执行状态（结果、$terminate() 的 Hook 数据、定时器、随机数）保存在各自 Context 的 OpState 或 thread_local 中，
+///   不同 Context 之间不共享
+/// - 进程级的状态（init() / set_defaults() 的设置、快照缓存、ContextPool/ThreadedContext 的任务队列）由 Mutex 保护；
+///   localStorage / sessionStorage 与浏览器的同源存储一样，在进程内所有 Context 之间共享
+#[pymodule(gil_used = false)]
 fn never_jscore(m: &Bound<'_, PyModule>) -> PyResult<()> {
-    // Initialize V8 platform when module is first imported
-    ensure_v8_initialized();
+    // V8 platform is initialized lazily on first Context creation (see runtime::ensure_v8_initialized),
+    // so a parent process that only imports the module can fork safely
+    fork::install_handlers();
 
-    // 只导出 Context 类
-    // 不提供模块级函数，确保用户必须实例化才能使用
+    // 导出 Context 类
+    // 模块级函数提供与具体 Context 无关的工具（如快照构建、全局初始化），
+    // 以及使用线程内隐式 Context 的 eval()
     m.add_class::<Context>()?;
+    m.add_class::<ContextPool>()?;
+    m.add_class::<ThreadedContext>()?;
+    m.add_class::<CompileTask>()?;
+    m.add_class::<Realm>()?;
+    m.add_class::<ShadowRealm>()?;
+    m.add_class::<SnapshotPool>()?;
+    m.add_class::<debugger::PausedFrame>()?;
+    m.add_class::<shared_buffer::SharedBuffer>()?;
+    m.add_class::<prepared::PreparedCall>()?;
+    m.add_class::<map_stream::MapStream>()?;
+    m.add_class::<registry::ContextRegistry>()?;
+    m.add_class::<abort::AbortToken>()?;
+    m.add_class::<handles::Handle>()?;
+    m.add_class::<undefined_policy::JsUndefined>()?;
+    m.add("UNDEFINED", undefined_policy::undefined(m.py())?)?;
+    m.add("QuotaExceeded", m.py().get_type::<quota::QuotaExceeded>())?;
+    m.add("PoolFull", m.py().get_type::<scheduler::PoolFull>())?;
+    m.add("ContextPoisoned", m.py().get_type::<poison::ContextPoisoned>())?;
+    m.add("SchemaMismatch", m.py().get_type::<schema::SchemaMismatch>())?;
+    m.add_function(wrap_pyfunction!(eval_context::eval, m)?)?;
+    m.add_function(wrap_pyfunction!(eval_context::aeval, m)?)?;
+    m.add_function(wrap_pyfunction!(eval_context::acall, m)?)?;
+    m.add_function(wrap_pyfunction!(eval_context::clear_eval_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(eval_context::configure_eval, m)?)?;
+    m.add_function(wrap_pyfunction!(eval_context::get_eval_context, m)?)?;
+    m.add_function(wrap_pyfunction!(runtime::init, m)?)?;
+    m.add_function(wrap_pyfunction!(runtime::set_v8_flags, m)?)?;
+    m.add_function(wrap_pyfunction!(runtime::shutdown, m)?)?;
+    m.add_function(wrap_pyfunction!(defaults::set_defaults, m)?)?;
+    m.add_function(wrap_pyfunction!(convert::register_converter, m)?)?;
+    m.add_function(wrap_pyfunction!(project::compile_project, m)?)?;
+    m.add_function(wrap_pyfunction!(exec_hooks::set_execution_hooks, m)?)?;
+    m.add_function(wrap_pyfunction!(snapshot::build_snapshot, m)?)?;
+    m.add_function(wrap_pyfunction!(version::version_info, m)?)?;
+    m.add_function(wrap_pyfunction!(transfer::transfer, m)?)?;
+    m.add_function(wrap_pyfunction!(remote_script::compile_url, m)?)?;
     Ok(())
 }
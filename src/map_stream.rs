@@ -0,0 +1,246 @@
+// map_stream.rs - ContextPool.imap()：每一项完成后就取回 map() 的结果
+//
+// map() 等所有项完成后才一次返回列表，下游处理（写库、上传）只能在整批结束后开始，
+// 整批结果也要同时放在内存中。imap() 返回 MapStream，既是同步迭代器也是异步迭代器：
+// - ordered=True（默认）按 items 的顺序产出结果；ordered=False 按完成顺序产出 (索引, 结果)
+// - 同时最多 2 × workers 个任务在执行、排队或等待取走：Python 处理上一个结果时工作线程继续执行后面的项，
+//   每次取结果时补充新的任务
+// - 某一项失败时在轮到它时抛出（包含索引），迭代随之结束，剩余的项不再提交
+// - 队列已满（on_full="raise"）时停止提交，产出已提交的项之后抛出 PoolFull
+// - async for 的每次 __anext__ 在辅助线程中等待下一项，不阻塞事件循环
+
+use pyo3::exceptions::{PyException, PyStopAsyncIteration};
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::convert::ResultFormat;
+use crate::pool::{map_task, MapResult, SubmitError, TaskSink};
+use crate::scheduler::{Scheduler, Submitter};
+
+/// 迭代的进度（在锁内修改）
+struct StreamState {
+    /// 尚未提交的项
+    pending: std::iter::Enumerate<std::vec::IntoIter<Vec<JsonValue>>>,
+    /// 已提交、结果尚未收到的任务数
+    running: usize,
+    results: Receiver<MapResult>,
+    sender: Sender<MapResult>,
+    /// 已收到、尚未产出的结果
+    buffered: BTreeMap<usize, Result<String, String>>,
+    /// ordered=True 时下一个产出的索引
+    next_index: usize,
+    /// 提交被拒绝（PoolFull），产出已提交的项之后抛出
+    rejected: Option<PyErr>,
+    finished: bool,
+}
+
+/// 流式的批量调用结果
+///
+/// 由 `ContextPool.imap()` 返回，可以用 `for` 或 `async for` 迭代（只能迭代一次）。
+///
+/// Example:
+///     ```python
+///     for token in pool.imap("sign", items):
+///         upload(token)            # 与后面的项的 JS 执行重叠
+///
+///     async for index, token in pool.imap("sign", items, ordered=False):
+///         await save(index, token)
+///     ```
+#[pyclass]
+pub struct MapStream {
+    scheduler: Arc<Scheduler>,
+    name: String,
+    priority: i32,
+    tenant: Option<String>,
+    auto_await: bool,
+    ordered: bool,
+    in_flight: usize,
+    format: ResultFormat,
+    state: Mutex<StreamState>,
+}
+
+impl MapStream {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        scheduler: Arc<Scheduler>,
+        name: String,
+        call_args: Vec<Vec<JsonValue>>,
+        priority: i32,
+        tenant: Option<String>,
+        auto_await: bool,
+        ordered: bool,
+        in_flight: usize,
+        format: ResultFormat,
+    ) -> Self {
+        let (sender, results) = mpsc::channel();
+        MapStream {
+            scheduler,
+            name,
+            priority,
+            tenant,
+            auto_await,
+            ordered,
+            in_flight,
+            format,
+            state: Mutex::new(StreamState {
+                pending: call_args.into_iter().enumerate(),
+                running: 0,
+                results,
+                sender,
+                buffered: BTreeMap::new(),
+                next_index: 0,
+                rejected: None,
+                finished: false,
+            }),
+        }
+    }
+
+    /// 补充任务直到达到并发上限（队列满时按 on_full 等待或拒绝）
+    fn refill(&self, state: &mut StreamState) {
+        let submitter = Submitter {
+            scheduler: &self.scheduler,
+            priority: self.priority,
+            tenant: self.tenant.clone(),
+            wait: true,
+        };
+        // 已收到但未产出的结果也计入上限：ordered=True 时一个慢的项不会让缓冲的结果无限增长
+        while state.running + state.buffered.len() < self.in_flight && state.rejected.is_none() {
+            let Some((index, args)) = state.pending.next() else {
+                break;
+            };
+            match submitter.submit(map_task(&self.name, index, args, self.auto_await, &state.sender)) {
+                Ok(()) => state.running += 1,
+                // 提交失败时任务被 drop，回复句柄补发的错误直接丢弃
+                Err(SubmitError::Closed) => {
+                    let _ = state.results.recv();
+                    state.buffered.insert(index, Err("Pool is closed".to_string()));
+                }
+                Err(full) => {
+                    let _ = state.results.recv();
+                    state.rejected = Some(full.into_py("Pool is closed"));
+                }
+            }
+        }
+    }
+
+    /// 取出下一个可以产出的结果
+    fn take_ready(&self, state: &mut StreamState) -> Option<MapResult> {
+        if self.ordered {
+            let result = state.buffered.remove(&state.next_index)?;
+            state.next_index += 1;
+            Some((state.next_index - 1, result))
+        } else {
+            state.buffered.pop_first()
+        }
+    }
+
+    /// 等待下一项（释放 GIL），迭代结束时返回 None
+    fn next_result(&self, py: Python<'_>) -> PyResult<Option<(usize, String)>> {
+        py.detach(|| {
+            let mut state = self.state.lock().unwrap();
+            loop {
+                if state.finished {
+                    return Ok(None);
+                }
+                if let Some((index, result)) = self.take_ready(&mut state) {
+                    self.refill(&mut state);
+                    return match result {
+                        Ok(json) => Ok(Some((index, json))),
+                        Err(e) => {
+                            state.finished = true;
+                            Err(PyException::new_err(format!("Call error (item {}): {}", index, e)))
+                        }
+                    };
+                }
+                self.refill(&mut state);
+                if state.running == 0 {
+                    // 池关闭时的错误不经过工作线程，已经在 buffered 中
+                    if self.has_ready(&state) {
+                        continue;
+                    }
+                    state.finished = true;
+                    return match state.rejected.take() {
+                        Some(error) => Err(error),
+                        None => Ok(None),
+                    };
+                }
+                match state.results.recv() {
+                    Ok((index, result)) => {
+                        state.running -= 1;
+                        state.buffered.insert(index, result);
+                    }
+                    Err(_) => state.running = 0,
+                }
+            }
+        })
+    }
+
+    /// 是否有可以产出的结果
+    fn has_ready(&self, state: &StreamState) -> bool {
+        if self.ordered {
+            state.buffered.contains_key(&state.next_index)
+        } else {
+            !state.buffered.is_empty()
+        }
+    }
+
+    /// 把下一项转换为 Python 对象：ordered=True 时为结果，否则为 (索引, 结果)
+    fn next_value<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let Some((index, json)) = self.next_result(py)? else {
+            return Ok(None);
+        };
+        let value = self.format.to_python(py, &json, false)?;
+        if self.ordered {
+            Ok(Some(value))
+        } else {
+            Ok(Some(PyTuple::new(py, [index.into_pyobject(py)?.into_any(), value])?.into_any()))
+        }
+    }
+}
+
+#[pymethods]
+impl MapStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        self.next_value(py)
+    }
+
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// 返回下一项的 asyncio.Future，由辅助线程等待结果后完成
+    fn __anext__<'py>(slf: Bound<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+        let future = event_loop.call_method0("create_future")?;
+
+        let stream = slf.unbind();
+        let loop_handle = event_loop.unbind();
+        let future_handle = future.clone().unbind();
+        std::thread::Builder::new()
+            .name("never_jscore-imap".to_string())
+            .spawn(move || {
+                Python::attach(|py| {
+                    let outcome = match stream.borrow(py).next_value(py) {
+                        Ok(Some(value)) => Ok(value.unbind()),
+                        Ok(None) => Err(PyStopAsyncIteration::new_err(())),
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = crate::aio::settle(py, loop_handle.bind(py), future_handle.bind(py), outcome) {
+                        // 事件循环已关闭时无法投递结果，只能打印出来
+                        e.print(py);
+                    }
+                });
+            })
+            .map_err(|e| PyException::new_err(format!("Failed to spawn imap thread: {}", e)))?;
+
+        Ok(future)
+    }
+}
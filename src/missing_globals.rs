@@ -0,0 +1,114 @@
+// missing_globals.rs - 缺失全局变量的诊断
+//
+// 从浏览器 / Node.js 中扒下来的脚本最常见的失败是 `ReferenceError: X is not defined`。
+// 错误信息末尾附加一行结构化提示 `hint: missing_global="X" (...)`，按名称给出对应的解决办法；
+// missing_globals(code) 试运行代码，一次列出脚本用到但当前 Context 中不存在的全部全局变量。
+//
+// 试运行方式：代码包装在函数中执行（声明的变量和函数留在函数作用域），
+// 执行期间在全局对象原型链的末端（Object.prototype 之前）插入记录访问的 Proxy，
+// 只有在全局对象上找不到的名称才会到达 Proxy：记录名称并返回吸收任何操作的桩对象，
+// 让脚本继续执行以发现后续缺失的名称。对这些名称的赋值保存在 Proxy 中，不写入全局对象。
+
+/// Context 的 Web API 扩展提供的全局变量（enable_extensions=False / no_ops=True 时不存在）
+const EXTENSION_GLOBALS: &[&str] = &[
+    "window", "self", "document", "navigator", "location", "screen", "localStorage", "sessionStorage",
+    "XMLHttpRequest", "fetch", "Headers", "Response", "FormData", "Blob", "crypto", "btoa", "atob",
+    "setTimeout", "setInterval", "clearTimeout", "clearInterval", "TextEncoder", "TextDecoder",
+    "URL", "URLSearchParams", "performance", "Event", "EventTarget", "Worker",
+];
+
+/// never_jscore 没有实现的浏览器 DOM / BOM API
+const BROWSER_GLOBALS: &[&str] = &[
+    "Element", "HTMLElement", "HTMLCanvasElement", "Node", "Image", "MutationObserver", "history",
+    "indexedDB", "WebSocket", "requestAnimationFrame", "getComputedStyle", "CustomEvent", "DOMParser",
+    "chrome", "top", "parent", "frames",
+];
+
+/// Node.js 的全局变量（never_jscore 不提供）
+const NODE_GLOBALS: &[&str] = &[
+    "require", "module", "exports", "process", "Buffer", "global", "__dirname", "__filename", "setImmediate",
+];
+
+/// 根据名称给出的解决办法
+fn suggestion(name: &str) -> &'static str {
+    if EXTENSION_GLOBALS.contains(&name) {
+        "Web API provided by the extensions: create the Context with enable_extensions=True and no_ops=False, \
+         or use configure_eval(preset='browser')"
+    } else if BROWSER_GLOBALS.contains(&name) {
+        "browser API not implemented by never_jscore: define a stub with compile() before running the script"
+    } else if NODE_GLOBALS.contains(&name) {
+        "Node.js global not provided by never_jscore: define a stub with compile(), e.g. 'var global = globalThis;'"
+    } else {
+        "not defined by the script or an earlier compile(): load the script that defines it first, \
+         or list everything the script expects with ctx.missing_globals(code)"
+    }
+}
+
+/// `X is not defined` 错误的提示行，其他错误返回 None
+pub fn hint(name: Option<&str>, message: Option<&str>) -> Option<String> {
+    if name != Some("ReferenceError") {
+        return None;
+    }
+    let global = message?.strip_suffix(" is not defined")?;
+    if global.is_empty() || global.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(format!("hint: missing_global=\"{}\" ({})", global, suggestion(global)))
+}
+
+// 试运行探测：参数是包装了用户代码的函数，返回缺失名称的 JSON 数组
+const PROBE: &str = r#"
+(function(run) {
+    const missing = new Set();
+    const assigned = new Map();
+    const stub = new Proxy(function() {}, {
+        get(target, key) {
+            if (key === Symbol.toPrimitive) return () => '';
+            if (key === 'then' || key === Symbol.unscopables) return undefined;
+            return stub;
+        },
+        set() { return true; },
+        deleteProperty() { return true; },
+        apply() { return stub; },
+        construct() { return stub; },
+    });
+
+    let last = globalThis;
+    while (Object.getPrototypeOf(last) !== null && Object.getPrototypeOf(last) !== Object.prototype) {
+        last = Object.getPrototypeOf(last);
+    }
+    const original = Object.getPrototypeOf(last);
+    const base = original === null ? Object.create(null) : original;
+    const recorder = new Proxy(base, {
+        has(target, key) {
+            return typeof key === 'string' || key in target;
+        },
+        get(target, key, receiver) {
+            if (typeof key !== 'string' || key in target) return Reflect.get(target, key, receiver);
+            if (assigned.has(key)) return assigned.get(key);
+            missing.add(key);
+            return stub;
+        },
+        set(target, key, value) {
+            if (typeof key !== 'string' || key in target) return Reflect.set(target, key, value);
+            assigned.set(key, value);
+            return true;
+        },
+    });
+
+    Object.setPrototypeOf(last, recorder);
+    try {
+        run.call(globalThis);
+    } catch (e) {
+        // 桩对象无法模拟所有行为，之后的错误不影响已经记录的名称
+    } finally {
+        Object.setPrototypeOf(last, original);
+    }
+    return JSON.stringify(Array.from(missing));
+})
+"#;
+
+/// 生成试运行脚本：用户代码包装在函数中交给 PROBE（不依赖 eval，allow_dynamic_code=False 时同样可用）
+pub fn probe_script(code: &str) -> String {
+    format!("{}(function() {{\n{}\n}})", PROBE.trim(), code)
+}
@@ -1,16 +1,40 @@
 use deno_core::{OpState, extension, op2};
 use std::rc::Rc;
 
-use crate::storage::{ResultStorage, save_hook_data};
+use crate::storage::ResultStorage;
 
 /// Op: 存储 JavaScript 执行结果
 ///
 /// 这个 op 允许 JavaScript 代码将执行结果存储到 Rust 端。
+/// `call_id` 是求值包装函数收到的调用 ID，用于区分交错进行的多次调用。
 /// 使用 #[op2(fast)] 优化性能。
 #[op2(fast)]
-pub fn op_store_result(state: &mut OpState, #[string] value: String) {
+pub fn op_store_result(state: &mut OpState, call_id: u32, #[string] value: String) {
     if let Some(storage) = state.try_borrow_mut::<Rc<ResultStorage>>() {
-        storage.store(value);
+        storage.store(call_id, value);
+    }
+}
+
+/// Op: 存储结果的一个分块（evaluate(stream_to=...) / call(stream_to=...)）
+///
+/// 求值包装函数按顺序把序列化的 JSON 分块传进来，立即写入调用附带的 Python 文件对象。
+/// 写入失败或超出 max_result_bytes 时抛出异常中断序列化，原始错误保存在 ResultStream 中。
+#[op2(fast)]
+pub fn op_store_result_chunk(state: &mut OpState, call_id: u32, #[string] chunk: &str) -> Result<(), std::io::Error> {
+    let Some(stream) = state.try_borrow::<Rc<ResultStorage>>().and_then(|storage| storage.stream(call_id)) else {
+        return Ok(());
+    };
+    let quota = state.try_borrow::<Rc<crate::quota::Quota>>().cloned();
+    stream.write(chunk, quota.as_deref()).map_err(|e| stream.fail(e))
+}
+
+/// Op: 结果不符合 call(expect_schema=...) 的规则
+///
+/// 求值包装函数在序列化之前检查结果，不符合时传入不符合的位置（JSON 数组），不再存储结果。
+#[op2(fast)]
+pub fn op_store_schema_mismatch(state: &mut OpState, call_id: u32, #[string] mismatches: String) {
+    if let Some(storage) = state.try_borrow_mut::<Rc<ResultStorage>>() {
+        storage.store_mismatch(call_id, mismatches);
     }
 }
 
@@ -24,19 +48,20 @@ pub fn op_store_result(state: &mut OpState, #[string] value: String) {
 #[op2(fast)]
 pub fn op_early_return(state: &mut OpState, #[string] value: String) {
     if let Some(storage) = state.try_borrow_mut::<Rc<ResultStorage>>() {
-        storage.store(value.clone());
-        storage.mark_early_return();
+        storage.mark_early_return(value);
     }
 }
 
-/// Op: 保存 Hook 数据到全局存储（新版本，配合 terminate_execution 使用）
+/// Op: 保存 Hook 数据到当前 Context 的 ResultStorage（新版本，配合 terminate_execution 使用）
 ///
 /// 在调用 op_terminate_execution 前保存数据。
-/// 数据保存到全局静态变量，即使 isolate 被终止也能访问。
+/// 数据保存在 Rust 侧，即使 isolate 被终止也能访问，不同 Context 之间互不可见。
 #[op2]
 #[string]
-pub fn op_save_hook_data(#[string] data: String) -> String {
-    save_hook_data(data.clone());
+pub fn op_save_hook_data(state: &mut OpState, #[string] data: String) -> String {
+    if let Some(storage) = state.try_borrow::<Rc<ResultStorage>>() {
+        storage.save_hook_data(data.clone());
+    }
     data
 }
 
@@ -62,6 +87,8 @@ extension!(
     pyexecjs_ext,
     ops = [
         op_store_result,
+        op_store_result_chunk,
+        op_store_schema_mismatch,
         op_early_return,
         op_save_hook_data,
         op_terminate_execution
@@ -0,0 +1,411 @@
+// permissions.rs - 按 Context 授予的能力（网络、文件读写、环境变量）
+//
+// 默认（不传 permissions）拒绝网络和文件读写，宿主环境变量不可见（process.env 为空，Context(env=...) 注入的变量可见）。
+// 传入字典后进入白名单模式：未列出的类别全部拒绝；permissions="all" 显式开放全部能力（以前的默认行为）。
+// 被拒绝的操作在 JS 中抛出 name 为 "PermissionDenied" 的错误。
+//
+// 权限保存在 OpState 中，由 fetch / fs / env 相关的 op 在执行前检查；OpState 中没有权限时（如构建快照的 runtime）全部拒绝。
+// 网络请求的每一次重定向都重新检查（redirect_policy），允许的主机不能把请求转发到白名单之外。
+// 文件路径先逐级解析符号链接再处理 `..`，op 打开的是检查过的路径（不跟随最后一级符号链接），
+// 打开后再确认解析结果没有变化。
+// ops 以字符串返回错误（"Error: ..."），拒绝时返回 "PermissionDenied: ..."，
+// 由 polyfill 转换为 PermissionDenied 异常。
+
+use deno_core::OpState;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// 拒绝时 op 返回值的前缀
+pub const DENIED_PREFIX: &str = "PermissionDenied: ";
+
+/// 某一类能力的授权范围
+#[derive(Clone, Debug)]
+enum Grant {
+    /// 全部允许
+    All,
+    /// 只允许列出的项（主机 / 路径 / 变量名）
+    Only(Vec<String>),
+}
+
+/// Context 的权限设置
+///
+/// 每个字段为 None 表示该类别被拒绝。
+#[derive(Clone, Debug, Default)]
+pub struct Permissions {
+    /// 网络访问：主机名（"api.example.com"）或主机名加端口（"localhost:8080"）
+    net: Option<Grant>,
+    /// 文件读取：允许的目录或文件（创建时解析为绝对路径）
+    read: Option<Grant>,
+    /// 文件写入：允许的目录或文件
+    write: Option<Grant>,
+    /// 环境变量：允许读取的变量名
+    env: Option<Grant>,
+}
+
+/// OpState 中没有权限设置时使用：全部拒绝
+static DENY_ALL: Permissions = Permissions {
+    net: None,
+    read: None,
+    write: None,
+    env: None,
+};
+
+/// 重定向次数上限（与 reqwest 的默认策略相同）
+const MAX_REDIRECTS: usize = 10;
+
+impl Permissions {
+    /// 解析 Context(permissions=...)：字典（白名单）或 "all"（全部允许）
+    pub fn from_arg(value: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(dict) = value.cast::<PyDict>() {
+            return Self::from_py(dict);
+        }
+        match value.extract::<String>() {
+            Ok(all) if all == "all" => Ok(Self::all()),
+            Ok(other) => Err(PyValueError::new_err(format!(
+                "Unknown permissions '{}', expected a dict or \"all\"",
+                other
+            ))),
+            Err(_) => Err(PyTypeError::new_err("permissions must be a dict or \"all\"")),
+        }
+    }
+
+    /// permissions="all"：不做任何限制
+    pub fn all() -> Self {
+        Permissions {
+            net: Some(Grant::All),
+            read: Some(Grant::All),
+            write: Some(Grant::All),
+            env: Some(Grant::All),
+        }
+    }
+
+    /// 不传 permissions 时的默认权限：拒绝网络和文件读写，宿主环境变量不可见
+    ///
+    /// injected_env 为 true（Context(env=...)）时注入的变量全部可见。
+    pub fn restricted(injected_env: bool) -> Self {
+        Permissions {
+            env: Some(if injected_env { Grant::All } else { Grant::Only(Vec::new()) }),
+            ..DENY_ALL.clone()
+        }
+    }
+
+    /// 从 Python 字典解析，如 `{"net": ["api.example.com"], "read": ["./data"], "env": False}`
+    ///
+    /// 每一项可以是 True（全部允许）、False / None（拒绝）、字符串或字符串列表（白名单）。
+    pub fn from_py(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let mut permissions = Permissions::default();
+        for (key, value) in dict.iter() {
+            let key: String = key.extract()?;
+            let grant = parse_grant(&key, &value)?;
+            match key.as_str() {
+                "net" => permissions.net = grant,
+                "read" => permissions.read = grant.map(resolve_paths),
+                "write" => permissions.write = grant.map(resolve_paths),
+                "env" => permissions.env = grant,
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown permission '{}', expected 'net', 'read', 'write' or 'env'",
+                        key
+                    )))
+                }
+            }
+        }
+        Ok(permissions)
+    }
+
+    fn check_net(&self, url: &str) -> Result<(), String> {
+        let denied = || format!("{}network access to '{}' is not allowed", DENIED_PREFIX, url);
+        match &self.net {
+            Some(Grant::All) => Ok(()),
+            Some(Grant::Only(hosts)) => {
+                let url = reqwest::Url::parse(url).map_err(|_| denied())?;
+                let host = url.host_str().ok_or_else(denied)?.to_ascii_lowercase();
+                let port = url.port_or_known_default();
+                let allowed = hosts.iter().any(|entry| match entry.rsplit_once(':') {
+                    Some((h, p)) if p.parse::<u16>().is_ok() => h == host && p.parse::<u16>().ok() == port,
+                    _ => *entry == host,
+                });
+                if allowed { Ok(()) } else { Err(denied()) }
+            }
+            None => Err(denied()),
+        }
+    }
+
+    /// 检查文件路径，返回要打开的路径：白名单模式下为解析后的绝对路径，全部允许时为原路径
+    fn check_path(grant: &Option<Grant>, kind: &str, path: &str) -> Result<CheckedPath, String> {
+        let denied = || format!("{}{} access to '{}' is not allowed", DENIED_PREFIX, kind, path);
+        match grant {
+            Some(Grant::All) => Ok(CheckedPath::Unrestricted(PathBuf::from(path))),
+            Some(Grant::Only(allowed)) => match absolute_path(Path::new(path)) {
+                Some(target) if allowed.iter().any(|dir| target.starts_with(dir)) => Ok(CheckedPath::Resolved(target)),
+                _ => Err(denied()),
+            },
+            None => Err(denied()),
+        }
+    }
+
+    fn env_allowed(&self, name: &str) -> bool {
+        match &self.env {
+            Some(Grant::All) => true,
+            Some(Grant::Only(names)) => names.iter().any(|n| n == name),
+            None => false,
+        }
+    }
+}
+
+fn parse_grant(key: &str, value: &Bound<'_, PyAny>) -> PyResult<Option<Grant>> {
+    if value.is_none() {
+        return Ok(None);
+    }
+    if let Ok(flag) = value.extract::<bool>() {
+        return Ok(flag.then_some(Grant::All));
+    }
+    if value.is_instance_of::<PyString>() {
+        return Ok(Some(Grant::Only(vec![value.extract()?])));
+    }
+    let items: Vec<String> = value.extract().map_err(|_| {
+        PyValueError::new_err(format!("Permission '{}' must be a bool, a string or a list of strings", key))
+    })?;
+    Ok(Some(Grant::Only(items)))
+}
+
+/// 把白名单中的路径解析为绝对路径（相对于创建 Context 时的工作目录，无法解析的符号链接不授予任何权限）
+fn resolve_paths(grant: Grant) -> Grant {
+    match grant {
+        Grant::All => Grant::All,
+        Grant::Only(paths) => Grant::Only(
+            paths
+                .iter()
+                .filter_map(|p| absolute_path(Path::new(p)))
+                .map(|p| p.to_string_lossy().into_owned())
+                .collect(),
+        ),
+    }
+}
+
+/// 转换为绝对路径：逐级解析符号链接之后再处理 `..`
+///
+/// `..` 作用于已解析的真实目录（与内核一致），`link/..` 回到链接目标的父目录而不是 link 所在的目录。
+/// 第一个不存在的部分之后按字面处理，其后出现 `..` 时返回 None：按字面弹出的目录不一定是真实路径的父目录
+/// （`allowed/missing/../link_out` 会被当作 `allowed/link_out`，内核却会跟随 link_out 离开白名单）。
+/// 某一级是无法解析的符号链接（目标不存在或循环）时同样返回 None：
+/// 写入会跟随链接在目标位置创建文件，而按字面解析的路径看起来仍在白名单内。
+pub(crate) fn absolute_path(path: &Path) -> Option<PathBuf> {
+    let joined = match std::env::current_dir() {
+        Ok(cwd) if path.is_relative() => cwd.join(path),
+        _ => path.to_path_buf(),
+    };
+    let mut resolved = PathBuf::new();
+    // resolved 是否仍是存在的真实路径
+    let mut existing = true;
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if existing => {
+                resolved.pop();
+            }
+            Component::ParentDir => return None,
+            Component::Normal(name) if existing => {
+                let candidate = resolved.join(name);
+                if candidate.symlink_metadata().is_ok() {
+                    resolved = candidate.canonicalize().ok()?;
+                } else {
+                    resolved = candidate;
+                    existing = false;
+                }
+            }
+            other => resolved.push(other),
+        }
+    }
+    Some(resolved)
+}
+
+/// 打开检查过的路径：不跟随最后一级符号链接，打开后确认解析结果仍是检查过的路径
+///
+/// 写入时不截断，由调用方确认之后再清空。路径在检查之后被替换时返回 Ok(None)，调用方按拒绝处理。
+/// 写入前先确认父目录仍是真实路径，避免在白名单之外创建文件；文件由这里新建而打开后的检查失败时删除它。
+pub(crate) fn open_resolved(target: &Path, write: bool) -> std::io::Result<Option<fs::File>> {
+    let options = |create: bool| {
+        let mut options = fs::OpenOptions::new();
+        if create {
+            options.write(true).create_new(true);
+        } else if write {
+            options.write(true);
+        } else {
+            options.read(true);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_NOFOLLOW);
+        }
+        options
+    };
+    if write && !unchanged_parent(target) {
+        return Ok(None);
+    }
+    let (file, created) = match options(false).open(target) {
+        Ok(file) => (file, false),
+        Err(e) if write && e.kind() == std::io::ErrorKind::NotFound => (options(true).open(target)?, true),
+        Err(e) => return Err(e),
+    };
+    match target.canonicalize() {
+        Ok(resolved) if resolved == target => Ok(Some(file)),
+        _ => {
+            if created {
+                drop(file);
+                let _ = fs::remove_file(target);
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// 解析过的路径是否仍然有效：重新解析后没有变化（检查之后某一级被替换为符号链接时为 false）
+///
+/// 路径不存在时返回 true，由调用方的文件操作报告 NotFound。
+pub(crate) fn unchanged(target: &Path) -> bool {
+    match target.canonicalize() {
+        Ok(resolved) => resolved == target,
+        Err(_) => true,
+    }
+}
+
+/// 父目录是否仍然有效（见 [`unchanged`]）
+fn unchanged_parent(target: &Path) -> bool {
+    target.parent().is_none_or(unchanged)
+}
+
+/// 通过 read / write 检查的路径
+pub enum CheckedPath {
+    /// 全部允许：按原路径访问
+    Unrestricted(PathBuf),
+    /// 白名单模式：解析后的绝对路径，打开时不能再跟随符号链接
+    Resolved(PathBuf),
+}
+
+impl CheckedPath {
+    /// 实际访问的路径
+    pub fn path(&self) -> &Path {
+        match self {
+            CheckedPath::Unrestricted(path) | CheckedPath::Resolved(path) => path,
+        }
+    }
+
+    /// 不经过 open() 的操作（exists / stat / readdir）完成后确认路径没有被替换，被替换时按拒绝处理
+    pub fn recheck(&self, kind: &str, path: &str) -> Result<(), String> {
+        match self {
+            CheckedPath::Resolved(target) if !unchanged(target) => {
+                Err(format!("{}{} access to '{}' is not allowed", DENIED_PREFIX, kind, path))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 打开文件（写入时不截断），白名单模式下不跟随最后一级符号链接，打开后确认解析结果没有变化
+    pub fn open(&self, kind: &str, path: &str, write: bool) -> Result<fs::File, String> {
+        let failed = |e: std::io::Error| format!("Error: Failed to {} file '{}': {}", kind, path, e);
+        match self {
+            CheckedPath::Unrestricted(target) => {
+                let mut options = fs::OpenOptions::new();
+                if write {
+                    options.write(true).create(true);
+                } else {
+                    options.read(true);
+                }
+                options.open(target).map_err(failed)
+            }
+            CheckedPath::Resolved(target) => match open_resolved(target, write).map_err(failed)? {
+                Some(file) => Ok(file),
+                None => Err(format!("{}{} access to '{}' is not allowed", DENIED_PREFIX, kind, path)),
+            },
+        }
+    }
+}
+
+/// 取出 Context 的权限设置（Context 总会设置，没有时全部拒绝）
+fn permissions(state: &OpState) -> &Permissions {
+    state.try_borrow::<Arc<Permissions>>().map_or(&DENY_ALL, |p| p)
+}
+
+/// 检查网络访问，拒绝时返回 "PermissionDenied: ..." 错误信息
+pub fn check_net(state: &OpState, url: &str) -> Result<(), String> {
+    permissions(state).check_net(url)
+}
+
+/// 网络请求的重定向策略：每一跳都重新检查 net 权限
+///
+/// 重定向到白名单之外的主机时请求失败，错误的来源链中包含 [`RedirectDenied`]。
+pub fn redirect_policy(state: &OpState) -> reqwest::redirect::Policy {
+    let permissions = permissions(state).clone();
+    match &permissions.net {
+        Some(Grant::All) => reqwest::redirect::Policy::limited(MAX_REDIRECTS),
+        None => reqwest::redirect::Policy::none(),
+        Some(Grant::Only(_)) => reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                return attempt.error("too many redirects");
+            }
+            match permissions.check_net(attempt.url().as_str()) {
+                Ok(()) => attempt.follow(),
+                Err(denied) => attempt.error(RedirectDenied(denied)),
+            }
+        }),
+    }
+}
+
+/// 重定向被 net 权限拒绝（message 为 "PermissionDenied: ..."）
+#[derive(Debug)]
+pub struct RedirectDenied(pub String);
+
+impl std::fmt::Display for RedirectDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RedirectDenied {}
+
+/// 请求失败是否因为重定向被拒绝，是时返回 "PermissionDenied: ..." 错误信息
+pub fn redirect_denied(error: &reqwest::Error) -> Option<String> {
+    let mut source = std::error::Error::source(error);
+    while let Some(e) = source {
+        if let Some(denied) = e.downcast_ref::<RedirectDenied>() {
+            return Some(denied.0.clone());
+        }
+        source = e.source();
+    }
+    None
+}
+
+/// 检查文件读取，返回要访问的路径
+pub fn check_read(state: &OpState, path: &str) -> Result<CheckedPath, String> {
+    Permissions::check_path(&permissions(state).read, "read", path)
+}
+
+/// 检查文件写入，返回要访问的路径
+pub fn check_write(state: &OpState, path: &str) -> Result<CheckedPath, String> {
+    Permissions::check_path(&permissions(state).write, "write", path)
+}
+
+/// 检查环境变量读取
+pub fn check_env(state: &OpState, name: &str) -> Result<(), String> {
+    if permissions(state).env_allowed(name) {
+        Ok(())
+    } else {
+        Err(format!("{}access to environment variable '{}' is not allowed", DENIED_PREFIX, name))
+    }
+}
+
+/// 读取全部环境变量时是否完全拒绝（未授予任何 env 权限）
+pub fn env_denied(state: &OpState) -> bool {
+    permissions(state).env.is_none()
+}
+
+/// 环境变量是否可见（白名单模式下只返回列出的变量）
+pub fn env_visible(state: &OpState, name: &str) -> bool {
+    permissions(state).env_allowed(name)
+}
@@ -0,0 +1,379 @@
+// pickle.rs - Context 的 pickle / copy.deepcopy 支持
+//
+// V8 不能序列化一个已经在运行的 isolate，因此 Context 记录执行过的全局脚本
+// （compile / compile_file / eval / compile_background / freeze_intrinsics），pickle 时：
+// - 在后台线程的快照 runtime 中按原顺序重放这些脚本，生成启动快照
+// - 与脚本本身和构造参数一起保存为 dict
+//
+// 恢复时 never_jscore 版本和 V8 参数（jitless / v8_flags）一致则直接从快照启动，
+// 否则（如在另一个版本的进程中加载）退回到重新执行脚本。
+//
+// 无法重放的状态（register() 的 Python 函数、WebAssembly、ES 模块、权限、共享内存、自定义快照、import_state()）
+// 会让 pickle 抛出 TypeError，而不是得到一个静默丢失状态的副本。
+//
+// 同一份记录也用于 recycle_after（recycle.rs）在当前进程中重建 isolate：构造参数（包括自定义快照）、
+// 权限和 register() 的 Python 函数可以原样重建，只有 WebAssembly、ES 模块、共享内存、import_state()
+// 和 Realm 会阻止重建。
+//
+// compile(code, tag="libA") 执行的脚本带有标签，unload("libA") 用去掉这些脚本的记录重建 isolate。
+//
+// 记录的代码总量有上限（MAX_REPLAY_BYTES）：长期运行、不断执行新脚本的 Context 超过上限后停止记录，
+// pickle 和重建随之不可用，内存不会无限增长。设置了 recycle_after 时重建依赖完整的记录，不受上限限制。
+
+use pyo3::exceptions::{PyException, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::context::ContextOptions;
+use crate::fs_ops::InjectedEnv;
+use crate::quota::QuotaLimits;
+use crate::runtime::runtime_config;
+use crate::snapshot::OwnedBlob;
+
+/// 重建 Context 的一步
+pub(crate) enum RebuildStep {
+    /// 全局脚本（名称, 代码）
+    Script(String, String),
+    /// register() 注册的 Python 函数（名称, 函数）
+    Function(String, Py<PyAny>),
+}
+
+/// pickle 数据格式版本
+const FORMAT: u32 = 1;
+
+/// 重放记录保存的代码总量上限
+const MAX_REPLAY_BYTES: usize = 16 * 1024 * 1024;
+
+/// 超过上限时 pickle / 重建的错误原因
+const OVERSIZED: &str = "its executed scripts exceed the 16 MB replay log limit";
+
+/// Context 的可重放状态
+pub(crate) struct ReplayLog {
+    /// 构造参数（已应用 set_defaults() 的默认值）
+    options: ContextOptions,
+    /// 构造时传入的启动快照（重建时使用，pickle 不保存）
+    snapshot: Option<&'static [u8]>,
+    /// 成功执行过的全局脚本（名称, 代码）
+    scripts: Vec<(String, String)>,
+    /// 每个脚本的标签（与 scripts 一一对应）
+    tags: Vec<Option<String>>,
+    /// scripts 中代码的总字节数
+    bytes: usize,
+    /// register() 注册的 Python 函数（注册时已执行的脚本数, 名称, 函数），重建时按原顺序注册
+    functions: Vec<(usize, String, Py<PyAny>)>,
+    /// 无法 pickle 的原因
+    blocker: Option<&'static str>,
+    /// 无法重建的原因
+    rebuild_blocker: Option<&'static str>,
+}
+
+impl ReplayLog {
+    pub fn new(options: &ContextOptions) -> Self {
+        let blocker = if options.snapshot.is_some() {
+            Some("it was started from a custom snapshot")
+        } else if options.permissions.is_some() {
+            Some("it has permissions")
+        } else if options.fs_roots.is_some() {
+            Some("it has fs_roots")
+        } else if !options.shared_buffers.is_empty() {
+            Some("it has shared buffers")
+        } else {
+            None
+        };
+        ReplayLog {
+            options: ContextOptions { snapshot: None, ..options.clone() },
+            snapshot: options.snapshot,
+            scripts: Vec::new(),
+            tags: Vec::new(),
+            bytes: 0,
+            functions: Vec::new(),
+            blocker,
+            rebuild_blocker: None,
+        }
+    }
+
+    /// 是否还在记录脚本（既无法 pickle 也无法重建时不再保存代码）
+    pub fn recording(&self) -> bool {
+        self.blocker.is_none() || self.rebuild_blocker.is_none()
+    }
+
+    /// 执行前检查是否要记录 len 字节的脚本（为 true 时调用方保留一份代码）
+    ///
+    /// 超过 MAX_REPLAY_BYTES 时停止记录，pickle 和重建都不可用；unbounded 为 true（设置了 recycle_after）时不受上限限制。
+    pub fn reserve(&mut self, len: usize, unbounded: bool) -> bool {
+        if !self.recording() {
+            return false;
+        }
+        if unbounded || self.bytes + len <= MAX_REPLAY_BYTES {
+            return true;
+        }
+        self.block_rebuild(OVERSIZED);
+        self.block(OVERSIZED);
+        false
+    }
+
+    /// 记录一个成功执行的全局脚本
+    pub fn record(&mut self, name: &str, code: String) {
+        if self.recording() {
+            self.bytes += code.len();
+            self.scripts.push((name.to_string(), code));
+            self.tags.push(None);
+        }
+    }
+
+    /// 已记录的脚本数（compile() 执行前记下，之后用 tag_since() 给新脚本加标签）
+    pub fn script_count(&self) -> usize {
+        self.scripts.len()
+    }
+
+    /// 给 start 之后记录的脚本加上标签
+    pub fn tag_since(&mut self, start: usize, tag: &str) {
+        for slot in self.tags.iter_mut().skip(start) {
+            *slot = Some(tag.to_string());
+        }
+    }
+
+    /// 是否有带该标签的脚本
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.as_deref() == Some(tag))
+    }
+
+    /// 去掉带该标签的脚本（之后注册的 Python 函数的位置随之前移）
+    pub fn remove_tag(&mut self, tag: &str) {
+        for (at, _, _) in &mut self.functions {
+            *at -= self.tags[..*at].iter().filter(|t| t.as_deref() == Some(tag)).count();
+        }
+        let scripts = std::mem::take(&mut self.scripts);
+        let tags = std::mem::take(&mut self.tags);
+        (self.scripts, self.tags) = scripts.into_iter().zip(tags).filter(|(_, t)| t.as_deref() != Some(tag)).unzip();
+        self.bytes = self.scripts.iter().map(|(_, code)| code.len()).sum();
+    }
+
+    /// 恢复 pickle 中保存的标签（脚本数不一致时忽略）
+    pub fn restore_tags(&mut self, tags: Vec<Option<String>>) {
+        if tags.len() == self.tags.len() {
+            self.tags = tags;
+        }
+    }
+
+    /// 记录 register() 注册的 Python 函数（无法 pickle，但可以重建）
+    pub fn record_function(&mut self, name: &str, func: Py<PyAny>) {
+        self.block("it has Python functions registered with register()");
+        if self.rebuild_blocker.is_none() {
+            self.functions.push((self.scripts.len(), name.to_string(), func));
+        }
+    }
+
+    /// 标记为无法 pickle（保留第一个原因）
+    pub fn block(&mut self, reason: &'static str) {
+        self.blocker.get_or_insert(reason);
+        if !self.recording() {
+            self.scripts.clear();
+            self.tags.clear();
+            self.bytes = 0;
+        }
+    }
+
+    /// 标记为无法重建（保留第一个原因）
+    pub fn block_rebuild(&mut self, reason: &'static str) {
+        self.rebuild_blocker.get_or_insert(reason);
+        self.functions.clear();
+        if !self.recording() {
+            self.scripts.clear();
+            self.tags.clear();
+            self.bytes = 0;
+        }
+    }
+
+    /// 无法重建的原因
+    pub fn rebuild_blocker(&self) -> Option<&'static str> {
+        self.rebuild_blocker
+    }
+
+    /// 重建所需的构造参数、脚本和 Python 函数（按执行顺序），跳过带 skip_tag 标签的脚本
+    pub fn rebuild_steps(&self, py: Python<'_>, skip_tag: Option<&str>) -> (ContextOptions, Vec<RebuildStep>) {
+        let options = ContextOptions { snapshot: self.snapshot, ..self.options.clone() };
+        let mut steps = Vec::with_capacity(self.scripts.len() + self.functions.len());
+        let mut functions = self.functions.iter().peekable();
+        for (index, (name, code)) in self.scripts.iter().enumerate() {
+            while let Some((_, name, func)) = functions.next_if(|(at, _, _)| *at <= index) {
+                steps.push(RebuildStep::Function(name.clone(), func.clone_ref(py)));
+            }
+            if skip_tag.is_none() || self.tags[index].as_deref() != skip_tag {
+                steps.push(RebuildStep::Script(name.clone(), code.clone()));
+            }
+        }
+        for (_, name, func) in functions {
+            steps.push(RebuildStep::Function(name.clone(), func.clone_ref(py)));
+        }
+        (options, steps)
+    }
+
+    /// 生成 pickle 状态（在后台线程构建快照）
+    pub fn to_python<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        if let Some(reason) = self.blocker {
+            return Err(PyTypeError::new_err(format!("cannot pickle Context: {}", reason)));
+        }
+        let options = &self.options;
+
+        // no_ops 的 Context 没有扩展 ops，不使用快照，恢复时重新执行脚本
+        let snapshot = if options.no_ops {
+            None
+        } else {
+            let scripts: Vec<(&str, &str)> = self.scripts.iter().map(|(n, c)| (n.as_str(), c.as_str())).collect();
+            let (enable_extensions, enable_logging) = (options.enable_extensions, options.enable_logging);
+            let blob = py
+                .detach(|| {
+                    std::thread::scope(|scope| {
+                        std::thread::Builder::new()
+                            .stack_size(crate::runtime::worker_stack_size())
+                            .name("never_jscore-pickle".to_string())
+                            .spawn_scoped(scope, || {
+                                crate::snapshot::create_snapshot_blob_from_scripts(&scripts, enable_extensions, enable_logging)
+                                    .map_err(|e| e.to_string())
+                            })
+                            .map_err(|e| format!("Failed to spawn snapshot thread: {}", e))?
+                            .join()
+                            .map_err(|_| "Snapshot thread panicked".to_string())?
+                    })
+                })
+                .map_err(|e| PyException::new_err(format!("Pickle error: {}", e)))?;
+            Some(crate::snapshot::with_header(&blob, enable_extensions, enable_logging))
+        };
+
+        let state = PyDict::new(py);
+        state.set_item("format", FORMAT)?;
+        state.set_item("version", env!("CARGO_PKG_VERSION"))?;
+        state.set_item("runtime", runtime_fingerprint())?;
+        state.set_item("enable_extensions", options.enable_extensions)?;
+        state.set_item("enable_logging", options.enable_logging)?;
+        state.set_item("random_seed", options.random_seed)?;
+        state.set_item("code_cache_dir", options.code_cache_dir.as_ref())?;
+        state.set_item("initial_heap_mb", options.initial_heap_mb)?;
+        state.set_item("max_heap_mb", options.max_heap_mb)?;
+        state.set_item("allow_dynamic_code", options.allow_dynamic_code)?;
+        state.set_item("timeout_ms", options.timeout_ms)?;
+        state.set_item("cpu_limit_ms", options.cpu_limit_ms)?;
+        state.set_item("yield_interval_ms", options.yield_interval_ms)?;
+        state.set_item("audit_ops", options.audit_ops)?;
+        state.set_item("no_ops", options.no_ops)?;
+        state.set_item("quotas", options.quotas.map(|q| quotas_to_python(py, &q)).transpose()?)?;
+        state.set_item("history", options.history)?;
+        state.set_item("shared_memory", options.shared_memory)?;
+        state.set_item("weak_refs", options.weak_refs)?;
+        state.set_item("auto_recreate", options.auto_recreate)?;
+        state.set_item("undefined_as", options.undefined_as.name())?;
+        state.set_item("env", options.env.as_ref().map(|env| env.0.clone()))?;
+        state.set_item("scripts", self.scripts.clone())?;
+        state.set_item("tags", self.tags.clone())?;
+        state.set_item("snapshot", snapshot.map(|data| PyBytes::new(py, &data)))?;
+        Ok(state)
+    }
+}
+
+/// 从 pickle 状态恢复的构造参数
+pub(crate) struct Restored {
+    pub options: ContextOptions,
+    pub scripts: Vec<(String, String)>,
+    /// 脚本的标签（早期版本的 pickle 没有标签）
+    pub tags: Vec<Option<String>>,
+    /// 可以在当前进程中使用的快照（options.snapshot 指向它，为 None 时需要重新执行 scripts）
+    pub snapshot: Option<Arc<OwnedBlob>>,
+}
+
+/// 解析 pickle 状态
+pub(crate) fn restore(state: &Bound<'_, PyDict>) -> PyResult<Restored> {
+    let format: u32 = get(state, "format")?;
+    if format != FORMAT {
+        return Err(PyValueError::new_err(format!("Unsupported Context pickle format {}", format)));
+    }
+
+    let quotas: Option<Bound<'_, PyDict>> = get(state, "quotas")?;
+    let code_cache_dir: Option<PathBuf> = get(state, "code_cache_dir")?;
+    let env: Option<Vec<(String, String)>> = get(state, "env")?;
+    // 早期版本的 pickle 没有 yield_interval_ms / undefined_as
+    let yield_interval_ms: Option<u64> =
+        state.get_item("yield_interval_ms")?.map(|ms| ms.extract()).transpose()?.flatten();
+    let undefined_as: Option<String> = state.get_item("undefined_as")?.map(|name| name.extract()).transpose()?.flatten();
+    let mut options = ContextOptions {
+        enable_extensions: get(state, "enable_extensions")?,
+        enable_logging: get(state, "enable_logging")?,
+        random_seed: get(state, "random_seed")?,
+        code_cache_dir,
+        allow_dynamic_code: get(state, "allow_dynamic_code")?,
+        audit_ops: get(state, "audit_ops")?,
+        no_ops: get(state, "no_ops")?,
+        shared_memory: get(state, "shared_memory")?,
+        weak_refs: get(state, "weak_refs")?,
+        auto_recreate: get(state, "auto_recreate")?,
+        env: env.map(|vars| Arc::new(InjectedEnv(vars))),
+        ..Default::default()
+    }
+    .with_heap_limits(get(state, "initial_heap_mb")?, get(state, "max_heap_mb")?)?
+    .with_time_limits(get(state, "timeout_ms")?, get(state, "cpu_limit_ms")?)?
+    .with_yield_interval(yield_interval_ms)?
+    .with_undefined_as(undefined_as.as_deref())?
+    .with_quotas(quotas.as_ref())?
+    .with_history(get(state, "history")?)?;
+
+    let version: String = get(state, "version")?;
+    let runtime: String = get(state, "runtime")?;
+    let snapshot: Option<Vec<u8>> = get(state, "snapshot")?;
+    let snapshot = snapshot.filter(|_| version == env!("CARGO_PKG_VERSION") && runtime == runtime_fingerprint());
+    let snapshot = snapshot.map(|data| crate::snapshot::owned_snapshot_from_py(&data)).transpose()?;
+    options.snapshot = snapshot.as_ref().map(|owner| owner.blob());
+
+    Ok(Restored {
+        options,
+        scripts: get(state, "scripts")?,
+        tags: state.get_item("tags")?.map(|tags| tags.extract()).transpose()?.unwrap_or_default(),
+        snapshot,
+    })
+}
+
+/// 恢复后的 Context 的重放记录（从快照启动不算作自定义快照）
+pub(crate) fn restored_log(options: &ContextOptions, scripts: Vec<(String, String)>) -> ReplayLog {
+    ReplayLog {
+        options: ContextOptions { snapshot: None, ..options.clone() },
+        snapshot: None,
+        tags: vec![None; scripts.len()],
+        bytes: scripts.iter().map(|(_, code)| code.len()).sum(),
+        scripts,
+        functions: Vec::new(),
+        blocker: None,
+        rebuild_blocker: None,
+    }
+}
+
+/// 影响快照兼容性的进程级 V8 参数
+fn runtime_fingerprint() -> String {
+    let config = runtime_config();
+    format!("jitless={};flags={}", config.jitless, config.v8_flags.join(" "))
+}
+
+fn quotas_to_python<'py>(py: Python<'py>, quotas: &QuotaLimits) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    if let Some(value) = quotas.max_executions {
+        dict.set_item("max_executions", value)?;
+    }
+    if let Some(value) = quotas.max_cpu_ms {
+        dict.set_item("max_cpu_ms", value)?;
+    }
+    if let Some(value) = quotas.max_result_bytes {
+        dict.set_item("max_result_bytes", value)?;
+    }
+    if let Some(value) = quotas.max_console_bytes {
+        dict.set_item("max_console_bytes", value)?;
+    }
+    Ok(dict)
+}
+
+fn get<'py, T: FromPyObjectOwned<'py>>(state: &Bound<'py, PyDict>, key: &str) -> PyResult<T> {
+    state
+        .get_item(key)?
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid Context pickle state: missing '{}'", key)))?
+        .extract()
+        .map_err(|e| PyValueError::new_err(format!("Invalid Context pickle state: '{}': {}", key, Into::<PyErr>::into(e))))
+}
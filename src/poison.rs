@@ -0,0 +1,63 @@
+// poison.rs - isolate 遇到致命错误后的降级处理：标记 Context 为不可用（poisoned），可选自动重建
+//
+// 能够捕获的致命错误不再让 Python 进程崩溃，也不会让之后的调用在损坏的 isolate 上继续执行：
+// - 超出 max_heap_mb：执行被终止并抛出 "Heap limit exceeded"，此时堆可能处于不一致的状态
+// - 执行过程中的 Rust panic（deno_core 内部的断言等）：捕获后抛出异常
+// 发生后 Context 被标记为 poisoned，之后的执行抛出 ContextPoisoned（包含最初的原因），ctx.poisoned 返回原因。
+//
+// 构造参数 auto_recreate=True 时，按 recycle_after 的方式（recycle.rs）重建 isolate：
+// Context 在下一次调用之前，ContextPool / ThreadedContext 的工作线程在当前任务结束后。
+// 构造参数、初始化脚本和 register() 的 Python 函数原样重建；无法重建时（load_wasm() 等无法重放的状态、
+// 初始化脚本失败）保持 poisoned。
+//
+// 无法捕获的情况：没有设置 max_heap_mb 时 V8 的堆耗尽、V8 内部的 CHECK 失败仍会中止进程，
+// 需要防止 OOM 崩溃时请同时设置 max_heap_mb。
+
+use pyo3::exceptions::PyRuntimeError;
+use std::any::Any;
+
+pyo3::create_exception!(
+    never_jscore,
+    ContextPoisoned,
+    PyRuntimeError,
+    "Context 的 isolate 遇到致命错误后不再可用（auto_recreate=False）"
+);
+
+/// Context 已 poisoned 的错误（转换为 Python 的 ContextPoisoned）
+#[derive(Debug)]
+pub struct PoisonedError(String);
+
+impl std::fmt::Display for PoisonedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Context is poisoned after a fatal error ({}); create a new Context or pass auto_recreate=True",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for PoisonedError {}
+
+/// 创建 Context 已 poisoned 的错误
+pub fn poisoned_error(reason: &str) -> anyhow::Error {
+    anyhow::Error::new(PoisonedError(reason.to_string()))
+}
+
+/// 遇到致命错误的那次调用抛出的错误信息
+pub fn fatal_message(reason: &str, auto_recreate: bool) -> String {
+    if auto_recreate {
+        format!("{}; the Context will be recreated before the next call", reason)
+    } else {
+        format!("{}; the Context is now poisoned and can no longer be used", reason)
+    }
+}
+
+/// panic 的信息
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
@@ -0,0 +1,773 @@
+// pool.rs - ContextPool: 多个工作线程各自持有一个 isolate
+//
+// Context 是 unsendable 的，无法在 Web 服务的多个工作线程之间共享。
+// ContextPool 自己创建 N 个工作线程，每个线程持有一个预热好的 Context，
+// 所有线程从同一个调度器取任务（优先级、有界队列和按租户轮转，见 scheduler.rs），
+// pool.call() 由空闲的线程执行。
+
+use anyhow::{Result, anyhow};
+use pyo3::exceptions::{PyException, PyRuntimeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Barrier, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use serde_json::Value as JsonValue;
+
+use crate::context::{Context, ContextOptions};
+use crate::convert::{call_args_to_json, ResultFormat};
+use crate::map_stream::MapStream;
+use crate::scheduler::{OnFull, Scheduler, Submitter};
+use crate::abort::AbortToken;
+
+/// 在工作线程的 Context 上执行的任务
+pub(crate) type Task = Box<dyn FnOnce(&Context) + Send>;
+
+/// 工作线程取任务的结果
+pub(crate) enum Next {
+    Task(Task),
+    /// 等待超时，没有新任务
+    Idle,
+    /// 队列已关闭且没有剩余任务
+    Closed,
+}
+
+/// 工作线程的任务来源：mpsc 队列（ThreadedContext 等）或 ContextPool 的调度器
+pub(crate) trait TaskSource: Send + Sync + 'static {
+    /// 取下一个任务；timeout 为 None 时一直等待
+    fn next_task(&self, timeout: Option<Duration>) -> Next;
+}
+
+impl TaskSource for Mutex<Receiver<Task>> {
+    fn next_task(&self, timeout: Option<Duration>) -> Next {
+        // 锁只在取任务时持有，任务在锁外执行
+        let Ok(receiver) = self.lock() else {
+            return Next::Closed;
+        };
+        match timeout {
+            Some(timeout) => match receiver.recv_timeout(timeout) {
+                Ok(task) => Next::Task(task),
+                Err(RecvTimeoutError::Timeout) => Next::Idle,
+                Err(RecvTimeoutError::Disconnected) => Next::Closed,
+            },
+            None => match receiver.recv() {
+                Ok(task) => Next::Task(task),
+                Err(_) => Next::Closed,
+            },
+        }
+    }
+}
+
+/// 提交任务失败的原因
+pub(crate) enum SubmitError {
+    Closed,
+    /// 队列已满（ContextPool 的 max_queue）
+    Full(String),
+}
+
+impl SubmitError {
+    /// 转换为 Python 异常；closed 为队列关闭时的错误信息
+    pub fn into_py(self, closed: &str) -> PyErr {
+        match self {
+            SubmitError::Closed => PyException::new_err(closed.to_string()),
+            SubmitError::Full(message) => crate::scheduler::PoolFull::new_err(message),
+        }
+    }
+}
+
+/// 任务的提交端
+pub(crate) trait TaskSink {
+    fn submit(&self, task: Task) -> Result<(), SubmitError>;
+}
+
+impl TaskSink for Sender<Task> {
+    fn submit(&self, task: Task) -> Result<(), SubmitError> {
+        self.send(task).map_err(|_| SubmitError::Closed)
+    }
+}
+
+/// 后台事件循环模式下，每次推进事件循环 / 等待新任务的时间片
+const EVENT_LOOP_TICK: Duration = Duration::from_millis(10);
+
+/// 启动一个持有 Context 的工作线程
+///
+/// Context 在工作线程内创建并执行 init_code，初始化失败时返回错误。
+/// 线程在任务队列关闭（所有 Sender 被 drop / 调度器关闭）并取完剩余任务后退出。
+pub(crate) fn spawn_worker<Q: TaskSource + ?Sized>(
+    name: String,
+    options: ContextOptions,
+    init_code: Option<String>,
+    queue: Arc<Q>,
+) -> Result<JoinHandle<()>> {
+    let (ready_tx, ready_rx) = mpsc::channel::<Result<(), String>>();
+
+    let handle = std::thread::Builder::new()
+        .stack_size(crate::runtime::worker_stack_size())
+        .name(name)
+        .spawn(move || {
+            let mut ctx = match Context::new(options) {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e.to_string()));
+                    return;
+                }
+            };
+
+            if let Some(code) = init_code {
+                if let Err(e) = ctx.exec_script(code) {
+                    let _ = ready_tx.send(Err(format!("Init code error: {}", e)));
+                    return;
+                }
+            }
+            let _ = ready_tx.send(Ok(()));
+
+            loop {
+                // 后台事件循环模式：没有新任务时推进定时器和 Promise
+                let timeout = ctx.background_event_loop().then_some(EVENT_LOOP_TICK);
+                match queue.next_task(timeout) {
+                    Next::Task(task) => task(&ctx),
+                    Next::Idle => ctx.pump_event_loop(EVENT_LOOP_TICK),
+                    Next::Closed => break,
+                }
+                // compile(recycle_after={...}) 的阈值：达到时换成重建的 Context
+                if let Some(fresh) = ctx.recycled() {
+                    ctx.replace_with(fresh);
+                }
+            }
+        })
+        .map_err(|e| anyhow!("Failed to spawn worker thread: {}", e))?;
+
+    match ready_rx.recv() {
+        Ok(Ok(())) => Ok(handle),
+        Ok(Err(e)) => {
+            let _ = handle.join();
+            Err(anyhow!("{}", e))
+        }
+        Err(_) => {
+            let _ = handle.join();
+            Err(anyhow!("Worker thread exited during initialization"))
+        }
+    }
+}
+
+/// 把任务发送到队列并等待结果
+///
+/// 队列已满（PoolFull）时返回包含 PyErr 的错误，调用方用 downcast 取出。
+pub(crate) fn run_task<R, F>(sender: &impl TaskSink, f: F) -> Result<R>
+where
+    R: Send + 'static,
+    F: FnOnce(&Context) -> R + Send + 'static,
+{
+    let (reply_tx, reply_rx) = mpsc::channel();
+    sender
+        .submit(Box::new(move |ctx: &Context| {
+            let _ = reply_tx.send(f(ctx));
+        }))
+        .map_err(|e| match e {
+            SubmitError::Closed => anyhow!("Pool is closed"),
+            full => anyhow::Error::from(full.into_py("Pool is closed")),
+        })?;
+
+    reply_rx
+        .recv()
+        .map_err(|_| anyhow!("Worker thread crashed while executing the task"))
+}
+
+/// map() / imap() 单项的结果：项的索引和结果 JSON（或错误信息）
+pub(crate) type MapResult = (usize, Result<String, String>);
+
+/// map() 单个任务的回复句柄
+///
+/// 工作线程在执行任务时崩溃的话，句柄随闭包一起被 drop，此时补发一个错误，
+/// 避免 map() 永远等待。
+struct MapReply {
+    index: usize,
+    sender: Sender<MapResult>,
+    sent: bool,
+}
+
+impl MapReply {
+    fn send(mut self, result: Result<String, String>) {
+        self.sent = true;
+        let _ = self.sender.send((self.index, result));
+    }
+}
+
+impl Drop for MapReply {
+    fn drop(&mut self) {
+        if !self.sent {
+            let _ = self.sender.send((self.index, Err("Worker thread crashed while executing the task".to_string())));
+        }
+    }
+}
+
+/// 创建 map() / imap() 的单项任务：调用 name 后把结果连同索引发回 sender
+pub(crate) fn map_task(name: &str, index: usize, args: Vec<JsonValue>, auto_await: bool, sender: &Sender<MapResult>) -> Task {
+    let reply = MapReply { index, sender: sender.clone(), sent: false };
+    let name = name.to_string();
+    Box::new(move |ctx: &Context| {
+        reply.send(ctx.execute_call(&name, &args, auto_await).map_err(|e| e.to_string()));
+    })
+}
+
+/// 分批提交调用任务，同时最多 in_flight 个任务在执行，结果按提交顺序返回
+///
+/// 队列已满（on_full="raise"）时停止提交，等待已提交的任务完成后返回 PoolFull。
+fn map_tasks(
+    sender: &impl TaskSink,
+    name: &str,
+    call_args: Vec<Vec<JsonValue>>,
+    in_flight: usize,
+    auto_await: bool,
+) -> PyResult<Vec<Result<String, String>>> {
+    let mut results: Vec<Option<Result<String, String>>> = vec![None; call_args.len()];
+    let (reply_tx, reply_rx) = mpsc::channel();
+
+    let mut pending = call_args.into_iter().enumerate();
+    let mut running = 0;
+    let mut rejected = None;
+    loop {
+        // 补充任务直到达到并发上限
+        while running < in_flight && rejected.is_none() {
+            let Some((index, args)) = pending.next() else {
+                break;
+            };
+            match sender.submit(map_task(name, index, args, auto_await, &reply_tx)) {
+                Ok(()) => running += 1,
+                // 提交失败时任务（连同回复句柄）被 drop，不计入 running
+                Err(SubmitError::Closed) => {
+                    let _ = reply_rx.recv();
+                    results[index] = Some(Err("Pool is closed".to_string()));
+                }
+                Err(full) => {
+                    let _ = reply_rx.recv();
+                    rejected = Some(full.into_py("Pool is closed"));
+                }
+            }
+        }
+
+        if running == 0 {
+            break;
+        }
+        if let Ok((index, result)) = reply_rx.recv() {
+            results[index] = Some(result);
+            running -= 1;
+        }
+    }
+
+    if let Some(error) = rejected {
+        return Err(error);
+    }
+    Ok(results
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Err("Task was not executed".to_string())))
+        .collect())
+}
+
+/// 转换任务的错误：PoolFull 等 Python 异常原样返回，其他错误加上前缀
+fn task_error(prefix: &str, e: anyhow::Error) -> PyErr {
+    match e.downcast::<PyErr>() {
+        Ok(err) => err,
+        Err(e) => PyException::new_err(format!("{}: {}", prefix, e)),
+    }
+}
+
+/// 多线程 Context 池
+///
+/// 创建 size 个工作线程，每个线程持有一个独立的 Context 并执行 init_code。
+/// 池对象本身可以在任意 Python 线程之间共享，调用时会释放 GIL 并分派给空闲的工作线程。
+///
+/// 注意：每个 Context 的状态相互独立，全局变量的修改只对执行该任务的工作线程可见，
+/// 适合无状态的函数调用（签名、加密等）。
+///
+/// 排队的任务按优先级（priority）和租户（tenant）调度，max_queue 限制排队的任务数，见 scheduler.rs。
+///
+/// Example:
+///     ```python
+///     import never_jscore
+///
+///     pool = never_jscore.ContextPool(js_code, size=4)
+///
+///     # Flask / FastAPI 处理函数中直接使用
+///     @app.route("/sign")
+///     def sign():
+///         return pool.call("sign", [request.args["data"]])
+///     ```
+#[pyclass]
+pub struct ContextPool {
+    scheduler: Arc<Scheduler>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+    size: usize,
+    fork_generation: usize,
+}
+
+impl ContextPool {
+    /// 以指定的优先级和租户提交任务（池已关闭时报错）
+    fn submitter(&self, priority: i32, tenant: Option<String>, wait: bool) -> PyResult<Submitter<'_>> {
+        self.check_open()?;
+        Ok(Submitter { scheduler: &self.scheduler, priority, tenant, wait })
+    }
+
+    /// 检查池可以接收任务（没有关闭、不是 fork 继承的）
+    fn check_open(&self) -> PyResult<()> {
+        crate::fork::check(self.fork_generation, "ContextPool")?;
+        if self.scheduler.is_closed() {
+            return Err(PyException::new_err("ContextPool is closed"));
+        }
+        Ok(())
+    }
+
+    /// 关闭任务队列并等待所有工作线程退出
+    fn shutdown(&self) {
+        self.scheduler.close();
+        let workers = std::mem::take(&mut *self.workers.lock().unwrap());
+        // fork 后的子进程中工作线程并不存在，不能 join
+        if crate::fork::is_inherited(self.fork_generation) {
+            std::mem::forget(workers);
+            return;
+        }
+        for worker in workers {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[pymethods]
+impl ContextPool {
+    /// 创建 Context 池
+    ///
+    /// Args:
+    ///     init_code: 每个 Context 创建后执行的初始化代码（通常是要加载的 JS 库），可选
+    ///     size: 工作线程（isolate）数量，默认 4
+    ///     enable_extensions / enable_logging / random_seed / snapshot / code_cache_dir / initial_heap_mb / max_heap_mb:
+    ///         与 Context 构造函数含义相同，应用于池中每个 Context
+    ///     shared_memory / shared_buffers: 与 Context 构造函数含义相同；
+    ///         shared_buffers 中的 SharedBuffer 在每个工作线程中都是同一块内存，在 init_code 之前定义
+    ///     auto_recreate: 工作线程的 Context 遇到致命错误（超出 max_heap_mb 等）后，在当前任务结束后自动重建，
+    ///         默认 False（之后分配到该工作线程的任务抛出异常）
+    ///     max_queue: 排队（尚未开始执行）的任务数上限，默认 None 不限制
+    ///     on_full: 队列满时的处理方式，"block"（默认，等待空位）或 "raise"（抛出 PoolFull）；
+    ///         call_async() / evaluate_async() 不能阻塞事件循环，队列满时总是抛出 PoolFull
+    ///     undefined_as: 结果中 undefined 的转换策略（"none" / "sentinel" / "omit"），与 Context 构造函数含义相同
+    ///     permissions: 网络 / 文件 / 环境变量权限（字典或 "all"），与 Context 构造函数含义相同，默认拒绝网络和文件读写
+    ///
+    /// Raises:
+    ///     ValueError: size 或 max_queue 为 0，on_full、undefined_as 或 permissions 无效，或快照参数不一致
+    ///     Exception: 初始化代码执行失败
+    #[new]
+    #[pyo3(signature = (init_code=None, size=4, enable_extensions=true, enable_logging=false, random_seed=None, snapshot=None, code_cache_dir=None, initial_heap_mb=None, max_heap_mb=None, shared_memory=false, shared_buffers=None, auto_recreate=false, max_queue=None, on_full="block", undefined_as=None, permissions=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        py: Python<'_>,
+        init_code: Option<String>,
+        size: usize,
+        enable_extensions: bool,
+        enable_logging: bool,
+        random_seed: Option<u32>,
+        snapshot: Option<&[u8]>,
+        code_cache_dir: Option<PathBuf>,
+        initial_heap_mb: Option<usize>,
+        max_heap_mb: Option<usize>,
+        shared_memory: bool,
+        shared_buffers: Option<&Bound<'_, PyDict>>,
+        auto_recreate: bool,
+        max_queue: Option<usize>,
+        on_full: &str,
+        undefined_as: Option<&str>,
+        permissions: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        if size == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("ContextPool size must be at least 1"));
+        }
+        if max_queue == Some(0) {
+            return Err(pyo3::exceptions::PyValueError::new_err("max_queue must be at least 1"));
+        }
+        let on_full = OnFull::from_py(on_full)?;
+
+        let options = Context::build_options(enable_extensions, enable_logging, random_seed, snapshot, code_cache_dir)?
+            .with_heap_limits(initial_heap_mb, max_heap_mb)?
+            .with_shared_memory(shared_memory, shared_buffers)?
+            .with_undefined_as(undefined_as)?
+            .with_permissions(permissions)?;
+        let options = ContextOptions { auto_recreate, ..options };
+        crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+        let scheduler = Arc::new(Scheduler::new(max_queue, on_full));
+
+        let pool = ContextPool {
+            scheduler: scheduler.clone(),
+            workers: Mutex::new(Vec::with_capacity(size)),
+            size,
+            fork_generation: crate::fork::generation(),
+        };
+
+        for i in 0..size {
+            let worker = py.detach(|| {
+                spawn_worker(
+                    format!("never_jscore-pool-{}", i),
+                    options.clone(),
+                    init_code.clone(),
+                    scheduler.clone(),
+                )
+            });
+            match worker {
+                Ok(handle) => pool.workers.lock().unwrap().push(handle),
+                Err(e) => {
+                    py.detach(|| pool.shutdown());
+                    return Err(PyException::new_err(format!("ContextPool error: {}", e)));
+                }
+            }
+        }
+
+        Ok(pool)
+    }
+
+    /// 调用 JavaScript 函数（由空闲的工作线程执行）
+    ///
+    /// Args:
+    ///     name: 函数名称
+    ///     args: 参数列表
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     result_format: 结果的格式："python"（默认）/ "json_str" / "orjson"，参见 Context.call()
+    ///     priority: 优先级（默认 0），排队时优先级高的任务先执行
+    ///     tenant: 租户（默认 None），同一优先级内各租户的任务轮流执行
+    ///
+    /// Returns:
+    ///     函数返回值，自动转换为 Python 对象
+    ///
+    /// Raises:
+    ///     PoolFull: 队列已满且 on_full="raise"
+    #[pyo3(signature = (name, args, auto_await=None, result_format=None, priority=0, tenant=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn call<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        auto_await: Option<bool>,
+        result_format: Option<&str>,
+        priority: i32,
+        tenant: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let format = ResultFormat::from_py(result_format, false, false)?;
+        let args = call_args_to_json(args)?;
+        let auto_await = auto_await.unwrap_or(true);
+        let submitter = self.submitter(priority, tenant, true)?;
+
+        let result_json = py
+            .detach(|| run_task(&submitter, move |ctx| ctx.execute_call(&name, &args, auto_await).map_err(|e| e.to_string())))
+            .and_then(|result| result.map_err(|e| anyhow!("{}", e)))
+            .map_err(|e| task_error("Call error", e))?;
+
+        format.to_python(py, &result_json, false)
+    }
+
+    /// 在某个工作线程中求值（不影响全局作用域）
+    ///
+    /// Args:
+    ///     code: JavaScript 代码
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     result_format: 结果的格式，同 call()
+    ///     priority / tenant: 调度的优先级和租户，同 call()
+    ///
+    /// Returns:
+    ///     表达式的值
+    #[pyo3(signature = (code, auto_await=None, result_format=None, priority=0, tenant=None))]
+    fn evaluate<'py>(
+        &self,
+        py: Python<'py>,
+        code: String,
+        auto_await: Option<bool>,
+        result_format: Option<&str>,
+        priority: i32,
+        tenant: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let format = ResultFormat::from_py(result_format, false, false)?;
+        let auto_await = auto_await.unwrap_or(true);
+        let submitter = self.submitter(priority, tenant, true)?;
+
+        let result_json = py
+            .detach(|| run_task(&submitter, move |ctx| ctx.execute_js(code, auto_await).map_err(|e| e.to_string())))
+            .and_then(|result| result.map_err(|e| anyhow!("{}", e)))
+            .map_err(|e| task_error("Evaluate error", e))?;
+
+        format.to_python(py, &result_json, false)
+    }
+
+    /// 并行批量调用：对 items 中的每一项调用一次 name 函数
+    ///
+    /// 任务分散到多个工作线程并行执行（释放 GIL），结果按 items 的顺序返回。
+    /// 适合批量生成 token / 签名等单个 isolate 吞吐量成为瓶颈的场景。
+    ///
+    /// Args:
+    ///     name: 函数名称
+    ///     items: 参数的可迭代对象；每一项与 call() 的 args 规则相同（list 展开为多个参数）
+    ///     workers: 最多同时使用的工作线程数（默认使用整个池）
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     result_format: 每一项结果的格式，同 call()
+    ///     priority / tenant: 每一项的优先级和租户，同 call()
+    ///
+    /// Returns:
+    ///     list: 每一项的返回值
+    ///
+    /// Raises:
+    ///     Exception: 任意一项调用失败时抛出（包含失败项的索引）
+    ///     PoolFull: 队列已满且 on_full="raise"（等待已提交的项完成后抛出）
+    ///
+    /// Example:
+    ///     ```python
+    ///     pool = never_jscore.ContextPool(js_code, size=8)
+    ///     tokens = pool.map("sign", [[uid, ts] for uid in user_ids])
+    ///     ```
+    #[pyo3(signature = (name, items, workers=None, auto_await=None, result_format=None, priority=0, tenant=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn map<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        items: &Bound<'_, PyAny>,
+        workers: Option<usize>,
+        auto_await: Option<bool>,
+        result_format: Option<&str>,
+        priority: i32,
+        tenant: Option<String>,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let format = ResultFormat::from_py(result_format, false, false)?;
+        let mut call_args = Vec::new();
+        for item in items.try_iter()? {
+            call_args.push(call_args_to_json(&item?)?);
+        }
+
+        let auto_await = auto_await.unwrap_or(true);
+        let in_flight = workers.unwrap_or(self.size).clamp(1, self.size);
+        let submitter = self.submitter(priority, tenant, true)?;
+
+        let results = py.detach(|| map_tasks(&submitter, &name, call_args, in_flight, auto_await))?;
+
+        let list = PyList::empty(py);
+        for (index, result) in results.into_iter().enumerate() {
+            let result_json = result
+                .map_err(|e| PyException::new_err(format!("Call error (item {}): {}", index, e)))?;
+            list.append(format.to_python(py, &result_json, false)?)?;
+        }
+        Ok(list)
+    }
+
+    /// map() 的流式版本：返回 MapStream，每一项完成后就可以取回结果
+    ///
+    /// 可以用 for 或 async for 迭代；Python 处理已完成的结果时，工作线程继续执行后面的项，
+    /// 同时最多 2 × workers 个项在执行、排队或等待取走。
+    ///
+    /// Args:
+    ///     name / items / workers / auto_await / result_format / priority / tenant: 同 map()
+    ///     ordered: True（默认）按 items 的顺序产出结果；False 按完成顺序产出 (索引, 结果)
+    ///
+    /// Returns:
+    ///     MapStream
+    ///
+    /// Raises:
+    ///     Exception: 迭代到失败的项时抛出（包含索引），之后迭代结束
+    ///     PoolFull: 队列已满且 on_full="raise"（产出已提交的项之后抛出）
+    ///
+    /// Example:
+    ///     ```python
+    ///     for token in pool.imap("sign", ([uid, ts] for uid in user_ids)):
+    ///         db.insert(token)
+    ///
+    ///     async for index, token in pool.imap("sign", items, ordered=False):
+    ///         await queue.put((index, token))
+    ///     ```
+    #[pyo3(signature = (name, items, workers=None, ordered=true, auto_await=None, result_format=None, priority=0, tenant=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn imap(
+        &self,
+        name: String,
+        items: &Bound<'_, PyAny>,
+        workers: Option<usize>,
+        ordered: bool,
+        auto_await: Option<bool>,
+        result_format: Option<&str>,
+        priority: i32,
+        tenant: Option<String>,
+    ) -> PyResult<MapStream> {
+        let format = ResultFormat::from_py(result_format, false, false)?;
+        let mut call_args = Vec::new();
+        for item in items.try_iter()? {
+            call_args.push(call_args_to_json(&item?)?);
+        }
+
+        let in_flight = workers.unwrap_or(self.size).clamp(1, self.size) * 2;
+        self.check_open()?;
+        Ok(MapStream::new(
+            self.scheduler.clone(),
+            name,
+            call_args,
+            priority,
+            tenant,
+            auto_await.unwrap_or(true),
+            ordered,
+            in_flight,
+            format,
+        ))
+    }
+
+    /// call() 的 asyncio 版本
+    ///
+    /// 立即返回 asyncio.Future，由空闲的工作线程执行，不阻塞事件循环。
+    /// 必须在运行中的事件循环内调用。
+    ///
+    /// Example:
+    ///     ```python
+    ///     pool = never_jscore.ContextPool(js_code, size=4)
+    ///
+    ///     @app.get("/sign")
+    ///     async def sign(data: str):
+    ///         return await pool.call_async("sign", [data])
+    ///     ```
+    ///
+    /// priority / tenant 同 call()；队列已满时不等待，直接抛出 PoolFull。
+    /// 等待的任务被取消（或 abort 传入的 AbortToken 被中止）时终止工作线程上的 JS，排队中的任务不再执行。
+    #[pyo3(signature = (name, args, auto_await=None, priority=0, tenant=None, abort=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn call_async<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        auto_await: Option<bool>,
+        priority: i32,
+        tenant: Option<String>,
+        abort: Option<PyRef<'_, AbortToken>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let args = call_args_to_json(args)?;
+        let auto_await = auto_await.unwrap_or(true);
+
+        crate::aio::submit(py, &self.submitter(priority, tenant, false)?, abort.as_deref(), move |ctx| {
+            ctx.execute_call(&name, &args, auto_await)
+                .map(Some)
+                .map_err(|e| PyException::new_err(format!("Call error: {}", e)))
+        })
+    }
+
+    /// evaluate() 的 asyncio 版本（priority / tenant / abort 同 call_async()）
+    #[pyo3(signature = (code, auto_await=None, priority=0, tenant=None, abort=None))]
+    fn evaluate_async<'py>(
+        &self,
+        py: Python<'py>,
+        code: String,
+        auto_await: Option<bool>,
+        priority: i32,
+        tenant: Option<String>,
+        abort: Option<PyRef<'_, AbortToken>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let auto_await = auto_await.unwrap_or(true);
+
+        crate::aio::submit(py, &self.submitter(priority, tenant, false)?, abort.as_deref(), move |ctx| {
+            ctx.execute_js(code, auto_await)
+                .map(Some)
+                .map_err(|e| PyException::new_err(format!("Evaluate error: {}", e)))
+        })
+    }
+
+    /// 在每个工作线程上预先执行有代表性的调用（参数与 Context.warmup() 相同）
+    ///
+    /// 预热任务排在所有排队的任务之前，不受 max_queue 限制；每个工作线程各执行一次，
+    /// 正在执行任务的工作线程完成当前任务后才开始预热，warmup() 等待所有工作线程预热完成后返回。
+    ///
+    /// Raises:
+    ///     Exception: 某个工作线程上的预热调用失败（包含函数名）
+    ///
+    /// Example:
+    ///     ```python
+    ///     pool = never_jscore.ContextPool(js_code, size=8)
+    ///     pool.warmup([("sign", ["sample"])], rounds=20)
+    ///     app.run()
+    ///     ```
+    #[pyo3(signature = (calls, rounds=1, auto_await=None))]
+    fn warmup(&self, py: Python<'_>, calls: &Bound<'_, PyAny>, rounds: usize, auto_await: Option<bool>) -> PyResult<()> {
+        let calls = Arc::new(crate::warmup::parse_calls(calls, rounds)?);
+        let auto_await = auto_await.unwrap_or(true);
+        crate::fork::check(self.fork_generation, "ContextPool")?;
+
+        // 每个任务先在屏障处等待，直到所有工作线程都取到了一个预热任务，保证不会有线程取走两个
+        let barrier = Arc::new(Barrier::new(self.size));
+        let (reply_tx, reply_rx) = mpsc::channel::<Result<(), String>>();
+        let tasks = (0..self.size)
+            .map(|_| {
+                let (calls, barrier, reply_tx) = (calls.clone(), barrier.clone(), reply_tx.clone());
+                Box::new(move |ctx: &Context| {
+                    barrier.wait();
+                    let result = crate::warmup::run(ctx, &calls, rounds, auto_await).map_err(|e| e.to_string());
+                    let _ = reply_tx.send(result);
+                }) as Task
+            })
+            .collect();
+        drop(reply_tx);
+        self.scheduler
+            .submit_control(tasks)
+            .map_err(|e| e.into_py("ContextPool is closed"))?;
+
+        let size = self.size;
+        py.detach(move || {
+            let mut first_error = None;
+            for _ in 0..size {
+                match reply_rx.recv() {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        first_error.get_or_insert(e);
+                    }
+                    Err(_) => {
+                        first_error.get_or_insert_with(|| "Worker thread exited".to_string());
+                        break;
+                    }
+                }
+            }
+            first_error.map_or(Ok(()), |e| Err(PyException::new_err(format!("Warmup error: {}", e))))
+        })
+    }
+
+    /// 工作线程数量
+    #[getter]
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 排队中（尚未开始执行）的任务数
+    #[getter]
+    fn queued(&self) -> usize {
+        self.scheduler.queued()
+    }
+
+    /// 关闭池：等待正在执行的任务完成后销毁所有工作线程
+    ///
+    /// 关闭后再调用 call()/evaluate() 会抛出异常。重复调用是安全的。
+    fn close(&self, py: Python<'_>) {
+        py.detach(|| self.shutdown());
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (_exc_type=None, _exc_value=None, _traceback=None))]
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> bool {
+        self.close(py);
+        false
+    }
+}
+
+impl Drop for ContextPool {
+    fn drop(&mut self) {
+        // 对象通常在持有 GIL 时被回收，而工作线程投递 asyncio 结果需要 GIL，
+        // 等待线程退出前必须先释放 GIL
+        Python::attach(|py| py.detach(|| self.shutdown()));
+    }
+}
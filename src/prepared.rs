@@ -0,0 +1,200 @@
+// prepared.rs - prepare("hash(?)")：预编译的调用语句
+//
+// render_call() 把参数序列化为 JSON 字面量拼进代码，参数不同代码就不同，每次调用都要重新解析。
+// prepare() 只编译一次：模板中的 ? 占位符换成参数 __arg0、__arg1…，整个表达式包装为箭头函数，
+// 定义在隐藏的全局对象 __neverjscore_prepared__ 上；stmt(arg) 按 call() 的参数规则（包括 register_converter()
+// 注册的转换器）转换参数后直接调用这个函数（经过 call() 的函数缓存，见 call_cache.rs），
+// 参数不拼接进代码，也就无法破坏代码或注入代码：
+// - 占位符是出现在表达式位置上的 ?：开头，或 `(`、`[`、`{`、`,`、`:`、`=`、运算符、await / typeof 等关键字之后；
+//   `?.`、`??` 和三元运算符的 ? 原样保留，字符串字面量和模板字符串中的 ? 不是占位符
+//   （注释和正则表达式中的 ? 不做区分）
+// - 模板必须是一个表达式，语法错误在 prepare() 时报告；自动等待 Promise
+// - 函数名由模板的哈希决定，同一模板重复 prepare() 得到同一个函数；定义按全局脚本记录，
+//   pickle / recycle_after 重建后仍然可用
+// - 函数在 PreparedCall 销毁后仍然保留，作为句柄列在 handles() 中，release() 删除（见 handles.rs）
+
+use pyo3::exceptions::{PyException, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::context::Context;
+use crate::convert::python_to_json;
+
+/// 存放预编译函数的全局对象（不可枚举）
+pub const HOLDER: &str = "__neverjscore_prepared__";
+
+/// 在此之后出现的 ? 是占位符（期望表达式的位置）
+const EXPRESSION_START: &[u8] = b"([{,:=?;+-*/%<>!&|^~";
+
+/// 之后期望表达式的关键字
+const EXPRESSION_KEYWORDS: &[&str] = &["await", "typeof", "void", "new", "delete", "in", "of", "instanceof", "return", "yield"];
+
+/// end 之前（跳过空白）的标识符
+fn preceding_word(template: &str, end: usize) -> &str {
+    let before = template[..end].trim_end();
+    let start = before
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+        .map_or(0, |i| i + 1);
+    &before[start..]
+}
+
+/// 找出模板中占位符的位置（字节偏移）
+fn placeholders(template: &str) -> Vec<usize> {
+    let bytes = template.as_bytes();
+    let mut positions = Vec::new();
+    let mut quote: Option<u8> = None;
+    let mut prev: Option<u8> = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if let Some(q) = quote {
+            match byte {
+                b'\\' => i += 1,
+                _ if byte == q => quote = None,
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+        match byte {
+            b'\'' | b'"' | b'`' => quote = Some(byte),
+            // ?? / ??= / ?.（?.5 是三元运算符后跟数字）
+            b'?' if bytes.get(i + 1) == Some(&b'?')
+                || (bytes.get(i + 1) == Some(&b'.') && !bytes.get(i + 2).is_some_and(u8::is_ascii_digit)) =>
+            {
+                prev = Some(bytes[i + 1]);
+                i += 2;
+                continue;
+            }
+            b'?' if prev.is_none_or(|prev| EXPRESSION_START.contains(&prev))
+                || EXPRESSION_KEYWORDS.contains(&preceding_word(template, i)) =>
+            {
+                // 占位符本身是一个值：之后的 ? 是三元运算符
+                positions.push(i);
+                prev = Some(b'0');
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        if !byte.is_ascii_whitespace() {
+            prev = Some(byte);
+        }
+        i += 1;
+    }
+    positions
+}
+
+/// 预编译的模板：函数在 HOLDER 上的属性路径、定义函数的脚本和参数个数
+pub struct Compiled {
+    pub path: String,
+    pub script: String,
+    pub params: usize,
+}
+
+/// 把模板转换为定义箭头函数的脚本
+pub fn compile(template: &str) -> Compiled {
+    let positions = placeholders(template);
+    let mut body = String::with_capacity(template.len() + positions.len() * 6);
+    let mut start = 0;
+    for (index, position) in positions.iter().enumerate() {
+        body.push_str(&template[start..*position]);
+        body.push_str(&format!("__arg{}", index));
+        start = position + 1;
+    }
+    body.push_str(&template[start..]);
+
+    let mut hasher = DefaultHasher::new();
+    template.hash(&mut hasher);
+    let path = format!("{}.p{:016x}", HOLDER, hasher.finish());
+    let params = (0..positions.len()).map(|index| format!("__arg{}", index)).collect::<Vec<_>>().join(", ");
+    let script = format!(
+        "if (!Object.prototype.hasOwnProperty.call(globalThis, '{holder}')) {{\n\
+         \x20   Object.defineProperty(globalThis, '{holder}', {{ value: Object.create(null) }});\n\
+         }}\n\
+         {path} = ({params}) => (\n{body}\n);",
+        holder = HOLDER,
+    );
+    Compiled { path, script, params: positions.len() }
+}
+
+/// 预编译的调用语句
+///
+/// 由 `Context.prepare(template)` 返回，以位置参数调用：`stmt(arg1, arg2)`。
+///
+/// Example:
+///     ```python
+///     stmt = ctx.prepare("hash(?, { salt: ? })")
+///     stmt("payload", "s1")    # 执行 hash("payload", { salt: "s1" })，只编译一次
+///     stmt.template            # 'hash(?, { salt: ? })'
+///     stmt.param_count         # 2
+///     ```
+#[pyclass(unsendable)]
+pub struct PreparedCall {
+    context: Py<Context>,
+    template: String,
+    path: String,
+    params: usize,
+    /// 函数已经通过 handles() 释放（与句柄表共享）
+    released: Rc<Cell<bool>>,
+}
+
+impl PreparedCall {
+    pub(crate) fn new(context: Py<Context>, template: String, compiled: &Compiled, released: Rc<Cell<bool>>) -> Self {
+        PreparedCall {
+            context,
+            template,
+            path: compiled.path.clone(),
+            params: compiled.params,
+            released,
+        }
+    }
+}
+
+#[pymethods]
+impl PreparedCall {
+    /// 以位置参数执行预编译的表达式，返回结果（自动等待 Promise）
+    ///
+    /// Raises:
+    ///     TypeError: 参数个数与占位符个数不一致
+    ///     Exception: 函数已经通过 handles() 释放（重新 prepare() 同一模板即可）
+    #[pyo3(signature = (*args))]
+    fn __call__<'py>(&self, py: Python<'py>, args: &Bound<'py, PyTuple>) -> PyResult<Bound<'py, PyAny>> {
+        if self.released.get() {
+            return Err(PyException::new_err(format!(
+                "Call error: prepared call '{}' has been released",
+                self.template
+            )));
+        }
+        if args.len() != self.params {
+            return Err(PyTypeError::new_err(format!(
+                "prepared call '{}' takes {} argument(s) but {} were given",
+                self.template,
+                self.params,
+                args.len()
+            )));
+        }
+        let args = args.iter().map(|arg| python_to_json(&arg)).collect::<PyResult<Vec<_>>>()?;
+        Context::call_prepared(self.context.bind(py), py, &self.path, args)
+    }
+
+    /// prepare() 传入的模板
+    #[getter]
+    fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// 占位符（参数）个数
+    #[getter]
+    fn param_count(&self) -> usize {
+        self.params
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PreparedCall({:?})", self.template)
+    }
+}
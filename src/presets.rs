@@ -0,0 +1,76 @@
+// presets.rs - 内置 JS 库预设（compile(code, preset=[...]) / build_snapshot(code, preset=[...])）
+//
+// 逆向脚本最常依赖的加密库编译进扩展，按名称加载，不需要在每段初始化代码前粘贴上万行的库文件：
+// - "crypto-js": 全局变量 CryptoJS（crypto-js 4.2.0，dddd_js/vendor/crypto-js/）
+// - "jsencrypt": 全局变量 JSEncrypt（jsencrypt 3.3.2，dddd_js/vendor/jsencrypt/）
+//
+// vendor/ 中是上游发布的文件，原样保存（版本和来源见 vendor/VERSIONS，由 scripts/vendor_presets.py 下载）。
+// build.rs 把它们复制到 OUT_DIR 后在这里嵌入；checkout 中缺少文件时嵌入空数据，使用该预设时抛出 RuntimeError。
+// 上游文件是 UMD 包装，而 polyfill 定义了全局 module / exports（require() 使用），直接执行时库会被导出到
+// module.exports 而不是全局变量。因此每个文件在一个函数中执行：module / exports / define 为 undefined，
+// this 为全局对象，window 不存在时（enable_extensions=False）指向全局对象，UMD 包装据此把库赋给全局变量。
+//
+// 预设在用户代码之前作为独立脚本执行（错误堆栈中显示为 <preset:名称>），
+// 与其他 compile() 一样使用代码缓存、记录到 pickle 的重放日志。
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+/// 内置的预设
+struct Preset {
+    name: &'static str,
+    /// vendor/ 中的文件（build.rs 复制到 OUT_DIR/vendor/）
+    file: &'static str,
+    /// 构建时 vendor/ 中有该文件
+    vendored: bool,
+    source: &'static str,
+}
+
+/// 在函数中执行上游 UMD 文件，让它把库赋给全局变量（见文件开头的说明）
+macro_rules! vendored {
+    ($name:literal, $file:literal) => {
+        Preset {
+            name: $name,
+            file: $file,
+            vendored: !include_str!(concat!(env!("OUT_DIR"), "/vendor/", $file)).is_empty(),
+            source: concat!(
+                "(function (module, exports, define, window) {\n",
+                include_str!(concat!(env!("OUT_DIR"), "/vendor/", $file)),
+                "\n}).call(globalThis, undefined, undefined, undefined, typeof window === 'undefined' ? globalThis : window);\n"
+            ),
+        }
+    };
+}
+
+/// 可用的预设
+const PRESETS: &[Preset] = &[
+    vendored!("crypto-js", "crypto-js/crypto-js.js"),
+    vendored!("jsencrypt", "jsencrypt/jsencrypt.js"),
+];
+
+/// 按名称查找预设，返回按传入顺序排列的 (脚本名, 源码)，重复的名称只加载一次
+pub fn resolve(names: Option<Vec<String>>) -> PyResult<Vec<(String, &'static str)>> {
+    let mut scripts: Vec<(String, &'static str)> = Vec::new();
+    for name in names.unwrap_or_default() {
+        let preset = PRESETS.iter().find(|preset| preset.name == name).ok_or_else(|| {
+            let available: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+            PyValueError::new_err(format!(
+                "Unknown preset '{}' (available: {})",
+                name,
+                available.join(", ")
+            ))
+        })?;
+        if !preset.vendored {
+            return Err(PyRuntimeError::new_err(format!(
+                "Preset '{}' is not included in this build: src/dddd_js/vendor/{} is missing \
+                 (run `python scripts/vendor_presets.py` and rebuild)",
+                name, preset.file
+            )));
+        }
+        let script_name = format!("<preset:{}>", name);
+        if !scripts.iter().any(|(existing, _)| *existing == script_name) {
+            scripts.push((script_name, preset.source));
+        }
+    }
+    Ok(scripts)
+}
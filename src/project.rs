@@ -0,0 +1,271 @@
+// project.rs - compile_project()：按依赖顺序把整个目录的脚本加载到一个 Context
+//
+// 加载顺序：
+// - 目录中有 never_jscore.json（{"files": ["env.js", "lib/crypto.js", "main.js"]}）时按其中的顺序
+// - 否则扫描目录中的 .js / .mjs / .cjs 文件（跳过 node_modules 和隐藏目录），从代码中找出依赖：
+//   `require('./x')`、`import ... from './x'`、`import './x'`、`export ... from './x'`
+//   和注释指令 `// @requires ./x.js`，被依赖的文件先加载
+//
+// 只识别相对路径（./ 或 ../）并且指向目录内文件的依赖，其余（npm 包、URL）忽略。
+// 依赖只决定加载顺序：文件仍按 compile_file() 的方式执行（全局脚本 / .mjs 为 ES 模块），
+// 脚本中的 require() 在运行时照常由 polyfill 解析。循环依赖无法确定顺序，抛出 ValueError。
+
+use pyo3::exceptions::{PyException, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::context::{Context, ContextOptions};
+use crate::source_file::SourceMode;
+
+/// 显式指定加载顺序的清单文件
+const MANIFEST: &str = "never_jscore.json";
+
+/// 作为项目文件扫描的扩展名
+const EXTENSIONS: &[&str] = &["js", "mjs", "cjs"];
+
+/// 按顺序加载的文件
+pub struct ProjectFile {
+    pub path: PathBuf,
+    pub code: String,
+    pub mode: SourceMode,
+}
+
+/// 计算项目的加载顺序并读取文件
+///
+/// `entry` 为 None 时加载所有文件，否则只加载 entry 及其（间接）依赖。
+pub fn plan(py: Python<'_>, dir: &Path, entry: Option<&Path>, encoding: Option<&str>) -> PyResult<Vec<ProjectFile>> {
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| PyException::new_err(format!("Failed to open project directory {}: {}", dir.display(), e)))?;
+    let entry = entry
+        .map(|entry| {
+            let path = if entry.is_absolute() { entry.to_path_buf() } else { dir.join(entry) };
+            path.canonicalize()
+                .map_err(|e| PyException::new_err(format!("Failed to open entry {}: {}", entry.display(), e)))
+        })
+        .transpose()?;
+
+    let manifest = dir.join(MANIFEST);
+    let order = if manifest.is_file() {
+        manifest_order(&dir, &manifest, entry.as_deref())?
+    } else {
+        dependency_order(py, &dir, entry.as_deref(), encoding)?
+    };
+
+    order
+        .into_iter()
+        .map(|path| {
+            let (code, mode) = crate::source_file::load(py, &path, encoding, None)?;
+            Ok(ProjectFile { path, code, mode })
+        })
+        .collect()
+}
+
+/// 清单中的文件顺序（有 entry 时截止到 entry）
+fn manifest_order(dir: &Path, manifest: &Path, entry: Option<&Path>) -> PyResult<Vec<PathBuf>> {
+    let invalid = |reason: String| PyValueError::new_err(format!("Invalid {}: {}", manifest.display(), reason));
+    let text = std::fs::read_to_string(manifest)
+        .map_err(|e| PyException::new_err(format!("Failed to read {}: {}", manifest.display(), e)))?;
+    let value: serde_json::Value = serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+    let files = value
+        .get("files")
+        .and_then(|files| files.as_array())
+        .ok_or_else(|| invalid("expected {\"files\": [...]}".to_string()))?;
+
+    let mut order = Vec::new();
+    for file in files {
+        let name = file.as_str().ok_or_else(|| invalid(format!("file names must be strings, got {}", file)))?;
+        let path = dir
+            .join(name)
+            .canonicalize()
+            .map_err(|e| invalid(format!("'{}': {}", name, e)))?;
+        let is_entry = entry == Some(path.as_path());
+        order.push(path);
+        if is_entry {
+            return Ok(order);
+        }
+    }
+    match entry {
+        Some(entry) => Err(PyValueError::new_err(format!("Entry {} is not listed in {}", entry.display(), manifest.display()))),
+        None => Ok(order),
+    }
+}
+
+/// 按代码中的依赖计算加载顺序
+fn dependency_order(py: Python<'_>, dir: &Path, entry: Option<&Path>, encoding: Option<&str>) -> PyResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_files(dir, &mut files)?;
+    files.sort();
+    if let Some(entry) = entry {
+        if !files.iter().any(|file| file == entry) {
+            files.push(entry.to_path_buf());
+        }
+    }
+
+    let mut deps = HashMap::new();
+    for file in &files {
+        let (code, _) = crate::source_file::load(py, file, encoding, Some("script"))?;
+        let base = file.parent().unwrap_or(dir);
+        let mut resolved = Vec::new();
+        for specifier in specifiers(&code) {
+            if let Some(path) = resolve(base, &specifier.path).filter(|path| files.contains(path)) {
+                if !resolved.contains(&path) && path != *file {
+                    resolved.push(path);
+                }
+            } else if specifier.directive {
+                return Err(PyValueError::new_err(format!(
+                    "{}: @requires {} does not match a file in {}",
+                    file.display(),
+                    specifier.path,
+                    dir.display()
+                )));
+            }
+        }
+        deps.insert(file.clone(), resolved);
+    }
+
+    let roots = match entry {
+        Some(entry) => vec![entry.to_path_buf()],
+        None => files,
+    };
+    let mut order = Vec::new();
+    let mut stack = Vec::new();
+    for root in &roots {
+        visit(root, &deps, &mut stack, &mut order, dir)?;
+    }
+    Ok(order)
+}
+
+/// 深度优先遍历：依赖先于依赖它的文件加入 order
+fn visit(file: &Path, deps: &HashMap<PathBuf, Vec<PathBuf>>, stack: &mut Vec<PathBuf>, order: &mut Vec<PathBuf>, dir: &Path) -> PyResult<()> {
+    if order.iter().any(|loaded| loaded == file) {
+        return Ok(());
+    }
+    if let Some(start) = stack.iter().position(|visiting| visiting == file) {
+        let cycle: Vec<String> = stack[start..]
+            .iter()
+            .chain(std::iter::once(&file.to_path_buf()))
+            .map(|path| path.strip_prefix(dir).unwrap_or(path).display().to_string())
+            .collect();
+        return Err(PyValueError::new_err(format!("Circular dependency: {}", cycle.join(" -> "))));
+    }
+    stack.push(file.to_path_buf());
+    for dep in deps.get(file).into_iter().flatten() {
+        visit(dep, deps, stack, order, dir)?;
+    }
+    stack.pop();
+    order.push(file.to_path_buf());
+    Ok(())
+}
+
+/// 递归收集目录中的脚本文件（跳过 node_modules 和隐藏目录）
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> PyResult<()> {
+    let entries = std::fs::read_dir(dir).map_err(|e| PyException::new_err(format!("Failed to read {}: {}", dir.display(), e)))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "node_modules" {
+                collect_files(&path, files)?;
+            }
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        {
+            files.push(path.canonicalize().unwrap_or(path));
+        }
+    }
+    Ok(())
+}
+
+/// 相对路径依赖解析为文件：原路径、补全扩展名、目录下的 index.js
+fn resolve(base: &Path, specifier: &str) -> Option<PathBuf> {
+    if !specifier.starts_with("./") && !specifier.starts_with("../") {
+        return None;
+    }
+    let path = base.join(specifier);
+    let mut candidates = vec![path.clone()];
+    candidates.extend(EXTENSIONS.iter().map(|ext| PathBuf::from(format!("{}.{}", path.display(), ext))));
+    candidates.push(path.join("index.js"));
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .and_then(|candidate| candidate.canonicalize().ok())
+}
+
+/// 代码中的一个依赖
+struct Specifier {
+    path: String,
+    /// 来自 @requires 指令（必须能解析到项目文件）
+    directive: bool,
+}
+
+/// 找出代码中的依赖路径（简单的文本扫描，不解析语法）
+fn specifiers(code: &str) -> Vec<Specifier> {
+    let mut found = Vec::new();
+    for (keyword, directive) in [("require", false), ("import", false), ("from", false), ("@requires", true)] {
+        for (index, _) in code.match_indices(keyword) {
+            let before = code[..index].chars().next_back();
+            if !keyword.starts_with('@') && before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '.') {
+                continue;
+            }
+            let rest = &code[index + keyword.len()..];
+            let path = if directive {
+                rest.strip_prefix([' ', '\t'])
+                    .and_then(|rest| rest.split_whitespace().next())
+                    .map(|path| path.trim_matches(|c| c == '\'' || c == '"'))
+            } else {
+                // require('x') / import('x') / import 'x' / from 'x'
+                let rest = rest.trim_start();
+                let rest = if keyword == "from" { rest } else { rest.strip_prefix('(').map(str::trim_start).unwrap_or(rest) };
+                string_literal(rest)
+            };
+            if let Some(path) = path.filter(|path| !path.is_empty()) {
+                found.push(Specifier { path: path.to_string(), directive });
+            }
+        }
+    }
+    found
+}
+
+/// 取出开头的字符串字面量（'x' 或 "x"）
+fn string_literal(text: &str) -> Option<&str> {
+    let quote = text.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let rest = &text[1..];
+    let end = rest.find([quote, '\n'])?;
+    (rest[end..].starts_with(quote)).then(|| &rest[..end])
+}
+
+/// 按依赖顺序加载目录中的脚本，返回新的 Context
+///
+/// 等价于 `ctx = Context(); ctx.compile_project(dir, entry)`；需要自定义 Context 参数时
+/// 先创建 Context 再调用 Context.compile_project()。
+///
+/// Args:
+///     dir: 项目目录
+///     entry: 入口文件（相对 dir 或绝对路径）。指定时只加载入口及其依赖，默认加载全部文件
+///     encoding: 文件编码（默认 UTF-8），同 compile_file()
+///
+/// Returns:
+///     加载好项目的 Context
+///
+/// Raises:
+///     ValueError: 循环依赖、清单格式错误或 @requires 指向不存在的文件
+///
+/// Example:
+///     ```python
+///     # sign/env.js, sign/lib/crypto.js（// @requires ../env.js）, sign/main.js（// @requires ./lib/crypto.js）
+///     ctx = never_jscore.compile_project("sign", entry="main.js")
+///     ctx.call("sign", ["data"])
+///     ```
+#[pyfunction]
+#[pyo3(signature = (dir, entry=None, encoding=None))]
+pub fn compile_project(py: Python<'_>, dir: PathBuf, entry: Option<PathBuf>, encoding: Option<&str>) -> PyResult<Py<Context>> {
+    crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    let context = Context::new(ContextOptions::default())?;
+    context.compile_project(py, dir, entry, encoding)?;
+    Py::new(py, context)
+}
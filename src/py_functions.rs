@@ -0,0 +1,180 @@
+// py_functions.rs - 把 Python 函数注册为 JS 全局函数（ctx.register(name, func)）
+//
+// 注册的函数是原生 V8 函数：JS 参数转换为 JSON 后作为位置参数传给 Python，
+// 返回值转换回 JS 值，Python 抛出的异常在 JS 中抛出为 Error。
+// 不依赖扩展 ops，enable_extensions=False / no_ops=True 时同样可用。
+//
+// 调用 Python 之前记录当时的 JS 调用栈，回调中通过 ctx.capture_stack() 取得，
+// 用于确定是哪个（混淆过的）函数调用了 Python 钩子。
+
+use anyhow::{anyhow, Result};
+use deno_core::{v8, JsRuntime};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::convert::{json_str_to_python, python_to_json};
+
+/// capture_stack() 最多返回的栈帧数
+const MAX_STACK_FRAMES: usize = 64;
+
+/// isolate 中注册的 Python 函数（保存在 isolate 的 slot 中）
+#[derive(Default)]
+struct PyFunctions(HashMap<String, Py<PyAny>>);
+
+/// 一个 JS 栈帧
+#[derive(Clone, Debug)]
+pub struct StackFrame {
+    function: String,
+    script: String,
+    line: usize,
+    column: usize,
+    is_eval: bool,
+    is_constructor: bool,
+}
+
+thread_local! {
+    /// 正在执行的 Python 回调被调用时的 JS 调用栈（回调中可能再次执行 JS，因此是栈）
+    static CALL_STACKS: RefCell<Vec<Vec<StackFrame>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 注册 Python 函数为 JS 全局函数（同名时替换）
+pub fn register(runtime: &mut JsRuntime, name: &str, func: Py<PyAny>) -> Result<()> {
+    deno_core::scope!(scope, runtime);
+    let function = new_function(scope, name, func)?;
+    let key = v8::String::new(scope, name).ok_or_else(|| anyhow!("Invalid function name"))?;
+    let global = scope.get_current_context().global(scope);
+    global
+        .set(scope, key.into(), function.into())
+        .ok_or_else(|| anyhow!("Failed to define global '{}'", name))?;
+    Ok(())
+}
+
+/// 创建调用 Python 函数的 JS 函数（wasm.rs 的导入同样使用），`key` 在 isolate 中唯一，同名时替换
+pub fn new_function<'s>(scope: &mut v8::PinScope<'s, '_>, key: &str, func: Py<PyAny>) -> Result<v8::Local<'s, v8::Function>> {
+    if scope.get_slot::<PyFunctions>().is_none() {
+        scope.set_slot(PyFunctions::default());
+    }
+    scope.get_slot_mut::<PyFunctions>().unwrap().0.insert(key.to_string(), func);
+
+    let name = v8::String::new(scope, key).ok_or_else(|| anyhow!("Invalid function name"))?;
+    let function = v8::Function::builder(call_python)
+        .data(name.into())
+        .build(scope)
+        .ok_or_else(|| anyhow!("Failed to create function '{}'", key))?;
+    function.set_name(name);
+    Ok(function)
+}
+
+/// 抛出 JS Error
+fn throw_error(scope: &mut v8::PinScope, message: &str) {
+    if let Some(message) = v8::String::new(scope, message) {
+        let exception = v8::Exception::error(scope, message);
+        scope.throw_exception(exception);
+    }
+}
+
+/// 注册的 JS 函数的实现：调用对应的 Python 函数
+fn call_python(scope: &mut v8::PinScope, args: v8::FunctionCallbackArguments, mut rv: v8::ReturnValue) {
+    let name = args.data().to_rust_string_lossy(scope);
+    let func = Python::attach(|py| {
+        scope
+            .get_slot::<PyFunctions>()
+            .and_then(|functions| functions.0.get(&name))
+            .map(|func| func.clone_ref(py))
+    });
+    let Some(func) = func else {
+        return throw_error(scope, &format!("Python function '{}' is not registered", name));
+    };
+
+    // start_replay()：不调用 Python 函数，使用 trace 中记录的结果
+    if let Some(replayed) = crate::replay::replay_call(scope, &name) {
+        return return_json(scope, replayed, &mut rv);
+    }
+
+    let array = v8::Array::new(scope, args.length());
+    for i in 0..args.length() {
+        array.set_index(scope, i as u32, args.get(i));
+    }
+    // 参数无法转换为 JSON 时（如 BigInt）异常已经抛出
+    let Some(json) = v8::json::stringify(scope, array.into()) else {
+        return;
+    };
+    let json = json.to_rust_string_lossy(scope);
+
+    CALL_STACKS.with(|stacks| stacks.borrow_mut().push(current_stack(scope)));
+    let result = Python::attach(|py| -> PyResult<String> {
+        let args = json_str_to_python(py, &json)?;
+        let args = args.cast::<PyList>()?.to_tuple();
+        let value = func.call1(py, args)?;
+        Ok(python_to_json(value.bind(py))?.to_string())
+    });
+    CALL_STACKS.with(|stacks| stacks.borrow_mut().pop());
+
+    let result = result.map_err(|e| format!("Python function '{}' raised {}", name, e));
+    crate::replay::record_call(scope, &name, result.as_deref().map_err(String::as_str));
+    return_json(scope, result, &mut rv);
+}
+
+/// 把 Python 函数的结果（JSON）作为 JS 返回值，或抛出错误信息
+fn return_json(scope: &mut v8::PinScope, result: Result<String, String>, rv: &mut v8::ReturnValue) {
+    match result {
+        Ok(json) => {
+            if let Some(value) = v8::String::new(scope, &json).and_then(|json| v8::json::parse(scope, json)) {
+                rv.set(value);
+            }
+        }
+        Err(message) => throw_error(scope, &message),
+    }
+}
+
+/// 当前的 JS 调用栈（栈顶在前）
+pub fn current_stack(scope: &mut v8::PinScope) -> Vec<StackFrame> {
+    let Some(trace) = v8::StackTrace::current_stack_trace(scope, MAX_STACK_FRAMES) else {
+        return Vec::new();
+    };
+    (0..trace.get_frame_count())
+        .filter_map(|i| trace.get_frame(scope, i))
+        .map(|frame| StackFrame {
+            function: frame
+                .get_function_name(scope)
+                .map(|name| name.to_rust_string_lossy(scope))
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "<anonymous>".to_string()),
+            script: frame
+                .get_script_name_or_source_url(scope)
+                .map(|name| name.to_rust_string_lossy(scope))
+                .unwrap_or_default(),
+            line: frame.get_line_number(),
+            column: frame.get_column(),
+            is_eval: frame.is_eval(),
+            is_constructor: frame.is_constructor(),
+        })
+        .collect()
+}
+
+/// 当前 Python 回调被调用时的 JS 调用栈（ctx.capture_stack()），栈顶在前
+pub fn capture_stack(py: Python<'_>) -> PyResult<Bound<'_, PyList>> {
+    let frames = CALL_STACKS.with(|stacks| stacks.borrow().last().cloned()).ok_or_else(|| {
+        PyRuntimeError::new_err("capture_stack() can only be called inside a Python function invoked from JS (see register())")
+    })?;
+    stack_to_python(py, frames)
+}
+
+/// 把调用栈转换为字典列表
+pub fn stack_to_python(py: Python<'_>, frames: Vec<StackFrame>) -> PyResult<Bound<'_, PyList>> {
+    let list = PyList::empty(py);
+    for frame in frames {
+        let item = PyDict::new(py);
+        item.set_item("function", frame.function)?;
+        item.set_item("script", frame.script)?;
+        item.set_item("line", frame.line)?;
+        item.set_item("column", frame.column)?;
+        item.set_item("is_eval", frame.is_eval)?;
+        item.set_item("is_constructor", frame.is_constructor)?;
+        list.append(item)?;
+    }
+    Ok(list)
+}
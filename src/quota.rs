@@ -0,0 +1,324 @@
+// quota.rs - Context 级资源配额（Context(quotas={...})）
+//
+// 与 timeout_ms / cpu_limit_ms 这类单次调用的限制不同，配额按 Context 的整个生命周期累计，
+// 用于多租户服务中防止某个租户的 Context 独占资源：
+// - max_executions: 最多执行次数（evaluate / call / compile 等各计一次）
+// - max_cpu_ms: 累计 CPU 时间（线程 CPU 时钟，目前仅支持 Linux），
+//   剩余预算交给看门狗，超出时终止当前调用
+// - max_console_bytes: 累计 console 输出字节数，超出时丢弃输出并终止当前调用
+// - max_result_bytes: 单次调用结果（JSON）的最大字节数，超出时本次调用失败，不影响之后的调用
+//
+// 前三项耗尽后 Context 拒绝之后的所有调用，统一抛出 QuotaExceeded 异常。
+// new Worker() 创建的 Context 与创建它的 Context 累计同一份用量（Worker 中的执行、CPU 时间和 console 输出计入父 Context）。
+// console 输出统计通过替换 deno_core 内置的 op_print 实现（eval(capture_output=True) 的捕获也在这里）。
+
+use deno_core::{extension, op2, v8, OpState};
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::cell::Cell;
+use std::io::Write;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pyo3::create_exception!(
+    never_jscore,
+    QuotaExceeded,
+    PyException,
+    "Context 的资源配额（quotas）已耗尽"
+);
+
+/// 超出配额的错误（转换为 Python 的 QuotaExceeded）
+#[derive(Debug)]
+pub struct QuotaError(String);
+
+impl std::fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for QuotaError {}
+
+/// 创建超出配额的错误
+pub fn quota_error(message: impl Into<String>) -> anyhow::Error {
+    anyhow::Error::new(QuotaError(message.into()))
+}
+
+/// 把执行错误转换为 Python 异常：超出配额时为 QuotaExceeded，Context 已 poisoned 时为 ContextPoisoned，
+/// 结果不符合 expect_schema 时为 SchemaMismatch，其他错误加上前缀
+pub fn py_error(prefix: &str, e: anyhow::Error) -> PyErr {
+    // 让出点上 Python 信号处理函数抛出的异常原样抛出（见 yield_points.rs）
+    let e = match e.downcast::<PyErr>() {
+        Ok(err) => return err,
+        Err(e) => e,
+    };
+    if let Some(quota) = e.downcast_ref::<QuotaError>() {
+        return QuotaExceeded::new_err(quota.to_string());
+    }
+    if let Some(schema) = e.downcast_ref::<crate::schema::SchemaError>() {
+        return crate::schema::py_error(prefix, schema);
+    }
+    match e.downcast_ref::<crate::poison::PoisonedError>() {
+        Some(poisoned) => crate::poison::ContextPoisoned::new_err(poisoned.to_string()),
+        None => PyException::new_err(format!("{}: {}", prefix, e)),
+    }
+}
+
+/// 配额设置
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QuotaLimits {
+    pub max_executions: Option<u64>,
+    pub max_cpu_ms: Option<u64>,
+    pub max_result_bytes: Option<usize>,
+    pub max_console_bytes: Option<usize>,
+}
+
+impl QuotaLimits {
+    /// 从 Python 字典解析（未知的键和 0 均报错）
+    pub fn from_py(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let mut limits = QuotaLimits::default();
+        for (key, value) in dict.iter() {
+            let key: String = key.extract()?;
+            let value: u64 = value
+                .extract()
+                .map_err(|_| PyValueError::new_err(format!("quotas['{}'] must be a positive integer", key)))?;
+            if value == 0 {
+                return Err(PyValueError::new_err(format!("quotas['{}'] must be at least 1", key)));
+            }
+            match key.as_str() {
+                "max_executions" => limits.max_executions = Some(value),
+                "max_cpu_ms" => limits.max_cpu_ms = Some(value),
+                "max_result_bytes" => limits.max_result_bytes = Some(value as usize),
+                "max_console_bytes" => limits.max_console_bytes = Some(value as usize),
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown quota '{}', expected max_executions / max_cpu_ms / max_result_bytes / max_console_bytes",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(limits)
+    }
+}
+
+/// 配额使用情况（可以在工作线程中获取后传回 Python 线程）
+#[derive(Clone, Debug)]
+pub struct QuotaUsage {
+    limits: QuotaLimits,
+    executions: u64,
+    cpu: Duration,
+    console_bytes: usize,
+    exhausted: Option<String>,
+}
+
+impl QuotaUsage {
+    /// 转换为 get_quota_usage() 的返回值
+    pub fn to_python<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let limits = PyDict::new(py);
+        limits.set_item("max_executions", self.limits.max_executions)?;
+        limits.set_item("max_cpu_ms", self.limits.max_cpu_ms)?;
+        limits.set_item("max_result_bytes", self.limits.max_result_bytes)?;
+        limits.set_item("max_console_bytes", self.limits.max_console_bytes)?;
+
+        let result = PyDict::new(py);
+        result.set_item("executions", self.executions)?;
+        result.set_item("cpu_ms", self.cpu.as_secs_f64() * 1000.0)?;
+        result.set_item("console_bytes", self.console_bytes)?;
+        result.set_item("exhausted", self.exhausted.as_deref())?;
+        result.set_item("limits", limits)?;
+        Ok(result)
+    }
+}
+
+/// 配额的累计用量
+#[derive(Clone, Default)]
+struct Usage {
+    executions: u64,
+    cpu: Duration,
+    console_bytes: usize,
+    /// 耗尽原因，设置后拒绝之后的所有调用
+    exhausted: Option<String>,
+}
+
+/// 可以在线程之间共享的配额用量（new Worker() 创建的 Context 计入创建它的 Context，见 worker_ops.rs）
+#[derive(Clone, Default)]
+pub struct SharedUsage(Arc<Mutex<Usage>>);
+
+/// Context 的配额状态（与 OpState 共享）
+pub struct Quota {
+    limits: QuotaLimits,
+    usage: SharedUsage,
+    /// 当前调用因 console 输出超出配额被终止
+    terminated: Cell<bool>,
+}
+
+impl Quota {
+    pub fn new(limits: QuotaLimits) -> Self {
+        Self::sharing(limits, SharedUsage::default())
+    }
+
+    /// 与其他 Context 累计同一份用量
+    pub fn sharing(limits: QuotaLimits, usage: SharedUsage) -> Self {
+        Quota {
+            limits,
+            usage,
+            terminated: Cell::new(false),
+        }
+    }
+
+    /// 用量（交给 Worker 的 Context）
+    pub fn shared_usage(&self) -> SharedUsage {
+        self.usage.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Usage> {
+        self.usage.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn exhaust(usage: &mut Usage, reason: String) {
+        usage.exhausted.get_or_insert(reason);
+    }
+
+    fn exhausted_error(&self) -> Option<anyhow::Error> {
+        self.lock().exhausted.as_ref().map(|reason| quota_error(reason.clone()))
+    }
+
+    /// 开始一次调用：配额已耗尽时拒绝，否则计入执行次数
+    pub fn begin(&self) -> anyhow::Result<()> {
+        let mut usage = self.lock();
+        if let Some(reason) = &usage.exhausted {
+            return Err(quota_error(reason.clone()));
+        }
+        if let Some(max) = self.limits.max_executions {
+            if usage.executions >= max {
+                Self::exhaust(&mut usage, format!("Execution quota exhausted (max_executions={})", max));
+                return Err(quota_error(usage.exhausted.clone().unwrap_or_default()));
+            }
+        }
+        usage.executions += 1;
+        self.terminated.set(false);
+        Ok(())
+    }
+
+    /// 剩余的 CPU 时间预算（交给看门狗）
+    pub fn remaining_cpu(&self) -> Option<Duration> {
+        let max = Duration::from_millis(self.limits.max_cpu_ms?);
+        Some(max.saturating_sub(self.lock().cpu))
+    }
+
+    /// 调用结束：累计本次调用消耗的 CPU 时间
+    pub fn charge_cpu(&self, used: Duration) {
+        let mut usage = self.lock();
+        usage.cpu += used;
+        if let Some(max) = self.limits.max_cpu_ms {
+            if usage.cpu >= Duration::from_millis(max) {
+                Self::exhaust(&mut usage, format!("CPU time quota exhausted (max_cpu_ms={})", max));
+            }
+        }
+    }
+
+    /// 检查结果大小
+    pub fn check_result(&self, len: usize) -> anyhow::Result<()> {
+        match self.limits.max_result_bytes {
+            Some(max) if len > max => Err(quota_error(format!(
+                "Result size quota exceeded: {} bytes (max_result_bytes={})",
+                len, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// 当前调用是否因 console 输出超出配额被终止
+    pub fn terminated(&self) -> bool {
+        self.terminated.get()
+    }
+
+    /// 当前调用因 console 输出超出配额被终止时，返回对应的错误（并清除标志）
+    pub fn take_terminated(&self) -> Option<anyhow::Error> {
+        if self.terminated.replace(false) {
+            return self.exhausted_error();
+        }
+        None
+    }
+
+    /// 记录 console 输出，返回是否允许输出
+    fn record_console(&self, len: usize) -> bool {
+        let mut usage = self.lock();
+        if usage.exhausted.is_some() {
+            return false;
+        }
+        let total = usage.console_bytes + len;
+        match self.limits.max_console_bytes {
+            Some(max) if total > max => {
+                Self::exhaust(&mut usage, format!("Console output quota exhausted (max_console_bytes={})", max));
+                self.terminated.set(true);
+                false
+            }
+            _ => {
+                usage.console_bytes = total;
+                true
+            }
+        }
+    }
+
+    /// 接管另一个 Quota 的用量（recycle_after 重建 isolate 时，配额按 Context 的整个生命周期累计）
+    pub fn carry_over(&self, from: &Quota) {
+        let usage = from.lock().clone();
+        *self.lock() = usage;
+    }
+
+    pub fn usage(&self) -> QuotaUsage {
+        let usage = self.lock();
+        QuotaUsage {
+            limits: self.limits,
+            executions: usage.executions,
+            cpu: usage.cpu,
+            console_bytes: usage.console_bytes,
+            exhausted: usage.exhausted.clone(),
+        }
+    }
+}
+
+#[op2(fast)]
+/// 替换 deno_core 的 op_print（console 输出）：统计输出字节数，超出配额时丢弃输出并终止执行
+pub fn op_print_quota(state: &mut OpState, #[string] msg: &str, is_err: bool) -> Result<(), std::io::Error> {
+    if let Some(quota) = state.try_borrow::<Rc<Quota>>() {
+        if !quota.record_console(msg.len()) {
+            if quota.terminated() {
+                state.borrow::<v8::IsolateHandle>().terminate_execution();
+            }
+            return Ok(());
+        }
+    }
+    // eval(capture_output=True)：输出写入当前调用的缓冲区
+    let mut traced = None;
+    if let Some(capture) = state.try_borrow::<Rc<crate::console_capture::ConsoleCapture>>() {
+        if capture.record(msg, is_err) {
+            return Ok(());
+        }
+        // call(trace_id=...)：加上 [trace_id=...] 前缀
+        traced = capture.traced(msg);
+    }
+    let msg = traced.as_deref().unwrap_or(msg);
+    if is_err {
+        let mut stderr = std::io::stderr();
+        stderr.write_all(msg.as_bytes())?;
+        stderr.flush()
+    } else {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(msg.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+// 只替换 op_print 的实现，不注册新的 op（始终加载，快照与运行时的 op 列表保持一致）
+extension!(
+    quota_ops,
+    middleware = |op| match op.name {
+        "op_print" => op.with_implementation_from(&op_print_quota()),
+        _ => op,
+    },
+);
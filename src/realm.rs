@@ -0,0 +1,351 @@
+// realm.rs - 共享 isolate 的轻量级执行环境
+//
+// 每个 Context 独占一个 isolate（几 MB 内存、几毫秒创建时间）。
+// Realm 是同一 isolate 中的另一个 V8 context：拥有独立的全局对象和内置对象，
+// 创建和销毁只需几十微秒，适合服务端按请求 / 按租户隔离。
+//
+// Realm 只包含 ECMAScript 内置对象（Object、JSON、Promise 等），
+// 不包含 Context 的扩展 API（btoa、crypto、setTimeout 等），也没有事件循环：
+// Promise 会在求值结束后通过微任务检查点推进，未能完成的 Promise 会报错。
+//
+// 同一 Context 的所有 Realm 共享堆和线程，一个 Realm 中的死循环会阻塞其他 Realm。
+
+use anyhow::{Result, anyhow};
+use deno_core::{JsRuntime, error::JsError, v8};
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::code_cache::exception_to_error;
+use crate::context::{Context, IsolateBound, IsolateLocal, format_call, format_error};
+use crate::convert::{call_args_to_json, json_str_to_python};
+use crate::quota::QuotaError;
+
+// 求值包装：在函数内使用直接 eval，let/const 不泄漏到 Realm 的全局作用域
+const REALM_EVAL_WRAPPER: &str = "(function(code) { return eval(code); })";
+
+/// Realm 在 isolate 中的句柄
+struct RealmHandles {
+    context: v8::Global<v8::Context>,
+    eval_wrapper: v8::Global<v8::Function>,
+    /// false：Context(allow_dynamic_code=False)，Evaluate 不经过 eval，把代码包在块语句中作为脚本执行
+    dynamic_code: bool,
+}
+
+// 只包含 V8 句柄，在 Context::without_gil 中随 Context 一起使用
+unsafe impl IsolateBound for &RealmHandles {}
+
+/// 在 runtime 的 isolate 中创建新的 V8 context
+fn create_handles(runtime: &mut JsRuntime) -> Result<RealmHandles> {
+    let isolate = &mut *runtime.v8_isolate();
+    v8::scope!(scope, isolate);
+    let context = v8::Context::new(scope, Default::default());
+    let dynamic_code = crate::shadow_realm::dynamic_code_allowed(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let source = v8::String::new(scope, REALM_EVAL_WRAPPER).ok_or_else(|| anyhow!("Failed to create eval wrapper"))?;
+    let wrapper = v8::Script::compile(scope, source, None)
+        .and_then(|script| script.run(scope))
+        .and_then(|value| v8::Local::<v8::Function>::try_from(value).ok())
+        .ok_or_else(|| anyhow!("Failed to compile realm eval wrapper"))?;
+
+    // init(icu=False)：与 Context 一样删除 Intl
+    if !crate::runtime::runtime_config().icu {
+        let source = v8::String::new(scope, crate::runtime::HIDE_INTL).ok_or_else(|| anyhow!("Failed to create string"))?;
+        v8::Script::compile(scope, source, None)
+            .and_then(|script| script.run(scope))
+            .ok_or_else(|| anyhow!("Failed to hide Intl"))?;
+    }
+
+    // 在包装函数和 Intl 处理之后禁止，与 Context 加载 polyfill 之后才禁止一致
+    crate::shadow_realm::inherit_code_generation(scope, context);
+
+    Ok(RealmHandles {
+        context: v8::Global::new(scope, context),
+        eval_wrapper: v8::Global::new(scope, wrapper),
+        dynamic_code,
+    })
+}
+
+/// Realm 中的执行方式
+#[derive(Clone, Copy)]
+enum Mode {
+    /// 作为全局脚本执行（定义的函数 / 变量保留在 Realm 中）
+    Script,
+    /// 求值并返回 JSON 结果，auto_await 为 true 时等待 Promise
+    Evaluate { auto_await: bool },
+}
+
+/// 在 Realm 中执行代码，Evaluate 模式返回结果的 JSON 字符串
+fn run(runtime: &mut JsRuntime, handles: &RealmHandles, code: &str, mode: Mode) -> Result<Option<String>> {
+    let isolate = &mut *runtime.v8_isolate();
+    v8::scope!(scope, isolate);
+    let context = v8::Local::new(scope, &handles.context);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    v8::tc_scope!(let tc_scope, scope);
+
+    let source = v8::String::new(tc_scope, code).ok_or_else(|| anyhow!("Code is too large"))?;
+    let name = v8::String::new(tc_scope, "<realm>").ok_or_else(|| anyhow!("Failed to create script name"))?;
+    let origin = v8::ScriptOrigin::new(tc_scope, name.into(), 0, 0, false, 0, None, false, false, false, None);
+
+    let auto_await = match mode {
+        Mode::Script => {
+            let script = v8::Script::compile(tc_scope, source, Some(&origin)).ok_or_else(|| exception_to_error(tc_scope))?;
+            script.run(tc_scope).ok_or_else(|| exception_to_error(tc_scope))?;
+            tc_scope.perform_microtask_checkpoint();
+            return Ok(None);
+        }
+        Mode::Evaluate { auto_await } => auto_await,
+    };
+
+    let mut value = if handles.dynamic_code {
+        let wrapper = v8::Local::new(tc_scope, &handles.eval_wrapper);
+        let recv = v8::undefined(tc_scope).into();
+        wrapper.call(tc_scope, recv, &[source.into()]).ok_or_else(|| exception_to_error(tc_scope))?
+    } else {
+        // 与 Context 的 evaluate_as_script 一致："{" 与代码放在同一行，错误行号保持不变
+        let block = v8::String::new(tc_scope, &format!("{{{}\n}}", code)).ok_or_else(|| anyhow!("Code is too large"))?;
+        let script = v8::Script::compile(tc_scope, block, Some(&origin)).ok_or_else(|| exception_to_error(tc_scope))?;
+        script.run(tc_scope).ok_or_else(|| exception_to_error(tc_scope))?
+    };
+
+    // Realm 没有事件循环，Promise 只能依靠微任务推进
+    tc_scope.perform_microtask_checkpoint();
+    if auto_await && value.is_promise() {
+        let promise = v8::Local::<v8::Promise>::try_from(value).map_err(|_| anyhow!("Invalid promise"))?;
+        match promise.state() {
+            v8::PromiseState::Fulfilled => value = promise.result(tc_scope),
+            v8::PromiseState::Rejected => {
+                let exception = promise.result(tc_scope);
+                return Err((*JsError::from_v8_exception(tc_scope, exception)).into());
+            }
+            v8::PromiseState::Pending => {
+                return Err(anyhow!("Promise is still pending: realms have no event loop (timers and async ops are unavailable)"));
+            }
+        }
+    }
+
+    if value.is_undefined() {
+        return Ok(Some("null".to_string()));
+    }
+
+    // 与 Context 的求值包装一致：无法 JSON 序列化的值转换为字符串
+    let json = v8::json::stringify(tc_scope, value)
+        .map(|json| json.to_rust_string_lossy(tc_scope))
+        .filter(|json| json != "undefined");
+    let json = match json {
+        Some(json) => json,
+        None => {
+            tc_scope.reset();
+            let text = value.to_rust_string_lossy(tc_scope);
+            serde_json::to_string(&text)?
+        }
+    };
+    Ok(Some(json))
+}
+
+/// 与其他 Realm 共享同一个 isolate 的轻量级执行环境
+///
+/// 由 `Context.create_realm()` 创建。每个 Realm 有独立的全局作用域和内置对象，
+/// 创建 / 销毁比 Context 快几个数量级，内存开销也小得多，适合按请求隔离。
+///
+/// 注意：
+/// - Realm 只有 ECMAScript 内置对象，没有 btoa / crypto / setTimeout 等扩展 API
+/// - 没有事件循环，只支持立即完成（仅依赖微任务）的 Promise
+/// - 只能在创建它的线程上使用，执行期间会占用所属 Context 的 isolate
+/// - 与所属 Context 的 allow_dynamic_code 一致：为 False 时 Realm 中同样禁止 eval / new Function
+///
+/// Example:
+///     ```python
+///     ctx = never_jscore.Context()
+///
+///     def handle(request):
+///         with ctx.create_realm() as realm:
+///             realm.compile(tenant_code)
+///             return realm.call("handle", [request])
+///     ```
+#[pyclass(unsendable)]
+pub struct Realm {
+    // 必须先于 context 释放：Global 句柄依赖 isolate 存活；句柄表（handles()）持有弱引用
+    handles: Rc<RefCell<Option<RealmHandles>>>,
+    context: Py<Context>,
+    fork_generation: usize,
+}
+
+impl Realm {
+    /// 在 Context 的 isolate 中创建新的 Realm
+    pub(crate) fn new(py: Python<'_>, context: Py<Context>) -> PyResult<Self> {
+        let handles = {
+            let ctx = context.borrow(py);
+            ctx.check_fork()?;
+            ctx.reserve_handle()
+                .map_err(|e| crate::quota::py_error("Failed to create realm", e))?;
+            let handles = ctx
+                .with_runtime(create_handles)
+                .map_err(|e| PyException::new_err(format!("Failed to create realm: {}", format_error(e))))?;
+            let handles = Rc::new(RefCell::new(Some(handles)));
+            ctx.handle_table().register_slot("realm", "<realm>", &handles);
+            handles
+        };
+        Ok(Realm {
+            handles,
+            context,
+            fork_generation: crate::fork::generation(),
+        })
+    }
+
+    /// 在 Realm 中执行代码（释放 GIL）
+    fn execute(&self, py: Python<'_>, code: &str, mode: Mode) -> Result<Option<String>> {
+        let handles = self.handles.borrow();
+        let handles = handles.as_ref().ok_or_else(|| anyhow!("Realm is closed"))?;
+
+        let context = self.context.borrow(py);
+        context.check_fork().map_err(|e| anyhow!("{}", e))?;
+        let kind = match mode {
+            Mode::Script => "script",
+            Mode::Evaluate { .. } => "evaluate",
+        };
+        let execution = context.begin_execution(kind, "<realm>", code);
+        let handles = IsolateLocal::new(handles);
+        let result = context.without_gil(py, |ctx| {
+            let handles = handles.into_inner();
+            // Realm 的调用同样计入 Context 的配额
+            ctx.with_quota(|| {
+                let result = ctx
+                    .with_runtime(|runtime| run(runtime, handles, code, mode))
+                    .map_err(|e| if e.is::<QuotaError>() { e } else { anyhow!("{}", format_error(e)) })?;
+                if let Some(json) = &result {
+                    ctx.check_result_quota(json)?;
+                }
+                Ok(result)
+            })
+        });
+        context.end_execution(execution, result)
+    }
+
+    fn evaluate_json(&self, py: Python<'_>, code: &str, auto_await: Option<bool>) -> Result<String> {
+        let mode = Mode::Evaluate {
+            auto_await: auto_await.unwrap_or(true),
+        };
+        Ok(self.execute(py, code, mode)?.unwrap_or_else(|| "null".to_string()))
+    }
+}
+
+#[pymethods]
+impl Realm {
+    /// 执行代码并将其加入 Realm 的全局作用域
+    ///
+    /// Args:
+    ///     code: JavaScript 代码字符串
+    #[pyo3(signature = (code))]
+    pub fn compile(&self, py: Python<'_>, code: String) -> PyResult<()> {
+        self.execute(py, &code, Mode::Script)
+            .map_err(|e| crate::quota::py_error("Compile error", e))?;
+        Ok(())
+    }
+
+    /// 执行代码并将其加入全局作用域（与 Context.eval() 一致）
+    ///
+    /// Args:
+    ///     code: JavaScript 代码
+    ///     return_value: 是否返回最后一个表达式的值（默认 False）
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    #[pyo3(signature = (code, return_value=false, auto_await=None))]
+    pub fn eval<'py>(
+        &self,
+        py: Python<'py>,
+        code: String,
+        return_value: bool,
+        auto_await: Option<bool>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if return_value {
+            let result_json = self
+                .evaluate_json(py, &code, auto_await)
+                .map_err(|e| crate::quota::py_error("Eval error", e))?;
+            json_str_to_python(py, &result_json)
+        } else {
+            self.execute(py, &code, Mode::Script)
+                .map_err(|e| crate::quota::py_error("Eval error", e))?;
+            Ok(py.None().into_bound(py))
+        }
+    }
+
+    /// 执行代码并返回结果（不影响全局作用域）
+    ///
+    /// Args:
+    ///     code: JavaScript 代码
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    #[pyo3(signature = (code, auto_await=None))]
+    pub fn evaluate<'py>(&self, py: Python<'py>, code: String, auto_await: Option<bool>) -> PyResult<Bound<'py, PyAny>> {
+        let result_json = self
+            .evaluate_json(py, &code, auto_await)
+            .map_err(|e| crate::quota::py_error("Evaluate error", e))?;
+        json_str_to_python(py, &result_json)
+    }
+
+    /// 调用 Realm 中的 JavaScript 函数
+    ///
+    /// Args:
+    ///     name: 函数名称
+    ///     args: 参数列表
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    #[pyo3(signature = (name, args, auto_await=None))]
+    pub fn call<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        auto_await: Option<bool>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let call_code = format_call(&name, &call_args_to_json(args)?);
+        let result_json = self
+            .evaluate_json(py, &call_code, auto_await)
+            .map_err(|e| crate::quota::py_error("Call error", e))?;
+        json_str_to_python(py, &result_json)
+    }
+
+    /// 销毁 Realm，释放其全局对象（重复调用是安全的）
+    ///
+    /// 之后的调用会抛出异常。V8 会在下一次 GC 时回收 Realm 占用的内存。
+    fn close(&self) {
+        let handles = self.handles.borrow_mut().take();
+        self.release(handles);
+    }
+
+    /// Realm 是否已关闭
+    #[getter]
+    fn closed(&self) -> bool {
+        self.handles.borrow().is_none()
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __exit__(
+        &self,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> bool {
+        self.close();
+        false
+    }
+}
+
+impl Realm {
+    /// 释放句柄；fork 前创建的 Realm 在子进程中不能访问 isolate，直接泄漏
+    fn release(&self, handles: Option<RealmHandles>) {
+        if crate::fork::is_inherited(self.fork_generation) {
+            std::mem::forget(handles);
+        }
+    }
+}
+
+impl Drop for Realm {
+    fn drop(&mut self) {
+        let handles = self.handles.borrow_mut().take();
+        self.release(handles);
+    }
+}
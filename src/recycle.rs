@@ -0,0 +1,78 @@
+// recycle.rs - compile(code, recycle_after={...})：达到阈值时自动重建 isolate
+//
+// 长时间运行的服务中，脚本留下的缓存、闭包和 V8 内部结构会让堆缓慢增长，GC 无法回收。
+// 设置 recycle_after 后，调用返回前检查阈值，达到时按原顺序重建一个新的 isolate 替换当前的：
+// - 构造参数、compile() / compile_file() / eval() 等执行过的全局脚本（初始化代码）和 register() 的 Python 函数
+//   原样重建（与 pickle 使用同一份记录，见 pickle.rs）
+// - evaluate() / call() 中的副作用不保留，这正是回收的目的
+// - 调用统计、配额用量、执行历史、on_gc / on_slow_script 回调转移到新的 isolate
+//
+// 阈值：
+// - calls: 当前 isolate 执行 JS 的次数（evaluate / call / eval / compile 等每次执行计一次）
+// - heap_mb: 调用结束后 V8 已用堆大小（MB）
+//
+// 重建失败（例如初始化代码抛出异常）时继续使用当前 isolate，错误通过 sys.unraisablehook 报告，
+// 下一次调用结束时重试。register() 的 Python 函数中再次调用同一个 Context 会抛出 RuntimeError，
+// 此时外层调用仍在执行，不检查，留到外层调用结束。
+// 无法重放的状态（load_wasm()、ES 模块、share_buffer()、import_state()、
+// create_realm() / create_shadow_realm()）与 recycle_after 不能同时使用。
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// 自动回收的阈值
+#[derive(Clone, Copy, Debug)]
+pub struct RecyclePolicy {
+    pub calls: Option<u64>,
+    pub heap_mb: Option<usize>,
+}
+
+impl RecyclePolicy {
+    /// 从 Python 字典解析（未知的键和空字典报错）
+    pub fn from_py(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let mut policy = RecyclePolicy { calls: None, heap_mb: None };
+        for (key, value) in dict.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "calls" => {
+                    let calls: u64 = positive(&value, "calls")?;
+                    policy.calls = Some(calls);
+                }
+                "heap_mb" => {
+                    let heap_mb: usize = positive(&value, "heap_mb")?;
+                    policy.heap_mb = Some(heap_mb);
+                }
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown recycle_after option '{}', expected calls / heap_mb",
+                        key
+                    )))
+                }
+            }
+        }
+        if policy.calls.is_none() && policy.heap_mb.is_none() {
+            return Err(PyValueError::new_err("recycle_after needs at least one of calls / heap_mb"));
+        }
+        Ok(policy)
+    }
+
+    /// 是否达到阈值
+    pub fn reached(&self, calls: u64, used_heap_bytes: impl FnOnce() -> usize) -> bool {
+        self.calls.is_some_and(|max| calls >= max)
+            || self.heap_mb.is_some_and(|max| used_heap_bytes() >= max * 1024 * 1024)
+    }
+}
+
+fn positive<'py, T: FromPyObjectOwned<'py> + PartialOrd + Default>(value: &Bound<'py, PyAny>, key: &str) -> PyResult<T> {
+    value
+        .extract()
+        .ok()
+        .filter(|value| *value > T::default())
+        .ok_or_else(|| PyValueError::new_err(format!("recycle_after['{}'] must be a positive integer", key)))
+}
+
+/// 无法重建时 recycle_after 的错误信息
+pub fn blocked_message(reason: &str) -> String {
+    format!("recycle_after cannot be used when {}", reason)
+}
@@ -0,0 +1,100 @@
+// regexp_guard.rs - 终止卡在正则表达式中的执行，并区分出错误原因
+//
+// 灾难性回溯（如 /(a+)+$/ 匹配 'aaaa...b'）发生在 V8 的正则引擎内部，不经过普通 JS 代码。
+// V8 的正则代码在回溯过程中会检查中断请求，因此看门狗不直接调用 terminate_execution()，
+// 而是通过 request_interrupt() 请求中断：回调在执行线程上运行，先检查当前位置是否在正则方法调用中，
+// 再终止执行。超时错误据此附加 "while executing a regular expression" 说明。
+//
+// 判断方式：内置函数（RegExp.prototype.test 等）不出现在可调试的调用栈中，
+// 栈顶的用户代码位置即为调用内置函数的位置，检查该位置是否为 `.test(` / `.replace(` 等正则方法调用。
+// 回调中不能重新进入 isolate 执行 JS，这里只用 Exception::create_message 读取位置和源码行。
+
+use deno_core::{v8, JsRuntime};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::watchdog::ExceededSlot;
+
+/// 会执行正则匹配的方法（RegExp.prototype 和 String.prototype 上的同名方法）
+const REGEXP_METHODS: &[&str] = &["exec", "test", "match", "matchAll", "replace", "replaceAll", "search", "split"];
+
+/// 中断回调中用于读取源码行的 context（保存在 isolate 的 slot 中）
+#[derive(Clone)]
+struct ProbeContext(v8::Global<v8::Context>);
+
+/// 为 runtime 的 isolate 设置中断回调使用的 context
+pub fn install(runtime: &mut JsRuntime) {
+    let context = runtime.main_context();
+    runtime.v8_isolate().set_slot(ProbeContext(context));
+}
+
+/// 中断回调中创建 ContextScope 使用的 context（slow_script.rs 同样使用）
+pub fn probe_context(isolate: &v8::Isolate) -> Option<v8::Global<v8::Context>> {
+    isolate.get_slot::<ProbeContext>().map(|probe| probe.0.clone())
+}
+
+/// 中断回调的参数
+struct Probe {
+    exceeded: ExceededSlot,
+    /// 监视仍在进行（调用已结束时中断可能在之后的调用中才运行，此时忽略）
+    active: Arc<AtomicBool>,
+}
+
+/// 请求中断正在执行的 isolate：检查是否在执行正则表达式，然后终止执行
+///
+/// isolate 已销毁时返回；isolate 在中断运行前被销毁时回调参数会泄漏（很小）。
+pub fn terminate(handle: &v8::IsolateHandle, exceeded: &ExceededSlot, active: &Arc<AtomicBool>) {
+    let probe = Box::into_raw(Box::new(Probe {
+        exceeded: exceeded.clone(),
+        active: active.clone(),
+    }));
+    if !handle.request_interrupt(probe_interrupt, probe as *mut c_void) {
+        drop(unsafe { Box::from_raw(probe) });
+    }
+}
+
+unsafe extern "C" fn probe_interrupt(isolate: &mut v8::Isolate, data: *mut c_void) {
+    let probe = unsafe { Box::from_raw(data as *mut Probe) };
+    if !probe.active.load(Ordering::Acquire) {
+        return;
+    }
+    if in_regexp(isolate) {
+        if let Some(exceeded) = probe.exceeded.lock().unwrap().as_mut() {
+            exceeded.in_regexp = true;
+        }
+    }
+    isolate.terminate_execution();
+}
+
+/// 当前是否正在执行正则方法
+fn in_regexp(isolate: &mut v8::Isolate) -> bool {
+    let Some(context) = probe_context(isolate) else {
+        return false;
+    };
+    v8::scope!(scope, isolate);
+    let context = v8::Local::new(scope, context);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let undefined = v8::undefined(scope).into();
+    let message = v8::Exception::create_message(scope, undefined);
+    match message.get_source_line(scope) {
+        Some(line) => is_regexp_call(&line.to_rust_string_lossy(scope), message.get_start_column()),
+        None => false,
+    }
+}
+
+/// 源码行中 `column`（UTF-16 下标）处是否为正则方法调用的括号，如 `re.test(`
+fn is_regexp_call(line: &str, column: usize) -> bool {
+    let units: Vec<u16> = line.encode_utf16().collect();
+    if units.get(column) != Some(&(b'(' as u16)) {
+        return false;
+    }
+    let before = String::from_utf16_lossy(&units[..column]);
+    let before = before.trim_end();
+    let name_start = before
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !(c.is_alphanumeric() || *c == '_' || *c == '$'))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    REGEXP_METHODS.contains(&&before[name_start..]) && before[..name_start].trim_end().ends_with('.')
+}
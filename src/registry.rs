@@ -0,0 +1,353 @@
+// registry.rs - ContextRegistry：按键（租户 / 目标站点）延迟创建并缓存 Context
+//
+// 处理很多不同站点脚本的服务通常为每个站点维护一个 Context：第一次请求时加载该站点的 JS，之后复用，
+// 长时间不用的释放内存。ContextRegistry 封装这一模式：
+// - define(key, code, **options)：登记该键的初始化代码和 Context 构造参数（max_heap_mb、timeout_ms、quotas 等），
+//   realm=True 时改为在共享的宿主 Context 中创建 Realm（更轻量，但没有扩展 API 和单独的限制）
+// - loader(key)：未 define() 的键第一次使用时调用，返回初始化代码，或 (代码, 参数 dict)
+// - get(key) / call(key, name, args)：第一次使用时创建并执行初始化代码，之后返回缓存的实例
+// - max_size：缓存的实例数上限，超出时释放最久未使用的（LRU）；ttl：空闲超过 ttl 秒的实例在下一次访问注册表时释放
+//
+// 实例通过 Python 层的构造函数创建，构造参数的校验与 Context(...) 完全相同。
+// 与 Context 一样不能跨线程使用；每个线程使用自己的注册表。
+
+use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyTuple};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::context::Context;
+
+/// 键的定义：初始化代码和创建方式
+struct Definition {
+    code: String,
+    preset: Option<Py<PyAny>>,
+    realm: bool,
+    options: Option<Py<PyDict>>,
+}
+
+impl Definition {
+    fn clone_ref(&self, py: Python<'_>) -> Self {
+        Definition {
+            code: self.code.clone(),
+            preset: self.preset.as_ref().map(|preset| preset.clone_ref(py)),
+            realm: self.realm,
+            options: self.options.as_ref().map(|options| options.clone_ref(py)),
+        }
+    }
+}
+
+/// 缓存的实例（Context 或 Realm）
+struct Cached {
+    instance: Py<PyAny>,
+    realm: bool,
+    last_used: Instant,
+}
+
+/// 按键延迟创建并缓存 Context（或 Realm），按 LRU / 空闲时间释放
+///
+/// Example:
+///     ```python
+///     registry = never_jscore.ContextRegistry(max_size=32, ttl=600)
+///     registry.define("site_a", open("site_a.js").read(), max_heap_mb=64, timeout_ms=500)
+///     registry.define("site_b", open("site_b.js").read(), realm=True)
+///     registry.call("site_a", "sign", ["data"])
+///     ```
+#[pyclass(unsendable)]
+pub struct ContextRegistry {
+    definitions: RefCell<HashMap<String, Definition>>,
+    cached: RefCell<HashMap<String, Cached>>,
+    max_size: Option<usize>,
+    ttl: Option<Duration>,
+    loader: Option<Py<PyAny>>,
+    // Realm 的宿主 Context（第一次创建 Realm 时创建）
+    host: RefCell<Option<Py<Context>>>,
+    evictions: Cell<usize>,
+}
+
+impl ContextRegistry {
+    /// 释放空闲超过 ttl 的实例
+    fn expire(&self, py: Python<'_>) {
+        let Some(ttl) = self.ttl else {
+            return;
+        };
+        let expired: Vec<String> = self
+            .cached
+            .borrow()
+            .iter()
+            .filter(|(_, cached)| cached.last_used.elapsed() >= ttl)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            self.remove(py, &key);
+        }
+    }
+
+    /// 从缓存中移除并释放实例（Realm 立即关闭）
+    fn remove(&self, py: Python<'_>, key: &str) -> bool {
+        // 先取出再释放：释放 Context 时不持有借用
+        let Some(cached) = self.cached.borrow_mut().remove(key) else {
+            return false;
+        };
+        if cached.realm {
+            let _ = cached.instance.bind(py).call_method0("close");
+        }
+        self.evictions.set(self.evictions.get() + 1);
+        drop(cached);
+        true
+    }
+
+    /// 缓存已满时释放最久未使用的实例，为新实例腾出位置
+    fn make_room(&self, py: Python<'_>) {
+        let Some(max_size) = self.max_size else {
+            return;
+        };
+        while self.cached.borrow().len() >= max_size {
+            let oldest = self
+                .cached
+                .borrow()
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.remove(py, &key),
+                None => break,
+            };
+        }
+    }
+
+    /// 键的定义：define() 登记的，或由 loader 加载（加载结果同样登记）
+    fn definition(&self, py: Python<'_>, key: &str) -> PyResult<Definition> {
+        if let Some(definition) = self.definitions.borrow().get(key) {
+            return Ok(definition.clone_ref(py));
+        }
+        let Some(loader) = &self.loader else {
+            return Err(PyKeyError::new_err(format!("no Context defined for key '{}'", key)));
+        };
+        let loaded = loader.bind(py).call1((key,))?;
+        let definition = if let Ok(code) = loaded.extract::<String>() {
+            parse_definition(code, None)?
+        } else if let Ok(tuple) = loaded.cast::<PyTuple>() {
+            let (code, options): (String, Bound<'_, PyDict>) = tuple.extract()?;
+            parse_definition(code, Some(&options))?
+        } else {
+            return Err(PyTypeError::new_err(format!(
+                "loader must return the init code (str) or (code, options), got {}",
+                loaded.get_type().name()?
+            )));
+        };
+        self.definitions.borrow_mut().insert(key.to_string(), definition.clone_ref(py));
+        Ok(definition)
+    }
+
+    /// 按定义创建实例并执行初始化代码
+    fn create<'py>(&self, py: Python<'py>, definition: &Definition) -> PyResult<Bound<'py, PyAny>> {
+        if definition.realm {
+            let host = self.host(py)?;
+            let realm = host.bind(py).call_method0("create_realm")?;
+            realm.call_method1("compile", (definition.code.as_str(),))?;
+            return Ok(realm);
+        }
+        let options = definition.options.as_ref().map(|options| options.bind(py));
+        let context = py.get_type::<Context>().call((), options)?;
+        let kwargs = PyDict::new(py);
+        if let Some(preset) = &definition.preset {
+            kwargs.set_item("preset", preset)?;
+        }
+        context.call_method("compile", (definition.code.as_str(),), Some(&kwargs))?;
+        Ok(context)
+    }
+
+    fn host(&self, py: Python<'_>) -> PyResult<Py<Context>> {
+        if let Some(host) = self.host.borrow().as_ref() {
+            return Ok(host.clone_ref(py));
+        }
+        let host: Py<Context> = py.get_type::<Context>().call0()?.cast_into::<Context>()?.unbind();
+        *self.host.borrow_mut() = Some(host.clone_ref(py));
+        Ok(host)
+    }
+}
+
+/// 由 define() / loader 的参数创建定义（preset、realm 从构造参数中分离出来）
+fn parse_definition(code: String, options: Option<&Bound<'_, PyDict>>) -> PyResult<Definition> {
+    let options = options.map(|options| options.copy()).transpose()?;
+    let take = |name: &str| -> PyResult<Option<Bound<'_, PyAny>>> {
+        match &options {
+            Some(options) => {
+                let value = options.get_item(name)?;
+                options.del_item(name).ok();
+                Ok(value)
+            }
+            None => Ok(None),
+        }
+    };
+    let preset = take("preset")?.filter(|preset| !preset.is_none()).map(Bound::unbind);
+    let realm = take("realm")?.map(|realm| realm.is_truthy()).transpose()?.unwrap_or(false);
+    let options = options.filter(|options| !options.is_empty());
+    if realm && (options.is_some() || preset.is_some()) {
+        return Err(PyValueError::new_err(
+            "realm=True cannot be combined with Context options or preset (realms share the host Context)",
+        ));
+    }
+    Ok(Definition {
+        code,
+        preset,
+        realm,
+        options: options.map(Bound::unbind),
+    })
+}
+
+#[pymethods]
+impl ContextRegistry {
+    /// 创建注册表
+    ///
+    /// Args:
+    ///     max_size: 缓存的实例数上限，超出时释放最久未使用的，默认 None 不限制
+    ///     ttl: 空闲时间（秒），超过后在下一次访问注册表时释放，默认 None 不过期
+    ///     loader: 未 define() 的键第一次使用时调用 loader(key)，返回初始化代码（str）
+    ///         或 (代码, 参数 dict)，参数与 define() 相同；默认 None（未定义的键抛出 KeyError）
+    ///
+    /// Raises:
+    ///     ValueError: max_size 为 0 或 ttl 不是正数
+    #[new]
+    #[pyo3(signature = (max_size=None, ttl=None, loader=None))]
+    fn py_new(max_size: Option<usize>, ttl: Option<f64>, loader: Option<Py<PyAny>>) -> PyResult<Self> {
+        if max_size == Some(0) {
+            return Err(PyValueError::new_err("max_size must be at least 1"));
+        }
+        let ttl = match ttl {
+            Some(ttl) if !(ttl > 0.0 && ttl.is_finite()) => {
+                return Err(PyValueError::new_err("ttl must be a positive number of seconds"));
+            }
+            ttl => ttl.map(Duration::from_secs_f64),
+        };
+        Ok(ContextRegistry {
+            definitions: RefCell::new(HashMap::new()),
+            cached: RefCell::new(HashMap::new()),
+            max_size,
+            ttl,
+            loader,
+            host: RefCell::new(None),
+            evictions: Cell::new(0),
+        })
+    }
+
+    /// 登记键的初始化代码和构造参数（实例在第一次使用时创建）
+    ///
+    /// 重新定义已缓存的键会释放旧实例，下一次使用时按新定义创建。
+    ///
+    /// Args:
+    ///     key: 键（租户 / 站点名）
+    ///     code: 初始化代码，创建实例后执行（与 compile() 相同）
+    ///     preset: 与 compile(preset=...) 相同
+    ///     realm: 为 True 时在注册表共享的宿主 Context 中创建 Realm，不能与其他参数同时使用
+    ///     **options: Context 的构造参数（max_heap_mb、timeout_ms、quotas 等）
+    ///
+    /// Example:
+    ///     ```python
+    ///     registry.define("site_a", js_a, preset=["crypto-js"], max_heap_mb=64, cpu_limit_ms=200)
+    ///     ```
+    #[pyo3(signature = (key, code, **options))]
+    fn define(&self, py: Python<'_>, key: String, code: String, options: Option<&Bound<'_, PyDict>>) -> PyResult<()> {
+        let definition = parse_definition(code, options)?;
+        self.definitions.borrow_mut().insert(key.clone(), definition);
+        self.remove(py, &key);
+        Ok(())
+    }
+
+    /// 获取键对应的实例，第一次使用（或已被释放）时创建并执行初始化代码
+    ///
+    /// Returns:
+    ///     Context（realm=True 时为 Realm）
+    ///
+    /// Raises:
+    ///     KeyError: 键没有定义，也没有 loader
+    ///     Exception: 创建实例或执行初始化代码失败（不缓存，下一次使用时重试）
+    #[pyo3(signature = (key))]
+    fn get<'py>(&self, py: Python<'py>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.expire(py);
+        if let Some(cached) = self.cached.borrow_mut().get_mut(key) {
+            cached.last_used = Instant::now();
+            return Ok(cached.instance.bind(py).clone());
+        }
+        let definition = self.definition(py, key)?;
+        let instance = self.create(py, &definition)?;
+        self.make_room(py);
+        self.cached.borrow_mut().insert(
+            key.to_string(),
+            Cached {
+                instance: instance.clone().unbind(),
+                realm: definition.realm,
+                last_used: Instant::now(),
+            },
+        );
+        Ok(instance)
+    }
+
+    /// 调用键对应实例中的 JavaScript 函数（等同于 get(key).call(name, args, **kwargs)）
+    ///
+    /// Args:
+    ///     key: 键
+    ///     name: 函数名称
+    ///     args: 参数列表
+    ///     **kwargs: 传给 call() 的其他参数（auto_await、result_format 等）
+    #[pyo3(signature = (key, name, args, **kwargs))]
+    fn call<'py>(
+        &self,
+        py: Python<'py>,
+        key: &str,
+        name: &str,
+        args: &Bound<'py, PyAny>,
+        kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let instance = self.get(py, key)?;
+        instance.call_method("call", (name, args), kwargs)
+    }
+
+    /// 释放键对应的实例（定义保留，下一次使用时重新创建）
+    ///
+    /// Returns:
+    ///     实例是否存在
+    #[pyo3(signature = (key))]
+    fn evict(&self, py: Python<'_>, key: &str) -> bool {
+        self.remove(py, key)
+    }
+
+    /// 释放所有实例（定义保留）
+    fn clear(&self, py: Python<'_>) {
+        let keys: Vec<String> = self.cached.borrow().keys().cloned().collect();
+        for key in keys {
+            self.remove(py, &key);
+        }
+    }
+
+    /// 当前缓存了实例的键（按最近使用排序，最近的在前）
+    fn keys<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        self.expire(py);
+        let cached = self.cached.borrow();
+        let mut keys: Vec<(&String, Instant)> = cached.iter().map(|(key, cached)| (key, cached.last_used)).collect();
+        keys.sort_by_key(|(_, last_used)| std::cmp::Reverse(*last_used));
+        PyList::new(py, keys.into_iter().map(|(key, _)| key))
+    }
+
+    /// 统计信息：{"cached": 缓存的实例数, "defined": 定义的键数, "evictions": 已释放的实例数}
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = PyDict::new(py);
+        stats.set_item("cached", self.cached.borrow().len())?;
+        stats.set_item("defined", self.definitions.borrow().len())?;
+        stats.set_item("evictions", self.evictions.get())?;
+        Ok(stats)
+    }
+
+    fn __len__(&self) -> usize {
+        self.cached.borrow().len()
+    }
+
+    /// 键是否缓存了实例
+    fn __contains__(&self, py: Python<'_>, key: &str) -> bool {
+        self.expire(py);
+        self.cached.borrow().contains_key(key)
+    }
+}
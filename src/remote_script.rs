@@ -0,0 +1,175 @@
+// remote_script.rs - compile_url(url, sha256=...)：下载、校验并编译远程脚本
+//
+// 逆向的目标脚本经常在线上悄悄更新，团队需要固定分析的那个版本。compile_url() 用 reqwest 下载脚本
+// （与 fetch 相同的 HTTP 栈，遵循 HTTP_PROXY / HTTPS_PROXY / NO_PROXY 环境变量，也可以传入 proxy），
+// 计算 SHA-256 并与 sha256 比较，一致时缓存到磁盘（文件名为哈希值）再编译：
+// - 传入 sha256 时优先使用缓存，缓存中的文件同样重新校验，离线环境也能加载已固定的版本
+// - 哈希不一致时抛出 ValueError（包含实际的哈希值），不缓存也不执行
+// - 不传 sha256 时每次都下载、不缓存；compile_url() 返回实际的哈希值，可以用来固定版本
+//
+// 缓存目录默认为 $XDG_CACHE_HOME/never_jscore/scripts（未设置时为 ~/.cache/never_jscore/scripts，
+// Windows 为 %LOCALAPPDATA%\never_jscore\scripts）。
+// 按 URL 路径的扩展名选择执行方式（.mjs 为 ES 模块，import.meta.url 为该 URL），可以用 mode 覆盖；
+// 错误堆栈中的文件名为 URL。
+
+use pyo3::exceptions::{PyException, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::context::{Context, ContextOptions};
+use crate::source_file::SourceMode;
+
+/// 下载选项
+pub struct RemoteOptions {
+    pub sha256: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+    pub proxy: Option<String>,
+    pub timeout: Duration,
+}
+
+/// 下载并校验后的脚本
+pub struct RemoteScript {
+    pub code: String,
+    pub mode: SourceMode,
+    pub sha256: String,
+}
+
+impl RemoteOptions {
+    pub fn new(sha256: Option<&str>, cache_dir: Option<PathBuf>, proxy: Option<String>, timeout_ms: u64) -> PyResult<Self> {
+        let sha256 = sha256.map(|hash| hash.trim().to_ascii_lowercase());
+        if sha256.as_ref().is_some_and(|hash| hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit())) {
+            return Err(PyValueError::new_err("sha256 must be 64 hexadecimal characters"));
+        }
+        Ok(RemoteOptions {
+            sha256,
+            cache_dir,
+            proxy,
+            timeout: Duration::from_millis(timeout_ms),
+        })
+    }
+
+    fn cache_dir(&self) -> Option<PathBuf> {
+        if let Some(dir) = &self.cache_dir {
+            return Some(dir.clone());
+        }
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+        Some(base.join("never_jscore").join("scripts"))
+    }
+}
+
+fn digest(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// 从缓存读取（文件不存在或内容与哈希不一致时返回 None）
+fn read_cached(path: &Path, sha256: &str) -> Option<Vec<u8>> {
+    let data = std::fs::read(path).ok()?;
+    if digest(&data) == sha256 {
+        return Some(data);
+    }
+    // 缓存文件损坏：删除后重新下载
+    let _ = std::fs::remove_file(path);
+    None
+}
+
+/// 写入缓存（先写临时文件再重命名，并发加载同一脚本时不会读到不完整的文件）；失败时忽略
+fn write_cached(dir: &Path, sha256: &str, data: &[u8]) {
+    let temp = dir.join(format!(".{}.{}.tmp", sha256, std::process::id()));
+    let written = std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(&temp, data))
+        .and_then(|()| std::fs::rename(&temp, dir.join(sha256)));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+}
+
+fn download(url: &str, options: &RemoteOptions) -> Result<Vec<u8>, String> {
+    let mut builder = reqwest::blocking::Client::builder().timeout(options.timeout);
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy).map_err(|e| format!("invalid proxy {}: {}", proxy, e))?);
+    }
+    let client = builder.build().map_err(|e| e.to_string())?;
+    let response = client.get(url).send().map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("HTTP {}", status));
+    }
+    response.bytes().map(|body| body.to_vec()).map_err(|e| e.to_string())
+}
+
+/// 获取脚本：sha256 对应的缓存存在时直接使用，否则下载并校验
+pub fn fetch(py: Python<'_>, url: &str, options: &RemoteOptions, encoding: Option<&str>, mode: Option<&str>) -> PyResult<RemoteScript> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| PyValueError::new_err(format!("Invalid URL {}: {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(PyValueError::new_err(format!("compile_url() only supports http(s) URLs, got {}", url)));
+    }
+    let mode = crate::source_file::resolve_mode(url, Path::new(parsed.path()), mode)?;
+
+    let cache_dir = options.sha256.as_ref().and_then(|_| options.cache_dir());
+    let cached = match (&options.sha256, &cache_dir) {
+        (Some(sha256), Some(dir)) => py.detach(|| read_cached(&dir.join(sha256), sha256)),
+        _ => None,
+    };
+    let data = match cached {
+        Some(data) => data,
+        None => {
+            let data = py
+                .detach(|| download(url, options))
+                .map_err(|e| PyException::new_err(format!("Failed to download {}: {}", url, e)))?;
+            let actual = digest(&data);
+            if let Some(expected) = &options.sha256 {
+                if *expected != actual {
+                    return Err(PyValueError::new_err(format!(
+                        "sha256 mismatch for {}: expected {}, got {}",
+                        url, expected, actual
+                    )));
+                }
+            }
+            if let (Some(sha256), Some(dir)) = (&options.sha256, &cache_dir) {
+                py.detach(|| write_cached(dir, sha256, &data));
+            }
+            data
+        }
+    };
+
+    let sha256 = digest(&data);
+    let code = crate::source_file::decode(py, url, data, encoding)?;
+    Ok(RemoteScript { code, mode, sha256 })
+}
+
+/// 下载远程脚本，校验 SHA-256 后在新的 Context 中编译
+///
+/// 等价于 `ctx = Context(); ctx.compile_url(url, ...)`；需要自定义 Context 参数时
+/// 先创建 Context 再调用 Context.compile_url()。参数与 Context.compile_url() 相同。
+///
+/// Returns:
+///     加载好脚本的 Context
+///
+/// Example:
+///     ```python
+///     ctx = never_jscore.compile_url("https://www.example.com/static/sign.js", sha256="9f86d0...")
+///     ctx.call("sign", ["data"])
+///     ```
+#[pyfunction]
+#[pyo3(signature = (url, sha256=None, cache_dir=None, proxy=None, timeout_ms=30000, encoding=None, mode=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn compile_url(
+    py: Python<'_>,
+    url: &str,
+    sha256: Option<&str>,
+    cache_dir: Option<PathBuf>,
+    proxy: Option<String>,
+    timeout_ms: u64,
+    encoding: Option<&str>,
+    mode: Option<&str>,
+) -> PyResult<Py<Context>> {
+    crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    let context = Context::new(ContextOptions::default())?;
+    context.compile_url(py, url, sha256, cache_dir, proxy, timeout_ms, encoding, mode)?;
+    Py::new(py, context)
+}
@@ -0,0 +1,322 @@
+// replay.rs - start_recording() / start_replay(trace)：记录并重放脚本消耗的不确定输入
+//
+// 偶发的签名错误（某一次生成的 token 不对）难以复现：随机数、时间戳、网络响应和 Python 回调的返回值每次都不同。
+// 记录模式把脚本消耗的这些输入按顺序记录到 trace 中，重放模式按同样的顺序把它们交还给脚本，
+// 同一段代码在另一个 Context（另一台机器）上得到完全相同的执行过程：
+// - random：Math.random()；now：Date.now() / new Date() / Date()；perf：performance.now()
+// - bytes：crypto.getRandomValues()（十六进制）；uuid：crypto.randomUUID()
+// - fetch：fetch()（XMLHttpRequest 基于 fetch）的状态、响应头和 body，按发起的顺序记录；网络错误同样记录
+// - call：register() 注册的 Python 函数的返回值或异常；重放时不调用 Python 函数
+//
+// trace 是 [kind, value] 的列表，value 是可以 JSON 序列化的值，可以用 json 保存。
+// 重放时脚本请求的输入与 trace 中的下一项类型不同、函数名 / URL 不同或 trace 已用完时，在 JS 中抛出 Error（replay diverged）。
+//
+// JS 侧的包装在第一次 start_recording() / start_replay() 时安装，之后一直保留（未记录 / 重放时直接调用原函数）；
+// 安装之前脚本保存的引用（如 `const rnd = Math.random`）不经过记录。trace 保存在 isolate 的 slot 中，
+// recycle_after / unload() 重建 isolate 后记录和重放停止。
+
+use anyhow::{anyhow, bail, Result};
+use deno_core::{v8, JsRuntime};
+use serde_json::{json, Value as JsonValue};
+
+use crate::context::format_error;
+
+/// JS 中切换模式的函数（不可枚举的全局变量）
+const HOLDER: &str = "__neverjscore_tape__";
+
+// 安装 JS 侧的包装：record(kind, json) 追加一项并返回索引，fill(index, json) 填入异步得到的值，
+// next(kind) 返回重放的下一项（JSON），不符合时抛出异常
+const TAPE_FACTORY: &str = r#"
+(function(record, fill, next) {
+    let mode = null;
+    Object.defineProperty(globalThis, '__neverjscore_tape__', { value: m => { mode = m; } });
+
+    // 记录 produce() 的值，或重放下一项
+    const tape = (kind, produce) => {
+        if (mode === 'replay') return JSON.parse(next(kind));
+        const value = produce();
+        if (mode === 'record') record(kind, JSON.stringify(value === undefined ? null : value));
+        return value;
+    };
+    const diverged = message => { throw new Error('replay diverged: ' + message); };
+
+    const random = Math.random;
+    Math.random = { random() { return tape('random', () => random.call(Math)); } }.random;
+
+    const RealDate = Date;
+    const now = () => tape('now', () => RealDate.now());
+    const TapeDate = function Date(...args) {
+        if (!new.target) return new RealDate(now()).toString();
+        return Reflect.construct(RealDate, args.length ? args : [now()], new.target);
+    };
+    for (const key of Object.getOwnPropertyNames(RealDate)) {
+        if (!['length', 'name', 'prototype', 'now'].includes(key)) {
+            Object.defineProperty(TapeDate, key, Object.getOwnPropertyDescriptor(RealDate, key));
+        }
+    }
+    TapeDate.now = { now() { return now(); } }.now;
+    TapeDate.prototype = RealDate.prototype;
+    Object.defineProperty(RealDate.prototype, 'constructor', { value: TapeDate, writable: true, configurable: true });
+    globalThis.Date = TapeDate;
+
+    if (typeof performance !== 'undefined' && typeof performance.now === 'function') {
+        const perfNow = performance.now;
+        performance.now = { now() { return tape('perf', () => perfNow.call(performance)); } }.now;
+    }
+
+    if (typeof crypto !== 'undefined' && typeof crypto.getRandomValues === 'function') {
+        const getRandomValues = crypto.getRandomValues;
+        crypto.getRandomValues = {
+            getRandomValues(array) {
+                if (mode === null) return getRandomValues.call(crypto, array);
+                const bytes = new Uint8Array(array.buffer, array.byteOffset, array.byteLength);
+                const hex = tape('bytes', () => {
+                    getRandomValues.call(crypto, array);
+                    return Array.from(bytes, b => b.toString(16).padStart(2, '0')).join('');
+                });
+                if (mode === 'replay') {
+                    if (hex.length !== bytes.length * 2) diverged(`getRandomValues() requested ${bytes.length} bytes, trace has ${hex.length / 2}`);
+                    for (let i = 0; i < bytes.length; i++) bytes[i] = parseInt(hex.substr(i * 2, 2), 16);
+                }
+                return array;
+            }
+        }.getRandomValues;
+    }
+    if (typeof crypto !== 'undefined' && typeof crypto.randomUUID === 'function') {
+        const randomUUID = crypto.randomUUID;
+        crypto.randomUUID = { randomUUID() { return tape('uuid', () => randomUUID.call(crypto)); } }.randomUUID;
+    }
+
+    if (typeof fetch === 'function') {
+        const realFetch = fetch;
+        globalThis.fetch = {
+            async fetch(input, init) {
+                if (mode === null) return realFetch(input, init);
+                const url = typeof input === 'string' ? input : (input && input.url) || String(input);
+                if (mode === 'replay') {
+                    const saved = JSON.parse(next('fetch'));
+                    if (saved.url !== url) diverged(`fetch(${JSON.stringify(url)}) but trace has fetch(${JSON.stringify(saved.url)})`);
+                    if (saved.error !== undefined) throw new TypeError(saved.error);
+                    return new Response(saved.body, {
+                        bodyBinary: saved.bodyBinary, status: saved.status, statusText: saved.statusText,
+                        headers: saved.headers, url: saved.url,
+                    });
+                }
+                // 按发起的顺序占位，完成后填入响应（并发请求的完成顺序不影响重放）
+                const index = mode === 'record' ? record('fetch', 'null') : -1;
+                const save = entry => { if (index >= 0) fill(index, JSON.stringify(Object.assign({ url }, entry))); };
+                let response;
+                try {
+                    response = await realFetch(input, init);
+                } catch (e) {
+                    save({ error: String(e && e.message || e) });
+                    throw e;
+                }
+                const headers = {};
+                if (response.headers && typeof response.headers.forEach === 'function') {
+                    response.headers.forEach((value, name) => { headers[name] = value; });
+                }
+                save({
+                    status: response.status, statusText: response.statusText, headers,
+                    body: await response.clone().text(), bodyBinary: response._bodyBinary || null,
+                });
+                return response;
+            }
+        }.fetch;
+    }
+})
+"#;
+
+/// 记录 / 重放模式
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Record,
+    Replay,
+}
+
+impl Mode {
+    fn js_name(self) -> &'static str {
+        match self {
+            Mode::Record => "record",
+            Mode::Replay => "replay",
+        }
+    }
+}
+
+/// isolate 的 trace（保存在 isolate 的 slot 中，安装包装之后存在）
+#[derive(Default)]
+pub struct Tape {
+    mode: Option<Mode>,
+    /// (kind, value 的 JSON)
+    entries: Vec<(String, String)>,
+    /// 重放的下一项
+    position: usize,
+}
+
+impl Tape {
+    /// 记录一项，返回索引
+    fn record(&mut self, kind: String, value: String) -> usize {
+        self.entries.push((kind, value));
+        self.entries.len() - 1
+    }
+
+    /// 重放的下一项（必须是 kind 类型）
+    fn next(&mut self, kind: &str) -> std::result::Result<String, String> {
+        let Some((expected, value)) = self.entries.get(self.position) else {
+            return Err(format!(
+                "replay diverged: script requested '{}' after the trace ended ({} entries)",
+                kind,
+                self.entries.len()
+            ));
+        };
+        if expected != kind {
+            return Err(format!(
+                "replay diverged at entry {}: script requested '{}' but the trace has '{}'",
+                self.position, kind, expected
+            ));
+        }
+        self.position += 1;
+        Ok(value.clone())
+    }
+}
+
+/// 停止后的结果：记录的各项，重放时还有未使用的项数
+pub struct Stopped {
+    pub entries: Vec<(String, String)>,
+    pub remaining: usize,
+}
+
+/// 开始记录（entries 为空）或重放 entries
+pub fn start(runtime: &mut JsRuntime, mode: Mode, entries: Vec<(String, String)>) -> Result<()> {
+    if runtime.v8_isolate().get_slot::<Tape>().is_none() {
+        install(runtime)?;
+    }
+    let tape = runtime.v8_isolate().get_slot_mut::<Tape>().expect("tape installed above");
+    match tape.mode {
+        Some(Mode::Record) => bail!("already recording; call stop_recording() first"),
+        Some(Mode::Replay) => bail!("already replaying; call stop_replay() first"),
+        None => {}
+    }
+    *tape = Tape { mode: Some(mode), entries, position: 0 };
+    set_mode(runtime, Some(mode))
+}
+
+/// 停止记录或重放（mode 为当前应处的模式），取出 trace
+pub fn stop(runtime: &mut JsRuntime, mode: Mode) -> Result<Stopped> {
+    let current = runtime.v8_isolate().get_slot::<Tape>().and_then(|tape| tape.mode);
+    if current != Some(mode) {
+        bail!("not {}", if mode == Mode::Record { "recording" } else { "replaying" });
+    }
+    set_mode(runtime, None)?;
+    let tape = std::mem::take(runtime.v8_isolate().get_slot_mut::<Tape>().expect("tape installed"));
+    Ok(Stopped {
+        remaining: tape.entries.len() - tape.position,
+        entries: tape.entries,
+    })
+}
+
+fn install(runtime: &mut JsRuntime) -> Result<()> {
+    let factory = runtime
+        .execute_script("<tape>", TAPE_FACTORY)
+        .map_err(|e| anyhow!("Failed to install replay hooks: {}", format_error(e.into())))?;
+    deno_core::scope!(scope, runtime);
+    let factory = v8::Local::<v8::Function>::try_from(v8::Local::new(scope, factory))
+        .map_err(|_| anyhow!("Replay hook factory is not a function"))?;
+    let record = v8::Function::new(scope, record_entry).ok_or_else(|| anyhow!("Failed to create function"))?;
+    let fill = v8::Function::new(scope, fill_entry).ok_or_else(|| anyhow!("Failed to create function"))?;
+    let next = v8::Function::new(scope, next_entry).ok_or_else(|| anyhow!("Failed to create function"))?;
+    let undefined = v8::undefined(scope);
+    factory
+        .call(scope, undefined.into(), &[record.into(), fill.into(), next.into()])
+        .ok_or_else(|| anyhow!("Failed to install replay hooks"))?;
+    scope.set_slot(Tape::default());
+    Ok(())
+}
+
+fn set_mode(runtime: &mut JsRuntime, mode: Option<Mode>) -> Result<()> {
+    let mode = mode.map_or("null".to_string(), |mode| format!("'{}'", mode.js_name()));
+    runtime
+        .execute_script("<tape>", format!("{}({})", HOLDER, mode))
+        .map(drop)
+        .map_err(|e| anyhow!("{}", format_error(e.into())))
+}
+
+/// 抛出 JS Error
+fn throw_error(scope: &mut v8::PinScope, message: &str) {
+    if let Some(message) = v8::String::new(scope, message) {
+        let exception = v8::Exception::error(scope, message);
+        scope.throw_exception(exception);
+    }
+}
+
+/// record(kind, json)：记录模式下追加一项，返回索引（未在记录时返回 -1）
+fn record_entry(scope: &mut v8::PinScope, args: v8::FunctionCallbackArguments, mut rv: v8::ReturnValue) {
+    let kind = args.get(0).to_rust_string_lossy(scope);
+    let value = args.get(1).to_rust_string_lossy(scope);
+    let index = match scope.get_slot_mut::<Tape>() {
+        Some(tape) if tape.mode == Some(Mode::Record) => tape.record(kind, value) as f64,
+        _ => -1.0,
+    };
+    rv.set(v8::Number::new(scope, index).into());
+}
+
+/// fill(index, json)：填入 record() 占位的项
+fn fill_entry(scope: &mut v8::PinScope, args: v8::FunctionCallbackArguments, _rv: v8::ReturnValue) {
+    let index = args.get(0).number_value(scope).unwrap_or(-1.0);
+    let value = args.get(1).to_rust_string_lossy(scope);
+    if let Some(tape) = scope.get_slot_mut::<Tape>() {
+        if let Some(entry) = (index >= 0.0).then(|| tape.entries.get_mut(index as usize)).flatten() {
+            entry.1 = value;
+        }
+    }
+}
+
+/// next(kind)：重放的下一项（JSON 字符串）
+fn next_entry(scope: &mut v8::PinScope, args: v8::FunctionCallbackArguments, mut rv: v8::ReturnValue) {
+    let kind = args.get(0).to_rust_string_lossy(scope);
+    let next = match scope.get_slot_mut::<Tape>() {
+        Some(tape) => tape.next(&kind),
+        None => Err("replay is not active".to_string()),
+    };
+    match next.map(|value| v8::String::new(scope, &value)) {
+        Ok(Some(value)) => rv.set(value.into()),
+        Ok(None) => throw_error(scope, "replayed value is too large"),
+        Err(message) => throw_error(scope, &message),
+    }
+}
+
+fn mode(scope: &mut v8::PinScope) -> Option<Mode> {
+    scope.get_slot::<Tape>().and_then(|tape| tape.mode)
+}
+
+/// 重放模式下 register() 的函数 name 的结果（Ok：返回值的 JSON，Err：抛出的错误信息）；不在重放时返回 None
+pub fn replay_call(scope: &mut v8::PinScope, name: &str) -> Option<std::result::Result<String, String>> {
+    if mode(scope) != Some(Mode::Replay) {
+        return None;
+    }
+    let entry = scope.get_slot_mut::<Tape>()?.next("call");
+    Some(entry.and_then(|entry| {
+        let entry: JsonValue = serde_json::from_str(&entry).map_err(|e| format!("invalid 'call' entry in the trace: {}", e))?;
+        if entry["name"] != name {
+            return Err(format!("replay diverged: script called '{}' but the trace has '{}'", name, entry["name"]));
+        }
+        match entry.get("error").and_then(JsonValue::as_str) {
+            Some(error) => Err(error.to_string()),
+            None => Ok(entry.get("value").unwrap_or(&JsonValue::Null).to_string()),
+        }
+    }))
+}
+
+/// 记录模式下记录 register() 的函数 name 的结果
+pub fn record_call(scope: &mut v8::PinScope, name: &str, result: std::result::Result<&str, &str>) {
+    if mode(scope) != Some(Mode::Record) {
+        return;
+    }
+    let entry = match result {
+        Ok(value) => json!({ "name": name, "value": serde_json::from_str::<JsonValue>(value).unwrap_or(JsonValue::Null) }),
+        Err(error) => json!({ "name": name, "error": error }),
+    };
+    if let Some(tape) = scope.get_slot_mut::<Tape>() {
+        tape.record("call".to_string(), entry.to_string());
+    }
+}
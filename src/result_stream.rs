@@ -0,0 +1,90 @@
+// result_stream.rs - evaluate(stream_to=...) / call(stream_to=...)：分块写出大结果
+//
+// 普通求值的结果先在 JS 中 JSON.stringify 成一个完整的字符串，再整体复制到 Rust，
+// 解析成 serde_json 值后转换为 Python 对象，几十 MB 的结果会让峰值内存成倍增长。
+//
+// 传入 stream_to（有 write(str) 方法的文件对象）时，求值包装函数改用分块序列化
+// （context.rs 的 RESULT_TO_JSON 中的 stream），每凑满约 1 MB 的 JSON 文本就经
+// op_store_result_chunk 交给 Rust，立即写入文件对象后丢弃：
+// - JS 侧不生成完整的 JSON 字符串，Rust 侧不保存完整结果，也不构建 Python 对象
+// - 调用返回写入的 UTF-8 字节数，JSON 内容与 JSON.stringify 相同
+// - max_result_bytes 按已写出的字节数检查，超出时中断序列化
+// - write() 抛出的异常原样抛给调用者；中断时文件对象中可能已有部分内容
+//
+// 与 JSON.stringify 不同，循环引用和 BigInt 无法像普通结果那样退回 String(value)，直接抛出 TypeError。
+
+use anyhow::Result;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use std::cell::{Cell, RefCell};
+
+use crate::quota::Quota;
+
+/// 一次调用的结果输出目标
+pub struct ResultStream {
+    target: Py<PyAny>,
+    written: Cell<usize>,
+    error: RefCell<Option<anyhow::Error>>,  // 中断序列化的原始错误（write() 的异常、配额错误）
+}
+
+impl ResultStream {
+    /// 创建输出目标，target 必须有 write 方法
+    pub fn new(target: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if !target.hasattr("write")? {
+            return Err(PyTypeError::new_err(format!(
+                "stream_to must be a file-like object with a write() method, got {}",
+                target.get_type().name()?
+            )));
+        }
+        Ok(Self {
+            target: target.clone().unbind(),
+            written: Cell::new(0),
+            error: RefCell::new(None),
+        })
+    }
+
+    /// 写入一个分块，先检查 max_result_bytes
+    pub fn write(&self, chunk: &str, quota: Option<&Quota>) -> Result<()> {
+        let written = self.written.get() + chunk.len();
+        if let Some(quota) = quota {
+            quota.check_result(written)?;
+        }
+        Python::attach(|py| self.target.bind(py).call_method1("write", (chunk,)).map(drop))?;
+        self.written.set(written);
+        Ok(())
+    }
+
+    /// 记录中断序列化的错误，返回交给 JS 抛出的错误
+    pub fn fail(&self, e: anyhow::Error) -> std::io::Error {
+        let error = std::io::Error::other(format!("Failed to write result: {}", e));
+        *self.error.borrow_mut() = Some(e);
+        error
+    }
+
+    /// 取出中断序列化的错误
+    pub fn take_error(&self) -> Option<anyhow::Error> {
+        self.error.borrow_mut().take()
+    }
+
+    /// 已写入的 UTF-8 字节数
+    pub fn written(&self) -> usize {
+        self.written.get()
+    }
+}
+
+/// 解析 evaluate() / call() 的 stream_to 参数（不能与 binary=True 同时使用）
+pub fn stream_target(stream_to: Option<&Bound<'_, PyAny>>, binary: bool) -> PyResult<Option<ResultStream>> {
+    match stream_to {
+        Some(_) if binary => Err(PyValueError::new_err("binary=True cannot be combined with stream_to")),
+        Some(target) => ResultStream::new(target).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// 转换流式调用的错误：write() 抛出的 Python 异常原样返回，其他错误同 quota::py_error
+pub fn py_error(prefix: &str, e: anyhow::Error) -> PyErr {
+    match e.downcast::<PyErr>() {
+        Ok(err) => err,
+        Err(e) => crate::quota::py_error(prefix, e),
+    }
+}
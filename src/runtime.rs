@@ -1,10 +1,111 @@
+use anyhow::{Result, anyhow};
+use deno_core::v8;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::{PyDeprecationWarning, PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyDict};
 use std::cell::RefCell;
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// 全局运行时配置
+///
+/// 只能在 V8 初始化（第一次创建 Context）之前通过 `never_jscore.init()` 修改。
+#[derive(Clone, Debug)]
+pub struct RuntimeConfig {
+    /// 每个线程的单线程 tokio runtime 的 max_blocking_threads（阻塞任务线程池的上限）
+    pub tokio_threads: usize,
+    /// 使用单线程 V8 平台（不创建后台编译 / GC 线程）
+    pub single_threaded_platform: bool,
+    /// 禁用 JIT（--jitless），只使用解释器执行，减少执行不可信代码时的攻击面
+    pub jitless: bool,
+    /// V8 栈大小上限（KB，--stack-size），None 使用 V8 默认值
+    pub stack_size_kb: Option<usize>,
+    /// 已应用的 V8 命令行参数（set_v8_flags）
+    pub v8_flags: Vec<String>,
+    /// WASM 特性开关对应的 V8 参数（init(wasm_features=...)）
+    pub wasm_flags: Vec<String>,
+    /// false: 每个 Context / Realm 删除全局 Intl（ICU 数据编译在扩展中，无法卸载）
+    pub icu: bool,
+    /// 未传入 snapshot 的 Context 使用的启动快照
+    pub snapshot: StartupSnapshot,
+}
+
+/// 未传入 snapshot 的 Context 的启动方式（init(snapshot=...)）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StartupSnapshot {
+    /// 启用扩展时使用编译时嵌入的内置快照（默认）
+    Builtin,
+    /// 不使用快照，每个 Context 首次执行时直接执行 polyfill
+    Disabled,
+    /// build_snapshot() 生成的快照，用于 enable_extensions 一致的 Context
+    Custom {
+        enable_extensions: bool,
+        enable_logging: bool,
+        blob: &'static [u8],
+    },
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        RuntimeConfig {
+            tokio_threads: 1,
+            single_threaded_platform: false,
+            jitless: false,
+            stack_size_kb: None,
+            v8_flags: Vec::new(),
+            wasm_flags: Vec::new(),
+            icu: true,
+            snapshot: StartupSnapshot::Builtin,
+        }
+    }
+}
+
+impl RuntimeConfig {
+    /// 是否使用默认的 V8 参数（编译时嵌入的内置快照按默认参数生成，见 snapshot::builtin_snapshot）
+    pub fn default_v8_flags(&self) -> bool {
+        !self.jitless
+            && !self.single_threaded_platform
+            && self.stack_size_kb.is_none()
+            && self.v8_flags.is_empty()
+            && self.wasm_flags.is_empty()
+    }
+}
+
+static RUNTIME_CONFIG: Lazy<Mutex<RuntimeConfig>> = Lazy::new(|| Mutex::new(RuntimeConfig::default()));
+
+/// run_with_tokio() 使用的 tokio 配置（RuntimeConfig 的一部分）
+#[derive(Clone, Copy)]
+struct TokioConfig {
+    threads: usize,
+}
+
+impl From<&RuntimeConfig> for TokioConfig {
+    fn from(config: &RuntimeConfig) -> Self {
+        TokioConfig {
+            threads: config.tokio_threads,
+        }
+    }
+}
+
+/// V8 初始化时固定下来的 tokio 配置（之后 init() 不能再修改）
+static TOKIO_CONFIG: OnceLock<TokioConfig> = OnceLock::new();
+
+/// 存活的 isolate 数（Context、后台编译和构建快照的临时 isolate）
+static LIVE_ISOLATES: AtomicUsize = AtomicUsize::new(0);
+
+/// shutdown() 已经调用：V8 释放后不能重新初始化
+static SHUT_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// shutdown() 等待 tokio runtime 的后台任务结束的时间
+const TOKIO_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// 全局 V8 平台初始化标志
 ///
 /// V8 平台只能初始化一次，使用 OnceLock 确保线程安全。
-static V8_INITIALIZED: OnceLock<()> = OnceLock::new();
+/// 保存初始化时的 fork 代数，用于检测平台是否是从父进程继承的。
+static V8_INITIALIZED: OnceLock<usize> = OnceLock::new();
 
 /// 线程本地 Tokio Runtime
 ///
@@ -29,30 +130,355 @@ thread_local! {
 /// - 每个线程创建独立的 Context
 /// - 不同线程的 Context 可以并行执行
 /// - 单个 Context 不应跨线程共享（V8 Isolate 限制）
-pub fn ensure_v8_initialized() {
+///
+/// # fork 检测
+///
+/// 平台在第一次创建 Context 时才初始化（而不是 import 时）。
+/// 如果平台已在父进程中初始化，子进程中没有平台的工作线程，也无法重新初始化，此时返回错误。
+pub fn ensure_v8_initialized() -> Result<()> {
+    // 初始化期间持有配置锁，避免 init() 与初始化交错
+    let config = RUNTIME_CONFIG.lock().unwrap();
+    check_not_shut_down()?;
+
     // 初始化 V8 平台（全局只执行一次）
-    V8_INITIALIZED.get_or_init(|| {
-        deno_core::JsRuntime::init_platform(None, false);
+    let initialized_generation = *V8_INITIALIZED.get_or_init(|| {
+        if config.jitless {
+            v8::V8::set_flags_from_string("--jitless");
+        }
+        v8::V8::set_flags_from_string(crate::shadow_realm::V8_FLAG);
+        // Temporal：V8 内置实现（temporal_rs），结果转换见 convert.rs
+        v8::V8::set_flags_from_string("--harmony-temporal");
+        if let Some(kb) = config.stack_size_kb {
+            v8::V8::set_flags_from_string(&format!("--stack-size={}", kb));
+        }
+        for flag in &config.wasm_flags {
+            v8::V8::set_flags_from_string(flag);
+        }
+        let platform = if config.single_threaded_platform {
+            // 单线程平台必须配合 --single-threaded，否则 V8 仍会尝试投递后台任务
+            v8::V8::set_flags_from_string("--single-threaded");
+            Some(v8::new_single_threaded_default_platform(false).make_shared())
+        } else {
+            None
+        };
+        deno_core::JsRuntime::init_platform(platform, false);
+        crate::fork::generation()
+    });
+    // V8 初始化之后配置不能再修改，run_with_tokio() 不再需要每次加锁读取
+    TOKIO_CONFIG.get_or_init(|| TokioConfig::from(&*config));
+    drop(config);
+
+    if crate::fork::is_inherited(initialized_generation) {
+        return Err(anyhow!(
+            "V8 was initialized in the parent process before os.fork(); \
+             create Contexts only after fork (or use the 'spawn' / 'forkserver' start method)"
+        ));
+    }
+    Ok(())
+}
+
+fn check_not_shut_down() -> Result<()> {
+    if SHUT_DOWN.load(Ordering::SeqCst) {
+        return Err(anyhow!(
+            "never_jscore.shutdown() has been called: V8 cannot be initialized again in this process"
+        ));
+    }
+    Ok(())
+}
+
+/// 一个存活的 isolate，创建 JsRuntime 之前获取，isolate 销毁后释放
+///
+/// shutdown() 只在没有存活的 isolate 时释放 V8。
+pub struct IsolateGuard(());
+
+impl IsolateGuard {
+    /// shutdown() 之后返回错误
+    pub fn acquire() -> Result<Self> {
+        // 与 shutdown() 使用同一把锁，检查和计数之间不会释放 V8
+        let _config = RUNTIME_CONFIG.lock().unwrap();
+        check_not_shut_down()?;
+        LIVE_ISOLATES.fetch_add(1, Ordering::SeqCst);
+        Ok(IsolateGuard(()))
+    }
+}
+
+impl Drop for IsolateGuard {
+    fn drop(&mut self) {
+        LIVE_ISOLATES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 丢弃当前线程的 Tokio Runtime（fork 后在子进程中调用）
+///
+/// 继承来的 runtime 引用的后台线程在子进程中不存在，析构时可能死锁，
+/// 因此直接泄漏，下次调用 run_with_tokio 时重新创建。
+pub fn forget_tokio_runtime() {
+    let _ = TOKIO_RUNTIME.try_with(|cell| {
+        if let Ok(mut rt) = cell.try_borrow_mut() {
+            std::mem::forget(rt.take());
+        }
     });
 }
 
+/// V8 平台是否已经初始化（之后不能再修改配置）
+pub fn v8_initialized() -> bool {
+    V8_INITIALIZED.get().is_some()
+}
+
+/// 当前的运行时配置
+pub fn runtime_config() -> RuntimeConfig {
+    RUNTIME_CONFIG.lock().unwrap().clone()
+}
+
+/// 修改运行时配置（V8 已初始化后返回错误）
+///
+/// `what` 为调用方的名称，用于错误信息。
+pub fn configure<R>(what: &str, f: impl FnOnce(&mut RuntimeConfig) -> R) -> Result<R> {
+    let mut config = RUNTIME_CONFIG.lock().unwrap();
+    if V8_INITIALIZED.get().is_some() {
+        return Err(anyhow!(
+            "never_jscore.{}() must be called before the first Context is created",
+            what
+        ));
+    }
+    Ok(f(&mut config))
+}
+
+/// 要求进程以 jitless 模式运行（Context(jitless=True) 调用）
+///
+/// jitless 是进程级设置：V8 尚未初始化时开启；已经以 JIT 模式初始化时返回错误。
+pub fn require_jitless() -> Result<()> {
+    let mut config = RUNTIME_CONFIG.lock().unwrap();
+    if config.jitless || config.v8_flags.iter().any(|flag| flag == "--jitless") {
+        return Ok(());
+    }
+    if V8_INITIALIZED.get().is_some() {
+        return Err(anyhow!(
+            "jitless mode is process-wide and V8 is already running with JIT; \
+             call never_jscore.init(jitless=True) or create the jitless Context before any other Context"
+        ));
+    }
+    config.jitless = true;
+    Ok(())
+}
+
+/// V8 在 64 位平台上默认的栈大小上限（KB）
+const DEFAULT_STACK_SIZE_KB: usize = 984;
+
+/// 栈上限之外为原生代码（V8 运行时函数、ops）保留的空间
+const STACK_MARGIN: usize = 256 * 1024;
+
+/// 校验 stack_size_kb 参数
+pub fn validate_stack_size(stack_size_kb: Option<usize>) -> PyResult<()> {
+    if stack_size_kb == Some(0) {
+        return Err(PyValueError::new_err("stack_size_kb must be at least 1"));
+    }
+    Ok(())
+}
+
+/// 要求进程使用指定的 V8 栈大小（Context(stack_size_kb=...) 调用）
+///
+/// V8 的栈大小是进程级设置：V8 尚未初始化时设置；已经以其他值初始化时返回错误。
+pub fn require_stack_size(stack_size_kb: usize) -> Result<()> {
+    let mut config = RUNTIME_CONFIG.lock().unwrap();
+    if config.stack_size_kb == Some(stack_size_kb) {
+        return Ok(());
+    }
+    if V8_INITIALIZED.get().is_some() {
+        return Err(anyhow!(
+            "stack_size_kb is process-wide and V8 is already running with stack size {} KB; \
+             call never_jscore.init(stack_size_kb={}) or pass it to the first Context",
+            config.stack_size_kb.unwrap_or(DEFAULT_STACK_SIZE_KB),
+            stack_size_kb
+        ));
+    }
+    config.stack_size_kb = Some(stack_size_kb);
+    Ok(())
+}
+
+/// WASM 特性及对应的 V8 特性参数（--experimental-wasm-<flag>）
+///
+/// 参数列表为空的特性在当前 V8 中已经正式发布、始终启用，没有开关。
+const WASM_FEATURES: &[(&str, &[&str])] = &[
+    ("simd", &[]),
+    ("relaxed_simd", &[]),
+    ("threads", &[]),
+    ("memory64", &[]),
+    ("multi_memory", &[]),
+    ("gc", &[]),
+    ("exceptions", &["legacy_eh", "exnref"]),
+    ("branch_hinting", &["branch_hinting"]),
+    ("type_reflection", &["type_reflection"]),
+    ("stringref", &["stringref"]),
+    ("fp16", &["fp16"]),
+    ("memory_control", &["memory_control"]),
+];
+
+/// 解析 init() 的 wasm_features 参数，生成 V8 参数
+fn wasm_feature_flags(features: &Bound<'_, PyDict>) -> PyResult<Vec<String>> {
+    let mut flags = Vec::new();
+    for (name, enabled) in features.iter() {
+        let name: String = name.extract()?;
+        let enabled: bool = enabled.extract()?;
+        let Some((_, feature_flags)) = WASM_FEATURES.iter().find(|(feature, _)| *feature == name) else {
+            let known: Vec<&str> = WASM_FEATURES.iter().map(|(feature, _)| *feature).collect();
+            return Err(PyValueError::new_err(format!(
+                "Unknown WASM feature '{}', expected one of: {}",
+                name,
+                known.join(", ")
+            )));
+        };
+        if feature_flags.is_empty() && !enabled {
+            return Err(PyValueError::new_err(format!(
+                "WASM feature '{}' is always enabled in this V8 version and cannot be disabled",
+                name
+            )));
+        }
+        for flag in feature_flags.iter() {
+            let prefix = if enabled { "--" } else { "--no-" };
+            flags.push(format!("{}experimental-wasm-{}", prefix, flag));
+        }
+    }
+    Ok(flags)
+}
+
+/// V8 编译错误中提示的特性参数（"enable with --experimental-wasm-exnref"）对应的 wasm_features 名称
+pub fn wasm_feature_for_flag(flag: &str) -> Option<&'static str> {
+    let flag = flag.replace('-', "_");
+    WASM_FEATURES
+        .iter()
+        .find(|(_, flags)| flags.contains(&flag.as_str()))
+        .map(|(feature, _)| *feature)
+}
+
+/// 工作线程（ThreadedContext / ContextPool / 后台编译）的线程栈大小
+///
+/// 线程栈必须大于 V8 的栈上限，否则深度递归会在抛出 RangeError 之前越过线程栈导致进程崩溃。
+pub fn worker_stack_size() -> usize {
+    let stack_size_kb = runtime_config().stack_size_kb.unwrap_or(DEFAULT_STACK_SIZE_KB);
+    (stack_size_kb * 1024 + STACK_MARGIN).max(2 * 1024 * 1024)
+}
+
+/// 检查当前线程剩余的栈空间是否容纳得下设置的 V8 栈大小
+///
+/// 只在显式设置了 stack_size_kb 时检查：V8 假设从进入 isolate 的位置起有 stack_size_kb 可用，
+/// 线程栈不够时深度递归会直接越过线程栈导致段错误。
+#[cfg(target_os = "linux")]
+pub fn check_thread_stack() -> Result<()> {
+    let Some(stack_size_kb) = runtime_config().stack_size_kb else {
+        return Ok(());
+    };
+
+    let mut low: *mut libc::c_void = std::ptr::null_mut();
+    let mut size: libc::size_t = 0;
+    let ok = unsafe {
+        let mut attr: libc::pthread_attr_t = std::mem::zeroed();
+        if libc::pthread_getattr_np(libc::pthread_self(), &mut attr) != 0 {
+            return Ok(());
+        }
+        let ret = libc::pthread_attr_getstack(&attr, &mut low, &mut size);
+        libc::pthread_attr_destroy(&mut attr);
+        ret == 0
+    };
+    if !ok {
+        return Ok(());
+    }
+
+    let marker = 0u8;
+    let available = (&marker as *const u8 as usize).saturating_sub(low as usize);
+    if available < stack_size_kb * 1024 + STACK_MARGIN {
+        return Err(anyhow!(
+            "stack_size_kb={} does not fit in the current thread's stack ({} KB available); \
+             use a smaller stack_size_kb, or create the Context in a thread with a larger stack \
+             (threading.stack_size()) or use ThreadedContext",
+            stack_size_kb,
+            available / 1024
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn check_thread_stack() -> Result<()> {
+    Ok(())
+}
+
+/// 把参数传给 V8，返回 V8 无法识别的参数
+fn apply_v8_flags(flags: &[String]) -> Vec<String> {
+    // 第一个参数是程序名，V8 会忽略
+    let mut args = vec!["never_jscore".to_string()];
+    args.extend(flags.iter().cloned());
+    v8::V8::set_flags_from_command_line(args).into_iter().skip(1).collect()
+}
+
+/// 应用 V8 参数并记录到配置中，返回 V8 无法识别的参数
+fn push_v8_flags(config: &mut RuntimeConfig, flags: Vec<String>) -> Vec<String> {
+    let unknown = apply_v8_flags(&flags);
+    config.v8_flags.extend(flags.into_iter().filter(|flag| !unknown.contains(flag)));
+    unknown
+}
+
+/// 检查 V8 参数中没有 NUL 字符
+fn validate_v8_flags(flags: &[String]) -> PyResult<()> {
+    if flags.iter().any(|flag| flag.contains('\0')) {
+        return Err(PyValueError::new_err("V8 flags must not contain NUL characters"));
+    }
+    Ok(())
+}
+
+/// V8 无法识别的参数转换为 ValueError
+fn unknown_v8_flags(unknown: Vec<String>) -> PyResult<()> {
+    if !unknown.is_empty() {
+        return Err(PyValueError::new_err(format!("Unrecognized V8 flags: {}", unknown.join(" "))));
+    }
+    Ok(())
+}
+
+/// 解析 init(snapshot=...)：True / False / build_snapshot() 生成的 bytes
+fn startup_snapshot_from_py(value: &Bound<'_, PyAny>) -> PyResult<StartupSnapshot> {
+    if let Ok(enabled) = value.cast::<PyBool>() {
+        return Ok(if enabled.is_true() { StartupSnapshot::Builtin } else { StartupSnapshot::Disabled });
+    }
+    let data = value
+        .cast::<PyBytes>()
+        .map_err(|_| PyTypeError::new_err("snapshot must be a bool or bytes returned by build_snapshot()"))?;
+    let info = crate::snapshot::snapshot_from_py(data.as_bytes())?;
+    Ok(StartupSnapshot::Custom {
+        enable_extensions: info.enable_extensions,
+        enable_logging: info.enable_logging,
+        blob: info.blob,
+    })
+}
+
+/// 删除全局 Intl（init(icu=False)）
+pub const HIDE_INTL: &str = "delete globalThis.Intl;";
+
 /// 在当前线程的 Tokio Runtime 上执行异步代码
 ///
-/// 每个线程有自己独立的单线程 Tokio runtime。
+/// 默认每个线程有自己独立的单线程 Tokio runtime。
 /// 这样可以避免多线程调度器带来的 RefCell 问题。
 ///
 /// # 线程安全
 ///
 /// 使用 thread_local! 确保每个 OS 线程有独立的 runtime。
 /// 多个 Python 线程可以并行运行，每个使用自己的 runtime。
+///
+/// JsRuntime 的 future 和 deno_core spawn 的任务都不是 Send，只能在单线程 runtime 上执行，
+/// 因此不提供多线程 runtime（init(current_thread=False) 抛出 ValueError）。
 pub fn run_with_tokio<F, R>(f: F) -> R
 where
     F: std::future::Future<Output = R>,
 {
+    // V8 初始化之前（配置仍可能被 init() 修改）才需要加锁读取
+    let config = match TOKIO_CONFIG.get() {
+        Some(config) => *config,
+        None => TokioConfig::from(&*RUNTIME_CONFIG.lock().unwrap()),
+    };
     TOKIO_RUNTIME.with(|cell| {
         // 如果当前线程还没有 runtime，创建一个
         if cell.borrow().is_none() {
             let rt = tokio::runtime::Builder::new_current_thread()
+                .max_blocking_threads(config.threads)
                 .enable_all()
                 .build()
                 .expect("Failed to create tokio runtime");
@@ -66,3 +492,202 @@ where
         rt.block_on(f)
     })
 }
+
+/// 全局初始化配置（必须在第一次创建 Context 之前调用）
+///
+/// 用于 uWSGI、celery、容器等对线程数敏感的环境，控制 never_jscore 和 V8 创建的后台线程。
+/// 不调用时使用默认配置。
+///
+/// Args:
+///     tokio_threads: 每个线程的 tokio runtime 的 max_blocking_threads，默认 1
+///                    - 执行 JS 的每个线程各有一个单线程（current-thread）runtime，不创建工作线程；
+///                      这里限制的是其中阻塞任务（文件读写等 spawn_blocking）线程池的大小
+///     current_thread: 已弃用，传入时发出 DeprecationWarning
+///                     - runtime 始终是每个线程独立的单线程 runtime（JsRuntime 不是 Send），False 抛出 ValueError
+///     single_threaded_platform: 是否使用单线程 V8 平台，默认 False
+///                               - True: 不创建 V8 后台编译 / GC 线程（适合线程配额严格的容器），
+///                                 GC 和优化编译在执行 JS 的线程上同步完成
+///     jitless: 是否禁用 JIT，默认 False
+///              - True: 只用解释器执行 JS（不生成机器码），适合执行不可信代码，性能明显下降
+///              - jitless 模式下 WebAssembly 通常不可用
+///     stack_size_kb: V8 栈大小上限（KB），默认 None 使用 V8 默认值（约 1 MB）
+///              - 递归超过上限时抛出可以捕获的 RangeError: Maximum call stack size exceeded
+///              - 调大可以支持更深的递归，但不能超过执行 JS 的线程的栈大小
+///                （ThreadedContext / ContextPool 的工作线程会自动分配足够的栈）
+///     wasm_features: WASM 特性开关，如 {"exceptions": True, "stringref": True}，默认 None 使用 V8 默认值
+///              - simd / relaxed_simd / threads / memory64 / multi_memory / gc 在当前 V8 中始终启用，只接受 True
+///              - exceptions（legacy 异常处理和 exnref，默认启用）、branch_hinting 可以关闭
+///              - type_reflection / stringref / fp16 / memory_control 为实验特性，默认关闭
+///     v8_flags: V8 命令行参数列表，与 set_v8_flags() 相同，如 ["--max-old-space-size=512"]
+///     icu: 是否提供 Intl，默认 True
+///          - False: 每个 Context / Realm 删除全局 Intl（ICU 数据编译在扩展中，
+///            Date / String 的 toLocale* 方法仍然可用）
+///     snapshot: 未传入 snapshot 的 Context 的启动快照，默认 True
+///          - True: 启用扩展时使用编译时嵌入的内置快照（polyfill 已执行过的堆）；
+///            没有嵌入快照或修改了 V8 参数（jitless、v8_flags 等）时直接执行 polyfill
+///          - False: 不使用快照，每个 Context 首次执行时重新执行 polyfill
+///          - bytes: build_snapshot() 生成的快照，用于 enable_extensions 与快照一致的 Context
+///            （build_snapshot() 本身会初始化 V8，快照需要事先构建并保存到文件）
+///
+/// Raises:
+///     ValueError: tokio_threads 或 stack_size_kb 为 0，current_thread 为 False，wasm_features 中有未知 / 无法关闭的特性，
+///                 snapshot 不是有效的快照，或 v8_flags 中有 V8 无法识别的参数（其余配置仍然生效）
+///     TypeError: snapshot 不是 bool / bytes
+///     RuntimeError: 已经创建过 Context
+///
+/// Example:
+///     ```python
+///     import never_jscore
+///
+///     never_jscore.init(tokio_threads=1)
+///     ctx = never_jscore.Context()
+///     ```
+#[pyfunction]
+#[pyo3(signature = (tokio_threads=None, current_thread=None, single_threaded_platform=None, jitless=None, stack_size_kb=None, wasm_features=None, v8_flags=None, icu=None, snapshot=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn init(
+    py: Python<'_>,
+    tokio_threads: Option<usize>,
+    current_thread: Option<bool>,
+    single_threaded_platform: Option<bool>,
+    jitless: Option<bool>,
+    stack_size_kb: Option<usize>,
+    wasm_features: Option<&Bound<'_, PyDict>>,
+    v8_flags: Option<Vec<String>>,
+    icu: Option<bool>,
+    snapshot: Option<&Bound<'_, PyAny>>,
+) -> PyResult<()> {
+    if tokio_threads == Some(0) {
+        return Err(PyValueError::new_err("tokio_threads must be at least 1"));
+    }
+    if current_thread == Some(false) {
+        return Err(PyValueError::new_err(
+            "current_thread=False is not supported: JS runtimes are not Send and always run on a per-thread current-thread runtime",
+        ));
+    }
+    if current_thread.is_some() {
+        PyErr::warn(
+            py,
+            &py.get_type::<PyDeprecationWarning>(),
+            c"init(current_thread=...) is deprecated: the runtime is always a per-thread current-thread runtime",
+            1,
+        )?;
+    }
+    validate_stack_size(stack_size_kb)?;
+    let wasm_flags = wasm_features.map(wasm_feature_flags).transpose()?;
+    if let Some(flags) = &v8_flags {
+        validate_v8_flags(flags)?;
+    }
+    let snapshot = snapshot.map(startup_snapshot_from_py).transpose()?;
+
+    let unknown = configure("init", |config| {
+        if let Some(threads) = tokio_threads {
+            config.tokio_threads = threads;
+        }
+        if let Some(single_threaded) = single_threaded_platform {
+            config.single_threaded_platform = single_threaded;
+        }
+        if let Some(jitless) = jitless {
+            config.jitless = jitless;
+        }
+        if stack_size_kb.is_some() {
+            config.stack_size_kb = stack_size_kb;
+        }
+        if let Some(wasm_flags) = wasm_flags {
+            config.wasm_flags = wasm_flags;
+        }
+        if let Some(icu) = icu {
+            config.icu = icu;
+        }
+        if let Some(snapshot) = snapshot {
+            config.snapshot = snapshot;
+        }
+        v8_flags.map(|flags| push_v8_flags(config, flags)).unwrap_or_default()
+    })
+    .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    unknown_v8_flags(unknown)
+}
+
+/// 设置 V8 命令行参数（必须在第一次创建 Context 之前调用）
+///
+/// 参数立即传给 V8，多次调用会累加。用于调整堆大小、关闭 JIT 等。
+///
+/// Args:
+///     flags: V8 参数列表，如 ["--max-old-space-size=512", "--jitless"]
+///
+/// Raises:
+///     ValueError: 包含 V8 无法识别的参数（其余可识别的参数仍然生效）
+///     RuntimeError: 已经创建过 Context
+///
+/// Example:
+///     ```python
+///     import never_jscore
+///
+///     never_jscore.set_v8_flags(["--max-old-space-size=512", "--jitless"])
+///     ctx = never_jscore.Context()
+///     ```
+#[pyfunction]
+pub fn set_v8_flags(flags: Vec<String>) -> PyResult<()> {
+    validate_v8_flags(&flags)?;
+
+    let unknown = configure("set_v8_flags", |config| push_v8_flags(config, flags))
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    unknown_v8_flags(unknown)
+}
+
+/// 按顺序关闭 never_jscore 的后台线程并释放 V8（进程退出前调用，可选）
+///
+/// 插件宿主、测试框架等在退出时检查残留线程的环境中使用。必须在所有 Context 销毁之后调用：
+/// 关闭当前线程的 tokio runtime、
+/// 停止看门狗线程，最后释放 V8 和 V8 平台（后台编译 / GC 线程）。
+///
+/// 释放后的 V8 不能重新初始化：之后创建 Context 抛出 RuntimeError。重复调用不做任何事。
+/// 其他线程的单线程 tokio runtime 随线程退出销毁；ThreadedContext / ContextPool 的工作线程在 close() 时退出。
+///
+/// Raises:
+///     RuntimeError: 还有存活的 Context（包括 ThreadedContext / ContextPool 的工作线程、
+///                   模块级 eval() 的隐式 Context，先 close() / clear_eval_cache() / del）
+///
+/// Example:
+///     ```python
+///     import atexit
+///     import never_jscore
+///
+///     ctx = never_jscore.Context()
+///     ...
+///     del ctx
+///     never_jscore.shutdown()
+///     ```
+#[pyfunction]
+pub fn shutdown(py: Python<'_>) -> PyResult<()> {
+    // 等待其他线程中的 Context 析构时不持有 GIL
+    py.detach(|| {
+        let _config = RUNTIME_CONFIG.lock().unwrap();
+        if SHUT_DOWN.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let live = LIVE_ISOLATES.load(Ordering::SeqCst);
+        if live > 0 {
+            return Err(PyRuntimeError::new_err(format!(
+                "Cannot shut down: {} Context(s) are still alive; close or delete them first",
+                live
+            )));
+        }
+        SHUT_DOWN.store(true, Ordering::SeqCst);
+
+        let _ = TOKIO_RUNTIME.try_with(|cell| {
+            if let Some(rt) = cell.borrow_mut().take() {
+                rt.shutdown_timeout(TOKIO_SHUTDOWN_TIMEOUT);
+            }
+        });
+        crate::watchdog::stop();
+
+        // 从父进程继承的平台没有工作线程，释放时可能死锁，只做标记
+        if V8_INITIALIZED.get().is_some_and(|generation| !crate::fork::is_inherited(*generation)) {
+            // SAFETY: 没有存活的 isolate（LIVE_ISOLATES 为 0，且持有锁期间不能创建新的）
+            unsafe { v8::V8::dispose() };
+            v8::V8::dispose_platform();
+        }
+        Ok(())
+    })
+}
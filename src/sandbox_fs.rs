@@ -0,0 +1,296 @@
+// sandbox_fs.rs - 限定目录的文件 API（Context(fs_roots=...)）
+//
+// 设置 fs_roots 后，JS 中可以使用 Deno 风格的文件 API（Deno.readTextFile、Deno.readFile、
+// Deno.readDir、Deno.stat，可写目录还有 Deno.writeTextFile / Deno.writeFile），
+// 但只能访问列出的目录，默认只读。用于加载字典文件、WASM 等资源，
+// 不需要像 permissions={"read": True} 那样开放整个文件系统。
+//
+// 路径检查与 permissions 相同：解析为绝对路径（包括符号链接）后必须位于某个根目录内，
+// 指向不存在目标的符号链接直接拒绝。读写文件时不跟随最后一级符号链接（O_NOFOLLOW），
+// 打开后再确认路径的解析结果没有变化，防止检查之后路径被替换为指向根目录之外的链接。
+// fs_roots 本身就是白名单，不受 permissions 的 read / write 设置影响。
+//
+// ops 以字符串返回结果：文本为 JSON 字符串字面量，二进制为 Base64，
+// 失败时返回 "Error: ..." 或 "PermissionDenied: ..."。
+
+use base64::prelude::*;
+use deno_core::{extension, op2, OpState};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use crate::permissions::{absolute_path, open_resolved, unchanged, DENIED_PREFIX};
+
+/// 一个可访问的根目录
+#[derive(Clone, Debug)]
+struct Root {
+    path: PathBuf,
+    writable: bool,
+}
+
+/// Context 可访问的目录
+#[derive(Clone, Debug)]
+pub struct FsRoots {
+    roots: Vec<Root>,
+}
+
+impl FsRoots {
+    /// 从 Python 参数解析：目录（或目录列表，均为只读），或 `{目录: "r" | "rw"}` 字典
+    pub fn from_py(value: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let entries: Vec<(String, bool)> = if let Ok(dict) = value.cast::<PyDict>() {
+            dict.iter()
+                .map(|(path, mode)| {
+                    let path: String = path.extract()?;
+                    let writable = match mode.extract::<String>()?.as_str() {
+                        "r" => false,
+                        "rw" => true,
+                        other => {
+                            return Err(PyValueError::new_err(format!(
+                                "fs_roots mode for '{}' must be 'r' or 'rw', got '{}'",
+                                path, other
+                            )))
+                        }
+                    };
+                    Ok((path, writable))
+                })
+                .collect::<PyResult<_>>()?
+        } else if value.is_instance_of::<PyString>() {
+            vec![(value.extract()?, false)]
+        } else {
+            let paths: Vec<String> = value.extract().map_err(|_| {
+                PyValueError::new_err("fs_roots must be a directory, a list of directories or a dict of {directory: 'r' | 'rw'}")
+            })?;
+            paths.into_iter().map(|path| (path, false)).collect()
+        };
+
+        let roots = entries
+            .into_iter()
+            .map(|(path, writable)| match Path::new(&path).canonicalize() {
+                Ok(resolved) if resolved.is_dir() => Ok(Root { path: resolved, writable }),
+                Ok(_) => Err(PyValueError::new_err(format!("fs_roots entry '{}' is not a directory", path))),
+                Err(e) => Err(PyValueError::new_err(format!("fs_roots entry '{}' is not accessible: {}", path, e))),
+            })
+            .collect::<PyResult<_>>()?;
+        Ok(FsRoots { roots })
+    }
+
+    /// 检查路径，返回解析后的绝对路径
+    fn check(&self, path: &str, write: bool) -> Result<PathBuf, String> {
+        let allowed = |target: &PathBuf| {
+            let mut roots = self.roots.iter().filter(|root| target.starts_with(&root.path));
+            if write { roots.any(|root| root.writable) } else { roots.next().is_some() }
+        };
+        match absolute_path(Path::new(path)) {
+            Some(target) if allowed(&target) => Ok(target),
+            _ => Err(outside(path, write)),
+        }
+    }
+}
+
+/// 路径不在 fs_roots 内时的错误信息
+fn outside(path: &str, write: bool) -> String {
+    let kind = if write { "write" } else { "read" };
+    format!("{}{} access to '{}' is outside fs_roots", DENIED_PREFIX, kind, path)
+}
+
+/// 打开检查过的文件：不跟随最后一级符号链接，打开后确认解析结果仍是检查过的路径
+///
+/// 写入时不截断，确认之后再由调用方清空，路径被替换时不会破坏根目录之外的文件。
+fn open_checked(target: &Path, path: &str, write: bool) -> Result<fs::File, String> {
+    let opened = open_resolved(target, write).map_err(|e| {
+        let action = if write { "write" } else { "read" };
+        format!("Error: Failed to {} file '{}': {}", action, path, e)
+    })?;
+    opened.ok_or_else(|| outside(path, write))
+}
+
+/// 不经过 open_checked 的操作（stat / readDir）完成后确认路径没有被替换，被替换时按根目录之外拒绝
+fn recheck(target: &Path, path: &str) -> Result<(), String> {
+    if unchanged(target) {
+        Ok(())
+    } else {
+        Err(outside(path, false))
+    }
+}
+
+/// 读取检查过的文件的全部内容
+fn read_checked(target: &Path, path: &str) -> Result<Vec<u8>, String> {
+    let mut content = Vec::new();
+    open_checked(target, path, false)?
+        .read_to_end(&mut content)
+        .map_err(|e| format!("Error: Failed to read file '{}': {}", path, e))?;
+    Ok(content)
+}
+
+/// 检查路径（未设置 fs_roots 时全部拒绝）
+fn resolve(state: &OpState, path: &str, write: bool) -> Result<PathBuf, String> {
+    match state.try_borrow::<Arc<FsRoots>>() {
+        Some(roots) => roots.check(path, write),
+        None => Err(format!("{}fs_roots is not configured", DENIED_PREFIX)),
+    }
+}
+
+#[op2(fast)]
+/// 是否设置了 fs_roots（polyfill 据此决定是否提供全局 Deno 文件 API）
+pub fn op_sandbox_fs_enabled(state: &mut OpState) -> bool {
+    state.has::<Arc<FsRoots>>()
+}
+
+#[op2]
+#[string]
+/// 读取文本文件
+///
+/// # Returns
+/// 文件内容的 JSON 字符串字面量，失败时返回错误信息
+pub fn op_sandbox_read_text(state: &mut OpState, #[string] path: String) -> String {
+    let target = match resolve(state, &path, false) {
+        Ok(target) => target,
+        Err(denied) => return denied,
+    };
+    let content = read_checked(&target, &path).and_then(|bytes| {
+        String::from_utf8(bytes).map_err(|e| format!("Error: Failed to read file '{}': {}", path, e))
+    });
+    match content {
+        Ok(content) => serde_json::Value::String(content).to_string(),
+        Err(e) => e,
+    }
+}
+
+#[op2]
+#[string]
+/// 读取二进制文件
+///
+/// # Returns
+/// Base64 编码的文件内容，失败时返回错误信息
+pub fn op_sandbox_read_bytes(state: &mut OpState, #[string] path: String) -> String {
+    let target = match resolve(state, &path, false) {
+        Ok(target) => target,
+        Err(denied) => return denied,
+    };
+    match read_checked(&target, &path) {
+        Ok(content) => BASE64_STANDARD.encode(content),
+        Err(e) => e,
+    }
+}
+
+#[op2]
+#[string]
+/// 写入文件（需要可写的根目录）
+///
+/// # Arguments
+/// * `content` - 文本内容，`base64` 为 true 时为 Base64 编码的二进制内容
+///
+/// # Returns
+/// 成功返回 "OK"，失败返回错误信息
+pub fn op_sandbox_write(state: &mut OpState, #[string] path: String, #[string] content: String, base64: bool) -> String {
+    let target = match resolve(state, &path, true) {
+        Ok(target) => target,
+        Err(denied) => return denied,
+    };
+    let bytes = if base64 {
+        match BASE64_STANDARD.decode(content) {
+            Ok(bytes) => bytes,
+            Err(e) => return format!("Error: Invalid file content: {}", e),
+        }
+    } else {
+        content.into_bytes()
+    };
+    let written = open_checked(&target, &path, true).and_then(|mut file| {
+        file.set_len(0)
+            .and_then(|_| file.write_all(&bytes))
+            .map_err(|e| format!("Error: Failed to write file '{}': {}", path, e))
+    });
+    match written {
+        Ok(_) => "OK".to_string(),
+        Err(e) => e,
+    }
+}
+
+/// Deno.FileInfo 风格的文件信息
+fn file_info(metadata: &fs::Metadata, symlink: bool) -> serde_json::Value {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64);
+    serde_json::json!({
+        "isFile": metadata.is_file(),
+        "isDirectory": metadata.is_dir(),
+        "isSymlink": symlink,
+        "size": metadata.len(),
+        "mtime": mtime,
+    })
+}
+
+#[op2]
+#[string]
+/// 获取文件信息
+///
+/// # Returns
+/// JSON 对象字符串（isFile / isDirectory / isSymlink / size / mtime），失败时返回错误信息
+pub fn op_sandbox_stat(state: &mut OpState, #[string] path: String) -> String {
+    let target = match resolve(state, &path, false) {
+        Ok(target) => target,
+        Err(denied) => return denied,
+    };
+    let symlink = fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink());
+    let metadata = fs::metadata(&target);
+    if let Err(denied) = recheck(&target, &path) {
+        return denied;
+    }
+    match metadata {
+        Ok(metadata) => file_info(&metadata, symlink).to_string(),
+        Err(e) => format!("Error: Failed to stat '{}': {}", path, e),
+    }
+}
+
+#[op2]
+#[string]
+/// 列出目录内容
+///
+/// # Returns
+/// JSON 数组字符串，每项为 { name, isFile, isDirectory, isSymlink }，失败时返回错误信息
+pub fn op_sandbox_read_dir(state: &mut OpState, #[string] path: String) -> String {
+    let target = match resolve(state, &path, false) {
+        Ok(target) => target,
+        Err(denied) => return denied,
+    };
+    let entries = fs::read_dir(&target);
+    if let Err(denied) = recheck(&target, &path) {
+        return denied;
+    }
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(e) => return format!("Error: Failed to read directory '{}': {}", path, e),
+    };
+    let items: Vec<serde_json::Value> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_type = entry.file_type().ok()?;
+            Some(serde_json::json!({
+                "name": entry.file_name().to_string_lossy(),
+                "isFile": file_type.is_file(),
+                "isDirectory": file_type.is_dir(),
+                "isSymlink": file_type.is_symlink(),
+            }))
+        })
+        .collect();
+    serde_json::Value::Array(items).to_string()
+}
+
+extension!(
+    sandbox_fs_ops,
+    ops = [
+        op_sandbox_fs_enabled,
+        op_sandbox_read_text,
+        op_sandbox_read_bytes,
+        op_sandbox_write,
+        op_sandbox_stat,
+        op_sandbox_read_dir,
+    ],
+);
@@ -0,0 +1,212 @@
+// scheduler.rs - ContextPool 的任务调度：优先级、有界队列和按租户轮转
+//
+// 工作线程空闲时才从调度器取任务，排队中的任务按下面的顺序执行：
+// - 优先级高的先执行（priority，默认 0，可以为负），同一优先级内按租户轮转：
+//   每个租户（tenant，默认 None）各自排队，轮流取一个任务，某个租户的大批任务不会让其他租户一直等待
+// - 同一租户、同一优先级的任务按提交顺序执行
+// - max_queue 限制排队（尚未开始执行）的任务数：队列满时 on_full="block" 等待空位，
+//   on_full="raise" 立即抛出 PoolFull；asyncio 调用（call_async 等）不能阻塞事件循环，队列满时总是抛出
+//
+// 优先级是严格的：持续有高优先级任务时低优先级任务一直等待，适合"延迟敏感的签名调用优先于后台批量任务"。
+
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use crate::pool::{Next, SubmitError, Task, TaskSink, TaskSource};
+
+pyo3::create_exception!(
+    never_jscore,
+    PoolFull,
+    PyException,
+    "ContextPool 的任务队列已满（max_queue）"
+);
+
+/// 控制任务的优先级（调用可以指定的最高优先级）
+const CONTROL_PRIORITY: i32 = i32::MAX;
+
+/// 队列满时的处理方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnFull {
+    Block,
+    Raise,
+}
+
+impl OnFull {
+    pub fn from_py(value: &str) -> PyResult<Self> {
+        match value {
+            "block" => Ok(OnFull::Block),
+            "raise" => Ok(OnFull::Raise),
+            other => Err(PyValueError::new_err(format!(
+                "Invalid on_full '{}': expected 'block' or 'raise'",
+                other
+            ))),
+        }
+    }
+}
+
+/// 一个优先级的任务：按租户分别排队，轮流取出
+#[derive(Default)]
+struct Level {
+    /// 有排队任务的租户，按轮转顺序
+    order: VecDeque<String>,
+    tenants: HashMap<String, VecDeque<Task>>,
+}
+
+#[derive(Default)]
+struct State {
+    /// 优先级从高到低
+    levels: BTreeMap<Reverse<i32>, Level>,
+    queued: usize,
+    closed: bool,
+}
+
+impl State {
+    fn push(&mut self, priority: i32, tenant: &str, task: Task) {
+        let level = self.levels.entry(Reverse(priority)).or_default();
+        let queue = level.tenants.entry(tenant.to_string()).or_default();
+        if queue.is_empty() {
+            level.order.push_back(tenant.to_string());
+        }
+        queue.push_back(task);
+        self.queued += 1;
+    }
+
+    fn pop(&mut self) -> Option<Task> {
+        let mut entry = self.levels.first_entry()?;
+        let level = entry.get_mut();
+        let tenant = level.order.pop_front()?;
+        let queue = level.tenants.get_mut(&tenant)?;
+        let task = queue.pop_front()?;
+        if queue.is_empty() {
+            level.tenants.remove(&tenant);
+        } else {
+            level.order.push_back(tenant);
+        }
+        if level.order.is_empty() {
+            entry.remove();
+        }
+        self.queued -= 1;
+        Some(task)
+    }
+}
+
+/// ContextPool 的任务调度器（所有工作线程共享）
+pub struct Scheduler {
+    state: Mutex<State>,
+    /// 有新任务或调度器关闭
+    ready: Condvar,
+    /// 队列有空位或调度器关闭
+    space: Condvar,
+    max_queue: Option<usize>,
+    on_full: OnFull,
+}
+
+impl Scheduler {
+    pub fn new(max_queue: Option<usize>, on_full: OnFull) -> Self {
+        Scheduler {
+            state: Mutex::new(State::default()),
+            ready: Condvar::new(),
+            space: Condvar::new(),
+            max_queue,
+            on_full,
+        }
+    }
+
+    /// 提交任务；wait 为 false 时即使 on_full="block" 也不等待空位
+    pub fn submit(&self, priority: i32, tenant: Option<&str>, task: Task, wait: bool) -> Result<(), SubmitError> {
+        let mut state = self.state.lock().unwrap();
+        while let Some(max_queue) = self.max_queue.filter(|max| state.queued >= *max && !state.closed) {
+            if !wait || self.on_full == OnFull::Raise {
+                return Err(SubmitError::Full(format!(
+                    "ContextPool queue is full ({} tasks waiting, max_queue={})",
+                    state.queued, max_queue
+                )));
+            }
+            state = self.space.wait(state).unwrap();
+        }
+        if state.closed {
+            return Err(SubmitError::Closed);
+        }
+        state.push(priority, tenant.unwrap_or_default(), task);
+        drop(state);
+        self.ready.notify_one();
+        Ok(())
+    }
+
+    /// 提交控制任务（warmup() 等）：排在所有排队的任务之前，不受 max_queue 限制
+    ///
+    /// tasks 一次性全部入队，空闲的工作线程可以立即取走它们。
+    pub fn submit_control(&self, tasks: Vec<Task>) -> Result<(), SubmitError> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Err(SubmitError::Closed);
+        }
+        for task in tasks {
+            state.push(CONTROL_PRIORITY, "", task);
+        }
+        drop(state);
+        self.ready.notify_all();
+        Ok(())
+    }
+
+    /// 关闭调度器：拒绝新任务，已排队的任务仍然执行
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.ready.notify_all();
+        self.space.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    /// 排队中（尚未开始执行）的任务数
+    pub fn queued(&self) -> usize {
+        self.state.lock().unwrap().queued
+    }
+}
+
+impl TaskSource for Scheduler {
+    fn next_task(&self, timeout: Option<Duration>) -> Next {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(task) = state.pop() {
+                drop(state);
+                self.space.notify_one();
+                return Next::Task(task);
+            }
+            if state.closed {
+                return Next::Closed;
+            }
+            match timeout {
+                Some(timeout) => {
+                    let (guard, result) = self.ready.wait_timeout(state, timeout).unwrap();
+                    state = guard;
+                    if result.timed_out() && state.queued == 0 && !state.closed {
+                        return Next::Idle;
+                    }
+                }
+                None => state = self.ready.wait(state).unwrap(),
+            }
+        }
+    }
+}
+
+/// 以指定的优先级和租户向调度器提交任务
+pub struct Submitter<'a> {
+    pub scheduler: &'a Scheduler,
+    pub priority: i32,
+    pub tenant: Option<String>,
+    /// 队列满时是否允许等待（asyncio 调用为 false）
+    pub wait: bool,
+}
+
+impl TaskSink for Submitter<'_> {
+    fn submit(&self, task: Task) -> Result<(), SubmitError> {
+        self.scheduler.submit(self.priority, self.tenant.as_deref(), task, self.wait)
+    }
+}
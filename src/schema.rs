@@ -0,0 +1,161 @@
+// schema.rs - call(name, args, expect_schema={...})：在沙箱内检查结果的结构
+//
+// 目标站点改版后签名函数返回的结构变了（字段改名、数字变成字符串），错误往往在很深的 Python 代码中才暴露。
+// expect_schema 在 JS 侧、序列化之前（经过 result_filter 之后）检查结果，不符合时整个调用失败，
+// 抛出 SchemaMismatch，mismatches 属性列出所有不符合的位置（最多 MAX_MISMATCHES 条）。
+//
+// 规则是 JSON Schema 的常用子集：
+// - type：字符串或列表，"string" / "number" / "integer" / "boolean" / "null" / "array" / "object"
+// - enum / const：与 JSON 序列化后的值比较
+// - 字符串：minLength / maxLength / pattern；数字：minimum / maximum
+// - 数组：items / minItems / maxItems；对象：properties / required / additionalProperties（false 或规则）
+// 不认识的关键字忽略；undefined 与 null 相同（序列化后为 null），值为 undefined 的属性视为不存在。
+// 求值包装函数把不符合的位置经 op_store_schema_mismatch 交给 ResultStorage，不抛出 JS 异常。
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::convert::python_to_json;
+
+pyo3::create_exception!(
+    never_jscore,
+    SchemaMismatch,
+    PyValueError,
+    "call() 的结果不符合 expect_schema（mismatches 属性列出不符合的位置）"
+);
+
+/// 最多报告的不符合位置数
+const MAX_MISMATCHES: usize = 20;
+
+/// 求值包装函数使用的检查函数：checkSchema(value, schemaJson) 返回不符合的位置列表
+pub const CHECK_SCHEMA: &str = r#"
+(function() {
+    const MAX = __MAX_MISMATCHES__;
+    const identifier = /^[A-Za-z_$][\w$]*$/;
+    const kindOf = v => v === null || v === undefined ? 'null'
+        : Array.isArray(v) ? 'array'
+        : typeof v === 'number' && Number.isInteger(v) ? 'integer'
+        : typeof v;
+    const same = (a, b) => JSON.stringify(a) === JSON.stringify(b);
+    const preview = v => {
+        let text;
+        try { text = JSON.stringify(v); } catch (e) { text = undefined; }
+        text = text === undefined ? String(v) : text;
+        return text.length > 40 ? text.slice(0, 37) + '...' : text;
+    };
+    const child = (path, key) => typeof key === 'number' ? `${path}[${key}]`
+        : identifier.test(key) ? `${path}.${key}` : `${path}[${JSON.stringify(key)}]`;
+
+    function check(value, schema, path, out) {
+        if (out.length >= MAX || schema === true || schema === null || typeof schema !== 'object') return;
+        const kind = kindOf(value);
+        if (schema.type !== undefined) {
+            const types = Array.isArray(schema.type) ? schema.type : [schema.type];
+            if (!types.some(t => t === kind || (t === 'number' && kind === 'integer'))) {
+                out.push(`${path}: expected ${types.join(' | ')}, got ${kind === 'integer' ? 'number' : kind}`);
+                return;
+            }
+        }
+        if (Array.isArray(schema.enum) && !schema.enum.some(e => same(e, value))) {
+            out.push(`${path}: expected one of ${preview(schema.enum)}, got ${preview(value)}`);
+        }
+        if ('const' in schema && !same(schema.const, value)) {
+            out.push(`${path}: expected ${preview(schema.const)}, got ${preview(value)}`);
+        }
+        if (typeof value === 'string') {
+            if (schema.minLength !== undefined && value.length < schema.minLength) {
+                out.push(`${path}: expected at least ${schema.minLength} characters, got ${value.length}`);
+            }
+            if (schema.maxLength !== undefined && value.length > schema.maxLength) {
+                out.push(`${path}: expected at most ${schema.maxLength} characters, got ${value.length}`);
+            }
+            if (schema.pattern !== undefined && !new RegExp(schema.pattern).test(value)) {
+                out.push(`${path}: ${preview(value)} does not match pattern ${preview(schema.pattern)}`);
+            }
+        } else if (typeof value === 'number') {
+            if (schema.minimum !== undefined && value < schema.minimum) {
+                out.push(`${path}: expected >= ${schema.minimum}, got ${value}`);
+            }
+            if (schema.maximum !== undefined && value > schema.maximum) {
+                out.push(`${path}: expected <= ${schema.maximum}, got ${value}`);
+            }
+        } else if (kind === 'array') {
+            if (schema.minItems !== undefined && value.length < schema.minItems) {
+                out.push(`${path}: expected at least ${schema.minItems} items, got ${value.length}`);
+            }
+            if (schema.maxItems !== undefined && value.length > schema.maxItems) {
+                out.push(`${path}: expected at most ${schema.maxItems} items, got ${value.length}`);
+            }
+            if (schema.items !== undefined) {
+                value.forEach((item, index) => check(item, schema.items, child(path, index), out));
+            }
+        } else if (kind === 'object') {
+            const present = key => Object.prototype.hasOwnProperty.call(value, key) && value[key] !== undefined;
+            for (const key of Array.isArray(schema.required) ? schema.required : []) {
+                if (!present(key) && out.length < MAX) out.push(`${child(path, key)}: missing required property`);
+            }
+            const properties = schema.properties || {};
+            for (const key of Object.keys(properties)) {
+                if (present(key)) check(value[key], properties[key], child(path, key), out);
+            }
+            const extra = schema.additionalProperties;
+            if (extra !== undefined && extra !== true) {
+                for (const key of Object.keys(value)) {
+                    if (Object.prototype.hasOwnProperty.call(properties, key) || !present(key) || out.length >= MAX) continue;
+                    if (extra === false) out.push(`${child(path, key)}: unexpected property`);
+                    else check(value[key], extra, child(path, key), out);
+                }
+            }
+        }
+    }
+
+    return function checkSchema(value, schemaJson) {
+        const out = [];
+        check(value, JSON.parse(schemaJson), '$', out);
+        return out;
+    };
+})()
+"#;
+
+/// CHECK_SCHEMA 的源码（填入上限）
+pub fn check_schema_source() -> String {
+    CHECK_SCHEMA.replace("__MAX_MISMATCHES__", &MAX_MISMATCHES.to_string())
+}
+
+/// 把 expect_schema 转换为 JSON 文本（交给求值包装函数）
+pub fn from_py(schema: &Bound<'_, PyDict>) -> PyResult<String> {
+    let value = python_to_json(schema.as_any())?;
+    if !value.is_object() {
+        return Err(PyTypeError::new_err("expect_schema must be a dict"));
+    }
+    Ok(value.to_string())
+}
+
+/// 结果不符合 expect_schema 的错误（转换为 Python 的 SchemaMismatch）
+#[derive(Debug)]
+pub struct SchemaError(pub Vec<String>);
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "result does not match expect_schema: {}", self.0.join("; "))
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// 由包装函数报告的不符合位置（JSON 数组）创建错误
+pub fn mismatch_error(mismatches: &str) -> anyhow::Error {
+    let mismatches = serde_json::from_str(mismatches).unwrap_or_else(|_| vec![mismatches.to_string()]);
+    anyhow::Error::new(SchemaError(mismatches))
+}
+
+/// 创建 SchemaMismatch 异常，设置 mismatches 属性
+pub fn py_error(prefix: &str, error: &SchemaError) -> PyErr {
+    let err = SchemaMismatch::new_err(format!("{}: {}", prefix, error));
+    Python::attach(|py| {
+        // 异常实例不接受属性时（极少见）只返回原异常
+        let _ = err.value(py).setattr("mismatches", error.0.clone());
+    });
+    err
+}
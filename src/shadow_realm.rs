@@ -0,0 +1,248 @@
+// shadow_realm.rs - ShadowRealm 提案（new ShadowRealm()）
+//
+// ShadowRealm 由 V8 的 --harmony-shadow-realm 启用（runtime.rs 初始化平台时设置），
+// 构造时 V8 调用宿主回调创建新的 context：与 Realm 一样只包含 ECMAScript 内置对象。
+// deno_core 的宿主钩子（Promise 拒绝跟踪、动态 import）从当前 context 的嵌入数据槽读取状态，
+// 新 context 共享主 context 的这些槽，否则在 ShadowRealm 中拒绝一个 Promise 就会读到空指针。
+//
+// 新 context 与 Context 的 allow_dynamic_code 一致：Context 禁止从字符串生成代码时，
+// ShadowRealm.prototype.evaluate 和 ShadowRealm 中的 eval / new Function 同样抛出 EvalError（Realm 同样如此）。
+//
+// Python 通过 Context.create_shadow_realm() 在 ShadowRealm 中求值。
+// 与 JS 中一样，跨越边界的只有原始值（和包装后的函数），对象需要在 ShadowRealm 内序列化。
+
+use anyhow::{Result, anyhow};
+use deno_core::{JsRuntime, v8};
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::code_cache::exception_to_error;
+use crate::context::{Context, IsolateLocal, format_error};
+use crate::convert::json_str_to_python;
+use crate::quota::QuotaError;
+
+/// 启用 ShadowRealm 的 V8 参数
+pub const V8_FLAG: &str = "--harmony-shadow-realm";
+
+/// Context(allow_dynamic_code=False) 的标记（isolate 槽），Realm / ShadowRealm 新建的 context 据此禁止从字符串生成代码
+struct DynamicCodeDisabled;
+
+/// 为 runtime 的 isolate 设置创建 ShadowRealm context 的回调
+///
+/// 依赖 regexp_guard::install 保存在 isolate 中的主 context。
+pub fn install(runtime: &mut JsRuntime, allow_dynamic_code: bool) {
+    let isolate = runtime.v8_isolate();
+    if !allow_dynamic_code {
+        isolate.set_slot(DynamicCodeDisabled);
+    }
+    isolate.set_host_create_shadow_realm_context_callback(create_context);
+}
+
+/// 新建的 context 是否允许从字符串生成代码（与 Context 的 allow_dynamic_code 一致）
+pub(crate) fn dynamic_code_allowed(isolate: &v8::Isolate) -> bool {
+    isolate.get_slot::<DynamicCodeDisabled>().is_none()
+}
+
+/// 按 Context 的 allow_dynamic_code 设置新建的 context（Realm / ShadowRealm 使用）
+pub(crate) fn inherit_code_generation(isolate: &v8::Isolate, context: v8::Local<v8::Context>) {
+    if !dynamic_code_allowed(isolate) {
+        context.set_allow_generation_from_strings(false);
+    }
+}
+
+/// new ShadowRealm() 的宿主回调：创建新的 context 并共享 deno_core 的状态槽
+fn create_context<'s>(scope: &mut v8::PinScope<'s, '_>) -> Option<v8::Local<'s, v8::Context>> {
+    // 发起者可能是没有状态槽的 Realm context，因此总是从主 context 复制
+    let main = crate::regexp_guard::probe_context(scope)?;
+    let main = v8::Local::new(scope, main);
+    let context = v8::Context::new(scope, Default::default());
+    inherit_code_generation(scope, context);
+    for slot in [deno_core::CONTEXT_STATE_SLOT_INDEX, deno_core::MODULE_MAP_SLOT_INDEX] {
+        // SAFETY: 槽中是 deno_core 持有的 Rc 裸指针，读取时才增加引用计数；
+        // ShadowRealm 的代码只能在主 context 存活期间执行
+        unsafe {
+            context.set_aligned_pointer_in_embedder_data(slot, main.get_aligned_pointer_from_embedder_data(slot));
+        }
+    }
+
+    // init(icu=False)：与 Context 一样删除 Intl
+    if !crate::runtime::runtime_config().icu {
+        let scope = &mut v8::ContextScope::new(scope, context);
+        let source = v8::String::new(scope, crate::runtime::HIDE_INTL)?;
+        v8::Script::compile(scope, source, None)?.run(scope)?;
+    }
+    Some(context)
+}
+
+/// 在主 context 中创建 ShadowRealm 实例
+fn create_realm(runtime: &mut JsRuntime) -> Result<v8::Global<v8::Object>> {
+    let value = runtime
+        .execute_script("<shadow_realm>", "new ShadowRealm()")
+        .map_err(|e| anyhow!("{}", format_error(e.into())))?;
+    deno_core::scope!(scope, runtime);
+    let value = v8::Local::new(scope, value);
+    let realm = v8::Local::<v8::Object>::try_from(value).map_err(|_| anyhow!("ShadowRealm is not an object"))?;
+    Ok(v8::Global::new(scope, realm))
+}
+
+/// 调用 ShadowRealm.prototype.evaluate，返回结果的 JSON 字符串
+fn evaluate(runtime: &mut JsRuntime, realm: &v8::Global<v8::Object>, code: &str) -> Result<String> {
+    deno_core::scope!(scope, runtime);
+    v8::tc_scope!(let tc_scope, scope);
+
+    let realm = v8::Local::new(tc_scope, realm);
+    let key = v8::String::new(tc_scope, "evaluate").ok_or_else(|| anyhow!("Failed to create string"))?;
+    let method = realm
+        .get(tc_scope, key.into())
+        .and_then(|value| v8::Local::<v8::Function>::try_from(value).ok())
+        .ok_or_else(|| anyhow!("ShadowRealm.prototype.evaluate is not a function"))?;
+    let source = v8::String::new(tc_scope, code).ok_or_else(|| anyhow!("Code is too large"))?;
+    let value = method
+        .call(tc_scope, realm.into(), &[source.into()])
+        .ok_or_else(|| exception_to_error(tc_scope))?;
+    tc_scope.perform_microtask_checkpoint();
+
+    if value.is_undefined() {
+        return Ok("null".to_string());
+    }
+
+    // 包装后的函数、Symbol、BigInt 无法 JSON 序列化，转换为字符串
+    let json = v8::json::stringify(tc_scope, value)
+        .map(|json| json.to_rust_string_lossy(tc_scope))
+        .filter(|json| json != "undefined");
+    match json {
+        Some(json) => Ok(json),
+        None => {
+            tc_scope.reset();
+            let text = value.to_rust_string_lossy(tc_scope);
+            Ok(serde_json::to_string(&text)?)
+        }
+    }
+}
+
+/// Context 中的一个 ShadowRealm
+///
+/// 由 `Context.create_shadow_realm()` 创建，等同于 JS 中的 `new ShadowRealm()`：
+/// 独立的全局作用域和内置对象，只有 ECMAScript 内置对象，没有扩展 API 和事件循环。
+///
+/// 与 Realm 不同，ShadowRealm 是 JS 标准 API，JS 代码自己也可以创建；
+/// 边界两侧只能传递原始值和函数，evaluate() 返回对象时抛出 TypeError。
+///
+/// Example:
+///     ```python
+///     ctx = never_jscore.Context()
+///     with ctx.create_shadow_realm() as realm:
+///         realm.evaluate("var secret = 42")
+///         realm.evaluate("secret + 1")  # 43
+///         realm.evaluate("JSON.stringify({ a: secret })")  # '{"a":42}'
+///     ```
+#[pyclass(unsendable)]
+pub struct ShadowRealm {
+    // 必须先于 context 释放：Global 句柄依赖 isolate 存活；句柄表（handles()）持有弱引用
+    realm: Rc<RefCell<Option<v8::Global<v8::Object>>>>,
+    context: Py<Context>,
+    fork_generation: usize,
+}
+
+impl ShadowRealm {
+    /// 在 Context 的主 context 中创建 ShadowRealm
+    pub(crate) fn new(py: Python<'_>, context: Py<Context>) -> PyResult<Self> {
+        let realm = {
+            let ctx = context.borrow(py);
+            ctx.check_fork()?;
+            ctx.reserve_handle()
+                .map_err(|e| crate::quota::py_error("Failed to create shadow realm", e))?;
+            let realm = ctx
+                .with_runtime(create_realm)
+                .map_err(|e| PyException::new_err(format!("Failed to create shadow realm: {}", format_error(e))))?;
+            let realm = Rc::new(RefCell::new(Some(realm)));
+            ctx.handle_table().register_slot("shadow_realm", "<shadow_realm>", &realm);
+            realm
+        };
+        Ok(ShadowRealm {
+            realm,
+            context,
+            fork_generation: crate::fork::generation(),
+        })
+    }
+
+    /// 释放句柄；fork 前创建的 ShadowRealm 在子进程中不能访问 isolate，直接泄漏
+    fn release(&self, realm: Option<v8::Global<v8::Object>>) {
+        if crate::fork::is_inherited(self.fork_generation) {
+            std::mem::forget(realm);
+        }
+    }
+}
+
+#[pymethods]
+impl ShadowRealm {
+    /// 在 ShadowRealm 中执行代码并返回结果（ShadowRealm.prototype.evaluate）
+    ///
+    /// 代码作为全局脚本执行，var / function 声明保留在 ShadowRealm 中。
+    ///
+    /// Args:
+    ///     code: JavaScript 代码
+    ///
+    /// Returns:
+    ///     原始值；函数返回其字符串形式，对象会抛出 TypeError（需要在 ShadowRealm 内 JSON.stringify）
+    #[pyo3(signature = (code))]
+    pub fn evaluate<'py>(&self, py: Python<'py>, code: String) -> PyResult<Bound<'py, PyAny>> {
+        let realm = self.realm.borrow();
+        let realm = realm.as_ref().ok_or_else(|| PyException::new_err("Evaluate error: ShadowRealm is closed"))?;
+
+        let context = self.context.borrow(py);
+        context.check_fork()?;
+        let execution = context.begin_execution("evaluate", "<shadow_realm>", &code);
+        let realm = IsolateLocal::new(realm);
+        let result = context.without_gil(py, |ctx| {
+            let realm = realm.into_inner();
+            // ShadowRealm 的调用同样计入 Context 的配额
+            ctx.with_quota(|| {
+                let json = ctx
+                    .with_runtime(|runtime| evaluate(runtime, realm, &code))
+                    .map_err(|e| if e.is::<QuotaError>() { e } else { anyhow!("{}", format_error(e)) })?;
+                ctx.check_result_quota(&json)?;
+                Ok(json)
+            })
+        });
+        let result_json = context
+            .end_execution(execution, result)
+            .map_err(|e| crate::quota::py_error("Evaluate error", e))?;
+        json_str_to_python(py, &result_json)
+    }
+
+    /// 释放 ShadowRealm（重复调用是安全的），之后的调用会抛出异常
+    fn close(&self) {
+        let realm = self.realm.borrow_mut().take();
+        self.release(realm);
+    }
+
+    /// ShadowRealm 是否已关闭
+    #[getter]
+    fn closed(&self) -> bool {
+        self.realm.borrow().is_none()
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __exit__(
+        &self,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> bool {
+        self.close();
+        false
+    }
+}
+
+impl Drop for ShadowRealm {
+    fn drop(&mut self) {
+        let realm = self.realm.borrow_mut().take();
+        self.release(realm);
+    }
+}
@@ -0,0 +1,98 @@
+// shared_buffer.rs - SharedBuffer：Python 与 Context（以及多个 Context 之间）共享的内存
+//
+// 内存由 Rust 分配，不属于任何 isolate：Context(shared_buffers={...}) / ctx.share_buffer() 把它定义为
+// 全局 SharedArrayBuffer，多个 Context（包括 ContextPool 的每个工作线程）看到的是同一块内存，
+// Python 通过 buffer.view() 得到的 memoryview 直接读写这块内存，不经过 JS / JSON。
+//
+// 内存在 SharedBuffer 和所有使用它的 SharedArrayBuffer 都被回收之后释放。
+// 读写之间没有任何同步：JS 之间用 Atomics，与 Python 之间需要调用方自行协调（如用 Atomics 读写的标志位）。
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyMemoryView};
+
+use crate::wasm_memory::{MemoryViews, PinnedStore};
+
+/// Python 与 JavaScript 共享的内存
+///
+/// Example:
+///     ```python
+///     buf = never_jscore.SharedBuffer(1024)
+///     pool = never_jscore.ContextPool(init_code, shared_buffers={"shared": buf})
+///     mem = buf.view()
+///     mem[0:4] = b"\x01\x02\x03\x04"
+///     pool.evaluate("new Uint8Array(shared)[3]")  # 4，每个工作线程都看到同一块内存
+///     ```
+#[pyclass(module = "never_jscore")]
+pub struct SharedBuffer {
+    store: PinnedStore,
+    views: MemoryViews,
+}
+
+impl SharedBuffer {
+    pub(crate) fn store(&self) -> PinnedStore {
+        self.store.clone()
+    }
+}
+
+#[pymethods]
+impl SharedBuffer {
+    /// 分配 size 字节的共享内存（初始为 0）
+    ///
+    /// Args:
+    ///     size: 字节数
+    ///
+    /// Raises:
+    ///     ValueError: size 为 0
+    #[new]
+    fn py_new(size: usize) -> PyResult<Self> {
+        if size == 0 {
+            return Err(PyValueError::new_err("SharedBuffer size must be at least 1 byte"));
+        }
+        Ok(SharedBuffer {
+            store: PinnedStore::new_shared(size),
+            views: MemoryViews::default(),
+        })
+    }
+
+    /// 返回直接读写共享内存的可写 memoryview
+    fn view<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyMemoryView>> {
+        self.views.view(py, self.store.clone())
+    }
+
+    /// 字节数
+    #[getter]
+    fn size(&self) -> usize {
+        self.store.byte_length()
+    }
+
+    fn __len__(&self) -> usize {
+        self.store.byte_length()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SharedBuffer(size={})", self.store.byte_length())
+    }
+}
+
+impl Drop for SharedBuffer {
+    fn drop(&mut self) {
+        // view() 返回的视图随 SharedBuffer 一起失效（仍被引用时内存保留到进程退出）
+        Python::attach(|py| self.views.release_all(py));
+    }
+}
+
+/// 解析 shared_buffers={"name": SharedBuffer} 参数
+pub(crate) fn from_py(buffers: Option<&Bound<'_, PyDict>>) -> PyResult<Vec<(String, PinnedStore)>> {
+    let Some(buffers) = buffers else {
+        return Ok(Vec::new());
+    };
+    buffers
+        .iter()
+        .map(|(name, buffer)| {
+            let name: String = name.extract()?;
+            let buffer = buffer.cast::<SharedBuffer>()?;
+            Ok((name, buffer.borrow().store()))
+        })
+        .collect()
+}
@@ -0,0 +1,178 @@
+// slow_script.rs - 慢脚本回调（ctx.on_slow_script(threshold_ms, callback)）
+//
+// 一次调用的墙钟时间超过阈值时调用回调，但不终止执行，服务可以记录慢调用而不让请求失败。
+// 看门狗到达阈值时请求中断 isolate（与 timeout_ms 相同的机制，见 watchdog.rs），
+// 中断回调在执行线程上读取当前 JS 调用栈并调用 Python 回调；阈值小于 timeout_ms 时回调先于超时终止运行。
+//
+// 事件循环等待定时器 / Promise 时 JS 不在运行，中断要等 JS 再次运行时才处理；
+// 到调用结束时仍未报告的慢调用在结束时报告，此时 stack 为 None。
+// 回调运行时 JS 处于暂停状态，回调中不能再使用同一个 Context 执行代码。
+
+use deno_core::{v8, JsRuntime};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::cell::{Cell, RefCell};
+use std::ffi::c_void;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::py_functions::{current_stack, stack_to_python, StackFrame};
+
+struct Hook {
+    threshold: Duration,
+    callback: Py<PyAny>,
+}
+
+/// 正在执行的代码
+struct Current {
+    kind: &'static str,
+    filename: String,
+    code: String,
+}
+
+/// Context 的慢脚本回调（与 isolate 的 slot 共享）
+#[derive(Default)]
+pub struct SlowScript {
+    hook: RefCell<Option<Hook>>,
+    current: RefCell<Option<Current>>,
+    /// 当前调用的开始时间（未设置回调时为 None）
+    started: Cell<Option<Instant>>,
+    /// 当前调用已经报告过
+    reported: Cell<bool>,
+}
+
+impl SlowScript {
+    /// 创建并保存到 runtime 的 isolate 中，供中断回调使用
+    pub fn install(runtime: &mut JsRuntime) -> Rc<Self> {
+        let slow_script = Rc::new(Self::default());
+        runtime.v8_isolate().set_slot(slow_script.clone());
+        slow_script
+    }
+
+    /// 设置回调（callback 为 None 时清除）
+    pub fn set(&self, threshold: Duration, callback: Option<Py<PyAny>>) {
+        *self.hook.borrow_mut() = callback.map(|callback| Hook { threshold, callback });
+    }
+
+    /// 取出阈值和回调（recycle_after 重建 isolate 时转移到新的 Context）
+    pub fn take(&self) -> Option<(Duration, Py<PyAny>)> {
+        self.hook.borrow_mut().take().map(|hook| (hook.threshold, hook.callback))
+    }
+
+    /// 报告阈值（未设置回调时为 None）
+    pub fn threshold(&self) -> Option<Duration> {
+        self.hook.borrow().as_ref().map(|hook| hook.threshold)
+    }
+
+    /// 记录即将执行的代码（报告时一并传给回调）
+    pub fn set_current(&self, kind: &'static str, filename: &str, code: &str) {
+        if self.hook.borrow().is_some() {
+            *self.current.borrow_mut() = Some(Current {
+                kind,
+                filename: filename.to_string(),
+                code: crate::history::preview(code),
+            });
+        }
+    }
+
+    /// 一次调用开始
+    pub fn begin(&self) {
+        self.started.set(self.hook.borrow().is_some().then(Instant::now));
+        self.reported.set(false);
+    }
+
+    /// 调用结束：超过阈值但中断未能运行时在这里报告（没有调用栈）
+    pub fn finish(&self) {
+        let Some(started) = self.started.take() else {
+            return;
+        };
+        if !self.reported.get() && self.threshold().is_some_and(|threshold| started.elapsed() >= threshold) {
+            self.report(started, None);
+        }
+        self.current.borrow_mut().take();
+    }
+
+    fn report(&self, started: Instant, stack: Option<Vec<StackFrame>>) {
+        self.reported.set(true);
+        Python::attach(|py| {
+            let Some((threshold, callback)) = self
+                .hook
+                .borrow()
+                .as_ref()
+                .map(|hook| (hook.threshold, hook.callback.clone_ref(py)))
+            else {
+                return;
+            };
+            let result = self.to_python(py, started, threshold, stack).and_then(|info| callback.call1(py, (info,)));
+            if let Err(e) = result {
+                e.write_unraisable(py, Some(callback.bind(py)));
+            }
+        });
+    }
+
+    fn to_python<'py>(
+        &self,
+        py: Python<'py>,
+        started: Instant,
+        threshold: Duration,
+        stack: Option<Vec<StackFrame>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let info = PyDict::new(py);
+        info.set_item("elapsed_ms", started.elapsed().as_secs_f64() * 1000.0)?;
+        info.set_item("threshold_ms", threshold.as_millis() as u64)?;
+        let current = self.current.borrow();
+        info.set_item("kind", current.as_ref().map(|c| c.kind))?;
+        info.set_item("filename", current.as_ref().map(|c| c.filename.as_str()))?;
+        info.set_item("code", current.as_ref().map(|c| c.code.as_str()))?;
+        info.set_item("stack", stack.map(|stack| stack_to_python(py, stack)).transpose()?)?;
+        Ok(info)
+    }
+}
+
+/// 校验 on_slow_script() 的参数
+pub fn validate(threshold_ms: u64, callback: Option<Bound<'_, PyAny>>) -> PyResult<(Duration, Option<Py<PyAny>>)> {
+    if threshold_ms == 0 {
+        return Err(PyValueError::new_err("threshold_ms must be at least 1"));
+    }
+    if callback.as_ref().is_some_and(|callback| !callback.is_callable()) {
+        return Err(PyTypeError::new_err("callback must be callable or None"));
+    }
+    Ok((Duration::from_millis(threshold_ms), callback.map(Bound::unbind)))
+}
+
+/// 请求中断正在执行的 isolate 并报告慢调用（由看门狗线程在到达阈值时调用）
+pub fn notify(handle: &v8::IsolateHandle, active: &Arc<AtomicBool>) {
+    let data = Box::into_raw(Box::new(active.clone()));
+    if !handle.request_interrupt(slow_interrupt, data as *mut c_void) {
+        drop(unsafe { Box::from_raw(data) });
+    }
+}
+
+unsafe extern "C" fn slow_interrupt(isolate: &mut v8::Isolate, data: *mut c_void) {
+    let active = unsafe { Box::from_raw(data as *mut Arc<AtomicBool>) };
+    if !active.load(Ordering::Acquire) {
+        return;
+    }
+    let Some(slow_script) = isolate.get_slot::<Rc<SlowScript>>().cloned() else {
+        return;
+    };
+    let Some(started) = slow_script.started.get().filter(|_| !slow_script.reported.get()) else {
+        return;
+    };
+    let stack = capture_stack(isolate);
+    slow_script.report(started, Some(stack));
+}
+
+/// 中断时的 JS 调用栈
+fn capture_stack(isolate: &mut v8::Isolate) -> Vec<StackFrame> {
+    let Some(context) = crate::regexp_guard::probe_context(isolate) else {
+        return Vec::new();
+    };
+    v8::scope!(scope, isolate);
+    let context = v8::Local::new(scope, context);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    current_stack(scope)
+}
@@ -0,0 +1,259 @@
+// snapshot.rs - V8 startup snapshot support
+//
+// build_snapshot() 在一个专用的快照 runtime 中执行 polyfill 和用户代码，
+// 然后把整个 V8 堆序列化为 bytes。Context(snapshot=...) 直接从这份堆启动，
+// 省去重复解析/执行大型 JS 库的时间。
+//
+// 此外，build.rs 把只包含扩展和 polyfill 的内置快照嵌入扩展（生成方式见 build.rs），
+// 默认 Context 都从它启动，不再重复解析 polyfill。
+
+use anyhow::{Result, anyhow};
+use deno_core::{JsRuntimeForSnapshot, RuntimeOptions};
+use once_cell::sync::Lazy;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use crate::context::{create_extensions, format_error, load_polyfill};
+use crate::runtime::{ensure_v8_initialized, run_with_tokio};
+use crate::storage::ResultStorage;
+
+/// 快照文件头魔数（版本号在最后一个字节）
+const SNAPSHOT_MAGIC: &[u8; 8] = b"NJSSNAP\x01";
+/// 文件头长度：魔数 + 1 字节标志位
+const HEADER_LEN: usize = SNAPSHOT_MAGIC.len() + 1;
+
+const FLAG_EXTENSIONS: u8 = 0b01;
+const FLAG_LOGGING: u8 = 0b10;
+
+/// 已加载的快照数据
+///
+/// V8 要求启动快照在整个 isolate 生命周期内有效，deno_core 因此要求 `&'static [u8]`。
+/// 相同内容的快照只会被 leak 一次，重复创建 Context 不会造成内存增长。
+static LOADED_SNAPSHOTS: Lazy<Mutex<Vec<&'static [u8]>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// 内置快照（只包含扩展和 polyfill，build_snapshot() 的格式，按日志开关区分）
+///
+/// 默认的 Context 从这里启动，避免每个 Context 重复解析执行 3000+ 行的 polyfill。
+/// 没有与当前源码对应的快照文件时为空，Context 回退到直接执行 polyfill。
+static BUILTIN_SNAPSHOT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/builtin_snapshot.bin"));
+static BUILTIN_SNAPSHOT_LOGGING: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/builtin_snapshot_logging.bin"));
+
+/// 当前源码的指纹（build.rs 计算），内置快照文件按它命名
+pub const SOURCE_HASH: &str = env!("NEVER_JSCORE_SOURCE_HASH");
+
+/// 解析后的快照信息
+pub struct SnapshotInfo {
+    /// 构建快照时是否启用了扩展（加载时必须一致，否则 ops 不匹配）
+    pub enable_extensions: bool,
+    /// 构建快照时 polyfill 使用的日志开关
+    pub enable_logging: bool,
+    /// 去掉文件头后的 V8 快照数据
+    pub blob: &'static [u8],
+}
+
+/// 构建启动快照
+///
+/// 使用与 Context 完全相同的扩展列表创建快照 runtime，依次执行 polyfill 和用户代码，
+/// 运行事件循环直到所有异步初始化完成后再序列化堆。
+pub fn create_snapshot(
+    presets: &[(String, &str)],
+    code: &str,
+    enable_extensions: bool,
+    enable_logging: bool,
+) -> Result<Vec<u8>> {
+    let mut scripts: Vec<(&str, &str)> = presets.iter().map(|(name, source)| (name.as_str(), *source)).collect();
+    scripts.push(("<snapshot>", code));
+    let blob = create_snapshot_blob_from_scripts(&scripts, enable_extensions, enable_logging)?;
+    Ok(with_header(&blob, enable_extensions, enable_logging))
+}
+
+/// 给原始 V8 快照加上文件头（load_snapshot() 解析）
+pub(crate) fn with_header(blob: &[u8], enable_extensions: bool, enable_logging: bool) -> Vec<u8> {
+    let mut flags = 0u8;
+    if enable_extensions {
+        flags |= FLAG_EXTENSIONS;
+    }
+    if enable_logging {
+        flags |= FLAG_LOGGING;
+    }
+
+    let mut data = Vec::with_capacity(HEADER_LEN + blob.len());
+    data.extend_from_slice(SNAPSHOT_MAGIC);
+    data.push(flags);
+    data.extend_from_slice(blob);
+    data
+}
+
+/// 构建不带文件头的原始 V8 快照
+pub(crate) fn create_snapshot_blob(code: &str, enable_extensions: bool, enable_logging: bool) -> Result<Box<[u8]>> {
+    create_snapshot_blob_from_scripts(&[("<snapshot>", code)], enable_extensions, enable_logging)
+}
+
+/// 依次执行多个具名脚本后构建原始 V8 快照（pickle 按原顺序重放 Context 执行过的脚本）
+pub(crate) fn create_snapshot_blob_from_scripts(
+    scripts: &[(&str, &str)],
+    enable_extensions: bool,
+    enable_logging: bool,
+) -> Result<Box<[u8]>> {
+    // 必须先以普通模式初始化 V8 平台，
+    // 否则快照 runtime 会以 --predictable 模式初始化整个进程
+    ensure_v8_initialized()?;
+    let _isolate = crate::runtime::IsolateGuard::acquire()?;
+
+    let storage = Rc::new(ResultStorage::new());
+    let mut runtime = JsRuntimeForSnapshot::try_new(RuntimeOptions {
+        extensions: create_extensions(storage, enable_extensions),
+        ..Default::default()
+    })
+    .map_err(|e| anyhow!("Failed to create snapshot runtime: {}", e))?;
+
+    if enable_extensions {
+        load_polyfill(&mut runtime, enable_logging)?;
+    }
+
+    for (name, code) in scripts {
+        runtime
+            .execute_script(name.to_string(), code.to_string())
+            .map_err(|e| anyhow!("{}", format_error(e.into())))?;
+    }
+
+    // 等待初始化代码中的 Promise / 定时器完成，快照中不能包含挂起的 op
+    run_with_tokio(async { runtime.run_event_loop(Default::default()).await })
+        .map_err(|e| anyhow!("{}", format_error(e.into())))?;
+
+    Ok(runtime.snapshot())
+}
+
+/// 获取编译时嵌入的内置快照
+///
+/// 内置快照只在启用扩展时有意义：它包含全部扩展 JS 和 polyfill 执行后的堆。
+/// 快照按默认的 V8 参数生成，init() 修改了 V8 参数（jitless、set_v8_flags 等）时不能使用。
+/// 没有嵌入快照或不能使用时返回 None，调用方应回退到直接执行 polyfill。
+pub fn builtin_snapshot(enable_logging: bool) -> Option<&'static [u8]> {
+    let data = if enable_logging { BUILTIN_SNAPSHOT_LOGGING } else { BUILTIN_SNAPSHOT };
+    if !crate::runtime::runtime_config().default_v8_flags() {
+        return None;
+    }
+    let (enable_extensions, logging, blob) = parse_header(data).ok()?;
+    (enable_extensions && logging == enable_logging).then_some(blob)
+}
+
+/// 校验并加载快照数据
+///
+/// 返回的 blob 具有 `'static` 生命周期，可以直接传给 RuntimeOptions::startup_snapshot。
+pub fn load_snapshot(data: &[u8]) -> Result<SnapshotInfo> {
+    let (enable_extensions, enable_logging, blob) = parse_header(data)?;
+    Ok(SnapshotInfo {
+        enable_extensions,
+        enable_logging,
+        blob: intern_blob(blob),
+    })
+}
+
+/// 校验文件头，返回 (enable_extensions, enable_logging, 去掉文件头后的 V8 快照数据)
+fn parse_header(data: &[u8]) -> Result<(bool, bool, &[u8])> {
+    if data.len() <= HEADER_LEN || &data[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+        return Err(anyhow!("Invalid snapshot: data was not created by never_jscore.build_snapshot()"));
+    }
+
+    let flags = data[SNAPSHOT_MAGIC.len()];
+
+    Ok((flags & FLAG_EXTENSIONS != 0, flags & FLAG_LOGGING != 0, &data[HEADER_LEN..]))
+}
+
+/// 可以释放的快照数据
+///
+/// 交给 V8 的快照必须是 `&'static [u8]`，这里把数据 leak 出来，最后一个持有者销毁时再释放。
+/// 使用它的 Context 通过 [`Context::keep_snapshot_alive`](crate::context::Context::keep_snapshot_alive)
+/// 持有一份，isolate 销毁之后才会释放；配置变化后不再需要的快照（如 configure_eval() 的隔离求值快照）因此不会一直占用内存。
+pub(crate) struct OwnedBlob {
+    blob: &'static [u8],
+}
+
+impl OwnedBlob {
+    pub fn new(data: Vec<u8>) -> Arc<Self> {
+        Arc::new(OwnedBlob { blob: Box::leak(data.into_boxed_slice()) })
+    }
+
+    /// 快照数据，只能交给持有本对象的 Context 使用
+    pub fn blob(&self) -> &'static [u8] {
+        self.blob
+    }
+}
+
+impl Drop for OwnedBlob {
+    fn drop(&mut self) {
+        // SAFETY: blob 由 new() 中的 Box::leak 得到，使用它的 isolate 都已经销毁（各自持有一份 Arc）
+        unsafe { drop(Box::from_raw(self.blob as *const [u8] as *mut [u8])) };
+    }
+}
+
+/// 将原始快照数据转换为 `'static`（相同内容只 leak 一次）
+pub(crate) fn intern_blob(blob: &[u8]) -> &'static [u8] {
+    let mut loaded = LOADED_SNAPSHOTS.lock().unwrap();
+    match loaded.iter().find(|existing| **existing == blob) {
+        Some(existing) => existing,
+        None => {
+            let leaked: &'static [u8] = Box::leak(blob.to_vec().into_boxed_slice());
+            loaded.push(leaked);
+            leaked
+        }
+    }
+}
+
+/// 构建启动快照
+///
+/// 执行 polyfill 和给定的初始化代码，将得到的 V8 堆序列化为 bytes。
+/// 之后可以用 `Context(snapshot=data)` 直接从该堆启动，
+/// 重复加载大型 JS 库时启动时间从几十毫秒降低到接近零。
+///
+/// Args:
+///     code: 初始化代码（通常是要预加载的 JS 库）
+///     enable_extensions: 是否启用扩展，必须与加载时的 Context 参数一致（默认 True）
+///     enable_logging: polyfill 的日志开关（默认 False）
+///     preset: 在 code 之前加载的内置库（可选），与 Context.compile(preset=...) 相同
+///
+/// Returns:
+///     bytes: 快照数据，可保存到文件后在其他进程中复用（要求相同版本的 never_jscore）
+///
+/// Example:
+///     ```python
+///     import never_jscore
+///
+///     snapshot = never_jscore.build_snapshot(open("crypto-lib.js").read())
+///
+///     # 从快照启动，无需重新执行 crypto-lib.js
+///     ctx = never_jscore.Context(snapshot=snapshot)
+///     result = ctx.call("encrypt", ["data"])
+///
+///     # 内置的 crypto-js 同样可以放进快照
+///     snapshot = never_jscore.build_snapshot("", preset=["crypto-js"])
+///     ```
+#[pyfunction]
+#[pyo3(signature = (code, enable_extensions=true, enable_logging=false, preset=None))]
+pub fn build_snapshot<'py>(
+    py: Python<'py>,
+    code: String,
+    enable_extensions: bool,
+    enable_logging: bool,
+    preset: Option<Vec<String>>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let presets = crate::presets::resolve(preset)?;
+    let data = create_snapshot(&presets, &code, enable_extensions, enable_logging)
+        .map_err(|e| PyException::new_err(format!("Snapshot error: {}", e)))?;
+    Ok(PyBytes::new(py, &data))
+}
+
+/// 将 Python 传入的快照 bytes 转换为 SnapshotInfo
+pub fn snapshot_from_py(data: &[u8]) -> PyResult<SnapshotInfo> {
+    load_snapshot(data).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// 将快照 bytes 复制为可释放的 [`OwnedBlob`]（不经过 intern_blob，Context 销毁后释放）
+pub(crate) fn owned_snapshot_from_py(data: &[u8]) -> PyResult<Arc<OwnedBlob>> {
+    let (_, _, blob) = parse_header(data).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(OwnedBlob::new(blob.to_vec()))
+}
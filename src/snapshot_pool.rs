@@ -0,0 +1,153 @@
+// snapshot_pool.rs - SnapshotPool: 每次请求一个从预热快照启动的全新 Context
+//
+// ContextPool 复用长期存活的 Context，速度快但请求之间会共享全局状态；
+// 每次请求 new Context() + 执行初始化代码则隔离彻底但太慢。
+// SnapshotPool 在创建时执行一次 init_code 并做成启动快照，
+// 之后每次 checkout() 都从快照反序列化出一个全新的 Context，兼顾隔离和速度。
+//
+// 快照数据是进程级的（'static），SnapshotPool 本身可以在线程之间共享，
+// checkout() 在调用线程上创建 Context（Context 仍然只能在该线程使用）。
+
+use pyo3::exceptions::{PyException, PyRuntimeError};
+use pyo3::prelude::*;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::context::{Context, ContextOptions};
+
+/// 从预热快照创建全新 Context 的工厂
+///
+/// 创建时执行一次 init_code 并生成启动快照；之后每次 `checkout()` 得到一个
+/// 独立的全新 Context，其中已经加载好 init_code，不会读到其他请求留下的任何状态。
+///
+/// Example:
+///     ```python
+///     import never_jscore
+///
+///     pool = never_jscore.SnapshotPool(open("sign.js").read())
+///
+///     @app.route("/sign")
+///     def sign():
+///         # 每个请求一个干净的 Context，同时省去重新执行 sign.js 的时间
+///         return pool.call("sign", [request.args["data"]])
+///
+///     # 需要多次调用时手动取出 Context
+///     with pool.checkout() as ctx:
+///         ctx.call("init", [config])
+///         result = ctx.call("sign", ["data"])
+///     ```
+#[pyclass]
+pub struct SnapshotPool {
+    options: ContextOptions,
+    snapshot_size: usize,
+    checkouts: AtomicUsize,
+}
+
+impl SnapshotPool {
+    /// 创建全新的 Context（在调用线程上）
+    fn new_context(&self) -> PyResult<Context> {
+        self.checkouts.fetch_add(1, Ordering::Relaxed);
+        Context::new(self.options.clone())
+    }
+}
+
+#[pymethods]
+impl SnapshotPool {
+    /// 执行初始化代码并生成快照
+    ///
+    /// Args:
+    ///     init_code: 初始化代码（通常是要加载的 JS 库），执行后的堆会被做成快照
+    ///     freeze_intrinsics: 是否在初始化代码执行后冻结内置对象（见 Context.freeze_intrinsics()），
+    ///         冻结状态保存在快照中，每个取出的 Context 都无法修改内置原型
+    ///     enable_extensions / enable_logging / random_seed / code_cache_dir / initial_heap_mb / max_heap_mb / permissions:
+    ///         与 Context 构造函数含义相同，应用于每个取出的 Context（初始化代码构建快照时没有网络和文件权限）
+    ///
+    /// Raises:
+    ///     ValueError: permissions 无效
+    ///     Exception: 初始化代码执行失败
+    #[new]
+    #[pyo3(signature = (init_code, enable_extensions=true, enable_logging=false, random_seed=None, code_cache_dir=None, initial_heap_mb=None, max_heap_mb=None, freeze_intrinsics=false, permissions=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        py: Python<'_>,
+        init_code: String,
+        enable_extensions: bool,
+        enable_logging: bool,
+        random_seed: Option<u32>,
+        code_cache_dir: Option<PathBuf>,
+        initial_heap_mb: Option<usize>,
+        max_heap_mb: Option<usize>,
+        freeze_intrinsics: bool,
+        permissions: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let mut options = Context::build_options(enable_extensions, enable_logging, random_seed, None, code_cache_dir)?
+            .with_heap_limits(initial_heap_mb, max_heap_mb)?
+            .with_permissions(permissions)?;
+        crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+        let init_code = if freeze_intrinsics {
+            format!("{}\n;{}", init_code, crate::harden::FREEZE_INTRINSICS)
+        } else {
+            init_code
+        };
+        let blob = py
+            .detach(|| crate::snapshot::create_snapshot_blob(&init_code, enable_extensions, enable_logging))
+            .map_err(|e| PyException::new_err(format!("Init code error: {}", e)))?;
+        let blob = crate::snapshot::intern_blob(&blob);
+        options.snapshot = Some(blob);
+
+        Ok(SnapshotPool {
+            options,
+            snapshot_size: blob.len(),
+            checkouts: AtomicUsize::new(0),
+        })
+    }
+
+    /// 取出一个从快照启动的全新 Context
+    ///
+    /// 返回的 Context 只能在当前线程使用，用完后 del 或离开 with 块即可，无需归还。
+    fn checkout(&self) -> PyResult<Context> {
+        self.new_context()
+    }
+
+    /// 在全新的 Context 中调用函数，调用后销毁 Context
+    ///
+    /// Args:
+    ///     name: 函数名称
+    ///     args: 参数列表
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    #[pyo3(signature = (name, args, auto_await=None))]
+    fn call<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        auto_await: Option<bool>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let ctx = Bound::new(py, self.new_context()?)?;
+        Context::call(&ctx, py, name, args, auto_await, false, false, None, None, None, None, None)
+    }
+
+    /// 在全新的 Context 中求值，求值后销毁 Context
+    ///
+    /// Args:
+    ///     code: JavaScript 代码
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    #[pyo3(signature = (code, auto_await=None))]
+    fn evaluate<'py>(&self, py: Python<'py>, code: String, auto_await: Option<bool>) -> PyResult<Bound<'py, PyAny>> {
+        let ctx = Bound::new(py, self.new_context()?)?;
+        Context::evaluate(&ctx, py, code, auto_await, false, false, None, None, None)
+    }
+
+    /// 快照大小（字节）
+    #[getter]
+    fn snapshot_size(&self) -> usize {
+        self.snapshot_size
+    }
+
+    /// 累计取出的 Context 数量（包括 call() / evaluate() 内部创建的）
+    #[getter]
+    fn checkouts(&self) -> usize {
+        self.checkouts.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,128 @@
+// source_file.rs - compile_file() 读取源文件：解码、预处理、按扩展名选择执行方式
+//
+// - encoding: 文件编码，默认 UTF-8；其他编码（如网上常见的 gbk 文件）用 Python 的 codecs 解码，
+//   编码名称与 open(encoding=...) 相同
+// - 去掉开头的 BOM（U+FEFF），否则 V8 把它当作非法字符
+// - 开头的 shebang（#!/usr/bin/env node）改为注释，保持行号不变
+// - mode: "script"（全局脚本）/ "module"（ES 模块）/ "typescript"，默认按扩展名选择：
+//   .mjs 为模块，.ts / .mts / .cts / .tsx 为 TypeScript，其余为脚本
+//
+// 没有内置 TypeScript 编译器，TypeScript 文件需要先用 tsc / esbuild 等工具转换为 JavaScript。
+
+use anyhow::{Result, anyhow};
+use deno_core::ModuleSpecifier;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 模块加载次数（同一文件多次作为模块执行时生成不同的 specifier）
+static MODULE_LOADS: AtomicUsize = AtomicUsize::new(0);
+
+/// 源文件的执行方式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SourceMode {
+    /// 全局脚本（与 compile() 相同）
+    Script,
+    /// ES 模块，导出的绑定定义为全局变量
+    Module,
+    /// TypeScript（不支持，只用于给出明确的错误）
+    TypeScript,
+}
+
+impl SourceMode {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "script" => Ok(SourceMode::Script),
+            "module" => Ok(SourceMode::Module),
+            "typescript" => Ok(SourceMode::TypeScript),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown mode '{}', expected 'script', 'module' or 'typescript'",
+                name
+            ))),
+        }
+    }
+
+    /// 按扩展名选择执行方式
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("mjs") => SourceMode::Module,
+            Some("ts" | "mts" | "cts" | "tsx") => SourceMode::TypeScript,
+            _ => SourceMode::Script,
+        }
+    }
+}
+
+/// 读取源文件，返回预处理后的代码和执行方式
+///
+/// `mode` 为 None 时按扩展名选择。TypeScript 返回 ValueError。
+pub fn load(py: Python<'_>, path: &Path, encoding: Option<&str>, mode: Option<&str>) -> PyResult<(String, SourceMode)> {
+    let name = path.display().to_string();
+    let mode = resolve_mode(&name, path, mode)?;
+    let data = std::fs::read(path)
+        .map_err(|e| PyException::new_err(format!("Failed to read {}: {}", name, e)))?;
+    Ok((decode(py, &name, data, encoding)?, mode))
+}
+
+/// 确定执行方式：`mode` 为 None 时按 path 的扩展名选择，TypeScript 返回 ValueError（name 用于错误信息）
+pub fn resolve_mode(name: &str, path: &Path, mode: Option<&str>) -> PyResult<SourceMode> {
+    let mode = match mode {
+        Some(mode) => SourceMode::parse(mode)?,
+        None => SourceMode::from_path(path),
+    };
+    if mode == SourceMode::TypeScript {
+        return Err(PyValueError::new_err(format!(
+            "TypeScript is not supported: {} must be compiled to JavaScript first (e.g. with tsc or esbuild), \
+             or pass mode='script' / mode='module' if it is plain JavaScript",
+            name
+        )));
+    }
+    Ok(mode)
+}
+
+/// 按指定编码解码（默认 UTF-8）并预处理
+pub fn decode(py: Python<'_>, name: &str, data: Vec<u8>, encoding: Option<&str>) -> PyResult<String> {
+    let code = match encoding {
+        None => String::from_utf8(data).map_err(|e| {
+            PyValueError::new_err(format!(
+                "{} is not valid UTF-8 ({}); pass encoding=... (e.g. encoding='gbk')",
+                name,
+                e.utf8_error()
+            ))
+        })?,
+        Some(encoding) => PyBytes::new(py, &data)
+            .call_method1("decode", (encoding,))
+            .and_then(|text| text.extract::<String>())
+            .map_err(|e| PyValueError::new_err(format!("Failed to decode {} as {}: {}", name, encoding, e)))?,
+    };
+    Ok(preprocess(code))
+}
+
+/// 去掉 BOM，把 shebang 改为注释
+fn preprocess(code: String) -> String {
+    let code = match code.strip_prefix('\u{feff}') {
+        Some(rest) => rest.to_string(),
+        None => code,
+    };
+    match code.strip_prefix("#!") {
+        Some(rest) => format!("//{}", rest),
+        None => code,
+    }
+}
+
+/// 模块的 specifier：文件的 file:// URL（compile_url() 加载的模块为其 http(s) URL），
+/// 带上加载序号避免与之前加载的同一文件冲突
+pub fn module_specifier(name: &str) -> Result<ModuleSpecifier> {
+    let mut specifier = match ModuleSpecifier::parse(name) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+        _ => {
+            let path = std::path::absolute(name).map_err(|e| anyhow!("Invalid module path {}: {}", name, e))?;
+            ModuleSpecifier::from_file_path(&path).map_err(|_| anyhow!("Invalid module path {}", path.display()))?
+        }
+    };
+    specifier
+        .query_pairs_mut()
+        .append_pair("load", &MODULE_LOADS.fetch_add(1, Ordering::Relaxed).to_string());
+    Ok(specifier)
+}
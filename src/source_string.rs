@@ -0,0 +1,23 @@
+// source_string.rs - 把大段代码交给 V8 时避免多余的复制
+//
+// v8::String::new 会把 UTF-8 数据复制（并转码）到 V8 堆中，几 MB 的 bundle
+// 在 Python str、Rust String、V8 堆中同时存在三份。
+// 纯 ASCII 的大段代码改为 V8 外部字符串：V8 直接接管 Rust String 的缓冲区，
+// 不再复制，缓冲区在字符串被 GC 回收时释放。
+
+use deno_core::v8;
+
+/// 超过该长度的纯 ASCII 代码以外部字符串传给 V8（小字符串复制的开销可以忽略）
+const EXTERNAL_MIN_LEN: usize = 64 * 1024;
+
+/// 创建 V8 源代码字符串，大段 ASCII 代码不复制
+///
+/// ASCII 是 Latin-1 的子集，可以直接作为单字节外部字符串；
+/// 含非 ASCII 字符时 V8 需要转码，回退到普通字符串。
+pub fn new_source<'s>(scope: &v8::PinScope<'s, '_, ()>, code: String) -> Option<v8::Local<'s, v8::String>> {
+    if code.len() >= EXTERNAL_MIN_LEN && code.is_ascii() {
+        v8::String::new_external_onebyte(scope, code.into_bytes().into_boxed_slice())
+    } else {
+        v8::String::new(scope, &code)
+    }
+}
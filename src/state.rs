@@ -0,0 +1,169 @@
+// state.rs - export_state() / import_state()：把选定的全局变量保存为字节，恢复到其他 Context
+//
+// 设备注册、密钥协商等只需要计算一次的状态，用 export_state(keys=[...]) 以 V8 结构化克隆
+// （Deno.core 的 op_serialize，for_storage 模式）序列化为 bytes，写入文件或数据库；
+// 进程重启后在新的 Context 中 import_state(state) 恢复为同名全局变量：
+// - 支持结构化克隆的所有类型：对象、数组、Map / Set / Date / RegExp / 类型化数组、循环引用等；
+//   同时导出的多个全局变量之间共享的对象恢复后仍然是同一个对象
+// - 函数、类实例的原型、Symbol 无法克隆，导出时抛出 DataCloneError（指出是哪个全局变量）；
+//   SharedArrayBuffer 和 WebAssembly.Module 同样不能导出
+// - 数据以 MAGIC 开头，后跟 V8 的序列化格式；V8 能读取旧版本写入的数据，但不能读取更新版本写入的数据
+//
+// 导入的状态不是脚本，无法在 pickle 时重放，导入后的 Context 不能 pickle。
+//
+// never_jscore.transfer() 使用同一套函数在进程内的两个 Context 之间复制全局变量（Mode::Transfer，见 transfer.rs）。
+
+use anyhow::{anyhow, Result};
+use deno_core::{v8, JsRuntime};
+
+use crate::code_cache::exception_to_error;
+use crate::context::{core_ops_key, format_error};
+
+/// 状态数据的开头（格式版本 1）
+const MAGIC: &[u8] = b"NJSTATE\x01";
+
+/// 序列化的用途
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// 保存为字节（export_state()）：V8 的 for_storage 格式，SharedArrayBuffer / WebAssembly.Module 不能导出
+    Storage,
+    /// 在同一进程的 isolate 之间传递（transfer()）：SharedArrayBuffer / WebAssembly.Module 通过进程级的存储共享
+    Transfer,
+}
+
+// 导出 / 导入函数：由 Rust 传入 Deno.core.ops 生成
+const STATE_FUNCTIONS: &str = r#"
+(function(ops) {
+    const cloneError = (key, reason) => {
+        const error = new Error(`Failed to export '${key}': ${reason}`);
+        error.name = 'DataCloneError';
+        return error;
+    };
+    const serialize = (value, key, forStorage) => ops.op_serialize(value, undefined, undefined, forStorage, reason => {
+        throw cloneError(key, reason);
+    });
+
+    return {
+        export(keys, forStorage) {
+            const state = new Map();
+            for (const key of keys) {
+                if (!(key in globalThis)) {
+                    throw new ReferenceError(`${key} is not defined`);
+                }
+                state.set(key, globalThis[key]);
+            }
+            try {
+                return serialize(state, keys.join(', '), forStorage);
+            } catch (e) {
+                // 逐个序列化，找出无法克隆的全局变量
+                for (const [key, value] of state) serialize(value, key, forStorage);
+                throw e;
+            }
+        },
+        import(bytes, forStorage, rename) {
+            const state = ops.op_deserialize(bytes, undefined, undefined, undefined, forStorage);
+            if (!(state instanceof Map)) {
+                throw new TypeError('Invalid state: not created by export_state()');
+            }
+            // rename：只有一个全局变量时以新的名称定义（transfer() 的 dst_name）
+            if (rename !== undefined) {
+                globalThis[rename] = state.values().next().value;
+                return JSON.stringify([rename]);
+            }
+            for (const [key, value] of state) {
+                globalThis[key] = value;
+            }
+            return JSON.stringify([...state.keys()]);
+        },
+    };
+})
+"#;
+
+/// 编译 STATE_FUNCTIONS，传入 Deno.core.ops，取出名为 name 的函数
+fn state_function(runtime: &mut JsRuntime, name: &str) -> Result<v8::Global<v8::Function>> {
+    let factory = runtime
+        .execute_script("<state>", STATE_FUNCTIONS)
+        .map_err(|e| anyhow!("Failed to compile state functions: {}", format_error(e.into())))?;
+
+    deno_core::scope!(scope, runtime);
+    let factory = v8::Local::<v8::Function>::try_from(v8::Local::new(scope, factory))
+        .map_err(|_| anyhow!("State functions factory is not a function"))?;
+    let global = scope.get_current_context().global(scope);
+    let key = core_ops_key(scope);
+    let ops = global
+        .get_private(scope, key)
+        .filter(|ops| ops.is_object())
+        .ok_or_else(|| anyhow!("Deno.core.ops was not captured before user code ran"))?;
+    let undefined = v8::undefined(scope);
+    let functions = factory
+        .call(scope, undefined.into(), &[ops])
+        .and_then(|functions| v8::Local::<v8::Object>::try_from(functions).ok())
+        .ok_or_else(|| anyhow!("State functions factory did not return an object"))?;
+    let name = v8::String::new(scope, name).ok_or_else(|| anyhow!("Failed to create string"))?;
+    let function = functions
+        .get(scope, name.into())
+        .and_then(|function| v8::Local::<v8::Function>::try_from(function).ok())
+        .ok_or_else(|| anyhow!("State function is missing"))?;
+    Ok(v8::Global::new(scope, function))
+}
+
+/// 序列化全局变量 keys，返回状态数据
+pub fn export(runtime: &mut JsRuntime, keys: &[String], mode: Mode) -> Result<Vec<u8>> {
+    let function = state_function(runtime, "export")?;
+
+    deno_core::scope!(scope, runtime);
+    let function = v8::Local::new(scope, function);
+    let mut names: Vec<v8::Local<v8::Value>> = Vec::with_capacity(keys.len());
+    for key in keys {
+        let name = v8::String::new(scope, key).ok_or_else(|| anyhow!("Failed to create string"))?;
+        names.push(name.into());
+    }
+    let names = v8::Array::new_with_elements(scope, &names);
+    let for_storage = v8::Boolean::new(scope, mode == Mode::Storage);
+    let undefined = v8::undefined(scope);
+
+    v8::tc_scope!(let tc_scope, scope);
+    let value = function
+        .call(tc_scope, undefined.into(), &[names.into(), for_storage.into()])
+        .ok_or_else(|| exception_to_error(tc_scope))?;
+    let view = v8::Local::<v8::ArrayBufferView>::try_from(value)
+        .map_err(|_| anyhow!("op_serialize did not return a buffer"))?;
+    let mut data = MAGIC.to_vec();
+    let start = data.len();
+    data.resize(start + view.byte_length(), 0);
+    view.copy_contents(&mut data[start..]);
+    Ok(data)
+}
+
+/// 检查状态数据的开头，返回 V8 序列化的数据
+pub fn payload(state: &[u8]) -> Result<&[u8], String> {
+    state
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| "Invalid state: not created by export_state()".to_string())
+}
+
+/// 把状态数据恢复为全局变量，返回恢复的名称
+///
+/// rename 不为 None 时（数据中只有一个全局变量）以该名称定义。
+pub fn import(runtime: &mut JsRuntime, payload: Vec<u8>, mode: Mode, rename: Option<&str>) -> Result<Vec<String>> {
+    let function = state_function(runtime, "import")?;
+
+    deno_core::scope!(scope, runtime);
+    let function = v8::Local::new(scope, function);
+    let len = payload.len();
+    let store = v8::ArrayBuffer::new_backing_store_from_vec(payload).make_shared();
+    let buffer = v8::ArrayBuffer::with_backing_store(scope, &store);
+    let bytes = v8::Uint8Array::new(scope, buffer, 0, len).ok_or_else(|| anyhow!("Failed to create Uint8Array"))?;
+    let for_storage = v8::Boolean::new(scope, mode == Mode::Storage);
+    let undefined = v8::undefined(scope);
+    let rename: v8::Local<v8::Value> = match rename {
+        Some(name) => v8::String::new(scope, name).ok_or_else(|| anyhow!("Failed to create string"))?.into(),
+        None => undefined.into(),
+    };
+
+    v8::tc_scope!(let tc_scope, scope);
+    let value = function
+        .call(tc_scope, undefined.into(), &[bytes.into(), for_storage.into(), rename])
+        .ok_or_else(|| exception_to_error(tc_scope))?;
+    Ok(serde_json::from_str(&value.to_rust_string_lossy(tc_scope))?)
+}
@@ -1,48 +1,136 @@
-use std::cell::RefCell;
-use std::sync::{Arc, Mutex};
-use once_cell::sync::Lazy;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
 
-/// 全局 Hook 数据存储
-///
-/// 在调用 terminate_execution() 前保存 Hook 拦截的数据。
-/// 使用全局静态变量确保数据在 V8 isolate 终止后仍然可访问。
-static HOOK_DATA: Lazy<Arc<Mutex<Option<String>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+use crate::result_stream::ResultStream;
 
 /// JavaScript 执行结果存储
 ///
 /// 用于在 Rust 和 JavaScript 之间传递执行结果。
 /// 通过 Deno Core 的 op 机制，JavaScript 可以将结果存储到这里。
+///
+/// 每次求值分配一个调用 ID，求值包装函数把 ID 连同结果一起传回（op_store_result(id, json)），
+/// 结果按 ID 存放。后台事件循环中迟到的 Promise 不会覆盖之后调用的结果；
+/// 已经结束的调用迟到的结果会被直接丢弃。
+///
+/// evaluate(stream_to=...) 的调用附带一个 ResultStream，结果分块经 op_store_result_chunk 写出，
+/// 不经过 values。
+///
+/// call(expect_schema=...) 的调用附带规则（JSON 文本），结果不符合时包装函数经 op_store_schema_mismatch
+/// 存入不符合的位置，结果记为 null。
+///
+/// $terminate() 在终止 isolate 之前把 Hook 拦截的数据保存在 hook_data 中，每个 Context 独立，
+/// 保存在 Rust 侧，isolate 被终止后仍然可以读取。
 pub struct ResultStorage {
-    pub value: RefCell<Option<String>>,
+    values: RefCell<HashMap<u32, String>>,
+    streams: RefCell<HashMap<u32, Rc<ResultStream>>>,
+    pending_stream: RefCell<Option<Rc<ResultStream>>>,  // 下一次 begin_call 使用的 ResultStream
+    schemas: RefCell<HashMap<u32, Rc<str>>>,
+    pending_schema: RefCell<Option<Rc<str>>>,  // 下一次 begin_call 使用的 expect_schema
+    mismatches: RefCell<HashMap<u32, String>>,
+    active_calls: RefCell<Vec<u32>>,  // 正在进行的调用（栈顶为当前调用）
+    next_call_id: Cell<u32>,
     early_return: RefCell<bool>,  // 标记是否是提前返回（用于Hook拦截）
     terminated: RefCell<bool>,    // 标记是否应该终止runtime
+    hook_data: RefCell<Option<String>>,  // $terminate() 保存的 Hook 数据
 }
 
 impl ResultStorage {
     pub fn new() -> Self {
         Self {
-            value: RefCell::new(None),
+            values: RefCell::new(HashMap::new()),
+            streams: RefCell::new(HashMap::new()),
+            pending_stream: RefCell::new(None),
+            schemas: RefCell::new(HashMap::new()),
+            pending_schema: RefCell::new(None),
+            mismatches: RefCell::new(HashMap::new()),
+            active_calls: RefCell::new(Vec::new()),
+            next_call_id: Cell::new(1),
             early_return: RefCell::new(false),
             terminated: RefCell::new(false),
+            hook_data: RefCell::new(None),
         }
     }
 
-    pub fn clear(&self) {
-        *self.value.borrow_mut() = None;
+    /// 开始一次求值，返回本次调用的 ID
+    pub fn begin_call(&self) -> u32 {
+        let id = self.next_call_id.get();
+        self.next_call_id.set(id.wrapping_add(1).max(1));
+        self.active_calls.borrow_mut().push(id);
+        if let Some(stream) = self.pending_stream.borrow_mut().take() {
+            self.streams.borrow_mut().insert(id, stream);
+        }
+        if let Some(schema) = self.pending_schema.borrow_mut().take() {
+            self.schemas.borrow_mut().insert(id, schema);
+        }
         *self.early_return.borrow_mut() = false;
         *self.terminated.borrow_mut() = false;
+        id
+    }
+
+    /// 结束一次求值，丢弃未取出的结果
+    pub fn end_call(&self, id: u32) {
+        self.active_calls.borrow_mut().retain(|active| *active != id);
+        self.values.borrow_mut().remove(&id);
+        self.streams.borrow_mut().remove(&id);
+        self.schemas.borrow_mut().remove(&id);
+        self.mismatches.borrow_mut().remove(&id);
+    }
+
+    /// 让下一次开始的调用把结果分块写入 stream
+    pub fn set_pending_stream(&self, stream: Option<Rc<ResultStream>>) {
+        *self.pending_stream.borrow_mut() = stream;
     }
 
-    pub fn store(&self, value: String) {
-        *self.value.borrow_mut() = Some(value);
+    /// 让下一次开始的调用按 schema（JSON 文本）检查结果
+    pub fn set_pending_schema(&self, schema: Option<Rc<str>>) {
+        *self.pending_schema.borrow_mut() = schema;
     }
 
-    pub fn take(&self) -> Option<String> {
-        self.value.borrow_mut().take()
+    /// 调用附带的 expect_schema
+    pub fn schema(&self, id: u32) -> Option<Rc<str>> {
+        self.schemas.borrow().get(&id).cloned()
+    }
+
+    /// 记录结果不符合 expect_schema（mismatches 为 JSON 数组），结果记为 null 以结束等待
+    pub fn store_mismatch(&self, id: u32, mismatches: String) {
+        if self.active_calls.borrow().contains(&id) {
+            self.mismatches.borrow_mut().insert(id, mismatches);
+            self.values.borrow_mut().insert(id, "null".to_string());
+        }
+    }
+
+    /// 取出调用的结果不符合 expect_schema 的位置
+    pub fn take_mismatch(&self, id: u32) -> Option<String> {
+        self.mismatches.borrow_mut().remove(&id)
+    }
+
+    /// 调用附带的 ResultStream（没有时结果整体存储）
+    pub fn stream(&self, id: u32) -> Option<Rc<ResultStream>> {
+        self.streams.borrow().get(&id).cloned()
+    }
+
+    /// 存储调用的结果（调用已结束时丢弃）
+    pub fn store(&self, id: u32, value: String) {
+        if self.active_calls.borrow().contains(&id) {
+            self.values.borrow_mut().insert(id, value);
+        }
     }
 
-    /// 标记为提前返回（Hook拦截）
-    pub fn mark_early_return(&self) {
+    pub fn take(&self, id: u32) -> Option<String> {
+        self.values.borrow_mut().remove(&id)
+    }
+
+    /// 检查是否已存储结果（不取出）
+    pub fn has_value(&self, id: u32) -> bool {
+        self.values.borrow().contains_key(&id)
+    }
+
+    /// 标记为提前返回（Hook拦截），值作为当前调用的结果
+    pub fn mark_early_return(&self, value: String) {
+        if let Some(&id) = self.active_calls.borrow().last() {
+            self.values.borrow_mut().insert(id, value);
+        }
         *self.early_return.borrow_mut() = true;
     }
 
@@ -60,36 +148,25 @@ impl ResultStorage {
     pub fn is_terminated(&self) -> bool {
         *self.terminated.borrow()
     }
-}
 
-impl Default for ResultStorage {
-    fn default() -> Self {
-        Self::new()
+    /// 保存 Hook 拦截的数据（JS 调用 __saveAndTerminate__() 时）
+    pub fn save_hook_data(&self, data: String) {
+        *self.hook_data.borrow_mut() = Some(data);
     }
-}
 
-/// 保存 Hook 拦截的数据到全局存储
-///
-/// 这个函数在 JS 调用 __saveAndTerminate__() 时被调用，
-/// 数据会保存到全局变量中，即使 V8 isolate 被终止也能访问。
-pub fn save_hook_data(data: String) {
-    let mut guard = HOOK_DATA.lock().unwrap();
-    *guard = Some(data);
-}
+    /// 获取保存的 Hook 数据（通常在 JS 被 terminate_execution() 终止后调用）
+    pub fn hook_data(&self) -> Option<String> {
+        self.hook_data.borrow().clone()
+    }
 
-/// 获取保存的 Hook 数据
-///
-/// 从全局存储中读取之前保存的 Hook 数据。
-/// 通常在 JS 被 terminate_execution() 终止后调用。
-pub fn get_hook_data() -> Option<String> {
-    let guard = HOOK_DATA.lock().unwrap();
-    guard.clone()
+    /// 清空保存的 Hook 数据，避免读取到旧数据
+    pub fn clear_hook_data(&self) {
+        self.hook_data.borrow_mut().take();
+    }
 }
 
-/// 清空保存的 Hook 数据
-///
-/// 在开始新的 JS 执行前调用，避免读取到旧数据。
-pub fn clear_hook_data() {
-    let mut guard = HOOK_DATA.lock().unwrap();
-    *guard = None;
+impl Default for ResultStorage {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -0,0 +1,116 @@
+// template.rs - render_call()：用命名占位符拼接调用代码
+//
+// 用 f-string 把参数拼进 JS 代码（f"sign({ts}, '{payload}')"）时，参数中的引号、换行和反斜杠
+// 会破坏代码，外部数据甚至能注入任意代码。render_call("sign({ts}, {payload})", ts=..., payload=...)
+// 把每个值按 call() 的参数规则（包括 register_converter() 注册的转换器）序列化为 JSON 字面量后再替换：
+// - 占位符为 {名称}，名称由字母、数字和下划线组成，不能以数字开头；其他花括号原样保留
+// - {{名称}} 输出字面的 {名称}（对象简写、解构等 JS 语法）
+// - 对象值替换为 ({...})，在语句开头也作为表达式解析
+// - 缺少占位符的值抛出 KeyError，传入了未使用的值抛出 TypeError
+//
+// 占位符按表达式替换，不理解 JS 语法：写在字符串字面量或注释中的占位符同样会被替换。
+
+use pyo3::exceptions::{PyKeyError, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value as JsonValue;
+
+use crate::convert::python_to_json;
+
+/// 模板中的一段：原样输出的文本或占位符名称
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+/// 从 start（'{' 之后）开始读取占位符名称，返回名称的结束位置
+fn placeholder_end(template: &str, start: usize) -> Option<usize> {
+    let bytes = template.as_bytes();
+    let mut end = start;
+    while end < bytes.len() && (bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_') {
+        end += 1;
+    }
+    let valid = end > start && !bytes[start].is_ascii_digit() && bytes.get(end) == Some(&b'}');
+    valid.then_some(end)
+}
+
+/// 把模板拆分为文本和占位符
+fn parse(template: &str) -> Vec<Part<'_>> {
+    let bytes = template.as_bytes();
+    let mut parts = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'{' {
+            i += 1;
+            continue;
+        }
+        // {{名称}} -> 字面的 {名称}
+        if bytes.get(i + 1) == Some(&b'{') {
+            if let Some(end) = placeholder_end(template, i + 2).filter(|end| bytes.get(end + 1) == Some(&b'}')) {
+                parts.push(Part::Text(&template[text_start..i]));
+                parts.push(Part::Text(&template[i + 1..end + 1]));
+                i = end + 2;
+                text_start = i;
+                continue;
+            }
+        }
+        match placeholder_end(template, i + 1) {
+            Some(end) => {
+                parts.push(Part::Text(&template[text_start..i]));
+                parts.push(Part::Placeholder(&template[i + 1..end]));
+                i = end + 1;
+                text_start = i;
+            }
+            None => i += 1,
+        }
+    }
+    parts.push(Part::Text(&template[text_start..]));
+    parts
+}
+
+/// 把 Python 值序列化为 JS 表达式
+fn to_expression(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    let json = python_to_json(value)?;
+    Ok(match json {
+        JsonValue::Object(_) => format!("({})", json),
+        _ => json.to_string(),
+    })
+}
+
+/// 替换模板中的占位符，返回 JS 代码
+pub fn render(template: &str, values: Option<&Bound<'_, PyDict>>) -> PyResult<String> {
+    let mut code = String::with_capacity(template.len());
+    let mut used: Vec<&str> = Vec::new();
+    for part in parse(template) {
+        match part {
+            Part::Text(text) => code.push_str(text),
+            Part::Placeholder(name) => {
+                let value = values
+                    .map(|values| values.get_item(name))
+                    .transpose()?
+                    .flatten()
+                    .ok_or_else(|| PyKeyError::new_err(format!("Missing value for placeholder {{{}}}", name)))?;
+                code.push_str(&to_expression(&value)?);
+                used.push(name);
+            }
+        }
+    }
+
+    if let Some(values) = values {
+        let mut unused = Vec::new();
+        for key in values.keys() {
+            let key: String = key.extract()?;
+            if !used.contains(&key.as_str()) {
+                unused.push(key);
+            }
+        }
+        if !unused.is_empty() {
+            return Err(PyTypeError::new_err(format!(
+                "render_call() got values for placeholders not in the template: {}",
+                unused.join(", ")
+            )));
+        }
+    }
+    Ok(code)
+}
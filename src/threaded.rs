@@ -0,0 +1,788 @@
+// threaded.rs - ThreadedContext: 由专用线程持有的 Context
+//
+// JsRuntime 只能在创建它的线程上使用，所以 Context 是 unsendable 的。
+// ThreadedContext 启动一个专用的 OS 线程持有 Context，Python 侧的句柄只保存任务队列，
+// 可以在任意线程之间传递和共享；所有调用按顺序在专用线程上执行。
+
+use pyo3::exceptions::{PyException, PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::IntoPyObjectExt;
+use pyo3::types::{PyBytes, PyDict, PyList, PyMemoryView};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{JoinHandle, ThreadId};
+
+use crate::context::{Context, ContextOptions, format_error, stats_to_python};
+use crate::convert::{call_args_to_json, json_str_to_python, ResultFormat};
+use crate::harden::FREEZE_INTRINSICS;
+use crate::pool::{Task, run_task, spawn_worker};
+use crate::wasm_memory::MemoryViews;
+use crate::shared_buffer::SharedBuffer;
+use crate::result_stream::stream_target;
+use crate::event_loop::EventLoopOptions;
+use crate::recycle::RecyclePolicy;
+use crate::abort::AbortToken;
+
+/// 线程安全的 JavaScript 执行上下文
+///
+/// 与 Context 的 API 相同，但可以在多个 Python 线程之间共享。
+/// 内部由一个专用线程持有 V8 isolate，调用会被串行执行，等待期间释放 GIL。
+///
+/// Example:
+///     ```python
+///     import never_jscore
+///     from concurrent.futures import ThreadPoolExecutor
+///
+///     ctx = never_jscore.ThreadedContext()
+///     ctx.compile("function add(a, b) { return a + b; }")
+///
+///     # 任意线程都可以使用同一个 ctx
+///     with ThreadPoolExecutor(4) as executor:
+///         results = list(executor.map(lambda i: ctx.call("add", [i, 1]), range(100)))
+///     ```
+#[pyclass]
+pub struct ThreadedContext {
+    sender: Mutex<Option<Sender<Task>>>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+    worker_thread: ThreadId,
+    fork_generation: usize,
+    wasm_views: MemoryViews,
+}
+
+impl ThreadedContext {
+    /// 在专用线程上执行任务并等待结果（释放 GIL）
+    pub(crate) fn run<R, F>(&self, py: Python<'_>, f: F) -> PyResult<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&Context) -> PyResult<R> + Send + 'static,
+    {
+        let sender = self.sender()?;
+        py.detach(|| run_task(&sender, f))
+            .map_err(|e| PyException::new_err(e.to_string()))?
+    }
+
+    /// 获取任务发送端（已关闭时报错）
+    ///
+    /// register() 的 Python 函数在专用线程上运行，其中再调用同一个 ThreadedContext 时
+    /// 任务永远等不到执行，直接抛出 RuntimeError。
+    fn sender(&self) -> PyResult<Sender<Task>> {
+        crate::fork::check(self.fork_generation, "ThreadedContext")?;
+        if std::thread::current().id() == self.worker_thread {
+            return Err(PyRuntimeError::new_err(
+                "ThreadedContext is already executing JavaScript: it cannot be called again from a register() callback",
+            ));
+        }
+        self.sender
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| PyException::new_err("ThreadedContext is closed"))
+    }
+
+    /// 关闭任务队列并等待专用线程退出
+    fn shutdown(&self) {
+        self.sender.lock().unwrap().take();
+        if let Some(worker) = self.worker.lock().unwrap().take() {
+            // fork 后的子进程中专用线程并不存在，不能 join
+            if crate::fork::is_inherited(self.fork_generation) {
+                std::mem::forget(worker);
+                return;
+            }
+            // 专用线程上的回调中关闭时不能等待自己，当前任务结束后线程自行退出
+            if std::thread::current().id() == self.worker_thread {
+                return;
+            }
+            let _ = worker.join();
+        }
+    }
+}
+
+#[pymethods]
+impl ThreadedContext {
+    /// 创建线程安全的执行上下文
+    ///
+    /// Args:
+    ///     enable_extensions / enable_logging / random_seed / snapshot / code_cache_dir / initial_heap_mb / max_heap_mb / permissions / allow_dynamic_code / timeout_ms / cpu_limit_ms / stack_size_kb / audit_ops / fs_roots / env / no_ops / quotas / history / shared_memory / shared_buffers / weak_refs / auto_recreate / undefined_as:
+    ///         与 Context 构造函数含义相同（auto_recreate 在遇到致命错误的任务结束后重建）
+    #[new]
+    #[pyo3(signature = (enable_extensions=true, enable_logging=false, random_seed=None, snapshot=None, code_cache_dir=None, initial_heap_mb=None, max_heap_mb=None, permissions=None, allow_dynamic_code=true, timeout_ms=None, cpu_limit_ms=None, stack_size_kb=None, audit_ops=false, fs_roots=None, env=None, no_ops=false, quotas=None, history=None, shared_memory=false, shared_buffers=None, weak_refs=true, auto_recreate=false, undefined_as=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        py: Python<'_>,
+        enable_extensions: bool,
+        enable_logging: bool,
+        random_seed: Option<u32>,
+        snapshot: Option<&[u8]>,
+        code_cache_dir: Option<PathBuf>,
+        initial_heap_mb: Option<usize>,
+        max_heap_mb: Option<usize>,
+        permissions: Option<&Bound<'_, PyAny>>,
+        allow_dynamic_code: bool,
+        timeout_ms: Option<u64>,
+        cpu_limit_ms: Option<u64>,
+        stack_size_kb: Option<usize>,
+        audit_ops: bool,
+        fs_roots: Option<&Bound<'_, PyAny>>,
+        env: Option<&Bound<'_, PyDict>>,
+        no_ops: bool,
+        quotas: Option<&Bound<'_, PyDict>>,
+        history: Option<usize>,
+        shared_memory: bool,
+        shared_buffers: Option<&Bound<'_, PyDict>>,
+        weak_refs: bool,
+        auto_recreate: bool,
+        undefined_as: Option<&str>,
+    ) -> PyResult<Self> {
+        let options = Context::build_options(enable_extensions && !no_ops, enable_logging, random_seed, snapshot, code_cache_dir)?
+            .with_heap_limits(initial_heap_mb, max_heap_mb)?
+            .with_permissions(permissions)?
+            .with_fs_roots(fs_roots)?
+            .with_env(env)?
+            .with_time_limits(timeout_ms, cpu_limit_ms)?
+            .with_undefined_as(undefined_as)?
+            .with_quotas(quotas)?
+            .with_history(history)?
+            .with_shared_memory(shared_memory, shared_buffers)?;
+        let options = ContextOptions { allow_dynamic_code, audit_ops, no_ops, weak_refs, auto_recreate, ..options };
+        crate::runtime::validate_stack_size(stack_size_kb)?;
+        if let Some(kb) = stack_size_kb {
+            crate::runtime::require_stack_size(kb).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        }
+        crate::runtime::ensure_v8_initialized().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let worker = py
+            .detach(|| {
+                spawn_worker(
+                    "never_jscore-threaded".to_string(),
+                    options,
+                    None,
+                    Arc::new(Mutex::new(receiver)),
+                )
+            })
+            .map_err(|e| PyException::new_err(format!("ThreadedContext error: {}", e)))?;
+
+        Ok(ThreadedContext {
+            sender: Mutex::new(Some(sender)),
+            worker_thread: worker.thread().id(),
+            worker: Mutex::new(Some(worker)),
+            fork_generation: crate::fork::generation(),
+            wasm_views: MemoryViews::default(),
+        })
+    }
+
+    /// 编译JavaScript代码并加入全局作用域（preset / recycle_after / result_filter 与 Context.compile() 相同）
+    ///
+    /// recycle_after 的阈值在专用线程每次执行完任务后检查。
+    #[pyo3(signature = (code, preset=None, recycle_after=None, result_filter=None))]
+    fn compile(
+        &self,
+        py: Python<'_>,
+        code: String,
+        preset: Option<Vec<String>>,
+        recycle_after: Option<&Bound<'_, PyDict>>,
+        result_filter: Option<String>,
+    ) -> PyResult<()> {
+        let presets = crate::presets::resolve(preset)?;
+        let recycle_after = recycle_after.map(RecyclePolicy::from_py).transpose()?;
+        self.run(py, move |ctx| {
+            if let Some(policy) = recycle_after {
+                ctx.set_recycle_policy(policy).map_err(|e| PyValueError::new_err(e.to_string()))?;
+            }
+            presets
+                .into_iter()
+                .try_for_each(|(name, source)| ctx.exec_named_script(&name, source.to_string(), true))
+                .and_then(|()| ctx.exec_named_script("<exec>", code, true))
+                .map_err(|e| crate::quota::py_error("Compile error", e))?;
+            if let Some(name) = result_filter {
+                ctx.set_result_filter(name).map_err(|e| PyValueError::new_err(format_error(e)))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// 从文件编译JavaScript代码（encoding / mode / watch 与 Context.compile_file() 相同）
+    #[pyo3(signature = (path, encoding=None, mode=None, watch=false))]
+    fn compile_file(
+        &self,
+        py: Python<'_>,
+        path: PathBuf,
+        encoding: Option<String>,
+        mode: Option<String>,
+        watch: bool,
+    ) -> PyResult<()> {
+        let stamp = crate::file_watch::stamp(&path);
+        let (code, source_mode) = crate::source_file::load(py, &path, encoding.as_deref(), mode.as_deref())?;
+
+        self.run(py, move |ctx| {
+            ctx.exec_source_file(&path, code, source_mode)
+                .map_err(|e| crate::quota::py_error("Compile error", e))?;
+            if watch {
+                ctx.watch_file(path, encoding, mode, stamp);
+            } else {
+                ctx.unwatch_file(&path);
+            }
+            Ok(())
+        })
+    }
+
+    /// 调用 JavaScript 函数
+    ///
+    /// binary=True 时把结果中的字符串返回为 bytes，stream_to 把结果分块写入文件对象，
+    /// event_loop 为本次调用的事件循环选项，result_format 为结果的格式，参见 Context.call()
+    #[pyo3(signature = (name, args, auto_await=None, binary=false, stream_to=None, event_loop=None, trace_id=None, result_format=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn call<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        auto_await: Option<bool>,
+        binary: bool,
+        stream_to: Option<&Bound<'_, PyAny>>,
+        event_loop: Option<&Bound<'_, PyDict>>,
+        trace_id: Option<String>,
+        result_format: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let format = ResultFormat::from_py(result_format, binary, stream_to.is_some())?;
+        let args = call_args_to_json(args)?;
+        let auto_await = auto_await.unwrap_or(true);
+        let event_loop = EventLoopOptions::from_py(event_loop)?;
+        let tag_id = trace_id.clone();
+        let tag = |e| crate::trace::tag_error(py, e, tag_id.as_deref());
+
+        if let Some(stream) = stream_target(stream_to, binary)? {
+            let written = self
+                .run(py, move |ctx| {
+                    ctx.with_trace_id(trace_id, || {
+                        ctx.with_event_loop(event_loop, || ctx.execute_call_streaming(&name, &args, auto_await, stream))
+                    })
+                    .map_err(|e| crate::result_stream::py_error("Call error", e))
+                })
+                .map_err(tag)?;
+            return written.into_bound_py_any(py);
+        }
+
+        let result_json = self
+            .run(py, move |ctx| {
+                ctx.with_trace_id(trace_id, || ctx.with_event_loop(event_loop, || ctx.execute_call(&name, &args, auto_await)))
+                    .map_err(|e| crate::quota::py_error("Call error", e))
+            })
+            .map_err(tag)?;
+
+        format.to_python(py, &result_json, binary)
+    }
+
+    /// 用命名占位符拼接调用代码并求值，参见 Context.render_call()
+    #[pyo3(signature = (template, **values))]
+    fn render_call<'py>(&self, py: Python<'py>, template: &str, values: Option<&Bound<'_, PyDict>>) -> PyResult<Bound<'py, PyAny>> {
+        let code = crate::template::render(template, values)?;
+
+        let result_json = self.run(py, move |ctx| {
+            ctx.execute_js(code, true)
+                .map_err(|e| crate::quota::py_error("Call error", e))
+        })?;
+
+        json_str_to_python(py, &result_json)
+    }
+
+    /// 执行代码并将其加入全局作用域
+    ///
+    /// capture_output=True 时返回 (结果, 本次调用的 console 输出)，参见 Context.eval()
+    #[pyo3(signature = (code, return_value=false, auto_await=None, capture_output=false, event_loop=None))]
+    fn eval<'py>(
+        &self,
+        py: Python<'py>,
+        code: String,
+        return_value: bool,
+        auto_await: Option<bool>,
+        capture_output: bool,
+        event_loop: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let auto_await = auto_await.unwrap_or(true);
+        let event_loop = EventLoopOptions::from_py(event_loop)?;
+
+        let (result_json, output) = self.run(py, move |ctx| {
+            ctx.capture_console(capture_output, || {
+                ctx.with_event_loop(event_loop, || {
+                    if return_value {
+                        ctx.execute_js(code, auto_await).map(Some)
+                    } else {
+                        ctx.exec_script(code).map(|_| None)
+                    }
+                })
+            })
+            .map_err(|e| crate::quota::py_error("Eval error", e))
+        })?;
+
+        let result = match result_json {
+            Some(json) => json_str_to_python(py, &json)?,
+            None => py.None().into_bound(py),
+        };
+        crate::console_capture::with_output(py, result, output)
+    }
+
+    /// 执行代码并返回结果（不影响全局作用域）
+    ///
+    /// binary=True 时把结果中的字符串返回为 bytes，stream_to 把结果分块写入文件对象，
+    /// event_loop 为本次调用的事件循环选项，result_format 为结果的格式，参见 Context.evaluate()
+    #[pyo3(signature = (code, auto_await=None, binary=false, stream_to=None, event_loop=None, result_format=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn evaluate<'py>(
+        &self,
+        py: Python<'py>,
+        code: String,
+        auto_await: Option<bool>,
+        binary: bool,
+        stream_to: Option<&Bound<'_, PyAny>>,
+        event_loop: Option<&Bound<'_, PyDict>>,
+        result_format: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let format = ResultFormat::from_py(result_format, binary, stream_to.is_some())?;
+        let auto_await = auto_await.unwrap_or(true);
+        let event_loop = EventLoopOptions::from_py(event_loop)?;
+
+        if let Some(stream) = stream_target(stream_to, binary)? {
+            let written = self.run(py, move |ctx| {
+                ctx.with_event_loop(event_loop, || ctx.execute_js_streaming(code, auto_await, stream))
+                    .map_err(|e| crate::result_stream::py_error("Evaluate error", e))
+            })?;
+            return written.into_bound_py_any(py);
+        }
+
+        let result_json = self.run(py, move |ctx| {
+            ctx.with_event_loop(event_loop, || ctx.execute_js(code, auto_await))
+                .map_err(|e| crate::quota::py_error("Evaluate error", e))
+        })?;
+
+        format.to_python(py, &result_json, binary)
+    }
+
+    /// 像浏览器控制台一样执行一行输入，参见 Context.repl_eval()
+    #[pyo3(signature = (line, auto_await=None))]
+    fn repl_eval<'py>(
+        &self,
+        py: Python<'py>,
+        line: String,
+        auto_await: Option<bool>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let auto_await = auto_await.unwrap_or(true);
+
+        let result_json = self.run(py, move |ctx| {
+            ctx.execute_repl(line, auto_await)
+                .map_err(|e| crate::quota::py_error("Eval error", e))
+        })?;
+
+        json_str_to_python(py, &result_json)
+    }
+
+    /// eval() 的 asyncio 版本
+    ///
+    /// 立即返回 asyncio.Future，JS 在专用线程上执行，不阻塞事件循环。
+    /// 必须在运行中的事件循环内调用。
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.ThreadedContext()
+    ///     result = await ctx.eval_async("1 + 2", return_value=True)
+    ///     ```
+    #[pyo3(signature = (code, return_value=false, auto_await=None, abort=None))]
+    fn eval_async<'py>(
+        &self,
+        py: Python<'py>,
+        code: String,
+        return_value: bool,
+        auto_await: Option<bool>,
+        abort: Option<PyRef<'_, AbortToken>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let auto_await = auto_await.unwrap_or(true);
+
+        crate::aio::submit(py, &self.sender()?, abort.as_deref(), move |ctx| {
+            let result = if return_value {
+                ctx.execute_js(code, auto_await).map(Some)
+            } else {
+                ctx.exec_script(code).map(|_| None)
+            };
+            result.map_err(|e| crate::quota::py_error("Eval error", e))
+        })
+    }
+
+    /// evaluate() 的 asyncio 版本
+    #[pyo3(signature = (code, auto_await=None, abort=None))]
+    fn evaluate_async<'py>(
+        &self,
+        py: Python<'py>,
+        code: String,
+        auto_await: Option<bool>,
+        abort: Option<PyRef<'_, AbortToken>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let auto_await = auto_await.unwrap_or(true);
+
+        crate::aio::submit(py, &self.sender()?, abort.as_deref(), move |ctx| {
+            ctx.execute_js(code, auto_await)
+                .map(Some)
+                .map_err(|e| crate::quota::py_error("Evaluate error", e))
+        })
+    }
+
+    /// call() 的 asyncio 版本
+    ///
+    /// 等待的任务被取消（或传入的 AbortToken 被中止）时终止正在执行的 JS，
+    /// 以 asyncio.CancelledError 结束，ThreadedContext 可以继续使用。
+    ///
+    /// Example:
+    ///     ```python
+    ///     async def handler(data):
+    ///         return await asyncio.wait_for(ctx.call_async("sign", [data]), timeout=1.0)
+    ///     ```
+    #[pyo3(signature = (name, args, auto_await=None, abort=None))]
+    fn call_async<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        auto_await: Option<bool>,
+        abort: Option<PyRef<'_, AbortToken>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let args = call_args_to_json(args)?;
+        let auto_await = auto_await.unwrap_or(true);
+
+        crate::aio::submit(py, &self.sender()?, abort.as_deref(), move |ctx| {
+            ctx.execute_call(&name, &args, auto_await)
+                .map(Some)
+                .map_err(|e| crate::quota::py_error("Call error", e))
+        })
+    }
+
+    /// 启动后台事件循环
+    ///
+    /// 默认情况下定时器和 Promise 只在调用期间推进。启动后台事件循环后：
+    /// - 专用线程在两次调用之间持续推进事件循环，setInterval 心跳等会按时触发
+    /// - call()/evaluate() 在结果就绪时立即返回，不再等待所有定时器完成
+    ///
+    /// 定时器回调写入全局变量的结果可以在之后用 evaluate() 读取。
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.ThreadedContext()
+    ///     ctx.start_event_loop()
+    ///     ctx.eval("var beats = 0; setInterval(() => beats++, 100);")
+    ///     time.sleep(1)
+    ///     print(ctx.evaluate("beats"))  # ~10
+    ///     ctx.stop_event_loop()
+    ///     ```
+    fn start_event_loop(&self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, |ctx| {
+            ctx.set_background_event_loop(true);
+            Ok(())
+        })
+    }
+
+    /// 停止后台事件循环
+    ///
+    /// 未完成的定时器不会被清除，会在下一次调用期间继续推进。
+    fn stop_event_loop(&self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, |ctx| {
+            ctx.set_background_event_loop(false);
+            Ok(())
+        })
+    }
+
+    /// isolate 遇到致命错误后不再可用的原因（与 Context.poisoned 相同）
+    #[getter]
+    fn poisoned(&self, py: Python<'_>) -> PyResult<Option<String>> {
+        self.run(py, |ctx| Ok(ctx.poison_reason()))
+    }
+
+    /// 后台事件循环是否正在运行
+    #[getter]
+    fn event_loop_running(&self, py: Python<'_>) -> PyResult<bool> {
+        self.run(py, |ctx| Ok(ctx.background_event_loop()))
+    }
+
+    /// 取出后台事件循环中发生的错误（如定时器回调抛出的未捕获异常）
+    ///
+    /// Returns:
+    ///     错误信息列表，取出后清空
+    fn take_event_loop_errors(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        self.run(py, |ctx| Ok(ctx.take_event_loop_errors()))
+    }
+
+    /// 请求垃圾回收
+    fn gc(&self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, |ctx| {
+            ctx.request_gc()
+                .map_err(|e| PyException::new_err(format!("GC error: {}", e)))
+        })
+    }
+
+    /// 通知 V8 系统内存不足（与 Context.notify_low_memory() 相同）
+    fn notify_low_memory(&self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, |ctx| {
+            ctx.low_memory_notification()
+                .map_err(|e| PyException::new_err(format!("GC error: {}", e)))
+        })
+    }
+
+    /// 强制垃圾回收并执行 FinalizationRegistry 清理回调（与 Context.collect_garbage() 相同）
+    #[pyo3(signature = (full=true))]
+    fn collect_garbage(&self, py: Python<'_>, full: bool) -> PyResult<()> {
+        self.run(py, move |ctx| {
+            ctx.force_gc(full)
+                .map_err(|e| PyException::new_err(format!("GC error: {}", e)))
+        })
+    }
+
+    /// 列出代码用到但当前 Context 中不存在的全局变量（与 Context.missing_globals() 相同）
+    fn missing_globals(&self, py: Python<'_>, code: String) -> PyResult<Vec<String>> {
+        self.run(py, move |ctx| {
+            ctx.find_missing_globals(&code)
+                .map_err(|e| PyException::new_err(format!("Missing globals error: {}", e)))
+        })
+    }
+
+    /// 把选定的全局变量序列化为 bytes（与 Context.export_state() 相同）
+    fn export_state<'py>(&self, py: Python<'py>, keys: Vec<String>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = self.run(py, move |ctx| {
+            ctx.export_globals(&keys)
+                .map_err(|e| PyException::new_err(format!("Export state error: {}", e)))
+        })?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// 把 export_state() 导出的数据恢复为全局变量（与 Context.import_state() 相同）
+    fn import_state(&self, py: Python<'_>, state: &[u8]) -> PyResult<Vec<String>> {
+        let payload = crate::state::payload(state).map_err(PyValueError::new_err)?.to_vec();
+        self.run(py, move |ctx| {
+            ctx.import_globals(payload)
+                .map_err(|e| PyException::new_err(format!("Import state error: {}", e)))
+        })
+    }
+
+    /// 记录全局对象每个属性的指纹（与 Context.snapshot_globals() 相同）
+    fn snapshot_globals(&self, py: Python<'_>) -> PyResult<BTreeMap<String, String>> {
+        self.run(py, |ctx| {
+            ctx.snapshot_global_fingerprints()
+                .map_err(|e| PyException::new_err(format!("Snapshot globals error: {}", e)))
+        })
+    }
+
+    /// 与 snapshot_globals() 的快照比较（与 Context.diff_globals() 相同）
+    fn diff_globals<'py>(&self, py: Python<'py>, prev: BTreeMap<String, String>) -> PyResult<Bound<'py, PyDict>> {
+        let current = self.run(py, |ctx| {
+            ctx.snapshot_global_fingerprints()
+                .map_err(|e| PyException::new_err(format!("Diff globals error: {}", e)))
+        })?;
+        crate::globals_diff::GlobalsDiff::between(&prev, &current).to_py_dict(py)
+    }
+
+    /// 预先执行有代表性的调用（与 Context.warmup() 相同）
+    #[pyo3(signature = (calls, rounds=1, auto_await=None))]
+    fn warmup(&self, py: Python<'_>, calls: &Bound<'_, PyAny>, rounds: usize, auto_await: Option<bool>) -> PyResult<()> {
+        let calls = crate::warmup::parse_calls(calls, rounds)?;
+        let auto_await = auto_await.unwrap_or(true);
+        self.run(py, move |ctx| {
+            crate::warmup::run(ctx, &calls, rounds, auto_await)
+                .map_err(|e| crate::quota::py_error("Warmup error", e))
+        })
+    }
+
+    /// 冻结内置对象（与 Context.freeze_intrinsics() 相同）
+    fn freeze_intrinsics(&self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, |ctx| {
+            ctx.exec_named_script("<freeze_intrinsics>", FREEZE_INTRINSICS.to_string(), false)
+                .map_err(|e| PyException::new_err(format!("Freeze error: {}", e)))
+        })
+    }
+
+    /// 把 Python 函数注册为 JS 全局函数（与 Context.register() 相同）
+    ///
+    /// 函数在专用线程上被调用（调用时获取 GIL）。
+    #[pyo3(signature = (name, func))]
+    fn register(&self, py: Python<'_>, name: String, func: Bound<'_, PyAny>) -> PyResult<()> {
+        if !func.is_callable() {
+            return Err(PyTypeError::new_err("func must be callable"));
+        }
+        let func = func.unbind();
+        self.run(py, move |ctx| {
+            ctx.register_py_function(&name, func)
+                .map_err(|e| PyException::new_err(format!("Register error: {}", e)))
+        })
+    }
+
+    /// 获取调用当前 Python 回调的 JS 调用栈（与 Context.capture_stack() 相同）
+    fn capture_stack<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        crate::py_functions::capture_stack(py)
+    }
+
+    /// 加载 WebAssembly 模块（参数和返回值与 Context.load_wasm() 相同）
+    #[pyo3(signature = (name, wasm_bytes, imports=None, wasi=None))]
+    fn load_wasm<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        wasm_bytes: &[u8],
+        imports: Option<&Bound<'_, PyDict>>,
+        wasi: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let imports = crate::wasm::imports_from_py(imports)?;
+        let wasi = crate::wasm::wasi_from_py(wasi)?;
+        let bytes = wasm_bytes.to_vec();
+        let exports = self.run(py, move |ctx| {
+            ctx.load_wasm_module(&name, bytes, imports, wasi)
+                .map_err(|e| PyException::new_err(format!("WASM error: {}", e)))
+        })?;
+        crate::wasm::exports_to_python(py, exports)
+    }
+
+    /// 零拷贝访问 WASM 线性内存（与 Context.wasm_memory() 相同，ThreadedContext 关闭时视图失效）
+    ///
+    /// 视图不与专用线程上的 JS 执行同步，并发读写需要调用方自行协调。
+    fn wasm_memory<'py>(&self, py: Python<'py>, expr: String) -> PyResult<Bound<'py, PyMemoryView>> {
+        let store = self.run(py, move |ctx| {
+            ctx.wasm_memory_store(&expr)
+                .map_err(|e| PyException::new_err(format!("WASM error: {}", e)))
+        })?;
+        self.wasm_views.view(py, store)
+    }
+
+    /// 把 SharedBuffer 定义为全局 SharedArrayBuffer（与 Context.share_buffer() 相同）
+    fn share_buffer(&self, py: Python<'_>, name: String, buffer: PyRef<'_, SharedBuffer>) -> PyResult<()> {
+        let store = buffer.store();
+        self.run(py, move |ctx| {
+            ctx.share_buffer_store(&name, store)
+                .map_err(|e| PyException::new_err(format!("Shared buffer error: {}", e)))
+        })
+    }
+
+    /// 设置慢脚本回调（参数与 Context.on_slow_script() 相同，回调在工作线程上运行）
+    #[pyo3(signature = (threshold_ms, callback))]
+    fn on_slow_script(&self, py: Python<'_>, threshold_ms: u64, callback: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+        let (threshold, callback) = crate::slow_script::validate(threshold_ms, callback)?;
+        self.run(py, move |ctx| {
+            ctx.set_slow_script(threshold, callback);
+            Ok(())
+        })
+    }
+
+    /// 设置 GC 事件回调（参数与 Context.on_gc() 相同，回调在工作线程上运行）
+    #[pyo3(signature = (callback))]
+    fn on_gc(&self, py: Python<'_>, callback: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+        let callback = crate::gc_events::validate(callback)?;
+        self.run(py, move |ctx| {
+            ctx.set_gc_callback(callback);
+            Ok(())
+        })
+    }
+
+    /// 告诉 V8 当前处于空闲期（与 Context.idle() 相同）
+    #[pyo3(signature = (idle_ms=50))]
+    fn idle(&self, py: Python<'_>, idle_ms: u64) -> PyResult<()> {
+        self.run(py, move |ctx| {
+            ctx.idle_notification(idle_ms)
+                .map_err(|e| PyException::new_err(format!("Idle error: {}", e)))
+        })
+    }
+
+    /// 获取执行统计信息（参数和返回值与 Context.get_stats() 相同）
+    #[pyo3(signature = (timings=false))]
+    fn get_stats<'py>(&self, py: Python<'py>, timings: bool) -> PyResult<Bound<'py, PyAny>> {
+        let stats = self.run(py, |ctx| Ok(ctx.stats()))?;
+        stats_to_python(py, &stats, timings)
+    }
+
+    /// 获取最近一次调用的 op 审计日志（返回值与 Context.get_audit_log() 相同）
+    fn get_audit_log<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let (entries, dropped) = self.run(py, |ctx| ctx.audit_log())?;
+        crate::audit::to_python(py, &entries, dropped)
+    }
+
+    /// 获取资源配额使用情况（返回值与 Context.get_quota_usage() 相同）
+    fn get_quota_usage<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.run(py, |ctx| ctx.quota_usage())?.to_python(py)
+    }
+
+    /// 获取执行历史（返回值与 Context.history() 相同）
+    fn history<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let entries = self.run(py, |ctx| ctx.history_entries())?;
+        crate::history::to_python(py, &entries)
+    }
+
+    /// 重置统计信息
+    fn reset_stats(&self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, |ctx| ctx.reset_stats())
+    }
+
+    /// 获取 V8 堆内存统计信息
+    fn get_heap_statistics(&self, py: Python<'_>) -> PyResult<Py<PyDict>> {
+        let stats = self.run(py, |ctx| {
+            ctx.get_heap_stats()
+                .map_err(|e| PyException::new_err(format!("Failed to get heap statistics: {}", e)))
+        })?;
+
+        let dict = PyDict::new(py);
+        for (key, value) in stats {
+            dict.set_item(key, value)?;
+        }
+        Ok(dict.into())
+    }
+
+    /// 导出 V8 堆快照（Chrome DevTools 格式）
+    fn take_heap_snapshot(&self, py: Python<'_>, file_path: String) -> PyResult<()> {
+        self.run(py, move |ctx| ctx.take_heap_snapshot(file_path))
+    }
+
+    /// 获取 $terminate() 保存的 Hook 数据（在专用线程的 Context 中读取）
+    fn get_hook_data(&self, py: Python<'_>) -> PyResult<Option<String>> {
+        self.run(py, |ctx| Ok(ctx.saved_hook_data()))
+    }
+
+    /// 清空保存的 Hook 数据
+    fn clear_hook_data(&self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, |ctx| {
+            ctx.clear_saved_hook_data();
+            Ok(())
+        })
+    }
+
+    /// 关闭上下文：等待正在执行的任务完成后销毁专用线程
+    ///
+    /// 关闭后再调用任何方法都会抛出异常。重复调用是安全的。
+    fn close(&self, py: Python<'_>) {
+        py.detach(|| self.shutdown());
+        self.wasm_views.release_all(py);
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (_exc_type=None, _exc_value=None, _traceback=None))]
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> bool {
+        self.close(py);
+        false
+    }
+}
+
+impl Drop for ThreadedContext {
+    fn drop(&mut self) {
+        // 对象通常在持有 GIL 时被回收，而工作线程投递 asyncio 结果需要 GIL，
+        // 等待线程退出前必须先释放 GIL
+        Python::attach(|py| {
+            py.detach(|| self.shutdown());
+            if crate::fork::is_inherited(self.fork_generation) {
+                self.wasm_views.forget();
+            } else {
+                self.wasm_views.release_all(py);
+            }
+        });
+    }
+}
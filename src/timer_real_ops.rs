@@ -1,11 +1,20 @@
 // timer_real_ops.rs - Real async timer implementation using tokio::time::sleep
 //
 // Uses tokio::time::sleep which works with the Tokio runtime
-
-use deno_core::{extension, op2};
+//
+// 另外提供单调时钟和不占用 CPU 的等待（Deno.core.ops）：
+// - op_now_ns(): 进程级单调时钟的纳秒数（BigInt），不受系统时间调整影响，所有 Context 共享同一个起点
+// - op_sleep(ms): 返回 Promise，等待 ms 毫秒后以 true 完成。等待在事件循环中进行，
+//   超出 timeout_ms 时调用在截止时间结束而不是等到 sleep 结束；执行被终止（超时、cpu_limit_ms、
+//   quotas、max_heap_mb 等）时，未完成的 sleep 立即以 false 完成，不会拖住之后的调用。
+//   以 false 完成而不是 reject，避免被终止调用遗留的 Promise 在下一次调用中成为未处理的 rejection
+
+use deno_core::{extension, op2, CancelFuture, CancelHandle, OpState};
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 // Thread-local storage for timer tracking
 thread_local! {
@@ -91,6 +100,48 @@ pub async fn op_set_interval_real(
     should_execute
 }
 
+// ============================================
+// Monotonic clock and sleep
+// ============================================
+
+/// op_now_ns() 的起点（第一次调用时）
+static CLOCK_ORIGIN: OnceLock<Instant> = OnceLock::new();
+
+/// Context 中未完成的 op_sleep()，执行被终止时一起中断（存放在 OpState）
+#[derive(Default)]
+pub struct SleepCancel(RefCell<Rc<CancelHandle>>);
+
+impl SleepCancel {
+    /// 中断当前所有未完成的 sleep，之后的 sleep 使用新的句柄
+    pub fn interrupt(&self) {
+        let handle = self.0.replace(CancelHandle::new_rc());
+        handle.cancel();
+    }
+
+    fn handle(&self) -> Rc<CancelHandle> {
+        self.0.borrow().clone()
+    }
+}
+
+#[op2(fast)]
+#[bigint]
+/// Nanoseconds on a process-wide monotonic clock
+pub fn op_now_ns() -> u64 {
+    CLOCK_ORIGIN.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+#[op2(async)]
+/// Sleep without blocking the thread; resolves false when interrupted by termination
+pub async fn op_sleep(state: Rc<RefCell<OpState>>, ms: f64) -> bool {
+    let handle = state
+        .borrow()
+        .try_borrow::<Rc<SleepCancel>>()
+        .map(|sleeps| sleeps.handle())
+        .unwrap_or_else(CancelHandle::new_rc);
+    let duration = Duration::from_secs_f64(if ms.is_finite() { ms.max(0.0) / 1000.0 } else { 0.0 });
+    tokio::time::sleep(duration).or_cancel(handle).await.is_ok()
+}
+
 // ============================================
 // Extension Definition
 // ============================================
@@ -102,5 +153,7 @@ extension!(
         op_set_interval_real,
         op_get_timer_id,
         op_clear_timer,
+        op_now_ns,
+        op_sleep,
     ],
 );
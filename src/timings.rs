@@ -0,0 +1,54 @@
+// timings.rs - 执行耗时分解
+//
+// 一次 evaluate()/call() 的耗时分为四段：
+// - wrap: 准备求值（首次编译包装函数、把代码和参数转换为 V8 值）
+// - compile: 编译脚本（compile()/compile_file()/eval() 执行全局脚本时）
+// - execute: 同步执行 JS（evaluate()/call() 的代码在包装函数内通过 eval 编译执行，
+//   V8 不单独报告这部分编译时间，计入 execute）
+// - event_loop: 运行事件循环，等待 Promise / 定时器
+//
+// Context 记录最近一次调用和累计的耗时，通过 get_stats(timings=True) 或
+// evaluate()/call() 的 return_timings=True 查看。
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::time::Duration;
+
+/// 各阶段耗时
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Timings {
+    pub wrap: Duration,
+    pub compile: Duration,
+    pub execute: Duration,
+    pub event_loop: Duration,
+}
+
+impl Timings {
+    /// 累加另一组耗时
+    pub fn add(&mut self, other: &Timings) {
+        self.wrap += other.wrap;
+        self.compile += other.compile;
+        self.execute += other.execute;
+        self.event_loop += other.event_loop;
+    }
+
+    pub fn total(&self) -> Duration {
+        self.wrap + self.compile + self.execute + self.event_loop
+    }
+
+    /// 转换为 Python 字典（单位：毫秒）
+    pub fn to_py_dict<'py>(self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("wrap_ms", as_ms(self.wrap))?;
+        dict.set_item("compile_ms", as_ms(self.compile))?;
+        dict.set_item("execute_ms", as_ms(self.execute))?;
+        dict.set_item("event_loop_ms", as_ms(self.event_loop))?;
+        dict.set_item("total_ms", as_ms(self.total()))?;
+        Ok(dict)
+    }
+}
+
+fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
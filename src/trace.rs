@@ -0,0 +1,41 @@
+// trace.rs - call(name, args, trace_id="...")：把请求的追踪 ID 带进 JS
+//
+// 服务端一次请求触发的调用带上 trace_id 后，JS 和 Python 两侧的日志可以按请求关联：
+// - 调用期间 JS 中的 globalThis.__trace_id 为该 ID，结束后删除
+// - 写到 stdout / stderr 的 console 输出加上 [trace_id=...] 前缀
+// - 执行钩子的字典带有 trace_id 键（没有时为 None）
+// - 调用失败时抛出的异常带有 trace_id 属性
+
+use anyhow::{anyhow, Result};
+use deno_core::{v8, JsRuntime};
+use pyo3::prelude::*;
+
+/// JS 中保存追踪 ID 的全局变量
+const TRACE_GLOBAL: &str = "__trace_id";
+
+/// 设置（trace_id 为 None 时删除）全局变量 __trace_id
+pub fn set_global(runtime: &mut JsRuntime, trace_id: Option<&str>) -> Result<()> {
+    deno_core::scope!(scope, runtime);
+    let global = scope.get_current_context().global(scope);
+    let key = v8::String::new(scope, TRACE_GLOBAL).ok_or_else(|| anyhow!("Failed to create string"))?;
+    let defined = match trace_id {
+        Some(trace_id) => {
+            let value = v8::String::new(scope, trace_id).ok_or_else(|| anyhow!("trace_id is too long"))?;
+            global.set(scope, key.into(), value.into())
+        }
+        None => global.delete(scope, key.into()),
+    };
+    defined
+        .filter(|ok| *ok)
+        .map(drop)
+        .ok_or_else(|| anyhow!("Failed to set globalThis.{}", TRACE_GLOBAL))
+}
+
+/// 在调用失败的异常上设置 trace_id 属性
+pub fn tag_error(py: Python<'_>, error: PyErr, trace_id: Option<&str>) -> PyErr {
+    if let Some(trace_id) = trace_id {
+        // 异常实例不接受属性时（极少见）只返回原异常
+        let _ = error.value(py).setattr("trace_id", trace_id);
+    }
+    error
+}
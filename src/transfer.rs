@@ -0,0 +1,98 @@
+// transfer.rs - transfer(src, "name", dst)：在同一进程的两个 Context 之间复制全局变量
+//
+// 跨 isolate 拆分的流水线（解析在一个 Context、签名在另一个 Context）需要传递中间结果，
+// 经过 Python（JSON 或 evaluate() 的结果转换）会丢失 Map / Set / 类型化数组 / Date 等类型，
+// 大的类型化数组还要复制成 Python 对象。transfer() 直接用 V8 结构化克隆（与 export_state() 相同的函数，
+// state.rs 的 Mode::Transfer）序列化 src 的全局变量，在 dst 中反序列化为同名（或 dst_name）全局变量：
+// - 语义是复制：src 中的变量保持不变，dst 得到一份独立的副本
+// - SharedArrayBuffer 和 WebAssembly.Module 不复制，两个 Context 共享同一块内存 / 同一个模块
+// - 函数、Symbol 等无法克隆的值抛出 DataCloneError；全局 let/const 声明不是全局对象的属性，同样无法传递
+// - src / dst 可以是 Context 或 ThreadedContext（在各自的线程上执行）
+//
+// 与 import_state() 相同，接收过值的 Context 不能 pickle。
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::marker::Ungil;
+use pyo3::prelude::*;
+
+use crate::context::Context;
+use crate::quota::py_error;
+use crate::threaded::ThreadedContext;
+
+/// transfer() 的一端
+enum Endpoint<'py> {
+    Local(Bound<'py, Context>),
+    Threaded(Bound<'py, ThreadedContext>),
+}
+
+impl<'py> Endpoint<'py> {
+    fn from_py(value: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(ctx) = value.cast::<Context>() {
+            return Ok(Endpoint::Local(ctx.clone()));
+        }
+        if let Ok(ctx) = value.cast::<ThreadedContext>() {
+            return Ok(Endpoint::Threaded(ctx.clone()));
+        }
+        Err(PyTypeError::new_err("src/dst must be a Context or ThreadedContext"))
+    }
+
+    /// 在该端的 Context 上执行 f（ThreadedContext 在其专用线程上，均释放 GIL）
+    fn run<R, F>(&self, py: Python<'_>, f: F) -> PyResult<R>
+    where
+        R: Send + Ungil + 'static,
+        F: FnOnce(&Context) -> PyResult<R> + Send + Ungil + 'static,
+    {
+        match self {
+            Endpoint::Local(ctx) => {
+                let ctx = ctx.borrow();
+                ctx.check_fork()?;
+                ctx.without_gil(py, f)
+            }
+            Endpoint::Threaded(ctx) => ctx.borrow().run(py, f),
+        }
+    }
+}
+
+/// 把 src 的全局变量 name 以结构化克隆复制到 dst
+///
+/// 不经过 Python / JSON，Map、Set、Date、类型化数组、循环引用等原样保留；
+/// SharedArrayBuffer 和 WebAssembly.Module 在两个 Context 之间共享而不是复制。
+/// 接收值之后 dst 不能再 pickle / deepcopy。
+///
+/// Args:
+///     src: 源 Context 或 ThreadedContext
+///     name: src 中的全局变量名
+///     dst: 目标 Context 或 ThreadedContext（可以与 src 相同）
+///     dst_name: dst 中的全局变量名，默认与 name 相同（覆盖同名变量）
+///
+/// Raises:
+///     TypeError: src / dst 不是 Context 或 ThreadedContext
+///     Exception: 变量不存在（ReferenceError）或无法克隆（DataCloneError）
+///
+/// Example:
+///     ```python
+///     parser = never_jscore.Context()
+///     signer = never_jscore.Context()
+///     parser.eval("globalThis.parsed = new Map([['body', new Uint8Array([1, 2, 3])]])")
+///     never_jscore.transfer(parser, "parsed", signer)
+///     signer.evaluate("parsed.get('body') instanceof Uint8Array")  # True
+///     ```
+#[pyfunction]
+#[pyo3(signature = (src, name, dst, dst_name=None))]
+pub fn transfer(
+    py: Python<'_>,
+    src: &Bound<'_, PyAny>,
+    name: String,
+    dst: &Bound<'_, PyAny>,
+    dst_name: Option<String>,
+) -> PyResult<()> {
+    let src = Endpoint::from_py(src)?;
+    let dst = Endpoint::from_py(dst)?;
+    let dst_name = dst_name.unwrap_or_else(|| name.clone());
+
+    let payload = src.run(py, move |ctx| ctx.send_global(&name).map_err(|e| py_error("Transfer error", e)))?;
+    dst.run(py, move |ctx| {
+        ctx.receive_global(payload, &dst_name)
+            .map_err(|e| py_error("Transfer error", e))
+    })
+}
@@ -0,0 +1,93 @@
+// undefined_policy.rs - Context(undefined_as=...)：结果中 undefined 的转换策略
+//
+// 结果经 JSON 传给 Python，默认沿用 JSON.stringify 的语义，同一个 undefined 在不同位置的结果并不一致：
+// 顶层结果为 None，对象中值为 undefined 的属性被省略，数组中的 undefined 和空位为 None。
+// 构造 Context 时可以选择统一的策略，对顶层结果、对象的值和数组元素（包括空位）同样生效：
+// - "none":     一律转换为 None（对象保留该键）
+// - "sentinel": 一律转换为 never_jscore.UNDEFINED，与 null 转换的 None 区分开
+// - "omit":     对象省略该键，数组删除该元素（后面的元素前移）；顶层结果无处省略，为 None
+//
+// 策略在 JS 侧序列化时应用（见 context.rs 的 RESULT_TO_JSON），sentinel 用带标记（UNDEFINED_TAG）的对象表示，
+// convert.rs 据此转换为 UNDEFINED。result_format="json_str" / "orjson" 不经过转换，得到的是标记对象本身。
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::sync::PyOnceLock;
+
+/// sentinel 策略下表示 undefined 的 JSON 对象的键
+pub const UNDEFINED_TAG: &str = "__never_jscore_undefined__";
+
+/// 结果中 undefined 的转换策略
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UndefinedAs {
+    /// JSON.stringify 的语义（默认）
+    #[default]
+    Json,
+    None,
+    Sentinel,
+    Omit,
+}
+
+impl UndefinedAs {
+    /// 解析 undefined_as 参数
+    pub fn from_py(value: Option<&str>) -> PyResult<Self> {
+        match value {
+            None => Ok(UndefinedAs::Json),
+            Some("none") => Ok(UndefinedAs::None),
+            Some("sentinel") => Ok(UndefinedAs::Sentinel),
+            Some("omit") => Ok(UndefinedAs::Omit),
+            Some(other) => Err(PyValueError::new_err(format!(
+                "Invalid undefined_as '{}': expected 'none', 'sentinel' or 'omit'",
+                other
+            ))),
+        }
+    }
+
+    /// 参数名称（默认策略为 None），也是传给 RESULT_TO_JSON 的值
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            UndefinedAs::Json => None,
+            UndefinedAs::None => Some("none"),
+            UndefinedAs::Sentinel => Some("sentinel"),
+            UndefinedAs::Omit => Some("omit"),
+        }
+    }
+}
+
+/// JavaScript 的 undefined（undefined_as="sentinel" 时的转换结果）
+///
+/// 只有一个实例 never_jscore.UNDEFINED，用 `is` 比较；布尔值为 False。
+///
+/// Example:
+///     ```python
+///     ctx = never_jscore.Context(undefined_as="sentinel")
+///     ctx.evaluate("({a: undefined, b: null})")  # {'a': never_jscore.UNDEFINED, 'b': None}
+///     ctx.evaluate("[1, , 3]")[1] is never_jscore.UNDEFINED  # True
+///     ```
+#[pyclass(module = "never_jscore", name = "JsUndefined", frozen)]
+pub struct JsUndefined;
+
+#[pymethods]
+impl JsUndefined {
+    fn __repr__(&self) -> &'static str {
+        "never_jscore.UNDEFINED"
+    }
+
+    fn __bool__(&self) -> bool {
+        false
+    }
+
+    /// pickle / copy 按名称引用同一个实例
+    fn __reduce__(&self) -> &'static str {
+        "UNDEFINED"
+    }
+}
+
+static UNDEFINED: PyOnceLock<Py<JsUndefined>> = PyOnceLock::new();
+
+/// never_jscore.UNDEFINED
+pub fn undefined(py: Python<'_>) -> PyResult<Bound<'_, JsUndefined>> {
+    UNDEFINED
+        .get_or_try_init(py, || Py::new(py, JsUndefined))
+        .map(|undefined| undefined.bind(py).clone())
+}
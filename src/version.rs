@@ -0,0 +1,72 @@
+// version.rs - 版本与构建信息（version_info()）
+//
+// 报告 bug 时附上 never_jscore / deno_core / V8 的版本，运行时按 features 判断可用的功能，
+// 不需要依赖 try/except 探测。features 反映当前进程的配置（init() 的 icu / snapshot / jitless 等），
+// 不是编译期开关：这些功能都编译在扩展中。
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::runtime::{runtime_config, v8_initialized, StartupSnapshot};
+
+/// 编译时解析到的 deno_core 版本（deno_core 没有导出版本常量，由 build.rs 从 Cargo.lock 读取）
+const DENO_CORE_VERSION: &str = env!("NEVER_JSCORE_DENO_CORE_VERSION");
+
+/// 返回版本与构建信息
+///
+/// Returns:
+///     dict: 包含以下字段
+///         - version: never_jscore 版本
+///         - deno_core: deno_core 版本
+///         - v8: V8 版本
+///         - source_hash: 源码指纹，内置快照文件按它命名（见 scripts/build_builtin_snapshot.py）
+///         - platform: 编译目标，如 "linux-x86_64"
+///         - initialized: V8 是否已经初始化（已创建过 Context，之后不能再调用 init()）
+///         - features: 功能是否可用（bool）
+///             - icu: Intl 是否可用（init(icu=False) 时为 False）
+///             - snapshot: 未传入 snapshot 的 Context 是否从快照启动
+///               （init(snapshot=False)、没有嵌入内置快照或 init() 修改了 V8 参数时为 False）
+///             - fetch: fetch() / XMLHttpRequest
+///             - wasm: WebAssembly（jitless 模式下为 False）
+///             - wasi: load_wasm(..., wasi=...)
+///             - jitless: 是否以 jitless 模式运行
+///             - temporal: Temporal 日期时间 API
+///
+/// Example:
+///     ```python
+///     import never_jscore
+///
+///     info = never_jscore.version_info()
+///     print(info["version"], info["v8"])
+///     if info["features"]["wasm"]:
+///         never_jscore.Context().load_wasm("sign", wasm_bytes)
+///     ```
+#[pyfunction]
+pub fn version_info(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let config = runtime_config();
+    let jitless = config.jitless || config.v8_flags.iter().any(|flag| flag == "--jitless");
+
+    let features = PyDict::new(py);
+    features.set_item("icu", config.icu)?;
+    let snapshot = match config.snapshot {
+        StartupSnapshot::Builtin => crate::snapshot::builtin_snapshot(false).is_some(),
+        StartupSnapshot::Disabled => false,
+        StartupSnapshot::Custom { .. } => true,
+    };
+    features.set_item("snapshot", snapshot)?;
+    features.set_item("fetch", true)?;
+    features.set_item("wasm", !jitless)?;
+    features.set_item("wasi", !jitless)?;
+    features.set_item("jitless", jitless)?;
+    features.set_item("temporal", true)?;
+
+    let info = PyDict::new(py);
+    info.set_item("version", env!("CARGO_PKG_VERSION"))?;
+    info.set_item("deno_core", DENO_CORE_VERSION)?;
+    info.set_item("v8", deno_core::v8::V8::get_version())?;
+    info.set_item("source_hash", crate::snapshot::SOURCE_HASH)?;
+    info.set_item("platform", format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH))?;
+    info.set_item("initialized", v8_initialized())?;
+    info.set_item("features", features)?;
+    Ok(info)
+}
@@ -0,0 +1,45 @@
+// warmup.rs - warmup(calls=[("sign", [sample])])：在接收真实流量之前预先执行有代表性的调用
+//
+// V8 的函数在第一次调用时才编译，内联缓存和分层编译（Sparkplug / Maglev / TurboFan）也要执行几次之后才生效，
+// 所以新建的 Context 前几次调用明显更慢，直接拉高服务刚启动时的 p99 延迟。warmup() 用样例参数把这些调用
+// 先执行 rounds 遍，结果丢弃：
+// - Context / ThreadedContext 在自身上执行；ContextPool 在每个工作线程上各执行一次（优先于排队中的任务）
+// - 预热调用和普通调用一样会修改全局状态、计入配额和调用次数，样例参数应当没有副作用
+// - compile(recycle_after=...) 重建的 Context 不会自动预热
+
+use anyhow::{anyhow, Result};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use serde_json::Value as JsonValue;
+
+use crate::context::Context;
+use crate::convert::call_args_to_json;
+
+/// 一个预热调用：函数名和参数
+pub type WarmupCall = (String, Vec<JsonValue>);
+
+/// 解析 warmup() 的 calls 参数：(name, args) 的可迭代对象，args 与 call() 的规则相同
+pub fn parse_calls(calls: &Bound<'_, PyAny>, rounds: usize) -> PyResult<Vec<WarmupCall>> {
+    if rounds == 0 {
+        return Err(PyValueError::new_err("rounds must be at least 1"));
+    }
+    let mut parsed = Vec::new();
+    for item in calls.try_iter()? {
+        let (name, args) = item?
+            .extract::<(String, Bound<'_, PyAny>)>()
+            .map_err(|_| PyTypeError::new_err("warmup calls must be (name, args) pairs"))?;
+        parsed.push((name, call_args_to_json(&args)?));
+    }
+    Ok(parsed)
+}
+
+/// 依次执行所有预热调用 rounds 遍，丢弃结果；任意一个调用失败时停止
+pub fn run(ctx: &Context, calls: &[WarmupCall], rounds: usize, auto_await: bool) -> Result<()> {
+    for _ in 0..rounds {
+        for (name, args) in calls {
+            ctx.execute_call(name, args, auto_await)
+                .map_err(|e| anyhow!("{}() failed: {}", name, e))?;
+        }
+    }
+    Ok(())
+}
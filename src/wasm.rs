@@ -0,0 +1,301 @@
+// wasm.rs - 从 Python 提供的字节加载 WebAssembly 模块（ctx.load_wasm(name, wasm_bytes, imports=None)）
+//
+// 字节直接移入 V8 的 ArrayBuffer（不经过 base64 / JSON），用 WebAssembly.Module / Instance 同步编译和实例化，
+// 实例的 exports 对象定义为全局变量 `name`：JS 中通过 name.fn(...) 调用，Python 中通过 ctx.call("name.fn", [...]) 调用。
+//
+// imports 为 {模块名: {字段名: 值}}：
+// - Python 可调用对象：与 register() 相同，参数和返回值经 JSON 转换
+// - 数字：导入为数值（用于 global 导入）
+// - 字符串：在全局作用域求值的 JS 表达式，如 "console.log" 或已有的 WebAssembly.Memory
+//
+// wasi=True / {...} 时提供最小 WASI 宿主（wasi_snapshot_preview1，见 dddd_js/wasi.js），
+// 可以直接运行 wasi-sdk 编译的程序：命令行参数、环境变量、时钟、随机数，stdout / stderr 输出到 console。
+// 没有预打开目录，不能访问文件系统；宿主环境变量只在 inherit_env=True 时传入（设置了 Context(env=...) 时为注入的变量），
+// 并受 Context 的 env 权限限制。
+
+use anyhow::{anyhow, Result};
+use deno_core::{v8, JsRuntime};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::code_cache::exception_to_error;
+use crate::permissions;
+
+/// WASI 宿主：求值为工厂函数 (config, stdin) => { imports, start(instance) }
+const WASI_HOST: &str = include_str!("dddd_js/wasi.js");
+
+/// 一个导入值
+pub enum ImportValue {
+    Python(Py<PyAny>),
+    Number(f64),
+    Js(String),
+}
+
+/// imports 中的一项
+pub struct Import {
+    module: String,
+    field: String,
+    value: ImportValue,
+}
+
+/// 解析 load_wasm() 的 imports 参数
+pub fn imports_from_py(imports: Option<&Bound<'_, PyDict>>) -> PyResult<Vec<Import>> {
+    let mut result = Vec::new();
+    let Some(imports) = imports else {
+        return Ok(result);
+    };
+    for (module, fields) in imports.iter() {
+        let module: String = module.extract()?;
+        let fields = fields
+            .cast::<PyDict>()
+            .map_err(|_| PyTypeError::new_err(format!("imports['{}'] must be a dict", module)))?;
+        for (field, value) in fields.iter() {
+            let field: String = field.extract()?;
+            let value = if let Ok(expr) = value.extract::<String>() {
+                ImportValue::Js(expr)
+            } else if value.is_callable() {
+                ImportValue::Python(value.unbind())
+            } else if let Ok(number) = value.extract::<f64>() {
+                ImportValue::Number(number)
+            } else {
+                return Err(PyTypeError::new_err(format!(
+                    "imports['{}']['{}'] must be a callable, a number or a JS expression string",
+                    module, field
+                )));
+            };
+            result.push(Import {
+                module: module.clone(),
+                field,
+                value,
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// load_wasm() 的 wasi 参数
+#[derive(Default)]
+pub struct WasiConfig {
+    /// 命令行参数（默认为 [name]）
+    args: Option<Vec<String>>,
+    env: Vec<(String, String)>,
+    /// 同时传入宿主的环境变量（受 env 权限限制）
+    inherit_env: bool,
+    stdin: Vec<u8>,
+}
+
+/// 解析 load_wasm() 的 wasi 参数：None / False 不启用，True 使用默认配置，
+/// 或 {"args": [...], "env": {...}, "inherit_env": bool, "stdin": bytes | str}
+pub fn wasi_from_py(wasi: Option<&Bound<'_, PyAny>>) -> PyResult<Option<WasiConfig>> {
+    let Some(wasi) = wasi.filter(|wasi| !wasi.is_none()) else {
+        return Ok(None);
+    };
+    if let Ok(enabled) = wasi.extract::<bool>() {
+        return Ok(enabled.then(WasiConfig::default));
+    }
+    let options = wasi
+        .cast::<PyDict>()
+        .map_err(|_| PyTypeError::new_err("wasi must be a bool or a dict"))?;
+    let mut config = WasiConfig::default();
+    for (key, value) in options.iter() {
+        let key: String = key.extract()?;
+        match key.as_str() {
+            "args" => config.args = Some(value.extract()?),
+            "env" => {
+                let env = value
+                    .cast::<PyDict>()
+                    .map_err(|_| PyTypeError::new_err("wasi['env'] must be a dict"))?;
+                for (name, value) in env.iter() {
+                    config.env.push((name.extract()?, value.extract()?));
+                }
+            }
+            "inherit_env" => config.inherit_env = value.extract()?,
+            "stdin" => {
+                config.stdin = match value.extract::<String>() {
+                    Ok(text) => text.into_bytes(),
+                    Err(_) => value.extract::<Vec<u8>>().map_err(|_| PyTypeError::new_err("wasi['stdin'] must be bytes or str"))?,
+                }
+            }
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown wasi option '{}', expected 'args', 'env', 'inherit_env' or 'stdin'",
+                    key
+                )))
+            }
+        }
+    }
+    Ok(Some(config))
+}
+
+impl WasiConfig {
+    /// 生成传给 wasi.js 的配置 {args, env}（读取宿主环境变量时检查 env 权限）
+    fn host_config(&self, runtime: &mut JsRuntime, name: &str) -> Result<serde_json::Value> {
+        let mut env = Vec::new();
+        if self.inherit_env {
+            let state = runtime.op_state();
+            let state = state.borrow();
+            if permissions::env_denied(&state) {
+                return Err(anyhow!("{}access to environment variables is not allowed", permissions::DENIED_PREFIX));
+            }
+            env.extend(crate::fs_ops::env_vars(&state).into_iter().filter(|(key, _)| {
+                permissions::env_visible(&state, key) && !self.env.iter().any(|(name, _)| name == key)
+            }));
+        }
+        env.extend(self.env.iter().cloned());
+        let args = self.args.clone().unwrap_or_else(|| vec![name.to_string()]);
+        Ok(serde_json::json!({ "args": args, "env": env }))
+    }
+}
+
+/// 编译并实例化模块，把 exports 定义为全局变量 `name`，返回导出项 [(名称, 类型)]
+pub fn load(
+    runtime: &mut JsRuntime,
+    name: &str,
+    bytes: Vec<u8>,
+    imports: Vec<Import>,
+    wasi: Option<WasiConfig>,
+) -> Result<Vec<(String, String)>> {
+    let wasi = match wasi {
+        Some(config) => Some((config.host_config(runtime, name)?, config.stdin)),
+        None => None,
+    };
+
+    deno_core::scope!(scope, runtime);
+    v8::tc_scope!(let tc_scope, scope);
+    let global = tc_scope.get_current_context().global(tc_scope);
+
+    let import_object = v8::Object::new(tc_scope);
+
+    // WASI 宿主的导入模块先放入，imports 中同名的字段可以覆盖其中的函数
+    let wasi_host = match wasi {
+        Some((config, stdin)) => {
+            let source = new_string(tc_scope, WASI_HOST)?;
+            let factory = v8::Script::compile(tc_scope, source, None)
+                .and_then(|script| script.run(tc_scope))
+                .and_then(|value| value.try_cast::<v8::Function>().ok())
+                .ok_or_else(|| exception_to_error(tc_scope))?;
+            let config = new_string(tc_scope, &config.to_string())?;
+            let config = v8::json::parse(tc_scope, config).ok_or_else(|| exception_to_error(tc_scope))?;
+            let store = v8::ArrayBuffer::new_backing_store_from_vec(stdin).make_shared();
+            let stdin = v8::ArrayBuffer::with_backing_store(tc_scope, &store);
+            let undefined = v8::undefined(tc_scope);
+            let host = factory
+                .call(tc_scope, undefined.into(), &[config, stdin.into()])
+                .and_then(|value| value.try_cast::<v8::Object>().ok())
+                .ok_or_else(|| exception_to_error(tc_scope))?;
+            let wasi_imports = get_object(tc_scope, host, "imports").ok_or_else(|| anyhow!("Invalid WASI host"))?;
+            let key = new_string(tc_scope, "wasi_snapshot_preview1")?;
+            import_object.set(tc_scope, key.into(), wasi_imports.into());
+            Some(host)
+        }
+        None => None,
+    };
+
+    for import in imports {
+        let module_key = new_string(tc_scope, &import.module)?;
+        let module = match import_object.get(tc_scope, module_key.into()).and_then(|value| value.try_cast::<v8::Object>().ok()) {
+            Some(module) => module,
+            None => {
+                let module = v8::Object::new(tc_scope);
+                import_object.set(tc_scope, module_key.into(), module.into());
+                module
+            }
+        };
+        let value: v8::Local<v8::Value> = match import.value {
+            ImportValue::Python(func) => {
+                let key = format!("{}.{}.{}", name, import.module, import.field);
+                crate::py_functions::new_function(tc_scope, &key, func)?.into()
+            }
+            ImportValue::Number(number) => v8::Number::new(tc_scope, number).into(),
+            ImportValue::Js(expr) => {
+                let source = new_string(tc_scope, &expr)?;
+                let script = v8::Script::compile(tc_scope, source, None).ok_or_else(|| exception_to_error(tc_scope))?;
+                script.run(tc_scope).ok_or_else(|| exception_to_error(tc_scope))?
+            }
+        };
+        let field_key = new_string(tc_scope, &import.field)?;
+        module.set(tc_scope, field_key.into(), value);
+    }
+
+    let webassembly = get_object(tc_scope, global, "WebAssembly")
+        .ok_or_else(|| anyhow!("WebAssembly is not available (it is disabled in jitless mode)"))?;
+    let module_class = get_object(tc_scope, webassembly, "Module").and_then(|value| value.try_cast::<v8::Function>().ok());
+    let instance_class = get_object(tc_scope, webassembly, "Instance").and_then(|value| value.try_cast::<v8::Function>().ok());
+    let (Some(module_class), Some(instance_class)) = (module_class, instance_class) else {
+        return Err(anyhow!("WebAssembly.Module / WebAssembly.Instance are not available"));
+    };
+
+    let store = v8::ArrayBuffer::new_backing_store_from_vec(bytes).make_shared();
+    let buffer = v8::ArrayBuffer::with_backing_store(tc_scope, &store);
+    let module = module_class
+        .new_instance(tc_scope, &[buffer.into()])
+        .ok_or_else(|| exception_to_error(tc_scope))?;
+    let instance = instance_class
+        .new_instance(tc_scope, &[module.into(), import_object.into()])
+        .ok_or_else(|| exception_to_error(tc_scope))?;
+    let exports = match wasi_host {
+        // 绑定内存、运行 _initialize，_start 包装为返回退出码
+        Some(host) => get_object(tc_scope, host, "start")
+            .and_then(|start| start.try_cast::<v8::Function>().ok())
+            .and_then(|start| start.call(tc_scope, host.into(), &[instance.into()]))
+            .and_then(|value| value.try_cast::<v8::Object>().ok())
+            .ok_or_else(|| exception_to_error(tc_scope))?,
+        None => get_object(tc_scope, instance, "exports").ok_or_else(|| anyhow!("WebAssembly instance has no exports"))?,
+    };
+
+    let key = new_string(tc_scope, name)?;
+    global
+        .set(tc_scope, key.into(), exports.into())
+        .ok_or_else(|| anyhow!("Failed to define global '{}'", name))?;
+
+    // WebAssembly.Module.exports(module): [{name, kind}]
+    let descriptors = get_object(tc_scope, module_class.into(), "exports")
+        .and_then(|value| value.try_cast::<v8::Function>().ok())
+        .and_then(|describe| describe.call(tc_scope, module_class.into(), &[module.into()]))
+        .and_then(|value| v8::json::stringify(tc_scope, value))
+        .ok_or_else(|| anyhow!("Failed to list WebAssembly exports"))?
+        .to_rust_string_lossy(tc_scope);
+    let descriptors: Vec<serde_json::Value> = serde_json::from_str(&descriptors)?;
+    Ok(descriptors
+        .iter()
+        .map(|item| {
+            let field = |key: &str| item[key].as_str().unwrap_or_default().to_string();
+            (field("name"), field("kind"))
+        })
+        .collect())
+}
+
+/// 编译错误提示需要开启 V8 特性（"enable with --experimental-wasm-exnref"）时，补充对应的 init() 写法
+pub fn feature_hint(message: String) -> String {
+    let feature = message
+        .split("--experimental-wasm-")
+        .nth(1)
+        .and_then(|rest| rest.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-')).next())
+        .and_then(crate::runtime::wasm_feature_for_flag);
+    match feature {
+        Some(feature) => format!(
+            "{}\nhint: call never_jscore.init(wasm_features={{'{}': True}}) before creating the first Context",
+            message, feature
+        ),
+        None => message,
+    }
+}
+
+/// 转换为 load_wasm() 的返回值 {名称: 类型}
+pub fn exports_to_python(py: Python<'_>, exports: Vec<(String, String)>) -> PyResult<Bound<'_, PyDict>> {
+    let result = PyDict::new(py);
+    for (name, kind) in exports {
+        result.set_item(name, kind)?;
+    }
+    Ok(result)
+}
+
+fn new_string<'s>(scope: &mut v8::PinScope<'s, '_>, value: &str) -> Result<v8::Local<'s, v8::String>> {
+    v8::String::new(scope, value).ok_or_else(|| anyhow!("String is too large"))
+}
+
+fn get_object<'s>(scope: &mut v8::PinScope<'s, '_>, object: v8::Local<'s, v8::Object>, key: &str) -> Option<v8::Local<'s, v8::Object>> {
+    let key = v8::String::new(scope, key)?;
+    object.get(scope, key.into())?.try_cast::<v8::Object>().ok()
+}
@@ -0,0 +1,145 @@
+// wasm_memory.rs - 零拷贝访问 WASM 线性内存（ctx.wasm_memory(expr)）
+//
+// 返回直接指向 V8 backing store 的可写 memoryview，Python 读写 WASM 代码使用的缓冲区时不经过 JS / JSON。
+// 视图存在期间 backing store 由 MemoryViews 持有引用，内存不会被释放：
+// - memory.grow() 之后旧视图仍然可以安全访问，但长度不变，也可能不再是当前的内存；需要重新调用 wasm_memory()
+// - Context 关闭 / 销毁时释放所有视图（之后访问抛出 ValueError）。仍有切片、np.frombuffer() 等
+//   派生对象引用这块内存时，backing store 保留到进程退出，Python 永远不会访问已释放的内存
+// - 视图不与 JS 执行同步，ThreadedContext 正在执行 JS 时读写需要调用方自行协调
+//
+// abi3 下 pyclass 无法实现缓冲区协议，这里用 PyMemoryView_FromMemory 创建不持有所有者的内部视图，
+// 交给 Python 的是基于它的二级视图：二级视图的切片和导出都挂在内部视图上，
+// 内部视图 release() 成功就说明已经没有任何对象引用这块内存。
+
+use anyhow::{anyhow, Result};
+use deno_core::{v8, JsRuntime};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::{PyMemoryView, PyWeakrefReference};
+use std::ffi::{c_char, c_int};
+use std::ptr::NonNull;
+use std::sync::Mutex;
+
+use crate::code_cache::exception_to_error;
+
+/// PyBUF_WRITE（abi3 下 pyo3::ffi 只在 Python 3.11+ 导出）
+const PYBUF_WRITE: c_int = 0x200;
+
+/// 持有引用的 backing store（shared_ptr 的引用计数是原子的，可以在线程之间传递）
+#[derive(Clone)]
+pub struct PinnedStore(v8::SharedRef<v8::BackingStore>);
+
+unsafe impl Send for PinnedStore {}
+// SharedBuffer 在线程之间共享：只读取指针和长度，内容由 JS / Python 自行同步
+unsafe impl Sync for PinnedStore {}
+
+impl PinnedStore {
+    /// 分配一块清零的共享内存（SharedBuffer），不属于任何 isolate
+    pub fn new_shared(size: usize) -> Self {
+        let store = v8::SharedArrayBuffer::new_backing_store_from_boxed_slice(vec![0u8; size].into_boxed_slice());
+        PinnedStore(store.make_shared())
+    }
+
+    pub fn byte_length(&self) -> usize {
+        self.0.byte_length()
+    }
+
+    /// 创建使用这块内存的 SharedArrayBuffer
+    pub fn to_shared_array_buffer<'s>(&self, scope: &v8::PinScope<'s, '_>) -> v8::Local<'s, v8::SharedArrayBuffer> {
+        v8::SharedArrayBuffer::with_backing_store(scope, &self.0)
+    }
+}
+
+/// 一个交给 Python 的视图
+struct Pin {
+    store: PinnedStore,
+    /// 不持有所有者的内部视图
+    inner: Py<PyMemoryView>,
+    /// 交给 Python 的二级视图（弱引用，关闭时主动 release）
+    outer: Py<PyWeakrefReference>,
+}
+
+/// Context 交给 Python 的 WASM 内存视图
+#[derive(Default)]
+pub struct MemoryViews {
+    pins: Mutex<Vec<Pin>>,
+}
+
+impl MemoryViews {
+    /// 为 backing store 创建可写的 memoryview
+    pub fn view<'py>(&self, py: Python<'py>, store: PinnedStore) -> PyResult<Bound<'py, PyMemoryView>> {
+        self.prune(py);
+        let data = store.0.data().unwrap_or(NonNull::dangling()).as_ptr() as *mut c_char;
+        let len = store.0.byte_length() as ffi::Py_ssize_t;
+        // SAFETY: store 保存在 Pin 中，内部视图 release() 成功之前不会释放
+        let inner = unsafe { Bound::from_owned_ptr_or_err(py, ffi::PyMemoryView_FromMemory(data, len, PYBUF_WRITE))? }
+            .cast_into::<PyMemoryView>()?;
+        let outer = PyMemoryView::from(inner.as_any())?;
+        let weak = PyWeakrefReference::new(outer.as_any())?;
+        self.pins.lock().unwrap().push(Pin {
+            store,
+            inner: inner.unbind(),
+            outer: weak.unbind(),
+        });
+        Ok(outer)
+    }
+
+    /// 释放已经没有对象引用的 backing store
+    fn prune(&self, py: Python<'_>) {
+        self.pins.lock().unwrap().retain(|pin| !release(pin.inner.bind(py).as_any()));
+    }
+
+    /// 释放所有视图（Context 关闭 / 销毁时调用）
+    pub fn release_all(&self, py: Python<'_>) {
+        let pins = std::mem::take(&mut *self.pins.lock().unwrap());
+        for pin in pins {
+            if let Some(outer) = pin.outer.bind(py).upgrade() {
+                release(&outer);
+            }
+            if !release(pin.inner.bind(py).as_any()) {
+                // 切片 / 导出仍在引用这块内存：保留到进程退出
+                std::mem::forget(pin.store);
+            }
+        }
+    }
+
+    /// fork 后的子进程中不释放 backing store（与 isolate 一样直接泄漏）
+    pub fn forget(&self) {
+        std::mem::forget(std::mem::take(&mut *self.pins.lock().unwrap()));
+    }
+}
+
+/// memoryview.release()：仍有导出时失败
+fn release(view: &Bound<'_, PyAny>) -> bool {
+    view.call_method0("release").is_ok()
+}
+
+/// 求值 JS 表达式，取出 WebAssembly.Memory / ArrayBuffer / SharedArrayBuffer 的 backing store
+pub fn find_store(runtime: &mut JsRuntime, expr: &str) -> Result<PinnedStore> {
+    deno_core::scope!(scope, runtime);
+    v8::tc_scope!(let tc_scope, scope);
+
+    let source = v8::String::new(tc_scope, expr).ok_or_else(|| anyhow!("Expression is too large"))?;
+    let script = v8::Script::compile(tc_scope, source, None).ok_or_else(|| exception_to_error(tc_scope))?;
+    let mut value = script.run(tc_scope).ok_or_else(|| exception_to_error(tc_scope))?;
+
+    // WebAssembly.Memory：取当前的 buffer（grow 之后会变化）
+    if !value.is_array_buffer() && !value.is_shared_array_buffer() && !value.is_array_buffer_view() {
+        if let Ok(object) = value.try_cast::<v8::Object>() {
+            let key = v8::String::new(tc_scope, "buffer").ok_or_else(|| anyhow!("Failed to create string"))?;
+            value = object.get(tc_scope, key.into()).ok_or_else(|| exception_to_error(tc_scope))?;
+        }
+    }
+
+    let store = if let Ok(buffer) = value.try_cast::<v8::ArrayBuffer>() {
+        buffer.get_backing_store()
+    } else if let Ok(buffer) = value.try_cast::<v8::SharedArrayBuffer>() {
+        buffer.get_backing_store()
+    } else {
+        return Err(anyhow!("'{}' is not a WebAssembly.Memory, ArrayBuffer or SharedArrayBuffer", expr));
+    };
+    if store.is_resizable_by_user_javascript() {
+        return Err(anyhow!("Resizable ArrayBuffers are not supported"));
+    }
+    Ok(PinnedStore(store))
+}
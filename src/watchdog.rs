@@ -0,0 +1,333 @@
+// watchdog.rs - 执行时间限制（墙钟超时 timeout_ms 和 CPU 时间预算 cpu_limit_ms）
+//
+// 一个进程级的看门狗线程监视所有正在执行的 Context：
+// - timeout_ms: 从调用开始计算的墙钟时间，包括等待定时器 / Promise 的时间
+// - cpu_limit_ms: 执行线程实际消耗的 CPU 时间（线程 CPU 时钟），
+//   await 一个永远不会完成的 Promise 不消耗 CPU，只受 timeout_ms 限制；
+//   死循环在宽松的 timeout_ms 下也会因为 CPU 时间耗尽被终止
+// - quotas 的 max_cpu_ms: Context 剩余的累计 CPU 时间预算，同样按线程 CPU 时钟检查
+//
+// 超出限制时看门狗记录原因并请求中断 isolate，中断回调在执行线程上终止执行
+// （正则表达式的回溯过程也会响应中断，见 regexp_guard.rs），Context 在执行返回后
+// 取消终止状态并把原因转换为异常。事件循环空闲等待时 JS 不在运行，
+// 墙钟超时由 Context 给事件循环加上截止时间处理。
+//
+// 设置了慢脚本回调时，到达报告阈值后同样请求中断，但不终止执行（见 slow_script.rs）；
+// 设置了 yield_interval_ms 时按间隔请求中断，处理 Python 的信号（见 yield_points.rs）。
+//
+// 线程 CPU 时钟依赖 pthread_getcpuclockid，目前只支持 Linux。
+
+use deno_core::v8;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// 看门狗检查 CPU 时间的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// 执行时间限制
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Limits {
+    pub timeout: Option<Duration>,
+    pub cpu_limit: Option<Duration>,
+    /// 剩余的 CPU 时间配额（quotas 的 max_cpu_ms）
+    pub cpu_quota: Option<Duration>,
+    /// 慢脚本回调的报告阈值（墙钟时间，不终止执行）
+    pub slow: Option<Duration>,
+    /// 处理 Python 信号的间隔（yield_interval_ms，不终止执行）
+    pub yield_interval: Option<Duration>,
+}
+
+impl Limits {
+    pub fn is_empty(&self) -> bool {
+        self.timeout.is_none()
+            && self.cpu_limit.is_none()
+            && self.cpu_quota.is_none()
+            && self.slow.is_none()
+            && self.yield_interval.is_none()
+    }
+}
+
+/// 超出的限制
+#[derive(Clone, Copy, Debug)]
+pub enum LimitExceeded {
+    Timeout(Duration),
+    CpuLimit(Duration),
+    CpuQuota,
+    /// 异步调用被中止（见 abort.rs），不是超出限制
+    Aborted,
+}
+
+/// 超出限制的原因，以及超出时是否正在执行正则表达式
+#[derive(Clone, Copy, Debug)]
+pub struct Exceeded {
+    pub limit: LimitExceeded,
+    pub in_regexp: bool,
+}
+
+impl From<LimitExceeded> for Exceeded {
+    fn from(limit: LimitExceeded) -> Self {
+        Exceeded { limit, in_regexp: false }
+    }
+}
+
+impl Exceeded {
+    pub fn to_error(self) -> anyhow::Error {
+        let mut message = match self.limit {
+            LimitExceeded::Timeout(limit) => format!("Execution timed out (timeout_ms={})", limit.as_millis()),
+            LimitExceeded::CpuLimit(limit) => format!("CPU time limit exceeded (cpu_limit_ms={})", limit.as_millis()),
+            LimitExceeded::CpuQuota => "CPU time quota exhausted".to_string(),
+            LimitExceeded::Aborted => "Execution aborted".to_string(),
+        };
+        if self.in_regexp {
+            message.push_str(" while executing a regular expression (likely catastrophic backtracking)");
+        }
+        match self.limit {
+            LimitExceeded::CpuQuota => crate::quota::quota_error(message),
+            _ => anyhow::anyhow!(message),
+        }
+    }
+}
+
+/// 超出限制的原因，由看门狗写入、Context 读取
+pub type ExceededSlot = Arc<Mutex<Option<Exceeded>>>;
+
+/// 当前线程的 CPU 时钟
+#[derive(Clone, Copy)]
+struct CpuClock(#[cfg_attr(not(target_os = "linux"), allow(dead_code))] i32);
+
+impl CpuClock {
+    #[cfg(target_os = "linux")]
+    fn current_thread() -> Option<Self> {
+        let mut clock_id: libc::clockid_t = 0;
+        let ret = unsafe { libc::pthread_getcpuclockid(libc::pthread_self(), &mut clock_id) };
+        (ret == 0).then_some(CpuClock(clock_id))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn current_thread() -> Option<Self> {
+        None
+    }
+
+    #[cfg(target_os = "linux")]
+    fn elapsed(self) -> Option<Duration> {
+        let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+        let ret = unsafe { libc::clock_gettime(self.0, &mut ts) };
+        (ret == 0).then(|| Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn elapsed(self) -> Option<Duration> {
+        None
+    }
+}
+
+/// 当前平台是否支持 cpu_limit_ms
+pub fn cpu_limit_supported() -> bool {
+    CpuClock::current_thread().is_some()
+}
+
+/// 当前线程已消耗的 CPU 时间（不支持的平台返回 None）
+pub fn thread_cpu_time() -> Option<Duration> {
+    CpuClock::current_thread()?.elapsed()
+}
+
+/// 一次受监视的执行
+struct Entry {
+    id: u64,
+    handle: v8::IsolateHandle,
+    limits: Limits,
+    deadline: Option<Instant>,
+    /// 报告慢调用的时间（报告后为 None）
+    slow_at: Option<Instant>,
+    /// 下一次处理 Python 信号的时间
+    yield_at: Option<Instant>,
+    cpu: Option<(CpuClock, Duration)>,
+    exceeded: ExceededSlot,
+    active: Arc<AtomicBool>,
+}
+
+impl Entry {
+    /// 检查是否超出限制
+    fn check(&self, now: Instant) -> Option<LimitExceeded> {
+        if let (Some(deadline), Some(limit)) = (self.deadline, self.limits.timeout) {
+            if now >= deadline {
+                return Some(LimitExceeded::Timeout(limit));
+            }
+        }
+        if let (Some((clock, start)), Some(limit)) = (self.cpu, self.limits.cpu_limit) {
+            let used = clock.elapsed()?.saturating_sub(start);
+            if used >= limit {
+                return Some(LimitExceeded::CpuLimit(limit));
+            }
+        }
+        if let (Some((clock, start)), Some(quota)) = (self.cpu, self.limits.cpu_quota) {
+            if clock.elapsed()?.saturating_sub(start) >= quota {
+                return Some(LimitExceeded::CpuQuota);
+            }
+        }
+        None
+    }
+}
+
+struct Shared {
+    entries: Mutex<Vec<Entry>>,
+    wake: Condvar,
+    /// shutdown() 要求线程退出
+    stopped: AtomicBool,
+}
+
+/// 看门狗线程：创建时的 fork 代数、共享状态和线程句柄
+type Watchdog = (usize, Arc<Shared>, JoinHandle<()>);
+
+/// 看门狗线程（按 fork 代数保存，子进程中重新创建）
+static WATCHDOG: Mutex<Option<Watchdog>> = Mutex::new(None);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn shared() -> Arc<Shared> {
+    let mut watchdog = WATCHDOG.lock().unwrap();
+    let generation = crate::fork::generation();
+    if let Some((created, shared, _)) = watchdog.as_ref() {
+        if *created == generation {
+            return shared.clone();
+        }
+    }
+
+    let shared = Arc::new(Shared {
+        entries: Mutex::new(Vec::new()),
+        wake: Condvar::new(),
+        stopped: AtomicBool::new(false),
+    });
+    let worker = shared.clone();
+    let handle = std::thread::Builder::new()
+        .name("never_jscore-watchdog".to_string())
+        .spawn(move || run(&worker))
+        .expect("failed to spawn watchdog thread");
+    *watchdog = Some((generation, shared.clone(), handle));
+    shared
+}
+
+/// 停止看门狗线程并等待它退出（shutdown() 调用，此时没有受监视的执行）
+pub fn stop() {
+    let Some((created, shared, handle)) = WATCHDOG.lock().unwrap().take() else {
+        return;
+    };
+    // 从父进程继承的记录：线程在子进程中不存在
+    if created != crate::fork::generation() {
+        return;
+    }
+    shared.stopped.store(true, Ordering::SeqCst);
+    // 持有锁时通知，避免线程在检查标记和开始等待之间错过通知
+    let entries = shared.entries.lock().unwrap();
+    shared.wake.notify_all();
+    drop(entries);
+    let _ = handle.join();
+}
+
+fn run(shared: &Shared) {
+    let mut entries = shared.entries.lock().unwrap();
+    loop {
+        if shared.stopped.load(Ordering::SeqCst) {
+            return;
+        }
+        if entries.is_empty() {
+            entries = shared.wake.wait(entries).unwrap();
+            continue;
+        }
+
+        let now = Instant::now();
+        entries.retain_mut(|entry| {
+            if entry.slow_at.is_some_and(|slow_at| now >= slow_at) {
+                entry.slow_at = None;
+                crate::slow_script::notify(&entry.handle, &entry.active);
+            }
+            if let (Some(yield_at), Some(interval)) = (entry.yield_at, entry.limits.yield_interval) {
+                if now >= yield_at {
+                    entry.yield_at = Some(now + interval);
+                    crate::yield_points::notify(&entry.handle, &entry.active);
+                }
+            }
+            match entry.check(now) {
+                Some(exceeded) => {
+                    *entry.exceeded.lock().unwrap() = Some(exceeded.into());
+                    crate::regexp_guard::terminate(&entry.handle, &entry.exceeded, &entry.active);
+                    false
+                }
+                None => true,
+            }
+        });
+
+        // 只有墙钟限制时睡到最近的截止时间，有 CPU 限制时定期检查
+        let mut wait = entries
+            .iter()
+            .flat_map(|entry| entry.deadline.into_iter().chain(entry.slow_at).chain(entry.yield_at))
+            .min()
+            .map(|deadline| deadline.saturating_duration_since(now))
+            .unwrap_or(Duration::MAX);
+        if entries.iter().any(|entry| entry.cpu.is_some()) {
+            wait = wait.min(POLL_INTERVAL);
+        }
+        entries = shared.wake.wait_timeout(entries, wait).unwrap().0;
+    }
+}
+
+/// 监视当前线程上的一次执行，返回的 guard 被丢弃时停止监视
+///
+/// 没有设置任何限制时返回 None。
+pub fn watch(handle: &v8::IsolateHandle, limits: Limits, exceeded: &ExceededSlot) -> Option<WatchGuard> {
+    if limits.is_empty() {
+        return None;
+    }
+
+    let cpu = limits
+        .cpu_limit
+        .or(limits.cpu_quota)
+        .and(CpuClock::current_thread())
+        .and_then(|clock| Some((clock, clock.elapsed()?)));
+    let now = Instant::now();
+    let deadline = limits.timeout.map(|timeout| now + timeout);
+    let slow_at = limits.slow.map(|slow| now + slow);
+    let yield_at = limits.yield_interval.map(|interval| now + interval);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let active = Arc::new(AtomicBool::new(true));
+
+    let shared = shared();
+    shared.entries.lock().unwrap().push(Entry {
+        id,
+        handle: handle.clone(),
+        limits,
+        deadline,
+        slow_at,
+        yield_at,
+        cpu,
+        exceeded: exceeded.clone(),
+        active: active.clone(),
+    });
+    shared.wake.notify_one();
+
+    Some(WatchGuard { id, deadline, shared, active })
+}
+
+/// 正在进行的监视
+pub struct WatchGuard {
+    id: u64,
+    deadline: Option<Instant>,
+    shared: Arc<Shared>,
+    active: Arc<AtomicBool>,
+}
+
+impl WatchGuard {
+    /// 墙钟截止时间（事件循环等待时使用）
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        self.active.store(false, Ordering::Release);
+        self.shared.entries.lock().unwrap().retain(|entry| entry.id != self.id);
+    }
+}
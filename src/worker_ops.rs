@@ -1,74 +1,436 @@
-// worker_ops.rs - Fake Worker API for JS reverse engineering
+// worker_ops.rs - Worker API：每个 Worker 在自己的线程上运行一个独立的 Context（isolate）
 //
-// This is NOT real multi-threading - everything runs in the same thread!
-// This is sufficient for most JS reverse engineering scenarios where
-// code only checks for Worker API existence.
+// new Worker(url) 在新线程中创建 Context，选项与创建它的 Context 相同（不包括历史记录和 shared_buffers），
+// 执行 Worker 脚本后等待消息。Worker 中同样可以创建 Worker。
+//
+// 资源限制：
+// - 配额（quotas）与创建它的 Context 累计同一份用量，Worker 执行脚本和处理每批消息各计一次执行
+// - 审计（audit_ops）记录 Worker 中的 op 调用，父 Context 收到 Worker 的事件时并入当前调用的审计日志
+//   （worker 键为 Worker 的名称）
+// - 同一个 Context 创建的 Worker（包括 Worker 中再创建的）同时最多存在 MAX_WORKERS 个，
+//   超出时 new Worker() 抛出错误
+//
+// 消息使用 V8 的结构化克隆（Deno.core.serialize / deserialize）传递，支持 Map / Set / Date /
+// 类型化数组等；SharedArrayBuffer 和 WebAssembly.Module 通过进程级的存储在 isolate 之间共享而不是复制。
+//
+// 事件循环：父 Context 发出的消息还没有被 Worker 处理完（或 Worker 脚本还在执行）时，父 Context 的
+// 事件循环等待 Worker，因此 `await` 一个由 onmessage 解决的 Promise 可以直接使用；
+// Worker 空闲时不阻止父 Context 的调用结束。Worker 在 terminate()、close() 或父 Context 销毁时退出。
 
-use deno_core::{extension, op2};
+use deno_core::v8::IsolateHandle;
+use deno_core::{CompiledWasmModuleStore, OpState, SharedArrayBufferStore, extension, op2};
+use once_cell::sync::Lazy;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::future::poll_fn;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context as TaskContext, Poll, Waker};
+
+use crate::audit::{AuditEntry, AuditLog};
+use crate::context::{Context, ContextOptions};
+use crate::quota::{Quota, SharedUsage};
+
+/// 同一个 Context 创建的 Worker（包括 Worker 中再创建的）同时存在的上限
+pub(crate) const MAX_WORKERS: usize = 16;
+
+/// Worker 发给父 Context 的事件类型（事件的第一个字节）
+const EVENT_MESSAGE: u8 = 0;
+const EVENT_ERROR: u8 = 1;
+/// Worker 空闲，后跟已处理的消息数（u32 小端）
+const EVENT_IDLE: u8 = 2;
+const EVENT_CLOSED: u8 = 3;
 
-thread_local! {
-    static WORKERS: RefCell<HashMap<i32, WorkerData>> = RefCell::new(HashMap::new());
-    static NEXT_WORKER_ID: RefCell<i32> = RefCell::new(1);
+/// 所有 isolate 共享的 SharedArrayBuffer / WebAssembly.Module 存储（postMessage 在 isolate 之间传递）
+static SHARED_ARRAY_BUFFERS: Lazy<SharedArrayBufferStore> = Lazy::new(SharedArrayBufferStore::default);
+static WASM_MODULES: Lazy<CompiledWasmModuleStore> = Lazy::new(CompiledWasmModuleStore::default);
+
+pub(crate) fn shared_array_buffer_store() -> SharedArrayBufferStore {
+    SHARED_ARRAY_BUFFERS.clone()
 }
 
-#[derive(Clone)]
-struct WorkerData {
-    script: String,
-    ready: bool,
+pub(crate) fn compiled_wasm_module_store() -> CompiledWasmModuleStore {
+    WASM_MODULES.clone()
 }
 
 // ============================================
-// Worker Operations (Fake - Single-threaded)
+// Message queues
 // ============================================
 
-#[op2(fast)]
-/// Create a fake worker (doesn't actually spawn a thread)
-pub fn op_worker_create(#[string] script: String) -> i32 {
-    NEXT_WORKER_ID.with(|next_id| {
-        let id = *next_id.borrow();
-        *next_id.borrow_mut() += 1;
+/// 跨线程的消息队列：接收方可以在事件循环中异步等待，也可以阻塞线程等待
+#[derive(Default)]
+struct MessageQueue {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+}
 
-        WORKERS.with(|workers| {
-            workers.borrow_mut().insert(id, WorkerData {
-                script,
-                ready: true,
-            });
-        });
+#[derive(Default)]
+struct QueueState {
+    items: VecDeque<Vec<u8>>,
+    waker: Option<Waker>,
+    /// 已取出的消息数
+    taken: u32,
+}
 
-        id
-    })
+impl MessageQueue {
+    fn push(&self, item: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        state.items.push_back(item);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.ready.notify_all();
+    }
+
+    fn push_event(&self, kind: u8, payload: &[u8]) {
+        let mut event = Vec::with_capacity(payload.len() + 1);
+        event.push(kind);
+        event.extend_from_slice(payload);
+        self.push(event);
+    }
+
+    /// 取出下一条消息；`closed` 时返回 None
+    fn poll_pop(&self, cx: &mut TaskContext<'_>, closed: &AtomicBool) -> Poll<Option<Vec<u8>>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(item) = state.items.pop_front() {
+            state.taken += 1;
+            return Poll::Ready(Some(item));
+        }
+        if closed.load(Ordering::SeqCst) {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// 阻塞直到有消息或 `closed`，返回是否有消息
+    fn wait(&self, closed: &AtomicBool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        while state.items.is_empty() && !closed.load(Ordering::SeqCst) {
+            state = self.ready.wait(state).unwrap();
+        }
+        !state.items.is_empty() && !closed.load(Ordering::SeqCst)
+    }
+
+    fn taken(&self) -> u32 {
+        self.state.lock().unwrap().taken
+    }
+
+    /// 唤醒所有等待者（关闭时使用）
+    fn wake_all(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.ready.notify_all();
+    }
 }
 
-#[op2]
-#[string]
-/// Post message to worker (fake - just returns the message back)
-pub fn op_worker_post_message(worker_id: i32, #[string] message: String) -> String {
-    WORKERS.with(|workers| {
-        if workers.borrow().contains_key(&worker_id) {
-            // Fake processing - just echo back
-            format!("{{\"type\":\"message\",\"data\":{}}}", message)
+/// 父 Context 与 Worker 之间的通道（两端共享）
+pub(crate) struct WorkerPort {
+    name: String,
+    to_worker: MessageQueue,
+    to_parent: MessageQueue,
+    /// terminate() / close() 之后为 true
+    closed: AtomicBool,
+    isolate: Mutex<Option<IsolateHandle>>,
+    /// 父 Context 的配额用量（父 Context 没有设置 quotas 时为 None）
+    quota: Option<SharedUsage>,
+    /// 同一个 Context 创建的 Worker 数（与父 Context 共享）
+    live: Arc<AtomicUsize>,
+    /// 还没有并入父 Context 审计日志的记录（以及超出上限未保存的记录数）
+    audit: Mutex<(Vec<AuditEntry>, usize)>,
+}
+
+impl WorkerPort {
+    /// Worker Context 的配额（与父 Context 累计同一份用量）
+    pub(crate) fn quota(&self, options: &ContextOptions) -> Option<Quota> {
+        let limits = options.quotas?;
+        Some(match &self.quota {
+            Some(usage) => Quota::sharing(limits, usage.clone()),
+            None => Quota::new(limits),
+        })
+    }
+
+    /// 保存 Worker 最近一次执行的审计记录，等待父 Context 取走
+    fn forward_audit(&self, ctx: &Context) {
+        let Ok((entries, dropped)) = ctx.audit_log() else {
+            return;
+        };
+        let mut audit = self.audit.lock().unwrap();
+        // Worker 中再创建的 Worker 的记录保留原来的名称
+        audit.0.extend(entries.into_iter().map(|entry| AuditEntry {
+            worker: entry.worker.or_else(|| Some(self.name.clone())),
+            ..entry
+        }));
+        audit.1 += dropped;
+    }
+
+    /// 把 Worker 的审计记录并入父 Context 的审计日志
+    fn deliver_audit(&self, log: &AuditLog) {
+        let (entries, dropped) = std::mem::take(&mut *self.audit.lock().unwrap());
+        log.append(entries, dropped);
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.to_worker.wake_all();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// 关闭通道并中断 Worker 中正在执行的代码
+    fn terminate(&self) {
+        self.close();
+        if let Some(isolate) = self.isolate.lock().unwrap().as_ref() {
+            isolate.terminate_execution();
+        }
+    }
+}
+
+// ============================================
+// Parent side
+// ============================================
+
+/// Context 创建的 Worker（放在 OpState 中，Context 销毁时终止所有 Worker）
+pub(crate) struct WorkerHost {
+    options: ContextOptions,
+    workers: HashMap<u32, Arc<WorkerPort>>,
+    next_id: u32,
+    quota: Option<SharedUsage>,
+    live: Arc<AtomicUsize>,
+}
+
+impl WorkerHost {
+    /// `options` 为创建它的 Context 的选项，`quota` 为它的配额
+    ///
+    /// Worker 中的 Context 沿用父 Context 的 Worker 计数，整棵 Worker 树共用 MAX_WORKERS。
+    pub(crate) fn new(options: &ContextOptions, quota: Option<&Quota>) -> Self {
+        WorkerHost {
+            options: ContextOptions {
+                history: None,
+                shared_buffers: Vec::new(),
+                worker: None,
+                ..options.clone()
+            },
+            workers: HashMap::new(),
+            next_id: 1,
+            quota: quota.map(Quota::shared_usage),
+            live: options.worker.as_ref().map_or_else(Default::default, |port| port.live.clone()),
+        }
+    }
+}
+
+/// 占用一个 Worker 名额，Worker 线程退出（或没能启动）时释放
+struct WorkerSlot(Arc<AtomicUsize>);
+
+impl WorkerSlot {
+    fn acquire(live: &Arc<AtomicUsize>) -> Option<Self> {
+        live.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| (n < MAX_WORKERS).then_some(n + 1))
+            .ok()
+            .map(|_| WorkerSlot(live.clone()))
+    }
+}
+
+impl Drop for WorkerSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Drop for WorkerHost {
+    fn drop(&mut self) {
+        for port in self.workers.values() {
+            port.terminate();
+        }
+    }
+}
+
+/// Worker 脚本
+struct WorkerScript {
+    url: String,
+    code: String,
+    module: bool,
+}
+
+/// 在 Worker 线程上运行：执行脚本，之后每收到消息就运行一次事件循环
+fn run_worker(options: ContextOptions, script: WorkerScript, port: Arc<WorkerPort>, _slot: WorkerSlot) {
+    let ctx = match Context::new(options) {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            port.to_parent.push_event(EVENT_ERROR, e.to_string().as_bytes());
+            port.to_parent.push_event(EVENT_CLOSED, &[]);
+            return;
+        }
+    };
+    let _ = ctx.with_runtime(|runtime| {
+        *port.isolate.lock().unwrap() = Some(runtime.v8_isolate().thread_safe_handle());
+        Ok(())
+    });
+
+    if !port.is_closed() {
+        let result = if script.module {
+            ctx.exec_module(&script.url, script.code)
         } else {
-            r#"{"type":"error","message":"Worker not found"}"#.to_string()
+            ctx.exec_named_script(&script.url, script.code, false)
+        };
+        port.forward_audit(&ctx);
+        if let Err(e) = result {
+            if !port.is_closed() {
+                port.to_parent.push_event(EVENT_ERROR, e.to_string().as_bytes());
+            }
+        }
+    }
+
+    // 消息由 Worker 作用域中等待 op_worker_scope_recv 的 Promise 接收
+    while !port.is_closed() {
+        port.to_parent.push_event(EVENT_IDLE, &port.to_worker.taken().to_le_bytes());
+        let taken = port.to_worker.taken();
+        if !port.to_worker.wait(&port.closed) {
+            break;
+        }
+        let result = ctx.run_event_loop();
+        port.forward_audit(&ctx);
+        if let Err(e) = result {
+            if !port.is_closed() {
+                port.to_parent.push_event(EVENT_ERROR, e.to_string().as_bytes());
+            }
+        }
+        // 没有取走消息说明 Worker 作用域已经不再接收（如执行被终止），避免空转
+        if port.to_worker.taken() == taken {
+            break;
         }
-    })
+    }
+    drop(ctx);
+    port.to_parent.push_event(EVENT_CLOSED, &[]);
 }
 
 #[op2(fast)]
-/// Terminate worker
-pub fn op_worker_terminate(worker_id: i32) {
-    WORKERS.with(|workers| {
-        workers.borrow_mut().remove(&worker_id);
+#[smi]
+/// 在新线程中启动 Worker，返回 Worker ID
+pub fn op_worker_create(
+    state: &mut OpState,
+    #[string] code: String,
+    #[string] url: String,
+    #[string] name: String,
+    module: bool,
+) -> Result<u32, std::io::Error> {
+    let host = state.borrow_mut::<WorkerHost>();
+    let slot = WorkerSlot::acquire(&host.live).ok_or_else(|| {
+        std::io::Error::other(format!(
+            "Too many workers: at most {} can run at the same time, terminate() unused workers first",
+            MAX_WORKERS
+        ))
+    })?;
+    let port = Arc::new(WorkerPort {
+        name,
+        to_worker: MessageQueue::default(),
+        to_parent: MessageQueue::default(),
+        closed: AtomicBool::new(false),
+        isolate: Mutex::new(None),
+        quota: host.quota.clone(),
+        live: host.live.clone(),
+        audit: Mutex::new((Vec::new(), 0)),
     });
+    let options = ContextOptions { worker: Some(port.clone()), ..host.options.clone() };
+    let script = WorkerScript { url, code, module };
+
+    let worker_port = port.clone();
+    std::thread::Builder::new()
+        .stack_size(crate::runtime::worker_stack_size())
+        .name(format!("never_jscore-worker-{}", host.next_id))
+        .spawn(move || run_worker(options, script, worker_port, slot))?;
+
+    let id = host.next_id;
+    host.next_id += 1;
+    host.workers.insert(id, port);
+    Ok(id)
+}
+
+#[op2(fast)]
+/// 把结构化克隆后的消息发给 Worker
+pub fn op_worker_post_message(state: &mut OpState, #[smi] worker_id: u32, #[buffer] data: &[u8]) {
+    if let Some(port) = state.borrow::<WorkerHost>().workers.get(&worker_id) {
+        port.to_worker.push(data.to_vec());
+    }
 }
 
 #[op2(fast)]
-/// Check if worker exists
-pub fn op_worker_exists(worker_id: i32) -> bool {
-    WORKERS.with(|workers| {
-        workers.borrow().contains_key(&worker_id)
-    })
+/// 终止 Worker
+pub fn op_worker_terminate(state: &mut OpState, #[smi] worker_id: u32) {
+    if let Some(port) = state.borrow_mut::<WorkerHost>().workers.remove(&worker_id) {
+        port.terminate();
+    }
+}
+
+#[op2(async)]
+#[buffer]
+/// 等待 Worker 的下一个事件（第一个字节为事件类型）
+pub async fn op_worker_recv(state: Rc<RefCell<OpState>>, #[smi] worker_id: u32) -> Vec<u8> {
+    let port = state.borrow().borrow::<WorkerHost>().workers.get(&worker_id).cloned();
+    let Some(port) = port else {
+        return vec![EVENT_CLOSED];
+    };
+    // Worker 线程退出前总会发出 EVENT_CLOSED，不需要检查 closed
+    let never = AtomicBool::new(false);
+    let event = poll_fn(|cx| port.to_parent.poll_pop(cx, &never)).await;
+    let event = event.unwrap_or_else(|| vec![EVENT_CLOSED]);
+    if let Some(log) = state.borrow().try_borrow::<Rc<AuditLog>>() {
+        port.deliver_audit(log);
+    }
+    if event.first() == Some(&EVENT_CLOSED) {
+        state.borrow_mut().borrow_mut::<WorkerHost>().workers.remove(&worker_id);
+    }
+    event
+}
+
+// ============================================
+// Worker side
+// ============================================
+
+#[op2]
+#[string]
+/// Worker 的名称（new Worker(url, { name })）
+pub fn op_worker_scope_name(state: &mut OpState) -> String {
+    state.try_borrow::<Arc<WorkerPort>>().map(|port| port.name.clone()).unwrap_or_default()
+}
+
+#[op2(fast)]
+/// 从 Worker 向父 Context 发送结构化克隆后的消息
+pub fn op_worker_scope_post(state: &mut OpState, #[buffer] data: &[u8]) {
+    if let Some(port) = state.try_borrow::<Arc<WorkerPort>>() {
+        port.to_parent.push_event(EVENT_MESSAGE, data);
+    }
+}
+
+#[op2(fast)]
+/// 把 Worker 中未捕获的错误报告给父 Context（触发 worker.onerror）
+pub fn op_worker_scope_error(state: &mut OpState, #[string] message: &str) {
+    if let Some(port) = state.try_borrow::<Arc<WorkerPort>>() {
+        port.to_parent.push_event(EVENT_ERROR, message.as_bytes());
+    }
+}
+
+#[op2(fast)]
+/// self.close()：处理完当前任务后退出
+pub fn op_worker_scope_close(state: &mut OpState) {
+    if let Some(port) = state.try_borrow::<Arc<WorkerPort>>() {
+        port.close();
+    }
+}
+
+#[op2(async)]
+#[buffer]
+/// 等待父 Context 的下一条消息（Worker 关闭时返回空数组）
+pub async fn op_worker_scope_recv(state: Rc<RefCell<OpState>>) -> Vec<u8> {
+    let port = state.borrow().try_borrow::<Arc<WorkerPort>>().cloned();
+    let Some(port) = port else {
+        return Vec::new();
+    };
+    poll_fn(|cx| port.to_worker.poll_pop(cx, &port.closed)).await.unwrap_or_default()
 }
 
 // ============================================
@@ -81,6 +443,11 @@ extension!(
         op_worker_create,
         op_worker_post_message,
         op_worker_terminate,
-        op_worker_exists,
+        op_worker_recv,
+        op_worker_scope_name,
+        op_worker_scope_post,
+        op_worker_scope_error,
+        op_worker_scope_close,
+        op_worker_scope_recv,
     ],
 );
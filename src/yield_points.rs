@@ -0,0 +1,69 @@
+// yield_points.rs - Context(yield_interval_ms=N)：长时间同步执行中的协作让出点
+//
+// timeout_ms / cpu_limit_ms / AbortToken 通过 V8 中断终止执行（见 watchdog.rs），while (true) {} 这样的死循环
+// 也会在循环的回边上响应，不需要改写代码。但执行期间 Python 没有机会处理信号：
+// 主线程上的 ctx.call() 陷入死循环时，Ctrl-C 只是设置了一个标记，KeyboardInterrupt 要等 JS 自己结束才会抛出。
+//
+// 设置 yield_interval_ms 后，同步执行每过 N 毫秒，看门狗请求一次中断（与 timeout_ms 相同的机制），
+// 中断回调在执行线程上（下一个循环回边 / 函数调用处）短暂获取 GIL，运行 Python 的信号处理函数：
+// - 信号处理函数抛出异常（Ctrl-C 的 KeyboardInterrupt、自定义 SIGTERM 处理函数等）时终止执行，
+//   调用原样抛出该异常，Context 仍然可用
+// - 没有待处理的信号时立即返回，JS 继续执行
+//
+// Python 只在主线程上处理信号，其他线程上的 Context 设置此选项没有效果。
+// 事件循环等待定时器 / Promise 时 JS 不在运行，中断要等 JS 再次运行时才处理（等待受 timeout_ms 限制）。
+
+use deno_core::{v8, JsRuntime};
+use pyo3::prelude::*;
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 让出点上信号处理函数抛出的异常（与 isolate 的 slot 共享）
+#[derive(Default)]
+pub struct YieldPoints {
+    pending: RefCell<Option<PyErr>>,
+}
+
+impl YieldPoints {
+    /// 创建并保存到 runtime 的 isolate 中，供中断回调使用
+    pub fn install(runtime: &mut JsRuntime) -> Rc<Self> {
+        let yield_points = Rc::new(Self::default());
+        runtime.v8_isolate().set_slot(yield_points.clone());
+        yield_points
+    }
+
+    /// 执行是否因信号处理函数抛出异常被终止
+    pub fn pending(&self) -> bool {
+        self.pending.borrow().is_some()
+    }
+
+    /// 取出信号处理函数抛出的异常
+    pub fn take(&self) -> Option<PyErr> {
+        self.pending.borrow_mut().take()
+    }
+}
+
+/// 请求中断正在执行的 isolate 并处理 Python 信号（由看门狗线程按 yield_interval_ms 调用）
+pub fn notify(handle: &v8::IsolateHandle, active: &Arc<AtomicBool>) {
+    let data = Box::into_raw(Box::new(active.clone()));
+    if !handle.request_interrupt(yield_interrupt, data as *mut c_void) {
+        drop(unsafe { Box::from_raw(data) });
+    }
+}
+
+unsafe extern "C" fn yield_interrupt(isolate: &mut v8::Isolate, data: *mut c_void) {
+    let active = unsafe { Box::from_raw(data as *mut Arc<AtomicBool>) };
+    if !active.load(Ordering::Acquire) {
+        return;
+    }
+    let Some(yield_points) = isolate.get_slot::<Rc<YieldPoints>>().cloned() else {
+        return;
+    };
+    if let Err(e) = Python::attach(|py| py.check_signals()) {
+        *yield_points.pending.borrow_mut() = Some(e);
+        isolate.terminate_execution();
+    }
+}
@@ -0,0 +1,149 @@
+"""
+测试 AbortToken：取消 asyncio 调用时终止后台线程上的 JS
+"""
+
+import asyncio
+import time
+
+import never_jscore
+
+JS_CODE = """
+    var finished = 0;
+    function spin() { while (true) {} }
+    async function slow(ms) {
+        await new Promise(r => setTimeout(r, ms));
+        finished++;
+        return 'slow';
+    }
+    function add(a, b) { return a + b; }
+"""
+
+
+def test_cancel_terminates_loop():
+    """测试取消等待的任务会终止死循环，Context 可以继续使用"""
+    async def main():
+        ctx = never_jscore.ThreadedContext()
+        ctx.compile(JS_CODE)
+        start = time.time()
+        try:
+            await asyncio.wait_for(ctx.call_async("spin", []), timeout=0.1)
+            assert False, "应该超时"
+        except asyncio.TimeoutError:
+            pass
+        # 死循环被终止：下一次调用不需要等待它结束
+        assert await ctx.call_async("add", [1, 2]) == 3
+        assert time.time() - start < 2.0, time.time() - start
+        ctx.close()
+
+    asyncio.run(main())
+    print("[OK] 取消任务终止死循环")
+
+
+def test_cancel_stops_waiting():
+    """测试取消正在等待定时器的调用，定时器之后的代码不再执行"""
+    async def main():
+        ctx = never_jscore.ThreadedContext()
+        ctx.compile(JS_CODE)
+        task = asyncio.ensure_future(ctx.call_async("slow", [500]))
+        await asyncio.sleep(0.05)
+        task.cancel()
+        try:
+            await task
+            assert False, "应该被取消"
+        except asyncio.CancelledError:
+            pass
+        start = time.time()
+        assert await ctx.evaluate_async("finished") == 0
+        assert time.time() - start < 0.4, time.time() - start
+        ctx.close()
+
+    asyncio.run(main())
+    print("[OK] 取消任务停止等待定时器")
+
+
+def test_abort_token():
+    """测试 AbortToken 中止多个调用，排队中的任务不再执行"""
+    async def main():
+        pool = never_jscore.ContextPool(JS_CODE, size=1)
+        token = never_jscore.AbortToken()
+        assert not token.aborted
+        running = asyncio.ensure_future(pool.call_async("spin", [], abort=token))
+        queued = asyncio.ensure_future(pool.evaluate_async("globalThis.ran = true", abort=token))
+        await asyncio.sleep(0.1)
+        token.abort()
+        assert token.aborted and "True" in repr(token)
+        for task in (running, queued):
+            try:
+                await task
+                assert False, "应该被中止"
+            except asyncio.CancelledError:
+                pass
+        assert await pool.evaluate_async("typeof ran") == "undefined"
+        assert await pool.call_async("add", [2, 3]) == 5
+
+        # 已中止的令牌：调用立即结束
+        try:
+            await pool.call_async("add", [1, 1], abort=token)
+            assert False, "应该被中止"
+        except asyncio.CancelledError:
+            pass
+        pool.close()
+
+    asyncio.run(main())
+    print("[OK] AbortToken")
+
+
+def test_abort_module_level():
+    """测试 aeval() / acall() 的 abort 参数"""
+    async def main():
+        await never_jscore.aeval("function spinForever() { while (true) {} }")
+        token = never_jscore.AbortToken()
+        task = asyncio.ensure_future(never_jscore.acall("spinForever", [], abort=token))
+        await asyncio.sleep(0.05)
+        token.abort()
+        try:
+            await task
+            assert False, "应该被中止"
+        except asyncio.CancelledError:
+            pass
+        assert await never_jscore.aeval("1 + 1") == 2
+
+    asyncio.run(main())
+    print("[OK] aeval / acall")
+
+
+def test_not_aborted():
+    """测试未中止的调用不受影响，错误照常抛出"""
+    async def main():
+        ctx = never_jscore.ThreadedContext(timeout_ms=100)
+        ctx.compile(JS_CODE)
+        token = never_jscore.AbortToken()
+        assert await ctx.call_async("add", [1, 2], abort=token) == 3
+        try:
+            await ctx.call_async("spin", [], abort=token)
+            assert False, "应该超时"
+        except asyncio.CancelledError:
+            assert False, "超时不是中止"
+        except Exception as e:
+            assert "timed out" in str(e), e
+        assert await ctx.call_async("add", [3, 4]) == 7
+        ctx.close()
+
+    asyncio.run(main())
+    print("[OK] 未中止的调用")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 AbortToken")
+    print("=" * 60)
+
+    test_cancel_terminates_loop()
+    test_cancel_stops_waiting()
+    test_abort_token()
+    test_abort_module_level()
+    test_not_aborted()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有中止测试通过！")
+    print("=" * 60)
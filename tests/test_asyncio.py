@@ -0,0 +1,140 @@
+"""
+测试 asyncio 集成
+
+展示如何在 async Python 服务中 await JS 执行，而不阻塞事件循环
+"""
+
+import asyncio
+import time
+
+import never_jscore
+
+
+JS_CODE = """
+    function add(a, b) { return a + b; }
+    async function delayed(x, ms) {
+        await new Promise(r => setTimeout(r, ms));
+        return x;
+    }
+    function busy(n) { let s = 0; for (let i = 0; i < n; i++) { s += i; } return s; }
+"""
+
+
+def test_threaded_context_async():
+    """测试 ThreadedContext 的 async 方法"""
+    async def main():
+        ctx = never_jscore.ThreadedContext()
+        assert await ctx.eval_async(JS_CODE) is None
+        assert await ctx.eval_async("var z = 7; z * 6", return_value=True) == 42
+        assert await ctx.call_async("add", [1, 2]) == 3
+        assert await ctx.evaluate_async("delayed('done', 20)") == "done"
+        ctx.close()
+
+    asyncio.run(main())
+    print("[OK] ThreadedContext eval_async/call_async/evaluate_async")
+
+
+def test_event_loop_not_blocked():
+    """测试 JS 执行期间事件循环仍然可以调度其他协程"""
+    async def main():
+        ctx = never_jscore.ThreadedContext()
+        ctx.compile(JS_CODE)
+
+        ticks = 0
+
+        async def ticker():
+            nonlocal ticks
+            while True:
+                ticks += 1
+                await asyncio.sleep(0.005)
+
+        task = asyncio.create_task(ticker())
+        await ctx.call_async("busy", [3e8])
+        task.cancel()
+        ctx.close()
+        return ticks
+
+    ticks = asyncio.run(main())
+    assert ticks > 5, f"JS 执行期间事件循环只调度了 {ticks} 次"
+    print(f"[OK] JS 执行期间事件循环调度了 {ticks} 次")
+
+
+def test_pool_gather():
+    """测试 ContextPool 并发 await"""
+    async def main():
+        with never_jscore.ContextPool(JS_CODE, size=4) as pool:
+            start = time.perf_counter()
+            results = await asyncio.gather(
+                *(pool.call_async("delayed", [i, 100]) for i in range(4))
+            )
+            elapsed = time.perf_counter() - start
+            assert await pool.evaluate_async("add(2, 3)") == 5
+        return results, elapsed
+
+    results, elapsed = asyncio.run(main())
+    assert results == [0, 1, 2, 3]
+    assert elapsed < 0.35, f"4 个 100ms 任务应该并行执行，实际耗时 {elapsed:.2f}s"
+    print(f"[OK] 4 个并发任务耗时 {elapsed * 1000:.0f}ms")
+
+
+def test_async_errors():
+    """测试 JS 错误作为 Python 异常抛出"""
+    async def main():
+        ctx = never_jscore.ThreadedContext()
+        try:
+            await ctx.evaluate_async("throw new Error('async boom')")
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "async boom" in str(e)
+        assert await ctx.evaluate_async("1 + 1") == 2
+        ctx.close()
+
+    asyncio.run(main())
+    print("[OK] 异步错误传播")
+
+
+def test_wait_for_timeout():
+    """测试 asyncio.wait_for 超时后 Context 仍然可用"""
+    async def main():
+        ctx = never_jscore.ThreadedContext()
+        ctx.compile(JS_CODE)
+        try:
+            await asyncio.wait_for(ctx.call_async("delayed", [1, 300]), timeout=0.05)
+            assert False, "应该超时"
+        except asyncio.TimeoutError:
+            pass
+        # 被取消的任务在后台完成后，后续调用正常
+        assert await ctx.call_async("add", [4, 5]) == 9
+        ctx.close()
+
+    asyncio.run(main())
+    print("[OK] wait_for 超时后可以继续使用")
+
+
+def test_requires_running_loop():
+    """测试在事件循环外调用会报错"""
+    ctx = never_jscore.ThreadedContext()
+    try:
+        ctx.call_async("add", [1, 2])
+        assert False, "应该抛出 RuntimeError"
+    except RuntimeError:
+        pass
+    ctx.close()
+    print("[OK] 事件循环外调用抛出 RuntimeError")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 asyncio 集成")
+    print("=" * 60)
+
+    test_threaded_context_async()
+    test_event_loop_not_blocked()
+    test_pool_gather()
+    test_async_errors()
+    test_wait_for_timeout()
+    test_requires_running_loop()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有 asyncio 测试通过！")
+    print("=" * 60)
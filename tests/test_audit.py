@@ -0,0 +1,100 @@
+"""
+测试 op 审计日志：Context(audit_ops=True) 和 get_audit_log()
+
+展示如何查看一次调用中脚本通过扩展 API 执行了哪些操作（读文件、发请求、哈希等）
+"""
+
+import os
+import tempfile
+
+import never_jscore
+
+
+def test_audit_disabled_by_default():
+    """测试默认不记录"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("md5('x')") == "9dd4e461268c8034f5c8564e155c67a6"
+    try:
+        ctx.get_audit_log()
+        assert False, "应该抛出 RuntimeError"
+    except RuntimeError as e:
+        assert "audit_ops" in str(e)
+    del ctx
+    print("[OK] 默认不开启审计")
+
+
+def test_audit_log_entries():
+    """测试记录 op 名称、参数、时间和结果"""
+    ctx = never_jscore.Context(audit_ops=True)
+    assert ctx.evaluate("md5('hello') + btoa('hi')") == "5d41402abc4b2a76b9719d911017c592aGk="
+
+    log = ctx.get_audit_log()
+    ops = [entry["op"] for entry in log["entries"]]
+    assert "op_md5" in ops and "op_codec_btoa" in ops, ops
+    assert log["dropped"] == 0
+
+    entry = next(e for e in log["entries"] if e["op"] == "op_md5")
+    assert entry["args"] == ["hello"]
+    assert entry["status"] == "ok"
+    assert entry["duration_ms"] >= 0
+    assert entry["start_ms"] > 1_600_000_000_000
+
+    # 每次调用开始时清空
+    ctx.evaluate("1 + 1")
+    assert ctx.get_audit_log()["entries"] == []
+
+    # 长参数被截断
+    ctx.evaluate("sha256('a'.repeat(10000))")
+    arg = ctx.get_audit_log()["entries"][0]["args"][0]
+    assert len(arg) < 300, len(arg)
+
+    # 异步 op 在 Promise 完成后记录耗时
+    ctx.evaluate("new Promise(r => setTimeout(r, 20))")
+    timers = [e for e in ctx.get_audit_log()["entries"] if e["op"] == "op_set_timeout_real"]
+    assert timers and timers[0]["status"] == "ok" and timers[0]["duration_ms"] >= 10, timers
+
+    del ctx
+    print("[OK] 记录 op 名称、参数、耗时")
+
+
+def test_audit_untrusted_script():
+    """测试检查不可信脚本尝试的操作（包括被权限拒绝的）"""
+    with tempfile.TemporaryDirectory() as allowed:
+        ctx = never_jscore.Context(audit_ops=True, permissions={"read": [allowed]})
+        ctx.evaluate("""
+            const fs = require('fs');
+            try { fs.readFileSync('/etc/passwd'); } catch (e) {}
+            fs.existsSync(%r);
+        """ % os.path.join(allowed, "missing.txt"))
+
+        entries = ctx.get_audit_log()["entries"]
+        denied = [e for e in entries if e["status"] == "denied"]
+        assert [e["op"] for e in denied] == ["op_read_file_sync"], entries
+        assert denied[0]["args"] == ["/etc/passwd"]
+        assert any(e["op"] == "op_file_exists" for e in entries)
+        del ctx
+    print("[OK] 记录被拒绝的操作")
+
+
+def test_audit_threaded():
+    """测试 ThreadedContext"""
+    ctx = never_jscore.ThreadedContext(audit_ops=True)
+    ctx.evaluate("sha1('x')")
+    assert [e["op"] for e in ctx.get_audit_log()["entries"]] == ["op_sha1"]
+    ctx.close()
+    print("[OK] ThreadedContext 审计日志")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 op 审计日志")
+    print("=" * 60)
+
+    test_audit_disabled_by_default()
+    test_audit_log_entries()
+    test_audit_untrusted_script()
+    test_audit_threaded()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有审计日志测试通过！")
+    print("=" * 60)
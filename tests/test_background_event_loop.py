@@ -0,0 +1,114 @@
+"""
+测试后台事件循环
+
+展示如何让 setInterval 心跳和未完成的 Promise 在两次 Python 调用之间继续运行
+"""
+
+import time
+
+import never_jscore
+
+
+def test_interval_keeps_running():
+    """测试 setInterval 在调用之间持续触发"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.start_event_loop()
+    assert ctx.event_loop_running
+
+    ctx.eval("var beats = 0; var hb = setInterval(() => beats++, 20);")
+    time.sleep(0.5)
+    beats = ctx.evaluate("beats")
+    assert beats >= 10, f"心跳次数太少: {beats}"
+
+    ctx.eval("clearInterval(hb);")
+    ctx.stop_event_loop()
+    assert not ctx.event_loop_running
+    ctx.close()
+
+    print(f"[OK] 0.5 秒内心跳 {beats} 次")
+
+
+def test_calls_return_while_timers_pending():
+    """测试存在未完成定时器时调用仍立即返回"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.start_event_loop()
+    ctx.eval("setInterval(() => {}, 50);")
+
+    start = time.perf_counter()
+    assert ctx.evaluate("Promise.resolve(1 + 1)") == 2
+    elapsed = time.perf_counter() - start
+    assert elapsed < 0.5
+
+    ctx.close()
+    print(f"[OK] 有活动定时器时调用耗时 {elapsed * 1000:.1f}ms")
+
+
+def test_pending_promise_result_later():
+    """测试后台完成的 Promise 结果可以稍后读取"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.start_event_loop()
+
+    ctx.eval("""
+        var token = null;
+        new Promise(r => setTimeout(() => r('tok-123'), 100)).then(v => { token = v; });
+    """)
+    assert ctx.evaluate("token") is None
+    time.sleep(0.3)
+    assert ctx.evaluate("token") == "tok-123"
+
+    ctx.close()
+    print("[OK] Promise 在后台完成")
+
+
+def test_late_result_not_mixed_into_next_call():
+    """测试提前返回的调用在后台完成时，迟到的结果不会被下一次调用读到"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.start_event_loop()
+
+    # 第一次调用在 20ms 时提前返回，但它的异步函数仍在后台运行，100ms 后产生结果
+    first = ctx.evaluate("""
+        (async () => {
+            setTimeout(() => $return('early'), 20);
+            await new Promise(r => setTimeout(r, 100));
+            return 'stale';
+        })()
+    """)
+    assert first == "early"
+
+    # 第二次调用在第一次的迟到结果产生之后才完成，必须拿到自己的结果
+    second = ctx.evaluate("new Promise(r => setTimeout(() => r('fresh'), 200))")
+    assert second == "fresh", second
+
+    ctx.close()
+    print("[OK] 迟到的结果按调用 ID 丢弃")
+
+
+def test_event_loop_errors():
+    """测试后台事件循环中的未捕获错误被记录"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.start_event_loop()
+
+    ctx.eval("Promise.reject(new Error('background failure'));")
+    time.sleep(0.1)
+    errors = ctx.take_event_loop_errors()
+    assert any("background failure" in e for e in errors), errors
+    assert ctx.take_event_loop_errors() == []
+
+    ctx.close()
+    print(f"[OK] 记录到 {len(errors)} 个后台错误")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试后台事件循环")
+    print("=" * 60)
+
+    test_interval_keeps_running()
+    test_calls_return_while_timers_pending()
+    test_pending_promise_result_later()
+    test_late_result_not_mixed_into_next_call()
+    test_event_loop_errors()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有后台事件循环测试通过！")
+    print("=" * 60)
@@ -0,0 +1,82 @@
+"""
+测试 binary=True：把 JS 的二进制字符串（每个字符一个字节）返回为 bytes
+
+默认按 UTF-8 返回 str，0x80 以上的字节编码后会变成两个字节
+"""
+
+import never_jscore
+
+
+def test_evaluate_binary():
+    """测试 evaluate(binary=True) 保留每个字节"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("String.fromCharCode(0xde, 0xad, 0xbe, 0xef)", binary=True) == b"\xde\xad\xbe\xef"
+
+    all_bytes = ctx.evaluate("Array.from({length: 256}, (_, i) => String.fromCharCode(i)).join('')", binary=True)
+    assert all_bytes == bytes(range(256))
+
+    # 默认仍然返回 str
+    assert ctx.evaluate("String.fromCharCode(0xff)") == "\xff"
+    # 非字符串结果不受影响
+    assert ctx.evaluate("1 + 1", binary=True) == 2
+    assert ctx.evaluate("null", binary=True) is None
+    del ctx
+    print("[OK] evaluate(binary=True)")
+
+
+def test_call_binary_nested():
+    """测试 call(binary=True) 和嵌套结构"""
+    ctx = never_jscore.Context()
+    ctx.compile("""
+        function encrypt(text) {
+            return text.split('').map(c => String.fromCharCode(c.charCodeAt(0) ^ 0xaa)).join('');
+        }
+        function pack() { return { iv: '\\x00\\x80', parts: [encrypt('ab'), 'plain'], size: 2 }; }
+    """)
+    assert ctx.call("encrypt", ["ab"], binary=True) == bytes([ord("a") ^ 0xAA, ord("b") ^ 0xAA])
+    assert ctx.call("pack", [], binary=True) == {
+        "iv": b"\x00\x80",
+        "parts": [bytes([ord("a") ^ 0xAA, ord("b") ^ 0xAA]), b"plain"],
+        "size": 2,
+    }
+    # Promise 的结果同样转换
+    assert ctx.evaluate("Promise.resolve('\\xfe')", binary=True) == b"\xfe"
+    del ctx
+    print("[OK] call(binary=True) 和嵌套结构")
+
+
+def test_not_binary():
+    """测试字符大于 0xFF 时抛出 ValueError"""
+    ctx = never_jscore.Context()
+    try:
+        ctx.evaluate("'ok' + '\\u4e2d'", binary=True)
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "U+4E2D" in str(e) and "index 2" in str(e), e
+    del ctx
+    print("[OK] 非二进制字符串抛出 ValueError")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 同样支持 binary=True"""
+    ctx = never_jscore.ThreadedContext()
+    assert ctx.evaluate("String.fromCharCode(0x80)", binary=True) == b"\x80"
+    ctx.compile("function raw() { return '\\xc3\\xa9'; }")
+    assert ctx.call("raw", [], binary=True) == b"\xc3\xa9"
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试二进制字符串结果")
+    print("=" * 60)
+
+    test_evaluate_binary()
+    test_call_binary_nested()
+    test_not_binary()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 binary=True 测试通过！")
+    print("=" * 60)
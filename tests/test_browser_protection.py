@@ -15,6 +15,33 @@ def test_deno_hidden():
     result = ctx.evaluate("typeof Deno")
     assert result == "undefined", f"Deno should be hidden, got: {result}"
 
+    # 内部 ops 只存在于闭包中，用户代码无法访问
+    for name in ("__getDeno", "__internalDeno", "__bootstrap", "__never_jscore_hide_deno__"):
+        assert ctx.evaluate(f"typeof {name}") == "undefined", name
+    assert ctx.evaluate("Object.getOwnPropertyNames(globalThis).filter(k => /deno/i.test(k) && k !== 'Deno')") == []
+
+    # 求值包装的内部变量不在用户代码的作用域中，也不遮蔽同名的全局变量
+    for name in ("ops", "toJson", "checkSchema", "callId"):
+        assert ctx.evaluate(f"typeof {name}") == "undefined", name
+    assert ctx.evaluate("var ops = 1; ops") == 1
+    ctx.compile("globalThis.callId = 'user'")
+    assert ctx.evaluate("callId") == "user"
+
+    # 伪造 op_store_result 不影响结果
+    ctx.evaluate("globalThis.Deno = { core: { ops: { op_store_result() {} } } }; 1")
+    assert ctx.evaluate("1 + 1") == 2
+
+    # 纯净环境同样隐藏
+    pure = never_jscore.Context(enable_extensions=False)
+    assert pure.evaluate("typeof Deno") == "undefined"
+    assert pure.evaluate("[1, 2, 3].map(x => x * 2)") == [2, 4, 6]
+    assert pure.evaluate("Promise.resolve(42)") == 42
+
+    # 从快照启动同样隐藏（快照中保留了 Deno，加载后才隐藏）
+    snap = never_jscore.Context(snapshot=never_jscore.build_snapshot("globalThis.ready = true;"))
+    assert snap.evaluate("ready && typeof Deno") == "undefined"
+    assert snap.evaluate("typeof __bootstrap") == "undefined"
+
     print("✓ Deno 对象已隐藏")
 
 
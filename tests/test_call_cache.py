@@ -0,0 +1,136 @@
+"""
+测试 call() 目标函数缓存：按函数名缓存解析出的函数，不再拼接代码重新解析
+
+函数被替换、重新编译和被 let/const 遮蔽时结果与拼接代码执行一致
+"""
+
+import never_jscore
+
+
+def test_repeated_calls():
+    """测试重复调用和大参数"""
+    ctx = never_jscore.Context()
+    ctx.compile("function sign(data, n) { return data.length + ':' + n; }")
+    for i in range(50):
+        assert ctx.call("sign", ["x" * 1000, i]) == f"1000:{i}"
+
+    big = {"items": list(range(100000))}
+    ctx.compile("function total(obj) { return obj.items.reduce((a, b) => a + b, 0); }")
+    assert ctx.call("total", [big]) == sum(range(100000))
+    assert ctx.call("total", [big]) == sum(range(100000))
+    del ctx
+    print("[OK] 重复调用")
+
+
+def test_replaced_functions():
+    """测试惰性初始化替换函数和重新编译"""
+    ctx = never_jscore.Context()
+    ctx.compile("""
+        var inits = 0;
+        function sign(x) {
+            inits++;
+            sign = function (y) { return 'fast:' + y; };
+            return sign(x);
+        }
+    """)
+    assert ctx.call("sign", [1]) == "fast:1"
+    assert ctx.call("sign", [2]) == "fast:2"
+    assert ctx.evaluate("inits") == 1
+
+    ctx.compile("function sign(x) { return 'v2:' + x; }")
+    assert ctx.call("sign", [3]) == "v2:3"
+    ctx.evaluate("globalThis.sign = (x) => 'v3:' + x")
+    assert ctx.call("sign", [4]) == "v3:4"
+    del ctx
+    print("[OK] 函数被替换")
+
+
+def test_property_paths():
+    """测试属性路径的 this 绑定"""
+    ctx = never_jscore.Context()
+    ctx.compile("""
+        var utils = { crypto: { prefix: 'p', sign(x) { return this.prefix + x; } } };
+    """)
+    assert ctx.call("utils.crypto.sign", ["1"]) == "p1"
+    ctx.evaluate("utils.crypto = { prefix: 'q', sign(x) { return this.prefix + x; } }")
+    assert ctx.call("utils.crypto.sign", ["2"]) == "q2"
+    del ctx
+    print("[OK] 属性路径")
+
+
+def test_lexical_and_fallback():
+    """测试 let/const 声明的函数、遮蔽和其他表达式"""
+    ctx = never_jscore.Context()
+    ctx.compile("const add = (a, b) => a + b;")
+    assert ctx.call("add", [1, 2]) == 3
+    assert ctx.call("add", [3, 4]) == 7
+
+    ctx.evaluate("globalThis.mul = (a, b) => a * b; 1")
+    assert ctx.call("mul", [2, 3]) == 6
+    ctx.eval("let mul = (a, b) => 'shadowed';")
+    assert ctx.call("mul", [2, 3]) == "shadowed"
+
+    ctx.compile("var table = { f: (x) => x * 10 };")
+    assert ctx.call("table['f']", [2]) == 20
+    try:
+        ctx.call("notDefined", [1])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "notDefined is not defined" in str(e), e
+    try:
+        ctx.call("table", [1])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "not a function" in str(e), e
+    del ctx
+    print("[OK] let/const 和其他表达式")
+
+
+def test_errors_and_promises():
+    """测试异常、Promise 和提前返回"""
+    ctx = never_jscore.Context()
+    ctx.compile("""
+        function fail(msg) { throw new Error(msg); }
+        async function later(x) { await null; return x * 2; }
+        function early(x) { __neverjscore_return__(x + 1); return 0; }
+    """)
+    for _ in range(2):
+        try:
+            ctx.call("fail", ["boom"])
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "boom" in str(e), e
+        assert ctx.call("later", [21]) == 42
+        assert ctx.call("early", [1]) == 2
+    del ctx
+    print("[OK] 异常、Promise 和提前返回")
+
+
+def test_threaded_and_pool():
+    """测试 ThreadedContext 和 ContextPool 同样使用缓存"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("function inc(x) { return x + 1; }")
+    assert [ctx.call("inc", [i]) for i in range(5)] == [1, 2, 3, 4, 5]
+    ctx.close()
+
+    pool = never_jscore.ContextPool("function sq(x) { return x * x; }", size=2)
+    assert pool.map("sq", [[i] for i in range(10)]) == [i * i for i in range(10)]
+    pool.close()
+    print("[OK] ThreadedContext 和 ContextPool")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 call() 目标函数缓存")
+    print("=" * 60)
+
+    test_repeated_calls()
+    test_replaced_functions()
+    test_property_paths()
+    test_lexical_and_fallback()
+    test_errors_and_promises()
+    test_threaded_and_pool()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 call() 缓存测试通过！")
+    print("=" * 60)
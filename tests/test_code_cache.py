@@ -0,0 +1,336 @@
+"""
+测试 V8 代码缓存功能
+
+展示如何通过 code_cache_dir 在多次编译（包括跨进程）之间复用 V8 字节码
+"""
+
+import os
+import tempfile
+
+import never_jscore
+
+
+BUNDLE_CODE = """
+    function encrypt(data) { return btoa(md5(data)); }
+    function add(a, b) { return a + b; }
+""" + "\n".join(f"function pad_{i}(x) {{ return x + {i}; }}" for i in range(2000))
+
+
+def _cache_files(cache_dir):
+    return [f for f in os.listdir(cache_dir) if f.endswith(".v8cache")]
+
+
+def test_compile_creates_cache():
+    """测试 compile() 在缓存目录中生成缓存文件"""
+    with tempfile.TemporaryDirectory() as cache_dir:
+        ctx = never_jscore.Context(code_cache_dir=cache_dir)
+        ctx.compile(BUNDLE_CODE)
+
+        assert ctx.call("add", [1, 2]) == 3
+        assert len(_cache_files(cache_dir)) == 1
+
+        print(f"[OK] 生成缓存文件: {_cache_files(cache_dir)[0]}")
+
+
+def test_cache_reused():
+    """测试第二个 Context 命中缓存后结果一致"""
+    with tempfile.TemporaryDirectory() as cache_dir:
+        ctx1 = never_jscore.Context(code_cache_dir=cache_dir)
+        ctx1.compile(BUNDLE_CODE)
+        r1 = ctx1.call("encrypt", ["hello"])
+        del ctx1
+
+        ctx2 = never_jscore.Context(code_cache_dir=cache_dir)
+        ctx2.compile(BUNDLE_CODE)
+        r2 = ctx2.call("encrypt", ["hello"])
+
+        assert r1 == r2
+        assert len(_cache_files(cache_dir)) == 1
+
+        print(f"[OK] 缓存命中，结果一致: {r2}")
+
+
+def test_corrupted_cache_regenerated():
+    """测试损坏的缓存文件会被 V8 拒绝并重新生成"""
+    with tempfile.TemporaryDirectory() as cache_dir:
+        ctx = never_jscore.Context(code_cache_dir=cache_dir)
+        ctx.compile(BUNDLE_CODE)
+        del ctx
+
+        cache_file = os.path.join(cache_dir, _cache_files(cache_dir)[0])
+        with open(cache_file, "wb") as f:
+            f.write(b"garbage")
+
+        ctx = never_jscore.Context(code_cache_dir=cache_dir)
+        ctx.compile(BUNDLE_CODE)
+        assert ctx.call("add", [2, 3]) == 5
+        assert os.path.getsize(cache_file) > len(b"garbage")
+
+        print("[OK] 损坏的缓存被自动重新生成")
+
+
+def test_compile_file():
+    """测试 compile_file() 从文件加载代码"""
+    with tempfile.TemporaryDirectory() as tmp:
+        path = os.path.join(tmp, "bundle.js")
+        with open(path, "w", encoding="utf-8") as f:
+            f.write(BUNDLE_CODE)
+
+        ctx = never_jscore.Context(code_cache_dir=os.path.join(tmp, "cache"))
+        ctx.compile_file(path)
+        assert ctx.call("add", [10, 20]) == 30
+
+        print("[OK] compile_file() 正常加载")
+
+
+def test_compile_file_error_shows_path():
+    """测试 compile_file() 的错误堆栈包含文件路径"""
+    with tempfile.TemporaryDirectory() as tmp:
+        path = os.path.join(tmp, "broken.js")
+        with open(path, "w", encoding="utf-8") as f:
+            f.write("function ok() {}\nthrow new Error('boom');")
+
+        ctx = never_jscore.Context()
+        try:
+            ctx.compile_file(path)
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "boom" in str(e)
+            assert "broken.js" in str(e)
+            print("[OK] 错误信息包含文件路径")
+
+
+def test_compile_file_missing():
+    """测试文件不存在时报错"""
+    ctx = never_jscore.Context()
+    try:
+        ctx.compile_file("/nonexistent/never_jscore.js")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        print(f"[OK] 文件不存在: {e}")
+
+
+def test_compile_file_encoding():
+    """测试 compile_file() 的 encoding、BOM 和 shebang 处理"""
+    with tempfile.TemporaryDirectory() as tmp:
+        # gbk 编码的文件
+        path = os.path.join(tmp, "legacy.js")
+        with open(path, "wb") as f:
+            f.write("var greeting = '你好，世界';".encode("gbk"))
+
+        ctx = never_jscore.Context()
+        try:
+            ctx.compile_file(path)
+            assert False, "应该抛出 ValueError"
+        except ValueError as e:
+            assert "encoding" in str(e)
+        ctx.compile_file(path, encoding="gbk")
+        assert ctx.evaluate("greeting") == "你好，世界"
+
+        # BOM 和 shebang，错误行号不变
+        path = os.path.join(tmp, "cli.js")
+        with open(path, "w", encoding="utf-8-sig") as f:
+            f.write("#!/usr/bin/env node\nvar fromCli = 1;\nthrow new Error('line3');")
+        try:
+            ctx.compile_file(path)
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "cli.js:3" in str(e), e
+        assert ctx.evaluate("fromCli") == 1
+
+        try:
+            ctx.compile_file(path, encoding="no-such-codec")
+            assert False, "应该抛出 ValueError"
+        except ValueError:
+            pass
+
+    print("[OK] compile_file() encoding / BOM / shebang")
+
+
+def test_compile_file_mode():
+    """测试 compile_file() 按扩展名选择脚本 / 模块，mode 参数覆盖"""
+    with tempfile.TemporaryDirectory() as tmp:
+        module = os.path.join(tmp, "sign.mjs")
+        with open(module, "w", encoding="utf-8") as f:
+            f.write(
+                "const secret = await Promise.resolve('k');\n"
+                "export function sign(x) { return btoa(secret + x); }\n"
+                "export const VERSION = 2;\n"
+                "export default function hidden() {}\n"
+            )
+
+        ctx = never_jscore.Context()
+        ctx.compile_file(module)
+        assert ctx.call("sign", ["data"]) == "a2RhdGE="
+        assert ctx.evaluate("VERSION") == 2
+        # 模块内部变量和 default 导出不会成为全局变量
+        assert ctx.evaluate("typeof secret") == "undefined"
+        assert ctx.evaluate("typeof hidden") == "undefined"
+
+        # 同一个文件可以再次加载
+        ctx.compile_file(module)
+
+        # .js 文件用 mode="module" 作为模块执行
+        plain = os.path.join(tmp, "esm.js")
+        with open(plain, "w", encoding="utf-8") as f:
+            f.write("export const answer = 42;")
+        try:
+            ctx.compile_file(plain)
+            assert False, "export 在脚本中是语法错误"
+        except Exception:
+            pass
+        ctx.compile_file(plain, mode="module")
+        assert ctx.evaluate("answer") == 42
+
+        threaded = never_jscore.ThreadedContext()
+        threaded.compile_file(module)
+        assert threaded.call("sign", ["data"]) == "a2RhdGE="
+        threaded.close()
+
+        # TypeScript 需要先转换为 JavaScript
+        ts = os.path.join(tmp, "sign.ts")
+        with open(ts, "w", encoding="utf-8") as f:
+            f.write("export const n: number = 1;")
+        for kwargs in ({}, {"mode": "typescript"}, {"mode": "jsx"}):
+            try:
+                ctx.compile_file(ts, **kwargs)
+                assert False, "应该抛出 ValueError"
+            except ValueError:
+                pass
+
+    print("[OK] compile_file() 脚本 / 模块模式")
+
+
+def _rewrite(path, code):
+    """重写文件并推后修改时间（避免文件系统时间精度导致检测不到修改）"""
+    before = os.stat(path).st_mtime
+    with open(path, "w", encoding="utf-8") as f:
+        f.write(code)
+    os.utime(path, (before + 2, before + 2))
+
+
+def test_compile_file_watch():
+    """测试 compile_file(watch=True) 在下一次调用前重新编译修改过的文件"""
+    with tempfile.TemporaryDirectory() as tmp:
+        path = os.path.join(tmp, "sign.js")
+        with open(path, "w", encoding="utf-8") as f:
+            f.write("function sign(x) { return 'v1:' + x; }")
+
+        ctx = never_jscore.Context()
+        ctx.compile_file(path, watch=True)
+        assert ctx.call("sign", ["a"]) == "v1:a"
+
+        _rewrite(path, "function sign(x) { return 'v2:' + x; }")
+        assert ctx.call("sign", ["a"]) == "v2:a"
+        assert ctx.evaluate("sign('b')") == "v2:b"
+
+        # 重新编译失败时该次调用抛出错误，修复后恢复
+        _rewrite(path, "function sign(x) { return ")
+        try:
+            ctx.call("sign", ["a"])
+            assert False, "应该抛出重新编译的错误"
+        except Exception as e:
+            assert "Failed to reload" in str(e) and "sign.js" in str(e), e
+        assert ctx.call("sign", ["a"]) == "v2:a"
+        _rewrite(path, "function sign(x) { return 'v3:' + x; }")
+        assert ctx.call("sign", ["a"]) == "v3:a"
+
+        # 不带 watch 再次编译时停止监视
+        ctx.compile_file(path)
+        _rewrite(path, "function sign(x) { return 'v4:' + x; }")
+        assert ctx.call("sign", ["a"]) == "v3:a"
+        del ctx
+
+        threaded = never_jscore.ThreadedContext()
+        threaded.compile_file(path, watch=True)
+        assert threaded.call("sign", ["a"]) == "v4:a"
+        _rewrite(path, "function sign(x) { return 'v5:' + x; }")
+        assert threaded.call("sign", ["a"]) == "v5:a"
+        threaded.close()
+
+    print("[OK] compile_file(watch=True) 自动重新编译")
+
+
+def test_compile_background():
+    """测试 compile_background() 在后台线程编译，wait() 后函数可用"""
+    ctx = never_jscore.Context()
+    task = ctx.compile_background(BUNDLE_CODE)
+
+    # 编译期间 Python 可以继续工作
+    busy = sum(range(100000))
+    assert busy > 0
+
+    task.wait()
+    assert task.done()
+    assert ctx.call("add", [1, 2]) == 3
+    assert ctx.call("pad_1999", [1]) == 2000
+
+    # 多次 wait() 不会重复执行
+    ctx.compile("var counter = 0;")
+    task2 = ctx.compile_background("counter++;")
+    task2.wait()
+    task2.wait()
+    assert ctx.evaluate("counter") == 1
+
+    print("[OK] compile_background() 后台编译")
+
+
+def test_compile_background_with_cache_dir():
+    """测试 compile_background() 写入磁盘缓存，之后的 compile() 命中"""
+    with tempfile.TemporaryDirectory() as cache_dir:
+        ctx1 = never_jscore.Context(code_cache_dir=cache_dir)
+        ctx1.compile_background(BUNDLE_CODE).wait()
+        assert len(_cache_files(cache_dir)) == 1
+
+        ctx2 = never_jscore.Context(code_cache_dir=cache_dir)
+        ctx2.compile(BUNDLE_CODE)
+        assert ctx2.call("encrypt", ["hello"]) == ctx1.call("encrypt", ["hello"])
+
+    print("[OK] compile_background() 复用磁盘缓存")
+
+
+def test_compile_background_errors():
+    """测试后台编译的语法错误和执行错误"""
+    ctx = never_jscore.Context()
+
+    task = ctx.compile_background("function broken( {")
+    for _ in range(2):
+        try:
+            task.wait()
+            assert False, "应该抛出语法错误"
+        except Exception as e:
+            assert "SyntaxError" in str(e), str(e)
+
+    try:
+        ctx.compile_background("throw new Error('boom at load')").wait()
+        assert False, "应该抛出执行错误"
+    except Exception as e:
+        assert "boom at load" in str(e)
+
+    # Context 仍然可用
+    assert ctx.evaluate("1 + 1") == 2
+
+    print("[OK] compile_background() 错误处理")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 V8 代码缓存")
+    print("=" * 60)
+
+    test_compile_creates_cache()
+    test_cache_reused()
+    test_corrupted_cache_regenerated()
+    test_compile_file()
+    test_compile_file_error_shows_path()
+    test_compile_file_missing()
+    test_compile_file_encoding()
+    test_compile_file_mode()
+    test_compile_file_watch()
+    test_compile_background()
+    test_compile_background_with_cache_dir()
+    test_compile_background_errors()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有代码缓存测试通过！")
+    print("=" * 60)
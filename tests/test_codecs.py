@@ -0,0 +1,107 @@
+"""
+测试 __codecs：Rust 实现的 base64 / hex 编解码，以及基于它的 atob / btoa / Buffer
+"""
+
+import base64
+import os
+import time
+
+import never_jscore
+
+
+def test_base64():
+    """测试 b64encode / b64decode（标准 / URL-safe、补齐、空白）"""
+    ctx = never_jscore.Context()
+    data = list(os.urandom(64)) + [0xfb, 0xff, 0xfe]
+    ctx.eval(f"var data = new Uint8Array({data});")
+    raw = bytes(data)
+
+    assert ctx.evaluate("__codecs.b64encode(data)") == base64.b64encode(raw).decode()
+    assert ctx.evaluate("__codecs.b64encode(data, { urlSafe: true })") == base64.urlsafe_b64encode(raw).decode().rstrip("=")
+    assert ctx.evaluate("__codecs.b64encode(data, { urlSafe: true, pad: true })") == base64.urlsafe_b64encode(raw).decode()
+    assert ctx.evaluate("__codecs.b64encode(data.buffer)") == base64.b64encode(raw).decode()
+    assert ctx.evaluate("__codecs.b64encode(new DataView(data.buffer, 1, 3))") == base64.b64encode(raw[1:4]).decode()
+    assert ctx.evaluate("__codecs.b64encode('中文')") == base64.b64encode("中文".encode()).decode()
+    assert ctx.evaluate("__codecs.b64encode([1, 2, 3])") == "AQID"
+
+    assert ctx.evaluate("Array.from(__codecs.b64decode(__codecs.b64encode(data)))") == data
+    assert ctx.evaluate("Array.from(__codecs.b64decode(__codecs.b64encode(data, { urlSafe: true })))") == data
+    assert ctx.evaluate("Array.from(__codecs.b64decode(' AQ\\nID '))") == [1, 2, 3]
+    assert ctx.evaluate("__codecs.b64decode('AQ==') instanceof Uint8Array") is True
+    assert ctx.evaluate("Object.keys(globalThis).includes('__codecs')") is False
+    del ctx
+    print("[OK] base64")
+
+
+def test_hex():
+    """测试 hexEncode / hexDecode"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("__codecs.hexEncode(new Uint8Array([0, 15, 255]))") == "000fff"
+    assert ctx.evaluate("Array.from(__codecs.hexDecode('000FfF'))") == [0, 15, 255]
+    assert ctx.evaluate("__codecs.hexDecode('').length") == 0
+    del ctx
+    print("[OK] hex")
+
+
+def test_invalid_input():
+    """测试无效输入抛出 InvalidCharacterError"""
+    ctx = never_jscore.Context()
+    for expr in ("__codecs.b64decode('A')", "__codecs.b64decode('A$==')", "__codecs.hexDecode('abc')",
+                 "__codecs.hexDecode('zz')", "atob('A')", "atob('YQ-_')", "btoa('中文')"):
+        assert ctx.evaluate(f"(() => {{ try {{ {expr}; return 'no error'; }} catch (e) {{ return e.name; }} }})()") \
+            == "InvalidCharacterError", expr
+    assert ctx.evaluate("(() => { try { __codecs.b64encode(42); } catch (e) { return e instanceof TypeError; } })()") is True
+    del ctx
+    print("[OK] 无效输入")
+
+
+def test_atob_btoa():
+    """测试 atob / btoa 与浏览器一致（Latin-1、宽松解码）"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("btoa('hello')") == "aGVsbG8="
+    assert ctx.evaluate("btoa('\\xff\\xfe')") == "//4="
+    assert ctx.evaluate("atob('//4=').split('').map(c => c.charCodeAt(0))") == [255, 254]
+    assert ctx.evaluate("atob('aGVsbG8')") == "hello"
+    assert ctx.evaluate("atob(' aGVs\\nbG8= ')") == "hello"
+    assert ctx.evaluate("atob(btoa(String.fromCharCode(...Array.from({length: 256}, (_, i) => i)))).length") == 256
+    del ctx
+    print("[OK] atob / btoa")
+
+
+def test_buffer():
+    """测试 Buffer 的 base64 / base64url / hex 使用 __codecs，大缓冲区不溢出"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("Buffer.from('hello').toString('base64')") == "aGVsbG8="
+    assert ctx.evaluate("Buffer.from('aGVsbG8', 'base64').toString()") == "hello"
+    assert ctx.evaluate("Buffer.from([0xfb, 0xff]).toString('base64url')") == "-_8"
+    assert ctx.evaluate("Array.from(Buffer.from('-_8', 'base64url'))") == [0xfb, 0xff]
+    assert ctx.evaluate("Buffer.from('68656c6c6fzz', 'hex').toString()") == "hello"
+    assert ctx.evaluate("Buffer.from('hello').toString('hex')") == "68656c6c6f"
+
+    start = time.time()
+    size = ctx.evaluate("""
+        const big = new Uint8Array(8 * 1024 * 1024).map((_, i) => i * 7);
+        const text = Buffer.from(big).toString('base64');
+        const back = Buffer.from(text, 'base64');
+        back.length === big.length && back[12345] === big[12345] ? text.length : -1
+    """)
+    assert size == (8 * 1024 * 1024 + 2) // 3 * 4, size
+    elapsed = time.time() - start
+    del ctx
+    print(f"[OK] Buffer（8 MB 往返 {elapsed:.2f}s）")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 __codecs")
+    print("=" * 60)
+
+    test_base64()
+    test_hex()
+    test_invalid_input()
+    test_atob_btoa()
+    test_buffer()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有编解码测试通过！")
+    print("=" * 60)
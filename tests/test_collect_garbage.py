@@ -0,0 +1,108 @@
+"""
+测试确定性 GC：collect_garbage() 和 weak_refs
+
+collect_garbage() 返回时失去引用的对象已被回收，FinalizationRegistry 的清理回调已经执行
+"""
+
+import pickle
+
+import never_jscore
+
+
+# 注册一个立即失去引用的对象，返回指向它的 WeakRef
+SETUP = """
+globalThis.freed = [];
+globalThis.registry = new FinalizationRegistry(token => freed.push(token));
+globalThis.makeGarbage = token => {
+    const obj = { token };
+    registry.register(obj, token);
+    return new WeakRef(obj);
+};
+"""
+
+
+def test_finalization_registry():
+    """测试 collect_garbage() 之后清理回调已经执行"""
+    ctx = never_jscore.Context()
+    ctx.eval(SETUP)
+    ctx.eval("globalThis.ref = makeGarbage('a');")
+    ctx.collect_garbage()
+    assert ctx.evaluate("freed") == ["a"]
+    assert ctx.evaluate("ref.deref() === undefined") is True
+
+    # 仍然被引用的对象不会被回收
+    ctx.eval("globalThis.kept = {}; registry.register(kept, 'kept');")
+    ctx.collect_garbage()
+    assert ctx.evaluate("freed") == ["a"]
+    del ctx
+    print("[OK] FinalizationRegistry 回调")
+
+
+def test_single_pass():
+    """测试 full=False 只执行一次完整 GC"""
+    ctx = never_jscore.Context()
+    ctx.eval(SETUP)
+    ctx.eval("for (let i = 0; i < 100; i++) makeGarbage(i);")
+    ctx.collect_garbage(full=False)
+    assert ctx.evaluate("freed.length") == 100
+    del ctx
+    print("[OK] full=False")
+
+
+def test_deref_in_same_call():
+    """测试同一次调用中 deref() 过的对象在 collect_garbage() 时也会被回收"""
+    ctx = never_jscore.Context()
+    ctx.eval(SETUP)
+    ctx.eval("globalThis.ref = makeGarbage('b'); ref.deref().token;")
+    ctx.collect_garbage()
+    assert ctx.evaluate("ref.deref()") is None
+    assert ctx.evaluate("freed") == ["b"]
+    del ctx
+    print("[OK] deref() 保留的目标被释放")
+
+
+def test_weak_refs_disabled():
+    """测试 weak_refs=False 删除 WeakRef / FinalizationRegistry"""
+    ctx = never_jscore.Context(weak_refs=False)
+    assert ctx.evaluate("typeof WeakRef") == "undefined"
+    assert ctx.evaluate("typeof FinalizationRegistry") == "undefined"
+    # WeakMap / WeakSet 不受影响
+    assert ctx.evaluate("typeof WeakMap") == "function"
+    ctx.collect_garbage()
+
+    # pickle 保留设置
+    restored = pickle.loads(pickle.dumps(ctx))
+    assert restored.evaluate("typeof WeakRef") == "undefined"
+    del ctx, restored
+    print("[OK] weak_refs=False")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext.collect_garbage()"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.eval(SETUP)
+    ctx.eval("globalThis.ref = makeGarbage('c');")
+    ctx.collect_garbage()
+    assert ctx.evaluate("freed") == ["c"]
+    ctx.close()
+
+    ctx = never_jscore.ThreadedContext(weak_refs=False)
+    assert ctx.evaluate("typeof WeakRef") == "undefined"
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 collect_garbage")
+    print("=" * 60)
+
+    test_finalization_registry()
+    test_single_pass()
+    test_deref_in_same_call()
+    test_weak_refs_disabled()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 collect_garbage 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,169 @@
+"""
+测试 compile_url(url, sha256=...)：下载、校验、缓存并编译远程脚本（本地 HTTP 服务器）
+"""
+
+import functools
+import hashlib
+import http.server
+import os
+import tempfile
+import threading
+
+import never_jscore
+
+SIGN = "var loaded = (globalThis.loaded || 0) + 1;\nfunction sign(s) { return 'v1:' + s; }\nfunction where() { return new Error('here').stack; }\n"
+MODULE = "export const url = import.meta.url;\nexport function sign(s) { return 'm:' + s; }\n"
+
+
+class QuietHandler(http.server.SimpleHTTPRequestHandler):
+    def log_message(self, *args):
+        pass
+
+
+class Server:
+    """在临时目录上启动本地 HTTP 服务器"""
+
+    def __init__(self, files):
+        self.dir = tempfile.TemporaryDirectory()
+        for name, code in files.items():
+            with open(os.path.join(self.dir.name, name), "w", encoding="utf-8") as f:
+                f.write(code)
+        handler = functools.partial(QuietHandler, directory=self.dir.name)
+        self.httpd = http.server.ThreadingHTTPServer(("127.0.0.1", 0), handler)
+        self.thread = threading.Thread(target=self.httpd.serve_forever, daemon=True)
+        self.thread.start()
+
+    def url(self, name):
+        return "http://127.0.0.1:%d/%s" % (self.httpd.server_address[1], name)
+
+    def stop(self):
+        self.httpd.shutdown()
+        self.httpd.server_close()
+        self.dir.cleanup()
+
+
+def sha256(code):
+    return hashlib.sha256(code.encode("utf-8")).hexdigest()
+
+
+def test_download_and_verify():
+    """测试返回实际的哈希值，哈希不一致时不执行"""
+    server = Server({"sign.js": SIGN})
+    try:
+        with tempfile.TemporaryDirectory() as cache:
+            ctx = never_jscore.Context()
+            digest = ctx.compile_url(server.url("sign.js"), cache_dir=cache)
+            assert digest == sha256(SIGN), digest
+            assert ctx.call("sign", ["x"]) == "v1:x"
+            assert os.listdir(cache) == []  # 未校验的脚本不缓存
+
+            try:
+                ctx.compile_url(server.url("sign.js"), sha256="0" * 64, cache_dir=cache)
+                assert False, "应该抛出 ValueError"
+            except ValueError as e:
+                assert "sha256 mismatch" in str(e) and digest in str(e), e
+            assert ctx.evaluate("loaded") == 1
+            assert os.listdir(cache) == []
+
+            assert ctx.compile_url(server.url("sign.js"), sha256=digest.upper(), cache_dir=cache) == digest
+            assert ctx.evaluate("loaded") == 2
+            assert os.listdir(cache) == [digest]
+            assert server.url("sign.js") in ctx.call("where", []), ctx.call("where", [])
+            del ctx
+    finally:
+        server.stop()
+    print("[OK] 下载与校验")
+
+
+def test_cache():
+    """测试服务器停止后从缓存加载，损坏的缓存文件重新下载"""
+    server = Server({"sign.js": SIGN})
+    digest = sha256(SIGN)
+    url = server.url("sign.js")
+    with tempfile.TemporaryDirectory() as cache:
+        try:
+            ctx = never_jscore.Context()
+            ctx.compile_url(url, sha256=digest, cache_dir=cache)
+
+            with open(os.path.join(cache, digest), "w", encoding="utf-8") as f:
+                f.write("function sign(s) { return 'tampered'; }")
+            ctx.compile_url(url, sha256=digest, cache_dir=cache)
+            assert ctx.call("sign", ["x"]) == "v1:x"
+        finally:
+            server.stop()
+
+        # 离线：直接使用缓存
+        offline = never_jscore.Context()
+        assert offline.compile_url(url, sha256=digest, cache_dir=cache) == digest
+        assert offline.call("sign", ["y"]) == "v1:y"
+        try:
+            offline.compile_url(url, cache_dir=cache, timeout_ms=2000)
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "Failed to download" in str(e), e
+        del ctx, offline
+    print("[OK] 磁盘缓存")
+
+
+def test_module_and_function():
+    """测试 .mjs 作为 ES 模块加载，模块级 compile_url() 返回新的 Context"""
+    server = Server({"app.mjs": MODULE, "sign.js": SIGN})
+    try:
+        ctx = never_jscore.Context()
+        ctx.compile_url(server.url("app.mjs"))
+        assert ctx.evaluate("url").startswith(server.url("app.mjs")), ctx.evaluate("url")
+        assert ctx.call("sign", ["x"]) == "m:x"
+
+        with tempfile.TemporaryDirectory() as cache:
+            other = never_jscore.compile_url(server.url("sign.js"), sha256=sha256(SIGN), cache_dir=cache)
+        assert isinstance(other, never_jscore.Context)
+        assert other.call("sign", ["z"]) == "v1:z"
+        del ctx, other
+    finally:
+        server.stop()
+    print("[OK] ES 模块与模块级函数")
+
+
+def test_errors():
+    """测试无效的 sha256、URL 和 HTTP 错误"""
+    server = Server({})
+    try:
+        ctx = never_jscore.Context()
+        for kwargs in ({"sha256": "abc"}, {"sha256": "g" * 64}):
+            try:
+                ctx.compile_url(server.url("x.js"), **kwargs)
+                assert False, "应该抛出 ValueError"
+            except ValueError as e:
+                assert "sha256" in str(e), e
+
+        for url in ("file:///etc/passwd", "not a url"):
+            try:
+                ctx.compile_url(url)
+                assert False, "应该抛出 ValueError"
+            except ValueError:
+                pass
+
+        try:
+            ctx.compile_url(server.url("missing.js"))
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "Failed to download" in str(e) and "404" in str(e), e
+        del ctx
+    finally:
+        server.stop()
+    print("[OK] 错误处理")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 compile_url()")
+    print("=" * 60)
+
+    test_download_and_verify()
+    test_cache()
+    test_module_and_function()
+    test_errors()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 compile_url 测试通过！")
+    print("=" * 60)
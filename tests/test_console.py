@@ -0,0 +1,122 @@
+"""
+测试 console 命名空间：table / group / time / count / assert 等方法
+
+console 输出直接写到进程的 stdout（不经过 Python 的 sys.stdout），
+因此在子进程中执行并检查其输出
+"""
+
+import subprocess
+import sys
+
+import never_jscore
+
+
+def run_js(code):
+    """在子进程中执行 JS，返回 stdout"""
+    script = f"import never_jscore\nnever_jscore.Context().evaluate({code!r})\n"
+    result = subprocess.run([sys.executable, "-c", script], capture_output=True, text=True, timeout=60)
+    assert result.returncode == 0, result.stderr
+    return result.stdout
+
+
+def test_console_methods_exist():
+    """测试所有 console 方法都可以调用"""
+    ctx = never_jscore.Context()
+    methods = ctx.evaluate("""
+        ['log', 'info', 'debug', 'warn', 'error', 'dir', 'dirxml', 'trace', 'table', 'assert',
+         'count', 'countReset', 'group', 'groupCollapsed', 'groupEnd', 'time', 'timeLog', 'timeEnd', 'clear']
+            .filter(name => typeof console[name] !== 'function')
+    """)
+    assert methods == [], methods
+    del ctx
+    print("[OK] console 方法齐全")
+
+
+def test_console_table():
+    """测试 console.table 输出表格"""
+    out = run_js("console.table([{ a: 1, b: 'Y' }, { a: 'Z', c: 2 }])")
+    assert "│ (index) │ a   │ b   │ c │" in out, out
+    assert "│ 0       │ 1   │ 'Y' │   │" in out, out
+    assert "│ 1       │ 'Z' │     │ 2 │" in out, out
+    assert out.splitlines()[0].startswith("┌")
+    print("[OK] console.table")
+
+
+def test_console_group_count_time():
+    """测试 group 缩进、count 计数、time 计时和 assert"""
+    out = run_js("""
+        console.group('outer');
+        console.log('inside %s=%d', 'x', 42);
+        console.groupEnd();
+        console.log('outside');
+        console.count(); console.count(); console.count('k');
+        console.time('t'); console.timeEnd('t');
+        console.assert(1 === 2, 'math is broken');
+        console.assert(true, 'not printed');
+        console.info('info works');
+    """)
+    lines = out.splitlines()
+    assert lines[:3] == ["outer", "  inside x=42", "outside"], lines
+    assert "default: 1" in lines and "default: 2" in lines and "k: 1" in lines, lines
+    assert any(line.startswith("t: ") and line.endswith("ms") for line in lines), lines
+    assert "Assertion failed: math is broken" in lines, lines
+    assert "not printed" not in out
+    assert "info works" in lines, lines
+    print("[OK] console.group / count / time / assert")
+
+
+def test_console_objects():
+    """测试对象输出：循环引用不会抛出异常"""
+    out = run_js("const o = { a: 1 }; o.self = o; console.log(o, 10n, undefined)")
+    assert '"self": "[Circular]"' in out, out
+    assert "10n undefined" in out, out
+    print("[OK] console 输出对象")
+
+
+def test_capture_output():
+    """测试 eval(capture_output=True) 返回本次调用的 console 输出"""
+    ctx = never_jscore.Context()
+    result, output = ctx.eval("console.log('step', 1); console.error('oops'); 6 * 7",
+                              return_value=True, capture_output=True)
+    assert result == 42
+    assert output == [
+        {"stream": "stdout", "message": "step 1"},
+        {"stream": "stderr", "message": "oops"},
+    ], output
+
+    # 不需要返回值时结果为 None；每次调用只包含自己的输出
+    result, output = ctx.eval("console.log('second')", capture_output=True)
+    assert result is None
+    assert [entry["message"] for entry in output] == ["second"], output
+
+    # 异步代码中的输出同样被收集
+    result, output = ctx.eval("(async () => { await 0; console.info('later'); return 'done'; })()",
+                              return_value=True, capture_output=True)
+    assert result == "done"
+    assert output == [{"stream": "stdout", "message": "later"}], output
+
+    # 收集的输出不写到 stdout
+    script = ("import never_jscore\n"
+              "r, o = never_jscore.Context().eval(\"console.log('hidden')\", capture_output=True)\n"
+              "print(len(o))\n")
+    proc = subprocess.run([sys.executable, "-c", script], capture_output=True, text=True, timeout=60)
+    assert proc.returncode == 0, proc.stderr
+    assert "hidden" not in proc.stdout and proc.stdout.strip() == "1", proc.stdout
+    del ctx
+    print("[OK] eval(capture_output=True)")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 console 命名空间")
+    print("=" * 60)
+
+    test_console_methods_exist()
+    test_console_table()
+    test_console_group_count_time()
+    test_console_objects()
+    test_capture_output()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 console 测试通过！")
+    print("=" * 60)
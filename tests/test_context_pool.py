@@ -0,0 +1,184 @@
+"""
+测试 ContextPool
+
+展示如何在多线程服务中共享一组预热好的 Context
+"""
+
+import threading
+import time
+from concurrent.futures import ThreadPoolExecutor
+
+import never_jscore
+
+
+JS_CODE = """
+    function sign(data) { return md5(data + ':salt'); }
+    function add(a, b) { return a + b; }
+    async function delayed(x) {
+        await new Promise(r => setTimeout(r, 10));
+        return x * 2;
+    }
+    function busy(n) { let s = 0; for (let i = 0; i < n; i++) { s += i; } return s; }
+"""
+
+
+def test_basic_call():
+    """测试基本调用"""
+    pool = never_jscore.ContextPool(JS_CODE, size=2)
+
+    assert pool.size == 2
+    assert pool.call("add", [1, 2]) == 3
+    assert pool.call("delayed", [21]) == 42
+    assert pool.evaluate("typeof sign") == "function"
+
+    pool.close()
+    print("[OK] 基本调用")
+
+
+def test_shared_across_threads():
+    """测试多个 Python 线程共享同一个池"""
+    pool = never_jscore.ContextPool(JS_CODE, size=4)
+    expected = {i: pool.call("sign", [str(i)]) for i in range(5)}
+
+    def handler(i):
+        return i % 5, pool.call("sign", [str(i % 5)])
+
+    with ThreadPoolExecutor(max_workers=8) as executor:
+        results = list(executor.map(handler, range(200)))
+
+    for key, value in results:
+        assert value == expected[key]
+
+    pool.close()
+    print(f"[OK] 8 个线程共享池，{len(results)} 次调用结果一致")
+
+
+def test_parallel_speedup():
+    """测试多个工作线程并行执行"""
+    with never_jscore.ContextPool(JS_CODE, size=4) as pool:
+        pool.call("busy", [1000])  # 预热
+
+        start = time.perf_counter()
+        for _ in range(4):
+            pool.call("busy", [5e7])
+        serial = time.perf_counter() - start
+
+        start = time.perf_counter()
+        threads = [threading.Thread(target=pool.call, args=("busy", [5e7])) for _ in range(4)]
+        for t in threads:
+            t.start()
+        for t in threads:
+            t.join()
+        parallel = time.perf_counter() - start
+
+    print(f"[OK] 串行: {serial * 1000:.0f}ms, 4 线程并行: {parallel * 1000:.0f}ms")
+
+
+def test_errors():
+    """测试 JS 错误和初始化错误"""
+    pool = never_jscore.ContextPool(JS_CODE, size=1)
+    try:
+        pool.call("notDefined", [])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "notDefined" in str(e)
+
+    # 出错后工作线程仍可用
+    assert pool.call("add", [2, 2]) == 4
+    pool.close()
+
+    try:
+        never_jscore.ContextPool("throw new Error('init failed')", size=2)
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "init failed" in str(e)
+
+    try:
+        never_jscore.ContextPool(JS_CODE, size=0)
+        assert False, "应该抛出 ValueError"
+    except ValueError:
+        pass
+
+    print("[OK] 错误处理")
+
+
+def test_map():
+    """测试 map() 并行批量调用"""
+    with never_jscore.ContextPool(JS_CODE, size=4) as pool:
+        items = [[i, i] for i in range(100)]
+        assert pool.map("add", items) == [i * 2 for i in range(100)]
+
+        # 非 list 的项作为单个参数
+        signs = pool.map("sign", (str(i) for i in range(10)))
+        assert signs == [pool.call("sign", [str(i)]) for i in range(10)]
+
+        # Promise 结果
+        assert pool.map("delayed", [1, 2, 3]) == [2, 4, 6]
+        assert pool.map("add", []) == []
+
+    print("[OK] map() 结果按输入顺序返回")
+
+
+def test_map_parallel():
+    """测试 map() 使用多个工作线程并行执行"""
+    with never_jscore.ContextPool(JS_CODE, size=4) as pool:
+        items = [5e7] * 8
+
+        start = time.perf_counter()
+        pool.map("busy", items, workers=1)
+        serial = time.perf_counter() - start
+
+        start = time.perf_counter()
+        pool.map("busy", items, workers=4)
+        parallel = time.perf_counter() - start
+
+    print(f"[OK] map() workers=1: {serial * 1000:.0f}ms, workers=4: {parallel * 1000:.0f}ms")
+
+
+def test_map_error():
+    """测试 map() 中某一项失败"""
+    with never_jscore.ContextPool(JS_CODE + "function check(x) { if (x === 3) throw new Error('bad item'); return x; }", size=2) as pool:
+        try:
+            pool.map("check", list(range(6)))
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "item 3" in str(e)
+            assert "bad item" in str(e)
+
+        assert pool.map("check", [1, 2]) == [1, 2]
+
+    print("[OK] map() 错误包含失败项索引")
+
+
+def test_closed_pool():
+    """测试关闭后的池"""
+    pool = never_jscore.ContextPool(JS_CODE, size=2)
+    pool.close()
+    pool.close()  # 重复关闭是安全的
+
+    try:
+        pool.call("add", [1, 2])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "closed" in str(e)
+
+    print("[OK] 关闭后调用抛出异常")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 ContextPool")
+    print("=" * 60)
+
+    test_basic_call()
+    test_shared_across_threads()
+    test_parallel_speedup()
+    test_errors()
+    test_map()
+    test_map_parallel()
+    test_map_error()
+    test_closed_pool()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有 ContextPool 测试通过！")
+    print("=" * 60)
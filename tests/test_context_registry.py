@@ -0,0 +1,188 @@
+"""
+测试 ContextRegistry：按键延迟创建并缓存 Context / Realm，按 LRU / ttl 释放
+"""
+
+import time
+
+import never_jscore
+
+SITE_A = "var created = Date.now(); function sign(s) { return 'a:' + s; }"
+SITE_B = "function sign(s) { return 'b:' + s; }"
+
+
+def test_lazy_and_cached():
+    """测试第一次使用时创建，之后复用同一个实例"""
+    registry = never_jscore.ContextRegistry()
+    registry.define("a", SITE_A)
+    registry.define("b", SITE_B, max_heap_mb=64)
+    assert len(registry) == 0 and "a" not in registry
+
+    assert registry.call("a", "sign", ["x"]) == "a:x"
+    assert registry.call("b", "sign", ["x"]) == "b:x"
+    ctx = registry.get("a")
+    assert isinstance(ctx, never_jscore.Context)
+    assert ctx is registry.get("a")
+    ctx.eval("globalThis.state = 1;")
+    assert registry.get("a").evaluate("state") == 1
+    assert len(registry) == 2 and "a" in registry
+    assert registry.stats() == {"cached": 2, "defined": 2, "evictions": 0}
+
+    try:
+        registry.get("missing")
+        assert False, "应该抛出 KeyError"
+    except KeyError as e:
+        assert "missing" in str(e), e
+    del ctx, registry
+    print("[OK] 延迟创建并复用")
+
+
+def test_lru():
+    """测试超出 max_size 时释放最久未使用的实例"""
+    registry = never_jscore.ContextRegistry(max_size=2)
+    for key in ("a", "b", "c"):
+        registry.define(key, SITE_A)
+    registry.get("a")
+    registry.get("b")
+    registry.get("a")
+    registry.get("c")
+    assert registry.keys() == ["c", "a"], registry.keys()
+    assert registry.stats()["evictions"] == 1
+
+    # 释放的键再次使用时按定义重新创建
+    registry.get("a").eval("globalThis.mark = 1;")
+    registry.get("b")
+    assert "c" not in registry
+    assert registry.get("b").evaluate("typeof mark") == "undefined"
+    del registry
+    print("[OK] LRU 释放")
+
+
+def test_ttl_and_evict():
+    """测试空闲超过 ttl 的实例被释放，evict() / clear() 手动释放"""
+    registry = never_jscore.ContextRegistry(ttl=0.2)
+    registry.define("a", SITE_A)
+    registry.define("b", SITE_B)
+    first = registry.get("a").evaluate("created")
+    registry.get("b")
+    time.sleep(0.3)
+    assert len(registry.keys()) == 0
+    time.sleep(0.01)
+    assert registry.get("a").evaluate("created") > first
+
+    assert registry.evict("a") is True
+    assert registry.evict("a") is False
+    registry.get("a")
+    registry.get("b")
+    registry.clear()
+    assert len(registry) == 0
+    assert registry.stats()["defined"] == 2
+    del registry
+    print("[OK] ttl / evict / clear")
+
+
+def test_redefine_and_options():
+    """测试重新定义释放旧实例，构造参数和 preset 生效，参数错误在创建时报告"""
+    registry = never_jscore.ContextRegistry()
+    registry.define("a", SITE_A)
+    assert registry.call("a", "sign", ["x"]) == "a:x"
+    registry.define("a", SITE_B)
+    assert "a" not in registry
+    assert registry.call("a", "sign", ["x"]) == "b:x"
+
+    registry.define("crypto", "var hasCrypto = typeof CryptoJS;", preset=["crypto-js"], timeout_ms=1000)
+    assert registry.get("crypto").evaluate("hasCrypto") == "object"
+    assert registry.call("a", "sign", ["y"], result_format="json_str") == '"b:y"'
+
+    registry.define("broken", "throw new Error('init failed')")
+    for _ in range(2):
+        try:
+            registry.get("broken")
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "init failed" in str(e), e
+    assert "broken" not in registry
+
+    registry.define("bad_option", SITE_A, no_such_option=1)
+    try:
+        registry.get("bad_option")
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+    del registry
+    print("[OK] 重新定义与构造参数")
+
+
+def test_realm():
+    """测试 realm=True 在共享的宿主 Context 中创建 Realm"""
+    registry = never_jscore.ContextRegistry(max_size=1)
+    registry.define("r1", SITE_A, realm=True)
+    registry.define("r2", SITE_B, realm=True)
+    realm = registry.get("r1")
+    assert isinstance(realm, never_jscore.Realm)
+    assert registry.call("r1", "sign", ["x"]) == "a:x"
+    assert registry.call("r2", "sign", ["x"]) == "b:x"
+    assert realm.closed  # 被 LRU 释放的 Realm 立即关闭
+
+    try:
+        registry.define("r3", SITE_A, realm=True, max_heap_mb=64)
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "realm" in str(e), e
+    del realm, registry
+    print("[OK] Realm")
+
+
+def test_loader():
+    """测试未定义的键交给 loader 加载，结果登记为定义"""
+    loaded = []
+
+    def loader(key):
+        loaded.append(key)
+        if key == "limited":
+            return SITE_B, {"max_heap_mb": 64}
+        if key == "wrong":
+            return 42
+        return "function sign(s) { return '%s:' + s; }" % key
+
+    registry = never_jscore.ContextRegistry(max_size=1, loader=loader)
+    assert registry.call("example.com", "sign", ["x"]) == "example.com:x"
+    assert registry.call("limited", "sign", ["x"]) == "b:x"
+    assert registry.call("example.com", "sign", ["y"]) == "example.com:y"
+    assert loaded == ["example.com", "limited"], loaded
+
+    try:
+        registry.get("wrong")
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "loader" in str(e), e
+    del registry
+    print("[OK] loader")
+
+
+def test_invalid_arguments():
+    """测试无效的 max_size / ttl"""
+    for kwargs in ({"max_size": 0}, {"ttl": 0}, {"ttl": -1.5}):
+        try:
+            never_jscore.ContextRegistry(**kwargs)
+            assert False, "应该抛出 ValueError"
+        except ValueError:
+            pass
+    print("[OK] 无效参数")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 ContextRegistry")
+    print("=" * 60)
+
+    test_lazy_and_cached()
+    test_lru()
+    test_ttl_and_evict()
+    test_redefine_and_options()
+    test_realm()
+    test_loader()
+    test_invalid_arguments()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 ContextRegistry 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,138 @@
+"""
+测试模块级类型转换器：never_jscore.register_converter()
+
+转换器对所有 Context（包括 ThreadedContext 和 eval() 的隐式 Context）生效
+"""
+
+import asyncio
+import dataclasses
+import datetime
+import decimal
+import fractions
+
+import never_jscore
+
+
+@dataclasses.dataclass
+class Order:
+    order_id: str
+    created: datetime.datetime
+
+
+def register_all():
+    never_jscore.register_converter(datetime.datetime, lambda d: d.isoformat())
+    never_jscore.register_converter(decimal.Decimal, str)
+    never_jscore.register_converter(Order, dataclasses.asdict)
+
+
+def unregister_all():
+    for py_type in (datetime.datetime, decimal.Decimal, Order):
+        never_jscore.register_converter(py_type, None)
+
+
+def test_call_arguments():
+    """测试 call() 参数和嵌套值的转换"""
+    register_all()
+    try:
+        ctx = never_jscore.Context()
+        ctx.compile("function echo(...args) { return args; }")
+        created = datetime.datetime(2024, 1, 1, 8, 30)
+        assert ctx.call("echo", [created, decimal.Decimal("9.90")]) == ["2024-01-01T08:30:00", "9.90"]
+
+        # dataclass 转换为 dict 后，其中的 datetime 同样被转换
+        result = ctx.call("echo", [Order("A1", created)])
+        assert result == [{"order_id": "A1", "created": "2024-01-01T08:30:00"}], result
+
+        # register() 注册的 Python 函数的返回值
+        ctx.register("now", lambda: created)
+        assert ctx.evaluate("now()") == "2024-01-01T08:30:00"
+        del ctx
+
+        tctx = never_jscore.ThreadedContext()
+        tctx.compile("function amount(x) { return typeof x + ':' + x; }")
+        assert tctx.call("amount", [decimal.Decimal("1.5")]) == "string:1.5"
+        tctx.close()
+    finally:
+        unregister_all()
+    print("[OK] Context / ThreadedContext 使用转换器")
+
+
+def test_eval_context():
+    """测试隐式 Context 使用转换器"""
+    register_all()
+    try:
+        never_jscore.eval("function year(t) { return new Date(t).getUTCFullYear(); }")
+        created = datetime.datetime(2024, 6, 1, tzinfo=datetime.timezone.utc)
+        assert never_jscore.get_eval_context().call("year", [created]) == 2024
+
+        async def main():
+            await never_jscore.aeval("globalThis.year = t => new Date(t).getUTCFullYear()")
+            return await never_jscore.acall("year", [created])
+
+        assert asyncio.run(main()) == 2024
+    finally:
+        unregister_all()
+        never_jscore.clear_eval_cache()
+    print("[OK] eval() / acall() 使用转换器")
+
+
+def test_unregister_and_errors():
+    """测试取消转换器和错误"""
+    ctx = never_jscore.Context()
+    ctx.compile("function echo(x) { return x; }")
+    try:
+        ctx.call("echo", [decimal.Decimal("1")])
+        assert False, "未注册时应该报错"
+    except Exception as e:
+        assert "Decimal" in str(e) and "register_converter" in str(e), e
+
+    # 后注册的优先，重复注册替换之前的转换器
+    never_jscore.register_converter(decimal.Decimal, str)
+    never_jscore.register_converter(decimal.Decimal, float)
+    try:
+        assert ctx.call("echo", [decimal.Decimal("1.5")]) == 1.5
+    finally:
+        never_jscore.register_converter(decimal.Decimal, None)
+
+    never_jscore.register_converter(decimal.Decimal, lambda d: d)
+    try:
+        ctx.call("echo", [decimal.Decimal("1")])
+        assert False, "返回相同类型应该报错"
+    except Exception as e:
+        assert "same type" in str(e), e
+    finally:
+        never_jscore.register_converter(decimal.Decimal, None)
+
+    # 互相转换的转换器（Decimal -> Fraction -> Decimal）达到嵌套上限时抛出 TypeError，而不是耗尽栈
+    never_jscore.register_converter(decimal.Decimal, lambda d: fractions.Fraction(d))
+    never_jscore.register_converter(fractions.Fraction, lambda f: decimal.Decimal(f.numerator) / f.denominator)
+    try:
+        ctx.call("echo", [decimal.Decimal("1.5")])
+        assert False, "循环的转换器应该报错"
+    except TypeError as e:
+        assert "cycle" in str(e), e
+    finally:
+        never_jscore.register_converter(decimal.Decimal, None)
+        never_jscore.register_converter(fractions.Fraction, None)
+
+    try:
+        never_jscore.register_converter(decimal.Decimal, "str")
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+    del ctx
+    print("[OK] 取消转换器和错误")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试模块级类型转换器")
+    print("=" * 60)
+
+    test_call_arguments()
+    test_eval_context()
+    test_unregister_and_errors()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 register_converter() 测试通过！")
+    print("=" * 60)
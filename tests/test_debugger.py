@@ -0,0 +1,205 @@
+"""
+测试断点调试：debug_call() / set_breakpoint() / PausedFrame
+"""
+
+import os
+import tempfile
+
+import never_jscore
+
+SOURCE = """function add(a, b) {
+    const sum = a + b;
+    debugger;
+    return sum;
+}
+function outer(x) {
+    let scale = 10;
+    function inner(y) {
+        return y * scale;
+    }
+    return inner(x + 1);
+}
+"""
+
+
+def test_debugger_statement():
+    """测试 debugger 语句处暂停，读取位置、变量并求值"""
+    ctx = never_jscore.Context()
+    ctx.compile(SOURCE)
+    seen = []
+
+    def on_pause(frame):
+        seen.append((frame.reason, frame.url, frame.line, frame.function))
+        assert frame.locals() == {"a": 1, "b": 2, "sum": 3}, frame.locals()
+        assert frame.evaluate("sum * 10") == 30
+        assert frame.evaluate("({k: [a, b]})") == {"k": [1, 2]}
+        assert frame.call_stack[0]["function"] == "add"
+        assert "add" in repr(frame)
+
+    assert ctx.debug_call("add", [1, 2], on_pause) == 3
+    assert seen == [("debugger", "<exec>", 3, "add")], seen
+
+    # 普通调用不暂停
+    assert ctx.call("add", [2, 3]) == 5
+    del ctx
+    print("[OK] debugger 语句")
+
+
+def test_breakpoint_file():
+    """测试 compile_file() 的脚本按文件名设置断点，条件断点和删除断点"""
+    with tempfile.TemporaryDirectory() as tmp:
+        path = os.path.join(tmp, "calc.js")
+        with open(path, "w", encoding="utf-8") as f:
+            f.write(SOURCE)
+
+        ctx = never_jscore.Context()
+        # 断点可以在编译之前设置
+        bp = ctx.set_breakpoint("calc.js", 9)
+        ctx.compile_file(path)
+        hits = []
+
+        def on_pause(frame):
+            if frame.reason == "debugger":
+                return None
+            hits.append((frame.line, frame.function, frame.hit_breakpoints))
+            assert frame.url.endswith("calc.js"), frame.url
+            assert frame.locals()["scale"] == 10
+            assert frame.evaluate("y") == 5
+            assert frame.evaluate("x", frame=1) == 4
+            assert [f["function"] for f in frame.call_stack[:2]] == ["inner", "outer"]
+            return "continue"
+
+        assert ctx.debug_call("outer", [4], on_pause) == 50
+        assert hits == [(9, "inner", [bp])], hits
+
+        ctx.remove_breakpoint(bp)
+        hits.clear()
+        assert ctx.debug_call("outer", [4], on_pause) == 50
+        assert hits == []
+
+        cond = ctx.set_breakpoint("calc.js", 9, condition="y > 100")
+        assert ctx.debug_call("outer", [4], on_pause) == 50
+        assert hits == []
+        ctx.remove_breakpoint(cond)
+        del ctx
+    print("[OK] 文件断点、条件断点")
+
+
+def test_stepping_and_start():
+    """测试 pause_on_start 和单步执行"""
+    ctx = never_jscore.Context()
+    ctx.compile(SOURCE)
+    pauses = []
+
+    def on_pause(frame):
+        pauses.append((frame.reason, frame.function, frame.line))
+        if frame.reason == "start":
+            return "step_into"
+        if frame.function == "inner":
+            return "step_out"
+        return "continue"
+
+    assert ctx.debug_call("outer", [1], on_pause, pause_on_start=True) == 20
+    assert pauses[0][:2] == ("start", "outer"), pauses
+    assert all(reason == "step" for reason, _, _ in pauses[1:]), pauses
+    assert any(function == "inner" for _, function, _ in pauses), pauses
+
+    # pause_on_start 的断点只用于本次调用
+    pauses.clear()
+    assert ctx.debug_call("outer", [1], on_pause) == 20
+    assert pauses == []
+    del ctx
+    print("[OK] pause_on_start / 单步执行")
+
+
+def test_callback_errors():
+    """测试 on_pause 抛出异常、非法返回值、在回调外使用 frame"""
+    ctx = never_jscore.Context()
+    ctx.compile(SOURCE)
+
+    class Stop(Exception):
+        pass
+
+    def raise_stop(frame):
+        raise Stop("stop")
+
+    try:
+        ctx.debug_call("add", [1, 2], raise_stop)
+        assert False, "应该抛出 Stop"
+    except Stop:
+        pass
+    assert ctx.call("add", [1, 1]) == 2
+
+    try:
+        ctx.debug_call("add", [1, 2], lambda frame: "jump")
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "on_pause" in str(e), e
+
+    frames = []
+    ctx.debug_call("add", [1, 2], frames.append)
+    try:
+        frames[0].evaluate("1")
+        assert False, "frame 在回调结束后应该失效"
+    except RuntimeError:
+        pass
+
+    try:
+        ctx.debug_call("add", [1, 2], lambda frame: frame.evaluate("missing.value"))
+        assert False, "求值出错应该终止执行"
+    except Exception as e:
+        assert "missing" in str(e), e
+
+    try:
+        ctx.debug_call("add", [1, 2], None)
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+
+    try:
+        ctx.debug_call("notDefined", [], lambda frame: None, pause_on_start=True)
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "notDefined" in str(e), e
+
+    try:
+        ctx.set_breakpoint("<exec>", 0)
+        assert False, "应该抛出 ValueError"
+    except ValueError:
+        pass
+    assert ctx.call("add", [2, 2]) == 4
+    del ctx
+    print("[OK] 回调错误处理")
+
+
+def test_async_function():
+    """测试 async 函数在 await 之后暂停"""
+    ctx = never_jscore.Context()
+    ctx.compile("""
+        async function load(n) {
+            const value = await Promise.resolve(n * 2);
+            debugger;
+            return value + 1;
+        }
+    """)
+    values = []
+    assert ctx.debug_call("load", [5], lambda frame: values.append(frame.evaluate("value"))) == 11
+    assert values == [10]
+    del ctx
+    print("[OK] async 函数")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试断点调试")
+    print("=" * 60)
+
+    test_debugger_statement()
+    test_breakpoint_file()
+    test_stepping_and_start()
+    test_callback_errors()
+    test_async_function()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有断点调试测试通过！")
+    print("=" * 60)
@@ -0,0 +1,142 @@
+"""
+测试全局默认限制：never_jscore.set_defaults()
+
+默认值应用于所有新建的 Context（包括 ThreadedContext 和模块级 eval() 的隐式 Context），
+显式参数优先
+"""
+
+import never_jscore
+
+
+def test_defaults_apply_to_new_contexts():
+    """测试新建的 Context 继承默认限制"""
+    never_jscore.set_defaults(timeout_ms=200, max_heap_mb=64, max_result_bytes=100)
+    try:
+        ctx = never_jscore.Context()
+        try:
+            ctx.evaluate("while (true) {}")
+            assert False, "应该超时"
+        except Exception as e:
+            assert "Execution timed out (timeout_ms=200)" in str(e), e
+        try:
+            ctx.evaluate("'x'.repeat(1000)")
+            assert False, "应该抛出 QuotaExceeded"
+        except never_jscore.QuotaExceeded as e:
+            assert "max_result_bytes=100" in str(e), e
+        assert ctx.evaluate("'ok'") == "ok"
+        del ctx
+
+        tctx = never_jscore.ThreadedContext()
+        try:
+            tctx.evaluate("while (true) {}")
+            assert False, "应该超时"
+        except Exception as e:
+            assert "Execution timed out" in str(e), e
+        tctx.close()
+    finally:
+        never_jscore.set_defaults()
+    print("[OK] 新建的 Context / ThreadedContext 继承默认限制")
+
+
+def test_explicit_arguments_win():
+    """测试显式参数优先，quotas 与默认值合并"""
+    never_jscore.set_defaults(timeout_ms=200, max_heap_mb=64, max_result_bytes=100)
+    try:
+        ctx = never_jscore.Context(timeout_ms=5000, quotas={"max_executions": 10})
+        assert ctx.evaluate("const start = Date.now(); while (Date.now() - start < 400) {} 1") == 1
+        limits = ctx.get_quota_usage()["limits"]
+        assert limits["max_executions"] == 10, limits
+        assert limits["max_result_bytes"] == 100, limits
+        del ctx
+
+        ctx = never_jscore.Context(quotas={"max_result_bytes": 10000})
+        assert len(ctx.evaluate("'x'.repeat(1000)")) == 1000
+        del ctx
+
+        # initial_heap_mb 大于默认上限时不应用默认的 max_heap_mb
+        ctx = never_jscore.Context(initial_heap_mb=128)
+        assert ctx.evaluate("1 + 1") == 2
+        del ctx
+    finally:
+        never_jscore.set_defaults()
+    print("[OK] 显式参数优先")
+
+
+def test_defaults_apply_to_eval():
+    """测试隐式 Context 按新的默认值重新创建"""
+    never_jscore.clear_eval_cache()
+    assert len(never_jscore.eval("'x'.repeat(1000)")) == 1000
+
+    never_jscore.set_defaults(max_result_bytes=100)
+    try:
+        try:
+            never_jscore.eval("'x'.repeat(1000)")
+            assert False, "应该抛出 QuotaExceeded"
+        except never_jscore.QuotaExceeded as e:
+            assert "max_result_bytes=100" in str(e), e
+    finally:
+        never_jscore.set_defaults()
+
+    # 清除默认值后恢复不限制
+    assert len(never_jscore.eval("'x'.repeat(1000)")) == 1000
+    never_jscore.clear_eval_cache()
+    print("[OK] eval() 的隐式 Context 使用默认限制")
+
+
+def test_second_and_mb_units():
+    """测试 timeout（秒）和 max_result_mb"""
+    never_jscore.set_defaults(timeout=0.2, max_heap_mb=64, max_result_mb=1)
+    try:
+        ctx = never_jscore.Context()
+        try:
+            ctx.evaluate("while (true) {}")
+            assert False, "应该超时"
+        except Exception as e:
+            assert "Execution timed out (timeout_ms=200)" in str(e), e
+        assert ctx.get_quota_usage()["limits"]["max_result_bytes"] == 1024 * 1024
+        assert len(ctx.evaluate("'x'.repeat(1000)")) == 1000
+        try:
+            ctx.evaluate("'x'.repeat(2 * 1024 * 1024)")
+            assert False, "应该抛出 QuotaExceeded"
+        except never_jscore.QuotaExceeded:
+            pass
+        del ctx
+    finally:
+        never_jscore.set_defaults()
+    print("[OK] timeout（秒）/ max_result_mb")
+
+
+def test_invalid_defaults():
+    """测试非法参数"""
+    for kwargs in (
+        {"timeout_ms": 0},
+        {"max_heap_mb": 0},
+        {"max_result_bytes": 0},
+        {"timeout": 0},
+        {"timeout": -1.5},
+        {"max_result_mb": 0},
+        {"timeout": 1, "timeout_ms": 1000},
+        {"max_result_mb": 1, "max_result_bytes": 1024},
+    ):
+        try:
+            never_jscore.set_defaults(**kwargs)
+            assert False, f"应该抛出 ValueError: {kwargs}"
+        except ValueError:
+            pass
+    print("[OK] 非法参数抛出 ValueError")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试全局默认限制")
+    print("=" * 60)
+
+    test_defaults_apply_to_new_contexts()
+    test_explicit_arguments_win()
+    test_defaults_apply_to_eval()
+    test_second_and_mb_units()
+    test_invalid_defaults()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 set_defaults() 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,241 @@
+"""
+测试模块级 eval() 和隐式 Context 管理
+
+展示 never_jscore.eval() / aeval() / acall() / clear_eval_cache() / configure_eval() / get_eval_context() 的用法
+"""
+
+import asyncio
+import threading
+
+import never_jscore
+
+
+def test_module_eval():
+    """测试模块级 eval() 无需创建 Context"""
+    never_jscore.clear_eval_cache()
+
+    assert never_jscore.eval("1 + 2") == 3
+    assert never_jscore.eval("btoa('hello')") == "aGVsbG8="
+    assert never_jscore.eval("Promise.resolve(42)") == 42
+
+    print("[OK] 模块级 eval()")
+
+
+def test_state_shared_and_cleared():
+    """测试同一线程共享状态，clear_eval_cache() 清除状态"""
+    never_jscore.clear_eval_cache()
+
+    never_jscore.eval("globalThis.leaked = 'yes'")
+    assert never_jscore.eval("typeof leaked") == "string"
+
+    never_jscore.clear_eval_cache()
+    assert never_jscore.eval("typeof leaked") == "undefined"
+
+    print("[OK] clear_eval_cache() 清除残留的全局状态")
+
+
+def test_get_eval_context():
+    """测试 get_eval_context() 返回 eval() 使用的同一个 Context"""
+    never_jscore.clear_eval_cache()
+
+    never_jscore.eval("globalThis.counter = 1")
+    ctx = never_jscore.get_eval_context()
+    assert isinstance(ctx, never_jscore.Context)
+    assert ctx.evaluate("counter") == 1
+
+    ctx.compile("function double(x) { return x * 2; }")
+    assert never_jscore.eval("double(21)") == 42
+    assert "used_heap_size" in ctx.get_heap_statistics()
+    del ctx
+
+    print("[OK] get_eval_context() 检查隐式 Context")
+
+
+def test_configure_eval():
+    """测试 configure_eval() 的预设和初始化代码"""
+    never_jscore.configure_eval(init_code="function sign(x) { return 'signed:' + x; }")
+    assert never_jscore.eval("sign('data')") == "signed:data"
+
+    # 纯净环境没有扩展 API
+    never_jscore.configure_eval(preset="pure", init_code="")
+    assert never_jscore.eval("typeof btoa") == "undefined"
+    assert never_jscore.eval("typeof sign") == "undefined"
+
+    never_jscore.configure_eval(preset="default")
+    assert never_jscore.eval("typeof btoa") == "function"
+
+    try:
+        never_jscore.configure_eval(preset="no-such-preset")
+        assert False, "应该抛出 ValueError"
+    except ValueError:
+        pass
+
+    # init_code 出错时 eval() 抛出异常，修正配置后恢复
+    never_jscore.configure_eval(init_code="throw new Error('bad init')")
+    try:
+        never_jscore.eval("1")
+        assert False, "应该抛出异常"
+    except RuntimeError as e:
+        assert "bad init" in str(e)
+    never_jscore.configure_eval(init_code="")
+    assert never_jscore.eval("1") == 1
+
+    print("[OK] configure_eval() 预设和初始化代码")
+
+
+def test_browser_preset():
+    """测试 browser 预设的 user_agent / url"""
+    ua = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Mobile/15E148"
+    never_jscore.configure_eval(preset="browser", user_agent=ua, url="https://m.example.com:8443/login?from=app#top")
+
+    assert never_jscore.eval("navigator.userAgent") == ua
+    assert never_jscore.eval("navigator.appVersion").startswith("5.0 (iPhone")
+    assert never_jscore.eval("[location.hostname, location.port, location.pathname, location.search]") == [
+        "m.example.com", "8443", "/login", "?from=app",
+    ]
+    assert never_jscore.eval("document.domain") == "m.example.com"
+    assert never_jscore.eval("typeof btoa") == "function"
+
+    # 隔离求值和 aeval() 使用相同的环境
+    assert never_jscore.eval("navigator.userAgent", isolated=True) == ua
+
+    async def from_async():
+        return await never_jscore.aeval("location.hostname")
+
+    assert asyncio.run(from_async()) == "m.example.com"
+
+    # user_agent / url 只能用于 browser 预设
+    try:
+        never_jscore.configure_eval(preset="pure", user_agent=ua)
+        assert False, "应该抛出 ValueError"
+    except ValueError:
+        pass
+
+    never_jscore.configure_eval(preset="default", user_agent="", url="")
+    assert never_jscore.eval("navigator.userAgent") != ua
+    print("[OK] browser 预设")
+
+
+def test_isolated_eval():
+    """测试 isolated=True 每次使用全新的临时 Context"""
+    never_jscore.clear_eval_cache()
+    never_jscore.eval("globalThis.shared = 1")
+
+    # 看不到隐式 Context 的状态，也不会留下状态
+    assert never_jscore.eval("typeof shared", isolated=True) == "undefined"
+    never_jscore.eval("globalThis.temp = 1", isolated=True)
+    assert never_jscore.eval("typeof temp", isolated=True) == "undefined"
+    assert never_jscore.eval("typeof temp") == "undefined"
+    assert never_jscore.eval("shared") == 1
+
+    assert never_jscore.eval("Promise.resolve(btoa('hi'))", isolated=True) == "aGk="
+
+    # init_code 在每个临时 Context 中都可用，修改其中的状态不会影响下一次
+    never_jscore.configure_eval(init_code="var counter = 0; function next() { return ++counter; }")
+    assert never_jscore.eval("next()", isolated=True) == 1
+    assert never_jscore.eval("next()", isolated=True) == 1
+
+    never_jscore.configure_eval(preset="pure")
+    assert never_jscore.eval("typeof btoa", isolated=True) == "undefined"
+    assert never_jscore.eval("next()", isolated=True) == 1
+
+    never_jscore.configure_eval(preset="default", init_code="")
+    assert never_jscore.eval("typeof next", isolated=True) == "undefined"
+
+    print("[OK] isolated=True 隔离求值")
+
+
+def test_per_thread_context():
+    """测试每个线程有独立的隐式 Context，配置对所有线程生效"""
+    never_jscore.configure_eval(preset="default", init_code="var origin = 'init';")
+    never_jscore.eval("globalThis.owner = 'main'")
+
+    results = {}
+
+    def worker():
+        results["owner"] = never_jscore.eval("typeof owner")
+        results["origin"] = never_jscore.eval("origin")
+
+    t = threading.Thread(target=worker)
+    t.start()
+    t.join()
+
+    assert results == {"owner": "undefined", "origin": "init"}, results
+    assert never_jscore.eval("owner") == "main"
+
+    never_jscore.configure_eval(init_code="")
+    print("[OK] 每个线程独立的隐式 Context")
+
+
+def test_async_eval():
+    """测试 aeval() / acall()：后台 Context 不阻塞事件循环，配置与 eval() 一致"""
+    never_jscore.configure_eval(preset="default", init_code="function sign(x) { return 'signed:' + x; }")
+    never_jscore.eval("globalThis.owner = 'sync'")
+
+    async def main():
+        assert await never_jscore.aeval("sign('a')") == "signed:a"
+        assert await never_jscore.acall("sign", ["b"]) == "signed:b"
+
+        # 与 eval() 的 Context 不共享全局变量，aeval() 之间共享
+        assert await never_jscore.aeval("typeof owner") == "undefined"
+        await never_jscore.aeval("globalThis.counter = 0")
+        assert await never_jscore.acall("(() => ++counter)", []) == 1
+
+        # 等待 JS 定时器期间事件循环继续运行
+        pending = never_jscore.aeval("new Promise(r => setTimeout(() => r('late'), 300))")
+        for _ in range(5):
+            await asyncio.sleep(0.01)
+        assert not pending.done()
+        assert await pending == "late"
+
+        # 并发提交按顺序执行
+        results = await asyncio.gather(*(never_jscore.acall("(() => ++counter)", []) for _ in range(10)))
+        assert sorted(results) == list(range(2, 12)), results
+
+        try:
+            await never_jscore.aeval("throw new Error('boom')")
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "boom" in str(e)
+
+    asyncio.run(main())
+
+    # 修改配置后后台 Context 重新创建
+    never_jscore.configure_eval(init_code="throw new Error('bad init')")
+
+    async def failing():
+        try:
+            await never_jscore.aeval("1")
+            assert False, "应该抛出异常"
+        except RuntimeError as e:
+            assert "bad init" in str(e)
+
+    asyncio.run(failing())
+
+    never_jscore.configure_eval(init_code="")
+    never_jscore.clear_eval_cache()
+
+    async def cleared():
+        return await never_jscore.aeval("typeof counter")
+
+    assert asyncio.run(cleared()) == "undefined"
+    print("[OK] aeval() / acall()")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试模块级 eval() 和隐式 Context")
+    print("=" * 60)
+
+    test_module_eval()
+    test_state_shared_and_cleared()
+    test_get_eval_context()
+    test_configure_eval()
+    test_browser_preset()
+    test_isolated_eval()
+    test_per_thread_context()
+    test_async_eval()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有隐式 Context 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,90 @@
+"""
+测试预编译的求值包装函数
+
+evaluate()/call() 不再为每次调用拼接包装脚本，这里验证语义保持不变
+"""
+
+import time
+
+import never_jscore
+
+
+def test_special_characters():
+    """测试包含引号、换行、反斜杠和 Unicode 的代码"""
+    ctx = never_jscore.Context()
+
+    assert ctx.evaluate("'a\"b' + \"c'd\"") == "a\"bc'd"
+    assert ctx.evaluate("`line1\nline2`") == "line1\nline2"
+    assert ctx.evaluate(r"'\\'.length") == 1
+    assert ctx.evaluate("'你好' + ' '") == "你好 "
+
+    print("[OK] 特殊字符正确传递")
+
+
+def test_scope_isolation():
+    """测试 evaluate 中的 let/const 不会泄漏到全局"""
+    ctx = never_jscore.Context()
+
+    assert ctx.evaluate("let __tmp = 1; const __tmp2 = 2; __tmp + __tmp2") == 3
+    assert ctx.evaluate("typeof __tmp") == "undefined"
+    assert ctx.evaluate("typeof __tmp2") == "undefined"
+
+    print("[OK] evaluate 作用域隔离")
+
+
+def test_sync_and_async_modes():
+    """测试同步和异步模式交替使用"""
+    ctx = never_jscore.Context()
+    ctx.compile("async function later(x) { return x * 2; }")
+
+    assert ctx.call("later", [21]) == 42
+    assert ctx.evaluate("1 + 1", auto_await=False) == 2
+    assert ctx.evaluate("Promise.resolve(5)") == 5
+    assert ctx.evaluate("undefined", auto_await=False) is None
+
+    print("[OK] 同步/异步模式交替使用正常")
+
+
+def test_errors_after_wrapper_compiled():
+    """测试包装函数编译后错误仍能正确抛出，且不影响后续执行"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("1") == 1
+
+    for auto_await in (True, False):
+        try:
+            ctx.evaluate("throw new TypeError('bad')", auto_await=auto_await)
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "bad" in str(e)
+
+    assert ctx.evaluate("2") == 2
+    print("[OK] 错误正确传播")
+
+
+def test_many_calls():
+    """测试大量调用的性能"""
+    ctx = never_jscore.Context()
+    ctx.compile("function add(a, b) { return a + b; }")
+
+    start = time.perf_counter()
+    for i in range(2000):
+        assert ctx.call("add", [i, 1]) == i + 1
+    elapsed = time.perf_counter() - start
+
+    print(f"[OK] 2000 次调用耗时: {elapsed * 1000:.1f}ms")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试预编译求值包装")
+    print("=" * 60)
+
+    test_special_characters()
+    test_scope_isolation()
+    test_sync_and_async_modes()
+    test_errors_after_wrapper_compiled()
+    test_many_calls()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有测试通过！")
+    print("=" * 60)
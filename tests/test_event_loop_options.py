@@ -0,0 +1,83 @@
+"""
+测试 event_loop={...}：单次调用的事件循环控制
+
+脚本注册永不停止的定时器时，max_iterations 限制本次调用 poll 事件循环的次数
+"""
+
+import never_jscore
+
+
+def test_max_iterations_result_ready():
+    """测试结果已就绪时达到 max_iterations 正常返回"""
+    ctx = never_jscore.Context()
+    result = ctx.evaluate(
+        "new Promise(r => { globalThis.ticks = 0; globalThis.timer = setInterval(() => ticks++, 0); r(42); })",
+        event_loop={"max_iterations": 20},
+    )
+    assert result == 42
+    # 剩余的定时器保留到之后的调用，清除后事件循环正常结束
+    assert ctx.evaluate("clearInterval(timer); ticks", event_loop={"max_iterations": 20}) <= 20
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] 结果就绪时达到 max_iterations")
+
+
+def test_max_iterations_not_settled():
+    """测试结果未就绪时达到 max_iterations 抛出异常"""
+    ctx = never_jscore.Context()
+    ctx.compile("function spin() { return new Promise(() => { globalThis.timer = setInterval(() => {}, 0); }); }")
+    try:
+        ctx.call("spin", [], event_loop={"max_iterations": 10})
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "max_iterations=10" in str(e), e
+    ctx.eval("clearInterval(timer)", event_loop={"max_iterations": 10})
+
+    # eval() 不需要返回值时只停止等待
+    ctx.eval("globalThis.timer = setInterval(() => {}, 0)", event_loop={"max_iterations": 10})
+    ctx.eval("clearInterval(timer)")
+    assert ctx.evaluate("'done'") == "done"
+    del ctx
+    print("[OK] 结果未就绪时抛出异常")
+
+
+def test_options():
+    """测试 pump_message_loop / wait_for_inspector 和参数检查"""
+    ctx = never_jscore.Context()
+    options = {"pump_message_loop": False, "wait_for_inspector": False}
+    assert ctx.evaluate("Promise.resolve(1).then(v => v + 1)", event_loop=options) == 2
+    assert ctx.evaluate("new Promise(r => setTimeout(() => r('t'), 5))", event_loop={"pump_message_loop": True}) == "t"
+
+    for bad in [{"max_iterations": 0}, {"max_iterations": "10"}, {"max_loops": 1}]:
+        try:
+            ctx.evaluate("1", event_loop=bad)
+            assert False, f"应该抛出 ValueError: {bad}"
+        except ValueError:
+            pass
+    del ctx
+    print("[OK] pump_message_loop / wait_for_inspector")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 同样支持 event_loop"""
+    ctx = never_jscore.ThreadedContext()
+    assert ctx.evaluate("Promise.resolve('ok')", event_loop={"max_iterations": 5}) == "ok"
+    ctx.compile("function later() { return new Promise(r => setTimeout(() => r(7), 1)); }")
+    assert ctx.call("later", [], event_loop={"pump_message_loop": False}) == 7
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试单次调用的事件循环控制")
+    print("=" * 60)
+
+    test_max_iterations_result_ready()
+    test_max_iterations_not_settled()
+    test_options()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 event_loop 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,88 @@
+"""
+测试 run_microtasks() / drain_tasks(max_ms=...)：auto_await=False 时手动推进事件循环
+"""
+
+import time
+
+import never_jscore
+
+SETUP = """
+globalThis.log = [];
+setTimeout(() => log.push('timeout'), 0);
+Promise.resolve().then(() => log.push('then')).then(() => log.push('then2'));
+queueMicrotask(() => log.push('microtask'));
+"""
+
+
+def log(ctx):
+    return ctx.evaluate("log", auto_await=False)
+
+
+def test_microtasks_then_tasks():
+    """测试 run_microtasks() 只运行微任务，drain_tasks() 再运行定时器"""
+    ctx = never_jscore.Context()
+    ctx.eval(SETUP, auto_await=False)
+    assert log(ctx) == []
+    ctx.run_microtasks()
+    assert log(ctx) == ["then", "microtask", "then2"], log(ctx)
+    ctx.run_microtasks()
+    assert log(ctx) == ["then", "microtask", "then2"]
+    assert ctx.drain_tasks() is True
+    assert log(ctx) == ["then", "microtask", "then2", "timeout"], log(ctx)
+    assert ctx.drain_tasks() is True
+    del ctx
+    print("[OK] 先微任务、后定时器")
+
+
+def test_max_ms():
+    """测试 max_ms 到达时返回 False，剩余任务保留"""
+    ctx = never_jscore.Context()
+    ctx.eval("var tick = 0; var timer = setInterval(() => tick++, 10);", auto_await=False)
+    start = time.time()
+    assert ctx.drain_tasks(max_ms=100) is False
+    elapsed = time.time() - start
+    ticks = ctx.evaluate("tick", auto_await=False)
+    assert 3 <= ticks <= 12, ticks
+    assert elapsed < 1.0, elapsed
+
+    assert ctx.drain_tasks(max_ms=0) is False
+    ctx.eval("clearInterval(timer)", auto_await=False)
+    assert ctx.drain_tasks(max_ms=100) is True
+    del ctx
+    print(f"[OK] max_ms（{ticks} 次 tick）")
+
+
+def test_errors_and_limits():
+    """测试定时器中的错误作为异常抛出，死循环受 timeout_ms 限制"""
+    ctx = never_jscore.Context()
+    ctx.eval("setTimeout(() => { throw new Error('timer boom'); }, 0);", auto_await=False)
+    try:
+        ctx.drain_tasks()
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Event loop error" in str(e) and "timer boom" in str(e), e
+
+    limited = never_jscore.Context(timeout_ms=200)
+    limited.eval("Promise.resolve().then(() => { while (true) {} });", auto_await=False)
+    try:
+        limited.run_microtasks()
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "timeout" in str(e).lower() or "terminated" in str(e).lower(), e
+    assert limited.evaluate("1 + 1") == 2
+    del ctx, limited
+    print("[OK] 错误与限制")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 run_microtasks() / drain_tasks()")
+    print("=" * 60)
+
+    test_microtasks_then_tasks()
+    test_max_ms()
+    test_errors_and_limits()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有事件循环单步测试通过！")
+    print("=" * 60)
@@ -0,0 +1,118 @@
+"""
+测试全局执行钩子：never_jscore.set_execution_hooks()
+
+展示如何在不包装每个调用点的情况下监控所有 JS 执行（APM、自定义埋点）
+"""
+
+import hashlib
+
+import never_jscore
+
+
+def test_hooks_metadata():
+    """测试钩子参数：kind、filename、code_hash、duration_ms、success"""
+    events = []
+    never_jscore.set_execution_hooks(
+        on_execute_start=lambda info: events.append(("start", info)),
+        on_execute_end=lambda info: events.append(("end", info)),
+    )
+    try:
+        ctx = never_jscore.Context()
+        assert ctx.evaluate("1 + 1") == 2
+
+        assert [kind for kind, _ in events] == ["start", "end"]
+        start, end = events[0][1], events[1][1]
+        assert start["kind"] == "evaluate"
+        assert start["filename"] == "<eval>"
+        assert start["code_hash"] == end["code_hash"]
+        assert start["code_length"] > 0
+        assert "duration_ms" not in start
+        assert end["success"] is True and end["error"] is None
+        assert end["duration_ms"] >= 0
+
+        events.clear()
+        code = "function add(a, b) { return a + b; }"
+        ctx.compile(code)
+        assert events[0][1]["kind"] == "script"
+        assert events[0][1]["code_hash"] == hashlib.sha256(code.encode()).hexdigest()
+
+        events.clear()
+        try:
+            ctx.evaluate("throw new Error('boom')")
+        except Exception:
+            pass
+        end = events[-1][1]
+        assert end["success"] is False
+        assert "boom" in end["error"]
+        del ctx
+    finally:
+        never_jscore.set_execution_hooks()
+    print("[OK] 钩子参数")
+
+
+def test_hooks_all_entry_points():
+    """测试 ThreadedContext、ContextPool 和模块级 eval() 同样触发钩子"""
+    ends = []
+    never_jscore.set_execution_hooks(on_execute_end=ends.append)
+    try:
+        ctx = never_jscore.ThreadedContext()
+        ctx.compile("function double(x) { return x * 2; }")
+        assert ctx.call("double", [21]) == 42
+        ctx.close()
+        assert [e["kind"] for e in ends] == ["script", "evaluate"]
+
+        ends.clear()
+        with never_jscore.ContextPool(size=2) as pool:
+            pool.evaluate("1")
+        assert len(ends) == 1
+
+        ends.clear()
+        assert never_jscore.eval("2 + 3") == 5
+        assert ends and ends[-1]["success"] is True
+    finally:
+        never_jscore.set_execution_hooks()
+
+    ends.clear()
+    never_jscore.eval("1")
+    assert ends == [], "清除后不再触发"
+    print("[OK] 所有入口都会触发钩子")
+
+
+def test_hook_errors_and_reentrancy():
+    """测试钩子异常不影响执行，钩子内执行 JS 不会递归触发"""
+    ctx = never_jscore.Context()
+    calls = []
+
+    def on_start(info):
+        calls.append(info["kind"])
+        ctx.evaluate("0")  # 钩子内执行 JS
+        raise RuntimeError("hook failed")
+
+    never_jscore.set_execution_hooks(on_execute_start=on_start)
+    try:
+        assert ctx.evaluate("40 + 2") == 42
+        assert calls == ["evaluate"]
+    finally:
+        never_jscore.set_execution_hooks()
+
+    try:
+        never_jscore.set_execution_hooks(on_execute_start=42)
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+    del ctx
+    print("[OK] 钩子异常和重入")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试全局执行钩子")
+    print("=" * 60)
+
+    test_hooks_metadata()
+    test_hooks_all_entry_points()
+    test_hook_errors_and_reentrancy()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有执行钩子测试通过！")
+    print("=" * 60)
@@ -0,0 +1,127 @@
+"""
+测试 call(expect_schema={...})：在 JS 侧序列化之前检查结果的结构，不符合时抛出 SchemaMismatch
+"""
+
+import io
+
+import never_jscore
+
+JS_CODE = """
+function sign(token, ts) { return { token: token, ts: ts, extra: 'x' }; }
+async function later(token) { await null; return { token: token }; }
+function list(items) { return items; }
+function nothing() {}
+"""
+
+SIGN_SCHEMA = {
+    "type": "object",
+    "required": ["token", "ts"],
+    "properties": {
+        "token": {"type": "string", "pattern": "^[0-9a-f]+$"},
+        "ts": {"type": "integer", "minimum": 0},
+    },
+}
+
+
+def make_context():
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    return ctx
+
+
+def expect_mismatch(call):
+    """执行 call，返回 SchemaMismatch 的 mismatches"""
+    try:
+        call()
+    except never_jscore.SchemaMismatch as e:
+        assert "expect_schema" in str(e), e
+        return e.mismatches
+    assert False, "应该抛出 SchemaMismatch"
+
+
+def test_match():
+    """测试符合规则的结果正常返回"""
+    ctx = make_context()
+    result = ctx.call("sign", ["abc123", 1700000000], expect_schema=SIGN_SCHEMA)
+    assert result == {"token": "abc123", "ts": 1700000000, "extra": "x"}
+    assert ctx.call("later", ["ff"], expect_schema={"type": "object"}) == {"token": "ff"}
+    assert ctx.call("nothing", [], expect_schema={"type": "null"}) is None
+    assert ctx.call("list", [[1, 2.5]], expect_schema={"type": "array", "items": {"type": "number"}}) == [1, 2.5]
+    del ctx
+    print("[OK] 符合规则")
+
+
+def test_mismatches():
+    """测试不符合时列出所有位置"""
+    ctx = make_context()
+    mismatches = expect_mismatch(lambda: ctx.call("sign", [12, -1], expect_schema=SIGN_SCHEMA))
+    assert mismatches == ["$.token: expected string, got number", "$.ts: expected >= 0, got -1"], mismatches
+
+    mismatches = expect_mismatch(lambda: ctx.call("later", ["XYZ"], expect_schema=SIGN_SCHEMA))
+    assert "$.ts: missing required property" in mismatches, mismatches
+    assert any(m.startswith("$.token:") and "pattern" in m for m in mismatches), mismatches
+
+    strict = {"type": "object", "properties": {"token": {}, "ts": {}}, "additionalProperties": False}
+    assert expect_mismatch(lambda: ctx.call("sign", ["a", 1], expect_schema=strict)) == ["$.extra: unexpected property"]
+
+    items = {"type": "array", "minItems": 3, "items": {"enum": ["a", "b"]}}
+    mismatches = expect_mismatch(lambda: ctx.call("list", [["a", "c"]], expect_schema=items))
+    assert mismatches == ["$: expected at least 3 items, got 2", '$[1]: expected one of ["a","b"], got "c"'], mismatches
+
+    nested = {"type": "object", "properties": {"odd key": {"type": ["string", "null"]}}}
+    mismatches = expect_mismatch(lambda: ctx.call("list", [{"odd key": 1}], expect_schema=nested))
+    assert mismatches == ['$["odd key"]: expected string | null, got number'], mismatches
+
+    many = expect_mismatch(lambda: ctx.call("list", [list(range(50))], expect_schema={"items": {"type": "string"}}))
+    assert len(many) == 20, len(many)
+    del ctx
+    print("[OK] 列出不符合的位置")
+
+
+def test_with_other_options():
+    """测试与 result_filter、stream_to、result_format 一起使用，之后的调用不受影响"""
+    ctx = make_context()
+    ctx.compile("function __wrap(v) { return { wrapped: v === undefined ? null : v }; }", result_filter="__wrap")
+    assert ctx.call("nothing", [], expect_schema={"required": ["wrapped"]}) == {"wrapped": None}
+    expect_mismatch(lambda: ctx.call("nothing", [], expect_schema={"type": "null"}))
+    ctx.compile("", result_filter="")
+
+    buffer = io.StringIO()
+    expect_mismatch(lambda: ctx.call("sign", [1, 1], expect_schema=SIGN_SCHEMA, stream_to=buffer))
+    assert buffer.getvalue() == ""
+    text = ctx.call("sign", ["a", 1], expect_schema=SIGN_SCHEMA, result_format="json_str")
+    assert text.startswith("{"), text
+
+    # 规则只作用于这一次调用
+    assert ctx.call("sign", [1, 1]) == {"token": 1, "ts": 1, "extra": "x"}
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] 与其他选项一起使用")
+
+
+def test_invalid_schema():
+    """测试规则不是 dict 时抛出 TypeError"""
+    ctx = make_context()
+    try:
+        ctx.call("nothing", [], expect_schema=["string"])
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+    assert issubclass(never_jscore.SchemaMismatch, ValueError)
+    del ctx
+    print("[OK] 无效的规则")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 call(expect_schema=...)")
+    print("=" * 60)
+
+    test_match()
+    test_mismatches()
+    test_with_other_options()
+    test_invalid_schema()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 expect_schema 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,147 @@
+"""
+测试 os.fork() 检测
+
+只检测并报错：fork 前创建的对象和父进程中已初始化的 V8 在子进程中不可用，不会重新初始化或回退到新的进程。
+
+展示 gunicorn preload / multiprocessing fork 模式下的正确用法：
+父进程只 import，子进程各自创建 Context。
+每个场景在独立的 Python 进程中运行，避免互相影响 V8 平台的初始化状态。
+"""
+
+import os
+import subprocess
+import sys
+import textwrap
+
+
+def _run(script):
+    """在新的解释器中运行脚本，返回 (退出码, 输出)"""
+    proc = subprocess.run(
+        [sys.executable, "-c", textwrap.dedent(script)],
+        capture_output=True,
+        text=True,
+        timeout=60,
+    )
+    return proc.returncode, proc.stdout + proc.stderr
+
+
+def test_fork_before_first_context():
+    """测试父进程只 import，子进程可以正常创建 Context"""
+    code, output = _run("""
+        import os
+        import never_jscore
+
+        pid = os.fork()
+        if pid == 0:
+            ctx = never_jscore.Context()
+            os._exit(0 if ctx.evaluate("1 + 2") == 3 else 1)
+
+        _, status = os.waitpid(pid, 0)
+        assert os.waitstatus_to_exitcode(status) == 0, "子进程执行失败"
+
+        # 父进程之后也可以正常使用
+        assert never_jscore.Context().evaluate("btoa('hi')") == "aGk="
+    """)
+    assert code == 0, output
+    print("[OK] 父进程只 import 时，fork 出的子进程可以创建 Context")
+
+
+def test_inherited_context_rejected():
+    """测试子进程中使用从父进程继承的 Context 会抛出明确的异常"""
+    code, output = _run("""
+        import os
+        import never_jscore
+
+        ctx = never_jscore.Context()
+        ctx.compile("function add(a, b) { return a + b; }")
+
+        pid = os.fork()
+        if pid == 0:
+            try:
+                ctx.call("add", [1, 2])
+                os._exit(1)
+            except RuntimeError as e:
+                os._exit(0 if "fork" in str(e) else 2)
+
+        _, status = os.waitpid(pid, 0)
+        assert os.waitstatus_to_exitcode(status) == 0, f"exit code {os.waitstatus_to_exitcode(status)}"
+
+        # 父进程中的 Context 不受影响
+        assert ctx.call("add", [1, 2]) == 3
+    """)
+    assert code == 0, output
+    print("[OK] 继承的 Context 在子进程中抛出 RuntimeError")
+
+
+def test_new_context_after_parent_initialized():
+    """测试父进程已初始化 V8 后，子进程创建 Context 报错而不是死锁"""
+    code, output = _run("""
+        import os
+        import never_jscore
+
+        never_jscore.Context().evaluate("1")
+
+        pid = os.fork()
+        if pid == 0:
+            try:
+                never_jscore.Context()
+                os._exit(1)
+            except RuntimeError as e:
+                os._exit(0 if "fork" in str(e) else 2)
+
+        _, status = os.waitpid(pid, 0)
+        assert os.waitstatus_to_exitcode(status) == 0, f"exit code {os.waitstatus_to_exitcode(status)}"
+    """)
+    assert code == 0, output
+    print("[OK] 父进程已初始化 V8 时，子进程创建 Context 抛出 RuntimeError")
+
+
+def test_inherited_pool_rejected():
+    """测试子进程中使用继承的 ContextPool / ThreadedContext 会抛出异常（而不是永远等待）"""
+    code, output = _run("""
+        import os
+        import never_jscore
+
+        pool = never_jscore.ContextPool("function add(a, b) { return a + b; }", size=2)
+        tctx = never_jscore.ThreadedContext()
+
+        pid = os.fork()
+        if pid == 0:
+            failures = 0
+            for fn in (lambda: pool.call("add", [1, 2]), lambda: tctx.evaluate("1")):
+                try:
+                    fn()
+                    failures += 1
+                except RuntimeError:
+                    pass
+            # 退出时析构继承的对象也不能卡住
+            del pool, tctx
+            os._exit(failures)
+
+        _, status = os.waitpid(pid, 0)
+        assert os.waitstatus_to_exitcode(status) == 0, f"exit code {os.waitstatus_to_exitcode(status)}"
+        assert pool.call("add", [1, 2]) == 3
+        pool.close()
+        tctx.close()
+    """)
+    assert code == 0, output
+    print("[OK] 继承的 ContextPool / ThreadedContext 在子进程中抛出 RuntimeError")
+
+
+if __name__ == "__main__":
+    if not hasattr(os, "fork"):
+        print("[SKIP] 当前平台不支持 os.fork()")
+        sys.exit(0)
+
+    print("=" * 60)
+    print("测试 fork 检测")
+    print("=" * 60)
+
+    test_fork_before_first_context()
+    test_inherited_context_rejected()
+    test_new_context_after_parent_initialized()
+    test_inherited_pool_rejected()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 fork 检测测试通过！")
+    print("=" * 60)
@@ -0,0 +1,105 @@
+"""
+测试 GC 事件回调：ctx.on_gc(callback)
+
+每次 V8 垃圾回收产生一个事件（类型、耗时、前后的堆使用量）
+"""
+
+import never_jscore
+
+ALLOCATE = """
+(() => {
+    let keep = [];
+    for (let i = 0; i < 200000; i++) {
+        keep.push({ i, s: 'x' + i });
+        if (keep.length > 1000) keep = [];
+    }
+    return keep.length;
+})()
+"""
+
+GC_TYPES = {"scavenge", "minor_mark_sweep", "mark_sweep_compact", "incremental_marking", "process_weak_callbacks"}
+
+
+def test_gc_events_during_call():
+    """测试分配内存产生的 GC 事件"""
+    ctx = never_jscore.Context()
+    events = []
+    ctx.on_gc(events.append)
+    ctx.evaluate(ALLOCATE)
+
+    assert events, "分配大量对象后应该发生 GC"
+    for event in events:
+        assert event["type"] in GC_TYPES, event
+        assert event["duration_ms"] >= 0
+        assert event["heap_before"] > 0 and event["heap_after"] > 0
+        assert event["freed"] == max(0, event["heap_before"] - event["heap_after"])
+    del ctx
+    print(f"[OK] 调用中的 GC 事件（{len(events)} 个）")
+
+
+def test_forced_gc():
+    """测试 notify_low_memory() 触发的完整 GC"""
+    ctx = never_jscore.Context()
+    events = []
+    ctx.on_gc(events.append)
+    ctx.evaluate("globalThis.big = new Array(100000).fill({}); 1")
+    ctx.evaluate("delete globalThis.big; 1")
+    events.clear()
+
+    ctx.notify_low_memory()
+    assert any(e["type"] == "mark_sweep_compact" and e["forced"] for e in events), events
+    del ctx
+    print("[OK] 强制 GC")
+
+
+def test_callback_uses_context():
+    """测试回调中可以使用同一个 Context，回调异常不影响执行"""
+    ctx = never_jscore.Context()
+    seen = []
+
+    def on_gc(event):
+        seen.append(ctx.evaluate("1 + 1"))
+        raise RuntimeError("ignored")
+
+    ctx.on_gc(on_gc)
+    assert ctx.evaluate(ALLOCATE) == 801
+    assert seen and all(v == 2 for v in seen)
+
+    ctx.on_gc(None)
+    seen.clear()
+    ctx.evaluate(ALLOCATE)
+    assert seen == []
+
+    try:
+        ctx.on_gc(42)
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+    del ctx
+    print("[OK] 回调中使用 Context、清除回调")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext"""
+    tctx = never_jscore.ThreadedContext()
+    events = []
+    tctx.on_gc(events.append)
+    tctx.evaluate(ALLOCATE)
+    assert events and all(e["type"] in GC_TYPES for e in events)
+    tctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 GC 事件回调")
+    print("=" * 60)
+
+    test_gc_events_during_call()
+    test_forced_gc()
+    test_callback_uses_context()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 GC 事件测试通过！")
+    print("=" * 60)
@@ -0,0 +1,100 @@
+"""
+测试 snapshot_globals() / diff_globals()：比较两次调用之间全局变量的变化
+
+用来确认初始化脚本创建了哪些全局状态，以及调用之间泄漏的状态
+"""
+
+import never_jscore
+
+
+def test_added_changed_removed():
+    """测试新增、变化和删除的全局变量"""
+    ctx = never_jscore.Context()
+    ctx.compile("var counter = 0; var config = { mode: 'a' }; var temp = 1;")
+    before = ctx.snapshot_globals()
+    assert "counter" in before and before["counter"].startswith("number:"), before.get("counter")
+    assert before["config"].startswith("object:")
+
+    ctx.compile("counter++; globalThis.fresh = [1, 2]; delete globalThis.temp;")
+    diff = ctx.diff_globals(before)
+    assert diff == {"added": ["fresh"], "changed": ["counter"], "removed": ["temp"]}, diff
+
+    # 没有变化
+    before = ctx.snapshot_globals()
+    assert ctx.diff_globals(before) == {"added": [], "changed": [], "removed": []}
+    del ctx
+    print("[OK] 新增 / 变化 / 删除")
+
+
+def test_nested_changes():
+    """测试对象内部、Map / Set、类型化数组和函数的修改"""
+    ctx = never_jscore.Context()
+    ctx.compile("""
+        var sdk = { cache: { keys: { a: 1 } } };
+        var seen = new Map([['x', 1]]);
+        var ids = new Set([1]);
+        var buf = new Uint8Array(4);
+        function sign(x) { return x; }
+        var untouched = { deep: { value: 1 } };
+    """)
+    before = ctx.snapshot_globals()
+    ctx.evaluate("""
+        sdk.cache.keys.a = 2;
+        seen.set('x', 2);
+        ids.add(2);
+        buf[3] = 7;
+        sign = function (x) { return x + 1; };
+        1
+    """)
+    diff = ctx.diff_globals(before)
+    assert diff["changed"] == ["buf", "ids", "seen", "sdk", "sign"], diff
+    assert diff["added"] == [] and diff["removed"] == []
+    del ctx
+    print("[OK] 嵌套修改")
+
+
+def test_call_leaks():
+    """测试发现 call() 之间泄漏的状态，访问器属性不会被调用"""
+    ctx = never_jscore.Context()
+    ctx.compile("""
+        var calls = 0;
+        Object.defineProperty(globalThis, 'trap', { get() { calls++; return calls; }, configurable: true });
+        function pure(x) { return x * 2; }
+        function leaky(x) { globalThis.lastInput = x; return x; }
+    """)
+    before = ctx.snapshot_globals()
+    assert before["trap"].startswith("accessor:"), before["trap"]
+    ctx.call("pure", [1])
+    assert ctx.diff_globals(before) == {"added": [], "changed": [], "removed": []}
+    assert ctx.evaluate("calls") == 0
+
+    ctx.call("leaky", [1])
+    assert ctx.diff_globals(before)["added"] == ["lastInput"]
+    del ctx
+    print("[OK] 调用之间泄漏的状态")
+
+
+def test_cycles_and_threaded():
+    """测试循环引用和 ThreadedContext"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("var node = { name: 'a' }; node.self = node; node.root = globalThis;")
+    before = ctx.snapshot_globals()
+    ctx.compile("node.self.name = 'b';")
+    assert ctx.diff_globals(before)["changed"] == ["node"]
+    ctx.close()
+    print("[OK] 循环引用和 ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 snapshot_globals() / diff_globals()")
+    print("=" * 60)
+
+    test_added_changed_removed()
+    test_nested_changes()
+    test_call_leaks()
+    test_cycles_and_threaded()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有全局状态比较测试通过！")
+    print("=" * 60)
@@ -0,0 +1,179 @@
+"""
+测试长期句柄：Context.handles() / Handle.release() / max_handles
+
+Realm、ShadowRealm、prepare() 的函数和 call() 缓存的函数都计入句柄表
+"""
+
+import never_jscore
+
+
+def kinds(ctx):
+    return [(h.kind, h.name) for h in ctx.handles()]
+
+
+def test_list_handles():
+    """测试列出各类句柄，关闭 / 销毁后不再列出"""
+    ctx = never_jscore.Context()
+    ctx.compile("function sign(x) { return x + 1; } var utils = { twice: x => x * 2 };")
+    assert ctx.handles() == []
+
+    stmt = ctx.prepare("sign(?)")
+    assert stmt(1) == 2
+    assert ctx.call("sign", [1]) == 2
+    assert ctx.call("utils.twice", [2]) == 4
+    realm = ctx.create_realm()
+    shadow = ctx.create_shadow_realm()
+
+    # prepare() 的函数经过 call() 的缓存调用，但只作为 prepared 列出
+    assert kinds(ctx) == [
+        ("prepared", "sign(?)"),
+        ("call_target", "sign"),
+        ("call_target", "utils.twice"),
+        ("realm", "<realm>"),
+        ("shadow_realm", "<shadow_realm>"),
+    ], kinds(ctx)
+    ids = [h.id for h in ctx.handles()]
+    assert ids == sorted(ids) and len(set(ids)) == len(ids)
+    assert "prepared" in repr(ctx.handles()[0])
+
+    realm.close()
+    del shadow
+    assert [kind for kind, _ in kinds(ctx)] == ["prepared", "call_target", "call_target"]
+
+    # 同一模板不占用新的句柄；执行全局脚本后 call() 的缓存重新解析
+    ctx.prepare("sign(?)")
+    assert kinds(ctx) == [("prepared", "sign(?)")]
+    assert stmt(1) == 2
+    del stmt, ctx
+    print("[OK] 列出句柄")
+
+
+def test_release():
+    """测试逐个释放句柄"""
+    ctx = never_jscore.Context()
+    ctx.compile("function sign(x) { return x + 1; }")
+    stmt = ctx.prepare("sign(?) * 10")
+    realm = ctx.create_realm()
+    realm.compile("var x = 1;")
+    ctx.call("sign", [1])
+
+    prepared, realm_handle, target = ctx.handles()
+    assert target.kind == "call_target"
+    assert not target.released
+
+    # call_target：只清除缓存，下一次调用重新解析
+    target.release()
+    assert target.released
+    assert ctx.call("sign", [2]) == 3
+    assert [h.kind for h in ctx.handles()] == ["prepared", "realm", "call_target"]
+
+    # prepared：删除函数，PreparedCall 不能再调用（删除脚本同时清空 call() 的缓存）
+    prepared.release()
+    prepared.release()  # 重复调用是安全的
+    assert prepared.released
+    try:
+        stmt(1)
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "released" in str(e), e
+    assert ctx.evaluate("Object.keys(__neverjscore_prepared__).length") == 0
+    assert [h.kind for h in ctx.handles()] == ["realm"]
+
+    # 重新 prepare() 同一模板得到新的句柄
+    again = ctx.prepare("sign(?) * 10")
+    assert again(1) == 20
+    assert [h.kind for h in ctx.handles()].count("prepared") == 1
+
+    # realm：与 close() 相同
+    realm_handle.release()
+    assert realm.closed and realm_handle.released
+    try:
+        realm.evaluate("x")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "closed" in str(e), e
+    del again, realm, ctx
+    print("[OK] 释放句柄")
+
+
+def test_max_handles():
+    """测试 max_handles 上限"""
+    ctx = never_jscore.Context(max_handles=2)
+    ctx.compile("function a() { return 1; } function b() { return 2; } function c() { return 3; }")
+
+    # call() 的缓存达到上限后照常执行，只是不再缓存
+    assert [ctx.call(name, []) for name in ("a", "b", "c")] == [1, 2, 3]
+    assert [h.name for h in ctx.handles()] == ["a", "b"]
+
+    # 新的 Realm / prepare() 先挤掉 call() 的缓存
+    first = ctx.prepare("a() + ?")
+    assert first(1) == 2
+    realm = ctx.create_realm()
+    assert [h.kind for h in ctx.handles()] == ["prepared", "realm"]
+    assert ctx.call("c", []) == 3
+    assert [h.kind for h in ctx.handles()] == ["prepared", "realm"]
+
+    for create in (ctx.create_realm, ctx.create_shadow_realm, lambda: ctx.prepare("b() + ?")):
+        try:
+            create()
+            assert False, "应该抛出 QuotaExceeded"
+        except never_jscore.QuotaExceeded as e:
+            assert "max_handles=2" in str(e), e
+
+    # 已经定义的模板不需要新的句柄；释放之后可以继续创建
+    assert ctx.prepare("a() + ?")(2) == 3
+    realm.close()
+    shadow = ctx.create_shadow_realm()
+    assert shadow.evaluate("1 + 1") == 2
+
+    try:
+        never_jscore.Context(max_handles=0)
+        assert False, "应该抛出 ValueError"
+    except ValueError:
+        pass
+    del first, shadow, ctx
+    print("[OK] max_handles 上限")
+
+
+def test_release_during_realm_call():
+    """测试 Realm 执行期间不能释放它的句柄"""
+    ctx = never_jscore.Context()
+    realm = ctx.create_realm()
+    errors = []
+
+    def release_all(info):
+        for handle in ctx.handles():
+            try:
+                handle.release()
+            except RuntimeError as e:
+                errors.append(str(e))
+
+    # 执行钩子在 Realm 执行期间调用
+    never_jscore.set_execution_hooks(on_execute_start=release_all)
+    try:
+        assert realm.evaluate("1 + 1") == 2
+    finally:
+        never_jscore.set_execution_hooks()
+    assert len(errors) == 1 and "executing" in errors[0], errors
+    assert not realm.closed
+
+    # 执行结束后可以释放
+    release_all(None)
+    assert realm.closed and len(errors) == 1
+    del realm, ctx
+    print("[OK] 执行期间释放")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试长期句柄")
+    print("=" * 60)
+
+    test_list_handles()
+    test_release()
+    test_max_handles()
+    test_release_during_realm_call()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有长期句柄测试通过！")
+    print("=" * 60)
@@ -0,0 +1,108 @@
+"""
+测试执行历史：Context(history=N) 和 ctx.history()
+
+长时间运行的服务出错后，查看最近执行了哪些代码、耗时和结果
+"""
+
+import never_jscore
+
+
+def test_history_records():
+    """测试记录内容"""
+    ctx = never_jscore.Context(history=10)
+    ctx.compile("function sign(x) { return x.split('').reverse().join(''); }")
+    assert ctx.call("sign", ["abc"]) == "cba"
+    assert ctx.evaluate("1 + 1") == 2
+
+    entries = ctx.history()
+    assert [e["kind"] for e in entries] == ["script", "evaluate", "evaluate"]
+    assert entries[0]["filename"] == "<exec>"
+    assert entries[1]["code"] == 'sign("abc")'
+    assert entries[2]["code"] == "1 + 1"
+    for entry in entries:
+        assert entry["success"] is True and entry["error"] is None
+        assert entry["duration_ms"] >= 0
+        assert entry["start_ms"] > 0
+    assert entries[0]["start_ms"] <= entries[2]["start_ms"]
+    del ctx
+    print("[OK] 记录类型、代码和耗时")
+
+
+def test_history_errors():
+    """测试失败的执行"""
+    ctx = never_jscore.Context(history=10)
+    ctx.compile("function sign(x) { return x.split(''); }")
+    try:
+        ctx.call("sign", [None])
+        assert False, "应该抛出异常"
+    except Exception:
+        pass
+
+    last = ctx.history()[-1]
+    assert last["success"] is False
+    assert last["code"] == "sign(null)"
+    assert "TypeError" in last["error"], last["error"]
+    del ctx
+    print("[OK] 记录失败的执行")
+
+
+def test_history_ring_buffer():
+    """测试只保留最近 N 次执行，长代码截断"""
+    ctx = never_jscore.Context(history=3)
+    for i in range(10):
+        ctx.evaluate(f"{i} * 2")
+    assert [e["code"] for e in ctx.history()] == ["7 * 2", "8 * 2", "9 * 2"]
+
+    code = "/*" + "x" * 2000 + "*/ 42"
+    assert ctx.evaluate(code) == 42
+    last = ctx.history()[-1]
+    assert last["code_length"] == len(code)
+    assert len(last["code"]) < 600 and last["code"].endswith("...")
+    del ctx
+    print("[OK] 环形缓冲区和代码截断")
+
+
+def test_history_disabled():
+    """测试未开启时的行为和参数校验"""
+    ctx = never_jscore.Context()
+    ctx.evaluate("1")
+    try:
+        ctx.history()
+        assert False, "应该抛出 RuntimeError"
+    except RuntimeError:
+        pass
+    del ctx
+
+    try:
+        never_jscore.Context(history=0)
+        assert False, "应该抛出 ValueError"
+    except ValueError:
+        pass
+    print("[OK] 未开启 history")
+
+
+def test_history_threaded():
+    """测试 ThreadedContext"""
+    tctx = never_jscore.ThreadedContext(history=5)
+    tctx.compile("var counter = 0;")
+    tctx.evaluate("++counter")
+    entries = tctx.history()
+    assert [e["code"] for e in entries] == ["var counter = 0;", "++counter"]
+    tctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试执行历史")
+    print("=" * 60)
+
+    test_history_records()
+    test_history_errors()
+    test_history_ring_buffer()
+    test_history_disabled()
+    test_history_threaded()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有执行历史测试通过！")
+    print("=" * 60)
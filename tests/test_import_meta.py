@@ -0,0 +1,106 @@
+"""
+测试 on_import_meta(callback)：由 Python 设置 ES 模块的 import.meta 字段
+"""
+
+import os
+import tempfile
+
+import never_jscore
+
+BUNDLE = """export const url = import.meta.url;
+export const mode = import.meta.env ? import.meta.env.MODE : 'none';
+export const api = import.meta.env && import.meta.env.MODE === 'production' ? 'https://api.example.com' : 'http://localhost';
+export function where() { return new Error('here').stack; }
+"""
+
+
+def write_module(tmp, name, code=BUNDLE):
+    path = os.path.join(tmp, name)
+    with open(path, "w", encoding="utf-8") as f:
+        f.write(code)
+    return path
+
+
+def test_fields():
+    """测试回调返回的字段在模块初始化之前设置，url 可以覆盖"""
+    seen = []
+
+    def on_import_meta(info):
+        seen.append(info)
+        return {"url": "https://www.example.com/static/" + os.path.basename(info["path"]), "env": {"MODE": "production"}}
+
+    with tempfile.TemporaryDirectory() as tmp:
+        path = write_module(tmp, "bundle.mjs")
+        ctx = never_jscore.Context()
+        ctx.on_import_meta(on_import_meta)
+        ctx.compile_file(path)
+        assert ctx.evaluate("url") == "https://www.example.com/static/bundle.mjs"
+        assert ctx.evaluate("mode") == "production"
+        assert ctx.evaluate("api") == "https://api.example.com"
+        assert len(seen) == 1
+        assert seen[0]["path"].endswith("bundle.mjs"), seen
+        assert seen[0]["url"].startswith("file://"), seen
+        del ctx
+    print("[OK] 设置 import.meta 字段")
+
+
+def test_defaults_and_clear():
+    """测试返回 None 或清除回调时保持默认值，行号不变"""
+    with tempfile.TemporaryDirectory() as tmp:
+        path = write_module(tmp, "app.mjs")
+        ctx = never_jscore.Context()
+        ctx.on_import_meta(lambda info: None)
+        ctx.compile_file(path)
+        assert ctx.evaluate("url").startswith("file://")
+        assert ctx.evaluate("mode") == "none"
+
+        ctx.on_import_meta(lambda info: {"env": {"MODE": "dev"}})
+        ctx.compile_file(path)
+        assert ctx.evaluate("mode") == "dev"
+        assert "app.mjs:4:" in ctx.call("where", []), ctx.call("where", [])
+
+        ctx.on_import_meta(None)
+        ctx.compile_file(path)
+        assert ctx.evaluate("mode") == "none"
+        del ctx
+    print("[OK] 默认值与清除")
+
+
+def test_errors():
+    """测试回调异常、返回值无效和不可调用的回调"""
+    with tempfile.TemporaryDirectory() as tmp:
+        path = write_module(tmp, "bad.mjs")
+        ctx = never_jscore.Context()
+
+        def fail(info):
+            raise RuntimeError("no meta")
+
+        for callback, message in ((fail, "no meta"), (lambda info: ["url"], "dict")):
+            ctx.on_import_meta(callback)
+            try:
+                ctx.compile_file(path)
+                assert False, "应该抛出异常"
+            except Exception as e:
+                assert "import.meta" in str(e) and message in str(e), e
+
+        try:
+            ctx.on_import_meta("not callable")
+            assert False, "应该抛出 TypeError"
+        except TypeError:
+            pass
+        del ctx
+    print("[OK] 错误处理")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 on_import_meta()")
+    print("=" * 60)
+
+    test_fields()
+    test_defaults_and_clear()
+    test_errors()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 import.meta 测试通过！")
+    print("=" * 60)
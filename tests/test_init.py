@@ -0,0 +1,403 @@
+"""
+测试 never_jscore.init() 全局初始化配置
+
+init() 只能在第一次创建 Context 之前调用，每个场景在独立的 Python 进程中运行。
+"""
+
+import os
+import subprocess
+import sys
+import tempfile
+import textwrap
+
+
+def _run(script):
+    """在新的解释器中运行脚本，返回 (退出码, 输出)"""
+    proc = subprocess.run(
+        [sys.executable, "-c", textwrap.dedent(script)],
+        capture_output=True,
+        text=True,
+        timeout=60,
+    )
+    return proc.returncode, proc.stdout + proc.stderr
+
+
+def test_current_thread_runtime():
+    """测试默认的单线程 runtime 配置，current_thread 参数发出 DeprecationWarning"""
+    code, output = _run("""
+        import warnings
+        import never_jscore
+
+        with warnings.catch_warnings(record=True) as caught:
+            warnings.simplefilter("always")
+            never_jscore.init(tokio_threads=1, current_thread=True)
+        assert [w.category for w in caught] == [DeprecationWarning], caught
+        assert "current_thread" in str(caught[0].message)
+        ctx = never_jscore.Context()
+        assert ctx.evaluate("new Promise(r => setTimeout(() => r(42), 10))") == 42
+    """)
+    assert code == 0, output
+    print("[OK] init(current_thread=True) 已弃用")
+
+
+def test_multi_thread_runtime():
+    """测试 current_thread=False 被拒绝，多个 Python 线程各自的单线程 runtime 正常工作"""
+    code, output = _run("""
+        import threading
+        import never_jscore
+
+        try:
+            never_jscore.init(tokio_threads=2, current_thread=False)
+            assert False, "应该抛出 ValueError"
+        except ValueError as e:
+            assert "current_thread" in str(e), e
+        never_jscore.init(tokio_threads=2)
+
+        results = []
+
+        def worker(i):
+            ctx = never_jscore.Context()
+            results.append(ctx.evaluate(f"new Promise(r => setTimeout(() => r({i}), 10))"))
+            del ctx
+
+        threads = [threading.Thread(target=worker, args=(i,)) for i in range(4)]
+        for t in threads:
+            t.start()
+        for t in threads:
+            t.join()
+
+        assert sorted(results) == [0, 1, 2, 3], results
+    """)
+    assert code == 0, output
+    print("[OK] init(current_thread=False) 被拒绝，多线程各自的 runtime")
+
+
+def test_single_threaded_platform():
+    """测试单线程 V8 平台：不创建 V8 后台线程，GC / Promise / 定时器正常工作"""
+    code, output = _run("""
+        import threading
+        import never_jscore
+
+        never_jscore.init(single_threaded_platform=True)
+        ctx = never_jscore.Context()
+        ctx.compile("function build(n) { const a = []; for (let i = 0; i < n; i++) a.push({ i, s: 'x' + i }); return a.length; }")
+        for _ in range(20):
+            assert ctx.call("build", [100000]) == 100000
+        ctx.gc()
+        assert ctx.evaluate("new Promise(r => setTimeout(() => r('done'), 10))") == "done"
+
+        # 多个线程各自的 Context 也能在单线程平台上运行
+        results = []
+        def worker(i):
+            c = never_jscore.Context()
+            results.append(c.evaluate(f"{i} * 2"))
+            del c
+        threads = [threading.Thread(target=worker, args=(i,)) for i in range(4)]
+        for t in threads:
+            t.start()
+        for t in threads:
+            t.join()
+        assert sorted(results) == [0, 2, 4, 6], results
+    """)
+    assert code == 0, output
+    print("[OK] init(single_threaded_platform=True)")
+
+
+def test_set_v8_flags():
+    """测试 set_v8_flags() 在 V8 初始化前生效"""
+    code, output = _run("""
+        import never_jscore
+
+        never_jscore.set_v8_flags(["--jitless"])
+        never_jscore.set_v8_flags(["--max-old-space-size=256"])
+        ctx = never_jscore.Context()
+        assert ctx.evaluate("[1, 2, 3].map(x => x * 2).join(',')") == "2,4,6"
+
+        stats = ctx.get_heap_statistics()
+        assert stats["heap_size_limit"] <= 300 * 1024 * 1024, stats["heap_size_limit"]
+
+        try:
+            never_jscore.set_v8_flags(["--stack-size=500"])
+            raise SystemExit(1)
+        except RuntimeError:
+            pass
+    """)
+    assert code == 0, output
+    print("[OK] set_v8_flags() 在创建 Context 之前生效")
+
+
+def test_set_v8_flags_unknown():
+    """测试无法识别的 V8 参数"""
+    code, output = _run("""
+        import never_jscore
+
+        try:
+            never_jscore.set_v8_flags(["--no-such-v8-flag"])
+            raise SystemExit(1)
+        except ValueError as e:
+            assert "--no-such-v8-flag" in str(e)
+    """)
+    assert code == 0, output
+    print("[OK] 无法识别的 V8 参数抛出 ValueError")
+
+
+def test_jitless_context():
+    """测试 Context(jitless=True)：第一个 Context 开启 jitless，之后的 Context 共享该模式"""
+    code, output = _run("""
+        import never_jscore
+
+        ctx = never_jscore.Context(jitless=True)
+        ctx.compile("function fib(n) { return n < 2 ? n : fib(n - 1) + fib(n - 2); }")
+        assert ctx.call("fib", [20]) == 6765
+        assert ctx.evaluate("Promise.resolve(btoa('hi'))") == "aGk="
+
+        # 之后再要求 jitless 不报错
+        never_jscore.Context(jitless=True).evaluate("1")
+    """)
+    assert code == 0, output
+
+    code, output = _run("""
+        import never_jscore
+
+        never_jscore.Context().evaluate("1")
+        try:
+            never_jscore.Context(jitless=True)
+            raise SystemExit(1)
+        except RuntimeError as e:
+            assert "jitless" in str(e)
+    """)
+    assert code == 0, output
+
+    code, output = _run("""
+        import never_jscore
+
+        never_jscore.init(jitless=True)
+        assert never_jscore.Context().evaluate("[3, 1, 2].sort().join()") == "1,2,3"
+    """)
+    assert code == 0, output
+    print("[OK] jitless 模式")
+
+
+def test_stack_size():
+    """测试 stack_size_kb：深度递归抛出可以捕获的 RangeError"""
+    code, output = _run("""
+        import never_jscore
+
+        ctx = never_jscore.Context(stack_size_kb=4096)
+        ctx.compile("function depth(n) { return n === 0 ? 0 : 1 + depth(n - 1); }")
+        assert ctx.call("depth", [20000]) == 20000
+
+        # 超过上限时抛出 RangeError，Context 仍然可用
+        name = ctx.evaluate("(() => { function f() { return f() + 1; } try { f() } catch (e) { return e.name } })()")
+        assert name == "RangeError", name
+        assert ctx.evaluate("1 + 1") == 2
+
+        # ThreadedContext 的工作线程自动分配足够的栈
+        threaded = never_jscore.ThreadedContext()
+        assert threaded.evaluate("(() => { function f() { return f() } try { f() } catch (e) { return e.name } })()") == "RangeError"
+        threaded.close()
+
+        # 进程级设置，之后不能再修改
+        try:
+            never_jscore.Context(stack_size_kb=512)
+            raise SystemExit(1)
+        except RuntimeError as e:
+            assert "stack_size_kb" in str(e)
+    """)
+    assert code == 0, output
+
+    code, output = _run("""
+        import never_jscore
+
+        never_jscore.init(stack_size_kb=256)
+        ctx = never_jscore.Context()
+        assert ctx.evaluate("(() => { function f() { return f() } try { f() } catch (e) { return e.name } })()") == "RangeError"
+    """)
+    assert code == 0, output
+    print("[OK] stack_size_kb 深度递归抛出 RangeError")
+
+
+# 使用 legacy 异常处理指令（try / catch_all）的模块：f() 返回 1
+EH_MODULE = bytes([
+    0, 0x61, 0x73, 0x6D, 1, 0, 0, 0,
+    1, 5, 1, 0x60, 0, 1, 0x7F,
+    3, 2, 1, 0,
+    7, 5, 1, 1, 0x66, 0, 0,
+    10, 12, 1, 10, 0, 0x06, 0x7F, 0x41, 1, 0x19, 0x41, 2, 0x0B, 0x0B,
+])
+
+
+def test_wasm_features():
+    """测试 wasm_features：默认启用异常处理，关闭后编译错误提示 init() 写法"""
+    code, output = _run(f"""
+        import never_jscore
+
+        never_jscore.init(wasm_features={{"simd": True, "threads": True, "memory64": True}})
+        ctx = never_jscore.Context()
+        ctx.load_wasm("eh", {EH_MODULE!r})
+        assert ctx.call("eh.f", []) == 1
+    """)
+    assert code == 0, output
+
+    code, output = _run(f"""
+        import never_jscore
+
+        never_jscore.init(wasm_features={{"exceptions": False}})
+        ctx = never_jscore.Context()
+        try:
+            ctx.load_wasm("eh", {EH_MODULE!r})
+            raise SystemExit(1)
+        except Exception as e:
+            assert "legacy_eh" in str(e), e
+            assert "wasm_features={{'exceptions': True}}" in str(e), e
+    """)
+    assert code == 0, output
+    print("[OK] init(wasm_features=...)")
+
+
+def test_init_v8_flags_and_icu():
+    """测试 init(v8_flags=..., icu=False)"""
+    code, output = _run("""
+        import never_jscore
+
+        never_jscore.init(v8_flags=["--max-old-space-size=256"], icu=False)
+        ctx = never_jscore.Context()
+        stats = ctx.get_heap_statistics()
+        assert stats["heap_size_limit"] <= 300 * 1024 * 1024, stats["heap_size_limit"]
+
+        # Context 和 Realm 中都没有 Intl，其他内置对象不受影响
+        assert ctx.evaluate("typeof Intl") == "undefined"
+        assert ctx.evaluate("typeof btoa") == "function"
+        realm = ctx.create_realm()
+        assert realm.evaluate("typeof Intl") == "undefined"
+    """)
+    assert code == 0, output
+
+    code, output = _run("""
+        import never_jscore
+
+        try:
+            never_jscore.init(tokio_threads=1, v8_flags=["--no-such-v8-flag"])
+            raise SystemExit(1)
+        except ValueError as e:
+            assert "--no-such-v8-flag" in str(e)
+        assert never_jscore.Context().evaluate("typeof Intl.DateTimeFormat") == "function"
+    """)
+    assert code == 0, output
+    print("[OK] init(v8_flags=..., icu=False)")
+
+
+def test_init_snapshot():
+    """测试 init(snapshot=...)：未传入 snapshot 的 Context 使用全局默认快照"""
+    # build_snapshot() 会初始化 V8，快照在另一个进程中构建后保存到文件
+    with tempfile.TemporaryDirectory() as tmp:
+        path = os.path.join(tmp, "app.snapshot")
+        code, output = _run(f"""
+            import never_jscore
+
+            with open({path!r}, "wb") as f:
+                f.write(never_jscore.build_snapshot("var preloaded = 'from snapshot';"))
+        """)
+        assert code == 0, output
+
+        code, output = _run(f"""
+            import never_jscore
+
+            with open({path!r}, "rb") as f:
+                never_jscore.init(snapshot=f.read())
+
+            ctx = never_jscore.Context()
+            assert ctx.evaluate("preloaded") == "from snapshot"
+            assert ctx.evaluate("btoa('hi')") == "aGk="
+
+            # 显式传入的快照优先，enable_extensions 不一致的 Context 不使用默认快照
+            other = never_jscore.build_snapshot("var preloaded = 'explicit';")
+            assert never_jscore.Context(snapshot=other).evaluate("preloaded") == "explicit"
+            assert never_jscore.Context(enable_extensions=False).evaluate("typeof preloaded") == "undefined"
+        """)
+        assert code == 0, output
+
+    code, output = _run("""
+        import never_jscore
+
+        never_jscore.init(snapshot=False)
+        ctx = never_jscore.Context()
+        assert ctx.evaluate("Promise.resolve(btoa('hi'))") == "aGk="
+    """)
+    assert code == 0, output
+    print("[OK] init(snapshot=...)")
+
+
+def test_init_after_first_context():
+    """测试创建 Context 之后调用 init() 抛出 RuntimeError"""
+    code, output = _run("""
+        import never_jscore
+
+        never_jscore.init(tokio_threads=2)  # 可以多次调用
+        never_jscore.Context().evaluate("1")
+        try:
+            never_jscore.init(tokio_threads=4)
+            raise SystemExit(1)
+        except RuntimeError as e:
+            assert "init()" in str(e)
+    """)
+    assert code == 0, output
+    print("[OK] 创建 Context 之后调用 init() 抛出 RuntimeError")
+
+
+def test_invalid_arguments():
+    """测试非法参数"""
+    import never_jscore
+
+    for kwargs in ({"tokio_threads": 0}, {"stack_size_kb": 0}):
+        try:
+            never_jscore.init(**kwargs)
+            assert False, "应该抛出 ValueError"
+        except ValueError:
+            pass
+
+    print("[OK] tokio_threads=0 / stack_size_kb=0 抛出 ValueError")
+
+    # 未知特性、当前 V8 中无法关闭的特性
+    for features in ({"tail_calls_v2": True}, {"simd": False}):
+        try:
+            never_jscore.init(wasm_features=features)
+            assert False, "应该抛出 ValueError"
+        except ValueError:
+            pass
+    print("[OK] wasm_features 非法参数抛出 ValueError")
+
+    for kwargs, error in (
+        ({"snapshot": b"not a snapshot"}, ValueError),
+        ({"snapshot": "snapshot.bin"}, TypeError),
+        ({"v8_flags": ["--jitless\0"]}, ValueError),
+    ):
+        try:
+            never_jscore.init(**kwargs)
+            assert False, f"应该抛出 {error.__name__}"
+        except error:
+            pass
+    print("[OK] snapshot / v8_flags 非法参数")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试全局初始化配置")
+    print("=" * 60)
+
+    test_current_thread_runtime()
+    test_multi_thread_runtime()
+    test_single_threaded_platform()
+    test_set_v8_flags()
+    test_set_v8_flags_unknown()
+    test_jitless_context()
+    test_stack_size()
+    test_wasm_features()
+    test_init_v8_flags_and_icu()
+    test_init_snapshot()
+    test_init_after_first_context()
+    test_invalid_arguments()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 init() 测试通过！")
+    print("=" * 60)
@@ -429,6 +429,127 @@ def test_memory_efficient_large_dataset():
     print(f"  策略: 分块处理 + 即时释放 Context")
 
 
+def test_per_context_heap_limits():
+    """每个 Context 独立的堆大小限制"""
+    small = never_jscore.Context(max_heap_mb=32)
+    large = never_jscore.Context(initial_heap_mb=64, max_heap_mb=512)
+
+    small_limit = small.get_heap_statistics()["heap_size_limit"]
+    large_limit = large.get_heap_statistics()["heap_size_limit"]
+    assert small_limit < large_limit, (small_limit, large_limit)
+
+    # 超出上限时抛出异常，进程不会崩溃
+    try:
+        small.evaluate("const a = []; while (true) { a.push(new Array(100000).fill('x')); }")
+        assert False, "应该抛出 Heap limit exceeded"
+    except Exception as e:
+        assert "Heap limit exceeded" in str(e), str(e)
+
+    # 其他 Context 不受影响
+    assert large.evaluate("new Array(1000000).fill(1).length") == 1000000
+
+    # 参数校验
+    for kwargs in ({"max_heap_mb": 0}, {"initial_heap_mb": 128, "max_heap_mb": 64}):
+        try:
+            never_jscore.Context(**kwargs)
+            assert False, f"应该抛出 ValueError: {kwargs}"
+        except ValueError:
+            pass
+
+    del small, large
+
+    print(f"\n=== 每个 Context 独立的堆限制 ===")
+    print(f"  小型 Context: {small_limit / 1024 / 1024:.0f} MB")
+    print(f"  大型 Context: {large_limit / 1024 / 1024:.0f} MB")
+    print("✓ 超出 max_heap_mb 抛出异常而不是崩溃")
+
+
+def test_timing_breakdown():
+    """测试编译 / 执行 / 事件循环耗时分解"""
+    ctx = never_jscore.Context()
+
+    ctx.compile("function work(n) { let s = 0; for (let i = 0; i < n; i++) s += i; return s; }")
+    ctx.call("work", [100000])
+    ctx.evaluate("new Promise(r => setTimeout(() => r(1), 50))")
+
+    stats = ctx.get_stats(timings=True)
+    assert stats["exec_count"] == ctx.get_stats()[0] == 3
+    total, last = stats["total"], stats["last"]
+    assert set(total) == {"wrap_ms", "compile_ms", "execute_ms", "event_loop_ms", "total_ms"}
+    assert total["compile_ms"] > 0
+    assert total["execute_ms"] > 0
+    assert last["event_loop_ms"] >= 40, last  # 等待 setTimeout
+    assert abs(total["total_ms"] - sum(v for k, v in total.items() if k != "total_ms")) < 0.01
+
+    # 单次调用的耗时
+    result, timings = ctx.call("work", [10], return_timings=True)
+    assert result == 45
+    assert timings == ctx.get_stats(timings=True)["last"]
+    value, timings = ctx.evaluate("1 + 1", return_timings=True)
+    assert value == 2 and timings["event_loop_ms"] >= 0
+
+    ctx.reset_stats()
+    stats = ctx.get_stats(timings=True)
+    assert stats["exec_count"] == 0 and stats["total"]["total_ms"] == 0
+
+    del ctx
+
+    print(f"\n=== 耗时分解 ===")
+    print(f"  编译: {total['compile_ms']:.2f}ms, 执行: {total['execute_ms']:.2f}ms, "
+          f"事件循环: {total['event_loop_ms']:.2f}ms")
+    print("✓ get_stats(timings=True) / return_timings=True 报告各阶段耗时")
+
+
+def test_large_bundle_code():
+    """测试多 MB 的代码（大段 ASCII 代码以外部字符串传给 V8，不再复制）"""
+    ctx = never_jscore.Context()
+
+    # 约 4MB 的纯 ASCII bundle
+    funcs = "\n".join(f"function f{i}(x) {{ return x + {i}; }}" for i in range(100000))
+    bundle = funcs + "\nfunction total() { return f0(1) + f99999(1); }"
+    assert len(bundle) > 1024 * 1024
+    ctx.compile(bundle)
+    assert ctx.call("total", []) == 100001
+
+    # evaluate() / call() 同样适用
+    assert ctx.evaluate(funcs + "\nf42(0)") == 42
+
+    # 含非 ASCII 字符的大段代码回退到普通字符串，内容保持不变
+    text = "中文" * 50000
+    assert ctx.evaluate(f"'{text}'.length") == len(text)
+
+    del ctx
+    print("✓ 多 MB 代码（ASCII / 非 ASCII）执行正确")
+
+
+def test_low_memory_and_idle():
+    """测试 notify_low_memory() / idle() 主动收缩堆"""
+    ctx = never_jscore.Context()
+
+    ctx.eval("globalThis.garbage = Array.from({length: 200000}, (_, i) => ({i, s: 'x' + i}))")
+    before = ctx.get_heap_statistics()["used_heap_size"]
+    ctx.eval("globalThis.garbage = null")
+
+    start = time.time()
+    ctx.idle(20)
+    assert time.time() - start < 1.0  # 不会超出太多
+    ctx.notify_low_memory()
+    after = ctx.get_heap_statistics()["used_heap_size"]
+    assert after < before, (before, after)
+
+    # 之后 Context 仍然可以正常使用
+    assert ctx.evaluate("1 + 1") == 2
+
+    threaded = never_jscore.ThreadedContext()
+    threaded.idle()
+    threaded.notify_low_memory()
+    assert threaded.evaluate("2 + 2") == 4
+
+    del ctx, threaded
+    print(f"  已用堆: {before / 1024 / 1024:.1f} MB -> {after / 1024 / 1024:.1f} MB")
+    print("✓ notify_low_memory() / idle() 释放内存")
+
+
 if __name__ == "__main__":
     print("=" * 60)
     print("测试内存监控和性能调优")
@@ -439,6 +560,10 @@ if __name__ == "__main__":
     test_heap_snapshot_memory_leak_detection()
     test_heap_statistics_monitoring()
     test_memory_efficient_large_dataset()
+    test_per_context_heap_limits()
+    test_timing_breakdown()
+    test_large_bundle_code()
+    test_low_memory_and_idle()
 
     print("\n" + "=" * 60)
     print("✅ 所有内存和性能测试通过！")
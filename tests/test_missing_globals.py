@@ -0,0 +1,125 @@
+"""
+测试缺失全局变量的诊断：错误提示和 missing_globals() 试运行
+"""
+
+import never_jscore
+
+
+def test_error_hint():
+    """测试 X is not defined 错误附加 missing_global 提示"""
+    ctx = never_jscore.Context()
+    for code, name, keyword in [
+        ("require('crypto')", "require", "Node.js"),
+        ("new MutationObserver(() => {})", "MutationObserver", "not implemented"),
+        ("initSign()", "initSign", "load the script"),
+    ]:
+        try:
+            ctx.evaluate(code)
+            assert False, "应该抛出异常"
+        except Exception as e:
+            message = str(e)
+            assert f'missing_global="{name}"' in message, message
+            assert keyword in message, message
+
+    # 其他错误没有提示
+    try:
+        ctx.evaluate("null.x")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "missing_global" not in str(e)
+    del ctx
+
+    ctx = never_jscore.Context(enable_extensions=False)
+    try:
+        ctx.evaluate("btoa('x')")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert 'missing_global="btoa"' in str(e) and "enable_extensions=True" in str(e), e
+    del ctx
+    print("[OK] 错误提示")
+
+
+def test_missing_globals():
+    """测试试运行列出全部缺失的全局变量"""
+    ctx = never_jscore.Context(enable_extensions=False)
+    code = """
+        var ua = navigator.userAgent;
+        function helper(x) { return x + 1; }
+        helper(1);
+        var token = btoa(require('./key').value);
+        if (typeof process !== 'undefined') { process.env.DEBUG; }
+        Math.max(1, 2);
+    """
+    assert ctx.missing_globals(code) == ["navigator", "btoa", "require", "process"]
+
+    # 已经定义的全局变量不列出
+    ctx.compile("var navigator = { userAgent: 'test' };")
+    assert ctx.missing_globals(code) == ["btoa", "require", "process"]
+    del ctx
+
+    ctx = never_jscore.Context()
+    assert ctx.missing_globals("document.cookie; localStorage.getItem('k'); jQuery('#id').hide();") == ["jQuery"]
+    del ctx
+    print("[OK] missing_globals()")
+
+
+def test_dry_run_isolation():
+    """测试试运行不在全局作用域留下声明和新名称"""
+    ctx = never_jscore.Context()
+    missing = ctx.missing_globals("""
+        var declared = 1;
+        leaked = unknownValue;
+        leaked.foo;
+        window.__touched = true;
+    """)
+    assert missing == ["unknownValue"], missing
+    assert ctx.evaluate("typeof declared") == "undefined"
+    assert ctx.evaluate("typeof leaked") == "undefined"
+    assert ctx.evaluate("typeof unknownValue") == "undefined"
+    # 调用已有对象的副作用是真实的
+    assert ctx.evaluate("window.__touched") is True
+
+    # 原型链已恢复
+    assert ctx.evaluate("Object.getPrototypeOf(Object.getPrototypeOf(globalThis)) !== null")
+    try:
+        ctx.evaluate("unknownValue")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "is not defined" in str(e)
+
+    # 脚本抛出的异常不影响结果，语法错误照常报告
+    assert ctx.missing_globals("missingA(); throw new Error('x'); missingB();") == ["missingA"]
+    try:
+        ctx.missing_globals("var = ;")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "SyntaxError" in str(e), e
+    del ctx
+    print("[OK] 试运行隔离")
+
+
+def test_dynamic_code_disabled():
+    """测试 allow_dynamic_code=False 和 ThreadedContext"""
+    ctx = never_jscore.Context(allow_dynamic_code=False)
+    assert ctx.missing_globals("missingFn()") == ["missingFn"]
+    del ctx
+
+    ctx = never_jscore.ThreadedContext()
+    assert ctx.missing_globals("require('fs'); module.exports = {};") == ["require", "module"]
+    ctx.close()
+    print("[OK] allow_dynamic_code=False / ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试缺失全局变量的诊断")
+    print("=" * 60)
+
+    test_error_hint()
+    test_missing_globals()
+    test_dry_run_isolation()
+    test_dynamic_code_disabled()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有缺失全局变量测试通过！")
+    print("=" * 60)
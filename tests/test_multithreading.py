@@ -5,6 +5,8 @@
 """
 
 import never_jscore
+import sys
+import sysconfig
 import threading
 from concurrent.futures import ThreadPoolExecutor, as_completed
 import time
@@ -311,6 +313,75 @@ def test_error_handling_in_threads():
     print(f"✓ 错误处理: {len(successes)} 成功, {len(errors)} 失败（符合预期）")
 
 
+def test_gil_released_during_execution():
+    """测试 JS 执行期间释放 GIL，其他 Python 线程可以继续运行"""
+    ticks = []
+    stop = threading.Event()
+
+    def ticker():
+        while not stop.is_set():
+            ticks.append(time.perf_counter())
+            time.sleep(0.005)
+
+    ctx = never_jscore.Context()
+    t = threading.Thread(target=ticker)
+    t.start()
+
+    start = time.perf_counter()
+    # 纯 CPU 的 JS 循环，约几百毫秒
+    ctx.evaluate("let s = 0; for (let i = 0; i < 3e8; i++) { s += i; } s")
+    end = time.perf_counter()
+
+    stop.set()
+    t.join()
+    del ctx
+
+    ticks_during = [x for x in ticks if start <= x <= end]
+    assert len(ticks_during) > 5, f"JS 执行期间 Python 线程只运行了 {len(ticks_during)} 次"
+
+    print(f"✓ GIL 释放: JS 执行 {(end - start) * 1000:.0f}ms 期间，其他线程运行了 {len(ticks_during)} 次")
+
+
+def test_free_threading():
+    """测试自由线程解释器（3.13t）：导入后不重新启用 GIL，每线程独立 isolate"""
+    if hasattr(sys, "_is_gil_enabled"):
+        # 普通解释器上 GIL 始终启用；自由线程解释器上导入扩展后不应重新启用
+        if sysconfig.get_config_var("Py_GIL_DISABLED"):
+            assert not sys._is_gil_enabled(), "导入 never_jscore 后 GIL 被重新启用"
+
+    def worker(n):
+        ctx = never_jscore.Context()
+        ctx.compile("var counter = 0; function inc() { return ++counter; }")
+        for _ in range(n):
+            ctx.call("inc", [])
+        result = ctx.call("inc", [])
+        del ctx
+        return result
+
+    with ThreadPoolExecutor(max_workers=8) as executor:
+        results = list(executor.map(worker, [100] * 8))
+    assert results == [101] * 8
+
+    # Context 只能在创建它的线程上使用
+    ctx = never_jscore.Context()
+    errors = []
+
+    def use_from_other_thread():
+        try:
+            ctx.evaluate("1 + 1")
+        except BaseException as e:
+            errors.append(e)
+
+    t = threading.Thread(target=use_from_other_thread)
+    t.start()
+    t.join()
+    assert len(errors) == 1, "跨线程使用 Context 应该抛出异常"
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+
+    print("✓ 自由线程: 8 个线程各自的 isolate 互不干扰，跨线程访问被拒绝")
+
+
 def test_best_practices_summary():
     """多线程最佳实践总结"""
     print("\n" + "=" * 60)
@@ -362,6 +433,8 @@ if __name__ == "__main__":
     test_concurrent_encryption()
     test_thread_pool_with_reused_contexts()
     test_error_handling_in_threads()
+    test_gil_released_during_execution()
+    test_free_threading()
     test_best_practices_summary()
 
     print("\n" + "=" * 60)
@@ -0,0 +1,633 @@
+"""
+测试沙箱选项：Context(permissions=...)、fs_roots、no_ops=True、allow_dynamic_code=False、freeze_intrinsics() 和执行时间限制
+
+展示如何限制不可信代码能访问的网络主机、文件和环境变量，如何只开放指定目录或完全不提供外部 API，如何防止动态代码和原型污染，以及如何终止死循环
+"""
+
+import os
+import sys
+import tempfile
+import threading
+import time
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import never_jscore
+
+
+def test_default_restricted():
+    """测试默认拒绝网络和文件访问，permissions="all" 不做限制"""
+    os.environ["NEVER_JSCORE_TEST_VAR"] = "hello"
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("process.env.NEVER_JSCORE_TEST_VAR") is None
+    assert ctx.evaluate("require('fs').existsSync(%r)" % __file__) is False
+    name = ctx.evaluate("""
+        (async () => {
+            try { await fetch('http://localhost:1/'); return 'no error'; }
+            catch (e) { return e.name; }
+        })()
+    """)
+    assert name == "PermissionDenied"
+    del ctx
+
+    ctx = never_jscore.Context(permissions="all")
+    assert ctx.evaluate("process.env.NEVER_JSCORE_TEST_VAR") == "hello"
+    assert ctx.evaluate("require('fs').existsSync(%r)" % __file__) is True
+    del ctx
+    print("[OK] 默认拒绝网络 / 文件 / 宿主环境变量，permissions=\"all\" 不做限制")
+
+
+def test_read_write_permissions():
+    """测试文件读写白名单"""
+    with tempfile.TemporaryDirectory() as allowed, tempfile.TemporaryDirectory() as other:
+        allowed_file = os.path.join(allowed, "a.txt")
+        other_file = os.path.join(other, "b.txt")
+        for path in (allowed_file, other_file):
+            with open(path, "w") as f:
+                f.write("data")
+
+        ctx = never_jscore.Context(permissions={"read": [allowed], "write": allowed})
+        ctx.compile("const fs = require('fs');")
+
+        assert ctx.evaluate(f"fs.readFileSync({allowed_file!r})") == "data"
+        ctx.evaluate(f"fs.writeFileSync({os.path.join(allowed, 'c.txt')!r}, 'x')")
+
+        # 拒绝的操作抛出 PermissionDenied，可以在 JS 中捕获
+        name = ctx.evaluate(f"""
+            try {{ fs.readFileSync({other_file!r}); 'no error' }}
+            catch (e) {{ e.name }}
+        """)
+        assert name == "PermissionDenied"
+
+        try:
+            ctx.evaluate(f"fs.writeFileSync({other_file!r}, 'x')")
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "PermissionDenied" in str(e)
+
+        # ".." 不能绕过白名单
+        escaped = os.path.join(allowed, "..", os.path.basename(other), "b.txt")
+        assert ctx.evaluate(f"try {{ fs.readFileSync({escaped!r}) }} catch (e) {{ e.name }}") == "PermissionDenied"
+
+        # "link/.." 按链接目标解析：链接指向白名单之外的目录时不能借 ".." 写到外部
+        if hasattr(os, "symlink"):
+            nested = os.path.join(other, "nested")
+            os.mkdir(nested)
+            os.symlink(nested, os.path.join(allowed, "link"))
+            through_link = os.path.join(allowed, "link", "..", "escaped.txt")
+            assert ctx.evaluate(f"try {{ fs.writeFileSync({through_link!r}, 'x') }} catch (e) {{ e.name }}") == "PermissionDenied"
+            assert ctx.evaluate(f"try {{ fs.readFileSync({os.path.join(allowed, 'link', '..', 'b.txt')!r}) }} catch (e) {{ e.name }}") == "PermissionDenied"
+            assert not os.path.exists(os.path.join(other, "escaped.txt"))
+            assert not os.path.exists(os.path.join(allowed, "escaped.txt"))
+
+            # 不存在的目录之后的 ".." 不能按字面弹出，否则 "missing/../link_out" 会被当作白名单内的路径
+            os.symlink(other, os.path.join(allowed, "link_out"))
+            via_missing = os.path.join(allowed, "missing", "..", "link_out", "escaped.txt")
+            assert ctx.evaluate(f"try {{ fs.writeFileSync({via_missing!r}, 'x') }} catch (e) {{ e.name }}") == "PermissionDenied"
+            assert ctx.evaluate(f"try {{ fs.readFileSync({via_missing!r}) }} catch (e) {{ e.name }}") == "PermissionDenied"
+            assert not os.path.exists(os.path.join(other, "escaped.txt"))
+
+        # 查询类操作没有权限时返回 false
+        assert ctx.evaluate(f"fs.existsSync({other_file!r})") is False
+        assert ctx.evaluate(f"fs.existsSync({allowed_file!r})") is True
+
+        with open(other_file) as f:
+            assert f.read() == "data"
+        del ctx
+    print("[OK] read / write 白名单")
+
+
+def test_env_permissions():
+    """测试环境变量权限"""
+    os.environ["NEVER_JSCORE_ALLOWED"] = "1"
+    os.environ["NEVER_JSCORE_SECRET"] = "secret"
+
+    ctx = never_jscore.Context(permissions={"env": ["NEVER_JSCORE_ALLOWED"]})
+    assert ctx.evaluate("process.env.NEVER_JSCORE_ALLOWED") == "1"
+    assert ctx.evaluate("process.env.NEVER_JSCORE_SECRET") is None
+
+    # 未列出 env 时完全拒绝
+    denied = never_jscore.Context(permissions={})
+    assert denied.evaluate("try { process.env.HOME } catch (e) { e.name }") == "PermissionDenied"
+    # 其他 process 属性不受影响
+    assert denied.evaluate("typeof process.version") == "string"
+
+    del ctx, denied
+    print("[OK] env 白名单 / 拒绝")
+
+
+def test_injected_env():
+    """测试 env 注入：process.env 和 Deno.env 只看到注入的变量"""
+    os.environ["NEVER_JSCORE_HOST_ONLY"] = "host"
+
+    ctx = never_jscore.Context(env={"API_KEY": "secret", "EMPTY": ""})
+    assert ctx.evaluate("process.env.API_KEY") == "secret"
+    assert ctx.evaluate("process.env.NEVER_JSCORE_HOST_ONLY") is None
+    assert ctx.evaluate("Deno.env.get('API_KEY')") == "secret"
+    assert ctx.evaluate("Deno.env.get('EMPTY')") == ""
+    assert ctx.evaluate("Deno.env.get('MISSING') === undefined")
+    assert ctx.evaluate("Deno.env.has('EMPTY') && !Deno.env.has('MISSING')")
+    assert ctx.evaluate("Deno.env.toObject()") == {"API_KEY": "secret", "EMPTY": ""}
+    # 只提供 env，没有文件 API
+    assert ctx.evaluate("typeof Deno.readTextFile") == "undefined"
+    del ctx
+
+    # 注入的变量同样受 env 权限限制
+    ctx = never_jscore.Context(env={"API_KEY": "secret", "DEBUG": "1"}, permissions={"env": ["DEBUG"]})
+    assert ctx.evaluate("process.env.DEBUG") == "1"
+    assert ctx.evaluate("process.env.API_KEY") is None
+    assert ctx.evaluate("try { Deno.env.get('API_KEY') } catch (e) { e.name }") == "PermissionDenied"
+    del ctx
+
+    # 不设置 env 时 Deno 仍然隐藏
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("typeof Deno") == "undefined"
+    del ctx
+
+    for kwargs in ({"env": {"A": 1}}, {"env": {"A": "1"}, "enable_extensions": False}):
+        try:
+            never_jscore.Context(**kwargs)
+            assert False, "应该抛出异常"
+        except (TypeError, ValueError):
+            pass
+    print("[OK] env 注入（process.env / Deno.env）")
+
+
+def test_net_permissions():
+    """测试网络白名单（被拒绝的请求不会发出）"""
+    ctx = never_jscore.Context(permissions={"net": ["localhost:1"]})
+    name = ctx.evaluate("""
+        (async () => {
+            try { await fetch('https://example.com/'); return 'no error'; }
+            catch (e) { return e.name; }
+        })()
+    """)
+    assert name == "PermissionDenied"
+
+    # 允许的主机会真正发出请求（这里连接失败，但不是权限错误）
+    name = ctx.evaluate("""
+        (async () => {
+            try { await fetch('http://localhost:1/'); return 'no error'; }
+            catch (e) { return e.name; }
+        })()
+    """)
+    assert name != "PermissionDenied"
+    del ctx
+    print("[OK] net 白名单")
+
+
+def _serve(handler):
+    server = HTTPServer(("127.0.0.1", 0), handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    return server
+
+
+def test_redirect_permissions():
+    """测试重定向的每一跳都重新检查 net 权限"""
+    denied_hits = []
+
+    class Denied(BaseHTTPRequestHandler):
+        def do_GET(self):
+            denied_hits.append(self.path)
+            self.send_response(200)
+            self.end_headers()
+            self.wfile.write(b"secret")
+
+        def log_message(self, *args):
+            pass
+
+    denied = _serve(Denied)
+
+    class Allowed(BaseHTTPRequestHandler):
+        def do_GET(self):
+            if self.path == "/ok":
+                self.send_response(200)
+                self.end_headers()
+                self.wfile.write(b"ok")
+                return
+            port = denied.server_port if self.path == "/out" else self.server.server_port
+            self.send_response(302)
+            self.send_header("Location", f"http://127.0.0.1:{port}/{'leak' if self.path == '/out' else 'ok'}")
+            self.end_headers()
+
+        def log_message(self, *args):
+            pass
+
+    allowed = _serve(Allowed)
+    try:
+        ctx = never_jscore.Context(permissions={"net": [f"127.0.0.1:{allowed.server_port}"]})
+        base = f"http://127.0.0.1:{allowed.server_port}"
+
+        # 允许的主机之间的重定向正常跟随
+        assert ctx.evaluate(f"(async () => (await fetch('{base}/in')).text())()") == "ok"
+
+        # 允许的主机重定向到未授权的主机：抛出 PermissionDenied，不向后者发出请求
+        name = ctx.evaluate(f"""
+            (async () => {{
+                try {{ await fetch('{base}/out'); return 'no error'; }}
+                catch (e) {{ return e.name; }}
+            }})()
+        """)
+        assert name == "PermissionDenied"
+        assert denied_hits == []
+        del ctx
+    finally:
+        allowed.shutdown()
+        denied.shutdown()
+    print("[OK] 重定向到未授权的主机时抛出 PermissionDenied")
+
+
+def test_invalid_permissions():
+    """测试非法参数"""
+    for permissions in ({"network": True}, {"net": 1}, "none"):
+        try:
+            never_jscore.Context(permissions=permissions)
+            assert False, "应该抛出 ValueError"
+        except ValueError:
+            pass
+    try:
+        never_jscore.Context(permissions=1)
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+    print("[OK] 非法权限参数抛出 ValueError / TypeError")
+
+
+def test_fs_roots():
+    """测试 fs_roots 提供限定目录的 Deno 文件 API"""
+    with tempfile.TemporaryDirectory() as assets, tempfile.TemporaryDirectory() as out, \
+            tempfile.TemporaryDirectory() as other:
+        words = os.path.join(assets, "words.txt")
+        with open(words, "w", encoding="utf-8") as f:
+            f.write("alpha\nbeta\n中文")
+        wasm = os.path.join(assets, "module.wasm")
+        blob = bytes(range(256)) * 3
+        with open(wasm, "wb") as f:
+            f.write(blob)
+        secret = os.path.join(other, "secret.txt")
+        with open(secret, "w") as f:
+            f.write("secret")
+
+        # 不设置 fs_roots 时没有 Deno
+        assert never_jscore.Context().evaluate("typeof Deno") == "undefined"
+
+        ctx = never_jscore.Context(fs_roots={assets: "r", out: "rw"})
+        assert ctx.evaluate(f"Deno.readTextFile({words!r})") == "alpha\nbeta\n中文"
+        assert ctx.evaluate(f"Deno.readTextFileSync({words!r}).split('\\n').length") == 3
+
+        assert ctx.evaluate(f"""
+            (async () => {{
+                const bytes = await Deno.readFile({wasm!r});
+                return [bytes instanceof Uint8Array, bytes.length, bytes[255], bytes[256]];
+            }})()
+        """) == [True, len(blob), 255, 0]
+
+        names = ctx.evaluate(f"""
+            (async () => {{
+                const names = [];
+                for await (const entry of Deno.readDir({assets!r})) names.push(entry.name);
+                return names.sort();
+            }})()
+        """)
+        assert names == ["module.wasm", "words.txt"]
+        assert ctx.evaluate(f"Deno.statSync({wasm!r}).size") == len(blob)
+
+        # 根目录之外的路径（包括 ".."）被拒绝
+        for path in (secret, os.path.join(assets, "..", os.path.basename(other), "secret.txt")):
+            assert ctx.evaluate(f"Deno.readTextFile({path!r}).catch(e => e.name)") == "PermissionDenied"
+
+        # 默认只读，"rw" 目录可以写入
+        readonly = os.path.join(assets, "x.txt")
+        assert ctx.evaluate(f"try {{ Deno.writeTextFileSync({readonly!r}, 'x') }} catch (e) {{ e.name }}") == "PermissionDenied"
+        ctx.evaluate(f"Deno.writeFile({os.path.join(out, 'copy.wasm')!r}, Deno.readFileSync({wasm!r}))")
+        with open(os.path.join(out, "copy.wasm"), "rb") as f:
+            assert f.read() == blob
+
+        # 指向根目录之外、目标还不存在的符号链接：写入被拒绝，不会在外部创建文件
+        if hasattr(os, "symlink"):
+            planted = os.path.join(other, "planted.txt")
+            link = os.path.join(out, "link.txt")
+            os.symlink(planted, link)
+            assert ctx.evaluate(f"try {{ Deno.writeTextFileSync({link!r}, 'x') }} catch (e) {{ e.name }}") == "PermissionDenied"
+            assert ctx.evaluate(f"Deno.readTextFile({link!r}).catch(e => e.name)") == "PermissionDenied"
+            assert not os.path.exists(planted)
+
+            fs_ctx = never_jscore.Context(permissions={"write": [out]})
+            assert fs_ctx.evaluate(f"try {{ require('fs').writeFileSync({link!r}, 'x') }} catch (e) {{ e.name }}") == "PermissionDenied"
+            assert not os.path.exists(planted)
+            del fs_ctx
+
+            # stat / readDir 同样不能借不存在的目录之后的 ".." 经符号链接访问根目录之外
+            os.symlink(other, os.path.join(assets, "link_out"))
+            via_missing = os.path.join(assets, "missing", "..", "link_out")
+            assert ctx.evaluate(f"try {{ Deno.statSync({via_missing!r}) }} catch (e) {{ e.name }}") == "PermissionDenied"
+            assert ctx.evaluate(f"try {{ Deno.readDirSync({via_missing!r}) }} catch (e) {{ e.name }}") == "PermissionDenied"
+
+            fs_ctx = never_jscore.Context(permissions={"read": [assets]})
+            assert fs_ctx.evaluate(f"require('fs').existsSync({os.path.join(via_missing, 'secret.txt')!r})") is False
+            assert fs_ctx.evaluate(f"try {{ require('fs').readdirSync({via_missing!r}) }} catch (e) {{ e.name }}") == "PermissionDenied"
+            del fs_ctx
+
+        # Deno 只包含文件 API
+        assert ctx.evaluate("typeof Deno.core") == "undefined"
+        assert ctx.evaluate("Object.isFrozen(Deno)") is True
+        del ctx
+
+    for fs_roots in ("/nonexistent/never_jscore", {__file__: "r"}, {".": "w"}):
+        try:
+            never_jscore.Context(fs_roots=fs_roots)
+            assert False, "应该抛出 ValueError"
+        except ValueError:
+            pass
+    print("[OK] fs_roots 限定目录的文件 API")
+
+
+def test_no_ops():
+    """测试 no_ops=True 纯计算模式"""
+    ctx = never_jscore.Context(no_ops=True)
+    ctx.compile("function fib(n) { return n < 2 ? n : fib(n - 1) + fib(n - 2); }")
+    assert ctx.call("fib", [20]) == 6765
+    assert ctx.evaluate("[3, 1, 2].sort().map(x => x * 2)") == [2, 4, 6]
+    assert ctx.evaluate("Promise.resolve(42)") == 42
+
+    # 没有任何能访问 V8 之外资源的 API
+    for name in ("Deno", "__bootstrap", "require", "fetch", "setTimeout", "btoa", "process", "localStorage"):
+        assert ctx.evaluate(f"typeof {name}") == "undefined", name
+
+    # console 可以调用，但不输出
+    assert ctx.evaluate("console.log('hidden'); typeof console.log") == "function"
+
+    # 忽略 enable_extensions
+    assert never_jscore.Context(no_ops=True, enable_extensions=True).evaluate("typeof btoa") == "undefined"
+    del ctx
+    print("[OK] no_ops=True 纯计算模式")
+
+
+def test_disallow_dynamic_code():
+    """测试 allow_dynamic_code=False 禁止 eval / new Function"""
+    ctx = never_jscore.Context(allow_dynamic_code=False)
+    ctx.compile("function add(a, b) { return a + b; }")
+
+    # evaluate() / call() 不经过 eval，仍然可用
+    assert ctx.call("add", [1, 2]) == 3
+    assert ctx.evaluate("[1, 2, 3].map(x => x * 2)") == [2, 4, 6]
+    assert ctx.evaluate("Promise.resolve(42)") == 42
+    assert ctx.evaluate("btoa('hello')") == "aGVsbG8="
+
+    # let/const 不泄漏到全局
+    ctx.evaluate("const local = 1; local")
+    assert ctx.evaluate("typeof local") == "undefined"
+
+    for code in ("eval('1 + 1')", "new Function('return 1')()", "(0, eval)('1')"):
+        assert ctx.evaluate(f"try {{ {code}; 'allowed' }} catch (e) {{ e.name }}") == "EvalError", code
+
+    try:
+        ctx.evaluate("eval('1')")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "EvalError" in str(e)
+
+    # 纯净环境同样生效；默认 Context 不受影响
+    pure = never_jscore.Context(enable_extensions=False, allow_dynamic_code=False)
+    assert pure.evaluate("try { eval('1') } catch (e) { e.name }") == "EvalError"
+    assert never_jscore.Context().evaluate("eval('1 + 1')") == 2
+
+    del ctx, pure
+    print("[OK] allow_dynamic_code=False 禁止 eval / new Function")
+
+
+def test_freeze_intrinsics():
+    """测试 freeze_intrinsics() 防止原型污染"""
+    ctx = never_jscore.Context()
+    ctx.compile("function double(arr) { return arr.map(x => x * 2); }")
+    ctx.freeze_intrinsics()
+    ctx.freeze_intrinsics()  # 重复调用是安全的
+
+    # 修改内置原型静默失败（严格模式下抛出 TypeError）
+    ctx.evaluate("Array.prototype.map = () => 'hacked'; Object.prototype.polluted = true")
+    assert ctx.call("double", [[1, 2]]) == [2, 4]
+    assert ctx.evaluate("({}).polluted") is None
+    assert ctx.evaluate("""
+        (() => { 'use strict'; try { JSON.parse = null; return 'no error' } catch (e) { return e.name } })()
+    """) == "TypeError"
+
+    # 在实例上覆盖继承来的属性仍然可用
+    assert ctx.evaluate("const o = {}; o.toString = () => 'custom'; String(o)") == "custom"
+    assert ctx.evaluate("""
+        class MyError extends Error { constructor(m) { super(m); this.name = 'MyError'; } }
+        new MyError('x').name
+    """) == "MyError"
+    assert ctx.evaluate("Error.stackTraceLimit = 20; Error.stackTraceLimit") == 20
+
+    # 扩展 API 不受影响
+    assert ctx.evaluate("btoa('hello')") == "aGVsbG8="
+    assert ctx.evaluate("new Promise(r => setTimeout(() => r(1), 10))") == 1
+
+    # SnapshotPool：冻结状态保存在快照中
+    pool = never_jscore.SnapshotPool("globalThis.ready = true;", freeze_intrinsics=True)
+    with pool.checkout() as fresh:
+        assert fresh.evaluate("Object.isFrozen(Array.prototype) && ready") is True
+
+    del ctx
+    print("[OK] freeze_intrinsics() 防止原型污染")
+
+
+def test_cpu_limit():
+    """测试 cpu_limit_ms 在宽松的墙钟超时下终止死循环"""
+    if not sys.platform.startswith("linux"):
+        print("[SKIP] cpu_limit_ms 仅支持 Linux")
+        return
+
+    ctx = never_jscore.Context(timeout_ms=10000, cpu_limit_ms=100)
+    ctx.compile("function spin() { while (true) {} }")
+
+    for run in (lambda: ctx.evaluate("while (true) {}"), lambda: ctx.call("spin", [])):
+        start = time.time()
+        try:
+            run()
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "CPU time limit exceeded" in str(e), e
+        assert time.time() - start < 2
+
+    # 等待定时器不消耗 CPU 时间
+    assert ctx.evaluate("new Promise(r => setTimeout(() => r('done'), 300))") == "done"
+
+    # 终止后 Context 仍然可用
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] cpu_limit_ms 终止死循环")
+
+
+def test_timeout():
+    """测试 timeout_ms 限制等待时间"""
+    ctx = never_jscore.Context(timeout_ms=200)
+
+    start = time.time()
+    try:
+        ctx.evaluate("new Promise(r => setTimeout(r, 10000))")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Execution timed out" in str(e), e
+    assert time.time() - start < 2
+
+    try:
+        ctx.compile("while (true) {}")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Execution timed out" in str(e), e
+
+    assert ctx.evaluate("Promise.resolve(42)") == 42
+
+    for kwargs in ({"timeout_ms": 0}, {"cpu_limit_ms": 0}):
+        try:
+            never_jscore.Context(**kwargs)
+            assert False, "应该抛出 ValueError"
+        except ValueError:
+            pass
+    del ctx
+    print("[OK] timeout_ms 限制等待时间")
+
+
+def test_regexp_backtracking():
+    """测试灾难性回溯的正则表达式被终止，并在错误中注明原因"""
+    ctx = never_jscore.Context(timeout_ms=200)
+
+    start = time.time()
+    try:
+        ctx.evaluate("/(a+)+$/.test('a'.repeat(40) + 'b')")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Execution timed out" in str(e), e
+        assert "regular expression" in str(e), e
+    assert time.time() - start < 2
+
+    try:
+        ctx.evaluate("('a'.repeat(40) + 'b').replace(/(a|a)*$/, '')")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "regular expression" in str(e), e
+
+    # 普通死循环不带正则说明
+    try:
+        ctx.evaluate("while (true) {}")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Execution timed out" in str(e), e
+        assert "regular expression" not in str(e), e
+
+    assert ctx.evaluate("/a+/.test('aaa')") is True
+    del ctx
+    print("[OK] 正则表达式灾难性回溯被终止")
+
+
+def test_quotas():
+    """测试 quotas 累计配额，耗尽后拒绝之后的调用"""
+    # 执行次数
+    ctx = never_jscore.Context(quotas={"max_executions": 2})
+    ctx.compile("function add(a, b) { return a + b; }")
+    assert ctx.call("add", [1, 2]) == 3
+    for _ in range(2):
+        try:
+            ctx.evaluate("1 + 1")
+            assert False, "应该抛出 QuotaExceeded"
+        except never_jscore.QuotaExceeded as e:
+            assert "max_executions=2" in str(e), e
+    usage = ctx.get_quota_usage()
+    assert usage["executions"] == 2, usage
+    assert usage["exhausted"] == "Execution quota exhausted (max_executions=2)", usage
+    del ctx
+
+    # 结果大小：只让本次调用失败
+    ctx = never_jscore.Context(quotas={"max_result_bytes": 100})
+    try:
+        ctx.evaluate("'x'.repeat(1000)")
+        assert False, "应该抛出 QuotaExceeded"
+    except never_jscore.QuotaExceeded as e:
+        assert "max_result_bytes=100" in str(e), e
+    assert ctx.evaluate("'ok'") == "ok"
+    assert ctx.get_quota_usage()["exhausted"] is None
+    del ctx
+
+    # console 输出：超出后终止当前调用并耗尽配额
+    ctx = never_jscore.Context(quotas={"max_console_bytes": 64})
+    ctx.evaluate("console.log('hello')")
+    try:
+        ctx.evaluate("for (let i = 0; i < 1000; i++) console.log('spam ' + i)")
+        assert False, "应该抛出 QuotaExceeded"
+    except never_jscore.QuotaExceeded as e:
+        assert "max_console_bytes=64" in str(e), e
+    assert ctx.get_quota_usage()["console_bytes"] <= 64
+    try:
+        ctx.evaluate("1 + 1")
+        assert False, "应该抛出 QuotaExceeded"
+    except never_jscore.QuotaExceeded:
+        pass
+    del ctx
+
+    # 累计 CPU 时间
+    if sys.platform.startswith("linux"):
+        ctx = never_jscore.Context(quotas={"max_cpu_ms": 100})
+        start = time.time()
+        try:
+            ctx.evaluate("while (true) {}")
+            assert False, "应该抛出 QuotaExceeded"
+        except never_jscore.QuotaExceeded as e:
+            assert "CPU time quota exhausted" in str(e), e
+        assert time.time() - start < 2
+        assert ctx.get_quota_usage()["cpu_ms"] >= 100
+        try:
+            ctx.evaluate("1 + 1")
+            assert False, "应该抛出 QuotaExceeded"
+        except never_jscore.QuotaExceeded:
+            pass
+        del ctx
+
+    # 普通错误不受影响，未设置 quotas 时 get_quota_usage() 报错
+    ctx = never_jscore.Context(quotas={"max_executions": 10})
+    try:
+        ctx.evaluate("throw new Error('boom')")
+        assert False, "应该抛出异常"
+    except never_jscore.QuotaExceeded:
+        assert False, "普通错误不应是 QuotaExceeded"
+    except Exception as e:
+        assert "boom" in str(e), e
+    del ctx
+    try:
+        never_jscore.Context().get_quota_usage()
+        assert False, "应该抛出 RuntimeError"
+    except RuntimeError:
+        pass
+
+    for quotas in ({"max_executions": 0}, {"max_memory": 1}, {"max_cpu_ms": "fast"}):
+        try:
+            never_jscore.Context(quotas=quotas)
+            assert False, "应该抛出 ValueError"
+        except ValueError:
+            pass
+    print("[OK] quotas 累计配额")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试权限控制")
+    print("=" * 60)
+
+    test_default_restricted()
+    test_read_write_permissions()
+    test_env_permissions()
+    test_injected_env()
+    test_net_permissions()
+    test_redirect_permissions()
+    test_invalid_permissions()
+    test_fs_roots()
+    test_no_ops()
+    test_disallow_dynamic_code()
+    test_freeze_intrinsics()
+    test_cpu_limit()
+    test_timeout()
+    test_regexp_backtracking()
+    test_quotas()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有权限测试通过！")
+    print("=" * 60)
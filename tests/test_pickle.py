@@ -0,0 +1,191 @@
+"""
+测试 Context 的 pickle 和 copy.deepcopy
+
+pickle 保存执行过的全局脚本和由它们生成的启动快照，恢复后的 Context 与原 Context 全局状态相同
+"""
+
+import copy
+import os
+import pickle
+import subprocess
+import sys
+import tempfile
+import textwrap
+
+import never_jscore
+
+
+SIGN_JS = """
+var counter = 41;
+function sign(text) { return btoa(text) + ':' + (++counter); }
+"""
+
+
+def test_pickle_roundtrip():
+    """测试 pickle 后全局函数和变量仍然可用"""
+    ctx = never_jscore.Context()
+    ctx.compile(SIGN_JS)
+    ctx.eval("var suffix = '!'")
+
+    restored = pickle.loads(pickle.dumps(ctx))
+    assert restored.call("sign", ["hi"]) == "aGk=:42"
+    assert restored.evaluate("suffix") == "!"
+
+    # 恢复的 Context 可以再次 pickle
+    again = pickle.loads(pickle.dumps(restored))
+    assert again.evaluate("typeof sign") == "function"
+    del ctx, restored, again
+    print("[OK] pickle.dumps / pickle.loads")
+
+
+def test_deepcopy_is_independent():
+    """测试 deepcopy 得到独立的 Context"""
+    ctx = never_jscore.Context()
+    ctx.compile(SIGN_JS)
+
+    clone = copy.deepcopy(ctx)
+    assert clone.call("sign", ["a"]) == "YQ==:42"
+    assert clone.call("sign", ["a"]) == "YQ==:43"
+    assert ctx.call("sign", ["a"]) == "YQ==:42"
+    del ctx, clone
+    print("[OK] copy.deepcopy")
+
+
+def test_options_are_preserved():
+    """测试构造参数随 pickle 保存"""
+    ctx = never_jscore.Context(enable_extensions=False, timeout_ms=200)
+    ctx.compile("function add(a, b) { return a + b; }")
+
+    restored = pickle.loads(pickle.dumps(ctx))
+    assert restored.call("add", [1, 2]) == 3
+    assert restored.evaluate("typeof setTimeout") == "undefined"
+    try:
+        restored.evaluate("while (true) {}")
+        assert False, "应该超时"
+    except Exception as e:
+        assert "Execution timed out (timeout_ms=200)" in str(e), e
+    del ctx, restored
+    print("[OK] 构造参数随 pickle 保存")
+
+
+def test_replay_fallback():
+    """测试快照不可用（版本不一致）时重新执行脚本"""
+    ctx = never_jscore.Context()
+    ctx.compile(SIGN_JS)
+    restore, (state,) = ctx.__reduce__()
+    assert isinstance(state["snapshot"], bytes)
+    assert [name for name, _ in state["scripts"]] == ["<exec>"]
+
+    state["version"] = "0.0.0"
+    restored = restore(state)
+    assert restored.call("sign", ["hi"]) == "aGk=:42"
+    del ctx, restored
+    print("[OK] 版本不一致时重新执行脚本")
+
+
+def test_other_process():
+    """测试在另一个进程中加载 pickle 数据"""
+    ctx = never_jscore.Context()
+    ctx.compile(SIGN_JS)
+    with tempfile.TemporaryDirectory() as tmp:
+        path = os.path.join(tmp, "ctx.pickle")
+        with open(path, "wb") as f:
+            pickle.dump(ctx, f)
+
+        script = textwrap.dedent(f"""
+            import pickle
+            with open({path!r}, "rb") as f:
+                ctx = pickle.load(f)
+            print(ctx.call("sign", ["hi"]))
+        """)
+        proc = subprocess.run([sys.executable, "-c", script], capture_output=True, text=True, timeout=60)
+        assert proc.returncode == 0, proc.stdout + proc.stderr
+        assert proc.stdout.strip() == "aGk=:42", proc.stdout
+    del ctx
+    print("[OK] 在另一个进程中加载")
+
+
+def test_unpicklable_state():
+    """测试无法重放的状态抛出 TypeError"""
+    ctx = never_jscore.Context()
+    ctx.register("pyAdd", lambda a, b: a + b)
+    try:
+        pickle.dumps(ctx)
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "register()" in str(e), e
+    del ctx
+
+    ctx = never_jscore.Context(permissions={"env": ["HOME"]})
+    try:
+        copy.deepcopy(ctx)
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "permissions" in str(e), e
+    del ctx
+    print("[OK] 无法重放的状态抛出 TypeError")
+
+
+def test_replay_log_limit():
+    """测试记录的脚本超过上限后停止记录，pickle 抛出 TypeError"""
+    ctx = never_jscore.Context()
+    ctx.compile("var small = 1;")
+    copy.deepcopy(ctx)
+
+    # 16 MB 以上的脚本（大部分是注释）
+    ctx.compile("var big = 2; //" + "x" * (17 * 1024 * 1024))
+    assert ctx.evaluate("small + big") == 3
+    try:
+        copy.deepcopy(ctx)
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "replay log" in str(e), e
+    del ctx
+    print("[OK] 重放记录超过上限后停止记录")
+
+
+def test_restored_snapshot_is_freed():
+    """测试恢复时的快照随 Context 一起释放（内容各不相同的快照不会累积占用内存）"""
+    if not os.path.exists("/proc/self/statm"):
+        print("[SKIP] 需要 /proc/self/statm")
+        return
+
+    def rss():
+        with open("/proc/self/statm") as f:
+            return int(f.read().split()[1]) * os.sysconf("SC_PAGE_SIZE")
+
+    def restore_distinct(i):
+        ctx = never_jscore.Context()
+        ctx.compile(f"var payload{i} = '{i}'.repeat(1 << 20);")
+        data = pickle.dumps(ctx)
+        restored = pickle.loads(data)
+        assert restored.evaluate(f"payload{i}.length") == 1 << 20
+        del ctx, restored
+        return len(data)
+
+    for i in range(5):
+        restore_distinct(i)
+    before = rss()
+    size = max(restore_distinct(i) for i in range(5, 45))
+    growth = rss() - before
+    assert growth < 20 * size, f"RSS grew by {growth} bytes over 40 restores (snapshot {size} bytes)"
+    print("[OK] 恢复时的快照随 Context 释放")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 Context pickle / deepcopy")
+    print("=" * 60)
+
+    test_pickle_roundtrip()
+    test_deepcopy_is_independent()
+    test_options_are_preserved()
+    test_replay_fallback()
+    test_other_process()
+    test_unpicklable_state()
+    test_replay_log_limit()
+    test_restored_snapshot_is_freed()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 pickle 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,122 @@
+"""
+测试致命错误后的降级：超出 max_heap_mb 后 Context 被标记为 poisoned，或者 auto_recreate=True 时自动重建
+
+执行中的 Rust panic 无法从 Python 触发，这里用超出堆上限覆盖同一条路径
+"""
+
+import never_jscore
+
+BLOW_UP = "const a = []; while (true) { a.push(new Array(100000).fill('x')); }"
+INIT_CODE = "var base = 41; function answer() { return base + 1; }"
+
+
+def test_poisoned():
+    """测试超出堆上限后之后的执行抛出 ContextPoisoned"""
+    ctx = never_jscore.Context(max_heap_mb=32)
+    ctx.compile(INIT_CODE)
+    assert ctx.poisoned is None
+    limit = ctx.get_heap_statistics()["heap_size_limit"]
+    try:
+        ctx.evaluate(BLOW_UP)
+        assert False, "应该抛出 Heap limit exceeded"
+    except Exception as e:
+        assert "Heap limit exceeded" in str(e) and "poisoned" in str(e), e
+        assert not isinstance(e, never_jscore.ContextPoisoned)
+    assert ctx.poisoned.startswith("Heap limit exceeded"), ctx.poisoned
+
+    # 终止执行时临时放宽的堆上限在执行结束后恢复，不会一直翻倍
+    restored = ctx.get_heap_statistics()["heap_size_limit"]
+    assert restored < limit * 2, (limit, restored)
+
+    for run in (lambda: ctx.call("answer", []), lambda: ctx.evaluate("1"), lambda: ctx.compile("var x = 1;")):
+        try:
+            run()
+            assert False, "应该抛出 ContextPoisoned"
+        except never_jscore.ContextPoisoned as e:
+            assert isinstance(e, RuntimeError)
+            assert "Heap limit exceeded" in str(e) and "auto_recreate" in str(e), e
+    del ctx
+    print("[OK] poisoned")
+
+
+def test_auto_recreate():
+    """测试 auto_recreate=True 时重建 isolate，初始化脚本原样重建、调用之间的状态不保留"""
+    ctx = never_jscore.Context(max_heap_mb=32, auto_recreate=True)
+    ctx.compile(INIT_CODE)
+    ctx.evaluate("globalThis.leftover = 1")
+    try:
+        ctx.evaluate(BLOW_UP)
+        assert False, "应该抛出 Heap limit exceeded"
+    except Exception as e:
+        assert "will be recreated" in str(e), e
+    assert ctx.poisoned is None
+    assert ctx.call("answer", []) == 42
+    assert ctx.evaluate("typeof leftover") == "undefined"
+
+    # compile() 遇到致命错误后，在下一次调用之前重建
+    try:
+        ctx.compile(BLOW_UP)
+        assert False, "应该抛出 Heap limit exceeded"
+    except Exception as e:
+        assert "Heap limit exceeded" in str(e), e
+    assert ctx.poisoned is not None
+    assert ctx.call("answer", []) == 42
+    assert ctx.poisoned is None
+    del ctx
+    print("[OK] auto_recreate")
+
+
+def test_threaded():
+    """测试 ThreadedContext 在遇到错误的任务结束后重建"""
+    ctx = never_jscore.ThreadedContext(max_heap_mb=32, auto_recreate=True)
+    ctx.compile(INIT_CODE)
+    try:
+        ctx.evaluate(BLOW_UP)
+        assert False, "应该抛出 Heap limit exceeded"
+    except Exception:
+        pass
+    assert ctx.call("answer", []) == 42
+    assert ctx.poisoned is None
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+def test_pool():
+    """测试 ContextPool 的工作线程：默认保持 poisoned，auto_recreate=True 时重建"""
+    pool = never_jscore.ContextPool(INIT_CODE, size=1, max_heap_mb=32)
+    try:
+        pool.evaluate(BLOW_UP)
+        assert False, "应该抛出 Heap limit exceeded"
+    except Exception:
+        pass
+    try:
+        pool.call("answer", [])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "poisoned" in str(e), e
+    pool.close()
+
+    pool = never_jscore.ContextPool(INIT_CODE, size=1, max_heap_mb=32, auto_recreate=True)
+    try:
+        pool.evaluate(BLOW_UP)
+        assert False, "应该抛出 Heap limit exceeded"
+    except Exception:
+        pass
+    assert pool.call("answer", []) == 42
+    pool.close()
+    print("[OK] ContextPool")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试致命错误后的降级")
+    print("=" * 60)
+
+    test_poisoned()
+    test_auto_recreate()
+    test_threaded()
+    test_pool()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有致命错误降级测试通过！")
+    print("=" * 60)
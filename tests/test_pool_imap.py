@@ -0,0 +1,166 @@
+"""
+测试 ContextPool.imap()：每一项完成后就产出 map() 的结果（同步和 async for 迭代）
+"""
+
+import asyncio
+import threading
+import time
+
+import never_jscore
+
+JS_CODE = """
+function spin(ms) { const end = Date.now() + ms; while (Date.now() < end) {} return ms; }
+function double(x) { return x * 2; }
+function fail(x) { if (x === 3) throw new Error("bad item " + x); return x; }
+function payload(x) { return { value: x }; }
+"""
+
+
+def test_ordered():
+    """测试 ordered=True 按输入顺序产出结果"""
+    pool = never_jscore.ContextPool(JS_CODE, size=4)
+    stream = pool.imap("double", range(20))
+    assert list(stream) == [x * 2 for x in range(20)]
+    assert list(stream) == []
+    assert list(pool.imap("double", [])) == []
+    assert list(pool.imap("spin", [[50], [10], [30]], workers=2)) == [50, 10, 30]
+    pool.close()
+    print("[OK] ordered=True 按输入顺序")
+
+
+def test_unordered():
+    """测试 ordered=False 按完成顺序产出 (索引, 结果)"""
+    pool = never_jscore.ContextPool(JS_CODE, size=2)
+    results = list(pool.imap("spin", [[300], [10]], ordered=False))
+    assert results == [(1, 10), (0, 300)], results
+    pairs = sorted(pool.imap("double", range(10), ordered=False))
+    assert pairs == [(i, i * 2) for i in range(10)]
+    pool.close()
+    print("[OK] ordered=False 按完成顺序")
+
+
+def test_streaming():
+    """测试第一项的结果在整批完成之前产出"""
+    pool = never_jscore.ContextPool(JS_CODE, size=2)
+    start = time.time()
+    stream = pool.imap("spin", [[10]] + [[200]] * 4, workers=2)
+    first = next(stream)
+    first_at = time.time() - start
+    rest = list(stream)
+    total = time.time() - start
+    assert first == 10 and rest == [200] * 4
+    assert first_at < total / 2, (first_at, total)
+    pool.close()
+    print(f"[OK] 流式产出（首项 {first_at:.3f}s，整批 {total:.3f}s）")
+
+
+def test_error():
+    """测试失败的项在轮到它时抛出（包含索引），之后迭代结束"""
+    pool = never_jscore.ContextPool(JS_CODE, size=2)
+    stream = pool.imap("fail", [1, 2, 3, 4, 5])
+    assert next(stream) == 1
+    assert next(stream) == 2
+    try:
+        next(stream)
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "item 2" in str(e) and "bad item 3" in str(e), e
+    assert list(stream) == []
+    pool.close()
+    print("[OK] 失败的项")
+
+
+def test_result_format():
+    """测试 result_format 作用于每一项"""
+    pool = never_jscore.ContextPool(JS_CODE, size=2)
+    texts = list(pool.imap("payload", [1, 2], result_format="json_str"))
+    assert texts == ['{"value":1}', '{"value":2}'], texts
+    pool.close()
+    print("[OK] result_format")
+
+
+def test_async_for():
+    """测试 async for 迭代不阻塞事件循环"""
+    pool = never_jscore.ContextPool(JS_CODE, size=2)
+
+    async def main():
+        ticks = 0
+
+        async def ticker():
+            nonlocal ticks
+            while True:
+                ticks += 1
+                await asyncio.sleep(0.01)
+
+        task = asyncio.create_task(ticker())
+        ordered = [value async for value in pool.imap("spin", [[100], [100], [100]])]
+        unordered = sorted([pair async for pair in pool.imap("double", range(5), ordered=False)])
+        task.cancel()
+        return ordered, unordered, ticks
+
+    ordered, unordered, ticks = asyncio.run(main())
+    assert ordered == [100, 100, 100]
+    assert unordered == [(i, i * 2) for i in range(5)]
+    assert ticks > 5, ticks
+
+    async def failing():
+        return [value async for value in pool.imap("fail", [3])]
+
+    try:
+        asyncio.run(failing())
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "item 0" in str(e), e
+    pool.close()
+    print(f"[OK] async for（期间事件循环运行了 {ticks} 次）")
+
+
+def test_pool_full():
+    """测试 on_full="raise" 时产出已提交的项后抛出 PoolFull"""
+    pool = never_jscore.ContextPool(JS_CODE, size=1, max_queue=1, on_full="raise")
+    blocker = threading.Thread(target=pool.call, args=("spin", [300]))
+    blocker.start()
+    time.sleep(0.1)
+    stream = pool.imap("double", [1, 2, 3])
+    results = []
+    try:
+        for value in stream:
+            results.append(value)
+        assert False, "应该抛出 PoolFull"
+    except never_jscore.PoolFull:
+        pass
+    assert results == [2], results
+    blocker.join()
+    pool.close()
+    print("[OK] on_full='raise'")
+
+
+def test_closed():
+    """测试池关闭后 imap() 抛出异常"""
+    pool = never_jscore.ContextPool(JS_CODE, size=1)
+    pool.close()
+    try:
+        pool.imap("double", [1])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "closed" in str(e), e
+    print("[OK] 池关闭后")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 ContextPool.imap()")
+    print("=" * 60)
+
+    test_ordered()
+    test_unordered()
+    test_streaming()
+    test_error()
+    test_result_format()
+    test_async_for()
+    test_pool_full()
+    test_closed()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 imap() 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,163 @@
+"""
+测试 ContextPool 的调度：优先级、有界队列（max_queue / on_full）和按租户轮转
+
+size=1 的池中先提交一个忙等的任务占住工作线程，之后提交的任务都在排队，
+JS 全局数组 order 记录它们实际的执行顺序
+"""
+
+import asyncio
+import threading
+import time
+
+import never_jscore
+
+JS_CODE = """
+var order = [];
+function spin(ms) { const end = Date.now() + ms; while (Date.now() < end) {} return ms; }
+function record(tag) { order.push(tag); return tag; }
+"""
+
+
+def occupy(pool, ms=300):
+    """提交一个忙等任务占住工作线程，返回其线程"""
+    thread = threading.Thread(target=pool.call, args=("spin", [ms]))
+    thread.start()
+    time.sleep(0.1)
+    return thread
+
+
+def submit_in_order(pool, calls):
+    """按顺序提交排队的 record() 调用（等待每个任务进入队列后再提交下一个）"""
+    threads = []
+    for tag, kwargs in calls:
+        thread = threading.Thread(target=pool.call, args=("record", [tag]), kwargs=kwargs)
+        thread.start()
+        threads.append(thread)
+        deadline = time.time() + 5
+        while pool.queued < len(threads) and time.time() < deadline:
+            time.sleep(0.005)
+    return threads
+
+
+def test_priority():
+    """测试优先级高的任务先执行，同一优先级按提交顺序"""
+    pool = never_jscore.ContextPool(JS_CODE, size=1)
+    blocker = occupy(pool)
+    threads = submit_in_order(pool, [
+        ("low", {"priority": -1}),
+        ("normal-1", {}),
+        ("high", {"priority": 10}),
+        ("normal-2", {}),
+    ])
+    assert pool.queued == 4, pool.queued
+    for thread in [blocker, *threads]:
+        thread.join()
+    assert pool.evaluate("order") == ["high", "normal-1", "normal-2", "low"]
+    assert pool.queued == 0
+    pool.close()
+    print("[OK] 优先级")
+
+
+def test_tenant_fairness():
+    """测试同一优先级内各租户轮流执行"""
+    pool = never_jscore.ContextPool(JS_CODE, size=1)
+    blocker = occupy(pool)
+    threads = submit_in_order(pool, [
+        ("a1", {"tenant": "a"}),
+        ("a2", {"tenant": "a"}),
+        ("a3", {"tenant": "a"}),
+        ("b1", {"tenant": "b"}),
+        ("c1", {"tenant": "c"}),
+        ("b2", {"tenant": "b"}),
+    ])
+    for thread in [blocker, *threads]:
+        thread.join()
+    assert pool.evaluate("order") == ["a1", "b1", "c1", "a2", "b2", "a3"], pool.evaluate("order")
+    pool.close()
+    print("[OK] 按租户轮转")
+
+
+def test_max_queue_raise():
+    """测试队列满时 on_full="raise" 抛出 PoolFull"""
+    pool = never_jscore.ContextPool(JS_CODE, size=1, max_queue=2, on_full="raise")
+    blocker = occupy(pool)
+    threads = submit_in_order(pool, [("q1", {}), ("q2", {})])
+    try:
+        pool.call("record", ["rejected"])
+        assert False, "应该抛出 PoolFull"
+    except never_jscore.PoolFull as e:
+        assert "max_queue=2" in str(e), e
+    try:
+        pool.map("record", ["m1", "m2"])
+        assert False, "应该抛出 PoolFull"
+    except never_jscore.PoolFull:
+        pass
+    for thread in [blocker, *threads]:
+        thread.join()
+    assert pool.evaluate("order") == ["q1", "q2"]
+    pool.close()
+    print("[OK] max_queue + on_full='raise'")
+
+
+def test_max_queue_block():
+    """测试队列满时 on_full="block" 等待空位"""
+    pool = never_jscore.ContextPool(JS_CODE, size=1, max_queue=1)
+    blocker = occupy(pool)
+    threads = submit_in_order(pool, [("q1", {})])
+    start = time.time()
+    assert pool.call("record", ["waited"]) == "waited"
+    assert time.time() - start > 0.1
+    for thread in [blocker, *threads]:
+        thread.join()
+    assert pool.evaluate("order") == ["q1", "waited"]
+    assert pool.map("record", ["m1", "m2", "m3"]) == ["m1", "m2", "m3"]
+    pool.close()
+    print("[OK] max_queue + on_full='block'")
+
+
+def test_async_never_blocks():
+    """测试 call_async() 在队列满时直接抛出 PoolFull（不阻塞事件循环）"""
+    pool = never_jscore.ContextPool(JS_CODE, size=1, max_queue=1)
+
+    async def main():
+        blocker = pool.call_async("spin", [300])
+        await asyncio.sleep(0.1)
+        queued = pool.call_async("record", ["q1"], priority=1, tenant="x")
+        try:
+            pool.call_async("record", ["rejected"])
+            assert False, "应该抛出 PoolFull"
+        except never_jscore.PoolFull:
+            pass
+        return await asyncio.gather(blocker, queued)
+
+    assert asyncio.run(main()) == [300, "q1"]
+    pool.close()
+    print("[OK] asyncio 调用不阻塞")
+
+
+def test_invalid_arguments():
+    """测试无效的 max_queue / on_full"""
+    for kwargs in [{"max_queue": 0}, {"on_full": "drop"}]:
+        try:
+            never_jscore.ContextPool(JS_CODE, size=1, **kwargs)
+            assert False, f"应该抛出 ValueError: {kwargs}"
+        except ValueError:
+            pass
+    print("[OK] 无效参数")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 ContextPool 调度")
+    print("=" * 60)
+
+    test_priority()
+    test_tenant_fairness()
+    test_max_queue_raise()
+    test_max_queue_block()
+    test_async_never_blocks()
+    test_invalid_arguments()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 ContextPool 调度测试通过！")
+    print("=" * 60)
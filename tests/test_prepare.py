@@ -0,0 +1,121 @@
+"""
+测试 prepare()：预编译带 ? 占位符的调用表达式，返回可重复调用的 PreparedCall
+"""
+
+import pickle
+
+import never_jscore
+
+JS_CODE = """
+function hash(s, opts) { return opts ? s.length + opts.salt : s.length; }
+function echo(...args) { return args; }
+async function later(x) { await null; return x * 2; }
+"""
+
+
+def test_basic():
+    """测试占位符按位置替换为转换后的参数"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    stmt = ctx.prepare("hash(?)")
+    assert [stmt(item) for item in ["a", "bb", "ccc"]] == [1, 2, 3]
+    assert stmt.template == "hash(?)"
+    assert stmt.param_count == 1
+    assert repr(stmt) == "PreparedCall(\"hash(?)\")"
+
+    salted = ctx.prepare("hash(?, { salt: ? })")
+    assert salted("abc", "!") == "3!"
+    assert ctx.prepare("echo()")() == []
+    assert ctx.prepare("later(?)")(21) == 42
+    del ctx
+    print("[OK] 基本用法")
+
+
+def test_no_injection():
+    """测试参数不拼进代码：引号、换行和代码片段按值传递"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    stmt = ctx.prepare("echo(?)")
+    for value in ["it's \"quoted\"", "a\nb", "'); globalThis.pwned = 1; ('", {"nested": [1, None, True]}]:
+        assert stmt(value) == [value]
+    assert ctx.evaluate("typeof pwned") == "undefined"
+    del ctx
+    print("[OK] 参数不拼进代码")
+
+
+def test_placeholder_rules():
+    """测试 ?.、??、三元运算符和字符串中的 ? 不是占位符"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    cases = [
+        ("? ?? 'default'", [None], "default"),
+        ("(?)?.length ?? -1", [None], -1),
+        ("? ? 'yes' : ?", [True, "no"], "yes"),
+        ("? ? 'yes' : ?", [False, "no"], "no"),
+        ("'what?' + ?", ["!"], "what?!"),
+        ("typeof ?", [1], "number"),
+        ("[?, ?].map(x => x ? .5 : 0)", [0, 1], [0, 0.5]),
+    ]
+    for template, args, expected in cases:
+        stmt = ctx.prepare(template)
+        assert stmt.param_count == len(args), (template, stmt.param_count)
+        assert stmt(*args) == expected, (template, stmt(*args))
+    del ctx
+    print("[OK] 占位符规则")
+
+
+def test_errors():
+    """测试参数个数不符和语法错误"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    stmt = ctx.prepare("hash(?)")
+    try:
+        stmt("a", "b")
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "takes 1 argument(s) but 2 were given" in str(e), e
+    try:
+        ctx.prepare("hash(?); hash(?)")
+        assert False, "应该抛出 SyntaxError"
+    except Exception as e:
+        assert "Prepare error" in str(e) and "SyntaxError" in str(e), e
+    try:
+        ctx.prepare("missing(?)")(1)
+        assert False, "应该抛出 ReferenceError"
+    except Exception as e:
+        assert "missing is not defined" in str(e), e
+    del ctx
+    print("[OK] 错误处理")
+
+
+def test_survives_rebuild():
+    """测试预编译的函数在 pickle 和 recycle_after 重建后仍然可用"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    ctx.prepare("hash(?)")
+    restored = pickle.loads(pickle.dumps(ctx))
+    assert restored.prepare("hash(?)")("abcd") == 4
+
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE, recycle_after={"calls": 2})
+    stmt = ctx.prepare("hash(?)")
+    assert [stmt("x" * n) for n in range(1, 6)] == [1, 2, 3, 4, 5]
+    assert "__neverjscore_prepared__" not in ctx.evaluate("Object.keys(globalThis)")
+    del ctx, restored
+    print("[OK] 重建后仍然可用")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 prepare()")
+    print("=" * 60)
+
+    test_basic()
+    test_no_injection()
+    test_placeholder_rules()
+    test_errors()
+    test_survives_rebuild()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 prepare() 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,136 @@
+"""
+测试 compile_project()：按依赖顺序加载目录中的脚本
+
+依赖来自 never_jscore.json 清单，或代码中的 require / import / // @requires
+"""
+
+import json
+import os
+import tempfile
+
+import never_jscore
+
+
+def _write(root, files):
+    """在 root 下创建文件（{相对路径: 内容}）"""
+    for name, code in files.items():
+        path = os.path.join(root, name)
+        os.makedirs(os.path.dirname(path), exist_ok=True)
+        with open(path, "w", encoding="utf-8") as f:
+            f.write(code)
+
+
+def _names(root, paths):
+    return [os.path.relpath(p, os.path.realpath(root)).replace(os.sep, "/") for p in paths]
+
+
+PROJECT = {
+    # 文件名的字母顺序与依赖顺序相反
+    "a_main.js": "// @requires ./lib/b_crypto.js\nfunction sign(x) { return PREFIX + hash(x); }\n",
+    "lib/b_crypto.js": "if (typeof require === 'undefined') { require('../c_env'); }\n"
+                       "function hash(x) { return ENV + ':' + btoa(x); }\n",
+    "c_env.js": "var ENV = 'env'; var PREFIX = '>';\n",
+    "node_modules/ignored.js": "throw new Error('node_modules should be skipped');\n",
+}
+
+
+def test_dependency_order():
+    """测试按 @requires / require 计算加载顺序"""
+    with tempfile.TemporaryDirectory() as root:
+        _write(root, PROJECT)
+        ctx = never_jscore.Context()
+        loaded = ctx.compile_project(root)
+        assert _names(root, loaded) == ["c_env.js", "lib/b_crypto.js", "a_main.js"], loaded
+        assert ctx.call("sign", ["hi"]) == ">env:aGk="
+        del ctx
+    print("[OK] 依赖顺序")
+
+
+def test_entry():
+    """测试 entry 只加载入口及其依赖"""
+    with tempfile.TemporaryDirectory() as root:
+        _write(root, dict(PROJECT, **{"extra.js": "var EXTRA = 1;"}))
+        ctx = never_jscore.compile_project(root, entry="lib/b_crypto.js")
+        assert ctx.call("hash", ["hi"]) == "env:aGk="
+        assert ctx.evaluate("typeof sign") == "undefined"
+        assert ctx.evaluate("typeof EXTRA") == "undefined"
+        del ctx
+    print("[OK] entry")
+
+
+def test_imports():
+    """测试 import 识别为依赖"""
+    with tempfile.TemporaryDirectory() as root:
+        _write(root, {
+            "1.js": "function lazy() { return import('./2.js'); }\nvar one = two + 1;\n",
+            "2.js": "var two = 2;\n",
+        })
+        ctx = never_jscore.Context()
+        loaded = ctx.compile_project(root)
+        assert _names(root, loaded) == ["2.js", "1.js"], loaded
+        assert ctx.evaluate("one") == 3
+        del ctx
+    print("[OK] import")
+
+
+def test_manifest():
+    """测试 never_jscore.json 指定顺序"""
+    with tempfile.TemporaryDirectory() as root:
+        _write(root, {
+            "z.js": "var order = ['z'];",
+            "y.js": "order.push('y');",
+            "x.js": "order.push('x');",
+            "never_jscore.json": json.dumps({"files": ["z.js", "y.js", "x.js"]}),
+        })
+        ctx = never_jscore.compile_project(root)
+        assert ctx.evaluate("order") == ["z", "y", "x"]
+        del ctx
+
+        ctx = never_jscore.compile_project(root, entry="y.js")
+        assert ctx.evaluate("order") == ["z", "y"]
+        del ctx
+    print("[OK] never_jscore.json")
+
+
+def test_errors():
+    """测试循环依赖、@requires 缺失文件和执行错误"""
+    with tempfile.TemporaryDirectory() as root:
+        _write(root, {"a.js": "require('./b')", "b.js": "require('./a.js')"})
+        try:
+            never_jscore.compile_project(root)
+            assert False, "应该抛出 ValueError"
+        except ValueError as e:
+            assert "Circular dependency" in str(e), e
+
+    with tempfile.TemporaryDirectory() as root:
+        _write(root, {"a.js": "// @requires ./missing.js\n"})
+        try:
+            never_jscore.compile_project(root)
+            assert False, "应该抛出 ValueError"
+        except ValueError as e:
+            assert "missing.js" in str(e), e
+
+    with tempfile.TemporaryDirectory() as root:
+        _write(root, {"broken.js": "function ("})
+        try:
+            never_jscore.compile_project(root)
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "broken.js" in str(e), e
+    print("[OK] 错误")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 compile_project()")
+    print("=" * 60)
+
+    test_dependency_order()
+    test_entry()
+    test_imports()
+    test_manifest()
+    test_errors()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 compile_project 测试通过！")
+    print("=" * 60)
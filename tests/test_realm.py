@@ -0,0 +1,188 @@
+"""
+测试 Realm：共享 isolate 的轻量级执行环境
+
+展示 Context.create_realm() 的用法：每个请求一个干净的全局作用域，而不必创建新的 isolate
+"""
+
+import time
+
+import never_jscore
+
+
+def test_realm_basic():
+    """测试 Realm 的基本执行方法"""
+    ctx = never_jscore.Context()
+    realm = ctx.create_realm()
+
+    realm.compile("function add(a, b) { return a + b; }")
+    assert realm.call("add", [1, 2]) == 3
+    assert realm.evaluate("[1, 2, 3].map(x => x * 2)") == [2, 4, 6]
+    assert realm.evaluate("({a: 1, b: 'x'})") == {"a": 1, "b": "x"}
+    assert realm.evaluate("undefined") is None
+
+    assert realm.eval("var counter = 10;") is None
+    assert realm.eval("counter + 1", return_value=True) == 11
+
+    # evaluate 中的 let/const 不泄漏到全局
+    realm.evaluate("let local = 1; local")
+    assert realm.evaluate("typeof local") == "undefined"
+
+    realm.close()
+    del ctx
+    print("[OK] Realm 基本执行")
+
+
+def test_realm_isolation():
+    """测试 Realm 之间、Realm 与 Context 之间互相隔离"""
+    ctx = never_jscore.Context()
+    ctx.compile("var owner = 'context';")
+
+    realm1 = ctx.create_realm()
+    realm2 = ctx.create_realm()
+    realm1.compile("var owner = 'realm1';")
+
+    assert realm1.evaluate("owner") == "realm1"
+    assert realm2.evaluate("typeof owner") == "undefined"
+    assert ctx.evaluate("owner") == "context"
+
+    # 修改内置对象只影响当前 Realm
+    realm1.compile("Array.prototype.polluted = true;")
+    assert realm2.evaluate("[].polluted === undefined") is True
+    assert ctx.evaluate("[].polluted === undefined") is True
+
+    realm1.close()
+    realm2.close()
+    del ctx
+    print("[OK] Realm 全局作用域互相隔离")
+
+
+def test_realm_builtins_only():
+    """测试 Realm 只有 ECMAScript 内置对象"""
+    ctx = never_jscore.Context()
+    with ctx.create_realm() as realm:
+        assert realm.evaluate("typeof JSON.stringify") == "function"
+        assert realm.evaluate("typeof btoa") == "undefined"
+        assert realm.evaluate("typeof setTimeout") == "undefined"
+    del ctx
+    print("[OK] Realm 只包含 ECMAScript 内置对象")
+
+
+def test_realm_promises():
+    """测试 Realm 中立即完成的 Promise 可以等待，挂起的 Promise 报错"""
+    ctx = never_jscore.Context()
+    with ctx.create_realm() as realm:
+        assert realm.evaluate("Promise.resolve(42)") == 42
+        assert realm.evaluate("(async () => { await null; return 'done'; })()") == "done"
+
+        try:
+            realm.evaluate("Promise.reject(new Error('boom'))")
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "boom" in str(e)
+
+        try:
+            realm.evaluate("new Promise(() => {})")
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "pending" in str(e)
+
+        assert realm.evaluate("Promise.resolve(1)", auto_await=False) == {}
+    del ctx
+    print("[OK] Realm 中的 Promise")
+
+
+def test_realm_errors():
+    """测试 Realm 中的异常不影响之后的执行"""
+    ctx = never_jscore.Context()
+    with ctx.create_realm() as realm:
+        try:
+            realm.compile("syntax error here")
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "Compile error" in str(e)
+
+        try:
+            realm.call("missingFunction", [])
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "missingFunction" in str(e)
+
+        assert realm.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] Realm 异常处理")
+
+
+def test_realm_close():
+    """测试关闭后的 Realm 不能再使用"""
+    ctx = never_jscore.Context()
+    realm = ctx.create_realm()
+    assert realm.closed is False
+
+    realm.close()
+    realm.close()  # 重复关闭是安全的
+    assert realm.closed is True
+
+    try:
+        realm.evaluate("1")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "closed" in str(e)
+
+    # Context 不受影响
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] Realm 关闭")
+
+
+def test_realm_creation_cost():
+    """测试 Realm 的创建开销远小于 Context"""
+    ctx = never_jscore.Context()
+
+    start = time.perf_counter()
+    for i in range(200):
+        with ctx.create_realm() as realm:
+            assert realm.evaluate(f"{i} * 2") == i * 2
+    elapsed = time.perf_counter() - start
+
+    ctx.gc()
+    assert ctx.evaluate("1") == 1
+    del ctx
+    print(f"[OK] 200 个 Realm 创建 + 求值 + 销毁耗时 {elapsed * 1000:.1f}ms")
+
+
+def test_realm_disallow_dynamic_code():
+    """测试 allow_dynamic_code=False 同样作用于 Realm：evaluate() 作为脚本执行，eval / new Function 被禁止"""
+    ctx = never_jscore.Context(allow_dynamic_code=False)
+    realm = ctx.create_realm()
+
+    realm.compile("function add(a, b) { return a + b; }")
+    assert realm.call("add", [1, 2]) == 3
+    assert realm.evaluate("[1, 2, 3].map(x => x * 2)") == [2, 4, 6]
+    realm.evaluate("let local = 1; local")
+    assert realm.evaluate("typeof local") == "undefined"
+
+    assert realm.evaluate("try { eval('1 + 1') } catch (e) { e.name }") == "EvalError"
+    assert realm.evaluate("try { new Function('return 1')() } catch (e) { e.name }") == "EvalError"
+
+    realm.close()
+    del ctx
+    print("[OK] Realm 继承 allow_dynamic_code=False")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 Realm")
+    print("=" * 60)
+
+    test_realm_basic()
+    test_realm_isolation()
+    test_realm_builtins_only()
+    test_realm_promises()
+    test_realm_errors()
+    test_realm_close()
+    test_realm_creation_cost()
+    test_realm_disallow_dynamic_code()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 Realm 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,142 @@
+"""
+测试 compile(code, recycle_after={...})：达到阈值时自动重建 isolate
+
+初始化代码和 register() 的函数保留，evaluate() / call() 中的副作用丢弃
+"""
+
+import sys
+
+import never_jscore
+
+BUMP = """
+var base = 'init';
+function bump() {
+    globalThis.leak = (globalThis.leak || 0) + 1;
+    return leak;
+}
+"""
+
+
+def test_recycle_by_calls():
+    """测试达到 calls 阈值后重建，初始化代码保留"""
+    ctx = never_jscore.Context()
+    ctx.compile(BUMP, recycle_after={"calls": 5})
+    values = [ctx.call("bump", []) for _ in range(20)]
+    assert values[0] == 1, values
+    assert max(values) <= 5, values
+    assert 1 in values[1:], values  # 重建后副作用被丢弃
+    assert ctx.evaluate("base") == "init"
+
+    # 调用统计在重建之间累计
+    assert ctx.get_stats()[0] >= 21
+    del ctx
+    print("[OK] 按调用次数回收")
+
+
+def test_recycle_by_heap():
+    """测试达到 heap_mb 阈值后重建"""
+    ctx = never_jscore.Context()
+    ctx.compile("var kept = 1;", recycle_after={"heap_mb": 1})
+    ctx.evaluate("globalThis.blob = new Array(100000).fill('x').join(''); 1")
+    assert ctx.evaluate("typeof blob") == "undefined"
+    assert ctx.evaluate("kept") == 1
+    del ctx
+    print("[OK] 按堆大小回收")
+
+
+def test_registered_functions():
+    """测试 register() 的函数和之后编译的脚本按原顺序重建"""
+    ctx = never_jscore.Context()
+    ctx.register("pyAdd", lambda a, b: a + b)
+    ctx.compile("var offset = pyAdd(10, 5);", recycle_after={"calls": 3})
+    ctx.compile("function calc(x) { return pyAdd(x, offset); }")
+    for i in range(10):
+        assert ctx.call("calc", [i]) == i + 15
+    del ctx
+    print("[OK] register() 的函数保留")
+
+
+def test_rebuild_failure():
+    """测试重建失败时继续使用当前 isolate，错误交给 sys.unraisablehook"""
+    calls = []
+
+    def init():
+        calls.append(1)
+        if len(calls) > 1:
+            raise RuntimeError("config service down")
+        return "token"
+
+    reported = []
+    old_hook = sys.unraisablehook
+    sys.unraisablehook = reported.append
+    try:
+        ctx = never_jscore.Context()
+        ctx.register("pyInit", init)
+        ctx.compile("var token = pyInit();", recycle_after={"calls": 2})
+        for _ in range(3):
+            assert ctx.evaluate("token") == "token"
+        assert reported, "应该报告重建失败"
+        assert "Recycle error" in str(reported[0].exc_value), reported[0].exc_value
+        del ctx
+    finally:
+        sys.unraisablehook = old_hook
+    print("[OK] 重建失败")
+
+
+def test_invalid_and_blocked():
+    """测试无效的阈值和无法重建的状态"""
+    ctx = never_jscore.Context()
+    for bad in [{}, {"calls": 0}, {"heap_mb": -1}, {"calls": "10"}, {"minutes": 5}]:
+        try:
+            ctx.compile("1", recycle_after=bad)
+            assert False, f"应该抛出 ValueError: {bad}"
+        except ValueError:
+            pass
+
+    ctx.compile("var x = 1;", recycle_after={"calls": 100})
+    try:
+        ctx.create_realm()
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "recycle_after" in str(e), e
+    del ctx
+
+    ctx = never_jscore.Context()
+    realm = ctx.create_realm()
+    try:
+        ctx.compile("var x = 1;", recycle_after={"calls": 100})
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "realms" in str(e), e
+    realm.close()
+    del ctx
+    print("[OK] 无效阈值和无法重建的状态")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 在每个任务之后检查阈值"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile(BUMP, recycle_after={"calls": 4})
+    values = [ctx.call("bump", []) for _ in range(12)]
+    assert max(values) <= 4, values
+    assert 1 in values[1:], values
+    assert ctx.evaluate("base") == "init"
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 recycle_after 自动回收")
+    print("=" * 60)
+
+    test_recycle_by_calls()
+    test_recycle_by_heap()
+    test_registered_functions()
+    test_rebuild_failure()
+    test_invalid_and_blocked()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 recycle_after 测试通过！")
+    print("=" * 60)
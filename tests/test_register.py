@@ -0,0 +1,163 @@
+"""
+测试从 JS 调用 Python：ctx.register() 和 ctx.capture_stack()
+
+展示如何把 JS 中的关键调用转发给 Python，并找出是哪个（混淆过的）函数触发了回调
+"""
+
+import never_jscore
+
+
+def test_register_basic():
+    """测试参数和返回值的转换"""
+    ctx = never_jscore.Context()
+    calls = []
+
+    def py_add(a, b):
+        calls.append((a, b))
+        return a + b
+
+    ctx.register("pyAdd", py_add)
+    assert ctx.evaluate("pyAdd(1, 2)") == 3
+    assert ctx.evaluate("pyAdd('a', 'b')") == "ab"
+    assert calls == [(1, 2), ("a", "b")]
+
+    ctx.register("pyEcho", lambda *args: {"args": list(args)})
+    assert ctx.evaluate("pyEcho([1, {x: true}], null).args") == [[1, {"x": True}], None]
+    assert ctx.evaluate("typeof pyEcho") == "function"
+    del ctx
+    print("[OK] register() 参数和返回值")
+
+
+def test_register_errors():
+    """测试 Python 异常在 JS 中可以被捕获"""
+    ctx = never_jscore.Context()
+
+    def fail():
+        raise ValueError("bad input")
+
+    ctx.register("pyFail", fail)
+    message = ctx.evaluate("try { pyFail(); 'no error' } catch (e) { e.message }")
+    assert "bad input" in message and "pyFail" in message, message
+
+    try:
+        ctx.register("notCallable", 42)
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+    del ctx
+    print("[OK] register() 异常处理")
+
+
+def test_capture_stack():
+    """测试在回调中获取 JS 调用栈"""
+    ctx = never_jscore.Context()
+    stacks = []
+
+    def hook(value):
+        stacks.append(ctx.capture_stack())
+        return value
+
+    ctx.register("pyHook", hook)
+    ctx.compile("""
+        function _0x3f2a(x) { return pyHook(x); }
+        function _0xa91c(x) { return _0x3f2a(x) + '!'; }
+    """)
+    assert ctx.call("_0xa91c", ["data"]) == "data!"
+
+    frames = stacks[0]
+    assert [f["function"] for f in frames[:2]] == ["_0x3f2a", "_0xa91c"], frames
+    assert frames[0]["line"] == 2
+    assert frames[0]["column"] > 0
+    assert set(frames[0]) == {"function", "script", "line", "column", "is_eval", "is_constructor"}
+
+    try:
+        ctx.capture_stack()
+        assert False, "应该抛出 RuntimeError"
+    except RuntimeError:
+        pass
+    del ctx
+    print("[OK] capture_stack()")
+
+
+def test_register_without_extensions():
+    """测试纯 V8 环境和 ThreadedContext"""
+    ctx = never_jscore.Context(enable_extensions=False)
+    ctx.register("pyDouble", lambda x: x * 2)
+    assert ctx.evaluate("pyDouble(21)") == 42
+    del ctx
+
+    tctx = never_jscore.ThreadedContext()
+    names = []
+
+    def hook():
+        names.append(tctx.capture_stack()[0]["function"])
+        return "ok"
+
+    tctx.register("pyHook", hook)
+    tctx.compile("function caller() { return pyHook(); }")
+    assert tctx.call("caller", []) == "ok"
+    assert names == ["caller"]
+    tctx.close()
+    print("[OK] enable_extensions=False 和 ThreadedContext")
+
+
+def test_nested_call():
+    """测试回调中再次调用同一个 Context 抛出 RuntimeError，Context 仍可继续使用"""
+    ctx = never_jscore.Context()
+    errors = []
+
+    def reenter(method):
+        try:
+            if method == "evaluate":
+                ctx.evaluate("1 + 1")
+            elif method == "call":
+                ctx.call("double", [1])
+            else:
+                ctx.compile("var late = 1;")
+        except RuntimeError as e:
+            errors.append(str(e))
+            raise
+        return "unreachable"
+
+    ctx.register("pyReenter", reenter)
+    ctx.compile("function double(x) { return x * 2; }")
+    for method in ["evaluate", "call", "compile"]:
+        message = ctx.evaluate(f"try {{ pyReenter('{method}') }} catch (e) {{ e.message }}")
+        assert "register() callback" in message, message
+    assert len(errors) == 3 and all("already executing" in e for e in errors), errors
+    assert ctx.call("double", [21]) == 42
+    assert ctx.evaluate("typeof late") == "undefined"
+    del ctx
+
+    tctx = never_jscore.ThreadedContext()
+    thread_errors = []
+
+    def reenter_threaded():
+        try:
+            tctx.evaluate("1")
+        except RuntimeError as e:
+            thread_errors.append(str(e))
+        return "done"
+
+    tctx.register("pyReenter", reenter_threaded)
+    assert tctx.evaluate("pyReenter()") == "done"
+    assert len(thread_errors) == 1 and "already executing" in thread_errors[0], thread_errors
+    assert tctx.evaluate("1 + 2") == 3
+    tctx.close()
+    print("[OK] 回调中嵌套调用抛出 RuntimeError")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试从 JS 调用 Python")
+    print("=" * 60)
+
+    test_register_basic()
+    test_register_errors()
+    test_capture_stack()
+    test_register_without_extensions()
+    test_nested_call()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 register / capture_stack 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,95 @@
+"""
+测试 render_call()：命名占位符的值序列化为 JSON 字面量后替换
+
+代替 f-string 拼接调用代码，参数中的引号、换行和反斜杠不会破坏代码，也无法注入代码
+"""
+
+import never_jscore
+
+
+def test_render_call():
+    """测试占位符替换和各种类型的值"""
+    ctx = never_jscore.Context()
+    ctx.compile("function sign(ts, payload) { return ts + ':' + payload.q; }")
+    assert ctx.render_call("sign({ts}, {payload})", ts=1700000000, payload={"q": "it's \"quoted\""}) == '1700000000:it\'s "quoted"'
+
+    # 字符串中的换行、反斜杠和 </script> 原样传入
+    tricky = "a'b\"c\nd\\e</script> "
+    assert ctx.render_call("{s}", s=tricky) == tricky
+    assert ctx.render_call("[{a}, {b}, {c}, {d}]", a=None, b=True, c=1.5, d=[1, "x"]) == [None, True, 1.5, [1, "x"]]
+
+    # 对象值在语句开头也作为表达式解析
+    assert ctx.render_call("{obj}", obj={"k": [1, 2]}) == {"k": [1, 2]}
+
+    # 同一个占位符可以出现多次；Promise 自动等待
+    assert ctx.render_call("Promise.resolve({n} + {n})", n=21) == 42
+    del ctx
+    print("[OK] 占位符替换")
+
+
+def test_injection_is_data():
+    """测试注入尝试只作为字符串数据"""
+    ctx = never_jscore.Context()
+    ctx.compile("globalThis.pwned = false; function echo(x) { return x; }")
+    payload = "'); globalThis.pwned = true; ('"
+    assert ctx.render_call("echo({x})", x=payload) == payload
+    assert ctx.evaluate("pwned") is False
+    del ctx
+    print("[OK] 注入尝试只作为数据")
+
+
+def test_braces():
+    """测试 {{名称}} 转义和其他花括号"""
+    ctx = never_jscore.Context()
+    assert ctx.render_call("(({{x}}) => x)({obj})", obj={"x": 1}) == 1
+    assert ctx.render_call("(() => { const o = {a: {v}}; return o.a; })()", v=5) == 5
+    assert ctx.render_call("({ 'k': 1 })") == {"k": 1}
+    del ctx
+    print("[OK] 花括号处理")
+
+
+def test_errors():
+    """测试缺少值、多余的值和无法转换的值"""
+    ctx = never_jscore.Context()
+    try:
+        ctx.render_call("sign({ts}, {payload})", ts=1)
+        assert False, "应该抛出 KeyError"
+    except KeyError as e:
+        assert "payload" in str(e), e
+    try:
+        ctx.render_call("sign({ts})", ts=1, tss=2)
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "tss" in str(e), e
+    try:
+        ctx.render_call("{x}", x=object())
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Unsupported Python type" in str(e), e
+    del ctx
+    print("[OK] 错误处理")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 同样支持 render_call()"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("function add(a, b) { return a + b; }")
+    assert ctx.render_call("add({a}, {b})", a="x'", b="y") == "x'y"
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 render_call()")
+    print("=" * 60)
+
+    test_render_call()
+    test_injection_is_data()
+    test_braces()
+    test_errors()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 render_call() 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,104 @@
+"""
+测试 repl_eval()：像浏览器控制台一样逐行执行
+
+输入作为全局脚本执行：返回完成值，let/const 保留到后续输入，结果赋给全局 _
+"""
+
+import copy
+
+import never_jscore
+
+
+def test_completion_value():
+    """测试返回语句的完成值"""
+    ctx = never_jscore.Context()
+    assert ctx.repl_eval("1 + 2") == 3
+    assert ctx.repl_eval("let x = 1; x + 1") == 2
+    assert ctx.repl_eval("if (x > 0) { 'pos' } else { 'neg' }") == "pos"
+    assert ctx.repl_eval("for (var i = 0; i < 3; i++) { i * 10 }") == 20
+    assert ctx.repl_eval("var y = 5") is None
+    del ctx
+    print("[OK] 完成值")
+
+
+def test_declarations_persist():
+    """测试 let/const/class 声明在后续输入中可见"""
+    ctx = never_jscore.Context()
+    ctx.repl_eval("let a = 20")
+    ctx.repl_eval("const b = 22")
+    ctx.repl_eval("class Point { constructor(x) { this.x = x; } }")
+    assert ctx.repl_eval("a + b") == 42
+    assert ctx.repl_eval("new Point(a).x") == 20
+    # 与 call() / evaluate() 共享全局作用域
+    assert ctx.evaluate("a * 2") == 40
+
+    # 与控制台相同，重复声明抛出 SyntaxError
+    try:
+        ctx.repl_eval("let a = 1")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "SyntaxError" in str(e) or "already been declared" in str(e), e
+    assert ctx.repl_eval("a") == 20
+    del ctx
+    print("[OK] let/const/class 声明保留")
+
+
+def test_underscore():
+    """测试上一次的结果赋给 _"""
+    ctx = never_jscore.Context()
+    ctx.repl_eval("6 * 7")
+    assert ctx.repl_eval("_") == 42
+    assert ctx.repl_eval("_ * 2") == 84
+    assert ctx.repl_eval("_") == 84
+
+    # 自动等待 Promise 时 _ 为 Promise 的结果
+    assert ctx.repl_eval("Promise.resolve('done')") == "done"
+    assert ctx.repl_eval("_") == "done"
+
+    # 用户自己定义了 _ 之后不再覆盖
+    ctx.repl_eval("globalThis._ = 'mine'")
+    ctx.repl_eval("1 + 1")
+    assert ctx.repl_eval("_") == "mine"
+    del ctx
+    print("[OK] _ 为上一次的结果")
+
+
+def test_no_dynamic_code():
+    """测试 allow_dynamic_code=False 时同样可用"""
+    ctx = never_jscore.Context(allow_dynamic_code=False)
+    ctx.repl_eval("let n = 3")
+    assert ctx.repl_eval("n * n") == 9
+    assert ctx.repl_eval("_") == 9
+    del ctx
+    print("[OK] 禁止动态代码时可用")
+
+
+def test_threaded_and_pickle():
+    """测试 ThreadedContext 和 pickle 重放"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.repl_eval("let total = 10")
+    assert ctx.repl_eval("total + 5") == 15
+    ctx.close()
+
+    ctx = never_jscore.Context()
+    ctx.repl_eval("let base = 100")
+    clone = copy.deepcopy(ctx)
+    assert clone.repl_eval("base + 1") == 101
+    del ctx, clone
+    print("[OK] ThreadedContext 和 pickle")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 repl_eval()")
+    print("=" * 60)
+
+    test_completion_value()
+    test_declarations_persist()
+    test_underscore()
+    test_no_dynamic_code()
+    test_threaded_and_pickle()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 repl_eval 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,153 @@
+"""
+测试 start_recording() / start_replay(trace)：记录脚本消耗的不确定输入，在另一个 Context 中精确重放
+"""
+
+import json
+
+import never_jscore
+
+JS_CODE = """
+function sign(data) {
+    const bytes = new Uint8Array(4);
+    crypto.getRandomValues(bytes);
+    return [data, Math.random(), Date.now(), new Date().getTime(), Array.from(bytes), crypto.randomUUID(), pySalt(data)];
+}
+function twoRandoms() { return [Math.random(), Math.random()]; }
+function onlyTime() { return Date.now(); }
+"""
+
+salt_calls = []
+
+
+def py_salt(data):
+    salt_calls.append(data)
+    return "salt-" + str(len(salt_calls))
+
+
+def make_context():
+    ctx = never_jscore.Context()
+    ctx.register("pySalt", py_salt)
+    ctx.compile(JS_CODE)
+    return ctx
+
+
+def test_record_and_replay():
+    """测试重放得到与记录时相同的结果，且不调用 Python 函数"""
+    ctx = make_context()
+    ctx.start_recording()
+    recorded = ctx.call("sign", ["a"])
+    trace = ctx.stop_recording()
+    kinds = [kind for kind, _ in trace]
+    assert kinds == ["bytes", "random", "now", "now", "uuid", "call"], kinds
+    assert trace[-1][1] == {"name": "pySalt", "value": recorded[-1]}
+
+    # trace 可以用 json 保存
+    trace = json.loads(json.dumps(trace))
+    calls_before = len(salt_calls)
+    replayer = make_context()
+    replayer.start_replay(trace)
+    assert replayer.call("sign", ["a"]) == recorded
+    assert replayer.stop_replay() == 0
+    assert len(salt_calls) == calls_before
+
+    # 停止后恢复为真实的输入
+    assert replayer.call("twoRandoms", [])[0] != replayer.call("twoRandoms", [])[0]
+    del ctx, replayer
+    print("[OK] 记录并重放")
+
+
+def test_python_error_replayed():
+    """测试 Python 函数的异常同样被记录和重放"""
+    ctx = never_jscore.Context()
+
+    def fail(x):
+        raise ValueError("boom")
+
+    ctx.register("pyFail", fail)
+    ctx.compile("function tryFail() { try { pyFail(1); } catch (e) { return e.message; } }")
+    ctx.start_recording()
+    message = ctx.call("tryFail", [])
+    trace = ctx.stop_recording()
+    assert "boom" in message, message
+
+    ctx.start_replay(trace)
+    assert ctx.call("tryFail", []) == message
+    assert ctx.stop_replay() == 0
+    del ctx
+    print("[OK] Python 函数的异常")
+
+
+def test_divergence():
+    """测试脚本请求的输入与 trace 不一致时抛出 replay diverged"""
+    ctx = make_context()
+    ctx.start_recording()
+    ctx.call("onlyTime", [])
+    trace = ctx.stop_recording()
+
+    ctx.start_replay(trace)
+    try:
+        ctx.call("twoRandoms", [])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "replay diverged" in str(e), e
+    ctx.stop_replay()
+
+    ctx.start_replay(trace)
+    ctx.call("onlyTime", [])
+    try:
+        ctx.call("onlyTime", [])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "after the trace ended" in str(e), e
+    ctx.stop_replay()
+
+    ctx.start_replay([["call", {"name": "other", "value": 1}]])
+    try:
+        ctx.call("sign", ["a"])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "replay diverged" in str(e), e
+    ctx.stop_replay()
+    del ctx
+    print("[OK] 不一致时报错")
+
+
+def test_remaining_and_state_errors():
+    """测试 stop_replay() 返回未使用的项数，重复开始 / 未开始时报错"""
+    ctx = make_context()
+    ctx.start_replay([["random", 0.25], ["random", 0.5], ["now", 0]])
+    assert ctx.call("twoRandoms", []) == [0.25, 0.5]
+    assert ctx.stop_replay() == 1
+
+    ctx.start_recording()
+    for start in (ctx.start_recording, lambda: ctx.start_replay([])):
+        try:
+            start()
+            assert False, "应该抛出异常"
+        except RuntimeError as e:
+            assert "already recording" in str(e), e
+    assert ctx.stop_recording() == []
+
+    for stop in (ctx.stop_recording, ctx.stop_replay):
+        try:
+            stop()
+            assert False, "应该抛出异常"
+        except RuntimeError as e:
+            assert "not " in str(e), e
+    del ctx
+    print("[OK] 未使用的项数与状态错误")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 start_recording() / start_replay()")
+    print("=" * 60)
+
+    test_record_and_replay()
+    test_python_error_replayed()
+    test_divergence()
+    test_remaining_and_state_errors()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有记录 / 重放测试通过！")
+    print("=" * 60)
@@ -0,0 +1,107 @@
+"""
+测试 compile(code, result_filter="...")：每次调用的结果先交给指定的 JS 函数再序列化
+
+在沙箱内统一整理输出（去掉 DOM 节点、截断大缓冲区等）
+"""
+
+import io
+
+import never_jscore
+
+SANITIZE = """
+function __sanitize(value) {
+    if (value && value.nodeName) return value.nodeName;
+    if (value instanceof Uint8Array && value.length > 4) return { truncated: value.length };
+    return value;
+}
+function sign(s) { return new Uint8Array(s.length * 2); }
+"""
+
+
+def test_filter_results():
+    """测试 evaluate / call / eval / render_call / repl_eval 的结果经过过滤"""
+    ctx = never_jscore.Context()
+    ctx.compile(SANITIZE, result_filter="__sanitize")
+    assert ctx.evaluate("document.createElement('div')") == "DIV"
+    assert ctx.call("sign", ["abcdef"]) == {"truncated": 12}
+    assert ctx.call("sign", ["ab"]) == {"0": 0, "1": 0, "2": 0, "3": 0}
+    assert ctx.eval("new Uint8Array(10)", return_value=True) == {"truncated": 10}
+    assert ctx.render_call("sign({s})", s="xyz") == {"truncated": 6}
+    assert ctx.repl_eval("new Uint8Array(8)") == {"truncated": 8}
+    assert ctx.evaluate("Promise.resolve(new Uint8Array(5))") == {"truncated": 5}
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] 结果经过过滤")
+
+
+def test_async_filter_and_stream():
+    """测试返回 Promise 的过滤函数和 stream_to 输出"""
+    ctx = never_jscore.Context()
+    ctx.compile("async function __wrap(v) { await null; return { data: v }; }", result_filter="__wrap")
+    assert ctx.evaluate("[1, 2]") == {"data": [1, 2]}
+    out = io.StringIO()
+    ctx.evaluate("'x'.repeat(10)", stream_to=out)
+    assert out.getvalue() == '{"data":"xxxxxxxxxx"}', out.getvalue()
+    del ctx
+    print("[OK] 异步过滤函数和 stream_to")
+
+
+def test_replace_and_clear():
+    """测试替换过滤函数、属性路径和清除"""
+    ctx = never_jscore.Context()
+    ctx.compile("var filters = { upper: (v) => typeof v === 'string' ? v.toUpperCase() : v };", result_filter="filters.upper")
+    assert ctx.evaluate("'abc'") == "ABC"
+    ctx.evaluate("filters.upper = (v) => 'replaced'; 1")
+    assert ctx.evaluate("'abc'") == "replaced"
+    ctx.compile("", result_filter="")
+    assert ctx.evaluate("'abc'") == "abc"
+    del ctx
+    print("[OK] 替换和清除")
+
+
+def test_errors():
+    """测试过滤函数不存在、不是函数和抛出异常"""
+    ctx = never_jscore.Context()
+    for name in ["__missing", "JSON"]:
+        try:
+            ctx.compile("var x = 1;", result_filter=name)
+            assert False, f"应该抛出 ValueError: {name}"
+        except ValueError:
+            pass
+    assert ctx.evaluate("'ok'") == "ok"
+
+    ctx.compile("function __strict(v) { if (v === 'secret') throw new Error('leak blocked'); return v; }",
+                result_filter="__strict")
+    try:
+        ctx.evaluate("'secret'")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "leak blocked" in str(e), e
+    assert ctx.evaluate("'public'") == "public"
+    del ctx
+    print("[OK] 错误处理")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 同样支持 result_filter"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile(SANITIZE, result_filter="__sanitize")
+    assert ctx.call("sign", ["abcdef"]) == {"truncated": 12}
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 compile(result_filter=...)")
+    print("=" * 60)
+
+    test_filter_results()
+    test_async_filter_and_stream()
+    test_replace_and_clear()
+    test_errors()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 result_filter 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,101 @@
+"""
+测试 result_format：结果在 Rust 中解析（"python"）、原样返回 JSON 字符串（"json_str"）或交给 orjson 解析（"orjson"）
+
+热点流水线可以跳过解析，或使用 SIMD 加速的 orjson
+"""
+
+import json
+
+import never_jscore
+
+try:
+    import orjson
+except ImportError:
+    orjson = None
+
+
+def test_json_str():
+    """测试 result_format="json_str" 返回未解析的 JSON 字符串"""
+    ctx = never_jscore.Context()
+    ctx.compile("function payload(n) { return { sign: 'abc', items: [n, n + 1], ok: true }; }")
+    text = ctx.call("payload", [1], result_format="json_str")
+    assert isinstance(text, str), text
+    assert json.loads(text) == {"sign": "abc", "items": [1, 2], "ok": True}
+    assert ctx.evaluate("'a\"b'", result_format="json_str") == '"a\\"b"'
+    assert ctx.evaluate("undefined", result_format="json_str") == "null"
+    assert ctx.evaluate("Promise.resolve([1, 2])", result_format="json_str") == "[1,2]"
+    assert ctx.call("payload", [1], result_format="python") == ctx.call("payload", [1])
+    del ctx
+    print("[OK] json_str")
+
+
+def test_orjson():
+    """测试 result_format="orjson"（没有安装 orjson 时抛出 ImportError）"""
+    ctx = never_jscore.Context()
+    ctx.compile("function rows(n) { return Array.from({ length: n }, (_, i) => ({ id: i, name: 'r' + i })); }")
+    if orjson is None:
+        try:
+            ctx.call("rows", [3], result_format="orjson")
+            assert False, "应该抛出 ImportError"
+        except ImportError as e:
+            assert "orjson" in str(e), e
+        print("[OK] orjson（未安装，ImportError）")
+        return
+    assert ctx.call("rows", [1000], result_format="orjson") == ctx.call("rows", [1000])
+    assert ctx.evaluate("({ a: 1.5, b: null })", result_format="orjson") == {"a": 1.5, "b": None}
+    del ctx
+    print("[OK] orjson")
+
+
+def test_invalid_combinations():
+    """测试无效的值以及与 binary / stream_to 同时使用"""
+    ctx = never_jscore.Context()
+    for kwargs in [
+        {"result_format": "yaml"},
+        {"result_format": "json_str", "binary": True},
+        {"result_format": "orjson", "stream_to": None, "binary": True},
+    ]:
+        try:
+            ctx.evaluate("1", **kwargs)
+            assert False, f"应该抛出 ValueError: {kwargs}"
+        except ValueError:
+            pass
+    import io
+    try:
+        ctx.evaluate("1", result_format="json_str", stream_to=io.StringIO())
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "stream_to" in str(e), e
+    del ctx
+    print("[OK] 无效组合")
+
+
+def test_threaded_and_pool():
+    """测试 ThreadedContext 和 ContextPool 同样支持 result_format"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("function sq(x) { return { v: x * x }; }")
+    assert ctx.call("sq", [3], result_format="json_str") == '{"v":9}'
+    assert ctx.evaluate("sq(4)", result_format="json_str") == '{"v":16}'
+    ctx.close()
+
+    pool = never_jscore.ContextPool("function sq(x) { return x * x; }", size=2)
+    assert pool.map("sq", [[i] for i in range(5)], result_format="json_str") == ["0", "1", "4", "9", "16"]
+    assert pool.call("sq", [5], result_format="json_str") == "25"
+    assert pool.evaluate("sq(6)", result_format="json_str") == "36"
+    pool.close()
+    print("[OK] ThreadedContext 和 ContextPool")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 result_format")
+    print("=" * 60)
+
+    test_json_str()
+    test_orjson()
+    test_invalid_combinations()
+    test_threaded_and_pool()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 result_format 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,142 @@
+"""
+测试 stream_to：把结果的 JSON 文本分块写入文件对象
+
+大结果不再整体复制到 Rust 并解析成 Python 对象，写入的内容与 JSON.stringify 相同
+"""
+
+import io
+import json
+
+import never_jscore
+
+
+class CountingWriter:
+    """记录每次 write() 的分块"""
+
+    def __init__(self):
+        self.chunks = []
+
+    def write(self, chunk):
+        self.chunks.append(chunk)
+        return len(chunk)
+
+
+def test_evaluate_stream():
+    """测试 evaluate(stream_to=...) 写入与 JSON.stringify 相同的内容"""
+    ctx = never_jscore.Context()
+    code = """({
+        n: 1, s: 'a"b\\n中文', list: [1, undefined, () => 1, NaN],
+        skipped: undefined, date: new Date(0), nested: { empty: [], obj: {} },
+    })"""
+    buf = io.StringIO()
+    written = ctx.evaluate(code, stream_to=buf)
+    expected = ctx.evaluate(f"JSON.stringify({code})")
+    assert buf.getvalue() == expected, buf.getvalue()
+    assert written == len(expected.encode("utf-8"))
+
+    # 原始值和 undefined
+    for code, text in [("42", "42"), ("'x'", '"x"'), ("undefined", "null"), ("Promise.resolve([true])", "[true]")]:
+        buf = io.StringIO()
+        ctx.evaluate(code, stream_to=buf)
+        assert buf.getvalue() == text, (code, buf.getvalue())
+    del ctx
+    print("[OK] evaluate(stream_to=...)")
+
+
+def test_large_result_chunks():
+    """测试大结果分多次写入"""
+    ctx = never_jscore.Context()
+    ctx.compile("""
+        function extract(n) {
+            return Array.from({ length: n }, (_, i) => ({ id: i, name: 'item' + i, tags: ['a', 'b'] }));
+        }
+    """)
+    writer = CountingWriter()
+    written = ctx.call("extract", [200000], stream_to=writer)
+    assert len(writer.chunks) > 1, len(writer.chunks)
+    text = "".join(writer.chunks)
+    assert written == len(text.encode("utf-8"))
+    data = json.loads(text)
+    assert len(data) == 200000 and data[-1] == {"id": 199999, "name": "item199999", "tags": ["a", "b"]}
+
+    # 超过一个分块的长字符串（包含代理对）
+    buf = io.StringIO()
+    ctx.evaluate("'x'.repeat(3 << 20) + '😀'", stream_to=buf)
+    assert json.loads(buf.getvalue()) == "x" * (3 << 20) + "😀"
+    del ctx
+    print("[OK] 大结果分块写入")
+
+
+def test_stream_errors():
+    """测试 write() 的异常、循环引用和参数检查"""
+    ctx = never_jscore.Context()
+
+    class Broken:
+        def write(self, chunk):
+            raise OSError("disk full")
+
+    try:
+        ctx.evaluate("[1, 2, 3]", stream_to=Broken())
+        assert False, "应该抛出 OSError"
+    except OSError as e:
+        assert "disk full" in str(e)
+
+    try:
+        ctx.evaluate("const a = {}; a.self = a; a", stream_to=io.StringIO())
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "circular" in str(e), e
+
+    for kwargs, error in [({"stream_to": object()}, TypeError), ({"stream_to": io.StringIO(), "binary": True}, ValueError)]:
+        try:
+            ctx.evaluate("1", **kwargs)
+            assert False, f"应该抛出 {error.__name__}"
+        except error:
+            pass
+
+    # 出错后 Context 仍然可用
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] 错误处理")
+
+
+def test_result_quota():
+    """测试 max_result_bytes 按写出的字节数检查"""
+    ctx = never_jscore.Context(quotas={"max_result_bytes": 1000})
+    buf = io.StringIO()
+    assert ctx.evaluate("'x'.repeat(100)", stream_to=buf) == 102
+    try:
+        ctx.evaluate("'x'.repeat(5000)", stream_to=io.StringIO())
+        assert False, "应该抛出 QuotaExceeded"
+    except never_jscore.QuotaExceeded:
+        pass
+    del ctx
+    print("[OK] max_result_bytes")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 同样支持 stream_to"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("function dump() { return { ok: true, items: [1, 2, 3] }; }")
+    buf = io.StringIO()
+    written = ctx.call("dump", [], stream_to=buf)
+    assert json.loads(buf.getvalue()) == {"ok": True, "items": [1, 2, 3]}
+    assert written == len(buf.getvalue())
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试大结果分块写出")
+    print("=" * 60)
+
+    test_evaluate_stream()
+    test_large_result_chunks()
+    test_stream_errors()
+    test_result_quota()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 stream_to 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,128 @@
+"""
+测试 ShadowRealm：JS 中的 new ShadowRealm() 和 Python 中的 create_shadow_realm()
+"""
+
+import never_jscore
+
+
+def test_js_api():
+    """测试 JS 代码直接使用 ShadowRealm"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("typeof ShadowRealm") == "function"
+    assert ctx.evaluate("""
+        const realm = new ShadowRealm();
+        realm.evaluate('Array.prototype.push = null; globalThis.inner = 1');
+        [typeof Array.prototype.push, typeof inner, realm.evaluate('typeof inner')]
+    """) == ["function", "undefined", "number"]
+
+    # 函数被包装后返回，参数和返回值只能是原始值
+    assert ctx.evaluate("new ShadowRealm().evaluate('(a, b) => a + b')(1, 2)") == 3
+    assert ctx.evaluate("""
+        try { new ShadowRealm().evaluate('({})'); 'no error'; }
+        catch (e) { e.constructor.name; }
+    """) == "TypeError"
+    del ctx
+    print("[OK] JS 中的 ShadowRealm")
+
+
+def test_no_extensions():
+    """测试 ShadowRealm 只有 ECMAScript 内置对象"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("""
+        new ShadowRealm().evaluate('[typeof btoa, typeof setTimeout, typeof JSON].join()')
+    """) == "undefined,undefined,object"
+    del ctx
+    print("[OK] 只有 ECMAScript 内置对象")
+
+
+def test_rejected_promise():
+    """测试 ShadowRealm 中被拒绝的 Promise 不影响宿主"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("""
+        new ShadowRealm().evaluate('Promise.reject(new Error("inner")).catch(() => {}); 1')
+    """) == 1
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] Promise 拒绝")
+
+
+def test_create_shadow_realm():
+    """测试 Python 中的 create_shadow_realm()"""
+    ctx = never_jscore.Context()
+    with ctx.create_shadow_realm() as realm:
+        assert realm.evaluate("var secret = 42") is None
+        assert realm.evaluate("secret + 1") == 43
+        assert realm.evaluate("JSON.stringify({ secret })") == '{"secret":42}'
+        assert realm.evaluate("'abc'.toUpperCase()") == "ABC"
+        assert realm.closed is False
+    assert realm.closed is True
+    assert ctx.evaluate("typeof secret") == "undefined"
+
+    try:
+        realm.evaluate("1")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "closed" in str(e), e
+    del ctx
+    print("[OK] create_shadow_realm()")
+
+
+def test_errors():
+    """测试 ShadowRealm 中的错误和对象结果"""
+    ctx = never_jscore.Context()
+    realm = ctx.create_shadow_realm()
+    try:
+        realm.evaluate("throw new Error('boom')")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Evaluate error" in str(e), e
+
+    try:
+        realm.evaluate("({ a: 1 })")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "TypeError" in str(e), e
+
+    # 出错后仍然可以继续使用
+    assert realm.evaluate("1 + 2") == 3
+    realm.close()
+    del ctx
+    print("[OK] 错误")
+
+
+def test_disallow_dynamic_code():
+    """测试 allow_dynamic_code=False 同样作用于 ShadowRealm：不能借 evaluate(字符串) 执行动态代码"""
+    ctx = never_jscore.Context(allow_dynamic_code=False)
+    assert ctx.evaluate("""
+        try { new ShadowRealm().evaluate('globalThis.ran = true; 1 + 1'); 'ran' } catch (e) { e.name }
+    """) in ("EvalError", "TypeError")
+
+    realm = ctx.create_shadow_realm()
+    try:
+        realm.evaluate("1 + 1")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Evaluate error" in str(e), e
+    realm.close()
+
+    # 默认 Context 不受影响
+    assert never_jscore.Context().evaluate("new ShadowRealm().evaluate('1 + 1')") == 2
+    del ctx
+    print("[OK] allow_dynamic_code=False")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 ShadowRealm")
+    print("=" * 60)
+
+    test_js_api()
+    test_no_extensions()
+    test_rejected_promise()
+    test_create_shadow_realm()
+    test_errors()
+    test_disallow_dynamic_code()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 ShadowRealm 测试通过！")
+    print("=" * 60)
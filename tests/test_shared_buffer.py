@@ -0,0 +1,128 @@
+"""
+测试 SharedBuffer：Python 与 Context（以及多个 Context 之间）共享 SharedArrayBuffer
+
+SharedArrayBuffer / Atomics 始终可用，shared_memory=True 时 crossOriginIsolated 为 true
+"""
+
+import pickle
+
+import never_jscore
+
+
+def test_builtin_shared_array_buffer():
+    """测试 SharedArrayBuffer 和 Atomics 默认可用"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("typeof SharedArrayBuffer") == "function"
+    assert ctx.evaluate("""
+        const ia = new Int32Array(new SharedArrayBuffer(8));
+        Atomics.add(ia, 0, 5);
+        Atomics.compareExchange(ia, 0, 5, 7);
+        Atomics.load(ia, 0)
+    """) == 7
+    assert ctx.evaluate("typeof crossOriginIsolated === 'undefined' || !crossOriginIsolated")
+    del ctx
+
+    ctx = never_jscore.Context(shared_memory=True)
+    assert ctx.evaluate("crossOriginIsolated") is True
+    del ctx
+    print("[OK] SharedArrayBuffer / Atomics / crossOriginIsolated")
+
+
+def test_view_visible_in_js():
+    """测试 Python 写入的数据在 JS 中可见，反之亦然"""
+    buf = never_jscore.SharedBuffer(16)
+    assert len(buf) == 16 and buf.size == 16
+    assert repr(buf) == "SharedBuffer(size=16)"
+
+    ctx = never_jscore.Context(shared_buffers={"shared": buf})
+    mem = buf.view()
+    mem[0:4] = b"\x01\x02\x03\x04"
+    assert ctx.evaluate("shared instanceof SharedArrayBuffer") is True
+    assert ctx.evaluate("shared.byteLength") == 16
+    assert ctx.evaluate("Array.from(new Uint8Array(shared, 0, 4))") == [1, 2, 3, 4]
+
+    ctx.evaluate("Atomics.store(new Int32Array(shared), 1, 42)")
+    assert int.from_bytes(mem[4:8], "little") == 42
+    del ctx
+    print("[OK] view() 与 JS 读写同一块内存")
+
+
+def test_share_buffer():
+    """测试 share_buffer() 和多个 Context 共享同一块内存"""
+    buf = never_jscore.SharedBuffer(8)
+    a = never_jscore.Context()
+    b = never_jscore.ThreadedContext()
+    a.share_buffer("shared", buf)
+    b.share_buffer("shared", buf)
+
+    a.evaluate("Atomics.add(new Int32Array(shared), 0, 3)")
+    b.evaluate("Atomics.add(new Int32Array(shared), 0, 4)")
+    assert a.evaluate("Atomics.load(new Int32Array(shared), 0)") == 7
+    assert int.from_bytes(buf.view()[0:4], "little") == 7
+    b.close()
+    del a
+    print("[OK] share_buffer()")
+
+
+def test_context_pool():
+    """测试 ContextPool 的每个工作线程看到同一块内存"""
+    buf = never_jscore.SharedBuffer(64)
+    pool = never_jscore.ContextPool(
+        "function bump() { return Atomics.add(new Int32Array(shared), 0, 1) + 1; }",
+        size=2,
+        shared_buffers={"shared": buf},
+    )
+    results = [pool.call("bump", []) for _ in range(10)]
+    assert sorted(results) == list(range(1, 11)), results
+    assert int.from_bytes(buf.view()[0:4], "little") == 10
+    del pool
+    print("[OK] ContextPool 共享内存")
+
+
+def test_errors():
+    """测试 size 为 0 和 pickle"""
+    try:
+        never_jscore.SharedBuffer(0)
+        assert False, "应该抛出 ValueError"
+    except ValueError:
+        pass
+
+    try:
+        never_jscore.Context(shared_buffers={"shared": b"not a buffer"})
+        assert False, "应该抛出异常"
+    except Exception:
+        pass
+
+    ctx = never_jscore.Context(shared_buffers={"shared": never_jscore.SharedBuffer(4)})
+    try:
+        pickle.dumps(ctx)
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "shared buffers" in str(e), e
+    del ctx
+
+    ctx = never_jscore.Context()
+    ctx.share_buffer("shared", never_jscore.SharedBuffer(4))
+    try:
+        pickle.dumps(ctx)
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "shared buffers" in str(e), e
+    del ctx
+    print("[OK] 错误")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 SharedBuffer")
+    print("=" * 60)
+
+    test_builtin_shared_array_buffer()
+    test_view_visible_in_js()
+    test_share_buffer()
+    test_context_pool()
+    test_errors()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 SharedBuffer 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,106 @@
+"""
+测试 never_jscore.shutdown()：销毁所有 Context 后按顺序关闭后台线程并释放 V8
+
+释放后的 V8 不能重新初始化，每个测试在独立进程中执行
+"""
+
+import subprocess
+import sys
+import textwrap
+
+
+def run_script(script):
+    proc = subprocess.run([sys.executable, "-c", textwrap.dedent(script)], capture_output=True, text=True, timeout=60)
+    assert proc.returncode == 0, proc.stdout + proc.stderr
+    return proc.stdout
+
+
+def test_shutdown_after_contexts():
+    """测试销毁 Context 后关闭，之后不能再创建 Context"""
+    run_script("""
+        import never_jscore
+
+        ctx = never_jscore.Context(timeout_ms=1000)
+        assert ctx.evaluate("Promise.resolve(1 + 1)") == 2
+        del ctx
+
+        never_jscore.shutdown()
+        never_jscore.shutdown()  # 重复调用不做任何事
+        try:
+            never_jscore.Context()
+            assert False, "应该抛出 RuntimeError"
+        except RuntimeError as e:
+            assert "shutdown()" in str(e), e
+    """)
+    print("[OK] 销毁 Context 后关闭")
+
+
+def test_live_contexts():
+    """测试还有存活的 Context 时抛出 RuntimeError"""
+    run_script("""
+        import never_jscore
+
+        ctx = never_jscore.Context()
+        threaded = never_jscore.ThreadedContext()
+        for live in (2, 1):
+            try:
+                never_jscore.shutdown()
+                assert False, "应该抛出 RuntimeError"
+            except RuntimeError as e:
+                assert f"{live} Context(s) are still alive" in str(e), e
+            if live == 2:
+                threaded.close()
+        assert ctx.evaluate("'still usable'") == "still usable"
+        del ctx
+        never_jscore.shutdown()
+    """)
+    print("[OK] 存活的 Context")
+
+
+def test_no_lingering_threads():
+    """测试关闭 tokio runtime 和看门狗线程后没有残留的线程"""
+    out = run_script("""
+        import os
+        import never_jscore
+
+        never_jscore.init(tokio_threads=2)
+        ctx = never_jscore.Context(timeout_ms=1000)
+        ctx.evaluate("new Promise(r => setTimeout(() => r(1), 5))")
+        del ctx
+        never_jscore.shutdown()
+
+        names = [open(f"/proc/self/task/{tid}/comm").read().strip() for tid in os.listdir("/proc/self/task")]
+        print(names)
+        assert not any(name.startswith("never_jscore") for name in names), names
+    """) if sys.platform.startswith("linux") else ""
+    print("[OK] 没有残留的线程", out.strip())
+
+
+def test_without_contexts():
+    """测试从未创建 Context 时调用"""
+    run_script("""
+        import never_jscore
+
+        never_jscore.shutdown()
+        try:
+            never_jscore.eval("1")
+            assert False, "应该抛出 RuntimeError"
+        except RuntimeError:
+            pass
+    """)
+    print("[OK] 未创建 Context 时关闭")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 shutdown()")
+    print("=" * 60)
+
+    test_shutdown_after_contexts()
+    test_live_contexts()
+    test_no_lingering_threads()
+    test_without_contexts()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 shutdown() 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,91 @@
+"""
+测试单调时钟与非忙等的等待：$nowNs() / $sleep(ms)（Rust ops: op_now_ns / op_sleep）
+
+$sleep() 受 timeout_ms 限制，执行被终止时未完成的 sleep 以 false 完成
+"""
+
+import time
+
+import never_jscore
+
+
+def test_now_ns():
+    """测试 $nowNs() 返回单调递增的 BigInt 纳秒数"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("typeof $nowNs()") == "bigint"
+    assert ctx.evaluate("typeof __neverjscore_now_ns__()") == "bigint"
+    assert ctx.evaluate("(() => { const a = $nowNs(); const b = $nowNs(); return b >= a; })()") is True
+    elapsed_ms = ctx.evaluate("""
+        (() => {
+            const start = $nowNs();
+            const end = Date.now() + 20;
+            while (Date.now() < end) {}
+            return Number($nowNs() - start) / 1e6;
+        })()
+    """)
+    assert 15 <= elapsed_ms < 1000, elapsed_ms
+    del ctx
+    print("[OK] $nowNs")
+
+
+def test_sleep():
+    """测试 $sleep() 等待指定时间后以 true 完成，且不占用 CPU"""
+    ctx = never_jscore.Context(cpu_limit_ms=50)
+    start = time.time()
+    assert ctx.evaluate("$sleep(200)") is True
+    assert time.time() - start >= 0.19
+    assert ctx.evaluate("$sleep(0)") is True
+    assert ctx.evaluate("$sleep(-5)") is True
+    ctx.compile("""
+        async function elapsed(ms) {
+            const start = $nowNs();
+            await __neverjscore_sleep__(ms);
+            return Number($nowNs() - start) / 1e6;
+        }
+    """)
+    assert ctx.call("elapsed", [50]) >= 49
+    del ctx
+    print("[OK] $sleep")
+
+
+def test_sleep_respects_timeout():
+    """测试调用在 timeout_ms 截止时失败，被中断的 sleep 以 false 完成且不影响之后的调用"""
+    ctx = never_jscore.Context(timeout_ms=200)
+    ctx.compile("""
+        var results = [];
+        async function poll() {
+            while (true) {
+                const slept = await $sleep(5000);
+                results.push(slept);
+                if (!slept) return 'interrupted';
+            }
+        }
+    """)
+    start = time.time()
+    try:
+        ctx.call("poll", [])
+        assert False, "应该超时"
+    except Exception as e:
+        assert "Execution timed out (timeout_ms=200)" in str(e), e
+    assert time.time() - start < 2, "sleep 应该在截止时间被中断"
+
+    start = time.time()
+    assert ctx.evaluate("$sleep(10)") is True
+    assert time.time() - start < 1
+    assert ctx.evaluate("results") == [False]
+    del ctx
+    print("[OK] 超时中断 $sleep")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 $nowNs / $sleep")
+    print("=" * 60)
+
+    test_now_ns()
+    test_sleep()
+    test_sleep_respects_timeout()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 $nowNs / $sleep 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,131 @@
+"""
+测试慢脚本回调：ctx.on_slow_script(threshold_ms, callback)
+
+调用超过阈值时报告（带当前 JS 调用栈），执行不会被终止
+"""
+
+import never_jscore
+
+BUSY_LOOP = """
+function _0xslow(ms) {
+    const end = Date.now() + ms;
+    let n = 0;
+    while (Date.now() < end) { n++; }
+    return 'done';
+}
+"""
+
+
+def test_slow_call_reported():
+    """测试超过阈值的调用被报告且正常完成"""
+    ctx = never_jscore.Context()
+    reports = []
+    ctx.on_slow_script(50, reports.append)
+    ctx.compile(BUSY_LOOP)
+
+    assert ctx.call("_0xslow", [200]) == "done"
+    assert len(reports) == 1, reports
+    info = reports[0]
+    assert info["threshold_ms"] == 50
+    assert info["elapsed_ms"] >= 50
+    assert info["kind"] == "evaluate"
+    assert info["code"] == "_0xslow(200)"
+    assert info["stack"][0]["function"] == "_0xslow", info["stack"]
+
+    # 快速调用不报告
+    assert ctx.call("_0xslow", [0]) == "done"
+    assert len(reports) == 1
+    del ctx
+    print("[OK] 慢调用报告调用栈，执行不被终止")
+
+
+def test_slow_before_timeout():
+    """测试回调先于超时终止运行"""
+    ctx = never_jscore.Context(timeout_ms=300)
+    reports = []
+    ctx.on_slow_script(50, reports.append)
+    try:
+        ctx.evaluate("while (true) {}")
+        assert False, "应该超时"
+    except Exception as e:
+        assert "timed out" in str(e)
+    assert len(reports) == 1
+    assert reports[0]["stack"] is not None
+
+    # Context 仍然可用
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] 回调先于超时运行")
+
+
+def test_slow_while_waiting():
+    """测试等待定时器时在调用结束时报告，没有调用栈"""
+    ctx = never_jscore.Context()
+    reports = []
+    ctx.on_slow_script(30, reports.append)
+    result = ctx.evaluate("new Promise(resolve => setTimeout(() => resolve('late'), 150))")
+    assert result == "late"
+    assert len(reports) == 1
+    assert reports[0]["elapsed_ms"] >= 30
+    del ctx
+    print("[OK] 等待定时器的慢调用")
+
+
+def test_callback_management():
+    """测试清除回调、参数校验和回调异常"""
+    ctx = never_jscore.Context()
+    ctx.compile(BUSY_LOOP)
+    reports = []
+    ctx.on_slow_script(20, reports.append)
+    ctx.on_slow_script(20, None)
+    ctx.call("_0xslow", [60])
+    assert reports == []
+
+    try:
+        ctx.on_slow_script(0, reports.append)
+        assert False, "应该抛出 ValueError"
+    except ValueError:
+        pass
+    try:
+        ctx.on_slow_script(10, "not callable")
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+
+    def broken(info):
+        raise RuntimeError("logger is down")
+
+    # 回调的异常不影响执行
+    ctx.on_slow_script(20, broken)
+    assert ctx.call("_0xslow", [60]) == "done"
+    del ctx
+    print("[OK] 清除回调和参数校验")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext"""
+    tctx = never_jscore.ThreadedContext()
+    reports = []
+    tctx.on_slow_script(50, reports.append)
+    tctx.compile(BUSY_LOOP)
+    assert tctx.call("_0xslow", [150]) == "done"
+    assert len(reports) == 1
+    assert reports[0]["stack"][0]["function"] == "_0xslow"
+    tctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试慢脚本回调")
+    print("=" * 60)
+
+    test_slow_call_reported()
+    test_slow_before_timeout()
+    test_slow_while_waiting()
+    test_callback_management()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有慢脚本回调测试通过！")
+    print("=" * 60)
@@ -0,0 +1,154 @@
+"""
+测试 V8 启动快照功能
+
+展示如何使用 build_snapshot() 预先构建快照，并通过 Context(snapshot=...) 快速启动
+"""
+
+import time
+
+import never_jscore
+
+
+LIB_CODE = """
+    var counter = 0;
+    function add(a, b) { return a + b; }
+    function sign(data) { counter++; return md5(data + ':' + counter); }
+    const CONFIG = { version: '1.0', key: 'secret' };
+"""
+
+
+def test_build_snapshot_returns_bytes():
+    """测试 build_snapshot 返回 bytes"""
+    snapshot = never_jscore.build_snapshot(LIB_CODE)
+
+    assert isinstance(snapshot, bytes)
+    assert len(snapshot) > 0
+
+    print(f"[OK] 快照大小: {len(snapshot) / 1024:.1f} KB")
+
+
+def test_context_from_snapshot():
+    """测试从快照启动的 Context 可以直接调用快照中的函数"""
+    snapshot = never_jscore.build_snapshot(LIB_CODE)
+    ctx = never_jscore.Context(snapshot=snapshot)
+
+    assert ctx.call("add", [1, 2]) == 3
+    assert ctx.evaluate("CONFIG.version") == "1.0"
+    # 扩展 API 同样来自快照
+    assert ctx.evaluate("btoa('hello')") == "aGVsbG8="
+
+    print("[OK] 快照中的函数、常量和扩展 API 均可用")
+
+
+def test_snapshot_contexts_are_isolated():
+    """测试同一个快照启动的多个 Context 互不影响"""
+    snapshot = never_jscore.build_snapshot(LIB_CODE)
+
+    ctx1 = never_jscore.Context(snapshot=snapshot)
+    ctx1.call("sign", ["a"])
+    ctx1.call("sign", ["a"])
+    assert ctx1.evaluate("counter") == 2
+    del ctx1
+
+    ctx2 = never_jscore.Context(snapshot=snapshot)
+    assert ctx2.evaluate("counter") == 0
+
+    print("[OK] 每个 Context 都从快照的初始状态开始")
+
+
+def test_snapshot_with_random_seed():
+    """测试快照与 random_seed 组合使用"""
+    snapshot = never_jscore.build_snapshot(LIB_CODE)
+
+    ctx1 = never_jscore.Context(snapshot=snapshot, random_seed=42)
+    r1 = ctx1.evaluate("Math.random()")
+    del ctx1
+
+    ctx2 = never_jscore.Context(snapshot=snapshot, random_seed=42)
+    r2 = ctx2.evaluate("Math.random()")
+
+    assert r1 == r2
+    print(f"[OK] 快照 + 固定种子: {r1}")
+
+
+def test_snapshot_extensions_mismatch():
+    """测试 enable_extensions 与快照不一致时报错"""
+    snapshot = never_jscore.build_snapshot("var x = 1;", enable_extensions=False)
+
+    try:
+        never_jscore.Context(snapshot=snapshot)
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        print(f"[OK] 参数不一致被拒绝: {e}")
+
+
+def test_invalid_snapshot():
+    """测试无效的快照数据"""
+    try:
+        never_jscore.Context(snapshot=b"not a snapshot")
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        print(f"[OK] 无效快照被拒绝: {e}")
+
+
+def test_builtin_snapshot_default_context():
+    """测试默认 Context 使用内置快照后扩展 API 和随机种子仍然正常"""
+    # 第一个 Context 触发内置快照构建
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("btoa('hello')") == "aGVsbG8="
+    del ctx
+
+    start = time.perf_counter()
+    for _ in range(10):
+        ctx = never_jscore.Context()
+        assert ctx.evaluate("typeof md5") == "function"
+        del ctx
+    elapsed = time.perf_counter() - start
+
+    ctx1 = never_jscore.Context(random_seed=7)
+    ctx2 = never_jscore.Context(random_seed=7)
+    assert ctx1.evaluate("Math.random()") == ctx2.evaluate("Math.random()")
+
+    print(f"[OK] 10 个默认 Context 启动耗时: {elapsed * 1000:.1f}ms")
+
+
+def test_snapshot_startup_speed():
+    """对比普通启动与快照启动的耗时"""
+    big_lib = LIB_CODE + "\n".join(
+        f"function helper_{i}(x) {{ return x * {i} + {i}; }}" for i in range(5000)
+    )
+    snapshot = never_jscore.build_snapshot(big_lib)
+
+    start = time.perf_counter()
+    ctx = never_jscore.Context()
+    ctx.compile(big_lib)
+    ctx.call("helper_10", [1])
+    normal = time.perf_counter() - start
+    del ctx
+
+    start = time.perf_counter()
+    ctx = never_jscore.Context(snapshot=snapshot)
+    ctx.call("helper_10", [1])
+    fast = time.perf_counter() - start
+    del ctx
+
+    print(f"[OK] 普通启动: {normal * 1000:.1f}ms, 快照启动: {fast * 1000:.1f}ms")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 V8 启动快照")
+    print("=" * 60)
+
+    test_build_snapshot_returns_bytes()
+    test_context_from_snapshot()
+    test_snapshot_contexts_are_isolated()
+    test_snapshot_with_random_seed()
+    test_snapshot_extensions_mismatch()
+    test_invalid_snapshot()
+    test_builtin_snapshot_default_context()
+    test_snapshot_startup_speed()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有快照测试通过！")
+    print("=" * 60)
@@ -0,0 +1,125 @@
+"""
+测试 SnapshotPool：从预热快照创建全新 Context
+
+展示高 QPS 签名服务的用法：每个请求一个干净的 Context，同时不必重复执行初始化代码
+"""
+
+import time
+from concurrent.futures import ThreadPoolExecutor
+
+import never_jscore
+
+JS_CODE = """
+var calls = 0;
+function sign(data) {
+    calls++;
+    return btoa(data) + ':' + calls;
+}
+"""
+
+
+def test_snapshot_pool_call():
+    """测试每次调用都在全新的 Context 中执行"""
+    pool = never_jscore.SnapshotPool(JS_CODE)
+
+    # calls 每次都从 0 开始：上一次调用的状态不会泄漏
+    assert pool.call("sign", ["hi"]) == "aGk=:1"
+    assert pool.call("sign", ["hi"]) == "aGk=:1"
+    assert pool.evaluate("calls") == 0
+    assert pool.evaluate("Promise.resolve(sign('a'))") == "YQ==:1"
+
+    assert pool.checkouts == 4
+    assert pool.snapshot_size > 0
+    print(f"[OK] SnapshotPool.call() 每次使用全新 Context（快照 {pool.snapshot_size} 字节）")
+
+
+def test_snapshot_pool_checkout():
+    """测试 checkout() 返回可多次调用的独立 Context"""
+    pool = never_jscore.SnapshotPool(JS_CODE)
+
+    ctx1 = pool.checkout()
+    ctx2 = pool.checkout()
+    assert isinstance(ctx1, never_jscore.Context)
+
+    assert ctx1.call("sign", ["a"]) == "YQ==:1"
+    assert ctx1.call("sign", ["a"]) == "YQ==:2"
+    assert ctx2.call("sign", ["a"]) == "YQ==:1"
+
+    ctx1.eval("globalThis.leaked = true")
+    assert ctx2.evaluate("typeof leaked") == "undefined"
+    del ctx1, ctx2
+
+    print("[OK] checkout() 返回独立的 Context")
+
+
+def test_snapshot_pool_options():
+    """测试构造参数应用于每个取出的 Context"""
+    pool = never_jscore.SnapshotPool(
+        "function add(a, b) { return a + b; }",
+        enable_extensions=False,
+    )
+    assert pool.call("add", [1, 2]) == 3
+    assert pool.evaluate("typeof btoa") == "undefined"
+
+    pool = never_jscore.SnapshotPool("var x = Math.random();", random_seed=42)
+    assert pool.evaluate("typeof x") == "number"
+
+    try:
+        never_jscore.SnapshotPool("throw new Error('bad init')")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "bad init" in str(e)
+
+    print("[OK] SnapshotPool 构造参数")
+
+
+def test_snapshot_pool_threads():
+    """测试 SnapshotPool 可以在多个线程之间共享"""
+    pool = never_jscore.SnapshotPool(JS_CODE)
+
+    def worker(i):
+        return pool.call("sign", [str(i)])
+
+    with ThreadPoolExecutor(max_workers=4) as executor:
+        results = list(executor.map(worker, range(20)))
+
+    assert all(result.endswith(":1") for result in results)
+    assert pool.checkouts == 20
+    print("[OK] SnapshotPool 跨线程共享")
+
+
+def test_snapshot_pool_faster_than_init():
+    """测试从快照启动比每次重新执行初始化代码快"""
+    big_code = JS_CODE + "\n".join(f"function helper{i}(x) {{ return x + {i}; }}" for i in range(2000))
+    pool = never_jscore.SnapshotPool(big_code)
+
+    start = time.perf_counter()
+    for _ in range(20):
+        pool.call("sign", ["x"])
+    snapshot_time = time.perf_counter() - start
+
+    start = time.perf_counter()
+    for _ in range(20):
+        ctx = never_jscore.Context()
+        ctx.compile(big_code)
+        ctx.call("sign", ["x"])
+        del ctx
+    fresh_time = time.perf_counter() - start
+
+    print(f"[OK] 快照启动 {snapshot_time * 1000:.1f}ms vs 重新初始化 {fresh_time * 1000:.1f}ms（20 次）")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 SnapshotPool")
+    print("=" * 60)
+
+    test_snapshot_pool_call()
+    test_snapshot_pool_checkout()
+    test_snapshot_pool_options()
+    test_snapshot_pool_threads()
+    test_snapshot_pool_faster_than_init()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 SnapshotPool 测试通过！")
+    print("=" * 60)
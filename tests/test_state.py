@@ -0,0 +1,148 @@
+"""
+测试 export_state() / import_state()：把选定的全局变量保存为 bytes，恢复到新的 Context
+
+只需要计算一次的状态（设备注册信息等）导出后跨进程恢复，不必重新计算
+"""
+
+import pickle
+
+import never_jscore
+
+
+def test_round_trip():
+    """测试结构化克隆类型恢复到新的 Context"""
+    ctx = never_jscore.Context()
+    ctx.eval("""
+        globalThis.device = {
+            id: 'dev-1',
+            created: new Date(1700000000000),
+            keys: new Map([['aes', new Uint8Array([1, 2, 3])]]),
+            tags: new Set(['a', 'b']),
+            pattern: /x+/gi,
+        };
+        globalThis.counter = 42;
+    """)
+    state = ctx.export_state(keys=["device", "counter"])
+    assert isinstance(state, bytes)
+    del ctx
+
+    ctx = never_jscore.Context()
+    assert ctx.import_state(state) == ["device", "counter"]
+    assert ctx.evaluate("device.id") == "dev-1"
+    assert ctx.evaluate("device.created instanceof Date && device.created.getTime()") == 1700000000000
+    assert ctx.evaluate("Array.from(device.keys.get('aes'))") == [1, 2, 3]
+    assert ctx.evaluate("device.keys.get('aes') instanceof Uint8Array") is True
+    assert ctx.evaluate("[...device.tags]") == ["a", "b"]
+    assert ctx.evaluate("device.pattern.flags") == "gi"
+    assert ctx.evaluate("counter") == 42
+    del ctx
+    print("[OK] 结构化克隆类型恢复")
+
+
+def test_shared_references():
+    """测试变量之间共享的对象和循环引用"""
+    ctx = never_jscore.Context()
+    ctx.eval("""
+        const shared = { n: 1 };
+        globalThis.a = { shared };
+        globalThis.b = [shared];
+        a.self = a;
+    """)
+    state = ctx.export_state(["a", "b"])
+    del ctx
+
+    ctx = never_jscore.Context()
+    ctx.import_state(state)
+    assert ctx.evaluate("a.shared === b[0]") is True
+    assert ctx.evaluate("a.self === a") is True
+    # 导入覆盖同名变量
+    ctx.eval("globalThis.a = 'old'")
+    ctx.import_state(state)
+    assert ctx.evaluate("a.shared.n") == 1
+    del ctx
+    print("[OK] 共享引用和循环引用")
+
+
+def test_export_errors():
+    """测试无法克隆的值和不存在的变量"""
+    ctx = never_jscore.Context()
+    ctx.eval("globalThis.ok = 1; globalThis.handler = { onload() {} };")
+    try:
+        ctx.export_state(["ok", "handler"])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "DataCloneError" in str(e) and "handler" in str(e), e
+    try:
+        ctx.export_state(["nope"])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "nope is not defined" in str(e), e
+    del ctx
+    print("[OK] 导出错误")
+
+
+def test_import_errors():
+    """测试无效的状态数据"""
+    ctx = never_jscore.Context()
+    for bad in [b"", b"not a state", b"NJSTATE"]:
+        try:
+            ctx.import_state(bad)
+            assert False, f"应该抛出 ValueError: {bad!r}"
+        except ValueError:
+            pass
+    del ctx
+    print("[OK] 无效数据")
+
+
+def test_blocks_pickle():
+    """测试导入状态后 Context 不能 pickle"""
+    ctx = never_jscore.Context()
+    ctx.eval("globalThis.x = 1")
+    state = ctx.export_state(["x"])
+    pickle.dumps(ctx)  # 导出不影响 pickle
+
+    ctx.import_state(state)
+    try:
+        pickle.dumps(ctx)
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "import_state" in str(e), e
+    del ctx
+    print("[OK] 导入后不能 pickle")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 同样支持导出和恢复"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.eval("globalThis.session = { token: 'abc', ts: new Date(0) }")
+    state = ctx.export_state(["session"])
+    ctx.close()
+
+    ctx = never_jscore.ThreadedContext()
+    assert ctx.import_state(state) == ["session"]
+    assert ctx.evaluate("session.token + session.ts.getTime()") == "abc0"
+    ctx.close()
+
+    # Context 和 ThreadedContext 之间通用
+    ctx = never_jscore.Context()
+    ctx.import_state(state)
+    assert ctx.evaluate("session.token") == "abc"
+    del ctx
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 export_state() / import_state()")
+    print("=" * 60)
+
+    test_round_trip()
+    test_shared_references()
+    test_export_errors()
+    test_import_errors()
+    test_blocks_pickle()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 export_state / import_state 测试通过！")
+    print("=" * 60)
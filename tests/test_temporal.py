@@ -0,0 +1,123 @@
+"""
+测试 Temporal API：V8 内置实现，返回给 Python 的值转换为 datetime 模块的类型
+"""
+
+import datetime
+
+import never_jscore
+
+
+def test_available():
+    """测试 Temporal 可用"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("typeof Temporal") == "object"
+    assert ctx.evaluate("typeof Temporal.Now.instant().epochNanoseconds") == "bigint"
+    assert never_jscore.version_info()["features"]["temporal"] is True
+    del ctx
+    print("[OK] Temporal 可用")
+
+
+def test_instant():
+    """测试 Instant -> UTC datetime"""
+    ctx = never_jscore.Context()
+    value = ctx.evaluate("Temporal.Instant.from('2024-05-01T08:30:00.123456789Z')")
+    assert value == datetime.datetime(2024, 5, 1, 8, 30, 0, 123456, tzinfo=datetime.timezone.utc)
+
+    # 1970 年之前
+    value = ctx.evaluate("Temporal.Instant.from('1969-12-31T23:59:59.5Z')")
+    assert value == datetime.datetime(1969, 12, 31, 23, 59, 59, 500000, tzinfo=datetime.timezone.utc)
+
+    now = ctx.evaluate("Temporal.Now.instant()")
+    assert abs((datetime.datetime.now(datetime.timezone.utc) - now).total_seconds()) < 60
+    del ctx
+    print("[OK] Instant")
+
+
+def test_zoned_date_time():
+    """测试 ZonedDateTime -> 带时区的 datetime"""
+    ctx = never_jscore.Context()
+    value = ctx.evaluate("Temporal.ZonedDateTime.from('2024-05-01T16:30:00[+08:00]')")
+    assert value.utcoffset() == datetime.timedelta(hours=8)
+    assert (value.year, value.month, value.day, value.hour, value.minute) == (2024, 5, 1, 16, 30)
+
+    try:
+        import zoneinfo
+        zoneinfo.ZoneInfo("America/New_York")
+    except Exception:
+        print("[SKIP] 没有 tzdata，跳过 IANA 时区")
+        del ctx
+        return
+
+    value = ctx.evaluate("Temporal.ZonedDateTime.from('2024-05-01T16:30:00[Asia/Shanghai]')")
+    assert value.tzinfo == zoneinfo.ZoneInfo("Asia/Shanghai")
+    assert value.utcoffset() == datetime.timedelta(hours=8)
+
+    # 夏令时结束时重复的本地时间：第二次（EST）通过 fold=1 区分
+    first, second = ctx.evaluate("""
+        const start = Temporal.ZonedDateTime.from('2024-11-03T01:30:00-04:00[America/New_York]');
+        [start, start.add({ hours: 1 })]
+    """)
+    assert (first.hour, second.hour) == (1, 1)
+    assert first.utcoffset() == datetime.timedelta(hours=-4)
+    assert second.utcoffset() == datetime.timedelta(hours=-5)
+    del ctx
+    print("[OK] ZonedDateTime")
+
+
+def test_plain_types():
+    """测试 PlainDateTime / PlainDate / PlainTime / Duration"""
+    ctx = never_jscore.Context()
+    result = ctx.evaluate("""
+        ({
+            dateTime: Temporal.PlainDateTime.from('2024-05-01T08:30:15.25'),
+            date: Temporal.PlainDate.from('2024-05-01'),
+            time: Temporal.PlainTime.from('23:59:59.999999'),
+            duration: Temporal.Duration.from({ days: 1, hours: 2, milliseconds: 5 }),
+            negative: Temporal.Duration.from({ minutes: -30 }),
+            months: Temporal.Duration.from({ months: 1, days: 2 }),
+            yearMonth: Temporal.PlainYearMonth.from('2024-05'),
+            chinese: Temporal.PlainDate.from('2024-05-01').withCalendar('chinese'),
+        })
+    """)
+    assert result["dateTime"] == datetime.datetime(2024, 5, 1, 8, 30, 15, 250000)
+    assert result["date"] == datetime.date(2024, 5, 1)
+    assert result["time"] == datetime.time(23, 59, 59, 999999)
+    assert result["duration"] == datetime.timedelta(days=1, hours=2, milliseconds=5)
+    assert result["negative"] == datetime.timedelta(minutes=-30)
+    assert result["months"] == "P1M2D"
+    assert result["yearMonth"] == "2024-05"
+    assert result["chinese"] == datetime.date(2024, 5, 1)
+    del ctx
+    print("[OK] Plain 类型和 Duration")
+
+
+def test_json_unchanged():
+    """测试 JS 中的 JSON.stringify() 和 toJSON 不受影响"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("JSON.stringify({ d: Temporal.PlainDate.from('2024-05-01') })") == '{"d":"2024-05-01"}'
+    ctx.evaluate("({ d: Temporal.PlainDate.from('2024-05-01') })")
+    assert ctx.evaluate("Temporal.PlainDate.from('2024-05-01').toJSON()") == "2024-05-01"
+
+    # 异步结果、call() 和 binary=True 同样转换
+    assert ctx.evaluate("Promise.resolve(Temporal.PlainDate.from('2024-05-01'))") == datetime.date(2024, 5, 1)
+    ctx.compile("function due(days) { return Temporal.PlainDate.from('2024-05-01').add({ days }); }")
+    assert ctx.call("due", [3]) == datetime.date(2024, 5, 4)
+    assert ctx.evaluate("[Temporal.PlainDate.from('2024-05-01'), 'ab']", binary=True) == [datetime.date(2024, 5, 1), b"ab"]
+    del ctx
+    print("[OK] JSON.stringify 不受影响")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 Temporal")
+    print("=" * 60)
+
+    test_available()
+    test_instant()
+    test_zoned_date_time()
+    test_plain_types()
+    test_json_unchanged()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 Temporal 测试通过！")
+    print("=" * 60)
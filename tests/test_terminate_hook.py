@@ -7,6 +7,7 @@
 
 import never_jscore
 import json
+import threading
 
 
 def test_basic_save_and_terminate():
@@ -299,7 +300,7 @@ def test_multiple_contexts():
     data1 = ctx1.get_hook_data()
     print(f"Context 1 数据: {json.loads(data1) if data1 else None}")
 
-    # Context 2 保存数据（会覆盖全局数据）
+    # Context 2 保存数据（不影响 Context 1）
     ctx2.clear_hook_data()
     try:
         ctx2.evaluate('__saveAndTerminate__({ context: "ctx2", value: 222 });')
@@ -309,9 +310,28 @@ def test_multiple_contexts():
     data2 = ctx2.get_hook_data()
     print(f"Context 2 数据: {json.loads(data2) if data2 else None}")
 
-    # 注意：由于使用全局存储，Context 2 会覆盖 Context 1 的数据
-    print("\n⚠️ 注意: Hook 数据使用全局存储，后执行的 Context 会覆盖之前的数据")
-    print("   建议：在单个 Context 内完成 Hook -> 执行 -> 获取数据 的流程")
+    # 每个 Context 的 Hook 数据独立保存
+    assert json.loads(ctx1.get_hook_data()) == {"context": "ctx1", "value": 111}
+    assert json.loads(data2) == {"context": "ctx2", "value": 222}
+
+    # 其他线程中的 Context 也不会读到或覆盖这里的数据
+    seen = []
+
+    def other_thread():
+        ctx3 = never_jscore.Context()
+        seen.append(ctx3.get_hook_data())
+        try:
+            ctx3.evaluate('__saveAndTerminate__({ context: "ctx3" });')
+        except:
+            pass
+        del ctx3
+
+    t = threading.Thread(target=other_thread)
+    t.start()
+    t.join()
+    assert seen == [None]
+    assert json.loads(ctx1.get_hook_data())["context"] == "ctx1"
+    print("✓ 每个 Context 的 Hook 数据互不影响")
 
 
 def run_all_tests():
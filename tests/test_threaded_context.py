@@ -0,0 +1,119 @@
+"""
+测试 ThreadedContext
+
+展示如何在多个 Python 线程之间共享同一个 JS 执行环境
+"""
+
+import os
+import tempfile
+import threading
+from concurrent.futures import ThreadPoolExecutor
+
+import never_jscore
+
+
+def test_basic_api():
+    """测试与 Context 相同的 API"""
+    ctx = never_jscore.ThreadedContext()
+
+    ctx.compile("function add(a, b) { return a + b; }")
+    assert ctx.call("add", [1, 2]) == 3
+    assert ctx.evaluate("btoa('hello')") == "aGVsbG8="
+    assert ctx.eval("var x = 10; x * 2", return_value=True) == 20
+    assert ctx.eval("var y = 5;") is None
+    assert ctx.evaluate("x + y") == 15
+    assert ctx.evaluate("Promise.resolve(42)") == 42
+
+    ctx.gc()
+    assert ctx.get_stats()[0] > 0
+    ctx.reset_stats()
+    assert ctx.get_stats() == (0,)
+    assert "used_heap_size" in ctx.get_heap_statistics()
+
+    ctx.close()
+    print("[OK] 基本 API")
+
+
+def test_shared_state_across_threads():
+    """测试多个线程共享同一个全局状态（调用被串行化）"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("var counter = 0; function inc() { return ++counter; }")
+
+    with ThreadPoolExecutor(max_workers=8) as executor:
+        results = list(executor.map(lambda _: ctx.call("inc", []), range(500)))
+
+    assert sorted(results) == list(range(1, 501))
+    assert ctx.evaluate("counter") == 500
+
+    ctx.close()
+    print("[OK] 8 个线程共享状态，500 次自增无竞争")
+
+
+def test_created_in_other_thread():
+    """测试在一个线程创建、在另一个线程使用"""
+    holder = {}
+
+    def create():
+        holder["ctx"] = never_jscore.ThreadedContext()
+        holder["ctx"].compile("function hello(n) { return 'hello ' + n; }")
+
+    t = threading.Thread(target=create)
+    t.start()
+    t.join()
+
+    assert holder["ctx"].call("hello", ["world"]) == "hello world"
+    holder["ctx"].close()
+    print("[OK] 跨线程传递句柄")
+
+
+def test_compile_file():
+    """测试 compile_file()"""
+    with tempfile.TemporaryDirectory() as tmp:
+        path = os.path.join(tmp, "lib.js")
+        with open(path, "w", encoding="utf-8") as f:
+            f.write("function triple(x) { return x * 3; }")
+
+        with never_jscore.ThreadedContext() as ctx:
+            ctx.compile_file(path)
+            assert ctx.call("triple", [3]) == 9
+
+    print("[OK] compile_file()")
+
+
+def test_errors_and_close():
+    """测试错误传播和关闭"""
+    ctx = never_jscore.ThreadedContext()
+
+    try:
+        ctx.evaluate("throw new Error('oops')")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "oops" in str(e)
+
+    assert ctx.evaluate("1 + 1") == 2
+
+    ctx.close()
+    ctx.close()
+    try:
+        ctx.evaluate("1")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "closed" in str(e)
+
+    print("[OK] 错误传播和关闭")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 ThreadedContext")
+    print("=" * 60)
+
+    test_basic_api()
+    test_shared_state_across_threads()
+    test_created_in_other_thread()
+    test_compile_file()
+    test_errors_and_close()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有 ThreadedContext 测试通过！")
+    print("=" * 60)
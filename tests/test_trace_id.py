@@ -0,0 +1,93 @@
+"""
+测试 call(name, args, trace_id="...")：追踪 ID 出现在 JS、console 输出、执行钩子和异常中
+
+console 输出直接写到进程的 stdout / stderr，相关测试在子进程中执行并检查其输出
+"""
+
+import subprocess
+import sys
+
+import never_jscore
+
+
+def test_global_in_js():
+    """测试调用期间 globalThis.__trace_id 可见，结束后删除"""
+    ctx = never_jscore.Context()
+    ctx.compile("""
+        function whoami() { return globalThis.__trace_id; }
+        async function later() { await new Promise(r => setTimeout(r, 5)); return __trace_id; }
+    """)
+    assert ctx.call("whoami", [], trace_id="req-1") == "req-1"
+    assert ctx.call("later", [], trace_id="req-2") == "req-2"
+    assert ctx.evaluate("typeof __trace_id") == "undefined"
+    assert ctx.call("whoami", []) is None
+    del ctx
+    print("[OK] globalThis.__trace_id")
+
+
+def test_console_prefix():
+    """测试 console 输出带上 [trace_id=...] 前缀"""
+    script = (
+        "import never_jscore\n"
+        "ctx = never_jscore.Context()\n"
+        "ctx.compile(\"function work() { console.log('working'); console.error('oops'); return 1; }\")\n"
+        "ctx.call('work', [], trace_id='req-42')\n"
+        "ctx.call('work', [])\n"
+    )
+    proc = subprocess.run([sys.executable, "-c", script], capture_output=True, text=True, timeout=60)
+    assert proc.returncode == 0, proc.stderr
+    lines = proc.stdout.splitlines()
+    assert "[trace_id=req-42] working" in lines, proc.stdout
+    assert "working" in lines, proc.stdout
+    assert "[trace_id=req-42] oops" in proc.stderr, proc.stderr
+    print("[OK] console 前缀")
+
+
+def test_hooks_and_errors():
+    """测试执行钩子的字典和异常的 trace_id"""
+    events = []
+    never_jscore.set_execution_hooks(on_execute_start=events.append)
+    try:
+        ctx = never_jscore.Context()
+        ctx.compile("function fail() { throw new Error('bad input'); }")
+        try:
+            ctx.call("fail", [], trace_id="req-7")
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert e.trace_id == "req-7", e
+            assert "bad input" in str(e), e
+        assert events[-1]["trace_id"] == "req-7", events[-1]
+        assert events[0]["trace_id"] is None, events[0]  # compile
+        del ctx
+    finally:
+        never_jscore.set_execution_hooks()
+    print("[OK] 执行钩子和异常")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext.call() 同样支持 trace_id"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("function whoami() { return __trace_id; }")
+    assert ctx.call("whoami", [], trace_id="t-1") == "t-1"
+    try:
+        ctx.call("missing", [], trace_id="t-2")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert e.trace_id == "t-2", e
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 call(trace_id=...)")
+    print("=" * 60)
+
+    test_global_in_js()
+    test_console_prefix()
+    test_hooks_and_errors()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 trace_id 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,113 @@
+"""
+测试 transfer()：在两个 Context 之间以 V8 结构化克隆传递全局变量
+
+不经过 Python / JSON，Map / 类型化数组等类型原样保留，SharedArrayBuffer 在两端共享
+"""
+
+import pickle
+
+import never_jscore
+
+SRC_CODE = """
+var parsed = new Map([['body', new Uint8Array([1, 2, 3])], ['at', new Date(0)]]);
+var nested = { list: [1, { deep: new Set(['a', 'b']) }] };
+nested.self = nested;
+var fn = function () {};
+"""
+
+
+def test_structured_clone():
+    """测试 Map、类型化数组、Set、Date 和循环引用原样保留"""
+    src = never_jscore.Context()
+    dst = never_jscore.Context()
+    src.compile(SRC_CODE)
+    assert never_jscore.transfer(src, "parsed", dst) is None
+    assert dst.evaluate("parsed instanceof Map") is True
+    assert dst.evaluate("parsed.get('body') instanceof Uint8Array") is True
+    assert dst.evaluate("Array.from(parsed.get('body'))") == [1, 2, 3]
+    assert dst.evaluate("parsed.get('at').getTime()") == 0
+
+    never_jscore.transfer(src, "nested", dst)
+    assert dst.evaluate("nested.self === nested") is True
+    assert dst.evaluate("nested.list[1].deep.has('b')") is True
+
+    # 复制而不是移动：修改副本不影响 src
+    dst.eval("parsed.get('body')[0] = 99")
+    assert src.evaluate("parsed.get('body')[0]") == 1
+    del src, dst
+    print("[OK] 结构化克隆")
+
+
+def test_dst_name_and_threaded():
+    """测试 dst_name 以及 ThreadedContext 作为两端"""
+    src = never_jscore.ThreadedContext()
+    dst = never_jscore.Context()
+    src.compile(SRC_CODE)
+    never_jscore.transfer(src, "parsed", dst, dst_name="input")
+    assert dst.evaluate("input.get('body').length") == 3
+    assert dst.evaluate("typeof parsed") == "undefined"
+
+    other = never_jscore.ThreadedContext()
+    never_jscore.transfer(dst, "input", other)
+    assert other.evaluate("input.size") == 2
+
+    never_jscore.transfer(dst, "input", dst, dst_name="copy")
+    assert dst.evaluate("copy !== input && copy.size === 2") is True
+    src.close()
+    other.close()
+    print("[OK] dst_name / ThreadedContext")
+
+
+def test_shared_array_buffer():
+    """测试 SharedArrayBuffer 在两个 Context 之间共享内存"""
+    src = never_jscore.Context()
+    dst = never_jscore.Context()
+    src.eval("globalThis.shared = new Int32Array(new SharedArrayBuffer(16))")
+    never_jscore.transfer(src, "shared", dst)
+    dst.eval("shared[0] = 42")
+    assert src.evaluate("shared[0]") == 42
+    del src, dst
+    print("[OK] SharedArrayBuffer 共享")
+
+
+def test_errors():
+    """测试不存在的变量、无法克隆的值、无效的参数，以及接收值后不能 pickle"""
+    src = never_jscore.Context()
+    dst = never_jscore.Context()
+    src.compile(SRC_CODE)
+    for name, error in [("missing", "ReferenceError"), ("fn", "DataCloneError")]:
+        try:
+            never_jscore.transfer(src, name, dst)
+            assert False, f"应该抛出 {error}"
+        except Exception as e:
+            assert "Transfer error" in str(e) and error in str(e), e
+    for args in [(src, "parsed", object()), ("ctx", "parsed", dst)]:
+        try:
+            never_jscore.transfer(*args)
+            assert False, "应该抛出 TypeError"
+        except TypeError:
+            pass
+
+    never_jscore.transfer(src, "parsed", dst)
+    try:
+        pickle.dumps(dst)
+        assert False, "应该抛出 TypeError"
+    except TypeError as e:
+        assert "transfer()" in str(e), e
+    del src, dst
+    print("[OK] 错误处理")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 transfer()")
+    print("=" * 60)
+
+    test_structured_clone()
+    test_dst_name_and_threaded()
+    test_shared_array_buffer()
+    test_errors()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 transfer() 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,110 @@
+"""
+测试 undefined_as：结果中 undefined 的转换策略（none / sentinel / omit）
+"""
+
+import copy
+import io
+import pickle
+
+import never_jscore
+
+CODE = "({a: undefined, b: null, list: [1, undefined, , 4], nested: {c: [undefined]}})"
+
+
+def test_default():
+    """测试默认保持 JSON 语义"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate(CODE) == {"b": None, "list": [1, None, None, 4], "nested": {"c": [None]}}
+    assert ctx.evaluate("undefined") is None
+    del ctx
+    print("[OK] 默认（JSON 语义）")
+
+
+def test_none():
+    """测试 none：对象保留键，数组元素和空位为 None"""
+    ctx = never_jscore.Context(undefined_as="none")
+    assert ctx.evaluate(CODE) == {"a": None, "b": None, "list": [1, None, None, 4], "nested": {"c": [None]}}
+    assert ctx.evaluate("undefined") is None
+    assert ctx.evaluate("Promise.resolve({x: undefined})") == {"x": None}
+    del ctx
+    print("[OK] none")
+
+
+def test_sentinel():
+    """测试 sentinel：undefined 转换为 never_jscore.UNDEFINED，与 null 区分"""
+    U = never_jscore.UNDEFINED
+    ctx = never_jscore.Context(undefined_as="sentinel")
+    result = ctx.evaluate(CODE)
+    assert result["a"] is U and result["b"] is None
+    assert result["list"][0] == 1 and result["list"][1] is U and result["list"][2] is U and result["list"][3] == 4
+    assert result["nested"]["c"][0] is U
+    assert ctx.evaluate("undefined") is U
+    assert ctx.evaluate("null") is None
+    ctx.compile("function get(o, k) { return o[k]; }")
+    assert ctx.call("get", [{"x": 1}, "y"]) is U
+    assert ctx.evaluate("[undefined]", binary=True) == [U]
+    assert ctx.evaluate("({a: undefined})", result_format="json_str") == '{"a":{"__never_jscore_undefined__":true}}'
+
+    assert not U and repr(U) == "never_jscore.UNDEFINED"
+    assert isinstance(U, never_jscore.JsUndefined)
+    assert pickle.loads(pickle.dumps(U)) is U and copy.deepcopy(U) is U
+    try:
+        never_jscore.JsUndefined()
+        assert False, "不应该可以创建新的实例"
+    except TypeError:
+        pass
+    del ctx
+    print("[OK] sentinel")
+
+
+def test_omit():
+    """测试 omit：对象省略键，数组删除元素和空位"""
+    ctx = never_jscore.Context(undefined_as="omit")
+    assert ctx.evaluate(CODE) == {"b": None, "list": [1, 4], "nested": {"c": []}}
+    assert ctx.evaluate("undefined") is None
+    assert ctx.evaluate("[null, undefined, () => 1]") == [None, None]
+    del ctx
+    print("[OK] omit")
+
+
+def test_stream_and_threaded():
+    """测试 stream_to、ThreadedContext、ContextPool 和 pickle 使用同样的策略"""
+    ctx = never_jscore.Context(undefined_as="omit")
+    out = io.StringIO()
+    ctx.evaluate(CODE, stream_to=out)
+    assert out.getvalue() == '{"b":null,"list":[1,4],"nested":{"c":[]}}', out.getvalue()
+
+    restored = pickle.loads(pickle.dumps(ctx))
+    assert restored.evaluate("[1, , 2]") == [1, 2]
+
+    threaded = never_jscore.ThreadedContext(undefined_as="none")
+    assert threaded.evaluate("({a: undefined})") == {"a": None}
+    threaded.close()
+
+    pool = never_jscore.ContextPool("function f() { return [undefined]; }", size=1, undefined_as="sentinel")
+    assert pool.call("f", []) == [never_jscore.UNDEFINED]
+    pool.close()
+
+    try:
+        never_jscore.Context(undefined_as="null")
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "undefined_as" in str(e), e
+    del ctx, restored
+    print("[OK] stream_to / ThreadedContext / ContextPool / pickle")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 undefined_as")
+    print("=" * 60)
+
+    test_default()
+    test_none()
+    test_sentinel()
+    test_omit()
+    test_stream_and_threaded()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 undefined 转换策略测试通过！")
+    print("=" * 60)
@@ -0,0 +1,136 @@
+"""
+测试 compile(code, tag=...) 和 unload(tag)：按标签卸载脚本，重建 isolate 时不再执行
+
+其余的全局脚本按原顺序重新执行，register() 的函数重新注册
+"""
+
+import pickle
+
+import never_jscore
+
+LIB_V1 = "var Lib = { version: 1, hash: (s) => 'v1:' + s };"
+LIB_V2 = "var Lib = { version: 2, hash: (s) => 'v2:' + s };"
+SIGN = "function sign(s) { return Lib.hash(s); }"
+
+
+def test_swap_version():
+    """测试卸载后加载另一个版本，其他脚本保留"""
+    ctx = never_jscore.Context()
+    ctx.compile("var env = 'ready';")
+    ctx.compile(LIB_V1, tag="lib")
+    ctx.compile(SIGN)
+    assert ctx.call("sign", ["a"]) == "v1:a"
+
+    ctx.unload("lib")
+    assert ctx.evaluate("typeof Lib") == "undefined"
+    assert ctx.evaluate("env") == "ready"
+    assert ctx.evaluate("typeof sign") == "function"
+
+    ctx.compile(LIB_V2, tag="lib")
+    assert ctx.call("sign", ["a"]) == "v2:a"
+    ctx.unload("lib")
+    assert ctx.evaluate("typeof Lib") == "undefined"
+    del ctx
+    print("[OK] 替换库的版本")
+
+
+def test_multiple_tags():
+    """测试同一标签多次使用、不同标签互不影响，preset 属于同一标签"""
+    ctx = never_jscore.Context()
+    ctx.compile("var a1 = 1;", tag="a")
+    ctx.compile("var b1 = 1;", tag="b")
+    ctx.compile("var a2 = 2;", tag="a")
+    ctx.compile("var hashed = typeof CryptoJS;", preset=["crypto-js"], tag="crypto")
+
+    ctx.unload("a")
+    assert ctx.evaluate("[typeof a1, typeof a2, typeof b1]") == ["undefined", "undefined", "number"]
+    assert ctx.evaluate("typeof CryptoJS") == "object"
+    ctx.unload("crypto")
+    assert ctx.evaluate("[typeof CryptoJS, typeof hashed]") == ["undefined", "undefined"]
+    del ctx
+    print("[OK] 多个标签")
+
+
+def test_side_effects_and_functions():
+    """测试 evaluate() 的副作用丢弃，register() 的函数和调用统计保留"""
+    ctx = never_jscore.Context()
+    ctx.register("pyDouble", lambda x: x * 2)
+    ctx.compile(LIB_V1, tag="lib")
+    ctx.compile("function twice(x) { return pyDouble(x); }")
+    ctx.eval("globalThis.counter = 5;")
+    executions = ctx.get_stats()[0]
+
+    ctx.unload("lib")
+    assert ctx.evaluate("typeof counter") == "undefined"
+    assert ctx.call("twice", [21]) == 42
+    assert ctx.get_stats()[0] >= executions
+    del ctx
+    print("[OK] 副作用丢弃，Python 函数保留")
+
+
+def test_errors():
+    """测试未知标签、无法重建和重新执行失败"""
+    ctx = never_jscore.Context()
+    ctx.compile(LIB_V1, tag="lib")
+    try:
+        ctx.unload("missing")
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "missing" in str(e), e
+
+    # 其余脚本依赖被卸载的库：重新执行失败，Context 保持不变
+    ctx.compile("var fixed = Lib.hash('x');")
+    try:
+        ctx.unload("lib")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Unload error" in str(e), e
+    assert ctx.evaluate("fixed") == "v1:x"
+    assert ctx.evaluate("Lib.version") == 1
+
+    blocked = never_jscore.Context()
+    blocked.compile(LIB_V1, tag="lib")
+    blocked.create_realm()
+    try:
+        blocked.unload("lib")
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "cannot unload" in str(e), e
+    del ctx, blocked
+    print("[OK] 错误处理")
+
+
+def test_tags_survive_rebuild():
+    """测试标签随 pickle 保存，recycle_after 重建后保留"""
+    ctx = never_jscore.Context()
+    ctx.compile("var env = 1;")
+    ctx.compile(LIB_V1, tag="lib")
+    restored = pickle.loads(pickle.dumps(ctx))
+    restored.unload("lib")
+    assert restored.evaluate("[typeof env, typeof Lib]") == ["number", "undefined"]
+
+    recycled = never_jscore.Context()
+    recycled.compile(LIB_V1, tag="lib")
+    recycled.compile("var kept = 1;", recycle_after={"calls": 2})
+    for _ in range(5):
+        recycled.evaluate("1")
+    recycled.unload("lib")
+    assert recycled.evaluate("[typeof kept, typeof Lib]") == ["number", "undefined"]
+    del ctx, restored, recycled
+    print("[OK] pickle / recycle_after 后仍然可以卸载")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 compile(tag=...) / unload()")
+    print("=" * 60)
+
+    test_swap_version()
+    test_multiple_tags()
+    test_side_effects_and_functions()
+    test_errors()
+    test_tags_survive_rebuild()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 unload() 测试通过！")
+    print("=" * 60)
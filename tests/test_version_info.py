@@ -0,0 +1,66 @@
+"""
+测试版本与构建信息：never_jscore.version_info()
+
+提交 bug 时附上 version_info()，运行时按 features 判断功能是否可用
+"""
+
+import subprocess
+import sys
+import textwrap
+
+import never_jscore
+
+
+def test_versions():
+    """测试版本字段"""
+    info = never_jscore.version_info()
+
+    assert info["version"] == never_jscore.__version__, info
+    assert info["deno_core"].count(".") == 2, info
+    major = int(info["v8"].split(".")[0])
+    assert major >= 14, info
+    assert "-" in info["platform"], info
+    assert len(info["source_hash"]) == 16, info
+    assert isinstance(info["initialized"], bool)
+
+    features = info["features"]
+    for name in ("icu", "snapshot", "fetch", "wasm", "wasi", "jitless"):
+        assert isinstance(features[name], bool), (name, features)
+    print(f"[OK] version_info(): {info['version']} / deno_core {info['deno_core']} / V8 {info['v8']}")
+
+
+def test_features_follow_init():
+    """测试 features 和 initialized 反映 init() 配置（独立进程）"""
+    script = textwrap.dedent("""
+        import never_jscore
+
+        info = never_jscore.version_info()
+        assert info["initialized"] is False, info
+        assert info["features"]["icu"] and info["features"]["wasm"], info
+
+        never_jscore.init(icu=False, jitless=True, snapshot=False)
+        features = never_jscore.version_info()["features"]
+        assert features == {
+            "icu": False, "snapshot": False, "fetch": True,
+            "wasm": False, "wasi": False, "jitless": True,
+        }, features
+
+        never_jscore.Context().evaluate("1")
+        assert never_jscore.version_info()["initialized"] is True
+    """)
+    proc = subprocess.run([sys.executable, "-c", script], capture_output=True, text=True, timeout=60)
+    assert proc.returncode == 0, proc.stdout + proc.stderr
+    print("[OK] features 反映 init() 配置")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试版本与构建信息")
+    print("=" * 60)
+
+    test_versions()
+    test_features_follow_init()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 version_info() 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,99 @@
+"""
+测试 warmup()：在接收流量之前预先执行有代表性的调用
+
+Context / ThreadedContext 在自身上执行，ContextPool 在每个工作线程上各执行一次
+"""
+
+import never_jscore
+
+JS_CODE = """
+var warmed = 0;
+function sign(data) { warmed++; return data + ':signed'; }
+async function fetchToken(id) { await null; warmed++; return 't' + id; }
+function getWarmed() { return warmed; }
+function broken() { throw new Error('not ready'); }
+"""
+
+
+def test_context():
+    """测试 Context.warmup() 执行每个调用 rounds 次"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    assert ctx.warmup([("sign", ["sample"]), ("fetchToken", [1])]) is None
+    assert ctx.call("getWarmed", []) == 2
+    ctx.warmup([("sign", "sample")], rounds=5)  # 非 list 参数作为单个参数
+    assert ctx.call("getWarmed", []) == 7
+    ctx.warmup([])
+    del ctx
+    print("[OK] Context")
+
+
+def test_errors():
+    """测试失败的预热调用和无效参数"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    try:
+        ctx.warmup([("sign", ["a"]), ("broken", [])])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "broken()" in str(e) and "not ready" in str(e), e
+    for calls, kwargs, error in [
+        ([("sign", ["a"])], {"rounds": 0}, ValueError),
+        (["sign"], {}, TypeError),
+        ([("sign", ["a"], "extra")], {}, TypeError),
+    ]:
+        try:
+            ctx.warmup(calls, **kwargs)
+            assert False, f"应该抛出 {error.__name__}"
+        except error:
+            pass
+    del ctx
+    print("[OK] 错误处理")
+
+
+def test_threaded():
+    """测试 ThreadedContext.warmup()"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile(JS_CODE)
+    ctx.warmup([("sign", ["sample"])], rounds=3)
+    assert ctx.call("getWarmed", []) == 3
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+def test_pool_every_worker():
+    """测试 ContextPool.warmup() 在每个工作线程上各执行一次"""
+    pool = never_jscore.ContextPool(JS_CODE, size=3, max_queue=1)
+    pool.warmup([("sign", ["sample"]), ("fetchToken", [1])], rounds=2)
+    counts = pool.map("getWarmed", [[] for _ in range(30)])
+    assert set(counts) == {4}, counts
+    assert pool.queued == 0
+
+    try:
+        pool.warmup([("broken", [])])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "Warmup error" in str(e) and "broken()" in str(e), e
+    pool.close()
+
+    try:
+        pool.warmup([("sign", ["a"])])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "closed" in str(e), e
+    print("[OK] ContextPool 每个工作线程")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 warmup()")
+    print("=" * 60)
+
+    test_context()
+    test_errors()
+    test_threaded()
+    test_pool_every_worker()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 warmup() 测试通过！")
+    print("=" * 60)
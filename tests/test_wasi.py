@@ -0,0 +1,168 @@
+"""
+测试 WASI：ctx.load_wasm(name, wasm_bytes, wasi=...)
+
+测试用的模块在这里直接生成（模拟 wasi-sdk 编译的命令模块）：
+- _start(): 向 stdout 写入 "hello wasi\n"，然后 proc_exit(3)
+- argc(): 通过 args_sizes_get 返回参数个数
+- echo(): 从 stdin 读取最多 64 字节写到 stdout
+"""
+
+import never_jscore
+from test_wasm import _name, _section, _vec
+
+
+def build_wasi_module():
+    """生成测试用的 WASI 模块"""
+    types = _vec([
+        bytes([0x60, 4, 0x7F, 0x7F, 0x7F, 0x7F, 1, 0x7F]),  # 0: (i32, i32, i32, i32) -> i32
+        bytes([0x60, 1, 0x7F, 0]),                          # 1: (i32) -> ()
+        bytes([0x60, 2, 0x7F, 0x7F, 1, 0x7F]),              # 2: (i32, i32) -> i32
+        bytes([0x60, 0, 0]),                                # 3: () -> ()
+        bytes([0x60, 0, 1, 0x7F]),                          # 4: () -> i32
+    ])
+    wasi = _name("wasi_snapshot_preview1")
+    imports = _vec([
+        wasi + _name("fd_write") + bytes([0x00, 0]),
+        wasi + _name("proc_exit") + bytes([0x00, 1]),
+        wasi + _name("args_sizes_get") + bytes([0x00, 2]),
+        wasi + _name("fd_read") + bytes([0x00, 0]),
+    ])
+    functions = _vec([bytes([3]), bytes([4]), bytes([3])])
+    memory = _vec([bytes([0x00, 1])])
+    exports = _vec([
+        _name("memory") + bytes([0x02, 0]),
+        _name("_start") + bytes([0x00, 4]),
+        _name("argc") + bytes([0x00, 5]),
+        _name("echo") + bytes([0x00, 6]),
+    ])
+    start = bytes([
+        0,
+        0x41, 1, 0x41, 0, 0x41, 1, 0x41, 8, 0x10, 0, 0x1A,  # fd_write(1, 0, 1, 8)
+        0x41, 3, 0x10, 1,                                   # proc_exit(3)
+        0x0B,
+    ])
+    argc = bytes([
+        0,
+        0x41, 0xE4, 0x00, 0x41, 0xE8, 0x00, 0x10, 2, 0x1A,  # args_sizes_get(100, 104)
+        0x41, 0xE4, 0x00, 0x28, 0x02, 0x00,                 # i32.load(100)
+        0x0B,
+    ])
+    echo = bytes([
+        0,
+        0x41, 0xC8, 0x01, 0x41, 0xAC, 0x02, 0x36, 0x02, 0x00,  # iovec(200) = {300, 64}
+        0x41, 0xCC, 0x01, 0x41, 0xC0, 0x00, 0x36, 0x02, 0x00,
+        0x41, 0, 0x41, 0xC8, 0x01, 0x41, 1, 0x41, 0xD0, 0x01, 0x10, 3, 0x1A,  # fd_read(0, 200, 1, 208)
+        0x41, 0xCC, 0x01, 0x41, 0xD0, 0x01, 0x28, 0x02, 0x00, 0x36, 0x02, 0x00,  # iovec.len = nread
+        0x41, 1, 0x41, 0xC8, 0x01, 0x41, 1, 0x41, 0xD4, 0x01, 0x10, 0, 0x1A,  # fd_write(1, 200, 1, 212)
+        0x0B,
+    ])
+    code = _vec([bytes([len(body)]) + body for body in (start, argc, echo)])
+    text = b"hello wasi\n"
+    segment = bytes([16, 0, 0, 0, len(text), 0, 0, 0]) + bytes(8) + text
+    data = _vec([bytes([0x00, 0x41, 0x00, 0x0B, len(segment)]) + segment])
+    return (b"\0asm" + bytes([1, 0, 0, 0])
+            + _section(1, types) + _section(2, imports) + _section(3, functions)
+            + _section(5, memory) + _section(7, exports) + _section(10, code) + _section(11, data))
+
+
+WASI = build_wasi_module()
+
+CAPTURE = """
+    var captured = [];
+    console.log = (...args) => captured.push('out:' + args.join(' '));
+    console.error = (...args) => captured.push('err:' + args.join(' '));
+"""
+
+
+def test_wasi_command():
+    """测试命令模块：stdout 输出到 console，_start() 返回退出码"""
+    ctx = never_jscore.Context()
+    ctx.compile(CAPTURE)
+    exports = ctx.load_wasm("prog", WASI, wasi=True)
+    assert exports == {"memory": "memory", "_start": "function", "argc": "function", "echo": "function"}
+
+    assert ctx.call("prog._start", []) == 3
+    assert ctx.evaluate("captured") == ["out:hello wasi"]
+
+    # 默认参数为 [name]
+    assert ctx.call("prog.argc", []) == 1
+    ctx.load_wasm("prog", WASI, wasi={"args": ["prog", "-v", "input.txt"]})
+    assert ctx.call("prog.argc", []) == 3
+    del ctx
+    print("[OK] WASI 命令模块")
+
+
+def test_wasi_stdin():
+    """测试 stdin 内容通过 fd_read 读取"""
+    ctx = never_jscore.Context()
+    ctx.compile(CAPTURE)
+    ctx.load_wasm("prog", WASI, wasi={"stdin": "ping 你好\n"})
+    ctx.call("prog.echo", [])
+    assert ctx.evaluate("captured") == ["out:ping 你好"]
+
+    # 读完之后是 EOF
+    ctx.call("prog.echo", [])
+    assert ctx.evaluate("captured.length") == 1
+    del ctx
+    print("[OK] WASI stdin")
+
+
+def test_wasi_options():
+    """测试配置校验、env 权限和未启用 WASI 时的链接错误"""
+    ctx = never_jscore.Context()
+    try:
+        ctx.load_wasm("prog", WASI)
+        assert False, "没有 WASI 宿主时应该链接失败"
+    except Exception as e:
+        assert "WASM error" in str(e), e
+
+    for bad, error in [({"argv": []}, ValueError), ("yes", TypeError), ({"env": ["A=1"]}, TypeError)]:
+        try:
+            ctx.load_wasm("prog", WASI, wasi=bad)
+            assert False, "应该抛出异常"
+        except error:
+            pass
+    del ctx
+
+    # 宿主环境变量受 env 权限限制
+    denied = never_jscore.Context(permissions={"env": False})
+    try:
+        denied.load_wasm("prog", WASI, wasi={"inherit_env": True})
+        assert False, "应该抛出 PermissionDenied"
+    except Exception as e:
+        assert "PermissionDenied" in str(e), e
+    denied.load_wasm("prog", WASI, wasi={"env": {"MODE": "test"}})
+    del denied
+    print("[OK] WASI 配置和权限")
+
+
+def test_wasi_threaded_and_pure():
+    """测试 ThreadedContext 和纯净 V8 环境"""
+    tctx = never_jscore.ThreadedContext()
+    tctx.compile(CAPTURE)
+    tctx.load_wasm("prog", WASI, wasi=True)
+    assert tctx.call("prog._start", []) == 3
+    assert tctx.evaluate("captured") == ["out:hello wasi"]
+    tctx.close()
+
+    ctx = never_jscore.Context(enable_extensions=False)
+    ctx.load_wasm("prog", WASI, wasi={"args": ["a", "b"]})
+    assert ctx.call("prog._start", []) == 3
+    assert ctx.call("prog.argc", []) == 2
+    del ctx
+    print("[OK] ThreadedContext 和 enable_extensions=False")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 WASI")
+    print("=" * 60)
+
+    test_wasi_command()
+    test_wasi_stdin()
+    test_wasi_options()
+    test_wasi_threaded_and_pure()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 WASI 测试通过！")
+    print("=" * 60)
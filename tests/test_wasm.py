@@ -0,0 +1,294 @@
+"""
+测试 WebAssembly：ctx.load_wasm(name, wasm_bytes, imports=None)
+以及 WebAssembly.compileStreaming / instantiateStreaming、ctx.wasm_memory(expr)
+
+测试用的模块在这里直接生成（不依赖外部 .wasm 文件）：
+- add(a, b): i32 加法
+- add_double(a, b): 调用导入的 env.double(a + b)
+- memory: 1 页线性内存
+"""
+
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import never_jscore
+
+
+def _vec(items):
+    return bytes([len(items)]) + b"".join(items)
+
+
+def _name(s):
+    return bytes([len(s)]) + s.encode()
+
+
+def _leb(n):
+    out = bytearray()
+    while True:
+        byte, n = n & 0x7F, n >> 7
+        out.append(byte | (0x80 if n else 0))
+        if not n:
+            return bytes(out)
+
+
+def _section(section_id, content):
+    return bytes([section_id]) + _leb(len(content)) + content
+
+
+def build_module():
+    """生成测试用的 WASM 模块"""
+    types = _vec([bytes([0x60, 2, 0x7F, 0x7F, 1, 0x7F]), bytes([0x60, 1, 0x7F, 1, 0x7F])])
+    imports = _vec([_name("env") + _name("double") + bytes([0x00, 1])])
+    functions = _vec([bytes([0]), bytes([0])])
+    memory = _vec([bytes([0x00, 1])])
+    exports = _vec([
+        _name("add") + bytes([0x00, 1]),
+        _name("add_double") + bytes([0x00, 2]),
+        _name("memory") + bytes([0x02, 0]),
+    ])
+    add = bytes([0, 0x20, 0, 0x20, 1, 0x6A, 0x0B])
+    add_double = bytes([0, 0x20, 0, 0x20, 1, 0x6A, 0x10, 0, 0x0B])
+    code = _vec([bytes([len(add)]) + add, bytes([len(add_double)]) + add_double])
+    return (b"\0asm" + bytes([1, 0, 0, 0])
+            + _section(1, types) + _section(2, imports) + _section(3, functions)
+            + _section(5, memory) + _section(7, exports) + _section(10, code))
+
+
+WASM = build_module()
+
+
+def test_load_wasm_python_import():
+    """测试 Python 函数作为导入，导出可以从 JS 和 Python 调用"""
+    ctx = never_jscore.Context()
+    calls = []
+
+    def double(x):
+        calls.append(x)
+        return x * 2
+
+    exports = ctx.load_wasm("calc", WASM, imports={"env": {"double": double}})
+    assert exports == {"add": "function", "add_double": "function", "memory": "memory"}
+
+    assert ctx.call("calc.add", [2, 3]) == 5
+    assert ctx.evaluate("calc.add_double(2, 3)") == 10
+    assert calls == [5]
+    assert ctx.evaluate("calc.memory.buffer.byteLength") == 65536
+    del ctx
+    print("[OK] Python 导入函数，从 JS / Python 调用导出")
+
+
+def test_load_wasm_js_import():
+    """测试 JS 表达式作为导入"""
+    ctx = never_jscore.Context()
+    ctx.compile("function triple(x) { return x * 3; }")
+    ctx.load_wasm("calc", WASM, imports={"env": {"double": "triple"}})
+    assert ctx.call("calc.add_double", [1, 1]) == 6
+
+    # 同名时替换
+    ctx.load_wasm("calc", WASM, imports={"env": {"double": "x => x * 10"}})
+    assert ctx.call("calc.add_double", [1, 1]) == 20
+    del ctx
+    print("[OK] JS 表达式导入")
+
+
+def test_load_wasm_errors():
+    """测试错误处理"""
+    ctx = never_jscore.Context()
+    try:
+        ctx.load_wasm("bad", b"not wasm")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "WASM error" in str(e) and "CompileError" in str(e), e
+
+    # 缺少导入
+    try:
+        ctx.load_wasm("calc", WASM)
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "LinkError" in str(e) or "TypeError" in str(e), e
+
+    try:
+        ctx.load_wasm("calc", WASM, imports={"env": {"double": object()}})
+        assert False, "应该抛出 TypeError"
+    except TypeError:
+        pass
+
+    # Context 仍然可用
+    assert ctx.evaluate("1 + 1") == 2
+    del ctx
+    print("[OK] 错误处理")
+
+
+def test_load_wasm_threaded():
+    """测试 ThreadedContext 和纯净 V8 环境"""
+    tctx = never_jscore.ThreadedContext()
+    tctx.load_wasm("calc", WASM, imports={"env": {"double": lambda x: x * 2}})
+    assert tctx.call("calc.add_double", [4, 5]) == 18
+    tctx.close()
+
+    ctx = never_jscore.Context(enable_extensions=False)
+    ctx.load_wasm("calc", WASM, imports={"env": {"double": "x => x"}})
+    assert ctx.call("calc.add", [40, 2]) == 42
+    del ctx
+    print("[OK] ThreadedContext 和 enable_extensions=False")
+
+
+def test_instantiate_streaming_response():
+    """测试 instantiateStreaming / compileStreaming 接受 Response 和字节"""
+    ctx = never_jscore.Context()
+    ctx.compile(f"var wasmBytes = new Uint8Array({list(WASM)});")
+    result = ctx.evaluate("""
+        (async () => {
+            const { module, instance } = await WebAssembly.instantiateStreaming(
+                Promise.resolve(new Response(wasmBytes, { headers: { 'Content-Type': 'application/wasm' } })),
+                { env: { double: x => x * 2 } }
+            );
+            const compiled = await WebAssembly.compileStreaming(wasmBytes.buffer);
+            return [module instanceof WebAssembly.Module, instance.exports.add_double(2, 3),
+                    WebAssembly.Module.exports(compiled).length];
+        })()
+    """)
+    assert result == [True, 10, 3], result
+
+    # 非 Response 参数和失败的响应
+    errors = ctx.evaluate("""
+        (async () => {
+            const errors = [];
+            for (const source of [42, new Response('', { status: 404 })]) {
+                try { await WebAssembly.compileStreaming(source); errors.push('no error'); }
+                catch (e) { errors.push(e.name); }
+            }
+            return errors;
+        })()
+    """)
+    assert errors == ["TypeError", "TypeError"], errors
+    assert "[native code]" in ctx.evaluate("WebAssembly.instantiateStreaming.toString()")
+    del ctx
+    print("[OK] instantiateStreaming / compileStreaming（Response / 字节）")
+
+
+def test_instantiate_streaming_fetch():
+    """测试浏览器写法 instantiateStreaming(fetch(url)) 直接运行"""
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            self.send_response(200)
+            self.send_header("Content-Type", "application/wasm")
+            self.send_header("Content-Length", str(len(WASM)))
+            self.end_headers()
+            self.wfile.write(WASM)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    try:
+        ctx = never_jscore.Context(permissions={"net": [f"127.0.0.1:{server.server_port}"]})
+        url = f"http://127.0.0.1:{server.server_port}/calc.wasm"
+        result = ctx.evaluate(f"""
+            (async () => {{
+                const {{ instance }} = await WebAssembly.instantiateStreaming(
+                    fetch('{url}'), {{ env: {{ double: x => x * 2 }} }}
+                );
+                return instance.exports.add_double(20, 1);
+            }})()
+        """)
+        assert result == 42, result
+        del ctx
+    finally:
+        server.shutdown()
+        server.server_close()
+    print("[OK] instantiateStreaming(fetch(url))")
+
+
+def test_wasm_memory_view():
+    """测试零拷贝访问 WASM 内存：Python 和 JS 看到同一块内存"""
+    ctx = never_jscore.Context()
+    ctx.load_wasm("calc", WASM, imports={"env": {"double": "x => x * 2"}})
+
+    mem = ctx.wasm_memory("calc.memory")
+    assert isinstance(mem, memoryview)
+    assert len(mem) == 65536 and not mem.readonly and mem.format == "B"
+
+    # Python 写入，JS 读取
+    mem[0:5] = b"hello"
+    assert ctx.evaluate("String.fromCharCode(...new Uint8Array(calc.memory.buffer, 0, 5))") == "hello"
+
+    # JS 写入，Python 读取
+    ctx.evaluate("new Uint8Array(calc.memory.buffer).set([1, 2, 3], 100)")
+    assert bytes(mem[100:103]) == b"\x01\x02\x03"
+
+    # ArrayBuffer 同样可以
+    ctx.compile("var buf = new ArrayBuffer(16);")
+    view = ctx.wasm_memory("buf")
+    view[0] = 42
+    assert ctx.evaluate("new Uint8Array(buf)[0]") == 42
+
+    for bad in ["42", "new Uint8Array(4)", "undefinedVariable"]:
+        try:
+            ctx.wasm_memory(bad)
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "WASM error" in str(e), e
+    del ctx
+    print("[OK] wasm_memory() 零拷贝读写")
+
+
+def test_wasm_memory_lifetime():
+    """测试视图的生命周期：grow 后旧视图仍可访问，Context 销毁后视图失效"""
+    ctx = never_jscore.Context()
+    ctx.load_wasm("calc", WASM, imports={"env": {"double": "x => x * 2"}})
+    old = ctx.wasm_memory("calc.memory")
+    old[0] = 7
+
+    ctx.evaluate("calc.memory.grow(1)")
+    new = ctx.wasm_memory("calc.memory")
+    assert len(old) == 65536 and len(new) == 131072
+    assert new[0] == 7
+    new[70000] = 9
+    assert ctx.evaluate("new Uint8Array(calc.memory.buffer)[70000]") == 9
+    _ = old[0]  # 旧视图仍然可以安全访问
+
+    piece = new[0:10]  # 切片在 Context 销毁后仍然持有内存
+    del ctx
+    for view in (old, new):
+        try:
+            view[0]
+            assert False, "Context 销毁后视图应该失效"
+        except ValueError:
+            pass
+    assert piece[0] == 7
+
+    tctx = never_jscore.ThreadedContext()
+    tctx.load_wasm("calc", WASM, imports={"env": {"double": "x => x * 2"}})
+    mem = tctx.wasm_memory("calc.memory")
+    mem[8:12] = b"wasm"
+    assert tctx.evaluate("String.fromCharCode(...new Uint8Array(calc.memory.buffer, 8, 4))") == "wasm"
+    tctx.close()
+    try:
+        mem[0]
+        assert False, "ThreadedContext 关闭后视图应该失效"
+    except ValueError:
+        pass
+    print("[OK] wasm_memory() 生命周期")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 WebAssembly")
+    print("=" * 60)
+
+    test_load_wasm_python_import()
+    test_load_wasm_js_import()
+    test_load_wasm_errors()
+    test_load_wasm_threaded()
+    test_instantiate_streaming_response()
+    test_instantiate_streaming_fetch()
+    test_wasm_memory_view()
+    test_wasm_memory_lifetime()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 WebAssembly 测试通过！")
+    print("=" * 60)
@@ -0,0 +1,305 @@
+"""
+测试 Worker：在独立的 isolate 和线程中运行脚本
+
+消息使用结构化克隆传递，SharedArrayBuffer 在线程之间共享
+"""
+
+import os
+import tempfile
+
+import never_jscore
+
+
+# 创建 Worker 并等待第一条回复
+ROUND_TRIP = """
+function runWorker(source, message, options) {
+    const worker = new Worker(URL.createObjectURL(new Blob([source])), options);
+    return new Promise((resolve, reject) => {
+        worker.onmessage = e => { resolve(e.data); worker.terminate(); };
+        worker.onerror = e => { e.preventDefault(); reject(new Error(e.message)); worker.terminate(); };
+        worker.postMessage(message);
+    });
+}
+"""
+
+
+def test_round_trip():
+    """测试 postMessage / onmessage 往返"""
+    ctx = never_jscore.Context()
+    ctx.compile(ROUND_TRIP)
+    result = ctx.evaluate("""
+        runWorker("onmessage = e => postMessage(e.data.map(x => x * 2))", [1, 2, 3])
+    """)
+    assert result == [2, 4, 6]
+    del ctx
+    print("[OK] postMessage 往返")
+
+
+def test_separate_isolate():
+    """测试 Worker 运行在独立的 isolate 和线程中"""
+    ctx = never_jscore.Context()
+    ctx.compile(ROUND_TRIP)
+    ctx.compile("var parentOnly = 1;")
+    result = ctx.evaluate("""
+        runWorker(`
+            onmessage = () => postMessage({
+                parentOnly: typeof parentOnly,
+                window: typeof window,
+                document: typeof document,
+                importScripts: typeof importScripts,
+                isSelf: self === globalThis,
+                isWorkerScope: typeof WorkerGlobalScope === 'function',
+                name: self.name,
+            });
+        `, null, { name: 'calc' })
+    """)
+    assert result == {
+        "parentOnly": "undefined",
+        "window": "undefined",
+        "document": "undefined",
+        "importScripts": "function",
+        "isSelf": True,
+        "isWorkerScope": True,
+        "name": "calc",
+    }, result
+    # 父 Context 不受影响
+    assert ctx.evaluate("typeof window") == "object"
+    del ctx
+    print("[OK] 独立的 isolate")
+
+
+def test_structured_clone():
+    """测试结构化克隆（Map / Set / Date / 类型化数组 / 循环引用）"""
+    ctx = never_jscore.Context()
+    ctx.compile(ROUND_TRIP)
+    result = ctx.evaluate("""
+        (async () => {
+            const cyclic = { n: 1 };
+            cyclic.self = cyclic;
+            const echoed = await runWorker("onmessage = e => postMessage(e.data)", {
+                map: new Map([['a', 1]]),
+                set: new Set([1, 2]),
+                date: new Date(0),
+                bytes: new Uint8Array([1, 2, 3]),
+                cyclic,
+            });
+            return [
+                echoed.map.get('a'),
+                echoed.set.has(2),
+                echoed.date.getTime(),
+                Array.from(echoed.bytes),
+                echoed.cyclic.self === echoed.cyclic,
+            ];
+        })()
+    """)
+    assert result == [1, True, 0, [1, 2, 3], True], result
+
+    # 函数无法克隆，抛出 DataCloneError
+    assert ctx.evaluate("""
+        (() => {
+            const worker = new Worker('data:text/javascript,');
+            try { worker.postMessage(() => 1); return 'no error'; }
+            catch (e) { return e.name; }
+            finally { worker.terminate(); }
+        })()
+    """) == "DataCloneError"
+    del ctx
+    print("[OK] 结构化克隆")
+
+
+def test_shared_array_buffer():
+    """测试 SharedArrayBuffer 在线程之间共享"""
+    ctx = never_jscore.Context()
+    ctx.compile(ROUND_TRIP)
+    result = ctx.evaluate("""
+        (async () => {
+            const shared = new Int32Array(new SharedArrayBuffer(8));
+            const reply = await runWorker(`
+                onmessage = e => {
+                    Atomics.add(e.data, 0, 41);
+                    postMessage(Atomics.load(e.data, 0));
+                };
+            `, shared);
+            Atomics.add(shared, 0, 1);
+            return [reply, Atomics.load(shared, 0)];
+        })()
+    """)
+    assert result == [41, 42], result
+    del ctx
+    print("[OK] SharedArrayBuffer 共享")
+
+
+def test_script_sources():
+    """测试 data: URL、文件路径和 module Worker"""
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("""
+        new Promise(resolve => {
+            const worker = new Worker('data:text/javascript,' + encodeURIComponent('postMessage("from data url")'));
+            worker.onmessage = e => { resolve(e.data); worker.terminate(); };
+        })
+    """) == "from data url"
+
+    with tempfile.TemporaryDirectory() as root:
+        helper = os.path.join(root, "helper.js").replace("\\", "/")
+        script = os.path.join(root, "worker.js").replace("\\", "/")
+        with open(helper, "w", encoding="utf-8") as f:
+            f.write("function triple(x) { return x * 3; }")
+        with open(script, "w", encoding="utf-8") as f:
+            f.write(f"importScripts({helper!r});\nonmessage = e => postMessage(triple(e.data));")
+        result = ctx.evaluate(f"""
+            new Promise(resolve => {{
+                const worker = new Worker({script!r});
+                worker.addEventListener('message', e => {{ resolve(e.data); worker.terminate(); }});
+                worker.postMessage(5);
+            }})
+        """)
+        assert result == 15
+
+    assert ctx.evaluate("""
+        new Promise(resolve => {
+            const source = 'const value = await Promise.resolve("module"); postMessage(value);';
+            const worker = new Worker(URL.createObjectURL(new Blob([source])), { type: 'module' });
+            worker.onmessage = e => { resolve(e.data); worker.terminate(); };
+        })
+    """) == "module"
+    del ctx
+    print("[OK] 脚本来源")
+
+
+def test_errors_and_lifecycle():
+    """测试 onerror、close() 和 terminate()"""
+    ctx = never_jscore.Context()
+    ctx.compile(ROUND_TRIP)
+    try:
+        ctx.evaluate("runWorker(\"onmessage = () => { throw new Error('boom'); }\", 1)")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "boom" in str(e), e
+
+    # close() 之后不再接收消息
+    assert ctx.evaluate("""
+        new Promise(resolve => {
+            const source = 'let n = 0; onmessage = () => { n++; postMessage(n); if (n === 2) close(); };';
+            const worker = new Worker(URL.createObjectURL(new Blob([source])));
+            const replies = [];
+            worker.onmessage = e => replies.push(e.data);
+            for (let i = 0; i < 2; i++) worker.postMessage(i);
+            setTimeout(() => { worker.postMessage(3); setTimeout(() => resolve(replies), 50); }, 50);
+        })
+    """) == [1, 2]
+
+    # 空闲的 Worker 不阻止调用结束，后续调用仍然可以使用它
+    ctx.eval("""
+        globalThis.counter = new Worker('data:text/javascript,' + encodeURIComponent(
+            'let total = 0; onmessage = e => postMessage(total += e.data);'));
+    """)
+    assert ctx.evaluate("""
+        new Promise(resolve => { counter.onmessage = e => resolve(e.data); counter.postMessage(10); })
+    """) == 10
+    assert ctx.evaluate("""
+        new Promise(resolve => { counter.onmessage = e => resolve(e.data); counter.postMessage(5); })
+    """) == 15
+
+    # terminate() 中断正在执行的死循环
+    ctx.eval("""
+        globalThis.busy = new Worker('data:text/javascript,' + encodeURIComponent('while (true) {}'));
+        busy.terminate();
+    """)
+    del ctx
+    print("[OK] 错误和生命周期")
+
+
+def test_quotas_and_audit():
+    """测试 Worker 的执行计入父 Context 的配额，op 调用并入父 Context 的审计日志"""
+    ctx = never_jscore.Context(quotas={"max_executions": 100}, audit_ops=True)
+    ctx.compile(ROUND_TRIP)
+    result = ctx.evaluate("""
+        runWorker("const encoded = btoa('hi'); onmessage = () => postMessage(encoded)", null, { name: 'audited' })
+    """)
+    assert result == "aGk="
+    # compile + evaluate + Worker 脚本 + Worker 处理消息
+    assert ctx.get_quota_usage()["executions"] >= 4, ctx.get_quota_usage()
+
+    entries = ctx.get_audit_log()["entries"]
+    worker_ops = [e["op"] for e in entries if e["worker"] == "audited"]
+    assert "op_codec_btoa" in worker_ops, entries
+    assert any(e["worker"] is None for e in entries), entries
+    del ctx
+
+    # compile + evaluate + Worker 脚本用完配额，Worker 处理消息时失败，之后父 Context 同样拒绝调用
+    ctx = never_jscore.Context(quotas={"max_executions": 3})
+    ctx.compile(ROUND_TRIP)
+    try:
+        ctx.evaluate("runWorker('onmessage = e => postMessage(e.data)', 1)")
+        assert False, "Worker 应该报告配额耗尽"
+    except Exception as e:
+        assert "quota exhausted" in str(e), e
+    try:
+        ctx.evaluate("1")
+        assert False, "应该抛出 QuotaExceeded"
+    except never_jscore.QuotaExceeded:
+        pass
+    del ctx
+    print("[OK] 配额和审计")
+
+
+def test_max_workers():
+    """测试同时存在的 Worker 数量上限"""
+    ctx = never_jscore.Context()
+    ctx.eval("""
+        globalThis.idle = [];
+        for (let i = 0; i < 16; i++) {
+            idle.push(new Worker('data:text/javascript,' + encodeURIComponent('onmessage = () => {};')));
+        }
+    """)
+    message = ctx.evaluate("""
+        try { new Worker('data:text/javascript,'); 'created' } catch (e) { e.message }
+    """)
+    assert "Too many workers" in message, message
+
+    # Worker 线程退出后名额释放
+    assert ctx.evaluate("""
+        (async () => {
+            idle.pop().terminate();
+            for (let i = 0; i < 50; i++) {
+                await new Promise(r => setTimeout(r, 20));
+                try {
+                    idle.push(new Worker('data:text/javascript,'));
+                    return 'created';
+                } catch (e) {}
+            }
+            return 'still full';
+        })()
+    """) == "created"
+    ctx.eval("idle.forEach(w => w.terminate());")
+    del ctx
+    print("[OK] Worker 数量上限")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 中使用 Worker"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile(ROUND_TRIP)
+    assert ctx.evaluate("runWorker('onmessage = e => postMessage(e.data.toUpperCase())', 'abc')") == "ABC"
+    ctx.close()
+    print("[OK] ThreadedContext")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 Worker")
+    print("=" * 60)
+
+    test_round_trip()
+    test_separate_isolate()
+    test_structured_clone()
+    test_shared_array_buffer()
+    test_script_sources()
+    test_errors_and_lifecycle()
+    test_quotas_and_audit()
+    test_max_workers()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有 Worker 测试通过！")
+    print("=" * 60)
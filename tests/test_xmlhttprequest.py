@@ -6,6 +6,9 @@
 
 import never_jscore
 
+# 真实网络请求的测试需要显式授予 net 权限（默认拒绝）
+HTTPBIN = {"net": ["httpbin.org"]}
+
 
 def test_basic_xhr():
     """测试基本 XMLHttpRequest 使用"""
@@ -98,7 +101,7 @@ def test_xhr_set_request_header():
 
 def test_xhr_send_and_response():
     """测试 xhr.send() 和响应处理"""
-    ctx = never_jscore.Context()
+    ctx = never_jscore.Context(permissions=HTTPBIN)
 
     # 注意：这会发起真实的网络请求
     # 使用一个公开的测试 API
@@ -138,7 +141,7 @@ def test_xhr_send_and_response():
 
 def test_xhr_post_json():
     """测试 POST JSON 数据"""
-    ctx = never_jscore.Context()
+    ctx = never_jscore.Context(permissions=HTTPBIN)
 
     result = ctx.evaluate("""
         (async () => {
@@ -181,7 +184,7 @@ def test_xhr_post_json():
 
 def test_xhr_event_handlers():
     """测试 XHR 事件处理器"""
-    ctx = never_jscore.Context()
+    ctx = never_jscore.Context(permissions=HTTPBIN)
 
     result = ctx.evaluate("""
         (async () => {
@@ -220,7 +223,7 @@ def test_xhr_event_handlers():
 
 def test_xhr_abort():
     """测试 xhr.abort()"""
-    ctx = never_jscore.Context()
+    ctx = never_jscore.Context(permissions=HTTPBIN)
 
     result = ctx.evaluate("""
         (async () => {
@@ -255,7 +258,7 @@ def test_xhr_abort():
 
 def test_xhr_get_response_header():
     """测试获取响应头"""
-    ctx = never_jscore.Context()
+    ctx = never_jscore.Context(permissions=HTTPBIN)
 
     result = ctx.evaluate("""
         (async () => {
@@ -338,7 +341,7 @@ def test_xhr_hook_interception():
 
 def test_xhr_with_timeout():
     """测试 XHR 超时"""
-    ctx = never_jscore.Context()
+    ctx = never_jscore.Context(permissions=HTTPBIN)
 
     result = ctx.evaluate("""
         (async () => {
@@ -374,7 +377,7 @@ def test_xhr_with_timeout():
 
 def test_xhr_response_types():
     """测试不同的响应类型"""
-    ctx = never_jscore.Context()
+    ctx = never_jscore.Context(permissions=HTTPBIN)
 
     result = ctx.evaluate("""
         (async () => {
@@ -407,7 +410,7 @@ def test_xhr_response_types():
 
 def test_real_world_api_call():
     """实战：调用真实 API"""
-    ctx = never_jscore.Context()
+    ctx = never_jscore.Context(permissions=HTTPBIN)
 
     result = ctx.evaluate("""
         (async () => {
@@ -0,0 +1,115 @@
+"""
+测试 yield_interval_ms：同步执行期间处理 Python 信号（Ctrl-C / 自定义信号处理函数中断死循环）
+"""
+
+import pickle
+import signal
+import threading
+import time
+
+import never_jscore
+
+
+class Deadline(Exception):
+    pass
+
+
+def raise_deadline(signum, frame):
+    raise Deadline("deadline")
+
+
+def test_signal_handler_interrupts_loop():
+    """测试信号处理函数抛出的异常终止死循环并原样抛出，Context 仍然可用"""
+    ctx = never_jscore.Context(yield_interval_ms=10)
+    old = signal.signal(signal.SIGALRM, raise_deadline)
+    try:
+        signal.setitimer(signal.ITIMER_REAL, 0.1)
+        start = time.time()
+        try:
+            ctx.evaluate("while (true) {}")
+            assert False, "应该抛出 Deadline"
+        except Deadline as e:
+            assert str(e) == "deadline", e
+        assert time.time() - start < 2.0, time.time() - start
+    finally:
+        signal.setitimer(signal.ITIMER_REAL, 0)
+        signal.signal(signal.SIGALRM, old)
+
+    assert ctx.evaluate("1 + 1") == 2
+    ctx.compile("function spin() { for (;;) {} }")
+    assert ctx.evaluate("typeof spin") == "function"
+    del ctx
+    print("[OK] 信号处理函数中断死循环")
+
+
+def test_keyboard_interrupt():
+    """测试 Ctrl-C（SIGINT）在死循环中抛出 KeyboardInterrupt"""
+    ctx = never_jscore.Context(yield_interval_ms=10)
+    ctx.compile("function spin() { while (true) {} }")
+    timer = threading.Timer(0.1, signal.raise_signal, args=(signal.SIGINT,))
+    timer.start()
+    try:
+        ctx.call("spin", [])
+        assert False, "应该抛出 KeyboardInterrupt"
+    except KeyboardInterrupt:
+        pass
+    finally:
+        timer.join()
+    assert ctx.evaluate("spin.name") == "spin"
+    del ctx
+    print("[OK] KeyboardInterrupt")
+
+
+def test_no_signal():
+    """测试没有信号时不影响执行结果，与 timeout_ms 同时使用"""
+    ctx = never_jscore.Context(yield_interval_ms=1, timeout_ms=200)
+    total = ctx.evaluate("let s = 0; for (let i = 0; i < 1e7; i++) s += i % 3; s")
+    assert total == 10 ** 7 // 3 * 3, total
+    try:
+        ctx.evaluate("while (true) {}")
+        assert False, "应该超时"
+    except Exception as e:
+        assert "timed out" in str(e), e
+    assert ctx.evaluate("'ok'") == "ok"
+    del ctx
+    print("[OK] 没有信号时正常执行")
+
+
+def test_invalid_and_pickle():
+    """测试 yield_interval_ms=0 抛出 ValueError，pickle 保留设置"""
+    try:
+        never_jscore.Context(yield_interval_ms=0)
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "yield_interval_ms" in str(e), e
+
+    ctx = never_jscore.Context(yield_interval_ms=10)
+    restored = pickle.loads(pickle.dumps(ctx))
+    old = signal.signal(signal.SIGALRM, raise_deadline)
+    try:
+        signal.setitimer(signal.ITIMER_REAL, 0.1)
+        try:
+            restored.evaluate("while (true) {}")
+            assert False, "应该抛出 Deadline"
+        except Deadline:
+            pass
+    finally:
+        signal.setitimer(signal.ITIMER_REAL, 0)
+        signal.signal(signal.SIGALRM, old)
+    del ctx, restored
+    print("[OK] 参数校验与 pickle")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 yield_interval_ms")
+    print("=" * 60)
+
+    test_signal_handler_interrupts_loop()
+    test_keyboard_interrupt()
+    test_no_signal()
+    test_invalid_and_pickle()
+
+    print("\n" + "=" * 60)
+    print("✅ 所有协作让出点测试通过！")
+    print("=" * 60)